@@ -0,0 +1,22 @@
+//! Embeds the build information that is reported by `reth --version` and sent to peers.
+use std::{env, process::Command};
+
+fn main() {
+    let sha = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|sha| sha.trim().to_string())
+        .filter(|sha| !sha.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RETH_GIT_SHA={sha}");
+    println!("cargo:rustc-env=RETH_BUILD_TARGET={}", env::var("TARGET").unwrap());
+    println!("cargo:rustc-env=RETH_BUILD_PROFILE={}", env::var("PROFILE").unwrap());
+
+    // rebuild when the checked out commit changes
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs/heads");
+    println!("cargo:rerun-if-changed=build.rs");
+}
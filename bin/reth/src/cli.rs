@@ -1,27 +1,38 @@
 //! CLI definition and entrypoint to executable
 
-use clap::{ArgAction, Parser, Subcommand};
+use clap::{ArgAction, Args, Parser, Subcommand};
+use std::{path::PathBuf, sync::Arc};
 use tracing_subscriber::util::SubscriberInitExt;
 
 use crate::{
-    db, node, test_eth_chain,
-    util::reth_tracing::{self, TracingMode},
+    config_cmd, db, export, import, init, node, p2p, stage, test_eth_chain, test_vectors,
+    util::{
+        parse_path,
+        reth_tracing::{self, FileLogConfig, LogConfig, OtlpConfig, TracingMode},
+    },
+    version,
 };
 
 /// main function that parses cli and runs command
 pub async fn run() -> eyre::Result<()> {
     let opt = Cli::parse();
-    reth_tracing::build_subscriber(if opt.silent {
-        TracingMode::Silent
-    } else {
-        TracingMode::from(opt.verbose)
-    })
-    .init();
+    let (subscriber, log_filter, _guard) = reth_tracing::build_subscriber(
+        if opt.silent { TracingMode::Silent } else { TracingMode::from(opt.verbose) },
+        opt.logs.config(),
+    )?;
+    subscriber.init();
 
     match opt.command {
-        Commands::Node(command) => command.execute().await,
+        Commands::Node(command) => command.execute(Arc::new(log_filter)).await,
         Commands::TestEthChain(command) => command.execute().await,
         Commands::Db(command) => command.execute().await,
+        Commands::Stage(command) => command.execute().await,
+        Commands::Import(command) => command.execute().await,
+        Commands::Export(command) => command.execute().await,
+        Commands::Init(command) => command.execute().await,
+        Commands::Config(command) => command.execute().await,
+        Commands::TestVectors(command) => command.execute().await,
+        Commands::P2P(command) => command.execute().await,
     }
 }
 
@@ -37,10 +48,37 @@ pub enum Commands {
     /// DB Debugging utilities
     #[command(name = "db")]
     Db(db::Command),
+    /// Run or unwind a single stage
+    #[command(name = "stage")]
+    Stage(stage::Command),
+    /// Import the blocks of an RLP export file
+    #[command(name = "import")]
+    Import(import::Command),
+    /// Export the blocks of the canonical chain to an RLP file
+    #[command(name = "export")]
+    Export(export::Command),
+    /// Initialize the database with the genesis block of a chain
+    #[command(name = "init")]
+    Init(init::Command),
+    /// Print the effective configuration
+    #[command(name = "config")]
+    Config(config_cmd::Command),
+    /// Generate test vectors
+    #[command(name = "test-vectors")]
+    TestVectors(test_vectors::Command),
+    /// P2P Debugging utilities
+    #[command(name = "p2p")]
+    P2P(p2p::Command),
 }
 
 #[derive(Parser)]
-#[command(author, version="0.1", about="Reth binary", long_about = None)]
+#[command(
+    author,
+    version = version::SHORT_VERSION,
+    long_version = version::LONG_VERSION,
+    about = "Reth binary",
+    long_about = None
+)]
 struct Cli {
     /// The command to run
     #[clap(subcommand)]
@@ -53,4 +91,106 @@ struct Cli {
     /// Silence all output
     #[clap(long, global = true)]
     silent: bool,
+
+    #[clap(flatten)]
+    logs: Logs,
+}
+
+/// The log output options
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Logging")]
+struct Logs {
+    /// Log every event as a JSON object instead of a line of text.
+    #[arg(long = "log.json", global = true)]
+    json: bool,
+
+    /// The filter directives of the log output, like `net=trace,stages=debug`.
+    ///
+    /// Takes precedence over `RUST_LOG` and `-v`. The filter can be replaced while the node is
+    /// running with the `admin_setLogFilter` RPC method.
+    #[arg(long = "log.filter", value_name = "DIRECTIVES", global = true)]
+    filter: Option<String>,
+
+    /// Also send the logs to the systemd journal, with the priorities of their levels.
+    ///
+    /// Requires building reth with the `journald` feature.
+    #[arg(long = "log.journald", global = true)]
+    journald: bool,
+
+    /// Also write the logs to rotated files in this directory.
+    #[arg(
+        long = "log.file.directory",
+        value_name = "PATH",
+        global = true,
+        value_parser = parse_path
+    )]
+    file_directory: Option<PathBuf>,
+
+    /// The size in megabytes after which the log file is rotated.
+    #[arg(long = "log.file.max-size", value_name = "MB", global = true, default_value_t = 200)]
+    file_max_size: u64,
+
+    /// The maximum number of rotated log files that are kept.
+    #[arg(long = "log.file.max-files", value_name = "COUNT", global = true, default_value_t = 5)]
+    file_max_files: usize,
+
+    /// The filter directives of the log files, independent of `RUST_LOG` and `-v`.
+    #[arg(
+        long = "log.file.filter",
+        value_name = "DIRECTIVES",
+        global = true,
+        default_value = "reth=debug"
+    )]
+    file_filter: String,
+
+    /// Export the spans to the OpenTelemetry collector at this gRPC endpoint, like
+    /// `http://localhost:4317`.
+    ///
+    /// Requires building reth with the `otlp` feature.
+    #[arg(long = "tracing.otlp", value_name = "URL", global = true)]
+    otlp: Option<String>,
+
+    /// The ratio of the traces that are exported, between `0.0` and `1.0`.
+    #[arg(
+        long = "tracing.otlp.sample-ratio",
+        value_name = "RATIO",
+        global = true,
+        default_value_t = 1.0
+    )]
+    otlp_sample_ratio: f64,
+
+    /// The filter directives of the exported spans, independent of `RUST_LOG` and `-v`.
+    ///
+    /// Add `jsonrpsee=trace` to export a span for every RPC call.
+    #[arg(
+        long = "tracing.otlp.filter",
+        value_name = "DIRECTIVES",
+        global = true,
+        default_value = "reth=info"
+    )]
+    otlp_filter: String,
+}
+
+impl Logs {
+    /// Returns the configuration of the log outputs.
+    fn config(&self) -> LogConfig {
+        let file = self.file_directory.clone().map(|directory| FileLogConfig {
+            directory,
+            max_size_mb: self.file_max_size,
+            max_files: self.file_max_files,
+            filter: self.file_filter.clone(),
+        });
+        let otlp = self.otlp.clone().map(|endpoint| OtlpConfig {
+            endpoint,
+            sample_ratio: self.otlp_sample_ratio,
+            filter: self.otlp_filter.clone(),
+        });
+        LogConfig {
+            json: self.json,
+            journald: self.journald,
+            filter: self.filter.clone(),
+            file,
+            otlp,
+        }
+    }
 }
@@ -1,16 +1,52 @@
 //! Configuration files.
+use reth_db::mdbx::{EnvConfig, SyncMode};
+use reth_network::{SessionLimits, SessionsConfig as NetworkSessionsConfig};
+use reth_primitives::{BlockNumber, H256};
+use reth_rpc::{EthFilterConfig, DEFAULT_MAX_BLOCKS_PER_FILTER, DEFAULT_MAX_LOGS_PER_RESPONSE};
+use reth_rpc_builder::{RpcServerLimits, TransportConfig};
+use reth_stages::{
+    prune::PruneModes,
+    stages::{execution::ExecutionStageThresholds, tx_lookup::TransactionLookupStage},
+};
 use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, path::Path, time::Duration};
 
 /// Configuration for the reth node.
+///
+/// The configuration is read from `reth.toml` in the data directory, which is created with the
+/// default values on first run. Values missing from the file take their defaults, and the command
+/// line flags take precedence over the file.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     /// Configuration for each stage in the pipeline.
     // TODO(onbjerg): Can we make this easier to maintain when we add/remove stages?
     pub stages: StageConfig,
+    /// Configuration for pruning the historical data.
+    pub prune: PruneConfig,
+    /// Configuration for the connections to peers.
+    pub peers: PeersConfig,
+    /// Configuration for the sessions with peers.
+    pub sessions: SessionsConfig,
+    /// Configuration for the RPC servers.
+    pub rpc: RpcConfig,
+    /// Configuration for the database.
+    pub db: DatabaseConfig,
+    /// Configuration for debugging and benchmarking runs.
+    pub debug: DebugConfig,
+}
+
+impl Config {
+    /// Reads the configuration file, or writes the default configuration to it if it doesn't
+    /// exist.
+    pub fn load_or_create(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Ok(confy::load_path(path)?)
+    }
 }
 
 /// Configuration for each stage in the pipeline.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct StageConfig {
     /// Header stage configuration.
     pub headers: HeadersConfig,
@@ -18,10 +54,15 @@ pub struct StageConfig {
     pub bodies: BodiesConfig,
     /// Sender recovery stage configuration.
     pub sender_recovery: SenderRecoveryConfig,
+    /// Execution stage configuration.
+    pub execution: ExecutionConfig,
+    /// Transaction lookup stage configuration.
+    pub transaction_lookup: TransactionLookupConfig,
 }
 
 /// Header stage configuration.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct HeadersConfig {
     /// The maximum number of headers to download before committing progress to the database.
     pub commit_threshold: u64,
@@ -29,16 +70,24 @@ pub struct HeadersConfig {
     pub downloader_batch_size: u64,
     /// The number of times to retry downloading a set of headers.
     pub downloader_retries: usize,
+    /// The maximum number of header requests to have in flight at a time.
+    pub downloader_concurrency: usize,
 }
 
 impl Default for HeadersConfig {
     fn default() -> Self {
-        Self { commit_threshold: 10_000, downloader_batch_size: 1000, downloader_retries: 5 }
+        Self {
+            commit_threshold: 10_000,
+            downloader_batch_size: 1000,
+            downloader_retries: 5,
+            downloader_concurrency: 10,
+        }
     }
 }
 
 /// Body stage configuration.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct BodiesConfig {
     /// The maximum number of bodies to download before committing progress to the database.
     pub commit_threshold: u64,
@@ -51,6 +100,9 @@ pub struct BodiesConfig {
     /// The maximum number of bodies downloaded at the same time is `downloader_batch_size *
     /// downloader_concurrency`.
     pub downloader_concurrency: usize,
+    /// The approximate number of bytes of downloaded bodies to buffer before pausing new
+    /// requests.
+    pub downloader_max_buffered_bytes: usize,
 }
 
 impl Default for BodiesConfig {
@@ -60,12 +112,14 @@ impl Default for BodiesConfig {
             downloader_batch_size: 200,
             downloader_retries: 5,
             downloader_concurrency: 10,
+            downloader_max_buffered_bytes: 256 * 1024 * 1024,
         }
     }
 }
 
 /// Sender recovery stage configuration.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct SenderRecoveryConfig {
     /// The maximum number of blocks to process before committing progress to the database.
     pub commit_threshold: u64,
@@ -78,3 +132,369 @@ impl Default for SenderRecoveryConfig {
         Self { commit_threshold: 5_000, batch_size: 1000 }
     }
 }
+
+/// Execution stage configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExecutionConfig {
+    /// The maximum number of blocks to execute before committing progress to the database.
+    pub max_blocks: u64,
+    /// The maximum cumulative gas to execute before committing progress to the database.
+    pub max_gas: u64,
+    /// The maximum number of account changes before committing progress to the database.
+    pub max_changed_accounts: usize,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        let ExecutionStageThresholds { max_blocks, max_gas, max_changed_accounts } =
+            Default::default();
+        Self { max_blocks, max_gas, max_changed_accounts }
+    }
+}
+
+impl From<ExecutionConfig> for ExecutionStageThresholds {
+    fn from(config: ExecutionConfig) -> Self {
+        let ExecutionConfig { max_blocks, max_gas, max_changed_accounts } = config;
+        Self { max_blocks, max_gas, max_changed_accounts }
+    }
+}
+
+/// Transaction lookup stage configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TransactionLookupConfig {
+    /// The maximum number of blocks to index before committing progress to the database.
+    pub commit_threshold: u64,
+    /// Don't build the transaction hash index and remove the existing one.
+    ///
+    /// Transactions and receipts can't be looked up by hash if the index is pruned.
+    pub prune: bool,
+}
+
+impl Default for TransactionLookupConfig {
+    fn default() -> Self {
+        let TransactionLookupStage { commit_threshold, prune } = Default::default();
+        Self { commit_threshold, prune }
+    }
+}
+
+/// Pruning configuration.
+///
+/// Each value is the number of most recent blocks whose data is kept. Data without a value is never
+/// pruned, which is the default.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PruneConfig {
+    /// The retention of the transaction receipts.
+    pub receipts: Option<u64>,
+    /// The retention of the transaction hash index.
+    pub transaction_lookup: Option<u64>,
+    /// The retention of the recovered transaction senders.
+    pub sender_recovery: Option<u64>,
+    /// The retention of the account changesets.
+    ///
+    /// The node can't unwind past the pruned blocks, so this should exceed the depth of any
+    /// expected reorg.
+    pub account_history: Option<u64>,
+    /// The retention of the storage changesets.
+    ///
+    /// The node can't unwind past the pruned blocks, so this should exceed the depth of any
+    /// expected reorg.
+    pub storage_history: Option<u64>,
+}
+
+impl From<PruneConfig> for PruneModes {
+    fn from(config: PruneConfig) -> Self {
+        let PruneConfig {
+            receipts,
+            transaction_lookup,
+            sender_recovery,
+            account_history,
+            storage_history,
+        } = config;
+        Self { receipts, transaction_lookup, sender_recovery, account_history, storage_history }
+    }
+}
+
+/// Configuration for the connections to peers.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PeersConfig {
+    /// The maximum number of outbound connections.
+    pub max_outbound: usize,
+    /// The maximum number of inbound connections.
+    pub max_inbound: usize,
+    /// The number of seconds bad peers are banned for.
+    pub ban_duration: u64,
+    /// The number of seconds to wait before connecting again to a peer that couldn't accept the
+    /// connection, e.g. because it had too many peers.
+    pub backoff_duration: u64,
+}
+
+impl Default for PeersConfig {
+    fn default() -> Self {
+        Self {
+            max_outbound: 100,
+            max_inbound: 30,
+            ban_duration: 12 * 60 * 60,
+            backoff_duration: 60 * 60,
+        }
+    }
+}
+
+impl From<PeersConfig> for reth_network::PeersConfig {
+    fn from(config: PeersConfig) -> Self {
+        let PeersConfig { max_outbound, max_inbound, ban_duration, backoff_duration } = config;
+        let mut peers = reth_network::PeersConfig::default()
+            .with_max_outbound(max_outbound)
+            .with_max_inbound(max_inbound);
+        peers.ban_duration = Duration::from_secs(ban_duration);
+        peers.backoff_duration = Duration::from_secs(backoff_duration);
+        peers
+    }
+}
+
+/// Configuration for the sessions with peers.
+///
+/// The number of sessions is not limited beyond the connections to peers by default.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SessionsConfig {
+    /// The maximum number of inbound sessions that are being established.
+    pub max_pending_inbound: Option<u32>,
+    /// The maximum number of outbound sessions that are being established.
+    pub max_pending_outbound: Option<u32>,
+    /// The maximum number of established inbound sessions.
+    pub max_established_inbound: Option<u32>,
+    /// The maximum number of established outbound sessions.
+    pub max_established_outbound: Option<u32>,
+    /// The number of milliseconds to wait for the response to a request to a peer.
+    pub request_timeout: u64,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            max_pending_inbound: None,
+            max_pending_outbound: None,
+            max_established_inbound: None,
+            max_established_outbound: None,
+            request_timeout: NetworkSessionsConfig::default().request_timeout.as_millis() as u64,
+        }
+    }
+}
+
+impl From<SessionsConfig> for NetworkSessionsConfig {
+    fn from(config: SessionsConfig) -> Self {
+        let mut limits = SessionLimits::default();
+        if let Some(limit) = config.max_pending_inbound {
+            limits = limits.with_max_pending_inbound(limit);
+        }
+        if let Some(limit) = config.max_pending_outbound {
+            limits = limits.with_max_pending_outbound(limit);
+        }
+        if let Some(limit) = config.max_established_inbound {
+            limits = limits.with_max_established_inbound(limit);
+        }
+        if let Some(limit) = config.max_established_outbound {
+            limits = limits.with_max_established_outbound(limit);
+        }
+        Self {
+            limits,
+            request_timeout: Duration::from_millis(config.request_timeout),
+            ..Default::default()
+        }
+    }
+}
+
+/// Configuration for the RPC servers.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// Serve the JSON-RPC API over HTTP.
+    pub http: bool,
+    /// The address of the HTTP server.
+    pub http_addr: SocketAddr,
+    /// The modules served over HTTP, as a comma separated list like `eth,net,web3`, or `all`.
+    pub http_api: Option<String>,
+    /// The comma separated list of domains from which the HTTP server accepts cross-origin
+    /// requests, or `*` for any domain.
+    pub http_corsdomain: Option<String>,
+    /// Serve the JSON-RPC API over WS.
+    pub ws: bool,
+    /// The address of the WS server.
+    pub ws_addr: SocketAddr,
+    /// The modules served over WS, as a comma separated list like `eth,net,web3`, or `all`.
+    pub ws_api: Option<String>,
+    /// The comma separated list of origins from which the WS server accepts cross-origin
+    /// requests, or `*` for any origin.
+    pub ws_origins: Option<String>,
+    /// The address of the Engine API server.
+    pub authrpc_addr: SocketAddr,
+    /// The maximum number of blocks a log query can span.
+    pub max_blocks_per_filter: u64,
+    /// The maximum number of logs a log query can return.
+    pub max_logs_per_response: usize,
+    /// The maximum number of concurrent connections of each server.
+    pub max_connections: u32,
+    /// The maximum size of a request body in bytes.
+    pub max_request_size: u32,
+    /// The maximum size of a response body in bytes.
+    pub max_response_size: u32,
+    /// The maximum number of calls in a batch request.
+    pub max_batch_len: u32,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        let limits = RpcServerLimits::default();
+        Self {
+            http: false,
+            http_addr: TransportConfig::default_http().addr(),
+            http_api: None,
+            http_corsdomain: None,
+            ws: false,
+            ws_addr: TransportConfig::default_ws().addr(),
+            ws_api: None,
+            ws_origins: None,
+            authrpc_addr: TransportConfig::default_engine().addr(),
+            max_blocks_per_filter: DEFAULT_MAX_BLOCKS_PER_FILTER,
+            max_logs_per_response: DEFAULT_MAX_LOGS_PER_RESPONSE,
+            max_connections: limits.max_connections,
+            max_request_size: limits.max_request_size,
+            max_response_size: limits.max_response_size,
+            max_batch_len: limits.max_batch_len,
+        }
+    }
+}
+
+/// Database configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// The maximum size of the database in bytes.
+    pub max_size: usize,
+    /// The number of bytes by which the database grows when it's full.
+    pub growth_step: usize,
+    /// How commits are flushed to disk.
+    pub sync_mode: DatabaseSyncMode,
+    /// The maximum number of concurrent read transactions of all processes that open the
+    /// database, or the MDBX default.
+    pub max_readers: Option<u32>,
+    /// The interval in seconds at which the read transactions are checked, or 0 to never check
+    /// them.
+    ///
+    /// The check clears the read transactions of processes that exited without ending them, and
+    /// warns about read transactions that are open for too long.
+    pub reader_check_interval: u64,
+    /// The number of transactions a read transaction can fall behind before it's reported.
+    ///
+    /// The database can't reuse the pages of the transactions that are still read, so it grows
+    /// while a read transaction is open.
+    pub max_reader_lag: usize,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        let EnvConfig { max_size, growth_step, max_readers, .. } = Default::default();
+        Self {
+            max_size,
+            growth_step,
+            sync_mode: DatabaseSyncMode::Durable,
+            max_readers,
+            reader_check_interval: 60,
+            max_reader_lag: 100,
+        }
+    }
+}
+
+impl From<&RpcConfig> for EthFilterConfig {
+    fn from(config: &RpcConfig) -> Self {
+        Self {
+            max_blocks_per_filter: config.max_blocks_per_filter,
+            max_logs_per_response: config.max_logs_per_response,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&RpcConfig> for RpcServerLimits {
+    fn from(config: &RpcConfig) -> Self {
+        Self {
+            max_connections: config.max_connections,
+            max_request_size: config.max_request_size,
+            max_response_size: config.max_response_size,
+            max_batch_len: config.max_batch_len,
+        }
+    }
+}
+
+impl From<&DatabaseConfig> for EnvConfig {
+    fn from(config: &DatabaseConfig) -> Self {
+        Self {
+            max_size: config.max_size,
+            growth_step: config.growth_step,
+            sync_mode: config.sync_mode.into(),
+            max_readers: config.max_readers,
+        }
+    }
+}
+
+/// How commits are flushed to disk, see [SyncMode].
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseSyncMode {
+    /// Flush the data and then the metadata on every commit.
+    Durable,
+    /// Don't flush the metadata on commit, a system crash may undo the last commit.
+    NoMetaSync,
+    /// Don't flush on commit, a system crash may undo the commits since the last flush.
+    SafeNoSync,
+    /// Don't flush on commit, a system crash may corrupt the database.
+    UtterlyNoSync,
+}
+
+impl From<DatabaseSyncMode> for SyncMode {
+    fn from(mode: DatabaseSyncMode) -> Self {
+        match mode {
+            DatabaseSyncMode::Durable => SyncMode::Durable,
+            DatabaseSyncMode::NoMetaSync => SyncMode::NoMetaSync,
+            DatabaseSyncMode::SafeNoSync => SyncMode::SafeNoSync,
+            DatabaseSyncMode::UtterlyNoSync => SyncMode::UtterlyNoSync,
+        }
+    }
+}
+
+/// Debug configuration.
+///
+/// The values can be overridden on the command line.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DebugConfig {
+    /// The hash of the block to sync to, instead of the tip announced by the consensus layer.
+    pub tip: Option<H256>,
+    /// The block number at which the pipeline stops.
+    pub max_block: Option<BlockNumber>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_missing_values_with_defaults() {
+        let config: Config =
+            toml::from_str("[peers]\nmax_inbound = 5\n\n[rpc]\nhttp = true\n").unwrap();
+        assert_eq!(config.peers.max_inbound, 5);
+        assert_eq!(config.peers.max_outbound, PeersConfig::default().max_outbound);
+        assert!(config.rpc.http);
+        assert_eq!(config.rpc.http_addr, RpcConfig::default().http_addr);
+        assert_eq!(RpcServerLimits::from(&config.rpc), RpcServerLimits::default());
+        assert_eq!(config.stages.headers.commit_threshold, 10_000);
+
+        // the default config survives a round trip through its file format
+        let config: Config = toml::from_str(&toml::to_string(&Config::default()).unwrap()).unwrap();
+        assert_eq!(config.rpc.authrpc_addr, RpcConfig::default().authrpc_addr);
+    }
+}
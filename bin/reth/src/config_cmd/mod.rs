@@ -0,0 +1,70 @@
+//! Config command
+//!
+//! Prints the configuration the node runs with.
+use crate::{
+    config::Config,
+    dirs::DataDirPath,
+    util::{chainspec::chain_spec_value_parser, parse_path},
+};
+use clap::Parser;
+use reth_primitives::ChainSpec;
+use std::path::PathBuf;
+
+/// Print the effective configuration
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The path to the configuration file to use, `<DATA_DIR>/<CHAIN>/reth.toml` by default.
+    ///
+    /// The file is created with the default configuration if it doesn't exist.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The chain whose configuration is printed.
+    ///
+    /// Possible values are either a built-in chain or the path to a genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - dev
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
+
+    /// Print the default configuration instead of the configuration file.
+    #[arg(long)]
+    default: bool,
+}
+
+impl Command {
+    /// Execute `config` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        // the values missing from the file are filled in with their defaults
+        let config = if self.default {
+            Config::default()
+        } else {
+            let datadir = self.datadir.chain(self.chain.chain);
+            Config::load_or_create(self.config.clone().unwrap_or_else(|| datadir.config_path()))?
+        };
+        println!("{}", toml::to_string_pretty(&config)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Consistency check of the database.
+//!
+//! Walks the canonical chain and verifies the invariants between the tables that the stages write,
+//! e.g. after a crash, a failed unwind or a manual change of the database.
+use eyre::Result;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::BlockNumHash,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{BlockNumber, TxNumber, H256};
+use reth_provider::static_file::StaticFileProvider;
+use reth_stages::stages::{bodies::BODIES, execution::EXECUTION};
+use std::{fmt, ops::RangeInclusive};
+use tracing::info;
+
+/// An inconsistency between the tables, found for a range of blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Inconsistency {
+    /// What is inconsistent.
+    pub(crate) problem: &'static str,
+    /// The blocks that are affected.
+    pub(crate) blocks: RangeInclusive<BlockNumber>,
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "blocks {}..={}: {}", self.blocks.start(), self.blocks.end(), self.problem)
+    }
+}
+
+/// The inconsistencies found so far, where the same problem of consecutive blocks is merged into
+/// one range.
+#[derive(Debug, Default)]
+struct Report(Vec<Inconsistency>);
+
+impl Report {
+    fn add(&mut self, problem: &'static str, blocks: RangeInclusive<BlockNumber>) {
+        let adjacent =
+            self.0.iter_mut().rev().find(|found| {
+                found.problem == problem && *found.blocks.end() + 1 == *blocks.start()
+            });
+        match adjacent {
+            Some(found) => found.blocks = *found.blocks.start()..=*blocks.end(),
+            None => self.0.push(Inconsistency { problem, blocks }),
+        }
+    }
+}
+
+/// Verifies the invariants between the tables:
+///
+/// - every canonical block has a header with the canonical hash, which is the child of the
+///   previous canonical header, and its number is indexed by its hash,
+/// - up to the bodies checkpoint, every canonical block has a body whose transactions follow the
+///   transactions of the previous body and are stored, either in the database or in the static
+///   files, and no transactions are stored after the last body,
+/// - the plain state is at the execution checkpoint, so there are no changesets of later blocks.
+pub(crate) fn check_consistency<DB: Database>(
+    db: &DB,
+    static_files: &StaticFileProvider,
+) -> Result<Vec<Inconsistency>> {
+    let tx = db.tx()?;
+    let mut report = Report::default();
+
+    let bodies_checkpoint = BODIES.get_progress(&tx)?.unwrap_or_default();
+    let execution_checkpoint = EXECUTION.get_progress(&tx)?.unwrap_or_default();
+    info!(bodies_checkpoint, execution_checkpoint, "Checking canonical chain");
+
+    let mut parent_hash: Option<H256> = None;
+    let mut next_tx_id: TxNumber = 0;
+    for entry in tx.cursor::<tables::CanonicalHeaders>()?.walk(0)? {
+        let (number, hash) = entry?;
+        let key: BlockNumHash = (number, hash).into();
+
+        let header = match tx.get::<tables::Headers>(key)? {
+            Some(header) => Some(header),
+            None => static_files.header(number)?,
+        };
+        match header {
+            Some(header) => {
+                if header.number != number || header.hash_slow() != hash {
+                    report.add("header doesn't match the canonical hash", number..=number);
+                }
+                if parent_hash.map_or(false, |parent_hash| header.parent_hash != parent_hash) {
+                    report.add("header is not a child of the previous block", number..=number);
+                }
+            }
+            None => report.add("canonical header is missing", number..=number),
+        }
+        if tx.get::<tables::HeaderNumbers>(hash)? != Some(number) {
+            report.add("block number is not indexed by the canonical hash", number..=number);
+        }
+        parent_hash = Some(hash);
+
+        if number > bodies_checkpoint {
+            continue
+        }
+        match tx.get::<tables::BlockBodies>(key)? {
+            Some(body) => {
+                if body.start_tx_id != next_tx_id {
+                    report.add("body transactions don't follow the previous body", number..=number);
+                }
+                if !body.is_empty() &&
+                    (!has_transaction(&tx, static_files, body.start_tx_id)? ||
+                        !has_transaction(&tx, static_files, body.last_tx_index())?)
+                {
+                    report.add("body transactions are missing", number..=number);
+                }
+                next_tx_id = body.start_tx_id + body.tx_count;
+            }
+            None => report.add("canonical body is missing", number..=number),
+        }
+    }
+
+    let after_bodies = bodies_checkpoint + 1;
+    if let Some((id, _)) = tx.cursor::<tables::Transactions>()?.last()? {
+        if id >= next_tx_id {
+            report.add("transactions are stored after the last body", after_bodies..=after_bodies);
+        }
+    }
+
+    info!("Checking changesets");
+    let checkpoint_transition = match tx.get::<tables::CanonicalHeaders>(execution_checkpoint)? {
+        Some(hash) => {
+            tx.get::<tables::BlockTransitionIndex>((execution_checkpoint, hash).into())?
+        }
+        None => None,
+    };
+    match checkpoint_transition {
+        Some(checkpoint_transition) => {
+            let last_account_change =
+                tx.cursor_dup::<tables::AccountChangeSet>()?.last()?.map(|(id, _)| id);
+            let last_storage_change = tx
+                .cursor_dup::<tables::StorageChangeSet>()?
+                .last()?
+                .map(|(key, _)| key.transition_id());
+            let last_change = last_account_change.max(last_storage_change).unwrap_or_default();
+            if last_change > checkpoint_transition {
+                let first = execution_checkpoint + 1;
+                let last = block_of_transition(&tx, first, last_change)?.unwrap_or(first);
+                report.add("changesets are stored after the execution checkpoint", first..=last);
+            }
+        }
+        None if execution_checkpoint > 0 => report.add(
+            "transition of the execution checkpoint is missing",
+            execution_checkpoint..=execution_checkpoint,
+        ),
+        None => {}
+    }
+
+    tx.commit()?;
+    Ok(report.0)
+}
+
+/// Whether the transaction is stored in the database or in the static files.
+fn has_transaction<'a>(
+    tx: &impl DbTx<'a>,
+    static_files: &StaticFileProvider,
+    id: TxNumber,
+) -> Result<bool> {
+    Ok(tx.get::<tables::Transactions>(id)?.is_some() || static_files.transaction(id)?.is_some())
+}
+
+/// The first block starting at `from` that ends at or after the transition.
+fn block_of_transition<'a>(
+    tx: &impl DbTx<'a>,
+    from: BlockNumber,
+    transition: u64,
+) -> Result<Option<BlockNumber>> {
+    let mut cursor = tx.cursor::<tables::BlockTransitionIndex>()?;
+    for entry in cursor.walk((from, H256::zero()).into())? {
+        let (key, block_transition) = entry?;
+        if block_transition >= transition {
+            return Ok(Some(key.number()))
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::AccountBeforeTx,
+        transaction::DbTxMut,
+    };
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_primitives::Address;
+    use reth_provider::insert_canonical_block;
+
+    #[test]
+    fn report_missing_bodies_and_stray_changesets() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+
+        let blocks = random_block_range(0..6, H256::zero(), 0..1);
+        db.update(|tx| {
+            for block in &blocks {
+                insert_canonical_block(tx, block, None, true).unwrap();
+            }
+            BODIES.save_progress(tx, 5).unwrap();
+            EXECUTION.save_progress(tx, 3).unwrap();
+        })
+        .unwrap();
+        assert_eq!(check_consistency(db.as_ref(), &static_files).unwrap(), vec![]);
+
+        // a gap in the bodies and a changeset of a block that was not executed
+        db.update(|tx| {
+            for block in &blocks[2..=3] {
+                tx.delete::<tables::BlockBodies>(block.header.num_hash().into(), None).unwrap();
+            }
+            let transition = tx
+                .get::<tables::BlockTransitionIndex>(blocks[4].header.num_hash().into())
+                .unwrap()
+                .unwrap();
+            let before = AccountBeforeTx { address: Address::random(), info: None };
+            tx.put::<tables::AccountChangeSet>(transition, before).unwrap();
+        })
+        .unwrap();
+
+        assert_eq!(
+            check_consistency(db.as_ref(), &static_files).unwrap(),
+            vec![
+                Inconsistency { problem: "canonical body is missing", blocks: 2..=3 },
+                Inconsistency {
+                    problem: "changesets are stored after the execution checkpoint",
+                    blocks: 4..=4
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Clearing and dropping of single tables.
+//!
+//! The checkpoints of the stages that write a table are reset together with the table, so the
+//! table can be rebuilt with `reth stage run` instead of syncing again.
+use eyre::{Result, WrapErr};
+use reth_db::{
+    database::Database,
+    mdbx::{Env, EnvironmentKind},
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_stages::{
+    stages::{
+        bodies::BODIES, execution::EXECUTION, headers::HEADERS, merkle::MERKLE,
+        sender_recovery::SENDER_RECOVERY, tx_lookup::TRANSACTION_LOOKUP,
+    },
+    StageId,
+};
+use tracing::info;
+
+/// The tables that are written by each stage.
+const STAGE_TABLES: &[(StageId, &[&str])] = &[
+    (HEADERS, &["CanonicalHeaders", "HeaderTD", "HeaderNumbers", "Headers"]),
+    (
+        BODIES,
+        &[
+            "BlockBodies",
+            "BlockOmmers",
+            "BlockWithdrawals",
+            "Transactions",
+            "BlockTransitionIndex",
+            "TxTransitionIndex",
+        ],
+    ),
+    (SENDER_RECOVERY, &["TxSenders"]),
+    (
+        EXECUTION,
+        &[
+            "PlainAccountState",
+            "PlainStorageState",
+            "Bytecodes",
+            "AccountChangeSet",
+            "StorageChangeSet",
+            "AccountHistory",
+            "StorageHistory",
+        ],
+    ),
+    (MERKLE, &["HashedAccount", "HashedStorage", "AccountsTrie", "StoragesTrie"]),
+    (TRANSACTION_LOOKUP, &["TxHashNumber"]),
+];
+
+/// Deletes all entries of the table, or the table itself if `drop` is set, and resets the
+/// checkpoints of the stages that write it, in a single transaction.
+///
+/// A dropped table is created again, empty, the next time the database is opened.
+pub(crate) fn clear_table<E: EnvironmentKind>(db: &Env<E>, table: &str, drop: bool) -> Result<()> {
+    let name = tables::TABLES
+        .iter()
+        .map(|(_, name)| *name)
+        .find(|name| *name == table)
+        .ok_or_else(|| eyre::eyre!("Unknown table: {table}"))?;
+
+    let tx = db.tx_mut()?;
+    let table_db = tx.inner.open_db(Some(name)).wrap_err("Could not open db.")?;
+    if drop {
+        // SAFETY: the table is not opened anywhere else within this transaction.
+        unsafe { tx.inner.drop_db(table_db) }.wrap_err(format!("Could not drop table: {name}"))?;
+        info!("Dropped table {name}");
+    } else {
+        tx.inner.clear_db(&table_db).wrap_err(format!("Could not clear table: {name}"))?;
+        info!("Cleared table {name}");
+    }
+
+    let stages = STAGE_TABLES.iter().filter(|(_, stage_tables)| stage_tables.contains(&name));
+    for (stage, _) in stages {
+        tx.delete::<tables::SyncStage>(stage.0.as_bytes().to_vec(), None)?;
+        info!("Reset the checkpoint of stage {stage}");
+    }
+
+    tx.commit()?;
+    Ok(())
+}
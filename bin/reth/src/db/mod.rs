@@ -1,30 +1,71 @@
 //! Database debugging tool
-use crate::dirs::DbPath;
+use crate::{
+    dirs::DataDirPath,
+    util::{chainspec::chain_spec_value_parser, parse_path},
+};
 use clap::{Parser, Subcommand};
 use eyre::{Result, WrapErr};
 use reth_db::{
     cursor::{DbCursorRO, Walker},
     database::Database,
-    table::Table,
+    mdbx::{Env, EnvKind, EnvironmentKind, NoWriteMap},
+    table::{Decode, Table},
     tables,
     transaction::DbTx,
+    version::{check_db_version_file, db_version_file_path, init_db_version_file},
 };
 use reth_interfaces::test_utils::generators::random_block_range;
-use reth_provider::insert_canonical_block;
-use tracing::info;
+use reth_primitives::{hex, ChainSpec};
+use reth_provider::{
+    insert_canonical_block,
+    static_file::{StaticFileProvider, StaticFileSegment},
+};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+mod check;
+mod clear;
 
 /// `reth db` command
 #[derive(Debug, Parser)]
 pub struct Command {
-    /// The path to the database folder.
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
     ///
     /// Defaults to the OS-specific data directory:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: DbPath,
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The path to the database folder, `<DATA_DIR>/<CHAIN>/db` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    db: Option<PathBuf>,
+
+    /// The path to the folder of the static files, `<DATA_DIR>/<CHAIN>/static_files` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    static_files: Option<PathBuf>,
+
+    /// The chain the database belongs to.
+    ///
+    /// Possible values are either a built-in chain or the path to a genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - dev
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
 
     #[clap(subcommand)]
     command: Subcommands,
@@ -32,6 +73,9 @@ pub struct Command {
 
 const DEFAULT_NUM_ITEMS: &str = "5";
 
+/// The name of the MDBX data file in the database folder.
+const MDBX_DATA_FILE: &str = "mdbx.dat";
+
 #[derive(Subcommand, Debug)]
 /// `reth db` subcommands
 pub enum Subcommands {
@@ -39,6 +83,32 @@ pub enum Subcommands {
     Stats,
     /// Lists the contents of a table
     List(ListArgs),
+    /// Gets the value of a key in a table
+    Get(GetArgs),
+    /// Verifies the invariants between the tables and reports the inconsistent blocks
+    Check,
+    /// Deletes all entries of a table and resets the checkpoints of the stages that write it
+    Clear {
+        /// The table name
+        table: String,
+    },
+    /// Drops a table from the database and resets the checkpoints of the stages that write it.
+    ///
+    /// Unlike `clear`, this also frees the pages of the table. The table is created again, empty,
+    /// the next time the database is opened.
+    Drop {
+        /// The table name
+        table: String,
+    },
+    /// Copies the database and the static files to a backup folder while the node is running
+    Backup(BackupArgs),
+    /// Verifies a backup and restores it into an empty database and static files folder
+    Restore(RestoreArgs),
+    /// Verifies that a backup can be opened and read
+    VerifyBackup {
+        /// The backup folder
+        path: PathBuf,
+    },
     /// Seeds the database with random blocks on top of each other
     Seed {
         /// How many blocks to generate
@@ -52,22 +122,115 @@ pub enum Subcommands {
 pub struct ListArgs {
     /// The table name
     table: String, // TODO: Convert to enum
-    /// Where to start iterating
-    #[arg(long, short, default_value = "0")]
-    start: usize,
+    /// How many items to skip from the start of the table
+    #[arg(long, short, alias = "start", default_value = "0")]
+    skip: usize,
     /// How many items to take from the walker
     #[arg(long, short, default_value = DEFAULT_NUM_ITEMS)]
     len: usize,
+    /// Print the items as JSON, with the keys and values in their debug format
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db get` command
+pub struct GetArgs {
+    /// The table name
+    table: String,
+    /// The key to get.
+    ///
+    /// Decimal numbers are encoded as 8 byte big-endian integers, like the block and transaction
+    /// numbers. Other keys are given as their hex encoding with an optional `0x` prefix, or as a
+    /// plain string, like the stage ids.
+    #[arg(verbatim_doc_comment)]
+    key: String,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db backup` command
+pub struct BackupArgs {
+    /// The backup folder, which must not contain a backup yet
+    path: PathBuf,
+    /// Omit the free pages of the database from the backup, which is slower but makes the backup
+    /// smaller
+    #[arg(long)]
+    compact: bool,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db restore` command
+pub struct RestoreArgs {
+    /// The backup folder
+    path: PathBuf,
+}
+
+/// Expands to a match over the table names, calling `$f::<tables::Table>($args)` for the table
+/// with the given name.
+macro_rules! table_match {
+    ($name:expr, $f:ident($($args:expr),*)) => {
+        table_match!($name, $f($($args),*) => [
+            CanonicalHeaders,
+            HeaderTD,
+            HeaderNumbers,
+            Headers,
+            BlockBodies,
+            BlockOmmers,
+            BlockWithdrawals,
+            NonCanonicalTransactions,
+            Transactions,
+            TxHashNumber,
+            Receipts,
+            Logs,
+            PlainAccountState,
+            Bytecodes,
+            BlockTransitionIndex,
+            TxTransitionIndex,
+            PlainStorageState,
+            AccountHistory,
+            StorageHistory,
+            AccountChangeSet,
+            StorageChangeSet,
+            HashedAccount,
+            HashedStorage,
+            AccountsTrie,
+            StoragesTrie,
+            TxSenders,
+            Config,
+            SyncStage,
+            PruneCheckpoints,
+            ForkChoice
+        ])
+    };
+    ($name:expr, $f:ident($($args:expr),*) => [$($table:ident),*]) => {
+        match $name {
+            $(stringify!($table) => $f::<tables::$table>($($args),*),)*
+            _ => Err(eyre::eyre!("Unknown table: {}", $name)),
+        }
+    };
 }
 
 impl Command {
     /// Execute `db` command
     pub async fn execute(&self) -> eyre::Result<()> {
-        std::fs::create_dir_all(&self.db)?;
+        let datadir = self.datadir.chain(self.chain.chain);
+        let db_path = self.db.clone().unwrap_or_else(|| datadir.db_path());
+        let static_files_path =
+            self.static_files.clone().unwrap_or_else(|| datadir.static_files_path());
+
+        // The backup is restored before the database is created
+        match &self.command {
+            Subcommands::Restore(args) => return restore(args, &db_path, &static_files_path),
+            Subcommands::VerifyBackup { path } => return verify_backup(path),
+            _ => {}
+        }
+
+        std::fs::create_dir_all(&db_path)?;
+        init_db_version_file(&db_path)?;
 
         // TODO: Auto-impl for Database trait
         let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
-            self.db.as_ref(),
+            &db_path,
             reth_db::mdbx::EnvKind::RW,
         )?;
 
@@ -111,6 +274,30 @@ impl Command {
             Subcommands::List(args) => {
                 tool.list(args)?;
             }
+            Subcommands::Get(args) => {
+                tool.get(args)?;
+            }
+            Subcommands::Check => {
+                let static_files = StaticFileProvider::open(&static_files_path)?;
+                let inconsistencies = check::check_consistency(&db, &static_files)?;
+                for inconsistency in &inconsistencies {
+                    warn!("Inconsistency at {inconsistency}");
+                }
+                if !inconsistencies.is_empty() {
+                    return Err(eyre::eyre!("Found {} inconsistencies", inconsistencies.len()))
+                }
+                info!("No inconsistencies found");
+            }
+            Subcommands::Clear { table } => {
+                clear::clear_table(&db, table, false)?;
+            }
+            Subcommands::Drop { table } => {
+                clear::clear_table(&db, table, true)?;
+            }
+            Subcommands::Backup(args) => {
+                backup(&db, &db_path, args, &static_files_path)?;
+            }
+            Subcommands::Restore(_) | Subcommands::VerifyBackup { .. } => unreachable!(),
         }
 
         Ok(())
@@ -135,7 +322,7 @@ impl<'a, DB: Database> DbTool<'a, DB> {
 
         self.db.update(|tx| {
             chain.iter().try_for_each(|block| {
-                insert_canonical_block(tx, block, true)?;
+                insert_canonical_block(tx, block, None, true)?;
                 Ok::<_, eyre::Error>(())
             })
         })??;
@@ -146,54 +333,153 @@ impl<'a, DB: Database> DbTool<'a, DB> {
 
     /// Lists the given table data
     fn list(&mut self, args: &ListArgs) -> Result<()> {
-        macro_rules! list_tables {
-            ($arg:expr, $start:expr, $len:expr  => [$($table:ident),*]) => {
-                match $arg {
-                    $(stringify!($table) => {
-                        self.list_table::<tables::$table>($start, $len)?
-                    },)*
-                    _ => {
-                        tracing::error!("Unknown table.");
-                        return Ok(())
-                    }
-                }
-            };
+        let db = self.db;
+        table_match!(args.table.as_str(), list_table(db, args))
+    }
+
+    /// Prints the value of the given key
+    fn get(&mut self, args: &GetArgs) -> Result<()> {
+        let db = self.db;
+        let key = parse_key(&args.key);
+        table_match!(args.table.as_str(), get_value(db, key))
+    }
+}
+
+fn list_table<T: Table>(db: &impl Database, args: &ListArgs) -> Result<()> {
+    let data = db.view(|tx| {
+        let mut cursor = tx.cursor::<T>().expect("Was not able to obtain a cursor.");
+
+        // TODO: Upstream this in the DB trait.
+        let start_walker = cursor.current().transpose();
+        let walker = Walker {
+            cursor: &mut cursor,
+            start: start_walker,
+            _tx_phantom: std::marker::PhantomData,
+        };
+
+        walker.skip(args.skip).take(args.len).collect::<Result<Vec<_>, _>>()
+    })??;
+
+    if args.json {
+        let entries = data
+            .iter()
+            .map(|(key, value)| {
+                serde_json::json!({ "key": format!("{key:?}"), "value": format!("{value:?}") })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        for (key, value) in data {
+            println!("{key:?}: {value:?}");
         }
+    }
+    Ok(())
+}
 
-        list_tables!(args.table.as_str(), args.start, args.len => [
-            CanonicalHeaders,
-            HeaderTD,
-            HeaderNumbers,
-            Headers,
-            BlockBodies,
-            BlockOmmers,
-            TxHashNumber,
-            PlainAccountState,
-            BlockTransitionIndex,
-            TxTransitionIndex,
-            SyncStage,
-            Transactions
-        ]);
+fn get_value<T: Table>(db: &impl Database, key: Vec<u8>) -> Result<()> {
+    let key = T::Key::decode(key).wrap_err(format!("Invalid key for table {}", T::NAME))?;
+    let tx = db.tx()?;
+    let value = tx.get::<T>(key)?;
+    tx.commit()?;
 
-        Ok(())
+    match value {
+        Some(value) => println!("{value:?}"),
+        None => info!("No value found in table {}", T::NAME),
+    }
+    Ok(())
+}
+
+/// Encodes a key given on the command line, see [GetArgs::key].
+fn parse_key(key: &str) -> Vec<u8> {
+    if let Ok(number) = key.parse::<u64>() {
+        return number.to_be_bytes().to_vec()
     }
+    hex::decode(key.strip_prefix("0x").unwrap_or(key)).unwrap_or_else(|_| key.as_bytes().to_vec())
+}
 
-    fn list_table<T: Table>(&mut self, start: usize, len: usize) -> Result<()> {
-        let data = self.db.view(|tx| {
-            let mut cursor = tx.cursor::<T>().expect("Was not able to obtain a cursor.");
+/// Copies the database with its version file and then the static files to the backup folder.
+///
+/// The database is copied within a single read transaction, so the copy is consistent even while
+/// the node is writing to it. The static files are copied afterwards, so they contain at least the
+/// blocks that the copy of the database has moved to them.
+fn backup<E: EnvironmentKind>(
+    db: &Env<E>,
+    source_db_path: &Path,
+    args: &BackupArgs,
+    static_files_path: &Path,
+) -> Result<()> {
+    let db_path = args.path.join("db");
+    std::fs::create_dir_all(&db_path)?;
+    info!("Copying database to {}", db_path.display());
+    db.copy(&db_path.join(MDBX_DATA_FILE), args.compact)
+        .wrap_err("Could not copy the database, the backup folder must be empty")?;
+    std::fs::copy(db_version_file_path(source_db_path), db_version_file_path(&db_path))?;
 
-            // TODO: Upstream this in the DB trait.
-            let start_walker = cursor.current().transpose();
-            let walker = Walker {
-                cursor: &mut cursor,
-                start: start_walker,
-                _tx_phantom: std::marker::PhantomData,
-            };
+    let backup_static_files_path = args.path.join("static_files");
+    info!("Copying static files to {}", backup_static_files_path.display());
+    StaticFileProvider::open(static_files_path)?.copy_to(&backup_static_files_path)?;
 
-            walker.skip(start).take(len).collect::<Vec<_>>()
-        })?;
+    verify_backup(&args.path)?;
+    info!("Backup written to {}", args.path.display());
+    Ok(())
+}
 
-        println!("{data:?}");
-        Ok(())
+/// Opens the database and the static files of the backup and reads all tables and segments.
+fn verify_backup(path: &Path) -> Result<()> {
+    let db_path = path.join("db");
+    let static_files_path = path.join("static_files");
+    if !db_path.join(MDBX_DATA_FILE).is_file() || !static_files_path.is_dir() {
+        return Err(eyre::eyre!("No backup found at {}", path.display()))
     }
+    check_db_version_file(&db_path)?;
+
+    let db = Env::<NoWriteMap>::open(&db_path, EnvKind::RO)?;
+    db.view(|tx| {
+        for table in tables::TABLES.iter().map(|(_, name)| name) {
+            let table_db = tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
+            let stats =
+                tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {table}"))?;
+            info!("Table {} has {} entries", table, stats.entries());
+        }
+        Ok::<(), eyre::Report>(())
+    })??;
+
+    let static_files = StaticFileProvider::open(&static_files_path)?;
+    for segment in StaticFileSegment::ALL {
+        match static_files.highest_block(segment) {
+            Some(block) => info!("Static files of {segment} end at block {block}"),
+            None => info!("No static files of {segment}"),
+        }
+    }
+
+    info!("Backup at {} is valid", path.display());
+    Ok(())
+}
+
+/// Verifies the backup and copies it into the database and static files folders, which must not
+/// contain any data yet.
+fn restore(args: &RestoreArgs, db_path: &Path, static_files_path: &Path) -> Result<()> {
+    verify_backup(&args.path)?;
+
+    let db_file = db_path.join(MDBX_DATA_FILE);
+    if db_file.exists() {
+        return Err(eyre::eyre!("A database already exists at {}", db_path.display()))
+    }
+    if static_files_path.is_dir() && std::fs::read_dir(static_files_path)?.next().is_some() {
+        return Err(eyre::eyre!(
+            "The static files folder {} is not empty",
+            static_files_path.display()
+        ))
+    }
+
+    info!("Restoring database to {}", db_path.display());
+    std::fs::create_dir_all(db_path)?;
+    std::fs::copy(args.path.join("db").join(MDBX_DATA_FILE), db_file)?;
+    std::fs::copy(db_version_file_path(args.path.join("db")), db_version_file_path(db_path))?;
+
+    info!("Restoring static files to {}", static_files_path.display());
+    StaticFileProvider::open(args.path.join("static_files"))?.copy_to(static_files_path)?;
+
+    info!("Backup restored");
+    Ok(())
 }
@@ -1,5 +1,6 @@
 //! reth data directories.
 use crate::util::parse_path;
+use reth_primitives::Chain;
 use std::{
     env::VarError,
     fmt::{Debug, Display, Formatter},
@@ -14,38 +15,38 @@ pub fn data_dir() -> Option<PathBuf> {
     dirs_next::data_dir().map(|root| root.join("reth"))
 }
 
-/// Returns the path to the reth database.
+/// A wrapper type that either parses a user-given path for the reth data directory or defaults to
+/// the OS-specific data directory:
 ///
-/// Refer to [dirs_next::data_dir] for cross-platform behavior.
-pub fn database_path() -> Option<PathBuf> {
-    data_dir().map(|root| root.join("db"))
-}
-
-/// Returns the path to the reth configuration directory.
+/// - Linux: `$XDG_DATA_HOME/reth` or `$HOME/.local/share/reth`
+/// - Windows: `{FOLDERID_RoamingAppData}/reth`
+/// - macOS: `$HOME/Library/Application Support/reth`
 ///
-/// Refer to [dirs_next::config_dir] for cross-platform behavior.
-pub fn config_dir() -> Option<PathBuf> {
-    dirs_next::config_dir().map(|root| root.join("reth"))
-}
-
-/// A wrapper type that either parses a user-given path for the reth database or defaults to an
-/// OS-specific path.
+/// The data of each chain is kept in its own subdirectory, see [DataDirPath::chain].
 #[derive(Clone, Debug)]
-pub struct DbPath(PathBuf);
+pub struct DataDirPath(PathBuf);
+
+impl DataDirPath {
+    /// Returns the data directory of the given chain, which is named after the chain, e.g.
+    /// `mainnet` or the chain id of a custom chain.
+    pub fn chain(&self, chain: Chain) -> ChainDataDir {
+        ChainDataDir(self.0.join(chain.to_string()))
+    }
+}
 
-impl Display for DbPath {
+impl Display for DataDirPath {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0.display())
     }
 }
 
-impl Default for DbPath {
+impl Default for DataDirPath {
     fn default() -> Self {
-        Self(database_path().expect("Could not determine default database path. Set one manually."))
+        Self(data_dir().expect("Could not determine default data directory. Set one manually."))
     }
 }
 
-impl FromStr for DbPath {
+impl FromStr for DataDirPath {
     type Err = shellexpand::LookupError<VarError>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -53,43 +54,73 @@ impl FromStr for DbPath {
     }
 }
 
-impl AsRef<Path> for DbPath {
+impl AsRef<Path> for DataDirPath {
     fn as_ref(&self) -> &Path {
         self.0.as_path()
     }
 }
 
-/// A wrapper type that either parses a user-given path for the reth config or defaults to an
-/// OS-specific path.
-#[derive(Clone, Debug)]
-pub struct ConfigPath(PathBuf);
+/// The data directory of a single chain, which contains the files of the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainDataDir(PathBuf);
 
-impl Display for ConfigPath {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.display())
+impl ChainDataDir {
+    /// Returns the path to the database folder.
+    pub fn db_path(&self) -> PathBuf {
+        self.0.join("db")
     }
-}
 
-impl Default for ConfigPath {
-    fn default() -> Self {
-        Self(
-            config_dir()
-                .expect("Could not determine default database path. Set one manually.")
-                .join("reth.toml"),
-        )
+    /// Returns the path to the folder of the static files, which store the finalized blocks.
+    pub fn static_files_path(&self) -> PathBuf {
+        self.0.join("static_files")
     }
-}
 
-impl FromStr for ConfigPath {
-    type Err = shellexpand::LookupError<VarError>;
+    /// Returns the path to the configuration file.
+    pub fn config_path(&self) -> PathBuf {
+        self.0.join("reth.toml")
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(parse_path(s)?))
+    /// Returns the path to the JWT secret the Engine API authenticates the consensus layer with.
+    pub fn jwt_secret_path(&self) -> PathBuf {
+        self.0.join("jwt.hex")
+    }
+
+    /// Returns the path to the secret key the p2p identity of the node is derived from.
+    pub fn p2p_secret_path(&self) -> PathBuf {
+        self.0.join("discovery-secret")
+    }
+
+    /// Returns the path to the file of the peers that are known from previous runs.
+    pub fn known_peers_path(&self) -> PathBuf {
+        self.0.join("known-peers.json")
+    }
+}
+
+impl Display for ChainDataDir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.display())
     }
 }
 
-impl AsRef<Path> for ConfigPath {
+impl AsRef<Path> for ChainDataDir {
     fn as_ref(&self) -> &Path {
         self.0.as_path()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_subdirectories() {
+        let datadir: DataDirPath = "/data/reth".parse().unwrap();
+        let mainnet = datadir.chain(Chain::from(1u64));
+        assert_eq!(mainnet.db_path(), Path::new("/data/reth/mainnet/db"));
+        assert_eq!(mainnet.config_path(), Path::new("/data/reth/mainnet/reth.toml"));
+        assert_eq!(mainnet.p2p_secret_path(), Path::new("/data/reth/mainnet/discovery-secret"));
+
+        let custom = datadir.chain(Chain::Id(424242));
+        assert_eq!(custom.static_files_path(), Path::new("/data/reth/424242/static_files"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Export command
+//!
+//! Writes the blocks of the canonical chain to an RLP export file, in the format of `geth export`,
+//! which can be imported by geth or by `reth import`.
+use crate::{
+    config::Config,
+    dirs::DataDirPath,
+    node::init_db,
+    util::{chainspec::chain_spec_value_parser, parse_path},
+};
+use clap::Parser;
+use eyre::eyre;
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber as RpcBlockNumber},
+    BlockNumber, ChainSpec,
+};
+use reth_provider::{db_provider::ProviderImpl, static_file::StaticFileProvider, BlockProvider};
+use reth_rlp::Encodable;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc,
+};
+use tracing::info;
+
+/// The number of blocks after which the progress of the export is logged.
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// Export the blocks of the canonical chain to an RLP file
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The path to the database folder, `<DATA_DIR>/<CHAIN>/db` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    db: Option<PathBuf>,
+
+    /// The path to the folder of the static files, `<DATA_DIR>/<CHAIN>/static_files` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    static_files: Option<PathBuf>,
+
+    /// The path to the configuration file to use, `<DATA_DIR>/<CHAIN>/reth.toml` by default.
+    #[arg(long, value_name = "FILE", value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The chain the blocks belong to.
+    ///
+    /// Possible values are either a built-in chain or the path to a genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - dev
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
+
+    /// The first block to export.
+    #[arg(long, value_name = "NUMBER", default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block to export, the best block that all stages processed by default.
+    #[arg(long, value_name = "NUMBER")]
+    to: Option<BlockNumber>,
+
+    /// The path to the export file, which is overwritten if it exists.
+    #[arg(value_name = "EXPORT_PATH", value_parser = parse_path)]
+    path: PathBuf,
+}
+
+impl Command {
+    /// Execute `export` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        let datadir = self.datadir.chain(self.chain.chain);
+        let config =
+            Config::load_or_create(self.config.clone().unwrap_or_else(|| datadir.config_path()))?;
+
+        let db_path = self.db.clone().unwrap_or_else(|| datadir.db_path());
+        info!("Opening database at {}", db_path.display());
+        let db = Arc::new(init_db(&db_path, &config.db)?);
+        let static_files = Arc::new(StaticFileProvider::open(
+            self.static_files.clone().unwrap_or_else(|| datadir.static_files_path()),
+        )?);
+        let provider = ProviderImpl::new(db).with_static_files(static_files);
+
+        let best = provider.chain_info()?.best_number;
+        let to = self.to.unwrap_or(best);
+        if to > best {
+            return Err(eyre!("Block {to} is beyond the best block {best}"))
+        }
+        if self.from > to {
+            return Err(eyre!("The first block {} is after the last block {to}", self.from))
+        }
+
+        info!(from = self.from, to, "Exporting blocks to {}", self.path.display());
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        let exported = export_blocks(&provider, self.from..=to, &mut file)?;
+        file.flush()?;
+
+        info!(blocks = exported, "Export finished");
+        Ok(())
+    }
+}
+
+/// Writes the RLP encoded blocks of the range back to back, and returns the number of written
+/// blocks.
+fn export_blocks(
+    provider: &impl BlockProvider,
+    range: RangeInclusive<BlockNumber>,
+    writer: &mut impl Write,
+) -> eyre::Result<u64> {
+    let (from, to) = (*range.start(), *range.end());
+    let mut buf = Vec::new();
+    for number in range {
+        let block = provider
+            .block(BlockId::Number(RpcBlockNumber::Number(number.into())))?
+            .ok_or_else(|| eyre!("Block {number} not found"))?;
+        buf.clear();
+        block.encode(&mut buf);
+        writer.write_all(&buf)?;
+
+        let exported = number - from + 1;
+        if exported % PROGRESS_INTERVAL == 0 {
+            let progress = format!("{:.1}%", exported as f64 * 100.0 / (to - from + 1) as f64);
+            info!(block = number, %progress, "Exported blocks");
+        }
+    }
+    Ok(to - from + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+    };
+    use reth_downloaders::file::FileClient;
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_provider::insert_canonical_block;
+
+    #[test]
+    fn export_and_read_blocks() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = random_block_range(0..10, Default::default(), 0..3);
+        db.update(|tx| {
+            for block in &blocks {
+                insert_canonical_block(tx, block, None, true).unwrap();
+            }
+        })
+        .unwrap();
+
+        let mut bytes = Vec::new();
+        let exported = export_blocks(&ProviderImpl::new(db), 2..=9, &mut bytes).unwrap();
+        assert_eq!(exported, 8);
+
+        let client = FileClient::from_bytes(&bytes).unwrap();
+        assert_eq!(client.len(), 8);
+        assert_eq!(client.tip(), Some(&blocks[9].header));
+    }
+}
@@ -0,0 +1,213 @@
+//! Import command
+//!
+//! Imports the blocks of an RLP export file, such as the output of `geth export`, by running the
+//! sync pipeline with the file as the source of the headers and bodies instead of the network.
+//!
+//! The file is imported in chunks, each of which is synced through all stages before the next
+//! chunk is read. An interrupted import continues with the first block that is not fully imported
+//! when it is started again.
+use crate::{
+    config::Config,
+    dirs::DataDirPath,
+    init::init_genesis,
+    node::init_db,
+    util::{chainspec::chain_spec_value_parser, parse_path},
+};
+use clap::Parser;
+use reth_consensus::BeaconConsensus;
+use reth_db::{
+    database::Database,
+    mdbx::{Env, WriteMap},
+    transaction::DbTx,
+};
+use reth_downloaders::{
+    bodies,
+    file::{ChunkedFileReader, FileClient, DEFAULT_CHUNK_LEN},
+    headers,
+};
+use reth_interfaces::{
+    consensus::{Consensus, ForkchoiceState},
+    p2p::headers::client::StatusUpdater,
+};
+use reth_primitives::{BlockNumber, ChainSpec, Head};
+use reth_stages::{
+    metrics::HeaderMetrics,
+    stages::{
+        bodies::BodyStage,
+        execution::ExecutionStage,
+        headers::HeaderStage,
+        merkle::MerkleStage,
+        sender_recovery::SenderRecoveryStage,
+        tx_lookup::{TransactionLookupStage, TRANSACTION_LOOKUP},
+    },
+    Pipeline,
+};
+use std::{path::PathBuf, sync::Arc};
+use tracing::{debug, info};
+
+/// Import the blocks of an RLP export file
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The path to the database folder, `<DATA_DIR>/<CHAIN>/db` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    db: Option<PathBuf>,
+
+    /// The path to the configuration file to use, `<DATA_DIR>/<CHAIN>/reth.toml` by default.
+    #[arg(long, value_name = "FILE", value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The chain the blocks belong to.
+    ///
+    /// Possible values are either a built-in chain or the path to a genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - dev
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
+
+    /// The path to the export file.
+    ///
+    /// The file contains RLP encoded blocks in ascending order, which must continue the chain in
+    /// the database.
+    #[arg(value_name = "IMPORT_PATH", verbatim_doc_comment)]
+    path: PathBuf,
+
+    /// The size in bytes of the chunks the file is read and imported in.
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_CHUNK_LEN)]
+    chunk_len: usize,
+}
+
+impl Command {
+    /// Execute `import` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        let datadir = self.datadir.chain(self.chain.chain);
+        let config =
+            Config::load_or_create(self.config.clone().unwrap_or_else(|| datadir.config_path()))?;
+
+        let db_path = self.db.clone().unwrap_or_else(|| datadir.db_path());
+        info!("Opening database at {}", db_path.display());
+        let db = Arc::new(init_db(&db_path, &config.db)?);
+        init_genesis(db.clone(), &self.chain)?;
+
+        let consensus = Arc::new(BeaconConsensus::new(self.chain.clone()));
+        let mut reader = ChunkedFileReader::new(&self.path, self.chunk_len)?;
+        info!("Importing blocks from {}", self.path.display());
+
+        while let Some(client) = reader.next_chunk()? {
+            let client = Arc::new(client);
+            let tip = client.tip().expect("chunks are not empty").clone();
+            let first = tip.number + 1 - client.len() as u64;
+            let (decoded, file_len) = reader.progress();
+            let progress = format!("{:.1}%", decoded as f64 * 100.0 / file_len.max(1) as f64);
+
+            // the last stage only progresses over blocks that all stages processed
+            let imported = imported_block(&db)?;
+            if imported.map_or(false, |imported| imported >= tip.number) {
+                info!(first, last = tip.number, %progress, "Skipping imported blocks");
+                continue
+            }
+
+            info!(first, last = tip.number, %progress, "Importing blocks");
+            let mut pipeline = self.build_pipeline(&config, consensus.clone(), client, tip.number);
+            debug!("Tip set to the last block of the chunk: {}", tip.hash());
+            consensus.notify_fork_choice_state(ForkchoiceState {
+                head_block_hash: tip.hash(),
+                safe_block_hash: tip.hash(),
+                finalized_block_hash: tip.hash(),
+            })?;
+            pipeline.run(db.clone()).await?;
+        }
+
+        info!("Import finished");
+        Ok(())
+    }
+
+    /// Builds the pipeline that imports the blocks of the chunk up to its last block.
+    fn build_pipeline(
+        &self,
+        config: &Config,
+        consensus: Arc<BeaconConsensus>,
+        client: Arc<FileClient>,
+        tip: BlockNumber,
+    ) -> Pipeline<Env<WriteMap>> {
+        Pipeline::new()
+            .push(HeaderStage {
+                downloader: headers::reverse::ReverseHeadersDownloaderBuilder::default()
+                    .request_limit(config.stages.headers.downloader_batch_size)
+                    .concurrent_requests(config.stages.headers.downloader_concurrency)
+                    .retries(config.stages.headers.downloader_retries)
+                    .build(consensus.clone(), client.clone()),
+                consensus: consensus.clone(),
+                client: client.clone(),
+                network_handle: NoopStatusUpdater,
+                commit_threshold: config.stages.headers.commit_threshold,
+                metrics: HeaderMetrics::default(),
+            })
+            .push(BodyStage {
+                downloader: Arc::new(
+                    bodies::buffered::BufferedDownloader::new(client, consensus.clone())
+                        .with_batch_size(config.stages.bodies.downloader_batch_size)
+                        .with_retries(config.stages.bodies.downloader_retries)
+                        .with_concurrency(config.stages.bodies.downloader_concurrency)
+                        .with_max_buffered_bytes(
+                            config.stages.bodies.downloader_max_buffered_bytes,
+                        ),
+                ),
+                consensus: consensus.clone(),
+                commit_threshold: config.stages.bodies.commit_threshold,
+            })
+            .push(SenderRecoveryStage {
+                batch_size: config.stages.sender_recovery.batch_size,
+                commit_threshold: config.stages.sender_recovery.commit_threshold,
+            })
+            .push(
+                ExecutionStage::new(self.chain.clone())
+                    .with_thresholds(config.stages.execution.clone().into()),
+            )
+            .push(MerkleStage)
+            .push(TransactionLookupStage {
+                commit_threshold: config.stages.transaction_lookup.commit_threshold,
+                prune: config.stages.transaction_lookup.prune,
+            })
+            // The pipeline stops once all blocks of the chunk are imported
+            .set_max_block(Some(tip))
+            .set_tip_receiver(consensus.fork_choice_state())
+    }
+}
+
+/// Returns the last block that all stages imported, which is the progress of the last stage.
+fn imported_block<DB: Database>(db: &DB) -> eyre::Result<Option<BlockNumber>> {
+    let tx = db.tx()?;
+    let progress = TRANSACTION_LOOKUP.get_progress(&tx)?;
+    tx.commit()?;
+    Ok(progress)
+}
+
+/// There are no peers to announce the imported blocks to.
+#[derive(Debug, Clone, Copy)]
+struct NoopStatusUpdater;
+
+impl StatusUpdater for NoopStatusUpdater {
+    fn update_status(&self, _head: Head) {}
+}
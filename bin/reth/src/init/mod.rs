@@ -0,0 +1,154 @@
+//! Init command
+//!
+//! Initializes the database with the genesis block and state of a chain, e.g. of a custom chain
+//! from a geth genesis file.
+use crate::{
+    config::Config,
+    dirs::DataDirPath,
+    node::init_db,
+    util::{chainspec::chain_spec_value_parser, parse_path},
+};
+use clap::Parser;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{keccak256, ChainSpec, StorageEntry, H256};
+use reth_stages::stages::STAGE_IDS;
+use std::{path::PathBuf, sync::Arc};
+use tracing::{debug, info};
+
+/// Initialize the database with the genesis block
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The path to the database folder, `<DATA_DIR>/<CHAIN>/db` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    db: Option<PathBuf>,
+
+    /// The path to the configuration file to use, `<DATA_DIR>/<CHAIN>/reth.toml` by default.
+    #[arg(long, value_name = "FILE", value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The chain to initialize the database with.
+    ///
+    /// Possible values are either a built-in chain or the path to a geth genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - dev
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
+}
+
+impl Command {
+    /// Execute `init` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        let datadir = self.datadir.chain(self.chain.chain);
+        let config =
+            Config::load_or_create(self.config.clone().unwrap_or_else(|| datadir.config_path()))?;
+
+        let db_path = self.db.clone().unwrap_or_else(|| datadir.db_path());
+        info!("Opening database at {}", db_path.display());
+        let db = Arc::new(init_db(&db_path, &config.db)?);
+
+        let hash = init_genesis(db, &self.chain)?;
+        info!(chain = %self.chain.chain, genesis = ?hash, "Database initialized");
+        Ok(())
+    }
+}
+
+/// Errors when initializing the database with the genesis of a chain.
+#[derive(Debug, thiserror::Error)]
+pub enum InitDatabaseError {
+    /// The database contains the genesis of another chain.
+    #[error("Genesis hash mismatch: the database contains {database:?}, the chain spec expects {chain_spec:?}.")]
+    GenesisHashMismatch {
+        /// The genesis hash of the chain spec.
+        chain_spec: H256,
+        /// The genesis hash in the database.
+        database: H256,
+    },
+    /// Failed to access the database.
+    #[error(transparent)]
+    Database(#[from] reth_db::Error),
+}
+
+/// Write the genesis block and state if they have not already been written, and mark the genesis
+/// block as processed by all stages.
+///
+/// Returns an error if the database already contains the genesis of another chain.
+pub(crate) fn init_genesis<DB: Database>(
+    db: Arc<DB>,
+    chain_spec: &ChainSpec,
+) -> Result<H256, InitDatabaseError> {
+    let tx = db.tx_mut()?;
+    if let Some((_, hash)) = tx.cursor::<tables::CanonicalHeaders>()?.first()? {
+        if hash != chain_spec.genesis_hash() {
+            return Err(InitDatabaseError::GenesisHashMismatch {
+                chain_spec: chain_spec.genesis_hash(),
+                database: hash,
+            })
+        }
+        debug!("Genesis already written, skipping.");
+        return Ok(hash)
+    }
+    debug!("Writing genesis block.");
+
+    // Insert account state, code and storage. The hashed state is written as well, so the state
+    // root of the blocks sealed on top of the genesis can be computed before the merkle stage ran.
+    for (address, account) in &chain_spec.genesis().alloc {
+        let db_account = account.account();
+        if let (Some(code), Some(code_hash)) = (&account.code, db_account.bytecode_hash) {
+            tx.put::<tables::Bytecodes>(code_hash, code.to_vec())?;
+        }
+        tx.put::<tables::PlainAccountState>(*address, db_account)?;
+        let hashed_address = keccak256(address);
+        tx.put::<tables::HashedAccount>(hashed_address, db_account)?;
+
+        for (key, value) in account.storage_slots().filter(|(_, value)| !value.is_zero()) {
+            tx.put::<tables::PlainStorageState>(*address, StorageEntry { key, value })?;
+            let hashed_entry = StorageEntry { key: keccak256(key), value };
+            tx.put::<tables::HashedStorage>(hashed_address, hashed_entry)?;
+        }
+    }
+
+    // Insert header
+    let header = chain_spec.genesis_header();
+    let hash = header.hash_slow();
+    tx.put::<tables::CanonicalHeaders>(0, hash)?;
+    tx.put::<tables::HeaderNumbers>(hash, 0)?;
+    tx.put::<tables::BlockBodies>((0, hash).into(), Default::default())?;
+    tx.put::<tables::BlockTransitionIndex>((0, hash).into(), 0)?;
+    tx.put::<tables::HeaderTD>((0, hash).into(), header.difficulty.into())?;
+    tx.put::<tables::Headers>((0, hash).into(), header)?;
+
+    // Every stage starts syncing after the genesis block
+    for stage_id in STAGE_IDS {
+        stage_id.save_progress(&tx, 0)?;
+    }
+
+    tx.commit()?;
+    Ok(hash)
+}
@@ -8,9 +8,17 @@
 
 pub mod cli;
 pub mod config;
+pub mod config_cmd;
 pub mod db;
 pub mod dirs;
+pub mod export;
+pub mod import;
+pub mod init;
 pub mod node;
+pub mod p2p;
 pub mod prometheus_exporter;
+pub mod stage;
 pub mod test_eth_chain;
+pub mod test_vectors;
 pub mod util;
+pub mod version;
@@ -2,65 +2,139 @@
 //!
 //! Starts the client
 use crate::{
-    config::Config,
-    dirs::{ConfigPath, DbPath},
+    config::{Config, DatabaseConfig},
+    dirs::DataDirPath,
+    init::init_genesis,
     prometheus_exporter,
-    util::chainspec::{chain_spec_value_parser, ChainSpecification, Genesis},
+    util::{
+        chainspec::chain_spec_value_parser,
+        parse_path,
+        secret_key::{get_or_create_secret_key, read_secret_key},
+    },
+    version::{CLIENT_VERSION, SHORT_VERSION},
+};
+use clap::Parser;
+use futures::StreamExt;
+use reth_consensus::{
+    auto_seal::{AutoSealMiner, MiningMode},
+    clique::{Clique, CliqueSigner},
+    engine::{BeaconConsensusEngine, EngineMessage},
+    ethash::Ethash,
+    verification::calculate_next_block_base_fee,
+    BeaconConsensus,
 };
-use clap::{crate_version, Parser};
-use reth_consensus::BeaconConsensus;
 use reth_db::{
-    cursor::DbCursorRO,
     database::Database,
-    mdbx::{Env, WriteMap},
-    tables,
-    transaction::{DbTx, DbTxMut},
+    mdbx::{Env, EnvKind, WriteMap},
+    version::init_db_version_file,
 };
 use reth_downloaders::{bodies, headers};
-use reth_executor::Config as ExecutorConfig;
-use reth_interfaces::consensus::ForkchoiceState;
+use reth_eth_wire::HelloMessage;
+use reth_executor::metrics::ExecutionMetrics;
+use reth_interfaces::consensus::{Consensus, ForkchoiceState};
 use reth_network::{
-    config::{mainnet_nodes, rng_secret_key},
-    error::NetworkError,
+    config::mainnet_nodes, error::NetworkError, transactions::TransactionsHandle, FetchMetrics,
     NetworkConfig, NetworkHandle, NetworkManager,
 };
-use reth_primitives::{Account, Header, H256};
-use reth_provider::{db_provider::ProviderImpl, BlockProvider, HeaderProvider};
+use reth_primitives::{rpc::BlockId, BlockNumber, ChainSpec, Head, NodeRecord, H256, U256};
+use reth_provider::{
+    db_provider::ProviderImpl,
+    read_fork_choice_state,
+    static_file::{StaticFileProducer, StaticFileProvider},
+    BlockProvider, CanonicalHead, ChainInfoTracker, HeaderProvider, StateCache, StateCacheMetrics,
+};
+use reth_rpc::{EngineApi, LogFilterReloader};
+use reth_rpc_builder::{
+    start_engine_api, JwtSecret, RpcModuleBuilder, RpcModuleSelection, RpcServerConfig,
+    TransportConfig, TransportRpcModuleConfig,
+};
 use reth_stages::{
     metrics::HeaderMetrics,
+    prune::Pruner,
     stages::{
-        bodies::BodyStage, execution::ExecutionStage, headers::HeaderStage,
-        sender_recovery::SenderRecoveryStage,
+        bodies::BodyStage, execution::ExecutionStage, headers::HeaderStage, merkle::MerkleStage,
+        sender_recovery::SenderRecoveryStage, tx_lookup::TransactionLookupStage,
     },
+    tree::BlockchainTree,
+    Pipeline,
+};
+use reth_tasks::{shutdown::Shutdown, TaskExecutor, TaskManager};
+use reth_transaction_pool::{
+    metrics::TxPoolMetrics, EthTransactionValidator, GasPriceOrdering, OnNewBlockEvent, Pool,
+    StateDiff, TransactionPool,
 };
-use std::{net::SocketAddr, path::Path, sync::Arc};
-use tracing::{debug, info};
+use secp256k1::SecretKey;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, oneshot, watch},
+};
+use tracing::{debug, error, info, warn};
+
+mod status;
 
 /// Start the client
 #[derive(Debug, Parser)]
 pub struct Command {
-    /// The path to the database folder.
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
     ///
     /// Defaults to the OS-specific data directory:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: DbPath,
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
 
-    /// The path to the configuration file to use.
-    #[arg(long, value_name = "FILE", verbatim_doc_comment, default_value_t)]
-    config: ConfigPath,
+    /// The path to the database folder, `<DATA_DIR>/<CHAIN>/db` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    db: Option<PathBuf>,
+
+    /// The path to the folder of the static files, which store the finalized blocks,
+    /// `<DATA_DIR>/<CHAIN>/static_files` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    static_files: Option<PathBuf>,
+
+    /// The path to the configuration file to use, `<DATA_DIR>/<CHAIN>/reth.toml` by default.
+    ///
+    /// The file is created with the default configuration if it doesn't exist. The flags take
+    /// precedence over the file.
+    #[arg(long, value_name = "FILE", value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The path to the file of the hex encoded secret key the p2p identity of the node is derived
+    /// from.
+    ///
+    /// If not set, the key is read from `<DATA_DIR>/<CHAIN>/discovery-secret`, where a new key is
+    /// generated on the first start.
+    #[arg(long = "p2p-secret-key", value_name = "PATH", value_parser = parse_path)]
+    p2p_secret_key: Option<PathBuf>,
+
+    /// The path to the file of the hex encoded JWT secret the consensus layer authenticates its
+    /// Engine API requests with.
+    ///
+    /// If not set, the secret is read from `<DATA_DIR>/<CHAIN>/jwt.hex`. A new secret is generated
+    /// if the file doesn't exist.
+    #[arg(long = "authrpc.jwtsecret", value_name = "PATH", value_parser = parse_path)]
+    auth_jwtsecret: Option<PathBuf>,
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain or the path to a genesis file.
     ///
     /// Built-in chains:
     /// - mainnet
     /// - goerli
     /// - sepolia
+    /// - dev
     #[arg(
         long,
         value_name = "CHAIN_OR_PATH",
@@ -68,7 +142,38 @@ pub struct Command {
         default_value = "mainnet",
         value_parser = chain_spec_value_parser
     )]
-    chain: ChainSpecification,
+    chain: ChainSpec,
+
+    /// Verify the Ethash seal of pre-merge headers.
+    ///
+    /// Only applies to chains that used Ethash before the merge, e.g. mainnet and sepolia.
+    #[arg(long)]
+    ethash: bool,
+
+    /// Run a local development chain that produces its own blocks.
+    ///
+    /// Uses the built-in `dev` chain with prefunded accounts and seals a new block whenever
+    /// transactions are ready, or every `--dev.block-time` seconds. The transactions are submitted
+    /// through the RPC. The node has no boot nodes, so it doesn't connect to the network on its
+    /// own, and the Engine API is not served.
+    #[arg(long, conflicts_with = "chain")]
+    dev: bool,
+
+    /// Seal a new block every given number of seconds in dev mode.
+    #[arg(
+        long = "dev.block-time",
+        value_name = "SECONDS",
+        requires = "dev",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    block_time: Option<u64>,
+
+    /// Seal the blocks of a clique chain with this secret key, given as hex.
+    ///
+    /// The signer must be authorized by the chain. The node has no boot nodes, so it doesn't
+    /// connect to the other signers on its own.
+    #[arg(long = "clique.signer-key", value_name = "HEX", conflicts_with = "dev")]
+    clique_signer_key: Option<SecretKey>,
 
     /// Enable Prometheus metrics.
     ///
@@ -76,166 +181,715 @@ pub struct Command {
     #[clap(long, value_name = "SOCKET")]
     metrics: Option<SocketAddr>,
 
+    /// The number of seconds between the status lines of the node, which summarize the sync
+    /// progress, the peers and the transaction pool. `0` disables the status lines.
+    #[arg(long = "status-interval", value_name = "SECONDS", default_value_t = 30)]
+    status_interval: u64,
+
+    /// The number of seconds the node waits for its tasks to shut down after SIGINT or SIGTERM.
+    #[arg(long = "shutdown-timeout", value_name = "SECONDS", default_value_t = 30)]
+    shutdown_timeout: u64,
+
+    /// The maximum number of outbound connections to peers.
+    ///
+    /// Overrides the `peers.max_outbound` value of the configuration file.
+    #[arg(long = "max-outbound-peers", value_name = "COUNT")]
+    max_outbound_peers: Option<usize>,
+
+    /// The maximum number of inbound connections from peers.
+    ///
+    /// Overrides the `peers.max_inbound` value of the configuration file.
+    #[arg(long = "max-inbound-peers", value_name = "COUNT")]
+    max_inbound_peers: Option<usize>,
+
+    /// Serve the JSON-RPC API over HTTP.
+    ///
+    /// Also enabled by the `rpc.http` value of the configuration file.
+    #[arg(long)]
+    http: bool,
+
+    /// The address of the HTTP server, `127.0.0.1:8545` by default.
+    ///
+    /// Overrides the `rpc.http_addr` value of the configuration file.
+    #[arg(long = "http.addr", value_name = "SOCKET")]
+    http_addr: Option<SocketAddr>,
+
+    /// The modules served over HTTP, as a comma separated list like `eth,net,web3`, or `all`.
+    ///
+    /// Overrides the `rpc.http_api` value of the configuration file. Defaults to `eth,net,web3`.
+    #[arg(long = "http.api", value_name = "MODULES")]
+    http_api: Option<RpcModuleSelection>,
+
+    /// The comma separated list of domains from which the HTTP server accepts cross-origin
+    /// requests, or `*` for any domain.
+    ///
+    /// Overrides the `rpc.http_corsdomain` value of the configuration file.
+    #[arg(long = "http.corsdomain", value_name = "DOMAINS")]
+    http_corsdomain: Option<String>,
+
+    /// Serve the JSON-RPC API over WS.
+    ///
+    /// Also enabled by the `rpc.ws` value of the configuration file.
+    #[arg(long)]
+    ws: bool,
+
+    /// The address of the WS server, `127.0.0.1:8546` by default.
+    ///
+    /// Overrides the `rpc.ws_addr` value of the configuration file.
+    #[arg(long = "ws.addr", value_name = "SOCKET")]
+    ws_addr: Option<SocketAddr>,
+
+    /// The modules served over WS, as a comma separated list like `eth,net,web3`, or `all`.
+    ///
+    /// Overrides the `rpc.ws_api` value of the configuration file. Defaults to `eth,net,web3`.
+    #[arg(long = "ws.api", value_name = "MODULES")]
+    ws_api: Option<RpcModuleSelection>,
+
+    /// The comma separated list of origins from which the WS server accepts cross-origin
+    /// requests, or `*` for any origin.
+    ///
+    /// Overrides the `rpc.ws_origins` value of the configuration file.
+    #[arg(long = "ws.origins", value_name = "ORIGINS")]
+    ws_origins: Option<String>,
+
+    /// The address of the Engine API server the consensus layer drives the node with,
+    /// `127.0.0.1:8551` by default.
+    ///
+    /// Requests must be authenticated with the JWT secret, see `--authrpc.jwtsecret`. Overrides
+    /// the `rpc.authrpc_addr` value of the configuration file.
+    #[arg(long = "authrpc.addr", value_name = "SOCKET")]
+    authrpc_addr: Option<SocketAddr>,
+
+    /// The maximum number of blocks a log query can span, larger queries are rejected.
+    ///
+    /// Overrides the `rpc.max_blocks_per_filter` value of the configuration file.
+    #[arg(long = "rpc.max-blocks-per-filter", value_name = "COUNT")]
+    rpc_max_blocks_per_filter: Option<u64>,
+
+    /// The maximum number of logs a log query can return, queries that match more logs are
+    /// rejected.
+    ///
+    /// Overrides the `rpc.max_logs_per_response` value of the configuration file.
+    #[arg(long = "rpc.max-logs-per-response", value_name = "COUNT")]
+    rpc_max_logs_per_response: Option<usize>,
+
+    /// The maximum number of concurrent connections of the HTTP and WS servers.
+    ///
+    /// Overrides the `rpc.max_connections` value of the configuration file.
+    #[arg(long = "rpc.max-connections", value_name = "COUNT")]
+    rpc_max_connections: Option<u32>,
+
+    /// The maximum size of a request body in bytes, larger requests are rejected.
+    ///
+    /// Overrides the `rpc.max_request_size` value of the configuration file.
+    #[arg(long = "rpc.max-request-size", value_name = "BYTES")]
+    rpc_max_request_size: Option<u32>,
+
+    /// The maximum size of a response body in bytes, calls with larger responses fail.
+    ///
+    /// Overrides the `rpc.max_response_size` value of the configuration file.
+    #[arg(long = "rpc.max-response-size", value_name = "BYTES")]
+    rpc_max_response_size: Option<u32>,
+
+    /// The maximum number of calls in a batch request, larger batches are rejected.
+    ///
+    /// Overrides the `rpc.max_batch_len` value of the configuration file.
+    #[arg(long = "rpc.max-batch-len", value_name = "COUNT")]
+    rpc_max_batch_len: Option<u32>,
+
     /// Set the chain tip manually for testing purposes.
     ///
+    /// Overrides the `debug.tip` value of the configuration file.
+    ///
     /// NOTE: This is a temporary flag
     #[arg(long = "debug.tip")]
     tip: Option<H256>,
+
+    /// Stop the pipeline once this block is reached.
+    ///
+    /// Overrides the `debug.max_block` value of the configuration file.
+    #[arg(long = "debug.max-block", value_name = "NUMBER")]
+    max_block: Option<BlockNumber>,
+
+    /// Dump the payloads that fail validation or execution to this directory, e.g. `bad_blocks`.
+    ///
+    /// Every dump contains the payload, the error and, if the block can be executed, the receipts
+    /// and the state changes of executing it locally.
+    #[arg(long = "debug.bad-blocks-dir", value_name = "PATH")]
+    bad_blocks_dir: Option<PathBuf>,
 }
 
 impl Command {
     /// Execute `node` command
-    // TODO: RPC
-    pub async fn execute(&self) -> eyre::Result<()> {
-        let config: Config = confy::load_path(&self.config).unwrap_or_default();
-        info!("reth {} starting", crate_version!());
+    ///
+    /// The log filter is replaced on `admin_setLogFilter` requests.
+    pub async fn execute(&self, log_filter: Arc<dyn LogFilterReloader>) -> eyre::Result<()> {
+        let chain = if self.dev { chain_spec_value_parser("dev")? } else { self.chain.clone() };
+        let datadir = self.datadir.chain(chain.chain);
+        let config =
+            self.load_config(self.config.clone().unwrap_or_else(|| datadir.config_path()))?;
+        info!("reth {} starting", SHORT_VERSION);
 
-        info!("Opening database at {}", &self.db);
-        let db = Arc::new(init_db(&self.db)?);
+        let db_path = self.db.clone().unwrap_or_else(|| datadir.db_path());
+        info!("Opening database at {}", db_path.display());
+        let db = Arc::new(init_db(&db_path, &config.db)?);
         info!("Database open");
 
+        if config.db.reader_check_interval > 0 {
+            tokio::spawn(check_readers(db.clone(), config.db.clone()));
+        }
+
+        let static_files = Arc::new(StaticFileProvider::open(
+            self.static_files.clone().unwrap_or_else(|| datadir.static_files_path()),
+        )?);
+
         if let Some(listen_addr) = self.metrics {
             info!("Starting metrics endpoint at {}", listen_addr);
-            prometheus_exporter::initialize(listen_addr)?;
+            prometheus_exporter::initialize(
+                listen_addr,
+                vec![prometheus_exporter::db_metrics_hook(db.clone())],
+            )?;
             HeaderMetrics::describe();
+            FetchMetrics::describe();
+            ExecutionMetrics::describe();
+            StateCacheMetrics::describe();
+            TxPoolMetrics::describe();
+        }
+
+        let mut consensus = BeaconConsensus::new(chain.clone());
+        if self.ethash {
+            consensus = consensus.with_ethash(Ethash::default());
+        }
+        if let Some(clique) = &chain.clique {
+            let client = Arc::new(ProviderImpl::new(db.clone()));
+            consensus = consensus.with_clique(Arc::new(Clique::new(clique.clone(), client)));
+        } else if self.clique_signer_key.is_some() {
+            eyre::bail!("The chain is not sealed by clique")
         }
+        let consensus = Arc::new(consensus);
+        init_genesis(db.clone(), &chain)?;
+        // the safe and finalized blocks of the previous run are known before the consensus layer
+        // announces them again
+        consensus.notify_fork_choice_state(read_fork_choice_state(&db.tx()?)?)?;
 
-        let chain_id = self.chain.consensus.chain_id;
-        let consensus = Arc::new(BeaconConsensus::new(self.chain.consensus.clone()));
-        let genesis_hash = init_genesis(db.clone(), self.chain.genesis.clone())?;
+        // dev and clique signer nodes seal their own blocks instead of syncing them
+        let seal = self.dev || self.clique_signer_key.is_some();
+        let mut tasks = TaskManager::new(Handle::current());
+        let executor = tasks.executor();
+        let chain_info = ChainInfoTracker::default();
+        let client = Arc::new(
+            ProviderImpl::new(db.clone())
+                .with_static_files(static_files.clone())
+                .with_chain_info(chain_info.clone()),
+        );
+
+        let pool = Pool::new(
+            Arc::new(EthTransactionValidator::new(client.clone(), chain.clone())),
+            Arc::new(GasPriceOrdering::default()),
+            Default::default(),
+        );
+        executor.spawn_critical(
+            "txpool maintenance",
+            maintain_pool(client.clone(), pool.clone(), chain_info.subscribe_canonical_head()),
+        );
+
+        let secret_key = match &self.p2p_secret_key {
+            Some(path) => read_secret_key(path)?,
+            None => get_or_create_secret_key(&datadir.p2p_secret_path())?,
+        };
 
         info!("Connecting to p2p");
         // ANCHOR: snippet-execute
-        let network = start_network(network_config(db.clone(), chain_id, genesis_hash)).await?;
-
-        // TODO: Are most of these Arcs unnecessary? For example, fetch client is completely
-        // cloneable on its own
-        // TODO: Remove magic numbers
-        let fetch_client = Arc::new(network.fetch_client().await?);
-        let mut pipeline = reth_stages::Pipeline::new()
-            .push(HeaderStage {
-                downloader: headers::linear::LinearDownloadBuilder::default()
-                    .batch_size(config.stages.headers.downloader_batch_size)
-                    .retries(config.stages.headers.downloader_retries)
-                    .build(consensus.clone(), fetch_client.clone()),
-                consensus: consensus.clone(),
-                client: fetch_client.clone(),
-                network_handle: network.clone(),
-                commit_threshold: config.stages.headers.commit_threshold,
-                metrics: HeaderMetrics::default(),
-            })
-            .push(BodyStage {
-                downloader: Arc::new(
-                    bodies::concurrent::ConcurrentDownloader::new(
-                        fetch_client.clone(),
-                        consensus.clone(),
-                    )
-                    .with_batch_size(config.stages.bodies.downloader_batch_size)
-                    .with_retries(config.stages.bodies.downloader_retries)
-                    .with_concurrency(config.stages.bodies.downloader_concurrency),
-                ),
-                consensus: consensus.clone(),
-                commit_threshold: config.stages.bodies.commit_threshold,
-            })
-            .push(SenderRecoveryStage {
-                batch_size: config.stages.sender_recovery.batch_size,
-                commit_threshold: config.stages.sender_recovery.commit_threshold,
-            })
-            .push(ExecutionStage { config: ExecutorConfig::new_ethereum() });
-
-        if let Some(tip) = self.tip {
+        let (network, transactions) = start_network(
+            network_config(
+                &config,
+                secret_key,
+                db.clone(),
+                static_files.clone(),
+                chain_info.clone(),
+                chain.clone(),
+                lookup_head(db.clone())?,
+                if seal { Vec::new() } else { mainnet_nodes() },
+            ),
+            pool.clone(),
+            &executor,
+        )
+        .await?;
+        executor
+            .spawn(update_network_status(chain_info.subscribe_canonical_head(), network.clone()));
+
+        let pipeline = if seal {
+            sealer_pipeline(&config, &chain)
+        } else {
+            sync_pipeline(&config, &chain, consensus.clone(), &network)
+                .await?
+                .set_pruner(
+                    Pruner::new(config.prune.clone().into())
+                        .with_static_files(static_files.clone()),
+                )
+                .set_static_file_producer(StaticFileProducer::new(
+                    static_files,
+                    consensus.fork_choice_state(),
+                ))
+        }
+        .set_max_block(config.debug.max_block)
+        .set_tip_receiver(consensus.fork_choice_state())
+        .set_chain_info_tracker(chain_info.clone());
+
+        // ANCHOR_END: snippet-execute
+
+        if self.status_interval > 0 {
+            executor.spawn(status::report_status(
+                client.clone(),
+                network.clone(),
+                pool.clone(),
+                chain_info,
+                pipeline.events(),
+                Duration::from_secs(self.status_interval),
+            ));
+        }
+
+        let clique = consensus.clique().cloned();
+        let tree = BlockchainTree::new(db.clone(), consensus.clone(), chain.clone());
+        let (engine_tx, engine_rx) = mpsc::unbounded_channel();
+        let mut engine = BeaconConsensusEngine::new(
+            chain.clone(),
+            db.clone(),
+            consensus,
+            pipeline,
+            tree,
+            engine_rx,
+        )
+        .with_payload_transactions(pool.clone());
+        if let Some(dir) = &self.bad_blocks_dir {
+            engine = engine.with_bad_blocks_dir(dir.clone());
+        }
+        executor.spawn_critical_with_shutdown_signal("consensus engine", |shutdown| {
+            engine.with_shutdown(shutdown)
+        });
+        if seal {
+            self.spawn_miner(
+                db.clone(),
+                clique,
+                pool.clone(),
+                &chain,
+                engine_tx.clone(),
+                &executor,
+            );
+        }
+
+        if let Some(tip) = config.debug.tip {
             debug!("Tip manually set: {}", tip);
-            consensus.notify_fork_choice_state(ForkchoiceState {
+            let state = ForkchoiceState {
                 head_block_hash: tip,
                 safe_block_hash: tip,
                 finalized_block_hash: tip,
-            })?;
+            };
+            // the engine syncs to the tip as if the consensus layer announced it
+            let (tx, _rx) = oneshot::channel();
+            let _ = engine_tx.send(EngineMessage::ForkchoiceUpdated(state, None, tx));
         }
 
-        // Run pipeline
-        info!("Starting pipeline");
-        pipeline.run(db.clone()).await?;
-        // ANCHOR_END: snippet-execute
+        let rpc = &config.rpc;
+        let mut modules = TransportRpcModuleConfig::default();
+        let mut servers = RpcServerConfig::default();
+        if rpc.http {
+            modules = modules.with_http(parse_modules(rpc.http_api.as_deref())?);
+            let mut http = TransportConfig::new(rpc.http_addr).with_limits(rpc.into());
+            if let Some(domains) = &rpc.http_corsdomain {
+                http = http.with_cors(domains);
+            }
+            servers = servers.with_http(http);
+        }
+        if rpc.ws {
+            modules = modules.with_ws(parse_modules(rpc.ws_api.as_deref())?);
+            let mut ws = TransportConfig::new(rpc.ws_addr).with_limits(rpc.into());
+            if let Some(origins) = &rpc.ws_origins {
+                ws = ws.with_cors(origins);
+            }
+            servers = servers.with_ws(ws);
+        }
+        let modules = RpcModuleBuilder::new(client, pool, network, transactions)
+            .with_chain_spec(chain)
+            .with_filter_config(rpc.into())
+            .with_client_version(CLIENT_VERSION)
+            .with_log_filter(log_filter)
+            .build(modules)?;
+        let rpc_server = servers.start(modules).await?;
+        // the blocks of a sealing node are not driven by a consensus layer
+        let engine_server = if seal {
+            None
+        } else {
+            let jwt_secret_path =
+                self.auth_jwtsecret.clone().unwrap_or_else(|| datadir.jwt_secret_path());
+            let jwt_secret = JwtSecret::load_or_generate(&jwt_secret_path)?;
+            let api = EngineApi::new(engine_tx);
+            Some(start_engine_api(TransportConfig::new(rpc.authrpc_addr), jwt_secret, api).await?)
+        };
+        executor.spawn_critical_with_shutdown_signal("rpc servers", |shutdown| async move {
+            shutdown.await;
+            for server in std::iter::once(rpc_server).chain(engine_server) {
+                if let Err(err) = server.stop_and_wait().await {
+                    warn!(%err, "Failed to stop rpc server");
+                }
+            }
+        });
+
+        info!("Node started");
+        tokio::select! {
+            Some(task) = tasks.next() => {
+                eyre::bail!("Critical task `{task}` stopped")
+            }
+            signal = shutdown_signal() => {
+                info!("Received {}, shutting down", signal?);
+            }
+        }
+
+        // The tasks stop in the reverse order they were spawned: the rpc servers, the engine with
+        // the pipeline and then the network. The database is only closed once no stage writes to
+        // it anymore, so it is never left in the middle of a commit.
+        if !tasks.graceful_shutdown(Duration::from_secs(self.shutdown_timeout)).await {
+            eyre::bail!("The node did not shut down within {} seconds", self.shutdown_timeout)
+        }
+        drop(db);
+        info!("Shutdown complete");
 
-        info!("Finishing up");
         Ok(())
     }
+
+    /// Loads the configuration file and applies the flags that override its values.
+    fn load_config(&self, path: PathBuf) -> eyre::Result<Config> {
+        let mut config = Config::load_or_create(path)?;
+
+        config.peers.max_outbound = self.max_outbound_peers.unwrap_or(config.peers.max_outbound);
+        config.peers.max_inbound = self.max_inbound_peers.unwrap_or(config.peers.max_inbound);
+
+        let rpc = &mut config.rpc;
+        rpc.http |= self.http;
+        rpc.http_addr = self.http_addr.unwrap_or(rpc.http_addr);
+        if let Some(api) = &self.http_api {
+            rpc.http_api = Some(api.to_string());
+        }
+        if let Some(domains) = &self.http_corsdomain {
+            rpc.http_corsdomain = Some(domains.clone());
+        }
+        rpc.ws |= self.ws;
+        rpc.ws_addr = self.ws_addr.unwrap_or(rpc.ws_addr);
+        if let Some(api) = &self.ws_api {
+            rpc.ws_api = Some(api.to_string());
+        }
+        if let Some(origins) = &self.ws_origins {
+            rpc.ws_origins = Some(origins.clone());
+        }
+        rpc.authrpc_addr = self.authrpc_addr.unwrap_or(rpc.authrpc_addr);
+        rpc.max_blocks_per_filter =
+            self.rpc_max_blocks_per_filter.unwrap_or(rpc.max_blocks_per_filter);
+        rpc.max_logs_per_response =
+            self.rpc_max_logs_per_response.unwrap_or(rpc.max_logs_per_response);
+        rpc.max_connections = self.rpc_max_connections.unwrap_or(rpc.max_connections);
+        rpc.max_request_size = self.rpc_max_request_size.unwrap_or(rpc.max_request_size);
+        rpc.max_response_size = self.rpc_max_response_size.unwrap_or(rpc.max_response_size);
+        rpc.max_batch_len = self.rpc_max_batch_len.unwrap_or(rpc.max_batch_len);
+
+        config.debug.tip = self.tip.or(config.debug.tip);
+        config.debug.max_block = self.max_block.or(config.debug.max_block);
+        Ok(config)
+    }
+
+    /// Spawns the miner that produces the blocks of the dev chain, or seals the blocks of the
+    /// clique chain, from the transactions of the pool and inserts them through the engine.
+    fn spawn_miner<Pool>(
+        &self,
+        db: Arc<Env<WriteMap>>,
+        clique: Option<Arc<Clique>>,
+        pool: Pool,
+        chain: &ChainSpec,
+        to_engine: mpsc::UnboundedSender<EngineMessage>,
+        executor: &TaskExecutor,
+    ) where
+        Pool: TransactionPool + 'static,
+    {
+        let clique = match (clique, self.clique_signer_key) {
+            (Some(clique), Some(key)) => Some((clique, CliqueSigner::new(key))),
+            _ => None,
+        };
+        let mode = match (&clique, self.block_time) {
+            (Some((clique, _)), _) => {
+                MiningMode::fixed(Duration::from_secs(clique.config().period.max(1)))
+            }
+            (None, Some(block_time)) => MiningMode::fixed(Duration::from_secs(block_time)),
+            (None, None) => MiningMode::instant(&pool),
+        };
+
+        let mut miner = AutoSealMiner::new(db, pool, mode, chain.clone(), to_engine);
+        if let Some((clique, signer)) = clique {
+            info!(signer = ?signer.address(), "Starting clique sealer");
+            miner = miner.with_clique(clique, signer);
+        } else {
+            info!("Starting dev mode");
+        }
+        executor.spawn_critical_with_shutdown_signal("auto seal miner", |shutdown| {
+            until_shutdown(
+                async move {
+                    if let Err(err) = miner.run().await {
+                        error!(%err, "Failed to seal a block");
+                    }
+                },
+                shutdown,
+            )
+        });
+    }
+}
+
+/// Resolves once the process receives SIGINT (ctrl-c) or SIGTERM, and returns the name of the
+/// signal.
+pub(crate) async fn shutdown_signal() -> eyre::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate())?;
+        tokio::select! {
+            res = tokio::signal::ctrl_c() => res.map(|_| "ctrl-c").map_err(Into::into),
+            _ = sigterm.recv() => Ok("SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await?;
+        Ok("ctrl-c")
+    }
+}
+
+/// Runs the task until it finishes or the shutdown is requested.
+async fn until_shutdown(task: impl Future<Output = ()>, shutdown: Shutdown) {
+    tokio::select! {
+        _ = task => {}
+        _ = shutdown => {}
+    }
+}
+
+/// Builds the pipeline that syncs the chain from the network.
+async fn sync_pipeline(
+    config: &Config,
+    chain: &ChainSpec,
+    consensus: Arc<BeaconConsensus>,
+    network: &NetworkHandle,
+) -> eyre::Result<Pipeline<Env<WriteMap>>> {
+    // TODO: Are most of these Arcs unnecessary? For example, fetch client is completely
+    // cloneable on its own
+    // TODO: Remove magic numbers
+    let fetch_client = Arc::new(network.fetch_client().await?);
+    let pipeline = Pipeline::new()
+        .push(HeaderStage {
+            downloader: headers::reverse::ReverseHeadersDownloaderBuilder::default()
+                .request_limit(config.stages.headers.downloader_batch_size)
+                .concurrent_requests(config.stages.headers.downloader_concurrency)
+                .retries(config.stages.headers.downloader_retries)
+                .build(consensus.clone(), fetch_client.clone()),
+            consensus: consensus.clone(),
+            client: fetch_client.clone(),
+            network_handle: network.clone(),
+            commit_threshold: config.stages.headers.commit_threshold,
+            metrics: HeaderMetrics::default(),
+        })
+        .push(BodyStage {
+            downloader: Arc::new(
+                bodies::buffered::BufferedDownloader::new(fetch_client.clone(), consensus.clone())
+                    .with_batch_size(config.stages.bodies.downloader_batch_size)
+                    .with_retries(config.stages.bodies.downloader_retries)
+                    .with_concurrency(config.stages.bodies.downloader_concurrency)
+                    .with_max_buffered_bytes(config.stages.bodies.downloader_max_buffered_bytes),
+            ),
+            consensus: consensus.clone(),
+            commit_threshold: config.stages.bodies.commit_threshold,
+        })
+        .push(SenderRecoveryStage {
+            batch_size: config.stages.sender_recovery.batch_size,
+            commit_threshold: config.stages.sender_recovery.commit_threshold,
+        })
+        .push(
+            ExecutionStage::new(chain.clone())
+                .with_thresholds(config.stages.execution.clone().into())
+                .with_state_cache(Arc::new(StateCache::new(Default::default()))),
+        )
+        .push(MerkleStage)
+        .push(TransactionLookupStage {
+            commit_threshold: config.stages.transaction_lookup.commit_threshold,
+            prune: config.stages.transaction_lookup.prune,
+        });
+    Ok(pipeline)
+}
+
+/// Builds the pipeline of a node that seals its own blocks, which only commits the blocks the
+/// tree makes canonical. The merkle stage keeps the hashed state up to date, which the state root
+/// of the next sealed block is computed from.
+fn sealer_pipeline(config: &Config, chain: &ChainSpec) -> Pipeline<Env<WriteMap>> {
+    Pipeline::new()
+        .push(
+            ExecutionStage::new(chain.clone())
+                .with_thresholds(config.stages.execution.clone().into()),
+        )
+        .push(MerkleStage)
+        .push(TransactionLookupStage {
+            commit_threshold: config.stages.transaction_lookup.commit_threshold,
+            prune: config.stages.transaction_lookup.prune,
+        })
+}
+
+/// Parses the modules of a transport, the standard modules if none are given.
+fn parse_modules(modules: Option<&str>) -> eyre::Result<RpcModuleSelection> {
+    Ok(modules.map(str::parse).transpose()?.unwrap_or_default())
 }
 
 /// Opens up an existing database or creates a new one at the specified path.
-fn init_db<P: AsRef<Path>>(path: P) -> eyre::Result<Env<WriteMap>> {
+///
+/// A new database gets the current version, an existing one is refused if it has another version.
+pub(crate) fn init_db<P: AsRef<Path>>(
+    path: P,
+    config: &DatabaseConfig,
+) -> eyre::Result<Env<WriteMap>> {
     std::fs::create_dir_all(path.as_ref())?;
-    let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
-        path.as_ref(),
-        reth_db::mdbx::EnvKind::RW,
-    )?;
+    init_db_version_file(path.as_ref())?;
+    let db = Env::<WriteMap>::open_with_config(path.as_ref(), EnvKind::RW, &config.into())?;
     db.create_tables()?;
 
     Ok(db)
 }
 
-/// Write the genesis block if it has not already been written
-#[allow(clippy::field_reassign_with_default)]
-fn init_genesis<DB: Database>(db: Arc<DB>, genesis: Genesis) -> Result<H256, reth_db::Error> {
-    let tx = db.tx_mut()?;
-    if let Some((_, hash)) = tx.cursor::<tables::CanonicalHeaders>()?.first()? {
-        debug!("Genesis already written, skipping.");
-        return Ok(hash)
-    }
-    debug!("Writing genesis block.");
-
-    // Insert account state
-    for (address, account) in &genesis.alloc {
-        tx.put::<tables::PlainAccountState>(
-            *address,
-            Account {
-                nonce: account.nonce.unwrap_or_default(),
-                balance: account.balance,
-                bytecode_hash: None,
-            },
-        )?;
+/// Periodically clears the read transactions of exited processes and warns about read
+/// transactions that keep the database from reusing the pages of old transactions.
+async fn check_readers(db: Arc<Env<WriteMap>>, config: DatabaseConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.reader_check_interval));
+    loop {
+        interval.tick().await;
+        match db.check_readers() {
+            Ok(status) => {
+                if status.cleared > 0 {
+                    warn!(cleared = status.cleared, "Cleared stale database readers");
+                }
+                if status.oldest_reader_lag > config.max_reader_lag {
+                    warn!(
+                        readers = status.readers,
+                        lag = status.oldest_reader_lag,
+                        "Long running database read transaction, the database grows until it ends"
+                    );
+                }
+            }
+            Err(error) => warn!(%error, "Failed to check the database readers"),
+        }
     }
-
-    // Insert header
-    let header: Header = genesis.into();
-    let hash = header.hash_slow();
-    tx.put::<tables::CanonicalHeaders>(0, hash)?;
-    tx.put::<tables::HeaderNumbers>(hash, 0)?;
-    tx.put::<tables::BlockBodies>((0, hash).into(), Default::default())?;
-    tx.put::<tables::BlockTransitionIndex>((0, hash).into(), 0)?;
-    tx.put::<tables::HeaderTD>((0, hash).into(), header.difficulty.into())?;
-    tx.put::<tables::Headers>((0, hash).into(), header)?;
-
-    tx.commit()?;
-    Ok(hash)
 }
 
-// TODO: This should be based on some external config
+/// Builds the network config from the `peers` and `sessions` sections of the configuration.
 fn network_config<DB: Database>(
+    config: &Config,
+    secret_key: SecretKey,
     db: Arc<DB>,
-    chain_id: u64,
-    genesis_hash: H256,
+    static_files: Arc<StaticFileProvider>,
+    chain_info: ChainInfoTracker,
+    chain_spec: ChainSpec,
+    head: Head,
+    boot_nodes: Vec<NodeRecord>,
 ) -> NetworkConfig<ProviderImpl<DB>> {
-    NetworkConfig::builder(Arc::new(ProviderImpl::new(db)), rng_secret_key())
-        .boot_nodes(mainnet_nodes())
-        .genesis_hash(genesis_hash)
-        .chain_id(chain_id)
+    let provider =
+        ProviderImpl::new(db).with_static_files(static_files).with_chain_info(chain_info);
+    let builder = NetworkConfig::builder(Arc::new(provider), secret_key);
+    let hello_message =
+        HelloMessage::builder(builder.get_peer_id()).client_version(CLIENT_VERSION).build();
+    builder
+        .hello_message(hello_message)
+        .boot_nodes(boot_nodes)
+        .peer_config(config.peers.clone().into())
+        .sessions_config(config.sessions.clone().into())
+        .chain_spec(chain_spec)
+        .set_head(head)
         .build()
 }
 
-/// Starts the networking stack given a [NetworkConfig] and returns a handle to the network.
+/// Looks up the head of the local chain the network starts from.
+fn lookup_head<DB: Database>(db: Arc<DB>) -> eyre::Result<Head> {
+    let provider = ProviderImpl::new(db);
+    let hash = provider.chain_info()?.best_hash;
+    let header = provider.header(&hash)?.ok_or_else(|| eyre::eyre!("Head {hash:?} not found"))?;
+    let total_difficulty =
+        provider.header_td(&hash)?.ok_or_else(|| eyre::eyre!("Head {hash:?} has no difficulty"))?;
+    Ok(Head { number: header.number, hash, total_difficulty, timestamp: header.timestamp })
+}
+
+/// Announces the head of the canonical chain in the `Status` of the network whenever it changes.
+async fn update_network_status(mut head: watch::Receiver<CanonicalHead>, network: NetworkHandle) {
+    while head.changed().await.is_ok() {
+        let CanonicalHead { header, total_difficulty } = head.borrow_and_update().clone();
+        network.update_status(Head {
+            number: header.number,
+            hash: header.hash(),
+            total_difficulty,
+            timestamp: header.timestamp,
+        });
+    }
+}
+
+/// Updates the transaction pool whenever the head of the canonical chain changes, so the mined
+/// transactions are removed and the base fee of the pending block is known.
+async fn maintain_pool<DB, P>(
+    client: Arc<ProviderImpl<DB>>,
+    pool: P,
+    mut head: watch::Receiver<CanonicalHead>,
+) where
+    DB: Database,
+    P: TransactionPool,
+{
+    while head.changed().await.is_ok() {
+        let header = head.borrow_and_update().header.clone();
+        let mined_transactions = match client.block(BlockId::Hash(header.hash())) {
+            Ok(Some(block)) => block.body.iter().map(|transaction| transaction.hash).collect(),
+            Ok(None) => Vec::new(),
+            Err(error) => {
+                warn!(%error, "Failed to read the new head block");
+                continue
+            }
+        };
+        let pending_block_base_fee = header.base_fee_per_gas.map(|base_fee| {
+            calculate_next_block_base_fee(header.gas_used, header.gas_limit, base_fee)
+        });
+        pool.on_new_block(OnNewBlockEvent {
+            hash: header.hash(),
+            pending_block_base_fee: U256::from(pending_block_base_fee.unwrap_or_default()),
+            state_changes: StateDiff {},
+            mined_transactions,
+        });
+    }
+}
+
+/// Starts the networking stack given a [NetworkConfig] and the transaction pool it exchanges
+/// transactions with, and returns the handles to the network and its transactions task.
 // ANCHOR: fn-start_network
-async fn start_network<C>(config: NetworkConfig<C>) -> Result<NetworkHandle, NetworkError>
+async fn start_network<C, P>(
+    config: NetworkConfig<C>,
+    pool: P,
+    executor: &TaskExecutor,
+) -> Result<(NetworkHandle, TransactionsHandle), NetworkError>
 where
     C: BlockProvider + HeaderProvider + 'static,
+    P: TransactionPool + Unpin + 'static,
 {
     let client = config.client.clone();
-    let (handle, network, _txpool, eth) =
-        NetworkManager::builder(config).await?.request_handler(client).split_with_handle();
+    let (handle, network, txpool, eth) = NetworkManager::builder(config)
+        .await?
+        .transactions(pool)
+        .request_handler(client)
+        .split_with_handle();
+    let transactions = txpool.handle();
 
-    tokio::task::spawn(network);
-    // TODO: tokio::task::spawn(txpool);
-    tokio::task::spawn(eth);
-    Ok(handle)
+    executor.spawn_critical_with_shutdown_signal("p2p network", |shutdown| {
+        until_shutdown(network, shutdown)
+    });
+    executor.spawn_critical_with_shutdown_signal("p2p transactions", |shutdown| {
+        until_shutdown(txpool, shutdown)
+    });
+    executor.spawn_critical_with_shutdown_signal("p2p eth requests", |shutdown| {
+        until_shutdown(eth, shutdown)
+    });
+    Ok((handle, transactions))
 }
 // ANCHOR_END: fn-start_network
@@ -0,0 +1,154 @@
+//! Periodic status report of the node.
+use reth_network::NetworkHandle;
+use reth_primitives::BlockNumber;
+use reth_provider::{ChainInfoTracker, HeaderProvider};
+use reth_stages::{stages::execution::EXECUTION, PipelineEvent, StageId};
+use reth_transaction_pool::TransactionPool;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{info, warn};
+
+/// Logs a one-line summary of the node every interval: the running stage and its checkpoint, the
+/// connected peers, the size of the transaction pool, the head block and the executed gas per
+/// second.
+pub(crate) async fn report_status<Client, Pool>(
+    client: Arc<Client>,
+    network: NetworkHandle,
+    pool: Pool,
+    chain_info: ChainInfoTracker,
+    mut events: Receiver<PipelineEvent>,
+    interval: Duration,
+) where
+    Client: HeaderProvider,
+    Pool: TransactionPool,
+{
+    let mut status = SyncStatus::default();
+    let mut interval = tokio::time::interval(interval);
+    // the first tick completes immediately
+    interval.tick().await;
+    let mut last_report = Instant::now();
+    let mut pipeline_stopped = false;
+
+    loop {
+        tokio::select! {
+            event = events.recv(), if !pipeline_stopped => match event {
+                Ok(event) => status.on_event(event),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Status report missed pipeline events")
+                }
+                Err(RecvError::Closed) => pipeline_stopped = true,
+            },
+            _ = interval.tick() => {
+                let gas = match status.take_executed() {
+                    Some((from, to)) => executed_gas(&*client, from, to),
+                    None => 0,
+                };
+                let elapsed = std::mem::replace(&mut last_report, Instant::now()).elapsed();
+                let mgas_per_second =
+                    format!("{:.2}", gas as f64 / 1_000_000.0 / elapsed.as_secs_f64());
+
+                let pool_size = pool.status();
+                let stage = status.stage.map(|(stage, _)| stage.to_string());
+                info!(
+                    stage = stage.as_deref().unwrap_or("none"),
+                    checkpoint = status.stage.and_then(|(_, checkpoint)| checkpoint),
+                    peers = network.num_connected_peers(),
+                    pending_txs = pool_size.pending,
+                    queued_txs = pool_size.queued,
+                    head = chain_info.canonical_head().header.number,
+                    %mgas_per_second,
+                    "Status"
+                );
+            }
+        }
+    }
+}
+
+/// Returns the gas used by the blocks in the range `(from, to]`.
+fn executed_gas(client: &impl HeaderProvider, from: BlockNumber, to: BlockNumber) -> u64 {
+    let mut gas = 0;
+    for number in from + 1..=to {
+        match client.header_by_number(number) {
+            Ok(Some(header)) => gas += header.gas_used,
+            // the blocks were unwound in the meantime
+            _ => break,
+        }
+    }
+    gas
+}
+
+/// The progress of the pipeline, as reported by its events.
+#[derive(Debug, Default)]
+struct SyncStatus {
+    /// The stage that runs or ran last, and its checkpoint.
+    stage: Option<(StageId, Option<BlockNumber>)>,
+    /// The progress of the execution stage at the last report.
+    reported_execution: Option<BlockNumber>,
+    /// The current progress of the execution stage.
+    execution: Option<BlockNumber>,
+}
+
+impl SyncStatus {
+    fn on_event(&mut self, event: PipelineEvent) {
+        match event {
+            PipelineEvent::Running { stage_id, stage_progress } => {
+                self.stage = Some((stage_id, stage_progress));
+                if stage_id == EXECUTION {
+                    self.execution = stage_progress;
+                    self.reported_execution.get_or_insert(stage_progress.unwrap_or_default());
+                }
+            }
+            PipelineEvent::Ran { stage_id, result } => {
+                self.stage = Some((stage_id, Some(result.stage_progress)));
+                if stage_id == EXECUTION {
+                    self.execution = Some(result.stage_progress);
+                }
+            }
+            PipelineEvent::Unwound { stage_id, result } => {
+                self.stage = Some((stage_id, Some(result.stage_progress)));
+                if stage_id == EXECUTION {
+                    self.execution = Some(result.stage_progress);
+                    self.reported_execution = Some(result.stage_progress);
+                }
+            }
+            PipelineEvent::Unwinding { stage_id, .. } |
+            PipelineEvent::Error { stage_id } |
+            PipelineEvent::Skipped { stage_id } => {
+                if self.stage.map_or(true, |(stage, _)| stage != stage_id) {
+                    self.stage = Some((stage_id, None));
+                }
+            }
+        }
+    }
+
+    /// Returns the blocks executed since the last call, as the range `(from, to]`.
+    fn take_executed(&mut self) -> Option<(BlockNumber, BlockNumber)> {
+        let to = self.execution?;
+        let from = self.reported_execution.replace(to)?;
+        (to > from).then_some((from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_stages::{stages::merkle::MERKLE, ExecOutput};
+
+    #[test]
+    fn track_execution_progress() {
+        let mut status = SyncStatus::default();
+        status.on_event(PipelineEvent::Running { stage_id: EXECUTION, stage_progress: Some(10) });
+        assert_eq!(status.stage, Some((EXECUTION, Some(10))));
+        assert_eq!(status.take_executed(), None);
+
+        let result = ExecOutput { stage_progress: 25, done: false };
+        status.on_event(PipelineEvent::Ran { stage_id: EXECUTION, result });
+        status.on_event(PipelineEvent::Running { stage_id: MERKLE, stage_progress: Some(10) });
+        assert_eq!(status.stage, Some((MERKLE, Some(10))));
+        assert_eq!(status.take_executed(), Some((10, 25)));
+        assert_eq!(status.take_executed(), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! P2P Debugging tool
+//!
+//! Starts a minimal network stack without a database, fetches a single header or body from the
+//! network and prints it, or runs a standalone discovery node.
+use crate::{
+    dirs::DataDirPath,
+    node::shutdown_signal,
+    util::{
+        chainspec::chain_spec_value_parser,
+        hash_or_num_value_parser, parse_path,
+        secret_key::{get_or_create_secret_key, read_secret_key},
+    },
+    version::CLIENT_VERSION,
+};
+use clap::{Parser, Subcommand};
+use eyre::eyre;
+use futures::StreamExt;
+use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config, DEFAULT_DISCOVERY_PORT};
+use reth_eth_wire::HelloMessage;
+use reth_interfaces::p2p::{
+    bodies::client::BodiesClient,
+    error::PeerRequestResult,
+    headers::client::{BlockHeaders, HeadersClient, HeadersRequest},
+};
+use reth_network::{
+    config::mainnet_nodes, FetchClient, NetworkConfig, NetworkHandle, NetworkManager,
+};
+use reth_primitives::{BlockHashOrNumber, ChainSpec, Header, HeadersDirection, NodeRecord};
+use reth_provider::test_utils::TestApi;
+use secp256k1::SecretKey;
+use std::{
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+
+/// The interval in which the number of connected peers is checked while waiting for a peer.
+const PEER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `reth p2p` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The chain of the peers.
+    ///
+    /// Possible values are either a built-in chain or the path to a genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
+
+    /// The path to the file of the hex encoded secret key the p2p identity is derived from,
+    /// `<DATA_DIR>/<CHAIN>/discovery-secret` by default.
+    #[arg(long = "p2p-secret-key", value_name = "PATH", value_parser = parse_path)]
+    p2p_secret_key: Option<PathBuf>,
+
+    /// Connect to this peer, given as enode URL, instead of discovering peers from the bootnodes.
+    ///
+    /// A bootnode uses it as its only bootnode.
+    #[arg(long, value_name = "ENODE")]
+    peer: Option<NodeRecord>,
+
+    /// The port the network listens on.
+    ///
+    /// A random port is used by default, so the command can run next to a node. A bootnode
+    /// listens on the default discovery port 30303 instead.
+    #[arg(long, value_name = "PORT")]
+    port: Option<u16>,
+
+    /// The number of times a request is sent again after it failed.
+    #[arg(long, default_value_t = 5)]
+    retries: usize,
+
+    /// The number of seconds to wait for the first peer.
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    peer_timeout: u64,
+
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth p2p` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Download a block header
+    Header {
+        /// The header number or hash
+        #[arg(value_parser = hash_or_num_value_parser)]
+        id: BlockHashOrNumber,
+    },
+    /// Download a block body
+    Body {
+        /// The block number or hash
+        #[arg(value_parser = hash_or_num_value_parser)]
+        id: BlockHashOrNumber,
+    },
+    /// Run a bootnode that only serves the discovery protocol
+    ///
+    /// Peers are discovered via discv4, but no eth sessions are established. The node key is kept
+    /// in the datadir, so the enode URL of the bootnode stays the same across restarts. Pass
+    /// `--p2p-secret-key` to run it next to a node that uses the same datadir.
+    Bootnode,
+}
+
+impl Command {
+    /// Execute `p2p` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        let datadir = self.datadir.chain(self.chain.chain);
+        let secret_key = match &self.p2p_secret_key {
+            Some(path) => read_secret_key(path)?,
+            None => get_or_create_secret_key(&datadir.p2p_secret_path())?,
+        };
+
+        if let Subcommands::Bootnode = self.command {
+            return self.run_bootnode(secret_key).await
+        }
+
+        let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, self.port.unwrap_or(0)));
+        let builder = NetworkConfig::builder(Arc::new(TestApi::default()), secret_key);
+        let hello_message =
+            HelloMessage::builder(builder.get_peer_id()).client_version(CLIENT_VERSION).build();
+        let mut config = builder
+            .hello_message(hello_message)
+            .chain_spec(self.chain.clone())
+            .listener_addr(addr)
+            .discovery_addr(addr);
+        if self.peer.is_none() {
+            config = config.boot_nodes(mainnet_nodes());
+        }
+
+        let network = NetworkManager::new(config.build()).await?;
+        let handle = network.handle().clone();
+        info!(peer_id = %handle.peer_id(), "Starting network");
+        tokio::spawn(network);
+
+        if let Some(peer) = &self.peer {
+            info!(%peer, "Connecting to peer");
+            handle.add_peer(peer.id, SocketAddr::new(peer.address, peer.tcp_port));
+        }
+
+        self.wait_for_peer(&handle).await?;
+        let client = handle.fetch_client().await?;
+
+        match self.command {
+            Subcommands::Header { id } => {
+                let header = self.get_header(&client, id).await?;
+                println!("{header:#?}");
+            }
+            Subcommands::Body { id } => {
+                let hash = match id {
+                    BlockHashOrNumber::Hash(hash) => hash,
+                    // the body is requested by hash, which is taken from the header
+                    BlockHashOrNumber::Number(_) => self.get_header(&client, id).await?.hash_slow(),
+                };
+                let bodies =
+                    self.retry(|| client.get_block_bodies(vec![hash]), "block body").await?;
+                let body = bodies.into_iter().next().ok_or_else(|| eyre!("Body not found"))?;
+                println!("{body:#?}");
+            }
+            Subcommands::Bootnode => unreachable!("bootnode is handled above"),
+        }
+
+        Ok(())
+    }
+
+    /// Runs the discv4 service until the process receives SIGINT or SIGTERM.
+    async fn run_bootnode(&self, secret_key: SecretKey) -> eyre::Result<()> {
+        let addr = SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            self.port.unwrap_or(DEFAULT_DISCOVERY_PORT),
+        );
+        let local_enr = NodeRecord::from_secret_key(addr, &secret_key);
+
+        let mut config = Discv4Config::builder();
+        match &self.peer {
+            Some(peer) => config.add_boot_node(*peer),
+            None => config.add_boot_nodes(mainnet_nodes()),
+        };
+
+        let (discv4, mut service) =
+            Discv4::bind(addr, local_enr, secret_key, config.build()).await?;
+        let mut updates = service.update_stream();
+        let _service = service.spawn();
+        info!(enode = %local_enr, addr = %discv4.local_addr(), "Started bootnode");
+
+        let log_updates = async {
+            while let Some(update) = updates.next().await {
+                log_discovery_update(update);
+            }
+        };
+        tokio::select! {
+            _ = log_updates => Err(eyre!("Discovery service stopped")),
+            signal = shutdown_signal() => {
+                info!("Received {}, shutting down", signal?);
+                Ok(())
+            }
+        }
+    }
+
+    /// Waits until the network is connected to a peer.
+    async fn wait_for_peer(&self, handle: &NetworkHandle) -> eyre::Result<()> {
+        let wait = async {
+            while handle.num_connected_peers() == 0 {
+                tokio::time::sleep(PEER_POLL_INTERVAL).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(self.peer_timeout), wait)
+            .await
+            .map_err(|_| eyre!("No peer connected within {} seconds", self.peer_timeout))?;
+        info!(peers = handle.num_connected_peers(), "Connected to peers");
+        Ok(())
+    }
+
+    /// Requests the header from the network.
+    async fn get_header(
+        &self,
+        client: &FetchClient,
+        id: BlockHashOrNumber,
+    ) -> eyre::Result<Header> {
+        let request = HeadersRequest { start: id, limit: 1, direction: HeadersDirection::Rising };
+        let headers = self.retry(|| client.get_headers(request.clone()), "header").await?;
+        headers.0.into_iter().next().ok_or_else(|| eyre!("Header not found"))
+    }
+
+    /// Sends the request until it succeeds or the retries are used up, and returns the response.
+    ///
+    /// Since a peer may not have the requested data, an empty response is retried as well, which
+    /// is likely sent to another peer.
+    async fn retry<T, F, Fut>(&self, request: F, name: &str) -> eyre::Result<T>
+    where
+        T: IsEmpty,
+        F: Fn() -> Fut,
+        Fut: Future<Output = PeerRequestResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let error = match request().await {
+                Ok(response) => {
+                    let (peer_id, response) = response.split();
+                    if !response.is_empty() || attempt >= self.retries {
+                        debug!(%peer_id, "Received {name}");
+                        return Ok(response)
+                    }
+                    format!("peer {peer_id} returned no {name}")
+                }
+                Err(err) if attempt < self.retries => err.to_string(),
+                Err(err) => return Err(err.into()),
+            };
+            attempt += 1;
+            warn!(%error, attempt, "Failed to download {name}, retrying");
+        }
+    }
+}
+
+fn log_discovery_update(update: DiscoveryUpdate) {
+    match update {
+        DiscoveryUpdate::Added(node) => debug!(%node, "Discovered node"),
+        DiscoveryUpdate::Removed(peer_id) => debug!(%peer_id, "Removed node"),
+        DiscoveryUpdate::EnrForkId(node, fork_id) => debug!(%node, ?fork_id, "Received fork id"),
+        DiscoveryUpdate::Batch(updates) => updates.into_iter().for_each(log_discovery_update),
+    }
+}
+
+/// A response that may contain no data.
+trait IsEmpty {
+    fn is_empty(&self) -> bool;
+}
+
+impl IsEmpty for BlockHeaders {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<T> IsEmpty for Vec<T> {
+    fn is_empty(&self) -> bool {
+        Vec::is_empty(self)
+    }
+}
@@ -1,20 +1,160 @@
 //! Prometheus exporter
 
 use eyre::WrapErr;
-use metrics_exporter_prometheus::PrometheusBuilder;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use metrics::{describe_gauge, gauge, Unit};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use metrics_util::layers::{PrefixLayer, Stack};
-use std::net::SocketAddr;
-
-pub(crate) fn initialize(listen_addr: SocketAddr) -> eyre::Result<()> {
-    let (recorder, exporter) = PrometheusBuilder::new()
-        .with_http_listener(listen_addr)
-        .build()
-        .wrap_err("Could not build Prometheus endpoint.")?;
-    tokio::task::spawn(exporter);
+use reth_db::mdbx::{Env, WriteMap};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use tracing::error;
+
+/// A function that records the current value of gauges, called right before the metrics are
+/// rendered for a scrape.
+pub(crate) type Hook = Box<dyn Fn() + Send + Sync>;
+
+/// Installs the Prometheus recorder that the metrics of all crates are recorded with, and serves
+/// the metrics in the Prometheus text format at the given address.
+///
+/// Besides the given hooks, every scrape records the metrics of the process and, if built with
+/// `--cfg tokio_unstable`, of the tokio runtime.
+pub(crate) fn initialize(listen_addr: SocketAddr, mut hooks: Vec<Hook>) -> eyre::Result<()> {
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let handle = recorder.handle();
     Stack::new(recorder)
         .push(PrefixLayer::new("reth"))
         .install()
         .wrap_err("Couldn't set metrics recorder.")?;
 
+    describe_process_metrics();
+    hooks.push(Box::new(collect_process_metrics));
+    #[cfg(tokio_unstable)]
+    {
+        describe_runtime_metrics();
+        let runtime = tokio::runtime::Handle::current();
+        hooks.push(Box::new(move || collect_runtime_metrics(&runtime)));
+    }
+
+    start_endpoint(listen_addr, handle, Arc::new(hooks))
+}
+
+/// Spawns the HTTP server that runs the hooks and renders the metrics on every request.
+fn start_endpoint(
+    listen_addr: SocketAddr,
+    handle: PrometheusHandle,
+    hooks: Arc<Vec<Hook>>,
+) -> eyre::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let handle = handle.clone();
+        let hooks = hooks.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
+                hooks.iter().for_each(|hook| hook());
+                let metrics = handle.render();
+                async move { Ok::<_, Infallible>(Response::new(Body::from(metrics))) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&listen_addr)
+        .wrap_err("Could not build Prometheus endpoint.")?
+        .serve(make_service);
+
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            error!(%err, "Prometheus endpoint failed");
+        }
+    });
     Ok(())
 }
+
+/// Returns a hook that records the size of the database and its pages on the freelist, which are
+/// reused by the next writes.
+pub(crate) fn db_metrics_hook(db: Arc<Env<WriteMap>>) -> Hook {
+    describe_gauge!("db_size_bytes", Unit::Bytes, "Size of the used pages of the database");
+    describe_gauge!("db_freelist_pages", "Number of free pages of the database");
+    Box::new(move || {
+        if let (Ok(info), Ok(stat)) = (db.inner.info(), db.inner.stat()) {
+            // page numbers are zero based
+            let used_pages = info.last_pgno() + 1;
+            gauge!("db_size_bytes", (used_pages * stat.page_size() as usize) as f64);
+        }
+        if let Ok(freelist) = db.inner.freelist() {
+            gauge!("db_freelist_pages", freelist as f64);
+        }
+    })
+}
+
+fn describe_process_metrics() {
+    describe_gauge!("process_resident_memory_bytes", Unit::Bytes, "Resident memory size");
+    describe_gauge!("process_virtual_memory_bytes", Unit::Bytes, "Virtual memory size");
+    describe_gauge!("process_threads", "Number of OS threads of the process");
+    describe_gauge!("process_open_fds", "Number of open file descriptors");
+}
+
+/// Records the memory usage, threads and open file descriptors of the process.
+///
+/// Only supported on Linux, where the values are read from `/proc/self`.
+fn collect_process_metrics() {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+            if let Some(rss) = status_value(&status, "VmRSS") {
+                gauge!("process_resident_memory_bytes", (rss * 1024) as f64);
+            }
+            if let Some(size) = status_value(&status, "VmSize") {
+                gauge!("process_virtual_memory_bytes", (size * 1024) as f64);
+            }
+            if let Some(threads) = status_value(&status, "Threads") {
+                gauge!("process_threads", threads as f64);
+            }
+        }
+        if let Ok(fds) = std::fs::read_dir("/proc/self/fd") {
+            gauge!("process_open_fds", fds.count() as f64);
+        }
+    }
+}
+
+/// Returns the number of the field of `/proc/self/status`, without its unit.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn status_value(status: &str, field: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(tokio_unstable)]
+fn describe_runtime_metrics() {
+    describe_gauge!("tokio_workers", "Number of worker threads of the tokio runtime");
+    describe_gauge!("tokio_blocking_threads", "Number of blocking threads of the tokio runtime");
+    describe_gauge!("tokio_injection_queue_depth", "Number of tasks in the global queue");
+    describe_gauge!("tokio_blocking_queue_depth", "Number of tasks waiting for a blocking thread");
+}
+
+/// Records the number of threads and the queue depths of the tokio runtime.
+#[cfg(tokio_unstable)]
+fn collect_runtime_metrics(runtime: &tokio::runtime::Handle) {
+    let metrics = runtime.metrics();
+    gauge!("tokio_workers", metrics.num_workers() as f64);
+    gauge!("tokio_blocking_threads", metrics.num_blocking_threads() as f64);
+    gauge!("tokio_injection_queue_depth", metrics.injection_queue_depth() as f64);
+    gauge!("tokio_blocking_queue_depth", metrics.blocking_queue_depth() as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_status_values() {
+        let status = "Name:\treth\nVmSize:\t  123456 kB\nVmRSS:\t    4096 kB\nThreads:\t12\n";
+        assert_eq!(status_value(status, "VmRSS"), Some(4096));
+        assert_eq!(status_value(status, "VmSize"), Some(123456));
+        assert_eq!(status_value(status, "Threads"), Some(12));
+        assert_eq!(status_value(status, "VmSwap"), None);
+    }
+}
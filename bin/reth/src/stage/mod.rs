@@ -0,0 +1,222 @@
+//! Stage debugging tool
+//!
+//! Runs or unwinds a single stage against an existing database. The changes are discarded unless
+//! `--commit` is passed.
+use crate::{
+    config::Config,
+    dirs::DataDirPath,
+    util::{chainspec::chain_spec_value_parser, parse_path},
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::eyre;
+use reth_db::{
+    mdbx::{Env, EnvKind, WriteMap},
+    version::check_db_version_file,
+};
+use reth_primitives::{BlockNumber, ChainSpec};
+use reth_stages::{
+    stages::{
+        bodies::BODIES,
+        execution::{ExecutionStage, EXECUTION},
+        merkle::{MerkleStage, MERKLE},
+        sender_recovery::{SenderRecoveryStage, SENDER_RECOVERY},
+        tx_lookup::TransactionLookupStage,
+    },
+    ExecInput, Stage, StageId, Transaction, UnwindInput,
+};
+use std::path::PathBuf;
+use tracing::info;
+
+/// `reth stage` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data directory for all reth files and subdirectories.
+    ///
+    /// The files of each chain are kept in a subdirectory named after the chain, e.g. `mainnet`.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: DataDirPath,
+
+    /// The path to the database folder, `<DATA_DIR>/<CHAIN>/db` by default.
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    db: Option<PathBuf>,
+
+    /// The path to the configuration file to use, `<DATA_DIR>/<CHAIN>/reth.toml` by default.
+    #[arg(long, value_name = "FILE", value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The chain the database belongs to.
+    ///
+    /// Possible values are either a built-in chain or the path to a genesis file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - dev
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
+
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Subcommand, Debug)]
+/// `reth stage` subcommands
+pub enum Subcommands {
+    /// Executes the stage over a range of blocks
+    Run(RunArgs),
+    /// Unwinds the stage to a block
+    Unwind(UnwindArgs),
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth stage run` command
+pub struct RunArgs {
+    /// The stage to run
+    #[arg(value_enum)]
+    stage: StageEnum,
+    /// The first block to execute
+    #[arg(long)]
+    from: BlockNumber,
+    /// The last block to execute
+    #[arg(long)]
+    to: BlockNumber,
+    /// Commit the changes and the progress of the stage to the database
+    #[arg(long)]
+    commit: bool,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth stage unwind` command
+pub struct UnwindArgs {
+    /// The stage to unwind
+    #[arg(value_enum)]
+    stage: StageEnum,
+    /// The block to unwind to, which is kept
+    #[arg(long)]
+    to: BlockNumber,
+    /// Commit the changes and the progress of the stage to the database
+    #[arg(long)]
+    commit: bool,
+}
+
+/// The stages that can be run without the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StageEnum {
+    /// The sender recovery stage
+    Senders,
+    /// The execution stage
+    Execution,
+    /// The merkle stage
+    Merkle,
+    /// The transaction lookup stage
+    TxLookup,
+}
+
+impl StageEnum {
+    /// Build the stage from the configuration.
+    fn build(&self, config: &Config, chain: &ChainSpec) -> Box<dyn Stage<Env<WriteMap>>> {
+        match self {
+            StageEnum::Senders => Box::new(SenderRecoveryStage {
+                batch_size: config.stages.sender_recovery.batch_size,
+                commit_threshold: config.stages.sender_recovery.commit_threshold,
+            }),
+            StageEnum::Execution => Box::new(
+                ExecutionStage::new(chain.clone())
+                    .with_thresholds(config.stages.execution.clone().into()),
+            ),
+            StageEnum::Merkle => Box::new(MerkleStage),
+            // The index is built even if it's pruned by the node, which allows rebuilding it
+            StageEnum::TxLookup => Box::new(TransactionLookupStage {
+                commit_threshold: config.stages.transaction_lookup.commit_threshold,
+                prune: false,
+            }),
+        }
+    }
+
+    /// The stage that runs before this stage in the pipeline.
+    fn previous_stage(&self) -> StageId {
+        match self {
+            StageEnum::Senders => BODIES,
+            StageEnum::Execution => SENDER_RECOVERY,
+            StageEnum::Merkle => EXECUTION,
+            StageEnum::TxLookup => MERKLE,
+        }
+    }
+}
+
+impl Command {
+    /// Execute `stage` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        let datadir = self.datadir.chain(self.chain.chain);
+        let config =
+            Config::load_or_create(self.config.clone().unwrap_or_else(|| datadir.config_path()))?;
+
+        let db_path = self.db.clone().unwrap_or_else(|| datadir.db_path());
+        info!("Opening database at {}", db_path.display());
+        check_db_version_file(&db_path)?;
+        let db = Env::<WriteMap>::open(&db_path, EnvKind::RW)?;
+        let mut tx = Transaction::new(&db)?;
+
+        match &self.command {
+            Subcommands::Run(args) => {
+                let mut stage = args.stage.build(&config, &self.chain);
+                let mut input = ExecInput {
+                    previous_stage: Some((args.stage.previous_stage(), args.to)),
+                    stage_progress: args.from.checked_sub(1),
+                };
+
+                loop {
+                    let output = stage.execute(&mut tx, input).await?;
+                    info!(stage = %stage.id(), stage_progress = output.stage_progress, done = output.done, "Stage executed");
+                    if args.commit {
+                        stage.id().save_progress(&*tx, output.stage_progress)?;
+                        tx.commit()?;
+                    }
+                    if output.done {
+                        break
+                    }
+                    input.stage_progress = Some(output.stage_progress);
+                }
+                if !args.commit {
+                    info!("Discarding the changes, pass --commit to keep them");
+                }
+            }
+            Subcommands::Unwind(args) => {
+                let mut stage = args.stage.build(&config, &self.chain);
+                let stage_progress = stage
+                    .id()
+                    .get_progress(&*tx)?
+                    .ok_or_else(|| eyre!("Stage {} has not been run", stage.id()))?;
+                if stage_progress <= args.to {
+                    info!(stage = %stage.id(), stage_progress, "Stage is already below the target");
+                    return Ok(())
+                }
+
+                let input = UnwindInput { stage_progress, unwind_to: args.to, bad_block: None };
+                let output = stage.unwind(&mut tx, input).await.map_err(|err| eyre!(err))?;
+                info!(stage = %stage.id(), stage_progress = output.stage_progress, "Stage unwound");
+                if args.commit {
+                    stage.id().save_progress(&*tx, output.stage_progress)?;
+                    tx.commit()?;
+                } else {
+                    info!("Discarding the changes, pass --commit to keep them");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use reth_primitives::{
-    Address, BigEndianHash, Bloom, Bytes, Header as RethHeader, JsonU256, SealedHeader, H160, H256,
-    H64,
+    Address, BigEndianHash, Bloom, Bytes, ChainSpec, ChainSpecBuilder, Header as RethHeader,
+    JsonU256, SealedHeader, H160, H256, H64,
 };
 use serde::{self, Deserialize};
 use std::collections::BTreeMap;
@@ -71,6 +71,14 @@ pub struct Header {
     pub uncle_hash: H256,
     /// Base fee per gas.
     pub base_fee_per_gas: Option<JsonU256>,
+    /// Withdrawals root.
+    pub withdrawals_root: Option<H256>,
+    /// Blob gas used.
+    pub blob_gas_used: Option<JsonU256>,
+    /// Excess blob gas.
+    pub excess_blob_gas: Option<JsonU256>,
+    /// Parent beacon block root.
+    pub parent_beacon_block_root: Option<H256>,
 }
 
 impl From<Header> for SealedHeader {
@@ -93,6 +101,10 @@ impl From<Header> for SealedHeader {
                 state_root: value.state_root,
                 parent_hash: value.parent_hash,
                 logs_bloom: Bloom::default(), // TODO: ?
+                withdrawals_root: value.withdrawals_root,
+                blob_gas_used: value.blob_gas_used.map(|v| v.0.as_u64()),
+                excess_blob_gas: value.excess_blob_gas.map(|v| v.0.as_u64()),
+                parent_beacon_block_root: value.parent_beacon_block_root,
             },
             value.hash,
         )
@@ -208,32 +220,35 @@ pub enum ForkSpec {
     MergePush0,
 }
 
-impl From<ForkSpec> for reth_executor::SpecUpgrades {
+impl From<ForkSpec> for ChainSpec {
     fn from(fork_spec: ForkSpec) -> Self {
+        let spec_builder = ChainSpecBuilder::default();
+
         match fork_spec {
-            ForkSpec::Frontier => Self::new_frontier_activated(),
+            ForkSpec::Frontier => spec_builder.frontier_activated(),
             ForkSpec::Homestead | ForkSpec::FrontierToHomesteadAt5 => {
-                Self::new_homestead_activated()
+                spec_builder.homestead_activated()
             }
             ForkSpec::EIP150 | ForkSpec::HomesteadToDaoAt5 | ForkSpec::HomesteadToEIP150At5 => {
-                Self::new_tangerine_whistle_activated()
+                spec_builder.tangerine_whistle_activated()
             }
-            ForkSpec::EIP158 => Self::new_spurious_dragon_activated(),
+            ForkSpec::EIP158 => spec_builder.spurious_dragon_activated(),
             ForkSpec::Byzantium |
             ForkSpec::EIP158ToByzantiumAt5 |
             ForkSpec::ConstantinopleFix |
-            ForkSpec::ByzantiumToConstantinopleFixAt5 => Self::new_byzantium_activated(),
-            ForkSpec::Istanbul => Self::new_istanbul_activated(),
-            ForkSpec::Berlin => Self::new_berlin_activated(),
-            ForkSpec::London | ForkSpec::BerlinToLondonAt5 => Self::new_london_activated(),
-            ForkSpec::Merge => Self::new_paris_activated(),
-            ForkSpec::MergeEOF => Self::new_paris_activated(),
-            ForkSpec::MergeMeterInitCode => Self::new_paris_activated(),
-            ForkSpec::MergePush0 => Self::new_paris_activated(),
+            ForkSpec::ByzantiumToConstantinopleFixAt5 => spec_builder.byzantium_activated(),
+            ForkSpec::Istanbul => spec_builder.istanbul_activated(),
+            ForkSpec::Berlin => spec_builder.berlin_activated(),
+            ForkSpec::London | ForkSpec::BerlinToLondonAt5 => spec_builder.london_activated(),
+            ForkSpec::Merge |
+            ForkSpec::MergeEOF |
+            ForkSpec::MergeMeterInitCode |
+            ForkSpec::MergePush0 => spec_builder.paris_activated(),
             ForkSpec::ByzantiumToConstantinopleAt5 | ForkSpec::Constantinople => {
                 panic!("Overriden with PETERSBURG")
             }
         }
+        .build()
     }
 }
 
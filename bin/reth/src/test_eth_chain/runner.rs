@@ -2,17 +2,16 @@ use super::models::Test;
 use crate::test_eth_chain::models::{ForkSpec, RootOrState};
 use eyre::eyre;
 use reth_db::{
-    cursor::DbCursorRO,
+    cursor::{DbCursorRO, DbDupCursorRO},
     database::Database,
     mdbx::{test_utils::create_test_rw_db, WriteMap},
     tables,
     transaction::{DbTx, DbTxMut},
     Error as DbError,
 };
-use reth_executor::SpecUpgrades;
 use reth_primitives::{
-    keccak256, Account as RethAccount, Address, JsonU256, SealedBlock, SealedHeader, StorageEntry,
-    H256, U256,
+    keccak256, Account as RethAccount, Address, ChainSpec, JsonU256, SealedBlock, SealedHeader,
+    StorageEntry, H256, U256,
 };
 use reth_rlp::Decodable;
 use reth_stages::{stages::execution::ExecutionStage, ExecInput, Stage, Transaction};
@@ -101,9 +100,9 @@ pub async fn run_test(path: PathBuf) -> eyre::Result<()> {
 
         debug!("Executing {:?} spec: {:?}", name, suite.network);
 
-        let spec_upgrades: SpecUpgrades = suite.network.into();
-        // if paris aka merge is not activated we dont have block rewards;
-        let has_block_reward = spec_upgrades.paris != 0;
+        let chain_spec: ChainSpec = suite.network.into();
+        // if paris aka merge is activated we dont have block rewards;
+        let has_block_reward = chain_spec.has_block_reward(0);
 
         // Create db and acquire transaction
         let db = create_test_rw_db::<WriteMap>();
@@ -111,12 +110,12 @@ pub async fn run_test(path: PathBuf) -> eyre::Result<()> {
 
         // insert genesis
         let header: SealedHeader = suite.genesis_block_header.into();
-        let genesis_block = SealedBlock { header, body: vec![], ommers: vec![] };
-        reth_provider::insert_canonical_block(&tx, &genesis_block, has_block_reward)?;
+        let genesis_block = SealedBlock { header, body: vec![], ommers: vec![], withdrawals: None };
+        reth_provider::insert_canonical_block(&tx, &genesis_block, None, has_block_reward)?;
 
         suite.blocks.iter().try_for_each(|block| -> eyre::Result<()> {
             let decoded = SealedBlock::decode(&mut block.rlp.as_ref())?;
-            reth_provider::insert_canonical_block(&tx, &decoded, has_block_reward)?;
+            reth_provider::insert_canonical_block(&tx, &decoded, None, has_block_reward)?;
             Ok(())
         })?;
 
@@ -162,9 +161,8 @@ pub async fn run_test(path: PathBuf) -> eyre::Result<()> {
         tracing::trace!("Pre state :{:?}", storage);
 
         // Initialize the execution stage
-        // Hardcode the chain_id to Ethereum 1.
-        let mut stage =
-            ExecutionStage::new(reth_executor::Config { chain_id: 1.into(), spec_upgrades });
+        // The chain id defaults to Ethereum 1.
+        let mut stage = ExecutionStage::new(chain_spec);
 
         // Call execution stage
         let input = ExecInput::default();
@@ -182,17 +180,7 @@ pub async fn run_test(path: PathBuf) -> eyre::Result<()> {
                 info!("Post state is root: #{root:?}")
             }
             Some(RootOrState::State(state)) => db.view(|tx| -> eyre::Result<()> {
-                let mut cursor = tx.cursor_dup::<tables::PlainStorageState>()?;
-                let walker = cursor.first()?.map(|first| cursor.walk(first.0)).transpose()?;
-                let storage = walker.map(|mut walker| {
-                    let mut map: HashMap<Address, HashMap<U256, U256>> = HashMap::new();
-                    while let Some(Ok((address, slot))) = walker.next() {
-                        let key = U256::from_big_endian(&slot.key.0);
-                        map.entry(address).or_default().insert(key, slot.value);
-                    }
-                    map
-                });
-                tracing::trace!("Our storage:{:?}", storage);
+                let mut storage_cursor = tx.cursor_dup::<tables::PlainStorageState>()?;
                 for (address, test_account) in state.iter() {
                     // check account
                     let our_account = tx
@@ -228,33 +216,25 @@ pub async fn run_test(path: PathBuf) -> eyre::Result<()> {
                         ))
                     }
 
-                    // get walker if present
-                    if let Some(storage) = storage.as_ref() {
-                        // iterate over storages
-                        for (JsonU256(key), JsonU256(value)) in test_account.storage.iter() {
-                            let our_value = storage
-                                .get(address)
-                                .ok_or(eyre!(
-                                    "Missing storage from test {storage:?} got {:?}",
-                                    test_account.storage
-                                ))?
-                                .get(key)
-                                .ok_or(eyre!(
-                                    "Slot is missing from table {storage:?} got:{:?}",
-                                    test_account.storage
-                                ))?;
-                            if value != our_value {
-                                return Err(eyre!(
-                                    "Storage diff we got {address}: {storage:?} but expect: {:?}",
-                                    test_account.storage
-                                ))
-                            }
-                        }
-                    } else if !test_account.storage.is_empty() {
-                        return Err(eyre!(
-                            "Walker is not present, but storage is not empty.{:?}",
+                    // check storage, the slots of the account are its duplicate values
+                    let storage = storage_cursor
+                        .walk_dup(*address, H256::zero())?
+                        .map(|entry| {
+                            entry.map(|(_, slot)| (U256::from_big_endian(&slot.key.0), slot.value))
+                        })
+                        .collect::<Result<HashMap<_, _>, _>>()?;
+                    tracing::trace!("Our storage of {address}: {storage:?}");
+                    for (JsonU256(key), JsonU256(value)) in test_account.storage.iter() {
+                        let our_value = storage.get(key).ok_or(eyre!(
+                            "Slot is missing from table {storage:?} got:{:?}",
                             test_account.storage
-                        ))
+                        ))?;
+                        if value != our_value {
+                            return Err(eyre!(
+                                "Storage diff we got {address}: {storage:?} but expect: {:?}",
+                                test_account.storage
+                            ))
+                        }
                     }
                 }
                 Ok(())
@@ -0,0 +1,46 @@
+//! Command for generating test vectors.
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+mod tables;
+
+/// `reth test-vectors` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Subcommand, Debug)]
+/// `reth test-vectors` subcommands
+pub enum Subcommands {
+    /// Generates random rows for every table, round-trips them through the table codecs and
+    /// writes them as JSON vectors, one file per table
+    Tables {
+        /// The folder of the vectors
+        #[arg(long, value_name = "PATH", default_value = "testdata/micro/db")]
+        path: PathBuf,
+        /// How many rows to generate per table
+        #[arg(long, default_value = "10")]
+        rows: usize,
+        /// Check the existing vectors against the codecs of this version instead of generating
+        /// new ones
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+impl Command {
+    /// Execute the command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Tables { path, rows, check } => {
+                if check {
+                    tables::check_vectors(&path)
+                } else {
+                    tables::generate_vectors(&path, rows)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Test vectors of the table codecs.
+//!
+//! A vector is a row of a table as it is stored in the database: the encoded key and the
+//! compressed value, both hex encoded. The vectors written by an older version have to decode with
+//! the codecs of a newer one, otherwise its databases can't be read anymore.
+use eyre::{ensure, Result, WrapErr};
+use rand::{thread_rng, Rng};
+use reth_db::{
+    table::{Compress, Decode, Decompress, Encode, Table},
+    tables::{
+        self,
+        codecs::CompactU256,
+        models::{
+            AccountBeforeTx, AddressStorageKey, BlockNumHash, ShardedKey, StorageTrieKey,
+            StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals, StoredTxLocation,
+            TransitionIdAddress,
+        },
+    },
+};
+use reth_interfaces::test_utils::generators::{random_header, random_signed_tx};
+use reth_primitives::{
+    hex, Account, Address, Bloom, BranchNodeCompact, IntegerList, Log, Receipt, StorageEntry,
+    TxType, Withdrawal, H256, U256,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use tracing::info;
+
+/// A row of a table as it is stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct Vector {
+    /// The hex encoded key.
+    key: String,
+    /// The hex encoded value.
+    value: String,
+}
+
+/// Declares [`generate_vectors`] and [`check_vectors`] for the tables, with the expression that
+/// generates a random row of each table.
+macro_rules! table_vectors {
+    (|$rng:ident| $($table:ident => $row:expr),+ $(,)?) => {
+        /// Writes `rows` random rows of every table to `<path>/<table>.json`.
+        pub(crate) fn generate_vectors(path: &Path, rows: usize) -> Result<()> {
+            fs::create_dir_all(path)
+                .wrap_err_with(|| format!("Could not create {}", path.display()))?;
+
+            let mut rng = thread_rng();
+            let $rng = &mut rng;
+            $(
+                let table_rows = (0..rows).map(|_| $row).collect::<Vec<_>>();
+                write_table::<tables::$table>(path, table_rows)?;
+            )+
+            Ok(())
+        }
+
+        /// Checks the vectors of every table in `path`.
+        pub(crate) fn check_vectors(path: &Path) -> Result<()> {
+            $(
+                check_table::<tables::$table>(path)?;
+            )+
+            Ok(())
+        }
+    };
+}
+
+table_vectors!(|rng|
+    CanonicalHeaders => (rng.gen(), H256::random()),
+    HeaderTD => (random_num_hash(rng), CompactU256(U256::from(rng.gen::<u128>()))),
+    HeaderNumbers => (H256::random(), rng.gen()),
+    Headers => {
+        let header = random_header(rng.gen(), Some(H256::random()));
+        (BlockNumHash(header.num_hash()), header.unseal())
+    },
+    BlockBodies => (
+        random_num_hash(rng),
+        StoredBlockBody { start_tx_id: rng.gen(), tx_count: rng.gen() },
+    ),
+    BlockOmmers => {
+        let ommers = (0..rng.gen_range(0..3)).map(|_| random_header(rng.gen(), None).unseal());
+        let ommers = ommers.collect();
+        (random_num_hash(rng), StoredBlockOmmers { ommers })
+    },
+    BlockWithdrawals => {
+        let withdrawals = (0..rng.gen_range(0..3)).map(|_| random_withdrawal(rng)).collect();
+        (random_num_hash(rng), StoredBlockWithdrawals { withdrawals })
+    },
+    NonCanonicalTransactions => (random_bytes(rng), random_signed_tx()),
+    Transactions => (rng.gen(), random_signed_tx()),
+    TxHashNumber => (
+        H256::random(),
+        StoredTxLocation { tx_number: rng.gen(), block_number: rng.gen() },
+    ),
+    Receipts => (rng.gen(), random_receipt(rng)),
+    Logs => (rng.gen(), random_receipt(rng)),
+    PlainAccountState => (Address::random(), random_account(rng)),
+    PlainStorageState => (Address::random(), random_storage_entry(rng)),
+    Bytecodes => (H256::random(), random_bytes(rng)),
+    BlockTransitionIndex => (random_num_hash(rng), rng.gen()),
+    TxTransitionIndex => (rng.gen(), rng.gen()),
+    AccountHistory => (ShardedKey::new(Address::random(), rng.gen()), random_list(rng)),
+    StorageHistory => {
+        let key = AddressStorageKey((Address::random(), H256::random()));
+        (ShardedKey::new(key, rng.gen()), random_list(rng))
+    },
+    AccountChangeSet => {
+        let info = rng.gen::<bool>().then(|| random_account(rng));
+        (rng.gen(), AccountBeforeTx { address: Address::random(), info })
+    },
+    StorageChangeSet => (
+        TransitionIdAddress((rng.gen(), Address::random())),
+        random_storage_entry(rng),
+    ),
+    HashedAccount => (H256::random(), random_account(rng)),
+    HashedStorage => (H256::random(), random_storage_entry(rng)),
+    AccountsTrie => (random_nibbles(rng), random_branch_node(rng)),
+    StoragesTrie => (
+        StorageTrieKey((H256::random(), random_nibbles(rng))),
+        random_branch_node(rng),
+    ),
+    TxSenders => (rng.gen(), Address::random()),
+    Config => (random_bytes(rng), random_bytes(rng)),
+    SyncStage => (random_bytes(rng), rng.gen()),
+    PruneCheckpoints => (random_bytes(rng), rng.gen()),
+    ForkChoice => (random_bytes(rng), H256::random()),
+);
+
+/// Round-trips the rows through the codecs of the table and writes them to `<path>/<table>.json`.
+fn write_table<T: Table>(path: &Path, rows: Vec<(T::Key, T::Value)>) -> Result<()>
+where
+    T::Key: Clone + PartialEq,
+    T::Value: Clone + PartialEq,
+{
+    let mut vectors = Vec::with_capacity(rows.len());
+    for (key, value) in rows {
+        let encoded_key = key.clone().encode().as_ref().to_vec();
+        let compressed_value = value.clone().compress().as_ref().to_vec();
+
+        let decoded_key = T::Key::decode(encoded_key.clone())?;
+        ensure!(decoded_key == key, "{}: key {key:?} decoded as {decoded_key:?}", T::NAME);
+        let decompressed_value = T::Value::decompress(compressed_value.clone())?;
+        ensure!(
+            decompressed_value == value,
+            "{}: value {value:?} decompressed as {decompressed_value:?}",
+            T::NAME
+        );
+
+        let (key, value) = (hex::encode(encoded_key), hex::encode(compressed_value));
+        vectors.push(Vector { key, value });
+    }
+
+    let file = path.join(format!("{}.json", T::NAME));
+    fs::write(&file, serde_json::to_string_pretty(&vectors)?)
+        .wrap_err_with(|| format!("Could not write {}", file.display()))?;
+    info!("Wrote {} vectors to {}", vectors.len(), file.display());
+    Ok(())
+}
+
+/// Checks that the vectors of the table still decode, that the keys encode to the same bytes,
+/// since the order of the rows depends on them, and that the values survive another round-trip.
+fn check_table<T: Table>(path: &Path) -> Result<()>
+where
+    T::Value: Clone + PartialEq,
+{
+    let file = path.join(format!("{}.json", T::NAME));
+    let json =
+        fs::read_to_string(&file).wrap_err_with(|| format!("Could not read {}", file.display()))?;
+    let vectors: Vec<Vector> = serde_json::from_str(&json)?;
+
+    for Vector { key, value } in &vectors {
+        let encoded_key = hex::decode(key)?;
+        let reencoded_key = T::Key::decode(encoded_key.clone())?.encode();
+        ensure!(
+            reencoded_key.as_ref() == encoded_key.as_slice(),
+            "{}: key {key} encodes as {}",
+            T::NAME,
+            hex::encode(reencoded_key)
+        );
+
+        let value = T::Value::decompress(hex::decode(value)?)?;
+        let round_tripped = T::Value::decompress(value.clone().compress().as_ref().to_vec())?;
+        ensure!(
+            round_tripped == value,
+            "{}: value {value:?} round-trips as {round_tripped:?}",
+            T::NAME
+        );
+    }
+
+    info!("Checked {} vectors of {}", vectors.len(), T::NAME);
+    Ok(())
+}
+
+/// Generates a random block number and hash.
+fn random_num_hash(rng: &mut impl Rng) -> BlockNumHash {
+    BlockNumHash((rng.gen(), H256::random()))
+}
+
+/// Generates up to 32 random bytes, which are never empty since they can be a key.
+fn random_bytes(rng: &mut impl Rng) -> Vec<u8> {
+    (0..rng.gen_range(1..=32)).map(|_| rng.gen()).collect()
+}
+
+/// Generates a random account, with or without bytecode.
+fn random_account(rng: &mut impl Rng) -> Account {
+    Account {
+        nonce: rng.gen(),
+        balance: U256::from(rng.gen::<u128>()),
+        bytecode_hash: rng.gen::<bool>().then(H256::random),
+    }
+}
+
+/// Generates a random storage slot.
+fn random_storage_entry(rng: &mut impl Rng) -> StorageEntry {
+    StorageEntry { key: H256::random(), value: U256::from(rng.gen::<u128>()) }
+}
+
+/// Generates a random path of up to 64 nibbles.
+fn random_nibbles(rng: &mut impl Rng) -> Vec<u8> {
+    (0..rng.gen_range(0..=64)).map(|_| rng.gen_range(0..16)).collect()
+}
+
+/// Generates a random branch node, with or without a root hash.
+fn random_branch_node(rng: &mut impl Rng) -> BranchNodeCompact {
+    let state_mask = rng.gen::<u16>() | 1;
+    let tree_mask = state_mask & rng.gen::<u16>();
+    let hash_mask = state_mask & rng.gen::<u16>();
+    let hashes = (0..hash_mask.count_ones()).map(|_| H256::random()).collect();
+    let root_hash = rng.gen::<bool>().then(H256::random);
+    BranchNodeCompact::new(state_mask, tree_mask, hash_mask, hashes, root_hash)
+}
+
+/// Generates a random receipt with up to 3 logs.
+fn random_receipt(rng: &mut impl Rng) -> Receipt {
+    let tx_type =
+        [TxType::Legacy, TxType::EIP2930, TxType::EIP1559, TxType::EIP4844][rng.gen_range(0..4)];
+    let mut bloom = Bloom::zero();
+    rng.fill(bloom.as_bytes_mut());
+
+    let logs = (0..rng.gen_range(0..=3))
+        .map(|_| Log {
+            address: Address::random(),
+            topics: (0..rng.gen_range(0..=4)).map(|_| H256::random()).collect(),
+            data: random_bytes(rng).into(),
+        })
+        .collect();
+
+    Receipt { tx_type, success: rng.gen(), cumulative_gas_used: rng.gen(), bloom, logs }
+}
+
+/// Generates a random withdrawal.
+fn random_withdrawal(rng: &mut impl Rng) -> Withdrawal {
+    Withdrawal {
+        index: rng.gen(),
+        validator_index: rng.gen(),
+        address: Address::random(),
+        amount: rng.gen(),
+    }
+}
+
+/// Generates a random list of transition ids, which is sorted and not empty.
+fn random_list(rng: &mut impl Rng) -> IntegerList {
+    let mut list =
+        (0..rng.gen_range(1..=10)).map(|_| rng.gen_range(0..1_000_000usize)).collect::<Vec<_>>();
+    list.sort_unstable();
+    list.dedup();
+    IntegerList::new(list).expect("the list is sorted and not empty")
+}
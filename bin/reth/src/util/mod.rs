@@ -1,13 +1,18 @@
 //! Utility functions.
+use reth_primitives::{BlockHashOrNumber, H256};
 use std::{
     env::VarError,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use walkdir::{DirEntry, WalkDir};
 
 /// Utilities for parsing chainspecs
 pub mod chainspec;
 
+/// Utilities for the secret key of the node's p2p identity
+pub mod secret_key;
+
 /// Finds all files in a directory with a given postfix.
 pub(crate) fn find_all_files_with_postfix(path: &Path, postfix: &str) -> Vec<PathBuf> {
     WalkDir::new(path)
@@ -24,12 +29,30 @@ pub(crate) fn parse_path(value: &str) -> Result<PathBuf, shellexpand::LookupErro
     shellexpand::full(value).map(|path| PathBuf::from(path.into_owned()))
 }
 
+/// Parses a user-specified block number, or a block hash if the value isn't a number.
+pub(crate) fn hash_or_num_value_parser(value: &str) -> eyre::Result<BlockHashOrNumber> {
+    match u64::from_str(value) {
+        Ok(num) => Ok(num.into()),
+        Err(_) => Ok(H256::from_str(value)?.into()),
+    }
+}
+
 /// Tracing utility
 pub mod reth_tracing {
+    use reth_rpc::LogFilterReloader;
+    use rolling_file::{RollingConditionBasic, RollingFileAppender};
+    use std::{error::Error, path::PathBuf};
     use tracing::Subscriber;
-    use tracing_subscriber::{prelude::*, EnvFilter};
+    use tracing_appender::non_blocking::WorkerGuard;
+    use tracing_subscriber::{prelude::*, reload, EnvFilter, Layer, Registry};
+
+    /// The name of the current log file, rotated files get a numeric suffix, e.g. `reth.log.1`.
+    const LOG_FILE_NAME: &str = "reth.log";
+
+    type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
 
     /// Tracing modes
+    #[derive(Debug, Clone, Copy)]
     pub enum TracingMode {
         /// Enable all traces.
         All,
@@ -70,22 +93,213 @@ pub mod reth_tracing {
         }
     }
 
+    /// Configuration of the log outputs.
+    #[derive(Debug, Clone, Default)]
+    pub struct LogConfig {
+        /// Log every event as a JSON object instead of a line of text.
+        pub json: bool,
+        /// Also send the events to the systemd journal.
+        pub journald: bool,
+        /// The filter directives of stdout and the journal, which take precedence over `RUST_LOG`.
+        pub filter: Option<String>,
+        /// Also write the events to log files.
+        pub file: Option<FileLogConfig>,
+        /// Also export the spans to an OpenTelemetry collector.
+        pub otlp: Option<OtlpConfig>,
+    }
+
+    /// Configuration of the log files.
+    #[derive(Debug, Clone)]
+    pub struct FileLogConfig {
+        /// The directory the log files are written to.
+        pub directory: PathBuf,
+        /// The size in megabytes after which the log file is rotated.
+        pub max_size_mb: u64,
+        /// The maximum number of rotated log files that are kept.
+        pub max_files: usize,
+        /// The filter directives of the log files, independent of the stdout filter.
+        pub filter: String,
+    }
+
+    /// Configuration of the span export to an OpenTelemetry collector.
+    #[derive(Debug, Clone)]
+    pub struct OtlpConfig {
+        /// The gRPC endpoint of the collector, like `http://localhost:4317`.
+        pub endpoint: String,
+        /// The ratio of the traces that are exported, between `0.0` and `1.0`.
+        pub sample_ratio: f64,
+        /// The filter directives of the exported spans, independent of the stdout filter.
+        pub filter: String,
+    }
+
+    /// Flushes the log file and the exported spans when dropped.
+    #[derive(Debug)]
+    #[must_use = "the logs are only flushed when the guard is dropped"]
+    pub struct TracingGuard {
+        _file: Option<WorkerGuard>,
+        #[cfg_attr(not(feature = "otlp"), allow(dead_code))]
+        otlp: bool,
+    }
+
+    impl Drop for TracingGuard {
+        fn drop(&mut self) {
+            #[cfg(feature = "otlp")]
+            if self.otlp {
+                opentelemetry::global::shutdown_tracer_provider();
+            }
+        }
+    }
+
+    /// Replaces the filter of stdout and the journal while the node is running.
+    #[derive(Debug, Clone, Default)]
+    pub struct LogFilterHandle {
+        handles: Vec<reload::Handle<EnvFilter, Registry>>,
+    }
+
+    impl LogFilterReloader for LogFilterHandle {
+        fn reload(&self, directives: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+            // parse once up front, so an invalid filter doesn't replace any of the filters
+            EnvFilter::try_new(directives)?;
+            for handle in &self.handles {
+                handle.reload(EnvFilter::try_new(directives)?)?;
+            }
+            Ok(())
+        }
+    }
+
     /// Build subscriber
-    // TODO: JSON/systemd support
-    pub fn build_subscriber(mods: TracingMode) -> impl Subscriber {
+    ///
+    /// Logs to stdout, filtered by the filter of the [LogConfig], `RUST_LOG` or else the tracing
+    /// mode. If a [FileLogConfig] is given, the logs are also written to size rotated files with
+    /// the filter of the file config. With `json`, every event is logged as a JSON object instead
+    /// of a line of text. With `journald`, the events are also sent to the systemd journal, with
+    /// the same filter as stdout.
+    ///
+    /// If an [OtlpConfig] is given, the sampled spans are exported to an OpenTelemetry collector,
+    /// for example the `execute` spans of the stages, or the `method_call` spans of the RPC
+    /// servers with a `jsonrpsee=trace` directive. If built with the `tokio-console` feature, the
+    /// tasks of the runtime can be inspected with tokio-console.
+    ///
+    /// The returned handle replaces the filter of stdout and the journal. The returned guard
+    /// flushes the log file and the exported spans when dropped, so it must be held until exit.
+    pub fn build_subscriber(
+        mods: TracingMode,
+        config: LogConfig,
+    ) -> eyre::Result<(impl Subscriber, LogFilterHandle, TracingGuard)> {
         // TODO: Auto-detect
         let no_color = std::env::var("RUST_LOG_STYLE").map(|val| val == "never").unwrap_or(false);
         let with_target = std::env::var("RUST_LOG_TARGET").map(|val| val != "0").unwrap_or(false);
+        let mut filter_handle = LogFilterHandle::default();
 
+        let stdout = tracing_subscriber::fmt::layer().with_target(with_target);
+        let stdout =
+            if config.json { stdout.json().boxed() } else { stdout.with_ansi(!no_color).boxed() };
+        let (filter, handle) = reload::Layer::new(stdout_filter(mods, config.filter.as_deref())?);
+        filter_handle.handles.push(handle);
+        let mut layers = vec![stdout.with_filter(filter).boxed()];
+
+        if config.journald {
+            let (filter, handle) =
+                reload::Layer::new(stdout_filter(mods, config.filter.as_deref())?);
+            filter_handle.handles.push(handle);
+            layers.push(journald_layer()?.with_filter(filter).boxed());
+        }
+
+        // the console layer records the task events of the runtime regardless of the log filters
+        #[cfg(feature = "tokio-console")]
+        layers.push(console_subscriber::spawn().boxed());
+
+        let file_guard = match config.file {
+            Some(file) => {
+                let (layer, guard) = file_layer(file, config.json)?;
+                layers.push(layer);
+                Some(guard)
+            }
+            None => None,
+        };
+
+        let otlp = config.otlp.is_some();
+        if let Some(otlp) = config.otlp {
+            let filter = EnvFilter::try_new(&otlp.filter)?;
+            layers.push(otlp_layer(otlp)?.with_filter(filter).boxed());
+        }
+
+        let guard = TracingGuard { _file: file_guard, otlp };
+        Ok((tracing_subscriber::registry().with(layers), filter_handle, guard))
+    }
+
+    /// Returns the filter of the given directives, of `RUST_LOG` if it is set, or else of the
+    /// tracing mode.
+    fn stdout_filter(mods: TracingMode, directives: Option<&str>) -> eyre::Result<EnvFilter> {
+        if let Some(directives) = directives {
+            return Ok(EnvFilter::try_new(directives)?)
+        }
         // Take env over config
-        let filter = if std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_default().is_empty() {
+        Ok(if std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_default().is_empty() {
             mods.into_env_filter()
         } else {
             EnvFilter::from_default_env()
+        })
+    }
+
+    /// Returns the layer that sends the events to the systemd journal, which maps their levels to
+    /// journal priorities.
+    #[cfg(feature = "journald")]
+    fn journald_layer() -> eyre::Result<BoxedLayer> {
+        Ok(tracing_journald::layer()?.boxed())
+    }
+
+    #[cfg(not(feature = "journald"))]
+    fn journald_layer() -> eyre::Result<BoxedLayer> {
+        Err(eyre::eyre!("Logging to journald requires building reth with the `journald` feature"))
+    }
+
+    /// Returns the layer that exports the spans to the collector in batches.
+    #[cfg(feature = "otlp")]
+    fn otlp_layer(config: OtlpConfig) -> eyre::Result<BoxedLayer> {
+        use opentelemetry::{
+            sdk::{
+                trace::{self, Sampler},
+                Resource,
+            },
+            KeyValue,
         };
+        use opentelemetry_otlp::WithExportConfig;
+
+        let sampler = Sampler::TraceIdRatioBased(config.sample_ratio);
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter().tonic().with_endpoint(config.endpoint),
+            )
+            .with_trace_config(
+                trace::config()
+                    // a span is sampled if its parent is, so traces are exported completely
+                    .with_sampler(Sampler::ParentBased(Box::new(sampler)))
+                    .with_resource(Resource::new([KeyValue::new("service.name", "reth")])),
+            )
+            .install_batch(opentelemetry::runtime::Tokio)?;
+        Ok(tracing_opentelemetry::layer().with_tracer(tracer).boxed())
+    }
+
+    #[cfg(not(feature = "otlp"))]
+    fn otlp_layer(_config: OtlpConfig) -> eyre::Result<BoxedLayer> {
+        Err(eyre::eyre!("Exporting spans requires building reth with the `otlp` feature"))
+    }
+
+    /// Returns the layer that writes to the log files from a background thread, and the guard of
+    /// the thread.
+    fn file_layer(config: FileLogConfig, json: bool) -> eyre::Result<(BoxedLayer, WorkerGuard)> {
+        std::fs::create_dir_all(&config.directory)?;
+        let appender = RollingFileAppender::new(
+            config.directory.join(LOG_FILE_NAME),
+            RollingConditionBasic::new().max_size(config.max_size_mb * 1024 * 1024),
+            config.max_files,
+        )?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
 
-        tracing_subscriber::registry()
-            .with(tracing_subscriber::fmt::layer().with_ansi(!no_color).with_target(with_target))
-            .with(filter)
+        let layer = tracing_subscriber::fmt::layer().with_ansi(false).with_writer(writer);
+        let layer = if json { layer.json().boxed() } else { layer.boxed() };
+        Ok((layer.with_filter(EnvFilter::try_new(&config.filter)?).boxed(), guard))
     }
 }
@@ -0,0 +1,67 @@
+//! Loading and storing the secret key of the node's p2p identity.
+use eyre::WrapErr;
+use reth_network::config::rng_secret_key;
+use reth_primitives::hex;
+use secp256k1::SecretKey;
+use std::{fs::OpenOptions, io::Write, path::Path, str::FromStr};
+
+/// Reads the secret key from the given file, or generates a new one and stores it there if the
+/// file doesn't exist, so the node keeps its [PeerId](reth_primitives::PeerId) across restarts.
+pub fn get_or_create_secret_key(path: &Path) -> eyre::Result<SecretKey> {
+    if path.exists() {
+        return read_secret_key(path)
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let secret_key = rng_secret_key();
+    write_secret_key(path, &secret_key)
+        .wrap_err_with(|| format!("Could not write secret key to {}", path.display()))?;
+    Ok(secret_key)
+}
+
+/// Reads a hex encoded secret key from the given file.
+pub fn read_secret_key(path: &Path) -> eyre::Result<SecretKey> {
+    let contents = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read secret key from {}", path.display()))?;
+    SecretKey::from_str(contents.trim())
+        .wrap_err_with(|| format!("Invalid secret key in {}", path.display()))
+}
+
+/// Writes the secret key hex encoded to a new file that only the owner can read and write.
+fn write_secret_key(path: &Path, secret_key: &SecretKey) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(hex::encode(secret_key.secret_bytes()).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_and_reload_secret_key() {
+        let dir = std::env::temp_dir().join(format!("reth-secret-key-{}", rand::random::<u64>()));
+        let path = dir.join("discovery-secret");
+
+        let secret_key = get_or_create_secret_key(&path).unwrap();
+        assert_eq!(get_or_create_secret_key(&path).unwrap(), secret_key);
+        assert_eq!(read_secret_key(&path).unwrap(), secret_key);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+//! Version information of the build.
+
+/// The version of the crate, like `0.1.0`.
+pub const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The short SHA of the commit the binary was built from, `unknown` if it wasn't built from a git
+/// checkout.
+pub const GIT_SHA: &str = env!("RETH_GIT_SHA");
+
+/// The target triple the binary was built for, like `x86_64-unknown-linux-gnu`.
+pub const BUILD_TARGET: &str = env!("RETH_BUILD_TARGET");
+
+/// The cargo profile the binary was built with, like `release`.
+pub const BUILD_PROFILE: &str = env!("RETH_BUILD_PROFILE");
+
+/// The version shown by `reth -V`, like `0.1.0 (abc1234)`.
+pub const SHORT_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("RETH_GIT_SHA"), ")");
+
+/// The version shown by `reth --version`, with the full build information.
+pub const LONG_VERSION: &str = concat!(
+    "Version: ",
+    env!("CARGO_PKG_VERSION"),
+    "\nCommit SHA: ",
+    env!("RETH_GIT_SHA"),
+    "\nBuild Target: ",
+    env!("RETH_BUILD_TARGET"),
+    "\nBuild Profile: ",
+    env!("RETH_BUILD_PROFILE"),
+);
+
+/// The client version the node identifies itself with, in the RLPx `Hello` message and in
+/// `web3_clientVersion`, like `reth/v0.1.0-abc1234/x86_64-unknown-linux-gnu`.
+pub const CLIENT_VERSION: &str = concat!(
+    "reth/v",
+    env!("CARGO_PKG_VERSION"),
+    "-",
+    env!("RETH_GIT_SHA"),
+    "/",
+    env!("RETH_BUILD_TARGET"),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_version_contains_build_info() {
+        assert!(CLIENT_VERSION.starts_with(&format!("reth/v{CARGO_PKG_VERSION}-{GIT_SHA}")));
+        assert!(CLIENT_VERSION.ends_with(BUILD_TARGET));
+        assert!(LONG_VERSION.contains(BUILD_PROFILE));
+    }
+}
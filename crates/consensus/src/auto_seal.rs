@@ -0,0 +1,308 @@
+//! Automatic block production for development chains.
+//!
+//! The [AutoSealMiner] seals blocks from the best transactions of the pool on top of the canonical
+//! head and inserts them through the [BeaconConsensusEngine](crate::engine::BeaconConsensusEngine)
+//! as a new block followed by a fork choice update, just like a consensus layer would. On clique
+//! chains, the blocks are signed by a local [CliqueSigner].
+use crate::{
+    builder::{build_block, BuildError, BuiltBlock},
+    clique::{Clique, CliqueSigner},
+    engine::{EngineApiError, EngineMessage},
+    verification::calculate_next_block_base_fee,
+};
+use reth_db::database::Database;
+use reth_interfaces::consensus::{Error as ConsensusError, ForkchoiceState};
+use reth_primitives::{
+    proofs, Address, ChainSpec, Hardfork, Header, SealedBlock, SealedHeader, TxHash, H256, U256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, ProviderImpl};
+use reth_rpc_types::engine::PayloadStatusEnum;
+use reth_transaction_pool::{OnNewBlockEvent, StateDiff, TransactionPool};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{
+        mpsc::{Receiver, UnboundedSender},
+        oneshot,
+    },
+    time::{Interval, MissedTickBehavior},
+};
+use tracing::*;
+
+/// How the [AutoSealMiner] decides when to seal the next block.
+#[derive(Debug)]
+pub enum MiningMode {
+    /// Seal a block as soon as new transactions are ready to be included.
+    Instant(Receiver<TxHash>),
+    /// Seal a block at a fixed interval, even if there are no transactions.
+    FixedBlockTime(Interval),
+}
+
+impl MiningMode {
+    /// Seal a block whenever the pool has new transactions that are ready.
+    pub fn instant<Pool: TransactionPool>(pool: &Pool) -> Self {
+        Self::Instant(pool.pending_transactions_listener())
+    }
+
+    /// Seal a block every `block_time`.
+    ///
+    /// # Panics
+    ///
+    /// If the block time is zero.
+    pub fn fixed(block_time: Duration) -> Self {
+        let mut interval = tokio::time::interval(block_time);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self::FixedBlockTime(interval)
+    }
+
+    /// Waits until the next block is due.
+    ///
+    /// Returns `false` if no more blocks are going to be sealed.
+    async fn next_block(&mut self) -> bool {
+        match self {
+            MiningMode::Instant(rx) => {
+                if rx.recv().await.is_none() {
+                    return false
+                }
+                // the transactions that are ready by now go into the same block
+                while rx.try_recv().is_ok() {}
+                true
+            }
+            MiningMode::FixedBlockTime(interval) => {
+                interval.tick().await;
+                true
+            }
+        }
+    }
+}
+
+/// Errors that stop the [AutoSealMiner].
+#[derive(Debug, thiserror::Error)]
+pub enum AutoSealError {
+    /// Failed to read the chain.
+    #[error(transparent)]
+    Provider(#[from] reth_interfaces::Error),
+    /// The canonical head is not found.
+    #[error("The canonical head {0:?} is not found.")]
+    HeadUnknown(H256),
+    /// The block can't be sealed by the clique signer.
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    /// Failed to read the state while executing a transaction.
+    #[error("Failed to read the state while executing transaction {0:?}.")]
+    Execution(TxHash),
+    /// The engine failed to handle a sealed block.
+    #[error(transparent)]
+    Engine(#[from] EngineApiError),
+    /// The engine did not accept a sealed block.
+    #[error("The engine did not accept the sealed block {hash:?}: {status:?}.")]
+    Rejected {
+        /// The hash of the sealed block.
+        hash: H256,
+        /// The status the engine answered with.
+        status: PayloadStatusEnum,
+    },
+    /// The engine is shut down.
+    #[error("The consensus engine is shut down.")]
+    EngineClosed,
+}
+
+/// Seals blocks on top of the canonical head for development chains.
+///
+/// The transactions are executed one by one and the ones that can't be included, because they
+/// don't fit into the block or fail to execute, are removed from the pool. The state root of the
+/// sealed blocks is computed from the hashed state of the parent, which is kept up to date by the
+/// merkle stage of the pipeline the canonical blocks are committed with.
+pub struct AutoSealMiner<DB: Database, Pool> {
+    /// The database the state root is computed with.
+    db: Arc<DB>,
+    /// Client for reading the canonical chain and its state.
+    client: ProviderImpl<DB>,
+    /// The pool the transactions are taken from.
+    pool: Pool,
+    /// When to seal the next block.
+    mode: MiningMode,
+    /// The chain specification the transactions are executed with.
+    chain_spec: ChainSpec,
+    /// Sender of the messages to the consensus engine.
+    to_engine: UnboundedSender<EngineMessage>,
+    /// The clique consensus and the local signer, if the blocks are sealed by clique.
+    clique: Option<(Arc<Clique>, CliqueSigner)>,
+}
+
+impl<DB: Database, Pool> std::fmt::Debug for AutoSealMiner<DB, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoSealMiner").field("mode", &self.mode).finish_non_exhaustive()
+    }
+}
+
+impl<DB, Pool> AutoSealMiner<DB, Pool>
+where
+    DB: Database,
+    Pool: TransactionPool,
+{
+    /// Create a new miner that inserts the sealed blocks through the engine the messages are sent
+    /// to.
+    pub fn new(
+        db: Arc<DB>,
+        pool: Pool,
+        mode: MiningMode,
+        chain_spec: ChainSpec,
+        to_engine: UnboundedSender<EngineMessage>,
+    ) -> Self {
+        Self {
+            client: ProviderImpl::new(db.clone()),
+            db,
+            pool,
+            mode,
+            chain_spec,
+            to_engine,
+            clique: None,
+        }
+    }
+
+    /// Seal the blocks with the clique signer.
+    ///
+    /// Blocks are only sealed once the clique period after the head has passed, and the slots
+    /// in which the signer is not allowed to seal a block are skipped.
+    pub fn with_clique(mut self, clique: Arc<Clique>, signer: CliqueSigner) -> Self {
+        self.clique = Some((clique, signer));
+        self
+    }
+
+    /// Seal blocks until the mining mode stops.
+    ///
+    /// Returns an error if a block can't be sealed or the engine doesn't accept it.
+    pub async fn run(mut self) -> Result<(), AutoSealError> {
+        let best_hash = self.client.chain_info()?.best_hash;
+        let mut head =
+            self.client.header(&best_hash)?.ok_or(AutoSealError::HeadUnknown(best_hash))?.seal();
+
+        while self.mode.next_block().await {
+            // the timestamp needs to increase with every block, but must not be in the future
+            let period =
+                self.clique.as_ref().map_or(1, |(clique, _)| clique.config().period.max(1));
+            let now = unix_timestamp();
+            if now < head.timestamp + period {
+                tokio::time::sleep(Duration::from_secs(head.timestamp + period - now)).await;
+            }
+
+            let (block, invalid) = match self.seal_block(&head) {
+                Ok(sealed) => sealed,
+                Err(AutoSealError::Consensus(ConsensusError::CliqueRecentlySigned { signer })) => {
+                    debug!(target: "consensus::auto_seal", ?signer, "Signed recently, skipping the slot");
+                    continue
+                }
+                Err(err) => return Err(err),
+            };
+            if !invalid.is_empty() {
+                debug!(target: "consensus::auto_seal", count = invalid.len(), "Removing invalid transactions");
+                self.pool.remove_invalid(invalid);
+            }
+
+            let mined_transactions =
+                block.body.iter().map(|transaction| transaction.hash).collect();
+            let header = block.header.clone();
+            self.insert_block(block).await?;
+            info!(target: "consensus::auto_seal", number = header.number, hash = ?header.hash(), "Sealed block");
+
+            let pending_block_base_fee = header.base_fee_per_gas.map(|base_fee| {
+                calculate_next_block_base_fee(header.gas_used, header.gas_limit, base_fee)
+            });
+            self.pool.on_new_block(OnNewBlockEvent {
+                hash: header.hash(),
+                pending_block_base_fee: U256::from(pending_block_base_fee.unwrap_or_default()),
+                state_changes: StateDiff {},
+                mined_transactions,
+            });
+            head = header;
+        }
+
+        Ok(())
+    }
+
+    /// Seal a block on top of the parent from the best transactions of the pool.
+    ///
+    /// Returns the block and the hashes of the transactions that can't be included.
+    fn seal_block(
+        &self,
+        parent: &SealedHeader,
+    ) -> Result<(SealedBlock, Vec<TxHash>), AutoSealError> {
+        let mut header = Header {
+            parent_hash: parent.hash(),
+            ommers_hash: proofs::EMPTY_LIST_HASH,
+            beneficiary: Address::zero(),
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp: unix_timestamp().max(parent.timestamp + 1),
+            base_fee_per_gas: parent.base_fee_per_gas.map(|base_fee| {
+                calculate_next_block_base_fee(parent.gas_used, parent.gas_limit, base_fee)
+            }),
+            ..Default::default()
+        };
+        if let Some((clique, signer)) = &self.clique {
+            clique.prepare(&mut header, parent, signer.address())?;
+        }
+
+        // the block doesn't process any withdrawals, but must commit to them after Shanghai
+        let withdrawals = self
+            .chain_spec
+            .is_fork_active_at_timestamp(Hardfork::Shanghai, header.timestamp)
+            .then(Vec::new);
+        let BuiltBlock { header, body, withdrawals, invalid } = build_block(
+            self.db.clone(),
+            &self.chain_spec,
+            header,
+            withdrawals,
+            self.pool.best_transactions(),
+        )
+        .map_err(|err| match err {
+            BuildError::Provider(err) => AutoSealError::Provider(err),
+            BuildError::Execution(hash) => AutoSealError::Execution(hash),
+        })?;
+
+        let header = match &self.clique {
+            Some((clique, signer)) => clique.seal(header, signer),
+            None => header.seal(),
+        };
+        let block = SealedBlock { header, body, ommers: Vec::new(), withdrawals };
+        Ok((block, invalid))
+    }
+
+    /// Insert the block and make it the head, safe and finalized block.
+    async fn insert_block(&self, block: SealedBlock) -> Result<(), AutoSealError> {
+        let hash = block.hash();
+
+        let (tx, rx) = oneshot::channel();
+        self.to_engine
+            .send(EngineMessage::NewBlock(block, tx))
+            .map_err(|_| AutoSealError::EngineClosed)?;
+        let status = rx.await.map_err(|_| AutoSealError::EngineClosed)??.status;
+        if status != PayloadStatusEnum::Valid {
+            return Err(AutoSealError::Rejected { hash, status })
+        }
+
+        let state = ForkchoiceState {
+            head_block_hash: hash,
+            safe_block_hash: hash,
+            finalized_block_hash: hash,
+        };
+        let (tx, rx) = oneshot::channel();
+        self.to_engine
+            .send(EngineMessage::ForkchoiceUpdated(state, None, tx))
+            .map_err(|_| AutoSealError::EngineClosed)?;
+        let status = rx.await.map_err(|_| AutoSealError::EngineClosed)??.payload_status.status;
+        if status != PayloadStatusEnum::Valid {
+            return Err(AutoSealError::Rejected { hash, status })
+        }
+
+        Ok(())
+    }
+}
+
+/// The current unix timestamp in seconds.
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
@@ -0,0 +1,192 @@
+//! Building blocks from pending transactions on top of the canonical head.
+//!
+//! The blocks of the [AutoSealMiner](crate::auto_seal::AutoSealMiner) and the payloads of the
+//! [BeaconConsensusEngine](crate::engine::BeaconConsensusEngine) are built the same way, they only
+//! differ in the fields of the header that are set before the transactions are executed.
+use reth_db::{
+    cursor::DbDupCursorRO,
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_executor::{
+    executor::{commit_changes, withdrawals_changeset, AccountChangeSet},
+    revm_wrap::{self, State, SubState},
+    state::ChainState,
+};
+use reth_primitives::{
+    bloom::{logs_bloom, receipts_bloom},
+    keccak256, proofs, ChainSpec, Header, IntoRecoveredTransaction, Log, Receipt, StorageEntry,
+    TransactionSigned, TxHash, Withdrawal, H160, H256,
+};
+use reth_provider::{ProviderImpl, StateProviderFactory};
+use reth_transaction_pool::{BestTransactions, PoolTransaction, ValidPoolTransaction};
+use reth_trie::StateRoot;
+use revm::{Return, SpecId, EVM};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// The pending transactions a block is built from, best first.
+pub(crate) type BestPoolTransactions<T> =
+    Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>;
+
+/// Errors of building a block.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BuildError {
+    /// Failed to read the chain or its state.
+    #[error(transparent)]
+    Provider(#[from] reth_interfaces::Error),
+    /// Failed to read the state while executing a transaction.
+    #[error("Failed to read the state while executing transaction {0:?}.")]
+    Execution(TxHash),
+}
+
+/// A block that was built from pending transactions, see [build_block].
+#[derive(Debug)]
+pub(crate) struct BuiltBlock {
+    /// The header with the fields that depend on the execution filled in.
+    pub(crate) header: Header,
+    /// The transactions that were included.
+    pub(crate) body: Vec<TransactionSigned>,
+    /// The withdrawals of the block, `Some` after Shanghai.
+    pub(crate) withdrawals: Option<Vec<Withdrawal>>,
+    /// The hashes of the transactions that were rejected by the EVM, e.g. because of their nonce
+    /// or the balance of the sender.
+    pub(crate) invalid: Vec<TxHash>,
+}
+
+/// Executes the transactions that fit into the block on top of the state of its parent, followed
+/// by the withdrawals, and fills in the gas used, the roots and the logs bloom of the header.
+///
+/// All other fields of the header must be set already. The rejected transactions are marked
+/// invalid, so the transactions that depend on them are skipped. The parent must be the canonical
+/// head, since the state root is computed from the hashed state of the database.
+pub(crate) fn build_block<DB: Database, T: PoolTransaction>(
+    db: Arc<DB>,
+    chain_spec: &ChainSpec,
+    mut header: Header,
+    withdrawals: Option<Vec<Withdrawal>>,
+    mut best_transactions: BestPoolTransactions<T>,
+) -> Result<BuiltBlock, BuildError> {
+    let client = ProviderImpl::new(db.clone());
+    let state = client.history_by_block_hash(header.parent_hash)?;
+    let mut evm = EVM::new();
+    evm.database(SubState::new(State::new(state)));
+    revm_wrap::fill_cfg_env(&mut evm.env.cfg, chain_spec, &header);
+    let after_merge = evm.env.cfg.spec_id >= SpecId::MERGE;
+    revm_wrap::fill_block_env(&mut evm.env.block, &header, after_merge);
+
+    let mut post_state = ChainState::default();
+    let mut cumulative_gas_used = 0;
+    let mut body = Vec::new();
+    let mut receipts = Vec::new();
+    let mut invalid = Vec::new();
+    while let Some(pool_transaction) = best_transactions.next() {
+        // smaller transactions may still fit into the rest of the block
+        if pool_transaction.transaction.gas_limit() > header.gas_limit - cumulative_gas_used {
+            best_transactions.mark_invalid(&pool_transaction);
+            continue
+        }
+
+        let transaction = pool_transaction.transaction.to_recovered_transaction();
+        revm_wrap::fill_tx_env(&mut evm.env.tx, &transaction);
+        let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = evm.transact();
+        if exit_reason == Return::FatalExternalError {
+            return Err(BuildError::Execution(transaction.hash))
+        }
+        // transactions that are rejected, e.g. because of their nonce or the balance of the
+        // sender, don't use any gas
+        if gas_used == 0 {
+            best_transactions.mark_invalid(&pool_transaction);
+            invalid.push(transaction.hash);
+            continue
+        }
+
+        cumulative_gas_used += gas_used;
+        let logs: Vec<Log> = logs
+            .into_iter()
+            .map(|log| Log {
+                address: H160(log.address.0),
+                topics: log.topics.into_iter().map(|topic| H256(topic.0)).collect(),
+                data: log.data,
+            })
+            .collect();
+        let (changeset, new_bytecodes) = commit_changes(evm.db().expect("database is set"), state);
+        post_state.apply_changeset(changeset, new_bytecodes);
+        receipts.push(Receipt {
+            tx_type: transaction.tx_type(),
+            success: matches!(exit_reason, revm::return_ok!()),
+            cumulative_gas_used,
+            bloom: logs_bloom(logs.iter()),
+            logs,
+        });
+        body.push(TransactionSigned::from(transaction));
+    }
+
+    // the withdrawals are processed after the transactions, like by the executor
+    if let Some(withdrawals) = &withdrawals {
+        let mut changeset = BTreeMap::new();
+        withdrawals_changeset(withdrawals, evm.db().expect("database is set"), &mut changeset)
+            .map_err(reth_interfaces::Error::from)?;
+        let changeset = changeset
+            .into_iter()
+            .map(|(address, account)| {
+                let storage = BTreeMap::new();
+                (address, AccountChangeSet { account, storage, wipe_storage: false })
+            })
+            .collect();
+        post_state.apply_changeset(changeset, BTreeMap::new());
+    }
+
+    header.gas_used = cumulative_gas_used;
+    header.state_root = state_root(db.as_ref(), &post_state)?;
+    header.transactions_root = proofs::calculate_transaction_root(body.iter());
+    header.receipts_root = proofs::calculate_receipt_root(receipts.iter());
+    header.logs_bloom = receipts_bloom(&receipts);
+    header.withdrawals_root = withdrawals.as_ref().map(proofs::calculate_withdrawals_root);
+    Ok(BuiltBlock { header, body, withdrawals, invalid })
+}
+
+/// Computes the state root after the changes on top of the state of the canonical head.
+///
+/// The changes are written to the hashed state in a transaction that is never committed, so the
+/// hashed state of the database is only ever updated by the merkle stage.
+fn state_root<DB: Database>(db: &DB, post_state: &ChainState) -> reth_interfaces::Result<H256> {
+    let tx = db.tx_mut()?;
+    for (address, account) in post_state.accounts() {
+        let hashed_address = keccak256(address);
+        match account {
+            Some(account) => tx.put::<tables::HashedAccount>(hashed_address, account)?,
+            None => {
+                tx.delete::<tables::HashedAccount>(hashed_address, None)?;
+                tx.delete::<tables::HashedStorage>(hashed_address, None)?;
+            }
+        }
+    }
+
+    let mut hashed_storage = tx.cursor_dup::<tables::HashedStorage>()?;
+    for (address, wiped, slots) in post_state.storage() {
+        let hashed_address = keccak256(address);
+        if wiped {
+            tx.delete::<tables::HashedStorage>(hashed_address, None)?;
+        }
+        for (slot, value) in slots {
+            let hashed_slot = keccak256(slot);
+            // duplicate table put will not override the old value
+            if let Some(old) = hashed_storage
+                .seek_by_key_subkey(hashed_address, hashed_slot)?
+                .filter(|entry| entry.key == hashed_slot)
+            {
+                tx.delete::<tables::HashedStorage>(hashed_address, Some(old))?;
+            }
+            if !value.is_zero() {
+                let entry = StorageEntry { key: hashed_slot, value: *value };
+                tx.put::<tables::HashedStorage>(hashed_address, entry)?;
+            }
+        }
+    }
+
+    let root = StateRoot::new(&tx).root()?;
+    // the changes are discarded with the transaction
+    drop(tx);
+    Ok(root)
+}
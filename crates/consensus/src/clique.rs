@@ -0,0 +1,638 @@
+//! Clique proof of authority consensus, see [EIP-225](https://eips.ethereum.org/EIPS/eip-225).
+//!
+//! The blocks of a clique chain are sealed by a set of authorized signers, who sign the header
+//! with the signature in its extra data. The signers vote to add or remove signers with the
+//! beneficiary and the nonce of the blocks they seal, and the signers are checkpointed in the extra
+//! data of every epoch block.
+use lru::LruCache;
+use parking_lot::Mutex;
+use reth_interfaces::consensus::Error;
+use reth_primitives::{
+    keccak256, proofs::EMPTY_LIST_HASH, Address, CliqueConfig, Header, SealedHeader, H256, U256,
+};
+use reth_provider::HeaderProvider;
+use reth_rlp::Encodable;
+use secp256k1::{
+    ecdsa::{RecoverableSignature, RecoveryId},
+    Message, PublicKey, SecretKey, SECP256K1,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    num::NonZeroUsize,
+    sync::Arc,
+};
+
+/// Number of extra data bytes reserved for the vanity of the signer.
+pub const EXTRA_VANITY: usize = 32;
+/// Number of extra data bytes reserved for the signature of the signer.
+pub const EXTRA_SEAL: usize = 65;
+/// Nonce of a vote to add the beneficiary to the signers.
+pub const NONCE_AUTH: u64 = u64::MAX;
+/// Nonce of a vote to remove the beneficiary from the signers.
+pub const NONCE_DROP: u64 = 0;
+/// Difficulty of a block sealed by the signer whose turn it is.
+pub const DIFF_IN_TURN: u64 = 2;
+/// Difficulty of a block sealed by any other signer.
+pub const DIFF_NO_TURN: u64 = 1;
+
+/// The number of snapshots that are kept in memory.
+const SNAPSHOT_CACHE_SIZE: usize = 128;
+
+/// Validates and seals the blocks of a clique chain.
+///
+/// The signers that are authorized to seal a block are tracked in a [Snapshot] for each validated
+/// block. Snapshots that are not cached are rebuilt from the signers of the last checkpoint and
+/// the headers after it.
+pub struct Clique {
+    /// The clique configuration of the chain.
+    config: CliqueConfig,
+    /// Client for reading the ancestors of the blocks whose snapshot is not cached.
+    client: Arc<dyn HeaderProvider>,
+    /// The snapshots of the latest validated blocks, by block hash.
+    snapshots: Mutex<LruCache<H256, Snapshot>>,
+}
+
+impl std::fmt::Debug for Clique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clique").field("config", &self.config).finish_non_exhaustive()
+    }
+}
+
+impl Clique {
+    /// Create a new clique consensus that reads the ancestors of the blocks from the client.
+    ///
+    /// # Panics
+    ///
+    /// If the epoch of the configuration is zero.
+    pub fn new(config: CliqueConfig, client: Arc<dyn HeaderProvider>) -> Self {
+        assert!(config.epoch > 0, "clique epoch is zero");
+        Self {
+            config,
+            client,
+            snapshots: Mutex::new(LruCache::new(
+                NonZeroUsize::new(SNAPSHOT_CACHE_SIZE).expect("cache size is not zero"),
+            )),
+        }
+    }
+
+    /// The clique configuration of the chain.
+    pub fn config(&self) -> &CliqueConfig {
+        &self.config
+    }
+
+    /// Validate the header on top of its parent.
+    ///
+    /// Only the rules that don't depend on the ancestors of the parent are validated, the signer
+    /// is verified by [Clique::validate_signer].
+    pub fn validate_header(
+        &self,
+        header: &SealedHeader,
+        parent: &SealedHeader,
+    ) -> Result<(), Error> {
+        validate_header_standalone(header, self.config.epoch)?;
+
+        if header.timestamp < parent.timestamp + self.config.period {
+            return Err(Error::CliqueTimestampTooEarly {
+                parent_timestamp: parent.timestamp,
+                timestamp: header.timestamp,
+                period: self.config.period,
+            })
+        }
+
+        recover_signer(header)?;
+        Ok(())
+    }
+
+    /// Verify that the signer of the header is authorized to seal it on top of the parent, and
+    /// that the difficulty and the checkpoint signers of the header match the signers.
+    ///
+    /// The snapshot of the parent has to be known, i.e. the parent was validated before or its
+    /// ancestors down to the last checkpoint are in the database. The headers are downloaded in
+    /// reverse, so the downloaded headers are verified in ascending order once they are stored.
+    pub fn validate_signer(
+        &self,
+        header: &SealedHeader,
+        parent: &SealedHeader,
+    ) -> Result<(), Error> {
+        let signer = recover_signer(header)?;
+        let mut snapshot =
+            self.snapshot(parent)?.ok_or(Error::ParentUnknown { hash: parent.hash() })?;
+
+        if self.is_checkpoint(header.number) && checkpoint_signers(header) != snapshot.signers {
+            return Err(Error::CliqueCheckpointSignersDiff)
+        }
+        snapshot.verify_signer(header.number, signer)?;
+        let expected = snapshot.difficulty(header.number, &signer);
+        if header.difficulty != expected {
+            return Err(Error::CliqueDifficultyDiff { got: header.difficulty, expected })
+        }
+
+        snapshot.apply(header, signer, self.config.epoch);
+        self.snapshots.lock().put(header.hash(), snapshot);
+        Ok(())
+    }
+
+    /// Prepare the header to be sealed by the signer on top of the parent.
+    ///
+    /// Sets the difficulty and the extra data of the header, keeping its vanity and leaving the
+    /// signature empty. The signer doesn't vote, so the beneficiary and the nonce are reset. The
+    /// timestamp is raised to at least the period after the parent.
+    ///
+    /// Returns an error if the signer is not allowed to seal the block, e.g. because it sealed
+    /// one of the recent blocks.
+    pub fn prepare(
+        &self,
+        header: &mut Header,
+        parent: &SealedHeader,
+        signer: Address,
+    ) -> Result<(), Error> {
+        let snapshot =
+            self.snapshot(parent)?.ok_or(Error::ParentUnknown { hash: parent.hash() })?;
+        snapshot.verify_signer(header.number, signer)?;
+
+        header.difficulty = snapshot.difficulty(header.number, &signer);
+        header.beneficiary = Address::zero();
+        header.nonce = NONCE_DROP;
+        header.mix_hash = H256::zero();
+        header.ommers_hash = EMPTY_LIST_HASH;
+        header.timestamp = header.timestamp.max(parent.timestamp + self.config.period);
+
+        let mut extra_data = vec![0; EXTRA_VANITY];
+        let vanity = header.extra_data.len().min(EXTRA_VANITY);
+        extra_data[..vanity].copy_from_slice(&header.extra_data[..vanity]);
+        if self.is_checkpoint(header.number) {
+            for signer in &snapshot.signers {
+                extra_data.extend_from_slice(signer.as_bytes());
+            }
+        }
+        extra_data.extend_from_slice(&[0; EXTRA_SEAL]);
+        header.extra_data = extra_data.into();
+        Ok(())
+    }
+
+    /// Sign the [prepared](Self::prepare) header.
+    pub fn seal(&self, mut header: Header, signer: &CliqueSigner) -> SealedHeader {
+        let signature = signer.sign(seal_hash(&header));
+        let mut extra_data = header.extra_data.to_vec();
+        let signature_start = extra_data.len() - EXTRA_SEAL;
+        extra_data[signature_start..].copy_from_slice(&signature);
+        header.extra_data = extra_data.into();
+        header.seal()
+    }
+
+    /// Returns the snapshot of the signers at the block.
+    ///
+    /// Returns `None` if the snapshot is not cached and the ancestors of the block down to the last
+    /// checkpoint are unknown, and an error if they can't be read.
+    pub fn snapshot(&self, header: &SealedHeader) -> Result<Option<Snapshot>, Error> {
+        let mut headers = Vec::new();
+        let mut current = header.clone();
+        let mut snapshot = loop {
+            if let Some(snapshot) = self.snapshots.lock().get(&current.hash()) {
+                break snapshot.clone()
+            }
+            // The checkpoint was validated before, so its signers can be trusted. The signers of
+            // the blocks right before the checkpoint are not known though and may sign again.
+            if self.is_checkpoint(current.number) {
+                break Snapshot::checkpoint(&current)?
+            }
+
+            let parent = match self.client.header(&current.parent_hash) {
+                Ok(Some(parent)) => SealedHeader::new(parent, current.parent_hash),
+                Ok(None) => return Ok(None),
+                Err(err) => {
+                    return Err(Error::CliqueSnapshotUnavailable {
+                        hash: header.hash(),
+                        error: err.to_string(),
+                    })
+                }
+            };
+            headers.push(std::mem::replace(&mut current, parent));
+        };
+
+        for header in headers.iter().rev() {
+            let signer = recover_signer(header)?;
+            snapshot.apply(header, signer, self.config.epoch);
+        }
+        if !headers.is_empty() {
+            self.snapshots.lock().put(snapshot.hash, snapshot.clone());
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Returns `true` if the block checkpoints the signers.
+    fn is_checkpoint(&self, number: u64) -> bool {
+        number % self.config.epoch == 0
+    }
+}
+
+/// The signers that are authorized to seal the next block, and their pending votes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The number of the block.
+    pub number: u64,
+    /// The hash of the block.
+    pub hash: H256,
+    /// The authorized signers.
+    pub signers: BTreeSet<Address>,
+    /// The signers of the recent blocks by block number, which can't sign again yet.
+    pub recents: BTreeMap<u64, Address>,
+    /// The pending votes in the order they were cast.
+    votes: Vec<Vote>,
+    /// The number of pending votes for each address that is voted on.
+    tally: HashMap<Address, Tally>,
+}
+
+/// A vote of a signer to add or remove an address from the signers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Vote {
+    signer: Address,
+    address: Address,
+    authorize: bool,
+}
+
+/// The pending votes to add or remove an address from the signers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tally {
+    authorize: bool,
+    votes: usize,
+}
+
+impl Snapshot {
+    /// Create the snapshot of a checkpoint block from the signers in its extra data.
+    fn checkpoint(header: &SealedHeader) -> Result<Self, Error> {
+        let len = header.extra_data.len();
+        if len < EXTRA_VANITY + EXTRA_SEAL ||
+            (len - EXTRA_VANITY - EXTRA_SEAL) % Address::len_bytes() != 0
+        {
+            return Err(Error::CliqueExtraDataInvalid { len })
+        }
+        Ok(Self {
+            number: header.number,
+            hash: header.hash(),
+            signers: checkpoint_signers(header),
+            recents: BTreeMap::new(),
+            votes: Vec::new(),
+            tally: HashMap::new(),
+        })
+    }
+
+    /// Returns `true` if it is the turn of the signer to seal the block.
+    pub fn in_turn(&self, number: u64, signer: &Address) -> bool {
+        self.signers
+            .iter()
+            .position(|s| s == signer)
+            .map_or(false, |index| number % self.signers.len() as u64 == index as u64)
+    }
+
+    /// The difficulty of the block if sealed by the signer.
+    pub fn difficulty(&self, number: u64, signer: &Address) -> U256 {
+        U256::from(if self.in_turn(number, signer) { DIFF_IN_TURN } else { DIFF_NO_TURN })
+    }
+
+    /// The number of consecutive blocks in which a signer may only seal one.
+    fn signer_limit(&self) -> u64 {
+        self.signers.len() as u64 / 2 + 1
+    }
+
+    /// Verify that the signer is authorized and did not seal any of the recent blocks.
+    fn verify_signer(&self, number: u64, signer: Address) -> Result<(), Error> {
+        if !self.signers.contains(&signer) {
+            return Err(Error::CliqueUnauthorizedSigner { signer })
+        }
+        let limit = self.signer_limit();
+        if self
+            .recents
+            .iter()
+            .any(|(seen, recent)| *recent == signer && (number < limit || *seen > number - limit))
+        {
+            return Err(Error::CliqueRecentlySigned { signer })
+        }
+        Ok(())
+    }
+
+    /// Apply the block sealed by the signer, which has been verified to be allowed to seal it.
+    fn apply(&mut self, header: &SealedHeader, signer: Address, epoch: u64) {
+        let number = header.number;
+        // the votes are reset on every checkpoint
+        if number % epoch == 0 {
+            self.votes.clear();
+            self.tally.clear();
+        }
+        let limit = self.signer_limit();
+        if number >= limit {
+            self.recents.remove(&(number - limit));
+        }
+        self.recents.insert(number, signer);
+
+        // only the latest vote of a signer for an address counts
+        let address = header.beneficiary;
+        if let Some(index) =
+            self.votes.iter().position(|vote| vote.signer == signer && vote.address == address)
+        {
+            let vote = self.votes.remove(index);
+            self.uncast(vote.address, vote.authorize);
+        }
+        let authorize = header.nonce == NONCE_AUTH;
+        if self.cast(address, authorize) {
+            self.votes.push(Vote { signer, address, authorize });
+        }
+
+        // the vote passes once the majority of the signers agrees
+        if let Some(tally) = self.tally.get(&address).copied() {
+            if tally.votes > self.signers.len() / 2 {
+                if tally.authorize {
+                    self.signers.insert(address);
+                } else {
+                    self.signers.remove(&address);
+                    // one less signer may release a recent signer
+                    let limit = self.signer_limit();
+                    if number >= limit {
+                        self.recents.remove(&(number - limit));
+                    }
+                    // the votes of the removed signer don't count anymore
+                    let removed = self
+                        .votes
+                        .iter()
+                        .filter(|vote| vote.signer == address)
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    for vote in removed {
+                        self.uncast(vote.address, vote.authorize);
+                    }
+                    self.votes.retain(|vote| vote.signer != address);
+                }
+                self.votes.retain(|vote| vote.address != address);
+                self.tally.remove(&address);
+            }
+        }
+
+        self.number = number;
+        self.hash = header.hash();
+    }
+
+    /// Count the vote if it would change the signers, i.e. it adds an address that is not a
+    /// signer or removes one that is.
+    fn cast(&mut self, address: Address, authorize: bool) -> bool {
+        if self.signers.contains(&address) == authorize {
+            return false
+        }
+        self.tally.entry(address).or_insert(Tally { authorize, votes: 0 }).votes += 1;
+        true
+    }
+
+    /// Remove a counted vote.
+    fn uncast(&mut self, address: Address, authorize: bool) {
+        if let Some(tally) = self.tally.get_mut(&address) {
+            if tally.authorize != authorize {
+                return
+            }
+            if tally.votes > 1 {
+                tally.votes -= 1;
+            } else {
+                self.tally.remove(&address);
+            }
+        }
+    }
+}
+
+/// The key of a clique signer, which seals blocks.
+pub struct CliqueSigner {
+    secret_key: SecretKey,
+    address: Address,
+}
+
+impl std::fmt::Debug for CliqueSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CliqueSigner").field("address", &self.address).finish_non_exhaustive()
+    }
+}
+
+impl CliqueSigner {
+    /// Create a signer from its secret key.
+    pub fn new(secret_key: SecretKey) -> Self {
+        let address = public_key_to_address(&PublicKey::from_secret_key(SECP256K1, &secret_key));
+        Self { secret_key, address }
+    }
+
+    /// The address of the signer.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Sign the hash, returning the signature in the format of the extra data.
+    fn sign(&self, hash: H256) -> [u8; EXTRA_SEAL] {
+        let message = Message::from_slice(hash.as_bytes()).expect("hash is 32 bytes");
+        let (recovery_id, signature) =
+            SECP256K1.sign_ecdsa_recoverable(&message, &self.secret_key).serialize_compact();
+        let mut seal = [0; EXTRA_SEAL];
+        seal[..64].copy_from_slice(&signature);
+        seal[64] = recovery_id.to_i32() as u8;
+        seal
+    }
+}
+
+/// Validate the clique fields of the header that don't depend on other blocks.
+pub fn validate_header_standalone(header: &SealedHeader, epoch: u64) -> Result<(), Error> {
+    let checkpoint = header.number % epoch == 0;
+    if checkpoint && !header.beneficiary.is_zero() {
+        return Err(Error::CliqueCheckpointBeneficiary)
+    }
+    if (header.nonce != NONCE_AUTH && header.nonce != NONCE_DROP) ||
+        (checkpoint && header.nonce != NONCE_DROP)
+    {
+        return Err(Error::CliqueInvalidVote { nonce: header.nonce })
+    }
+
+    // only checkpoints list the signers between the vanity and the signature
+    let len = header.extra_data.len();
+    if len < EXTRA_VANITY + EXTRA_SEAL {
+        return Err(Error::CliqueExtraDataInvalid { len })
+    }
+    let signers_len = len - EXTRA_VANITY - EXTRA_SEAL;
+    if (!checkpoint && signers_len != 0) || signers_len % Address::len_bytes() != 0 {
+        return Err(Error::CliqueExtraDataInvalid { len })
+    }
+
+    if !header.mix_hash.is_zero() {
+        return Err(Error::CliqueMixHashIsNotZero)
+    }
+    if header.ommers_hash != EMPTY_LIST_HASH {
+        return Err(Error::CliqueOmmerRootIsNotEmpty)
+    }
+    if header.difficulty != U256::from(DIFF_IN_TURN) &&
+        header.difficulty != U256::from(DIFF_NO_TURN)
+    {
+        return Err(Error::CliqueInvalidDifficulty { difficulty: header.difficulty })
+    }
+
+    Ok(())
+}
+
+/// The hash the signer signs, which is the hash of the header without the signature.
+pub fn seal_hash(header: &Header) -> H256 {
+    let mut header = header.clone();
+    header.extra_data.truncate(header.extra_data.len().saturating_sub(EXTRA_SEAL));
+    let mut buf = Vec::with_capacity(header.length());
+    header.encode(&mut buf);
+    keccak256(buf)
+}
+
+/// Recover the signer of the header from the signature in its extra data.
+pub fn recover_signer(header: &Header) -> Result<Address, Error> {
+    let len = header.extra_data.len();
+    if len < EXTRA_SEAL {
+        return Err(Error::CliqueExtraDataInvalid { len })
+    }
+    let signature = &header.extra_data[len - EXTRA_SEAL..];
+    let recovery_id =
+        RecoveryId::from_i32(signature[64] as i32).map_err(|_| Error::CliqueSignerRecoveryError)?;
+    let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id)
+        .map_err(|_| Error::CliqueSignerRecoveryError)?;
+    let message = Message::from_slice(seal_hash(header).as_bytes())
+        .map_err(|_| Error::CliqueSignerRecoveryError)?;
+    let public = SECP256K1
+        .recover_ecdsa(&message, &signature)
+        .map_err(|_| Error::CliqueSignerRecoveryError)?;
+    Ok(public_key_to_address(&public))
+}
+
+/// The signers listed in the extra data of a checkpoint.
+fn checkpoint_signers(header: &Header) -> BTreeSet<Address> {
+    let len = header.extra_data.len();
+    header.extra_data[EXTRA_VANITY..len - EXTRA_SEAL]
+        .chunks_exact(Address::len_bytes())
+        .map(Address::from_slice)
+        .collect()
+}
+
+/// The address of the public key.
+fn public_key_to_address(public: &PublicKey) -> Address {
+    let hash = keccak256(&public.serialize_uncompressed()[1..]);
+    Address::from_slice(&hash[12..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::TestApi;
+
+    const EPOCH: u64 = 30_000;
+
+    fn signer(seed: u8) -> CliqueSigner {
+        CliqueSigner::new(SecretKey::from_slice(&[seed; 32]).unwrap())
+    }
+
+    fn genesis(signers: &[&CliqueSigner]) -> SealedHeader {
+        let mut addresses = signers.iter().map(|signer| signer.address()).collect::<Vec<_>>();
+        addresses.sort();
+        let mut extra_data = vec![0; EXTRA_VANITY];
+        for address in addresses {
+            extra_data.extend_from_slice(address.as_bytes());
+        }
+        extra_data.extend_from_slice(&[0; EXTRA_SEAL]);
+        Header { extra_data: extra_data.into(), difficulty: U256::from(1), ..Default::default() }
+            .seal()
+    }
+
+    fn clique() -> Clique {
+        Clique::new(CliqueConfig { period: 1, epoch: EPOCH }, Arc::new(TestApi::default()))
+    }
+
+    fn seal(
+        clique: &Clique,
+        parent: &SealedHeader,
+        signer: &CliqueSigner,
+    ) -> Result<SealedHeader, Error> {
+        let mut header = Header {
+            parent_hash: parent.hash(),
+            number: parent.number + 1,
+            timestamp: parent.timestamp + 1,
+            ..Default::default()
+        };
+        clique.prepare(&mut header, parent, signer.address())?;
+        Ok(clique.seal(header, signer))
+    }
+
+    #[test]
+    fn seal_and_validate() {
+        let (a, b) = (signer(1), signer(2));
+        let clique = clique();
+        let genesis = genesis(&[&a, &b]);
+
+        let block = seal(&clique, &genesis, &a).unwrap();
+        assert_eq!(recover_signer(&block), Ok(a.address()));
+        assert_eq!(clique.validate_header(&block, &genesis), Ok(()));
+        assert_eq!(clique.validate_signer(&block, &genesis), Ok(()));
+
+        // a signer can't seal two consecutive blocks of two signers
+        assert_eq!(
+            seal(&clique, &block, &a),
+            Err(Error::CliqueRecentlySigned { signer: a.address() })
+        );
+        let next = seal(&clique, &block, &b).unwrap();
+        assert_eq!(clique.validate_header(&next, &block), Ok(()));
+        assert_eq!(clique.validate_signer(&next, &block), Ok(()));
+
+        // only the signers of the snapshot are authorized
+        let c = signer(3);
+        assert_eq!(
+            seal(&clique, &next, &c),
+            Err(Error::CliqueUnauthorizedSigner { signer: c.address() })
+        );
+    }
+
+    #[test]
+    fn validate_signer_of_downloaded_headers() {
+        let (a, b, c) = (signer(1), signer(2), signer(3));
+        let sealer = clique();
+        let genesis = genesis(&[&a, &b]);
+        let block = seal(&sealer, &genesis, &a).unwrap();
+        let forged = Header {
+            parent_hash: block.hash(),
+            ommers_hash: EMPTY_LIST_HASH,
+            number: 2,
+            timestamp: block.timestamp + 1,
+            difficulty: U256::from(DIFF_NO_TURN),
+            extra_data: vec![0; EXTRA_VANITY + EXTRA_SEAL].into(),
+            ..Default::default()
+        };
+        let forged = sealer.seal(forged, &c);
+
+        // the headers are valid on their own while the ancestors are unknown
+        let clique = clique();
+        assert_eq!(clique.validate_header(&forged, &block), Ok(()));
+        assert_eq!(
+            clique.validate_signer(&forged, &block),
+            Err(Error::ParentUnknown { hash: block.hash() })
+        );
+
+        // the signer is rejected once the headers are verified in ascending order
+        assert_eq!(clique.validate_signer(&block, &genesis), Ok(()));
+        assert_eq!(
+            clique.validate_signer(&forged, &block),
+            Err(Error::CliqueUnauthorizedSigner { signer: c.address() })
+        );
+    }
+
+    #[test]
+    fn vote_in_signer() {
+        let (a, b) = (signer(1), signer(2));
+        let genesis = genesis(&[&a]);
+        let mut snapshot = Snapshot::checkpoint(&genesis).unwrap();
+
+        // a single signer is the majority
+        let vote =
+            Header { number: 1, beneficiary: b.address(), nonce: NONCE_AUTH, ..Default::default() };
+        snapshot.apply(&vote.seal(), a.address(), EPOCH);
+        assert_eq!(snapshot.signers, BTreeSet::from([a.address(), b.address()]));
+        assert!(snapshot.tally.is_empty());
+
+        // both signers need to agree to drop a signer
+        let vote =
+            Header { number: 2, beneficiary: b.address(), nonce: NONCE_DROP, ..Default::default() };
+        snapshot.apply(&vote.seal(), a.address(), EPOCH);
+        assert_eq!(snapshot.signers.len(), 2);
+        assert_eq!(snapshot.tally[&b.address()], Tally { authorize: false, votes: 1 });
+    }
+}
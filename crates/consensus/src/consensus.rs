@@ -1,7 +1,8 @@
 //! Consensus for ethereum network
-use crate::{verification, Config};
+use crate::{clique::Clique, ethash::Ethash, verification};
 use reth_interfaces::consensus::{Consensus, Error, ForkchoiceState};
-use reth_primitives::{BlockNumber, SealedBlock, SealedHeader, H256};
+use reth_primitives::{BlockNumber, ChainSpec, Hardfork, SealedBlock, SealedHeader, H256};
+use std::sync::Arc;
 use tokio::sync::{watch, watch::error::SendError};
 
 /// Ethereum beacon consensus
@@ -11,23 +12,46 @@ use tokio::sync::{watch, watch::error::SendError};
 pub struct BeaconConsensus {
     /// Watcher over the forkchoice state
     channel: (watch::Sender<ForkchoiceState>, watch::Receiver<ForkchoiceState>),
-    /// Configuration
-    config: Config,
+    /// The chain specification, e.g. the fork activations
+    chain_spec: ChainSpec,
+    /// Ethash seal verifier for pre-merge headers, seals are not verified if unset.
+    ethash: Option<Ethash>,
+    /// Clique validator for chains that are sealed by clique instead of proof of work.
+    clique: Option<Arc<Clique>>,
 }
 
 impl BeaconConsensus {
     /// Create a new instance of [BeaconConsensus]
-    pub fn new(config: Config) -> Self {
+    pub fn new(chain_spec: ChainSpec) -> Self {
         Self {
             channel: watch::channel(ForkchoiceState {
                 head_block_hash: H256::zero(),
                 finalized_block_hash: H256::zero(),
                 safe_block_hash: H256::zero(),
             }),
-            config,
+            chain_spec,
+            ethash: None,
+            clique: None,
         }
     }
 
+    /// Verify the Ethash seal of pre-merge headers.
+    pub fn with_ethash(mut self, ethash: Ethash) -> Self {
+        self.ethash = Some(ethash);
+        self
+    }
+
+    /// Validate the clique seal of pre-merge headers instead of the Ethash seal.
+    pub fn with_clique(mut self, clique: Arc<Clique>) -> Self {
+        self.clique = Some(clique);
+        self
+    }
+
+    /// The clique validator, if the chain is sealed by clique.
+    pub fn clique(&self) -> Option<&Arc<Clique>> {
+        self.clique.as_ref()
+    }
+
     /// Notifies all listeners of the latest [ForkchoiceState].
     pub fn notify_fork_choice_state(
         &self,
@@ -43,13 +67,32 @@ impl Consensus for BeaconConsensus {
     }
 
     fn validate_header(&self, header: &SealedHeader, parent: &SealedHeader) -> Result<(), Error> {
-        verification::validate_header_standalone(header, &self.config)?;
-        verification::validate_header_regarding_parent(parent, header, &self.config)?;
+        verification::validate_header_standalone(header, &self.chain_spec)?;
+        verification::validate_header_regarding_parent(parent, header, &self.chain_spec)?;
 
-        if header.number < self.config.paris_block {
-            // TODO Consensus checks for old blocks:
-            //  * difficulty, mix_hash & nonce aka PoW stuff
-            // low priority as syncing is done in reverse order
+        if self.chain_spec.is_fork_active_at_block(Hardfork::Paris, header.number) {
+            verification::validate_header_post_merge(parent, header)?;
+        } else if let Some(clique) = &self.clique {
+            clique.validate_header(header, parent)?;
+        } else if self.chain_spec.clique.is_none() {
+            verification::validate_header_difficulty(parent, header, &self.chain_spec)?;
+            if let Some(ethash) = &self.ethash {
+                ethash.verify_seal(header)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_header_with_ancestors(
+        &self,
+        header: &SealedHeader,
+        parent: &SealedHeader,
+    ) -> Result<(), Error> {
+        if self.chain_spec.is_fork_active_at_block(Hardfork::Paris, header.number) {
+            return Ok(())
+        }
+        if let Some(clique) = &self.clique {
+            clique.validate_signer(header, parent)?;
         }
         Ok(())
     }
@@ -59,6 +102,6 @@ impl Consensus for BeaconConsensus {
     }
 
     fn has_block_reward(&self, block_num: BlockNumber) -> bool {
-        block_num < self.config.paris_block
+        self.chain_spec.has_block_reward(block_num)
     }
 }
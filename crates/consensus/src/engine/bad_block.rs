@@ -0,0 +1,123 @@
+//! Forensics dumps of the payloads that fail validation or execution.
+
+use reth_executor::{
+    executor::{self, ExecutionResult},
+    revm_wrap::{State, SubState},
+};
+use reth_primitives::{proofs, Bloom, ChainSpec, SealedBlock};
+use reth_provider::StateProvider;
+use reth_rpc_types::engine::ExecutionPayload;
+use serde_json::{json, Value};
+use std::{
+    fmt::{Display, Write},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Dumps the invalid block to `<dir>/<number>_<hash>/` and returns the path of the dump.
+///
+/// The dump contains:
+///
+/// - `payload.json`: the block as an execution payload, which can be sent to `engine_newPayload`
+///   to reproduce the failure.
+/// - `error.txt`: why the block is invalid.
+/// - `receipts.json`: the receipts of executing the block locally, and how the receipts root, the
+///   logs bloom and the gas used differ from the header. Only if the block can be executed.
+/// - `trace.txt`: the state changes of every transaction of the local execution. Only if the
+///   block can be executed.
+///
+/// The block is executed on top of the `state` of its parent, which is `None` if the state is not
+/// available, e.g. because the parent is not canonical.
+pub(crate) fn dump_bad_block<SP: StateProvider>(
+    dir: &Path,
+    block: &SealedBlock,
+    error: &dyn Display,
+    state: Option<(SP, &ChainSpec)>,
+) -> io::Result<PathBuf> {
+    let path = dir.join(format!("{}_{:?}", block.number, block.hash()));
+    fs::create_dir_all(&path)?;
+
+    let payload = ExecutionPayload::from(block.clone());
+    fs::write(path.join("payload.json"), serde_json::to_vec_pretty(&payload)?)?;
+    fs::write(path.join("error.txt"), format!("{error}\n"))?;
+
+    let Some((state, chain_spec)) = state else { return Ok(path) };
+    let transactions = block.body.iter().map(|transaction| transaction.try_ecrecovered());
+    let Some(transactions) = transactions.collect::<Option<Vec<_>>>() else { return Ok(path) };
+    let ommers = block.ommers.iter().map(|ommer| ommer.as_ref().clone()).collect::<Vec<_>>();
+
+    // revm can exceed the default stack size, see the execution stage
+    let result = std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .stack_size(50 * 1024 * 1024)
+            .spawn_scoped(scope, || {
+                executor::execute(
+                    &block.header,
+                    &transactions,
+                    &ommers,
+                    block.withdrawals.as_deref(),
+                    chain_spec,
+                    SubState::new(State::new(state)),
+                )
+            })
+            .expect("Expects that thread name is not null");
+        handle.join().expect("Expects for thread to not panic")
+    });
+    match result {
+        Ok(result) => {
+            fs::write(
+                path.join("receipts.json"),
+                serde_json::to_vec_pretty(&receipts_diff(block, &result))?,
+            )?;
+            fs::write(path.join("trace.txt"), state_trace(block, &result))?;
+        }
+        Err(err) => {
+            fs::write(path.join("error.txt"), format!("{error}\nLocal execution: {err}\n"))?;
+        }
+    }
+
+    Ok(path)
+}
+
+/// The receipts of the local execution, and the values of the header they are verified against.
+fn receipts_diff(block: &SealedBlock, result: &ExecutionResult) -> Value {
+    let receipts = result.changesets.iter().map(|changeset| &changeset.receipt);
+    let receipts_root = proofs::calculate_receipt_root(receipts.clone());
+    let logs_bloom = receipts.clone().fold(Bloom::zero(), |bloom, receipt| bloom | receipt.bloom);
+    let gas_used = receipts.clone().last().map(|receipt| receipt.cumulative_gas_used);
+
+    json!({
+        "receiptsRoot": { "got": receipts_root, "expected": block.receipts_root },
+        "logsBloom": { "got": logs_bloom, "expected": block.logs_bloom },
+        "gasUsed": { "got": gas_used.unwrap_or_default(), "expected": block.gas_used },
+        "receipts": block.body.iter().zip(receipts).map(|(transaction, receipt)| json!({
+            "transactionHash": transaction.hash,
+            "success": receipt.success,
+            "cumulativeGasUsed": receipt.cumulative_gas_used,
+            "logs": receipt.logs.iter().map(|log| json!({
+                "address": log.address,
+                "topics": log.topics,
+                "data": log.data,
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// The state changes of every transaction and of the block reward, including the block storage.
+fn state_trace(block: &SealedBlock, result: &ExecutionResult) -> String {
+    let mut trace = String::new();
+    for (transaction, changeset) in block.body.iter().zip(&result.changesets) {
+        let _ = writeln!(trace, "Transaction {:?}", transaction.hash);
+        let _ = writeln!(trace, "{:#?}", changeset.changeset);
+        let _ = writeln!(trace, "New bytecodes: {:?}", changeset.new_bytecodes.keys());
+    }
+    if let Some(block_reward) = &result.block_reward {
+        let _ = writeln!(trace, "Block reward");
+        let _ = writeln!(trace, "{block_reward:#?}");
+    }
+    if !result.block_storage.is_empty() {
+        let _ = writeln!(trace, "Block storage");
+        let _ = writeln!(trace, "{:#?}", result.block_storage);
+    }
+    trace
+}
@@ -4,7 +4,7 @@ use thiserror::Error;
 /// The Engine API result type
 pub type EngineApiResult<Ok> = Result<Ok, EngineApiError>;
 
-/// Error returned by [`EngineApi`][crate::engine::EngineApi]
+/// Error returned by the [`BeaconConsensusEngine`][crate::engine::BeaconConsensusEngine]
 #[derive(Error, Debug)]
 pub enum EngineApiError {
     /// Invalid payload extra data.
@@ -29,6 +29,18 @@ pub enum EngineApiError {
         /// Latest available timestamp.
         latest: u64,
     },
+    /// The payload or the payload attributes have withdrawals before Shanghai or none after.
+    #[error("Withdrawals must be present exactly after Shanghai, timestamp: {timestamp}")]
+    PayloadWithdrawals {
+        /// The timestamp of the payload.
+        timestamp: u64,
+    },
+    /// The payload or the payload attributes are of a fork that is not supported yet.
+    #[error("Unsupported fork at timestamp {timestamp}")]
+    UnsupportedFork {
+        /// The timestamp of the payload.
+        timestamp: u64,
+    },
     /// Received pre-merge payload.
     #[error("Received pre-merge payload.")]
     PayloadPreMerge,
@@ -64,4 +76,7 @@ pub enum EngineApiError {
     /// API encountered an internal error.
     #[error(transparent)]
     Internal(#[from] reth_interfaces::Error),
+    /// The blockchain tree or the pipeline failed to sync the chain.
+    #[error(transparent)]
+    Sync(#[from] reth_stages::BlockchainTreeError),
 }
@@ -1,59 +1,74 @@
-use futures::StreamExt;
+use crate::{
+    builder::{build_block, BestPoolTransactions, BuiltBlock},
+    verification::calculate_next_block_base_fee,
+    BeaconConsensus,
+};
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt, StreamExt,
+};
+use lru::LruCache;
+use reth_db::database::Database;
 use reth_interfaces::consensus::ForkchoiceState;
 use reth_primitives::{
+    keccak256,
     proofs::{self, EMPTY_LIST_HASH},
-    rpc::BlockId,
-    Header, SealedBlock, TransactionSigned, H64,
+    BlockHash, BlockNumber, ChainSpec, Hardfork, Header, SealedBlock, TransactionSigned,
+    TransactionSignedEcRecovered, Withdrawal, H256, H64,
+};
+use reth_provider::{
+    write_fork_choice_state, BlockProvider, HeaderProvider, ProviderImpl, StateProviderFactory,
 };
-use reth_provider::{BlockProvider, HeaderProvider};
 use reth_rlp::Decodable;
 use reth_rpc_types::engine::{
     ExecutionPayload, ForkchoiceUpdated, PayloadAttributes, PayloadStatus, PayloadStatusEnum,
     TransitionConfiguration,
 };
+use reth_stages::{
+    tree::{BlockStatus, BlockchainTree},
+    BlockchainTreeError, Pipeline,
+};
+use reth_tasks::shutdown::Shutdown;
+use reth_transaction_pool::TransactionPool;
 use std::{
-    collections::HashMap,
     future::Future,
+    num::NonZeroUsize,
+    path::PathBuf,
     pin::Pin,
     sync::Arc,
-    task::{ready, Context, Poll},
+    task::{Context, Poll},
 };
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc::UnboundedReceiver, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::*;
 
+mod bad_block;
 mod error;
-use crate::Config;
 pub use error::{EngineApiError, EngineApiResult};
 
 /// The Engine API response sender
 pub type EngineApiSender<Ok> = oneshot::Sender<EngineApiResult<Ok>>;
 
-/// Consensus engine API trait.
-pub trait ConsensusEngine {
-    /// Retrieves payload from local cache.
-    fn get_payload(&self, payload_id: H64) -> Option<ExecutionPayload>;
+/// The number of invalid blocks that are remembered, so their descendants are rejected right away.
+const INVALID_HEADERS_CACHE_SIZE: usize = 512;
 
-    /// Receives a payload to validate and execute.
-    fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiResult<PayloadStatus>;
+/// The number of built payloads that are kept for `engine_getPayload`.
+const PAYLOADS_CACHE_SIZE: usize = 16;
 
-    /// Updates the fork choice state
-    fn fork_choice_updated(
-        &self,
-        fork_choice_state: ForkchoiceState,
-        payload_attributes: Option<PayloadAttributes>,
-    ) -> EngineApiResult<ForkchoiceUpdated>;
+/// Returns the best pending transactions a payload is built from.
+type PayloadTransactions =
+    Arc<dyn Fn() -> BestPoolTransactions<TransactionSignedEcRecovered> + Send + Sync>;
 
-    /// Verifies the transition configuration between execution and consensus clients.
-    fn exchange_transition_configuration(
-        &self,
-        config: TransitionConfiguration,
-    ) -> EngineApiResult<TransitionConfiguration>;
-}
+/// A payload that is built in the background, `None` if building it failed.
+type PayloadFuture = Shared<BoxFuture<'static, Option<ExecutionPayload>>>;
 
-/// Message type for communicating with [EthConsensusEngine]
+/// Message type for communicating with [BeaconConsensusEngine]
 pub enum EngineMessage {
     /// New payload message
     NewPayload(ExecutionPayload, EngineApiSender<PayloadStatus>),
+    /// New block message for the blocks that are sealed locally, e.g. by clique, which can't be
+    /// converted from an execution payload.
+    NewBlock(SealedBlock, EngineApiSender<PayloadStatus>),
     /// Get payload message
     GetPayload(H64, EngineApiSender<ExecutionPayload>),
     /// Forkchoice updated message
@@ -69,31 +84,173 @@ pub enum EngineMessage {
     ),
 }
 
-/// The consensus engine API implementation
-#[must_use = "EthConsensusEngine does nothing unless polled."]
-pub struct EthConsensusEngine<Client> {
-    /// Consensus configuration
-    config: Config,
-    client: Arc<Client>,
-    /// Placeholder for storing future blocks
-    local_store: HashMap<H64, ExecutionPayload>, // TODO: bound
-    // remote_store: HashMap<H64, ExecutionPayload>,
+/// The pipeline and the blockchain tree, which are moved into the running sync action and returned
+/// once it is done.
+struct ChainSync<DB: Database> {
+    pipeline: Pipeline<DB>,
+    tree: BlockchainTree<DB, BeaconConsensus>,
+}
+
+/// The outcome of a sync action, together with the pipeline and the tree it was run with.
+type SyncOutcome<DB> = (ChainSync<DB>, Result<(), BlockchainTreeError>);
+
+/// A sync action that is running in the background.
+struct RunningSync<DB: Database> {
+    /// The action, either a pass of the pipeline or making a side chain of the tree canonical.
+    action: BoxFuture<'static, SyncOutcome<DB>>,
+    /// The head of the fork choice state the action syncs to.
+    head: H256,
+    /// The fork choice update that is answered once a side chain is canonical.
+    pending: Option<PendingForkchoiceUpdate>,
+    /// Whether the action can be dropped on shutdown. This is the case for a pass of the
+    /// pipeline, which only loses the progress of its current batch that is not committed yet.
+    interruptible: bool,
+}
+
+/// A fork choice update that is answered once its head is canonical.
+struct PendingForkchoiceUpdate {
+    /// The attributes of the payload to build on top of the head, if requested.
+    attributes: Option<PayloadAttributes>,
+    /// The response to the update.
+    tx: EngineApiSender<ForkchoiceUpdated>,
+}
+
+/// The consensus engine that drives the chain from the messages of the Engine API.
+///
+/// The engine connects the consensus layer to the [Pipeline] and the [BlockchainTree]:
+///
+/// - New payloads are inserted into the tree, which validates and executes them on top of their
+///   parent. The status of the payload is reported back once it is executed, or as `SYNCING` if
+///   its parent is unknown.
+/// - Fork choice updates make the head canonical. If the head is in the tree, i.e. close to the tip
+///   of the canonical chain, the side chain of the head is committed by the tree. Otherwise the
+///   node is far behind and the head is announced to the pipeline, which downloads and executes
+///   the missing blocks, while the update is answered as `SYNCING`.
+///
+/// Only one of these sync actions runs at a time. While the pipeline syncs, new payloads are
+/// answered as `SYNCING` and the latest fork choice state is synced once the pipeline is done.
+///
+/// Blocks that fail validation or execution are remembered together with their latest valid
+/// ancestor, so the payloads and fork choice updates that build on them are rejected right away.
+///
+/// If a fork choice update comes with payload attributes, a payload is built on top of the head
+/// once it is canonical, from the transactions of
+/// [BeaconConsensusEngine::with_payload_transactions]. The payload is built in the background and
+/// returned by `engine_getPayload` under the id the update is answered with.
+///
+/// The engine stops once the channel of the messages is closed or the shutdown is requested, see
+/// [BeaconConsensusEngine::with_shutdown]. A running pass of the pipeline is dropped on shutdown,
+/// while a side chain that is being committed by the tree is committed first.
+#[must_use = "BeaconConsensusEngine does nothing unless polled."]
+pub struct BeaconConsensusEngine<DB: Database> {
+    /// The chain specification, e.g. the terminal total difficulty
+    chain_spec: ChainSpec,
+    /// The database of the canonical chain.
+    db: Arc<DB>,
+    /// Client for reading the canonical chain.
+    client: ProviderImpl<DB>,
+    /// The consensus the head of the fork choice is announced to, which is followed by the header
+    /// stage of the pipeline.
+    consensus: Arc<BeaconConsensus>,
+    /// The pipeline and the tree, `None` while a sync action is running.
+    sync: Option<ChainSync<DB>>,
+    /// The running sync action.
+    running: Option<RunningSync<DB>>,
+    /// The latest fork choice state of the consensus layer.
+    forkchoice_state: Option<ForkchoiceState>,
+    /// The latest valid ancestors of the invalid blocks, by the hash of the invalid block.
+    invalid_headers: LruCache<H256, H256>,
+    /// The directory the invalid payloads are dumped to, if enabled.
+    bad_blocks_dir: Option<PathBuf>,
+    /// The source of the transactions of the payloads.
+    payload_transactions: PayloadTransactions,
+    /// The payloads that are built or being built, by their id.
+    payloads: LruCache<H64, PayloadFuture>,
+    /// Resolves once the engine should shut down, if set.
+    shutdown: Option<Shutdown>,
     rx: UnboundedReceiverStream<EngineMessage>,
 }
 
-impl<Client: HeaderProvider + BlockProvider> EthConsensusEngine<Client> {
+impl<DB: Database> std::fmt::Debug for BeaconConsensusEngine<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BeaconConsensusEngine")
+            .field("syncing", &self.running.is_some())
+            .field("forkchoice_state", &self.forkchoice_state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<DB: Database + 'static> BeaconConsensusEngine<DB> {
+    /// Create a new engine that handles the messages of the Engine API.
+    ///
+    /// The tree has to validate blocks with the same consensus the head of the fork choice is
+    /// announced to, which has to be the consensus of the header stage of the pipeline.
+    pub fn new(
+        chain_spec: ChainSpec,
+        db: Arc<DB>,
+        consensus: Arc<BeaconConsensus>,
+        pipeline: Pipeline<DB>,
+        tree: BlockchainTree<DB, BeaconConsensus>,
+        rx: UnboundedReceiver<EngineMessage>,
+    ) -> Self {
+        Self {
+            chain_spec,
+            client: ProviderImpl::new(db.clone()),
+            db,
+            consensus,
+            sync: Some(ChainSync { pipeline, tree }),
+            running: None,
+            forkchoice_state: None,
+            invalid_headers: LruCache::new(
+                NonZeroUsize::new(INVALID_HEADERS_CACHE_SIZE).expect("cache size is not zero"),
+            ),
+            bad_blocks_dir: None,
+            payload_transactions: Arc::new(|| -> BestPoolTransactions<_> {
+                Box::new(std::iter::empty())
+            }),
+            payloads: LruCache::new(
+                NonZeroUsize::new(PAYLOADS_CACHE_SIZE).expect("cache size is not zero"),
+            ),
+            shutdown: None,
+            rx: UnboundedReceiverStream::new(rx),
+        }
+    }
+
+    /// Dump the payloads that fail validation or execution to the directory, see
+    /// [dump_bad_block](bad_block::dump_bad_block) for the contents of a dump.
+    pub fn with_bad_blocks_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.bad_blocks_dir = Some(dir.into());
+        self
+    }
+
+    /// Build the payloads from the best transactions of the pool.
+    ///
+    /// Without a pool, the payloads are built without any transactions.
+    pub fn with_payload_transactions<Pool>(mut self, pool: Pool) -> Self
+    where
+        Pool: TransactionPool<Transaction = TransactionSignedEcRecovered> + 'static,
+    {
+        self.payload_transactions = Arc::new(move || pool.best_transactions());
+        self
+    }
+
+    /// Shut down the engine once the given future resolves.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
     fn on_message(&mut self, msg: EngineMessage) {
         match msg {
-            EngineMessage::GetPayload(payload_id, tx) => {
-                // NOTE: Will always result in `PayloadUnknown` since we don't support block
-                // building for now.
-                let _ = tx.send(self.get_payload(payload_id).ok_or(EngineApiError::PayloadUnknown));
-            }
+            EngineMessage::GetPayload(id, tx) => self.on_get_payload(id, tx),
             EngineMessage::NewPayload(payload, tx) => {
-                let _ = tx.send(self.new_payload(payload));
+                let _ = tx.send(self.on_new_payload(payload));
+            }
+            EngineMessage::NewBlock(block, tx) => {
+                let _ = tx.send(self.on_new_block(block));
             }
             EngineMessage::ForkchoiceUpdated(state, attrs, tx) => {
-                let _ = tx.send(self.fork_choice_updated(state, attrs));
+                self.on_forkchoice_updated(state, attrs, tx);
             }
             EngineMessage::ExchangeTransitionConfiguration(config, tx) => {
                 let _ = tx.send(self.exchange_transition_configuration(config));
@@ -101,132 +258,320 @@ impl<Client: HeaderProvider + BlockProvider> EthConsensusEngine<Client> {
         }
     }
 
-    /// Try to construct a block from given payload. Perform addition validation of `extra_data` and
-    /// `base_fee_per_gas` fields.
-    ///
-    /// NOTE: The log bloom is assumed to be validated during serialization.
-    /// NOTE: Ommers hash is validated upon computing block hash and comparing the value with
-    /// `payload.block_hash`.
-    /// Ref: https://github.com/ethereum/go-ethereum/blob/79a478bb6176425c2400e949890e668a3d9a3d05/core/beacon/types.go#L145
-    fn try_construct_block(&self, payload: ExecutionPayload) -> EngineApiResult<SealedBlock> {
-        if payload.extra_data.len() > 32 {
-            return Err(EngineApiError::PayloadExtraData(payload.extra_data))
-        }
+    /// Validate and execute the payload on top of its parent.
+    fn on_new_payload(&mut self, payload: ExecutionPayload) -> EngineApiResult<PayloadStatus> {
+        let block = match try_construct_block(&self.chain_spec, payload) {
+            Ok(b) => b,
+            // a payload of the wrong fork is a malformed request rather than an invalid block
+            Err(
+                err @ (EngineApiError::PayloadWithdrawals { .. } |
+                EngineApiError::UnsupportedFork { .. }),
+            ) => return Err(err),
+            Err(err) => {
+                return Ok(PayloadStatus::from_status(PayloadStatusEnum::InvalidBlockHash {
+                    validation_error: err.to_string(),
+                }))
+            }
+        };
+        self.on_new_block(block)
+    }
+
+    /// Validate and execute the block on top of its parent.
+    fn on_new_block(&mut self, block: SealedBlock) -> EngineApiResult<PayloadStatus> {
+        let hash = block.hash();
 
-        if payload.base_fee_per_gas.is_zero() {
-            return Err(EngineApiError::PayloadBaseFee(payload.base_fee_per_gas))
+        if let Some(latest_valid_hash) = self.invalid_headers.get(&block.parent_hash).copied() {
+            // the descendants of an invalid block are invalid as well
+            self.invalid_headers.put(hash, latest_valid_hash);
+            return Ok(invalid_ancestor_status(latest_valid_hash))
         }
 
-        let transactions = payload
-            .transactions
-            .iter()
-            .map(|tx| TransactionSigned::decode(&mut tx.as_ref()))
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-        let transactions_root = proofs::calculate_transaction_root(transactions.iter());
-        let header = Header {
-            parent_hash: payload.parent_hash,
-            beneficiary: payload.fee_recipient,
-            state_root: payload.state_root,
-            transactions_root,
-            receipts_root: payload.receipts_root,
-            logs_bloom: payload.logs_bloom,
-            number: payload.block_number.as_u64(),
-            gas_limit: payload.gas_limit.as_u64(),
-            gas_used: payload.gas_used.as_u64(),
-            timestamp: payload.timestamp.as_u64(),
-            mix_hash: payload.prev_randao,
-            base_fee_per_gas: Some(payload.base_fee_per_gas.as_u64()),
-            extra_data: payload.extra_data.0,
-            // Defaults
-            ommers_hash: EMPTY_LIST_HASH,
-            difficulty: Default::default(),
-            nonce: Default::default(),
+        // the state of the parent may be written by the pipeline right now
+        let Some(sync) = self.sync.as_mut() else {
+            return Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
         };
-        let header = header.seal();
 
-        if payload.block_hash != header.hash() {
-            return Err(EngineApiError::PayloadBlockHash {
-                execution: header.hash(),
-                consensus: payload.block_hash,
-            })
+        let parent_hash = block.parent_hash;
+        let bad_block = self.bad_blocks_dir.is_some().then(|| block.clone());
+        match sync.tree.insert_block(block) {
+            Ok(BlockStatus::Executed | BlockStatus::Known) => {
+                Ok(PayloadStatus::new(PayloadStatusEnum::Valid, hash))
+            }
+            // a block below the finalized block that is not canonical can't be validated anymore,
+            // but that doesn't make it or its descendants invalid
+            Ok(BlockStatus::Buffered) |
+            Err(
+                BlockchainTreeError::ParentNotExecuted { .. } |
+                BlockchainTreeError::BelowFinalized { .. },
+            ) => Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing)),
+            Err(
+                err @ (BlockchainTreeError::Validation { .. } |
+                BlockchainTreeError::Execution { .. } |
+                BlockchainTreeError::SenderRecovery { .. }),
+            ) => {
+                debug!(target: "consensus::engine", ?hash, %err, "Invalid payload");
+                self.invalid_headers.put(hash, parent_hash);
+                if let Some(block) = bad_block {
+                    self.dump_bad_block(&block, &err);
+                }
+                Ok(PayloadStatus::new(
+                    PayloadStatusEnum::Invalid { validation_error: err.to_string() },
+                    parent_hash,
+                ))
+            }
+            Err(err) => Err(err.into()),
         }
+    }
 
-        Ok(SealedBlock { header, body: transactions, ommers: Default::default() })
+    /// Dump the invalid block to the bad blocks directory.
+    ///
+    /// The block is only executed again for the dump if its parent is canonical.
+    fn dump_bad_block(&self, block: &SealedBlock, err: &BlockchainTreeError) {
+        let (Some(dir), Some(sync)) = (&self.bad_blocks_dir, &self.sync) else { return };
+        let state = match self.canonical_number(block.parent_hash) {
+            Ok(Some(_)) => self.client.history_by_block_hash(block.parent_hash).ok(),
+            _ => None,
+        };
+        let state = state.map(|state| (state, sync.tree.chain_spec()));
+        match bad_block::dump_bad_block(dir, block, err, state) {
+            Ok(path) => {
+                warn!(target: "consensus::engine", hash = ?block.hash(), path = %path.display(), "Dumped invalid payload")
+            }
+            Err(error) => {
+                warn!(target: "consensus::engine", hash = ?block.hash(), %error, "Failed to dump invalid payload")
+            }
+        }
     }
-}
 
-impl<Client: HeaderProvider + BlockProvider> ConsensusEngine for EthConsensusEngine<Client> {
-    fn get_payload(&self, payload_id: H64) -> Option<ExecutionPayload> {
-        self.local_store.get(&payload_id).cloned()
+    /// Make the head of the fork choice state canonical, either with the tree or by syncing with
+    /// the pipeline.
+    fn on_forkchoice_updated(
+        &mut self,
+        state: ForkchoiceState,
+        attributes: Option<PayloadAttributes>,
+        tx: EngineApiSender<ForkchoiceUpdated>,
+    ) {
+        let head = state.head_block_hash;
+        if head.is_zero() {
+            let _ = tx.send(Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Invalid {
+                validation_error: EngineApiError::ForkchoiceEmptyHead.to_string(),
+            })));
+            return
+        }
+        if let Some(latest_valid_hash) = self.invalid_headers.get(&head).copied() {
+            let _ = tx.send(Ok(ForkchoiceUpdated::new(invalid_ancestor_status(latest_valid_hash))));
+            return
+        }
+
+        // the safe and finalized blocks are known right after a restart
+        let persisted = self.db.update(|tx| write_fork_choice_state(tx, &state));
+        if let Err(err) = persisted.and_then(|result| result) {
+            let _ = tx.send(Err(reth_interfaces::Error::from(err).into()));
+            return
+        }
+
+        self.forkchoice_state = Some(state.clone());
+        // the header stage of the pipeline syncs to the announced head
+        let _ = self.consensus.notify_fork_choice_state(state);
+
+        if self.running.is_some() {
+            let _ = tx.send(Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing)));
+            return
+        }
+        self.sync_to_head(Some(PendingForkchoiceUpdate { attributes, tx }), true);
     }
 
-    fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiResult<PayloadStatus> {
-        let block = match self.try_construct_block(payload) {
-            Ok(b) => b,
+    /// Start the sync action for the head of the latest fork choice state.
+    ///
+    /// The fork choice update is answered right away if the head is canonical already or needs
+    /// to be synced by the pipeline, and once the head is canonical if it is in the tree. The
+    /// payload of the update is only built once the head is canonical. The pipeline is only
+    /// started if `run_pipeline` is set.
+    fn sync_to_head(&mut self, pending: Option<PendingForkchoiceUpdate>, run_pipeline: bool) {
+        let Some(state) = self.forkchoice_state.clone() else { return };
+        let Some(mut sync) = self.sync.take() else { return };
+        let head = state.head_block_hash;
+
+        match self.canonical_number(head) {
+            Ok(Some(_)) => {
+                // the side chains that fork below the finalized block can't become canonical
+                if let Ok(Some(finalized)) = self.canonical_number(state.finalized_block_hash) {
+                    sync.tree.finalize_block(finalized);
+                }
+                self.sync = Some(sync);
+                if let Some(PendingForkchoiceUpdate { attributes, tx }) = pending {
+                    let response = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+                        .with_latest_valid_hash(head);
+                    let response = match attributes {
+                        Some(attributes) => self
+                            .build_payload(head, attributes)
+                            .map(|id| response.with_payload_id(id)),
+                        None => Ok(response),
+                    };
+                    let _ = tx.send(response);
+                }
+                return
+            }
+            Ok(None) => {}
             Err(err) => {
-                return Ok(PayloadStatus::from_status(PayloadStatusEnum::InvalidBlockHash {
-                    validation_error: err.to_string(),
-                }))
+                self.sync = Some(sync);
+                if let Some(pending) = pending {
+                    let _ = pending.tx.send(Err(err.into()));
+                }
+                return
             }
-        };
-
-        // The block already exists in our database
-        if self.client.is_known(&block.hash())? {
-            return Ok(PayloadStatus::new(PayloadStatusEnum::Valid, block.hash()))
         }
 
-        let Some(parent) = self.client.block(BlockId::Hash(block.parent_hash))? else {
-             // TODO: cache block for storing later
-             return Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
-        };
+        if sync.tree.contains(head) {
+            trace!(target: "consensus::engine", ?head, "Making the head canonical");
+            // the tree executes the blocks of the unwound chain again, which must not block the
+            // workers of the runtime
+            let handle = tokio::runtime::Handle::current();
+            let action = tokio::task::spawn_blocking(move || {
+                let ChainSync { pipeline, tree } = &mut sync;
+                let result = handle.block_on(tree.make_canonical(pipeline, head));
+                (sync, result)
+            })
+            .map(|outcome| outcome.expect("making the head canonical doesn't panic"));
+            self.running =
+                Some(RunningSync { action: action.boxed(), head, pending, interruptible: false });
+        } else if run_pipeline {
+            debug!(target: "consensus::engine", ?head, "Head is unknown, syncing with the pipeline");
+            let db = self.db.clone();
+            let action = async move {
+                let result = sync.pipeline.run_once(db).await.map_err(Into::into);
+                (sync, result)
+            };
+            self.running = Some(RunningSync {
+                action: action.boxed(),
+                head,
+                pending: None,
+                interruptible: true,
+            });
+            if let Some(pending) = pending {
+                let _ =
+                    pending.tx.send(Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing)));
+            }
+        } else {
+            self.sync = Some(sync);
+            if let Some(pending) = pending {
+                let _ =
+                    pending.tx.send(Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing)));
+            }
+        }
+    }
 
-        let parent_td = self.client.header_td(&block.parent_hash)?;
-        if parent_td.unwrap_or_default() <= self.config.merge_terminal_total_difficulty.into() {
-            return Ok(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
-                validation_error: EngineApiError::PayloadPreMerge.to_string(),
-            }))
+    /// Handle the outcome of a finished sync action.
+    ///
+    /// The pipeline is only run again if a new head was announced in the meantime, since the
+    /// consensus layer announces the head again in the next slot anyway.
+    fn on_sync_finished(
+        &mut self,
+        (sync, result): SyncOutcome<DB>,
+        head: H256,
+        pending: Option<PendingForkchoiceUpdate>,
+    ) {
+        self.sync = Some(sync);
+        match result {
+            Ok(()) => {
+                let head_changed = self
+                    .forkchoice_state
+                    .as_ref()
+                    .map_or(false, |state| state.head_block_hash != head);
+                self.sync_to_head(pending, head_changed)
+            }
+            Err(err) => {
+                error!(target: "consensus::engine", ?head, %err, "Failed to sync to the head");
+                if let Some(pending) = pending {
+                    let _ = pending.tx.send(Err(err.into()));
+                }
+            }
         }
+    }
 
-        if block.timestamp <= parent.timestamp {
+    /// Validates the payload attributes and starts building a payload with them on top of the
+    /// canonical head.
+    ///
+    /// Returns the id the payload is requested with. The same attributes on top of the same head
+    /// result in the same id, so the payload is only built once.
+    fn build_payload(&mut self, head: H256, attributes: PayloadAttributes) -> EngineApiResult<H64> {
+        let parent = self.client.header(&head)?.ok_or_else(|| {
+            reth_interfaces::Error::from(reth_interfaces::provider::Error::BlockHash {
+                block_hash: head,
+            })
+        })?;
+        let timestamp = attributes.timestamp.as_u64();
+        if timestamp <= parent.timestamp {
             return Err(EngineApiError::PayloadTimestamp {
-                invalid: block.timestamp,
+                invalid: timestamp,
                 latest: parent.timestamp,
             })
         }
+        validate_fork_fields(&self.chain_spec, timestamp, attributes.withdrawals.is_some())?;
 
-        // TODO: execute block
-
-        Ok(PayloadStatus::new(PayloadStatusEnum::Valid, block.hash()))
-    }
-
-    fn fork_choice_updated(
-        &self,
-        fork_choice_state: ForkchoiceState,
-        _payload_attributes: Option<PayloadAttributes>,
-    ) -> EngineApiResult<ForkchoiceUpdated> {
-        let ForkchoiceState { head_block_hash, finalized_block_hash, .. } = fork_choice_state;
-
-        if head_block_hash.is_zero() {
-            return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Invalid {
-                validation_error: EngineApiError::ForkchoiceEmptyHead.to_string(),
-            }))
+        let withdrawals: Option<Vec<Withdrawal>> = attributes
+            .withdrawals
+            .map(|withdrawals| withdrawals.into_iter().map(Into::into).collect());
+        let header = Header {
+            parent_hash: head,
+            ommers_hash: EMPTY_LIST_HASH,
+            beneficiary: attributes.suggested_fee_recipient,
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp,
+            mix_hash: attributes.prev_randao,
+            base_fee_per_gas: parent.base_fee_per_gas.map(|base_fee| {
+                calculate_next_block_base_fee(parent.gas_used, parent.gas_limit, base_fee)
+            }),
+            ..Default::default()
+        };
+        let id = payload_id(&header, withdrawals.as_deref());
+        if self.payloads.contains(&id) {
+            return Ok(id)
         }
 
-        // Block is not known, nothing to do.
-        if !self.client.is_known(&head_block_hash)? {
-            return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
-        }
+        debug!(target: "consensus::engine", ?id, parent = ?head, "Building payload");
+        let db = self.db.clone();
+        let chain_spec = self.chain_spec.clone();
+        let transactions = (self.payload_transactions)();
+        // executing the transactions must not block the workers of the runtime
+        let payload = tokio::task::spawn_blocking(move || {
+            build_block(db, &chain_spec, header, withdrawals, transactions)
+        })
+        .map(move |result| match result.expect("building a payload doesn't panic") {
+            Ok(BuiltBlock { header, body, withdrawals, .. }) => {
+                let block =
+                    SealedBlock { header: header.seal(), body, ommers: Vec::new(), withdrawals };
+                Some(ExecutionPayload::from(block))
+            }
+            Err(err) => {
+                warn!(target: "consensus::engine", ?id, %err, "Failed to build payload");
+                None
+            }
+        });
+        self.payloads.put(id, payload.boxed().shared());
+        Ok(id)
+    }
 
-        // The finalized block hash is not known, we are still syncing
-        if !finalized_block_hash.is_zero() && !self.client.is_known(&finalized_block_hash)? {
-            return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
-        }
+    /// Answers with the payload of the id once it is built.
+    fn on_get_payload(&mut self, id: H64, tx: EngineApiSender<ExecutionPayload>) {
+        let Some(payload) = self.payloads.get(&id).cloned() else {
+            let _ = tx.send(Err(EngineApiError::PayloadUnknown));
+            return
+        };
+        tokio::spawn(async move {
+            let _ = tx.send(payload.await.ok_or(EngineApiError::PayloadUnknown));
+        });
+    }
 
-        let chain_info = self.client.chain_info()?;
-        Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
-            .with_latest_valid_hash(chain_info.best_hash))
+    /// Returns the number of the block if it is part of the canonical chain.
+    fn canonical_number(&self, hash: BlockHash) -> reth_interfaces::Result<Option<BlockNumber>> {
+        let Some(number) = self.client.block_number(hash)? else { return Ok(None) };
+        let canonical_hash = self.client.block_hash(number.into())?;
+        Ok((canonical_hash == Some(hash)).then_some(number))
     }
 
+    /// Verifies the transition configuration between execution and consensus clients.
     fn exchange_transition_configuration(
         &self,
         config: TransitionConfiguration,
@@ -238,7 +583,7 @@ impl<Client: HeaderProvider + BlockProvider> ConsensusEngine for EthConsensusEng
         } = config;
 
         // Compare total difficulty values
-        let merge_terminal_td = self.config.merge_terminal_total_difficulty.into();
+        let merge_terminal_td = self.chain_spec.terminal_total_difficulty().unwrap_or_default();
         if merge_terminal_td != terminal_total_difficulty {
             return Err(EngineApiError::TerminalTD {
                 execution: merge_terminal_td,
@@ -272,22 +617,395 @@ impl<Client: HeaderProvider + BlockProvider> ConsensusEngine for EthConsensusEng
     }
 }
 
-impl<Client> Future for EthConsensusEngine<Client>
-where
-    Client: HeaderProvider + BlockProvider + Unpin,
-{
+impl<DB: Database + 'static> Future for BeaconConsensusEngine<DB> {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
         loop {
-            match ready!(this.rx.poll_next_unpin(cx)) {
-                Some(msg) => this.on_message(msg),
-                None => {
-                    // channel closed
-                    return Poll::Ready(())
+            if let Some(running) = this.running.as_mut() {
+                if let Poll::Ready(outcome) = running.action.poll_unpin(cx) {
+                    let RunningSync { head, pending, .. } =
+                        this.running.take().expect("sync action is running");
+                    this.on_sync_finished(outcome, head, pending);
+                    continue
+                }
+            }
+
+            if this.shutdown.as_mut().map_or(false, |shutdown| shutdown.poll_unpin(cx).is_ready()) {
+                match &this.running {
+                    // the side chain is committed before the engine stops
+                    Some(running) if !running.interruptible => return Poll::Pending,
+                    _ => {
+                        debug!(target: "consensus::engine", "Shutting down");
+                        return Poll::Ready(())
+                    }
                 }
             }
+
+            match this.rx.poll_next_unpin(cx) {
+                Poll::Ready(Some(msg)) => this.on_message(msg),
+                // channel closed
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Returns the status of a block that descends from an invalid block.
+fn invalid_ancestor_status(latest_valid_hash: H256) -> PayloadStatus {
+    PayloadStatus::new(
+        PayloadStatusEnum::Invalid { validation_error: "links to an invalid block".to_string() },
+        latest_valid_hash,
+    )
+}
+
+/// Returns the id of the payload that is built with the attributes in the header on top of its
+/// parent.
+fn payload_id(header: &Header, withdrawals: Option<&[Withdrawal]>) -> H64 {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(header.parent_hash.as_bytes());
+    preimage.extend_from_slice(&header.timestamp.to_be_bytes());
+    preimage.extend_from_slice(header.mix_hash.as_bytes());
+    preimage.extend_from_slice(header.beneficiary.as_bytes());
+    if let Some(withdrawals) = withdrawals {
+        preimage.extend_from_slice(proofs::calculate_withdrawals_root(withdrawals).as_bytes());
+    }
+    H64::from_slice(&keccak256(preimage).as_bytes()[..8])
+}
+
+/// Checks that a payload or payload attributes with the timestamp have the fields of the fork
+/// that is active at the timestamp.
+///
+/// The withdrawals are present exactly after Shanghai. Cancun payloads are rejected, since Cancun
+/// blocks can't be executed yet.
+fn validate_fork_fields(
+    chain_spec: &ChainSpec,
+    timestamp: u64,
+    has_withdrawals: bool,
+) -> EngineApiResult<()> {
+    if chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, timestamp) {
+        return Err(EngineApiError::UnsupportedFork { timestamp })
+    }
+    if chain_spec.is_fork_active_at_timestamp(Hardfork::Shanghai, timestamp) != has_withdrawals {
+        return Err(EngineApiError::PayloadWithdrawals { timestamp })
+    }
+    Ok(())
+}
+
+/// Try to construct a block from given payload. Perform addition validation of `extra_data` and
+/// `base_fee_per_gas` fields, and of the fields of the fork that is active at the timestamp of
+/// the payload.
+///
+/// NOTE: The log bloom is assumed to be validated during serialization.
+/// NOTE: Ommers hash is validated upon computing block hash and comparing the value with
+/// `payload.block_hash`.
+/// Ref: https://github.com/ethereum/go-ethereum/blob/79a478bb6176425c2400e949890e668a3d9a3d05/core/beacon/types.go#L145
+fn try_construct_block(
+    chain_spec: &ChainSpec,
+    payload: ExecutionPayload,
+) -> EngineApiResult<SealedBlock> {
+    if payload.extra_data.len() > 32 {
+        return Err(EngineApiError::PayloadExtraData(payload.extra_data))
+    }
+
+    if payload.base_fee_per_gas.is_zero() {
+        return Err(EngineApiError::PayloadBaseFee(payload.base_fee_per_gas))
+    }
+
+    validate_fork_fields(chain_spec, payload.timestamp.as_u64(), payload.withdrawals.is_some())?;
+
+    let transactions = payload
+        .transactions
+        .iter()
+        .map(|tx| TransactionSigned::decode(&mut tx.as_ref()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let transactions_root = proofs::calculate_transaction_root(transactions.iter());
+    let withdrawals: Option<Vec<Withdrawal>> =
+        payload.withdrawals.map(|withdrawals| withdrawals.into_iter().map(Into::into).collect());
+    let withdrawals_root = withdrawals.as_ref().map(proofs::calculate_withdrawals_root);
+    let header = Header {
+        parent_hash: payload.parent_hash,
+        beneficiary: payload.fee_recipient,
+        state_root: payload.state_root,
+        transactions_root,
+        receipts_root: payload.receipts_root,
+        logs_bloom: payload.logs_bloom,
+        number: payload.block_number.as_u64(),
+        gas_limit: payload.gas_limit.as_u64(),
+        gas_used: payload.gas_used.as_u64(),
+        timestamp: payload.timestamp.as_u64(),
+        mix_hash: payload.prev_randao,
+        base_fee_per_gas: Some(payload.base_fee_per_gas.as_u64()),
+        withdrawals_root,
+        extra_data: payload.extra_data.0,
+        // Cancun payloads are rejected above
+        blob_gas_used: None,
+        excess_blob_gas: None,
+        parent_beacon_block_root: None,
+        // Defaults
+        ommers_hash: EMPTY_LIST_HASH,
+        difficulty: Default::default(),
+        nonce: Default::default(),
+    };
+    let header = header.seal();
+
+    if payload.block_hash != header.hash() {
+        return Err(EngineApiError::PayloadBlockHash {
+            execution: header.hash(),
+            consensus: payload.block_hash,
+        })
+    }
+
+    Ok(SealedBlock { header, body: transactions, ommers: Default::default(), withdrawals })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use reth_interfaces::consensus::Consensus;
+    use reth_primitives::{
+        proofs::EMPTY_ROOT, Address, ChainSpecBuilder, SealedHeader, EIP1559_INITIAL_BASE_FEE,
+        MAINNET,
+    };
+    use reth_provider::insert_canonical_block;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    /// Returns an empty post-merge block on top of the parent.
+    fn next_block(parent: &SealedHeader, timestamp: u64) -> SealedBlock {
+        let header = Header {
+            parent_hash: parent.hash(),
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp,
+            base_fee_per_gas: Some(calculate_next_block_base_fee(
+                parent.gas_used,
+                parent.gas_limit,
+                parent.base_fee_per_gas.unwrap_or_default(),
+            )),
+            transactions_root: EMPTY_ROOT,
+            receipts_root: EMPTY_ROOT,
+            ommers_hash: EMPTY_LIST_HASH,
+            ..Default::default()
+        };
+        SealedBlock { header: header.seal(), ..Default::default() }
+    }
+
+    /// Sends the payloads to an engine on top of a post-merge genesis and returns their statuses.
+    async fn new_payloads(genesis: &SealedBlock, blocks: Vec<SealedBlock>) -> Vec<PayloadStatus> {
+        let chain_spec = ChainSpecBuilder::mainnet().paris_activated().build();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        db.update(|tx| insert_canonical_block(tx, genesis, None, false)).unwrap().unwrap();
+
+        let consensus = Arc::new(BeaconConsensus::new(chain_spec.clone()));
+        let tree = BlockchainTree::new(db.clone(), consensus.clone(), chain_spec.clone());
+        let (engine_tx, engine_rx) = unbounded_channel();
+        let engine =
+            BeaconConsensusEngine::new(chain_spec, db, consensus, Pipeline::new(), tree, engine_rx);
+
+        let responses = blocks
+            .into_iter()
+            .map(|block| {
+                let (tx, rx) = oneshot::channel();
+                engine_tx.send(EngineMessage::NewPayload(block.into(), tx)).unwrap();
+                rx
+            })
+            .collect::<Vec<_>>();
+        drop(engine_tx);
+        engine.await;
+
+        let mut statuses = Vec::new();
+        for rx in responses {
+            statuses.push(rx.await.unwrap().unwrap());
         }
+        statuses
+    }
+
+    fn genesis() -> SealedBlock {
+        let header = Header {
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(EIP1559_INITIAL_BASE_FEE),
+            transactions_root: EMPTY_ROOT,
+            receipts_root: EMPTY_ROOT,
+            ommers_hash: EMPTY_LIST_HASH,
+            ..Default::default()
+        };
+        SealedBlock { header: header.seal(), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn new_payload_valid_and_known() {
+        let genesis = genesis();
+        let block1 = next_block(&genesis.header, 12);
+        let statuses =
+            new_payloads(&genesis, vec![block1.clone(), block1.clone(), genesis.clone()]).await;
+
+        assert_eq!(statuses[0], PayloadStatus::new(PayloadStatusEnum::Valid, block1.hash()));
+        // the block is in the tree already
+        assert_eq!(statuses[1], PayloadStatus::new(PayloadStatusEnum::Valid, block1.hash()));
+        // a canonical block that is not after the finalized block is sent again after a restart
+        assert_eq!(statuses[2], PayloadStatus::new(PayloadStatusEnum::Valid, genesis.hash()));
+    }
+
+    #[tokio::test]
+    async fn new_payload_invalid_and_invalid_ancestor() {
+        let genesis = genesis();
+        // the timestamp has to be after the one of the parent
+        let invalid = next_block(&genesis.header, 0);
+        let child = next_block(&invalid.header, 12);
+        let statuses = new_payloads(&genesis, vec![invalid, child]).await;
+
+        assert!(matches!(statuses[0].status, PayloadStatusEnum::Invalid { .. }));
+        assert_eq!(statuses[0].latest_valid_hash, Some(genesis.hash()));
+        assert_eq!(statuses[1], invalid_ancestor_status(genesis.hash()));
+    }
+
+    #[tokio::test]
+    async fn new_payload_below_finalized() {
+        let genesis = genesis();
+        // a block at the height of the finalized genesis that is not canonical
+        let mut fork = genesis.header.clone().unseal();
+        fork.extra_data = vec![1].into();
+        let fork = SealedBlock { header: fork.seal(), ..Default::default() };
+        let child = next_block(&fork.header, 12);
+        let statuses = new_payloads(&genesis, vec![fork, child]).await;
+
+        assert_eq!(statuses[0], PayloadStatus::from_status(PayloadStatusEnum::Syncing));
+        // the descendants are not rejected
+        assert_eq!(statuses[1], PayloadStatus::from_status(PayloadStatusEnum::Syncing));
+    }
+
+    #[tokio::test]
+    async fn forkchoice_updated_with_unknown_head() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let consensus = Arc::new(BeaconConsensus::new(MAINNET.clone()));
+        let tree = BlockchainTree::new(db.clone(), consensus.clone(), MAINNET.clone());
+        let (engine_tx, engine_rx) = unbounded_channel();
+        let engine = BeaconConsensusEngine::new(
+            MAINNET.clone(),
+            db,
+            consensus.clone(),
+            Pipeline::new(),
+            tree,
+            engine_rx,
+        );
+
+        let empty_head = ForkchoiceState::default();
+        let (empty_tx, empty_rx) = oneshot::channel();
+        engine_tx.send(EngineMessage::ForkchoiceUpdated(empty_head, None, empty_tx)).unwrap();
+
+        let unknown_head =
+            ForkchoiceState { head_block_hash: H256::from_low_u64_be(1), ..Default::default() };
+        let (unknown_tx, unknown_rx) = oneshot::channel();
+        engine_tx
+            .send(EngineMessage::ForkchoiceUpdated(unknown_head.clone(), None, unknown_tx))
+            .unwrap();
+
+        // the engine stops once the channel is closed
+        drop(engine_tx);
+        engine.await;
+
+        let status = empty_rx.await.unwrap().unwrap().payload_status.status;
+        assert!(matches!(status, PayloadStatusEnum::Invalid { .. }));
+        let status = unknown_rx.await.unwrap().unwrap().payload_status.status;
+        assert_eq!(status, PayloadStatusEnum::Syncing);
+        // the pipeline syncs to the announced head
+        assert_eq!(*consensus.fork_choice_state().borrow(), unknown_head);
+    }
+
+    #[tokio::test]
+    async fn forkchoice_updated_builds_payload() {
+        let chain_spec = ChainSpecBuilder::mainnet().paris_activated().build();
+        let genesis = genesis();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        db.update(|tx| insert_canonical_block(tx, &genesis, None, false)).unwrap().unwrap();
+
+        let consensus = Arc::new(BeaconConsensus::new(chain_spec.clone()));
+        let tree = BlockchainTree::new(db.clone(), consensus.clone(), chain_spec.clone());
+        let (engine_tx, engine_rx) = unbounded_channel();
+        let engine =
+            BeaconConsensusEngine::new(chain_spec, db, consensus, Pipeline::new(), tree, engine_rx);
+        let engine = tokio::spawn(engine);
+
+        let forkchoice_updated = |attributes| {
+            let state = ForkchoiceState {
+                head_block_hash: genesis.hash(),
+                safe_block_hash: genesis.hash(),
+                finalized_block_hash: genesis.hash(),
+            };
+            let (tx, rx) = oneshot::channel();
+            engine_tx.send(EngineMessage::ForkchoiceUpdated(state, Some(attributes), tx)).unwrap();
+            rx
+        };
+        let attributes = PayloadAttributes {
+            timestamp: 12u64.into(),
+            prev_randao: H256::from_low_u64_be(1),
+            suggested_fee_recipient: Address::from_low_u64_be(2),
+            withdrawals: None,
+        };
+
+        let response = forkchoice_updated(attributes.clone()).await.unwrap().unwrap();
+        assert_eq!(response.payload_status.status, PayloadStatusEnum::Valid);
+        let id = response.payload_id.expect("payload is built");
+
+        let (tx, rx) = oneshot::channel();
+        engine_tx.send(EngineMessage::GetPayload(id, tx)).unwrap();
+        let payload = rx.await.unwrap().unwrap();
+        assert_eq!(payload.parent_hash, genesis.hash());
+        assert_eq!(payload.block_number.as_u64(), 1);
+        assert_eq!(payload.timestamp, attributes.timestamp);
+        assert_eq!(payload.prev_randao, attributes.prev_randao);
+        assert_eq!(payload.fee_recipient, attributes.suggested_fee_recipient);
+        assert_eq!(
+            payload.base_fee_per_gas.as_u64(),
+            calculate_next_block_base_fee(0, genesis.header.gas_limit, EIP1559_INITIAL_BASE_FEE)
+        );
+        assert_eq!(payload.withdrawals, None);
+        // the payload is accepted as a new payload
+        let block = try_construct_block(&MAINNET, payload).unwrap();
+        assert_eq!(block.header.state_root, EMPTY_ROOT);
+
+        // the timestamp has to be after the one of the head
+        let invalid = PayloadAttributes { timestamp: 0u64.into(), ..attributes.clone() };
+        let response = forkchoice_updated(invalid).await.unwrap();
+        assert!(matches!(
+            response,
+            Err(EngineApiError::PayloadTimestamp { invalid: 0, latest: 0 })
+        ));
+
+        // withdrawals are only allowed after Shanghai
+        let invalid = PayloadAttributes { withdrawals: Some(Vec::new()), ..attributes };
+        let response = forkchoice_updated(invalid).await.unwrap();
+        assert!(matches!(response, Err(EngineApiError::PayloadWithdrawals { timestamp: 12 })));
+
+        let (tx, rx) = oneshot::channel();
+        engine_tx.send(EngineMessage::GetPayload(H64::zero(), tx)).unwrap();
+        assert!(matches!(rx.await.unwrap(), Err(EngineApiError::PayloadUnknown)));
+
+        drop(engine_tx);
+        engine.await.unwrap();
+    }
+    #[tokio::test]
+    async fn stop_on_shutdown() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let consensus = Arc::new(BeaconConsensus::new(MAINNET.clone()));
+        let tree = BlockchainTree::new(db.clone(), consensus.clone(), MAINNET.clone());
+        let (_engine_tx, engine_rx) = unbounded_channel();
+        let (signal, shutdown) = reth_tasks::shutdown::signal();
+        let engine = BeaconConsensusEngine::new(
+            MAINNET.clone(),
+            db,
+            consensus,
+            Pipeline::new(),
+            tree,
+            engine_rx,
+        )
+        .with_shutdown(shutdown);
+
+        // the engine stops although the channel is still open
+        signal.fire();
+        engine.await;
     }
 }
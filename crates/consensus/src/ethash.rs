@@ -0,0 +1,326 @@
+//! Ethash proof of work verification for pre-merge headers.
+//!
+//! Verification uses the light client mode of the algorithm: the dataset items accessed by a seal
+//! are computed on demand from the verification cache of the epoch, so only the cache (16MiB and
+//! growing) has to be kept in memory instead of the full dataset.
+//!
+//! See also: <https://ethereum.org/en/developers/docs/consensus-mechanisms/pow/mining-algorithms/ethash/>
+use lru::LruCache;
+use parking_lot::Mutex;
+use reth_interfaces::consensus::Error;
+use reth_primitives::{keccak256, BlockNumber, SealedHeader, H256, U256};
+use reth_rlp::Encodable;
+use std::{num::NonZeroUsize, sync::Arc};
+use tiny_keccak::{Hasher, Keccak};
+
+/// Number of blocks per epoch, the verification cache changes each epoch.
+pub const EPOCH_LENGTH: u64 = 30_000;
+/// Default number of epoch verification caches kept in memory.
+pub const DEFAULT_MAX_CACHES: usize = 2;
+
+const DATASET_BYTES_INIT: u64 = 1 << 30;
+const DATASET_BYTES_GROWTH: u64 = 1 << 23;
+const CACHE_BYTES_INIT: u64 = 1 << 24;
+const CACHE_BYTES_GROWTH: u64 = 1 << 17;
+const MIX_BYTES: u64 = 128;
+const HASH_BYTES: u64 = 64;
+const DATASET_PARENTS: u32 = 256;
+const CACHE_ROUNDS: usize = 3;
+const ACCESSES: u32 = 64;
+const FNV_PRIME: u32 = 0x01000193;
+
+/// A 64 byte node of the cache or the dataset as little endian words.
+type Node = [u32; 16];
+
+/// The verification cache of an epoch.
+#[derive(Debug)]
+struct EpochCache {
+    /// The nodes of the cache.
+    nodes: Vec<Node>,
+    /// The size of the full dataset of the epoch in bytes.
+    dataset_size: u64,
+}
+
+impl EpochCache {
+    /// Generate the verification cache of the epoch.
+    fn new(epoch: u64) -> Self {
+        Self {
+            nodes: make_cache(cache_size(epoch), seed_hash(epoch)),
+            dataset_size: dataset_size(epoch),
+        }
+    }
+}
+
+/// Ethash seal verifier.
+///
+/// The verification caches of the most recently used epochs are kept in a bounded cache, as
+/// generating one takes a noticeable amount of time.
+#[derive(Debug)]
+pub struct Ethash {
+    caches: Mutex<LruCache<u64, Arc<EpochCache>>>,
+}
+
+impl Default for Ethash {
+    fn default() -> Self {
+        Self::new(NonZeroUsize::new(DEFAULT_MAX_CACHES).unwrap())
+    }
+}
+
+impl Ethash {
+    /// Create a new verifier that keeps at most `max_caches` epoch verification caches.
+    pub fn new(max_caches: NonZeroUsize) -> Self {
+        Self { caches: Mutex::new(LruCache::new(max_caches)) }
+    }
+
+    /// Verify that the mix hash and the nonce of the header are a valid proof of work for its
+    /// difficulty.
+    pub fn verify_seal(&self, header: &SealedHeader) -> Result<(), Error> {
+        if header.difficulty.is_zero() {
+            return Err(Error::EthashInsufficientWork { difficulty: header.difficulty })
+        }
+
+        let cache = self.epoch_cache(header.number);
+        let (mix_hash, result) = hashimoto_light(seal_hash(header), header.nonce, &cache);
+        if mix_hash != header.mix_hash {
+            return Err(Error::EthashMixHashDiff { got: header.mix_hash, expected: mix_hash })
+        }
+
+        // The result needs to be below the target of 2^256 / difficulty.
+        if U256::from_big_endian(result.as_bytes()) > U256::MAX / header.difficulty {
+            return Err(Error::EthashInsufficientWork { difficulty: header.difficulty })
+        }
+
+        Ok(())
+    }
+
+    /// Returns the verification cache of the epoch of the block, generating it if necessary.
+    fn epoch_cache(&self, block_number: BlockNumber) -> Arc<EpochCache> {
+        let epoch = block_number / EPOCH_LENGTH;
+        // The lock is held while generating so the same cache is not generated concurrently.
+        let mut caches = self.caches.lock();
+        caches.get_or_insert(epoch, || Arc::new(EpochCache::new(epoch))).clone()
+    }
+}
+
+/// Compute the hash of the header without the mix hash and the nonce, which is the input of the
+/// proof of work.
+fn seal_hash(header: &SealedHeader) -> H256 {
+    let mut payload = Vec::new();
+    header.parent_hash.encode(&mut payload);
+    header.ommers_hash.encode(&mut payload);
+    header.beneficiary.encode(&mut payload);
+    header.state_root.encode(&mut payload);
+    header.transactions_root.encode(&mut payload);
+    header.receipts_root.encode(&mut payload);
+    header.logs_bloom.encode(&mut payload);
+    header.difficulty.encode(&mut payload);
+    U256::from(header.number).encode(&mut payload);
+    U256::from(header.gas_limit).encode(&mut payload);
+    U256::from(header.gas_used).encode(&mut payload);
+    header.timestamp.encode(&mut payload);
+    header.extra_data.encode(&mut payload);
+    if let Some(base_fee) = header.base_fee_per_gas {
+        U256::from(base_fee).encode(&mut payload);
+    }
+
+    let mut out = Vec::with_capacity(payload.len() + 9);
+    reth_rlp::Header { list: true, payload_length: payload.len() }.encode(&mut out);
+    out.extend_from_slice(&payload);
+    keccak256(out)
+}
+
+/// Compute the mix hash and the result of the proof of work, computing the accessed dataset
+/// items from the verification cache.
+fn hashimoto_light(hash: H256, nonce: u64, cache: &EpochCache) -> (H256, H256) {
+    let rows = (cache.dataset_size / MIX_BYTES) as u32;
+
+    let mut seed = [0u8; 40];
+    seed[..32].copy_from_slice(hash.as_bytes());
+    seed[32..].copy_from_slice(&nonce.to_le_bytes());
+    let seed = keccak512(&seed);
+
+    // The mix is the seed replicated to the size of the mix.
+    let mut mix = [0u32; 32];
+    mix[..16].copy_from_slice(&seed);
+    mix[16..].copy_from_slice(&seed);
+
+    for i in 0..ACCESSES {
+        let row = fnv(i ^ seed[0], mix[i as usize % mix.len()]) % rows;
+        for (j, words) in mix.chunks_exact_mut(16).enumerate() {
+            let item = dataset_item(&cache.nodes, row * 2 + j as u32);
+            for (word, item_word) in words.iter_mut().zip(item) {
+                *word = fnv(*word, item_word);
+            }
+        }
+    }
+
+    // Compress the mix into the mix hash.
+    let mut mix_hash = H256::zero();
+    for (bytes, words) in mix_hash.as_bytes_mut().chunks_exact_mut(4).zip(mix.chunks_exact(4)) {
+        let word = words.iter().skip(1).fold(words[0], |acc, word| fnv(acc, *word));
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+
+    let mut result = H256::zero();
+    let mut hasher = Keccak::v256();
+    hasher.update(&node_to_bytes(&seed));
+    hasher.update(mix_hash.as_bytes());
+    hasher.finalize(result.as_bytes_mut());
+
+    (mix_hash, result)
+}
+
+/// Compute the dataset item at the index from the verification cache.
+fn dataset_item(cache: &[Node], index: u32) -> Node {
+    let mut mix = cache[index as usize % cache.len()];
+    mix[0] ^= index;
+    let mut mix = keccak512(&node_to_bytes(&mix));
+
+    for i in 0..DATASET_PARENTS {
+        let parent = fnv(index ^ i, mix[i as usize % mix.len()]) as usize % cache.len();
+        for (word, parent_word) in mix.iter_mut().zip(cache[parent]) {
+            *word = fnv(*word, parent_word);
+        }
+    }
+
+    keccak512(&node_to_bytes(&mix))
+}
+
+/// Generate the verification cache of the given size from the seed.
+fn make_cache(size: u64, seed: H256) -> Vec<Node> {
+    let len = (size / HASH_BYTES) as usize;
+
+    let mut nodes = Vec::with_capacity(len);
+    nodes.push(keccak512(seed.as_bytes()));
+    for i in 1..len {
+        let node = keccak512(&node_to_bytes(&nodes[i - 1]));
+        nodes.push(node);
+    }
+
+    // RandMemoHash rounds
+    for _ in 0..CACHE_ROUNDS {
+        for i in 0..len {
+            let previous = nodes[(i + len - 1) % len];
+            let other = nodes[nodes[i][0] as usize % len];
+            let mut node = [0u32; 16];
+            for (word, (a, b)) in node.iter_mut().zip(previous.iter().zip(other.iter())) {
+                *word = a ^ b;
+            }
+            nodes[i] = keccak512(&node_to_bytes(&node));
+        }
+    }
+
+    nodes
+}
+
+/// The seed of the verification cache of the epoch.
+fn seed_hash(epoch: u64) -> H256 {
+    (0..epoch).fold(H256::zero(), |seed, _| keccak256(seed))
+}
+
+/// The size of the verification cache of the epoch in bytes.
+fn cache_size(epoch: u64) -> u64 {
+    let mut size = CACHE_BYTES_INIT + CACHE_BYTES_GROWTH * epoch - HASH_BYTES;
+    while !is_prime(size / HASH_BYTES) {
+        size -= 2 * HASH_BYTES;
+    }
+    size
+}
+
+/// The size of the full dataset of the epoch in bytes.
+fn dataset_size(epoch: u64) -> u64 {
+    let mut size = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * epoch - MIX_BYTES;
+    while !is_prime(size / MIX_BYTES) {
+        size -= 2 * MIX_BYTES;
+    }
+    size
+}
+
+fn is_prime(number: u64) -> bool {
+    number >= 2 && (2..).take_while(|i| i * i <= number).all(|i| number % i != 0)
+}
+
+fn fnv(a: u32, b: u32) -> u32 {
+    a.wrapping_mul(FNV_PRIME) ^ b
+}
+
+fn keccak512(data: &[u8]) -> Node {
+    let mut out = [0u8; 64];
+    let mut hasher = Keccak::v512();
+    hasher.update(data);
+    hasher.finalize(&mut out);
+
+    let mut node = [0u32; 16];
+    for (word, bytes) in node.iter_mut().zip(out.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("chunk is 4 bytes"));
+    }
+    node
+}
+
+fn node_to_bytes(node: &Node) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    for (bytes, word) in out.chunks_exact_mut(4).zip(node) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex_literal::hex, Header};
+
+    /// Mainnet block 1
+    fn mainnet_block_1() -> Header {
+        Header {
+            parent_hash: hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+                .into(),
+            ommers_hash: hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+                .into(),
+            beneficiary: hex!("05a56e2d52c817161883f50c441c3228cfe54d9f").into(),
+            state_root: hex!("d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3")
+                .into(),
+            transactions_root: hex!(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+            )
+            .into(),
+            receipts_root: hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .into(),
+            difficulty: U256::from(0x3ff800000u64),
+            number: 1,
+            gas_limit: 5000,
+            timestamp: 0x55ba4224,
+            extra_data: hex!("476574682f76312e302e302f6c696e75782f676f312e342e32")[..].into(),
+            mix_hash: hex!("969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59")
+                .into(),
+            nonce: 0x539bd4979fef1ec4,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn epoch_sizes() {
+        assert_eq!(cache_size(0), 16776896);
+        assert_eq!(dataset_size(0), 1073739904);
+        assert_eq!(
+            seed_hash(1),
+            H256(hex!("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"))
+        );
+    }
+
+    // Generating the verification cache takes a while in debug builds.
+    #[test]
+    #[ignore]
+    fn verify_mainnet_seal() {
+        let ethash = Ethash::default();
+        let header = mainnet_block_1();
+        assert_eq!(ethash.verify_seal(&header.clone().seal()), Ok(()));
+
+        let mut invalid = header;
+        invalid.nonce += 1;
+        assert!(matches!(
+            ethash.verify_seal(&invalid.seal()),
+            Err(Error::EthashMixHashDiff { .. })
+        ));
+    }
+}
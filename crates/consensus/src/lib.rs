@@ -5,17 +5,15 @@
     attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
 ))]
 //! Consensus algorithms for Ethereum.
-//!
-//! # Features
-//!
-//! - `serde`: Enable serde support for configuration types.
-pub mod config;
+pub mod auto_seal;
+mod builder;
+pub mod clique;
 pub mod consensus;
+pub mod ethash;
 pub mod verification;
 
 /// Engine API module.
 pub mod engine;
 
-pub use config::Config;
 pub use consensus::BeaconConsensus;
 pub use reth_interfaces::consensus::Error;
@@ -1,9 +1,11 @@
 //! ALl functions for verification of block
-use crate::{config, Config};
 use reth_interfaces::{consensus::Error, Result as RethResult};
 use reth_primitives::{
-    BlockNumber, Header, SealedBlock, SealedHeader, Transaction, TransactionSignedEcRecovered,
-    TxEip1559, TxEip2930, TxLegacy, EMPTY_OMMER_ROOT, H256, U256,
+    eip4844::calculate_excess_blob_gas, BlockNumber, ChainSpec, Hardfork, Header, SealedBlock,
+    SealedHeader, Transaction, TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxEip4844,
+    TxLegacy, BLOB_GAS_PER_BLOB, EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR,
+    EIP1559_ELASTICITY_MULTIPLIER, EIP1559_INITIAL_BASE_FEE, EMPTY_OMMER_ROOT,
+    MAX_BLOB_GAS_PER_BLOCK, U256,
 };
 use reth_provider::{AccountProvider, HeaderProvider};
 use std::{
@@ -14,7 +16,7 @@ use std::{
 /// Validate header standalone
 pub fn validate_header_standalone(
     header: &SealedHeader,
-    config: &config::Config,
+    chain_spec: &ChainSpec,
 ) -> Result<(), Error> {
     // Gas used needs to be less then gas limit. Gas used is going to be check after execution.
     if header.gas_used > header.gas_limit {
@@ -33,33 +35,56 @@ pub fn validate_header_standalone(
 
     // From yellow papper: extraData: An arbitrary byte array containing data
     // relevant to this block. This must be 32 bytes or fewer; formally Hx.
-    if header.extra_data.len() > 32 {
+    // Clique blocks carry the signature and the signers in the extra data, which is validated by
+    // the clique consensus.
+    let clique = chain_spec.clique.is_some() &&
+        !chain_spec.is_fork_active_at_block(Hardfork::Paris, header.number);
+    if !clique && header.extra_data.len() > 32 {
         return Err(Error::ExtraDataExceedsMax { len: header.extra_data.len() })
     }
 
     // Check if base fee is set.
-    if header.number >= config.london_block && header.base_fee_per_gas.is_none() {
+    if chain_spec.is_fork_active_at_block(Hardfork::London, header.number) &&
+        header.base_fee_per_gas.is_none()
+    {
         return Err(Error::BaseFeeMissing)
     }
 
-    // EIP-3675: Upgrade consensus to Proof-of-Stake:
-    // https://eips.ethereum.org/EIPS/eip-3675#replacing-difficulty-with-0
-    if header.number >= config.paris_block {
-        if header.difficulty != U256::zero() {
-            return Err(Error::TheMergeDifficultyIsNotZero)
-        }
+    // EIP-4895: Beacon chain push withdrawals as operations
+    let shanghai = chain_spec.is_fork_active_at_timestamp(Hardfork::Shanghai, header.timestamp);
+    if shanghai && header.withdrawals_root.is_none() {
+        return Err(Error::WithdrawalsRootMissing)
+    } else if !shanghai && header.withdrawals_root.is_some() {
+        return Err(Error::WithdrawalsRootUnexpected)
+    }
 
-        if header.nonce != 0 {
-            return Err(Error::TheMergeNonceIsNotZero)
+    // EIP-4844: Shard Blob Transactions and EIP-4788: Beacon block root in the EVM
+    if chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, header.timestamp) {
+        let blob_gas_used = header.blob_gas_used.ok_or(Error::BlobGasUsedMissing)?;
+        if header.excess_blob_gas.is_none() {
+            return Err(Error::ExcessBlobGasMissing)
         }
-
-        if header.ommers_hash != EMPTY_OMMER_ROOT {
-            return Err(Error::TheMergeOmmerRootIsNotEmpty)
+        if header.parent_beacon_block_root.is_none() {
+            return Err(Error::ParentBeaconBlockRootMissing)
         }
-
-        if header.mix_hash != H256::zero() {
-            return Err(Error::TheMergeMixHashIsNotZero)
+        if blob_gas_used > MAX_BLOB_GAS_PER_BLOCK {
+            return Err(Error::BlobGasUsedExceedsMaxBlobGasPerBlock {
+                blob_gas_used,
+                max_blob_gas_per_block: MAX_BLOB_GAS_PER_BLOCK,
+            })
         }
+        if blob_gas_used % BLOB_GAS_PER_BLOB != 0 {
+            return Err(Error::BlobGasUsedNotMultipleOfBlobGasPerBlob {
+                blob_gas_used,
+                blob_gas_per_blob: BLOB_GAS_PER_BLOB,
+            })
+        }
+    } else if header.blob_gas_used.is_some() {
+        return Err(Error::BlobGasUsedUnexpected)
+    } else if header.excess_blob_gas.is_some() {
+        return Err(Error::ExcessBlobGasUnexpected)
+    } else if header.parent_beacon_block_root.is_some() {
+        return Err(Error::ParentBeaconBlockRootUnexpected)
     }
 
     Ok(())
@@ -70,21 +95,24 @@ pub fn validate_header_standalone(
 /// The only parameter from the header that affects the transaction is `base_fee`.
 pub fn validate_transaction_regarding_header(
     transaction: &Transaction,
-    config: &Config,
+    chain_spec: &ChainSpec,
     at_block_number: BlockNumber,
+    at_timestamp: u64,
     base_fee: Option<u64>,
 ) -> Result<(), Error> {
     let chain_id = match transaction {
         Transaction::Legacy(TxLegacy { chain_id, .. }) => {
             // EIP-155: Simple replay attack protection: https://eips.ethereum.org/EIPS/eip-155
-            if config.eip_155_block <= at_block_number && chain_id.is_some() {
+            if chain_spec.is_fork_active_at_block(Hardfork::SpuriousDragon, at_block_number) &&
+                chain_id.is_some()
+            {
                 return Err(Error::TransactionOldLegacyChainId)
             }
             *chain_id
         }
         Transaction::Eip2930(TxEip2930 { chain_id, .. }) => {
             // EIP-2930: Optional access lists: https://eips.ethereum.org/EIPS/eip-2930 (New transaction type)
-            if config.berlin_block > at_block_number {
+            if !chain_spec.is_fork_active_at_block(Hardfork::Berlin, at_block_number) {
                 return Err(Error::TransactionEip2930Disabled)
             }
             Some(*chain_id)
@@ -96,7 +124,7 @@ pub fn validate_transaction_regarding_header(
             ..
         }) => {
             // EIP-1559: Fee market change for ETH 1.0 chain https://eips.ethereum.org/EIPS/eip-1559
-            if config.berlin_block > at_block_number {
+            if !chain_spec.is_fork_active_at_block(Hardfork::Berlin, at_block_number) {
                 return Err(Error::TransactionEip1559Disabled)
             }
 
@@ -108,9 +136,16 @@ pub fn validate_transaction_regarding_header(
 
             Some(*chain_id)
         }
+        Transaction::Eip4844(TxEip4844 { chain_id, .. }) => {
+            // EIP-4844: Shard Blob Transactions https://eips.ethereum.org/EIPS/eip-4844
+            if !chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, at_timestamp) {
+                return Err(Error::TransactionEip4844Disabled)
+            }
+            Some(*chain_id)
+        }
     };
     if let Some(chain_id) = chain_id {
-        if chain_id != config.chain_id {
+        if chain_id != chain_spec.chain_id() {
             return Err(Error::TransactionChainId)
         }
     }
@@ -134,15 +169,16 @@ pub fn validate_all_transaction_regarding_block_and_nonces<
     transactions: impl Iterator<Item = &'a TransactionSignedEcRecovered>,
     header: &Header,
     provider: Provider,
-    config: &Config,
+    chain_spec: &ChainSpec,
 ) -> RethResult<()> {
     let mut account_nonces = HashMap::new();
 
     for transaction in transactions {
         validate_transaction_regarding_header(
             transaction,
-            config,
+            chain_spec,
             header.number,
+            header.timestamp,
             header.base_fee_per_gas,
         )?;
 
@@ -180,6 +216,7 @@ pub fn validate_all_transaction_regarding_block_and_nonces<
 ///
 /// - Compares the ommer hash in the block header to the block body
 /// - Compares the transactions root in the block header to the block body
+/// - Compares the blob gas used in the block header to the blobs of the transactions
 /// - Pre-execution transaction validation
 /// - (Optionally) Compares the receipts root in the block header to the block body
 pub fn validate_block_standalone(block: &SealedBlock) -> Result<(), Error> {
@@ -204,12 +241,38 @@ pub fn validate_block_standalone(block: &SealedBlock) -> Result<(), Error> {
         })
     }
 
+    // Check withdrawals root, the presence of the root is checked with the header.
+    match (block.header.withdrawals_root, &block.withdrawals) {
+        (Some(expected), Some(withdrawals)) => {
+            let withdrawals_root =
+                reth_primitives::proofs::calculate_withdrawals_root(withdrawals.iter());
+            if withdrawals_root != expected {
+                return Err(Error::BodyWithdrawalsRootDiff { got: withdrawals_root, expected })
+            }
+        }
+        (Some(_), None) => return Err(Error::BodyWithdrawalsMissing),
+        (None, Some(_)) => return Err(Error::BodyWithdrawalsUnexpected),
+        (None, None) => {}
+    }
+
+    // Check blob gas used, the presence of the field is checked with the header.
+    if let Some(expected) = block.header.blob_gas_used {
+        let blob_gas_used = block
+            .body
+            .iter()
+            .map(|tx| tx.blob_versioned_hashes().len() as u64 * BLOB_GAS_PER_BLOB)
+            .sum();
+        if blob_gas_used != expected {
+            return Err(Error::BlobGasUsedDiff { got: blob_gas_used, expected })
+        }
+    }
+
     Ok(())
 }
 
 /// Calculate base fee for next block. EIP-1559 spec
 pub fn calculate_next_block_base_fee(gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
-    let gas_target = gas_limit / config::EIP1559_ELASTICITY_MULTIPLIER;
+    let gas_target = gas_limit / EIP1559_ELASTICITY_MULTIPLIER;
 
     if gas_used == gas_target {
         return base_fee
@@ -220,14 +283,14 @@ pub fn calculate_next_block_base_fee(gas_used: u64, gas_limit: u64, base_fee: u6
             1,
             base_fee as u128 * gas_used_delta as u128 /
                 gas_target as u128 /
-                config::EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128,
+                EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128,
         );
         base_fee + (base_fee_delta as u64)
     } else {
         let gas_used_delta = gas_target - gas_used;
         let base_fee_per_gas_delta = base_fee as u128 * gas_used_delta as u128 /
             gas_target as u128 /
-            config::EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128;
+            EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128;
 
         base_fee.saturating_sub(base_fee_per_gas_delta as u64)
     }
@@ -237,7 +300,7 @@ pub fn calculate_next_block_base_fee(gas_used: u64, gas_limit: u64, base_fee: u6
 pub fn validate_header_regarding_parent(
     parent: &SealedHeader,
     child: &SealedHeader,
-    config: &config::Config,
+    chain_spec: &ChainSpec,
 ) -> Result<(), Error> {
     // Parent number is consistent.
     if parent.number + 1 != child.number {
@@ -255,17 +318,15 @@ pub fn validate_header_regarding_parent(
         })
     }
 
-    // difficulty check is done by consensus.
-    if config.paris_block > child.number {
-        // TODO how this needs to be checked? As ice age did increment it by some formula
-    }
+    // The difficulty depends on the consensus of the chain, the difficulty of proof of work blocks
+    // is checked by [validate_header_difficulty].
 
     let mut parent_gas_limit = parent.gas_limit;
 
     // By consensus, gas_limit is multiplied by elasticity (*2) on
     // on exact block that hardfork happens.
-    if config.london_block == child.number {
-        parent_gas_limit = parent.gas_limit * config::EIP1559_ELASTICITY_MULTIPLIER;
+    if chain_spec.fork(Hardfork::London).as_block() == Some(child.number) {
+        parent_gas_limit = parent.gas_limit * EIP1559_ELASTICITY_MULTIPLIER;
     }
 
     // Check gas limit, max diff between child/parent gas_limit should be  max_diff=parent_gas/1024
@@ -284,24 +345,150 @@ pub fn validate_header_regarding_parent(
     }
 
     // EIP-1559 check base fee
-    if child.number >= config.london_block {
+    if chain_spec.is_fork_active_at_block(Hardfork::London, child.number) {
         let base_fee = child.base_fee_per_gas.ok_or(Error::BaseFeeMissing)?;
 
-        let expected_base_fee = if config.london_block == child.number {
-            config::EIP1559_INITIAL_BASE_FEE
-        } else {
-            // This BaseFeeMissing will not happen as previous blocks are checked to have them.
-            calculate_next_block_base_fee(
-                parent.gas_used,
-                parent.gas_limit,
-                parent.base_fee_per_gas.ok_or(Error::BaseFeeMissing)?,
-            )
-        };
+        let expected_base_fee =
+            if chain_spec.fork(Hardfork::London).as_block() == Some(child.number) {
+                EIP1559_INITIAL_BASE_FEE
+            } else {
+                // This BaseFeeMissing will not happen as previous blocks are checked to have them.
+                calculate_next_block_base_fee(
+                    parent.gas_used,
+                    parent.gas_limit,
+                    parent.base_fee_per_gas.ok_or(Error::BaseFeeMissing)?,
+                )
+            };
         if expected_base_fee != base_fee {
             return Err(Error::BaseFeeDiff { expected: expected_base_fee, got: base_fee })
         }
     }
 
+    // EIP-4844 check excess blob gas, the parent of the first Cancun block has neither excess nor
+    // used blob gas.
+    if chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, child.timestamp) {
+        let excess_blob_gas = child.excess_blob_gas.ok_or(Error::ExcessBlobGasMissing)?;
+        let expected_excess_blob_gas = calculate_excess_blob_gas(
+            parent.excess_blob_gas.unwrap_or_default(),
+            parent.blob_gas_used.unwrap_or_default(),
+        );
+        if excess_blob_gas != expected_excess_blob_gas {
+            return Err(Error::ExcessBlobGasDiff {
+                got: excess_blob_gas,
+                expected: expected_excess_blob_gas,
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// The minimum difficulty of a proof of work block.
+const MINIMUM_DIFFICULTY: u64 = 131_072;
+
+/// The number of blocks after which the difficulty bomb doubles.
+const DIFFICULTY_BOMB_PERIOD: u64 = 100_000;
+
+/// Validates that the difficulty of a proof of work header is the difficulty calculated from its
+/// parent.
+pub fn validate_header_difficulty(
+    parent: &SealedHeader,
+    child: &SealedHeader,
+    chain_spec: &ChainSpec,
+) -> Result<(), Error> {
+    let expected = calculate_difficulty(parent, child.number, child.timestamp, chain_spec);
+    if child.difficulty != expected {
+        return Err(Error::DifficultyDiff { got: child.difficulty, expected })
+    }
+    Ok(())
+}
+
+/// Calculates the difficulty of the proof of work block with the given number and timestamp on
+/// top of the parent.
+///
+/// The difficulty is adjusted to the time since the parent as specified by Frontier, Homestead
+/// (EIP-2) or Byzantium (EIP-100), which also accounts for the ommers of the parent. The difficulty
+/// bomb is added on top and is delayed by Byzantium and the later forks.
+pub fn calculate_difficulty(
+    parent: &Header,
+    number: BlockNumber,
+    timestamp: u64,
+    chain_spec: &ChainSpec,
+) -> U256 {
+    let elapsed = timestamp.saturating_sub(parent.timestamp);
+    let adjustment = if chain_spec.is_fork_active_at_block(Hardfork::Byzantium, number) {
+        let ommers = if parent.ommers_hash == EMPTY_OMMER_ROOT { 1 } else { 2 };
+        (ommers - (elapsed / 9) as i64).max(-99)
+    } else if chain_spec.is_fork_active_at_block(Hardfork::Homestead, number) {
+        (1 - (elapsed / 10) as i64).max(-99)
+    } else if elapsed < 13 {
+        1
+    } else {
+        -1
+    };
+
+    let step = parent.difficulty / 2048 * U256::from(adjustment.unsigned_abs());
+    let mut difficulty = if adjustment < 0 {
+        parent.difficulty.saturating_sub(step)
+    } else {
+        parent.difficulty.saturating_add(step)
+    };
+    difficulty = difficulty.max(U256::from(MINIMUM_DIFFICULTY));
+
+    let period =
+        number.saturating_sub(difficulty_bomb_delay(number, chain_spec)) / DIFFICULTY_BOMB_PERIOD;
+    if period > 1 {
+        difficulty = difficulty.saturating_add(U256::one() << (period - 2));
+    }
+    difficulty
+}
+
+/// Returns the number of blocks the difficulty bomb is delayed by at the given block.
+fn difficulty_bomb_delay(number: BlockNumber, chain_spec: &ChainSpec) -> u64 {
+    [
+        (Hardfork::GrayGlacier, 11_400_000),
+        (Hardfork::ArrowGlacier, 10_700_000),
+        (Hardfork::London, 9_700_000),
+        (Hardfork::Muirglacier, 9_000_000),
+        (Hardfork::Constantinople, 5_000_000),
+        (Hardfork::Byzantium, 3_000_000),
+    ]
+    .into_iter()
+    .find(|(fork, _)| chain_spec.is_fork_active_at_block(*fork, number))
+    .map_or(0, |(_, delay)| delay)
+}
+
+/// Validate header in regards to parent for blocks after the Paris hard-fork.
+///
+/// EIP-3675: Upgrade consensus to Proof-of-Stake:
+/// https://eips.ethereum.org/EIPS/eip-3675#block-structure
+///
+/// The mix hash is not checked as it holds the `prevrandao` value of the beacon chain
+/// (EIP-4399). The base fee is checked by [validate_header_regarding_parent].
+pub fn validate_header_post_merge(
+    parent: &SealedHeader,
+    header: &SealedHeader,
+) -> Result<(), Error> {
+    if header.difficulty != U256::zero() {
+        return Err(Error::TheMergeDifficultyIsNotZero)
+    }
+
+    if header.nonce != 0 {
+        return Err(Error::TheMergeNonceIsNotZero)
+    }
+
+    if header.ommers_hash != EMPTY_OMMER_ROOT {
+        return Err(Error::TheMergeOmmerRootIsNotEmpty)
+    }
+
+    // Timestamp needs to be strictly greater than the parent timestamp.
+    if header.timestamp <= parent.timestamp {
+        return Err(Error::TimestampIsInPast {
+            parent_timestamp: parent.timestamp,
+            timestamp: header.timestamp,
+        })
+    }
+
     Ok(())
 }
 
@@ -336,12 +523,15 @@ pub fn validate_block_regarding_chain<PROV: HeaderProvider>(
 pub fn full_validation<Provider: HeaderProvider + AccountProvider>(
     block: &SealedBlock,
     provider: Provider,
-    config: &Config,
+    chain_spec: &ChainSpec,
 ) -> RethResult<()> {
-    validate_header_standalone(&block.header, config)?;
+    validate_header_standalone(&block.header, chain_spec)?;
     validate_block_standalone(block)?;
     let parent = validate_block_regarding_chain(block, &provider)?;
-    validate_header_regarding_parent(&parent, &block.header, config)?;
+    validate_header_regarding_parent(&parent, &block.header, chain_spec)?;
+    if chain_spec.is_fork_active_at_block(Hardfork::Paris, block.number) {
+        validate_header_post_merge(&parent, &block.header)?;
+    }
 
     // NOTE: depending on the need of the stages, recovery could be done in different place.
     let transactions = block
@@ -354,7 +544,7 @@ pub fn full_validation<Provider: HeaderProvider + AccountProvider>(
         transactions.iter(),
         &block.header,
         provider,
-        config,
+        chain_spec,
     )?;
     Ok(())
 }
@@ -363,8 +553,8 @@ pub fn full_validation<Provider: HeaderProvider + AccountProvider>(
 mod tests {
     use reth_interfaces::Result;
     use reth_primitives::{
-        hex_literal::hex, Account, Address, BlockHash, Bytes, Header, Signature, TransactionKind,
-        TransactionSigned,
+        hex_literal::hex, Account, Address, BlockHash, Bytes, ChainSpecBuilder, Header, Signature,
+        TransactionKind, TransactionSigned, Withdrawal, H256, MAINNET,
     };
 
     use super::*;
@@ -396,6 +586,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn calculate_pow_difficulty() {
+        // mainnet block 1, the difficulty drops by Frontier rules as it is mined long after genesis
+        let genesis = Header { difficulty: U256::from(0x400000000u64), ..Default::default() };
+        assert_eq!(
+            calculate_difficulty(&genesis, 1, 1438269988, &MAINNET),
+            U256::from(0x3ff800000u64)
+        );
+
+        let parent = Header {
+            timestamp: 1000,
+            difficulty: U256::from(2_048_000u64),
+            ommers_hash: EMPTY_OMMER_ROOT,
+            ..Default::default()
+        };
+        // Homestead drops a step per 10 seconds, the bomb adds 2^10
+        assert_eq!(
+            calculate_difficulty(&parent, 1_200_000, 1030, &MAINNET),
+            U256::from(2_047_024u64)
+        );
+        // the bomb of Byzantium is delayed by 3M blocks and adds 2^11
+        assert_eq!(
+            calculate_difficulty(&parent, 4_370_000, 1009, &MAINNET),
+            U256::from(2_050_048u64)
+        );
+        // a parent with ommers raises the difficulty by another step
+        let parent_with_ommers = Header { ommers_hash: H256::zero(), ..parent.clone() };
+        assert_eq!(
+            calculate_difficulty(&parent_with_ommers, 4_370_000, 1009, &MAINNET),
+            U256::from(2_051_048u64)
+        );
+        // the difficulty doesn't drop below the minimum
+        let parent = Header { difficulty: U256::from(MINIMUM_DIFFICULTY), ..parent };
+        assert_eq!(
+            calculate_difficulty(&parent, 4_370_000, 2000, &MAINNET),
+            U256::from(133_120u64)
+        );
+    }
+
     struct Provider {
         is_known: bool,
         parent: Option<Header>,
@@ -477,6 +706,10 @@ mod tests {
             mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
             nonce: 0x0000000000000000,
             base_fee_per_gas: 0x28f0001df.into(),
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         };
         // size: 0x9b5
 
@@ -485,30 +718,29 @@ mod tests {
         parent.gas_limit = 30000000;
         parent.base_fee_per_gas = Some(0x28041f7f5);
         parent.number -= 1;
+        parent.timestamp -= 12;
 
         let ommers = Vec::new();
         let body = Vec::new();
 
-        (SealedBlock { header: header.seal(), body, ommers }, parent)
+        (SealedBlock { header: header.seal(), body, ommers, withdrawals: None }, parent)
     }
 
     #[test]
     fn sanity_check() {
         let (block, parent) = mock_block();
         let provider = Provider::new(Some(parent));
-        let config = Config::default();
 
-        assert_eq!(full_validation(&block, provider, &config), Ok(()), "Validation should pass");
+        assert_eq!(full_validation(&block, provider, &MAINNET), Ok(()), "Validation should pass");
     }
 
     #[test]
     fn validate_known_block() {
         let (block, _) = mock_block();
         let provider = Provider::new_known();
-        let config = Config::default();
 
         assert_eq!(
-            full_validation(&block, provider, &config),
+            full_validation(&block, provider, &MAINNET),
             Err(Error::BlockKnown { hash: block.hash(), number: block.number }.into()),
             "Should fail with error"
         );
@@ -520,14 +752,13 @@ mod tests {
         let tx1 = mock_tx(0);
         let tx2 = mock_tx(1);
         let provider = Provider::new_known();
-        let config = Config::default();
 
         let txs = vec![tx1, tx2];
         validate_all_transaction_regarding_block_and_nonces(
             txs.iter(),
             &block.header,
             provider,
-            &config,
+            &MAINNET,
         )
         .expect("To Pass");
     }
@@ -537,7 +768,6 @@ mod tests {
         let (block, _) = mock_block();
         let tx1 = mock_tx(1);
         let provider = Provider::new_known();
-        let config = Config::default();
 
         let txs = vec![tx1];
         assert_eq!(
@@ -545,7 +775,7 @@ mod tests {
                 txs.iter(),
                 &block.header,
                 provider,
-                &config,
+                &MAINNET,
             ),
             Err(Error::TransactionNonceNotConsistent.into())
         )
@@ -557,7 +787,6 @@ mod tests {
         let tx1 = mock_tx(0);
         let tx2 = mock_tx(3);
         let provider = Provider::new_known();
-        let config = Config::default();
 
         let txs = vec![tx1, tx2];
         assert_eq!(
@@ -565,9 +794,133 @@ mod tests {
                 txs.iter(),
                 &block.header,
                 provider,
-                &config,
+                &MAINNET,
             ),
             Err(Error::TransactionNonceNotConsistent.into())
         );
     }
+
+    #[test]
+    fn post_merge_header_rules() {
+        let (block, parent) = mock_block();
+        let parent = parent.seal();
+        let header = block.header.clone().unseal();
+        assert_eq!(validate_header_post_merge(&parent, &block.header), Ok(()));
+
+        // mix hash holds prevrandao
+        let mut prevrandao = header.clone();
+        prevrandao.mix_hash = H256::from_low_u64_be(1);
+        assert_eq!(validate_header_post_merge(&parent, &prevrandao.seal()), Ok(()));
+
+        let mut difficulty = header.clone();
+        difficulty.difficulty = U256::from(1);
+        assert_eq!(
+            validate_header_post_merge(&parent, &difficulty.seal()),
+            Err(Error::TheMergeDifficultyIsNotZero)
+        );
+
+        let mut nonce = header.clone();
+        nonce.nonce = 1;
+        assert_eq!(
+            validate_header_post_merge(&parent, &nonce.seal()),
+            Err(Error::TheMergeNonceIsNotZero)
+        );
+
+        let mut ommers = header.clone();
+        ommers.ommers_hash = H256::from_low_u64_be(1);
+        assert_eq!(
+            validate_header_post_merge(&parent, &ommers.seal()),
+            Err(Error::TheMergeOmmerRootIsNotEmpty)
+        );
+
+        let mut timestamp = header;
+        timestamp.timestamp = parent.timestamp;
+        assert_eq!(
+            validate_header_post_merge(&parent, &timestamp.seal()),
+            Err(Error::TimestampIsInPast {
+                parent_timestamp: parent.timestamp,
+                timestamp: parent.timestamp
+            })
+        );
+    }
+
+    #[test]
+    fn validate_withdrawals() {
+        let (block, _) = mock_block();
+        let shanghai = ChainSpecBuilder::mainnet().shanghai_activated().build();
+
+        assert_eq!(
+            validate_header_standalone(&block.header, &shanghai),
+            Err(Error::WithdrawalsRootMissing)
+        );
+
+        let withdrawals = vec![Withdrawal {
+            index: 1,
+            validator_index: 2,
+            address: Address::from_low_u64_be(3),
+            amount: 4,
+        }];
+        let mut header = block.header.clone().unseal();
+        header.withdrawals_root =
+            Some(reth_primitives::proofs::calculate_withdrawals_root(withdrawals.iter()));
+        assert_eq!(
+            validate_header_standalone(&header.clone().seal(), &MAINNET),
+            Err(Error::WithdrawalsRootUnexpected)
+        );
+        assert_eq!(validate_header_standalone(&header.clone().seal(), &shanghai), Ok(()));
+
+        let mut block = SealedBlock { header: header.seal(), ..block };
+        assert_eq!(validate_block_standalone(&block), Err(Error::BodyWithdrawalsMissing));
+
+        block.withdrawals = Some(withdrawals);
+        assert_eq!(validate_block_standalone(&block), Ok(()));
+
+        block.withdrawals = Some(Vec::new());
+        assert_eq!(
+            validate_block_standalone(&block),
+            Err(Error::BodyWithdrawalsRootDiff {
+                got: reth_primitives::proofs::EMPTY_ROOT,
+                expected: block.header.withdrawals_root.unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_blob_gas() {
+        let (block, _) = mock_block();
+        let cancun = ChainSpecBuilder::mainnet().cancun_activated().build();
+        let mut header = block.header.clone().unseal();
+        header.withdrawals_root = Some(reth_primitives::proofs::EMPTY_ROOT);
+        assert_eq!(
+            validate_header_standalone(&header.clone().seal(), &cancun),
+            Err(Error::BlobGasUsedMissing)
+        );
+
+        header.blob_gas_used = Some(BLOB_GAS_PER_BLOB);
+        header.excess_blob_gas = Some(0);
+        header.parent_beacon_block_root = Some(H256::random());
+        assert_eq!(validate_header_standalone(&header.clone().seal(), &cancun), Ok(()));
+        let shanghai = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        assert_eq!(
+            validate_header_standalone(&header.clone().seal(), &shanghai),
+            Err(Error::BlobGasUsedUnexpected)
+        );
+
+        header.blob_gas_used = Some(BLOB_GAS_PER_BLOB + 1);
+        assert_eq!(
+            validate_header_standalone(&header.clone().seal(), &cancun),
+            Err(Error::BlobGasUsedNotMultipleOfBlobGasPerBlob {
+                blob_gas_used: BLOB_GAS_PER_BLOB + 1,
+                blob_gas_per_blob: BLOB_GAS_PER_BLOB,
+            })
+        );
+
+        // the transactions of the block don't carry any blobs
+        header.blob_gas_used = Some(BLOB_GAS_PER_BLOB);
+        let block = SealedBlock { header: header.seal(), withdrawals: Some(Vec::new()), ..block };
+        assert_eq!(
+            validate_block_standalone(&block),
+            Err(Error::BlobGasUsedDiff { got: 0, expected: BLOB_GAS_PER_BLOB })
+        );
+    }
 }
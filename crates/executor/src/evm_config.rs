@@ -0,0 +1,207 @@
+//! Extension point for networks that run a modified EVM.
+
+use crate::inspector::BlockInspector;
+use hashbrown::HashMap;
+use reth_primitives::{Address, Bytes, ChainSpec, Header, TransactionSignedEcRecovered};
+use revm::{
+    Account as RevmAccount, CallInputs, Database, EVMData, ExecutionResult, Gas, Inspector,
+    Interpreter, Return, B160, EVM,
+};
+use std::fmt::Debug;
+
+/// A precompile, called with the input and the gas limit of the call.
+///
+/// Returns `None` if the call fails, which consumes all gas of the call.
+pub type Precompile = fn(input: &[u8], gas_limit: u64) -> Option<PrecompileOutput>;
+
+/// The result of a successful precompile call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecompileOutput {
+    /// The gas used by the call. The call runs out of gas if it exceeds the gas limit.
+    pub gas_used: u64,
+    /// The returned data.
+    pub output: Bytes,
+}
+
+/// Configures the EVM the blocks of a chain are executed with, so networks with additional
+/// precompiles or a different gas schedule can use the executor.
+pub trait EvmConfig: Debug + Send + Sync {
+    /// Returns the extensions of the EVM that executes the given block of the chain.
+    fn extensions(&self, chain_spec: &ChainSpec, header: &Header) -> EvmExtensions;
+}
+
+/// The EVM of Ethereum, without any extensions.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EthEvmConfig;
+
+impl EvmConfig for EthEvmConfig {
+    fn extensions(&self, _chain_spec: &ChainSpec, _header: &Header) -> EvmExtensions {
+        EvmExtensions::default()
+    }
+}
+
+/// Additions to the EVM of the active hardfork.
+///
+/// The extensions are applied by intercepting the execution as a [BlockInspector]:
+/// - A call to an additional precompile returns the output of the precompile without executing
+///   the code of the address. The value of the call is not transferred.
+/// - The extra gas of an opcode is charged on top of its cost in the active hardfork before the
+///   opcode is executed, so the gas schedule can only be raised.
+#[derive(Clone)]
+pub struct EvmExtensions {
+    /// The additional precompiles by their address.
+    precompiles: HashMap<B160, Precompile>,
+    /// The extra gas of every opcode.
+    extra_opcode_gas: [u64; 256],
+}
+
+impl EvmExtensions {
+    /// Adds a precompile at the given address, replacing the code of the address.
+    pub fn with_precompile(mut self, address: Address, precompile: Precompile) -> Self {
+        self.precompiles.insert(B160(address.0), precompile);
+        self
+    }
+
+    /// Charges the given gas for the opcode on top of its cost in the active hardfork.
+    pub fn with_extra_opcode_gas(mut self, opcode: u8, gas: u64) -> Self {
+        self.extra_opcode_gas[opcode as usize] = gas;
+        self
+    }
+
+    /// Returns `true` if the EVM is not extended.
+    pub fn is_empty(&self) -> bool {
+        self.precompiles.is_empty() && self.extra_opcode_gas.iter().all(|gas| *gas == 0)
+    }
+}
+
+impl Default for EvmExtensions {
+    fn default() -> Self {
+        Self { precompiles: HashMap::new(), extra_opcode_gas: [0; 256] }
+    }
+}
+
+impl Debug for EvmExtensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvmExtensions")
+            .field("precompiles", &self.precompiles.keys().collect::<Vec<_>>())
+            .field(
+                "extra_opcode_gas",
+                &self
+                    .extra_opcode_gas
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, gas)| **gas != 0)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<DB: Database> BlockInspector<DB> for EvmExtensions {
+    fn inspect_transaction(
+        &mut self,
+        _transaction_index: usize,
+        _transaction: &TransactionSignedEcRecovered,
+        evm: &mut EVM<DB>,
+    ) -> (ExecutionResult, HashMap<B160, RevmAccount>) {
+        evm.inspect(ExtensionsInspector(self))
+    }
+}
+
+/// The inspector that applies the [EvmExtensions] to a transaction.
+#[derive(Debug)]
+pub(crate) struct ExtensionsInspector<'a>(pub(crate) &'a EvmExtensions);
+
+impl<'a, DB: Database> Inspector<DB> for ExtensionsInspector<'a> {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        let gas = self.0.extra_opcode_gas[interp.current_opcode() as usize];
+        if gas != 0 && !interp.gas.record_cost(gas) {
+            return Return::OutOfGas
+        }
+        Return::Continue
+    }
+
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (Return, Gas, bytes::Bytes) {
+        let precompile = match self.0.precompiles.get(&inputs.contract) {
+            Some(precompile) => precompile,
+            None => return (Return::Continue, Gas::new(0), bytes::Bytes::new()),
+        };
+
+        let mut gas = Gas::new(inputs.gas_limit);
+        match precompile(&inputs.input, inputs.gas_limit) {
+            Some(PrecompileOutput { gas_used, output }) if gas.record_cost(gas_used) => {
+                (Return::Return, gas, output.0)
+            }
+            _ => {
+                gas.record_cost(inputs.gas_limit);
+                (Return::OutOfGas, gas, bytes::Bytes::new())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::revm_wrap::{State, SubState};
+    use reth_interfaces::Result;
+    use reth_primitives::{Account, StorageKey, StorageValue, H256, U256};
+    use reth_provider::{AccountProvider, StateProvider};
+    use revm::{TransactOut, TransactTo};
+
+    /// A state where every account is funded.
+    struct FundedState;
+
+    impl AccountProvider for FundedState {
+        fn basic_account(&self, _address: Address) -> Result<Option<Account>> {
+            Ok(Some(Account { balance: U256::from(u64::MAX), ..Default::default() }))
+        }
+    }
+
+    impl StateProvider for FundedState {
+        fn storage(&self, _: Address, _: StorageKey) -> Result<Option<StorageValue>> {
+            Ok(None)
+        }
+
+        fn bytecode_by_hash(&self, _: H256) -> Result<Option<Bytes>> {
+            Ok(None)
+        }
+
+        fn block_hash(&self, _: U256) -> Result<Option<H256>> {
+            Ok(None)
+        }
+    }
+
+    fn echo(input: &[u8], _gas_limit: u64) -> Option<PrecompileOutput> {
+        Some(PrecompileOutput { gas_used: 100, output: Bytes::from(input.to_vec()) })
+    }
+
+    #[test]
+    fn call_additional_precompile() {
+        let address = Address::from_low_u64_be(0x100);
+        let extensions = EvmExtensions::default().with_precompile(address, echo);
+        assert!(!extensions.is_empty());
+
+        let mut evm = EVM::new();
+        evm.database(SubState::new(State::new(FundedState)));
+        evm.env.tx.caller = B160(Address::from_low_u64_be(1).0);
+        evm.env.tx.transact_to = TransactTo::Call(B160(address.0));
+        evm.env.tx.gas_limit = 100_000;
+        evm.env.tx.data = bytes::Bytes::from_static(b"input");
+
+        let (result, _) = evm.inspect(ExtensionsInspector(&extensions));
+        assert_eq!(result.exit_reason, Return::Return);
+        assert_eq!(result.gas_used, 21_000 + 5 * 16 + 100);
+        assert!(matches!(result.out, TransactOut::Call(output) if output.as_ref() == b"input"));
+    }
+}
@@ -1,26 +1,30 @@
 use crate::{
-    config::{WEI_2ETH, WEI_3ETH, WEI_5ETH},
+    config::{
+        BEACON_ROOTS_ADDRESS, BEACON_ROOTS_HISTORY_BUFFER_LENGTH, WEI_2ETH, WEI_3ETH, WEI_5ETH,
+    },
+    dao_fork::{DAO_HARDFORK_ACCOUNTS, DAO_HARDFORK_BENEFICIARY},
+    evm_config::{EthEvmConfig, EvmConfig},
+    inspector::{BlockInspector, NoopBlockInspector},
     revm_wrap::{self, to_reth_acc, SubState},
-    Config,
 };
 use hashbrown::hash_map::Entry;
-use reth_db::{models::AccountBeforeTx, tables, transaction::DbTxMut, Error as DbError};
-use reth_interfaces::executor::Error;
+use reth_interfaces::executor::{BlockValidationError, Error};
 use reth_primitives::{
-    bloom::logs_bloom, Account, Address, Bloom, Header, Log, Receipt, TransactionSignedEcRecovered,
-    H160, H256, U256,
+    bloom::{logs_bloom, receipts_bloom},
+    Account, Address, BigEndianHash, Bloom, ChainSpec, Hardfork, Header, Log, Receipt,
+    TransactionSignedEcRecovered, Withdrawal, BLOB_GAS_PER_BLOB, H160, H256, KECCAK_EMPTY, U256,
 };
-use reth_provider::StateProvider;
+use reth_provider::{ChangedStateKeys, StateProvider};
 use revm::{
-    db::AccountState, Account as RevmAccount, AccountInfo, AnalysisKind, Bytecode, Database,
-    Return, SpecId, B160, EVM, U256 as evmU256,
+    db::AccountState, Account as RevmAccount, AccountInfo, Bytecode, Database, Return, SpecId,
+    B160, EVM,
 };
 use std::collections::BTreeMap;
 
 /// Main block executor
 pub struct Executor {
-    /// Configuration, Spec and optional flags.
-    pub config: Config,
+    /// The chain specification, e.g. the fork activations.
+    pub chain_spec: ChainSpec,
 }
 
 /// Contains old/new account changes
@@ -49,46 +53,6 @@ pub enum AccountInfoChangeSet {
     NoChange,
 }
 
-impl AccountInfoChangeSet {
-    /// Apply the changes from the changeset to a database transaction.
-    pub fn apply_to_db<'a, TX: DbTxMut<'a>>(
-        self,
-        tx: &TX,
-        address: Address,
-        tx_index: u64,
-    ) -> Result<(), DbError> {
-        match self {
-            AccountInfoChangeSet::Changed { old, new } => {
-                // insert old account in AccountChangeSet
-                // check for old != new was already done
-                tx.put::<tables::AccountChangeSet>(
-                    tx_index,
-                    AccountBeforeTx { address, info: Some(old) },
-                )?;
-                tx.put::<tables::PlainAccountState>(address, new)?;
-            }
-            AccountInfoChangeSet::Created { new } => {
-                tx.put::<tables::AccountChangeSet>(
-                    tx_index,
-                    AccountBeforeTx { address, info: None },
-                )?;
-                tx.put::<tables::PlainAccountState>(address, new)?;
-            }
-            AccountInfoChangeSet::Destroyed { old } => {
-                tx.delete::<tables::PlainAccountState>(address, None)?;
-                tx.put::<tables::AccountChangeSet>(
-                    tx_index,
-                    AccountBeforeTx { address, info: Some(old) },
-                )?;
-            }
-            AccountInfoChangeSet::NoChange => {
-                // do nothing storage account didn't change
-            }
-        }
-        Ok(())
-    }
-}
-
 /// Diff change set that is neede for creating history index and updating current world state.
 #[derive(Debug, Clone)]
 pub struct AccountChangeSet {
@@ -105,13 +69,48 @@ pub struct AccountChangeSet {
 
 /// Execution Result containing vector of transaction changesets
 /// and block reward if present
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExecutionResult {
     /// Transaction changeest contraining [Receipt], changed [Accounts][Account] and Storages.
     pub changesets: Vec<TransactionChangeSet>,
-    /// Block reward if present. It represent changeset for block reward slot in
-    /// [tables::AccountChangeSet] .
+    /// Block reward if present. It represent changeset for block reward slot in the account
+    /// changesets.
+    ///
+    /// The irregular state change of the DAO hardfork block and the withdrawals of the block are
+    /// part of this changeset as well. It is present for every block with withdrawals.
     pub block_reward: Option<BTreeMap<Address, AccountInfoChangeSet>>,
+    /// The storage changes of the block that are not made by its transactions, i.e. the parent
+    /// beacon block root stored by the system call of EIP-4788, as old and new values. They are
+    /// applied with the block reward changeset, which is present for these blocks as they are
+    /// after Shanghai.
+    pub block_storage: BTreeMap<Address, BTreeMap<U256, (U256, U256)>>,
+}
+
+impl ExecutionResult {
+    /// Returns the accounts and storage slots changed by the block, whose cached values are stale
+    /// after the block.
+    pub fn changed_state_keys(&self) -> ChangedStateKeys {
+        let mut keys = ChangedStateKeys::default();
+        for changeset in &self.changesets {
+            for (address, account) in &changeset.changeset {
+                if account.account != AccountInfoChangeSet::NoChange {
+                    keys.accounts.insert(*address);
+                }
+                if account.wipe_storage {
+                    keys.wiped_storage.insert(*address);
+                }
+                keys.storage
+                    .extend(account.storage.keys().map(|slot| (*address, H256::from_uint(slot))));
+            }
+        }
+        if let Some(block_reward) = &self.block_reward {
+            keys.accounts.extend(block_reward.keys());
+        }
+        for (address, storage) in &self.block_storage {
+            keys.storage.extend(storage.keys().map(|slot| (*address, H256::from_uint(slot))));
+        }
+        keys
+    }
 }
 
 /// Commit change to database and return change diff that is used to update state and create
@@ -252,45 +251,64 @@ pub struct TransactionChangeSet {
 }
 
 /// Execute and verify block
+///
+/// The gas used of the block is verified during the execution, the logs bloom and the receipts
+/// root of the header are verified against the receipts afterwards. The receipts root is only
+/// verified after Byzantium.
 pub fn execute_and_verify_receipt<DB: StateProvider>(
     header: &Header,
     transactions: &[TransactionSignedEcRecovered],
     ommers: &[Header],
-    config: &Config,
+    withdrawals: Option<&[Withdrawal]>,
+    chain_spec: &ChainSpec,
     db: SubState<DB>,
+    evm_config: &dyn EvmConfig,
 ) -> Result<ExecutionResult, Error> {
-    let transaction_change_set = execute(header, transactions, ommers, config, db)?;
-
-    let receipts_iter =
-        transaction_change_set.changesets.iter().map(|changeset| &changeset.receipt);
+    let transaction_change_set =
+        execute_with_config(header, transactions, ommers, withdrawals, chain_spec, db, evm_config)?;
+    verify_receipts(header, chain_spec, &transaction_change_set)?;
+    Ok(transaction_change_set)
+}
 
-    if header.number >= config.spec_upgrades.byzantium {
-        verify_receipt(header.receipts_root, header.logs_bloom, receipts_iter)?;
+/// Verify the logs bloom and, after Byzantium, the receipts root of the header against the
+/// receipts of the executed block.
+pub fn verify_receipts(
+    header: &Header,
+    chain_spec: &ChainSpec,
+    result: &ExecutionResult,
+) -> Result<(), BlockValidationError> {
+    let receipts_iter = result.changesets.iter().map(|changeset| &changeset.receipt);
+
+    verify_logs_bloom(header.logs_bloom, receipts_iter.clone())?;
+    if chain_spec.is_fork_active_at_block(Hardfork::Byzantium, header.number) {
+        verify_receipts_root(header.receipts_root, receipts_iter)?;
     }
     // TODO Before Byzantium, receipts contained state root that would mean that expensive operation
     // as hashing that is needed for state root got calculated in every transaction
     // This was replaced with is_success flag.
     // See more about EIP here: https://eips.ethereum.org/EIPS/eip-658
 
-    Ok(transaction_change_set)
+    Ok(())
 }
 
-/// Verify receipts
-pub fn verify_receipt<'a>(
-    expected_receipts_root: H256,
+/// Verify the logs bloom of the header against the blooms of the receipts.
+///
+/// The first transaction with logs that are not part of the expected bloom is reported, otherwise
+/// the mismatch of the whole bloom.
+pub fn verify_logs_bloom<'a>(
     expected_logs_bloom: Bloom,
     receipts: impl Iterator<Item = &'a Receipt> + Clone,
-) -> Result<(), Error> {
-    // Check receipts root.
-    let receipts_root = reth_primitives::proofs::calculate_receipt_root(receipts.clone());
-    if receipts_root != expected_receipts_root {
-        return Err(Error::ReceiptRootDiff { got: receipts_root, expected: expected_receipts_root })
+) -> Result<(), BlockValidationError> {
+    if let Some(transaction_index) =
+        receipts.clone().position(|receipt| !expected_logs_bloom.contains_bloom(&receipt.bloom))
+    {
+        return Err(BlockValidationError::TransactionLogsNotInBloom { transaction_index })
     }
 
     // Create header log bloom.
-    let logs_bloom = receipts.fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
+    let logs_bloom = receipts_bloom(receipts);
     if logs_bloom != expected_logs_bloom {
-        return Err(Error::BloomLogDiff {
+        return Err(BlockValidationError::BloomLogDiff {
             expected: Box::new(expected_logs_bloom),
             got: Box::new(logs_bloom),
         })
@@ -298,32 +316,119 @@ pub fn verify_receipt<'a>(
     Ok(())
 }
 
+/// Verify the receipts root of the header against the receipts.
+pub fn verify_receipts_root<'a>(
+    expected_receipts_root: H256,
+    receipts: impl Iterator<Item = &'a Receipt>,
+) -> Result<(), BlockValidationError> {
+    let receipts_root = reth_primitives::proofs::calculate_receipt_root(receipts);
+    if receipts_root != expected_receipts_root {
+        return Err(BlockValidationError::ReceiptRootDiff {
+            got: receipts_root,
+            expected: expected_receipts_root,
+        })
+    }
+    Ok(())
+}
+
 /// Verify block. Execute all transaction and compare results.
 /// Returns ChangeSet on transaction granularity.
 /// NOTE: If block reward is still active (Before Paris/Merge) we would return
 /// additional TransactionStatechangeset for account that receives the reward.
+/// The withdrawals of the block (after Shanghai) are part of that changeset as well.
 pub fn execute<DB: StateProvider>(
     header: &Header,
     transactions: &[TransactionSignedEcRecovered],
     ommers: &[Header],
-    config: &Config,
+    withdrawals: Option<&[Withdrawal]>,
+    chain_spec: &ChainSpec,
     db: SubState<DB>,
 ) -> Result<ExecutionResult, Error> {
+    execute_with_config(header, transactions, ommers, withdrawals, chain_spec, db, &EthEvmConfig)
+}
+
+/// Same as [execute], but the block is executed by the EVM with the extensions of the given
+/// [EvmConfig].
+pub fn execute_with_config<DB: StateProvider>(
+    header: &Header,
+    transactions: &[TransactionSignedEcRecovered],
+    ommers: &[Header],
+    withdrawals: Option<&[Withdrawal]>,
+    chain_spec: &ChainSpec,
+    db: SubState<DB>,
+    evm_config: &dyn EvmConfig,
+) -> Result<ExecutionResult, Error> {
+    let mut extensions = evm_config.extensions(chain_spec, header);
+    // the transactions are only inspected if there is anything to apply
+    if extensions.is_empty() {
+        execute_with_inspector(
+            header,
+            transactions,
+            ommers,
+            withdrawals,
+            chain_spec,
+            db,
+            &mut NoopBlockInspector,
+        )
+    } else {
+        execute_with_inspector(
+            header,
+            transactions,
+            ommers,
+            withdrawals,
+            chain_spec,
+            db,
+            &mut extensions,
+        )
+    }
+}
+
+/// Same as [execute], but every transaction is executed by the given [BlockInspector], e.g. to
+/// trace the transactions of the block or to generate their access lists.
+pub fn execute_with_inspector<DB: StateProvider, I: BlockInspector<SubState<DB>>>(
+    header: &Header,
+    transactions: &[TransactionSignedEcRecovered],
+    ommers: &[Header],
+    withdrawals: Option<&[Withdrawal]>,
+    chain_spec: &ChainSpec,
+    db: SubState<DB>,
+    inspector: &mut I,
+) -> Result<ExecutionResult, Error> {
+    // revm has no Cancun spec yet, so the transient storage (EIP-1153), MCOPY (EIP-5656) and the
+    // restricted SELFDESTRUCT (EIP-6780) can't be executed
+    if chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, header.timestamp) {
+        return Err(Error::CancunNotSupported { block_number: header.number })
+    }
+
     let mut evm = EVM::new();
     evm.database(db);
 
-    let spec_id = config.spec_upgrades.revm_spec(header.number);
-    evm.env.cfg.chain_id = evmU256::from_limbs(config.chain_id.0);
-    evm.env.cfg.spec_id = config.spec_upgrades.revm_spec(header.number);
-    evm.env.cfg.perf_all_precompiles_have_balance = false;
-    evm.env.cfg.perf_analyse_created_bytecodes = AnalysisKind::Raw;
+    revm_wrap::fill_cfg_env(&mut evm.env.cfg, chain_spec, header);
+    let spec_id = evm.env.cfg.spec_id;
 
     revm_wrap::fill_block_env(&mut evm.env.block, header, spec_id >= SpecId::MERGE);
+
+    // The DAO hardfork drains the DAO accounts before the transactions of the block.
+    let dao_drained_accounts = if chain_spec.fork(Hardfork::Dao).as_block() == Some(header.number) {
+        Some(apply_dao_fork(evm.db().expect("database is set"))?)
+    } else {
+        None
+    };
+
+    // EIP-4788 stores the parent beacon block root before the transactions of the block.
+    let block_storage =
+        if chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, header.timestamp) {
+            apply_beacon_root_contract_call(header, evm.db().expect("database is set"))?
+        } else {
+            BTreeMap::new()
+        };
+    let blob_gasprice = header.blob_gasprice();
+
     let mut cumulative_gas_used = 0;
     // output of verification
     let mut changesets = Vec::with_capacity(transactions.len());
 
-    for transaction in transactions.iter() {
+    for (transaction_index, transaction) in transactions.iter().enumerate() {
         // The sum of the transaction’s gas limit, Tg, and the gas utilised in this block prior,
         // must be no greater than the block’s gasLimit.
         let block_available_gas = header.gas_limit - cumulative_gas_used;
@@ -334,18 +439,42 @@ pub fn execute<DB: StateProvider>(
             })
         }
 
+        // EIP-4844: the blob gas is paid on top of the execution gas and burned. The sender has
+        // to be able to pay for both at their max fees.
+        let blob_fee = match (transaction.max_fee_per_blob_gas(), blob_gasprice) {
+            (Some(max_fee_per_blob_gas), Some(blob_gasprice)) => {
+                if max_fee_per_blob_gas < blob_gasprice {
+                    return Err(Error::BlobGasPriceMoreThanMaxFee {
+                        max_fee_per_blob_gas,
+                        blob_gasprice,
+                    })
+                }
+                let blob_gas = transaction.blob_versioned_hashes().len() as u64 * BLOB_GAS_PER_BLOB;
+                let max_cost = revm::U256::from(transaction.gas_limit()) *
+                    revm::U256::from(transaction.max_fee_per_gas()) +
+                    revm::U256::from(transaction.value()) +
+                    revm::U256::from(blob_gas) * revm::U256::from(max_fee_per_blob_gas);
+                let sender = evm
+                    .db()
+                    .expect("database is set")
+                    .basic(B160(transaction.signer().0))
+                    .map_err(|_| Error::ProviderError)?
+                    .unwrap_or_default();
+                if sender.balance < max_cost {
+                    return Err(Error::BlobTransactionInsufficientFunds)
+                }
+                revm::U256::from(blob_gas) * revm::U256::from(blob_gasprice)
+            }
+            _ => revm::U256::ZERO,
+        };
+
         // Fill revm structure.
         revm_wrap::fill_tx_env(&mut evm.env.tx, transaction);
 
         // Execute transaction.
-        let out = evm.transact();
-
-        // Useful for debugging
-        // let out = evm.inspect(revm::inspectors::CustomPrintTracer::default());
-        // tracing::trace!(target:"evm","Executing transaction {:?}, \n:{out:?}: {:?}
-        // \nENV:{:?}",transaction.hash(),transaction,evm.env);
+        let out = inspector.inspect_transaction(transaction_index, transaction, &mut evm);
 
-        let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = out;
+        let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, mut state) = out;
 
         // Fatal internal error.
         if exit_reason == revm::Return::FatalExternalError {
@@ -364,6 +493,14 @@ pub fn execute<DB: StateProvider>(
 
         // Add spend gas.
         cumulative_gas_used += gas_used;
+        if cumulative_gas_used > header.gas_used {
+            return Err(BlockValidationError::TransactionGasUsedExceedsBlockGasUsed {
+                transaction_index,
+                cumulative_gas_used,
+                block_gas_used: header.gas_used,
+            }
+            .into())
+        }
 
         // Transform logs to reth format.
         let logs: Vec<Log> = logs
@@ -375,6 +512,13 @@ pub fn execute<DB: StateProvider>(
             })
             .collect();
 
+        // burn the blob fee, which the balance check above leaves room for
+        if blob_fee != revm::U256::ZERO {
+            if let Some(sender) = state.get_mut(&B160(transaction.signer().0)) {
+                sender.info.balance -= blob_fee;
+            }
+        }
+
         // commit state
         let (changeset, new_bytecodes) = commit_changes(evm.db().unwrap(), state);
 
@@ -394,13 +538,172 @@ pub fn execute<DB: StateProvider>(
 
     // Check if gas used matches the value set in header.
     if header.gas_used != cumulative_gas_used {
-        return Err(Error::BlockGasUsed { got: cumulative_gas_used, expected: header.gas_used })
+        return Err(BlockValidationError::BlockGasUsed {
+            got: cumulative_gas_used,
+            expected: header.gas_used,
+        }
+        .into())
     }
 
     let mut db = evm.db.expect("It is set at the start of the function");
-    let block_reward = block_reward_changeset(header, ommers, &mut db, config)?;
+    let mut block_reward = block_reward_changeset(header, ommers, &mut db, chain_spec)?;
+    if let Some(drained_accounts) = dao_drained_accounts {
+        dao_fork_changeset(
+            &mut db,
+            drained_accounts,
+            block_reward.get_or_insert_with(BTreeMap::new),
+        )?;
+    }
+    if let Some(withdrawals) = withdrawals {
+        withdrawals_changeset(
+            withdrawals,
+            &mut db,
+            block_reward.get_or_insert_with(BTreeMap::new),
+        )?;
+    }
 
-    Ok(ExecutionResult { changesets, block_reward })
+    Ok(ExecutionResult { changesets, block_reward, block_storage })
+}
+
+/// Stores the parent beacon block root in the [BEACON_ROOTS_ADDRESS] contract, which is the effect
+/// of the system call of [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788) before the
+/// transactions of the block.
+///
+/// The contract keeps the timestamp of the block and the root in a ring buffer. Nothing is stored
+/// if the contract is not deployed. Returns the changed slots with their old and new values.
+pub fn apply_beacon_root_contract_call<DB: StateProvider>(
+    header: &Header,
+    db: &mut SubState<DB>,
+) -> Result<BTreeMap<Address, BTreeMap<U256, (U256, U256)>>, Error> {
+    let Some(parent_beacon_block_root) = header.parent_beacon_block_root else {
+        return Ok(BTreeMap::new())
+    };
+    let address = B160(BEACON_ROOTS_ADDRESS.0);
+    let info = db.basic(address).map_err(|_| Error::ProviderError)?;
+    if info.map_or(true, |info| info.code_hash.0 == KECCAK_EMPTY.0) {
+        return Ok(BTreeMap::new())
+    }
+
+    let timestamp_index = header.timestamp % BEACON_ROOTS_HISTORY_BUFFER_LENGTH;
+    let root_index = timestamp_index + BEACON_ROOTS_HISTORY_BUFFER_LENGTH;
+    let mut slots = BTreeMap::new();
+    for (index, value) in [
+        (timestamp_index, U256::from(header.timestamp)),
+        (root_index, U256::from_big_endian(parent_beacon_block_root.as_bytes())),
+    ] {
+        let slot = revm::U256::from(index);
+        let old = db.storage(address, slot).map_err(|_| Error::ProviderError)?;
+        db.insert_account_storage(address, slot, revm::U256::from_limbs(value.0))
+            .map_err(|_| Error::ProviderError)?;
+        slots.insert(U256::from(index), (U256(*old.as_limbs()), value));
+    }
+    Ok(BTreeMap::from([(BEACON_ROOTS_ADDRESS, slots)]))
+}
+
+/// Adds the balance increments of the withdrawals to the changeset of the block reward.
+///
+/// The withdrawals are processed after the transactions of the block, see
+/// [EIP-4895](https://eips.ethereum.org/EIPS/eip-4895).
+pub fn withdrawals_changeset<DB: StateProvider>(
+    withdrawals: &[Withdrawal],
+    db: &mut SubState<DB>,
+    changeset: &mut BTreeMap<Address, AccountInfoChangeSet>,
+) -> Result<(), Error> {
+    // the withdrawals to the same address are applied cumulatively
+    let mut increments = BTreeMap::<Address, U256>::new();
+    for withdrawal in withdrawals.iter().filter(|withdrawal| withdrawal.amount > 0) {
+        *increments.entry(withdrawal.address).or_default() += withdrawal.amount_wei();
+    }
+
+    for (address, increment) in increments {
+        let account_changeset = match changeset.remove(&address) {
+            Some(AccountInfoChangeSet::Changed { old, mut new }) => {
+                new.balance += increment;
+                AccountInfoChangeSet::Changed { old, new }
+            }
+            Some(AccountInfoChangeSet::Created { mut new }) => {
+                new.balance += increment;
+                AccountInfoChangeSet::Created { new }
+            }
+            _ => match db.basic(B160(address.0)).map_err(|_| Error::ProviderError)? {
+                Some(info) => {
+                    let old = to_reth_acc(&info);
+                    let mut new = old;
+                    new.balance += increment;
+                    AccountInfoChangeSet::Changed { old, new }
+                }
+                None => AccountInfoChangeSet::Created {
+                    new: Account { nonce: 0, balance: increment, bytecode_hash: None },
+                },
+            },
+        };
+        changeset.insert(address, account_changeset);
+    }
+    Ok(())
+}
+
+/// Moves the balances of the [DAO_HARDFORK_ACCOUNTS] to the [DAO_HARDFORK_BENEFICIARY].
+///
+/// Returns the changed accounts as they were before the change.
+pub fn apply_dao_fork<DB: StateProvider>(
+    db: &mut SubState<DB>,
+) -> Result<BTreeMap<Address, Option<Account>>, Error> {
+    let mut drained_accounts = BTreeMap::new();
+    let mut drained_balance = revm::U256::ZERO;
+    for address in DAO_HARDFORK_ACCOUNTS {
+        let Some(mut info) = db.basic(B160(address.0)).map_err(|_| Error::ProviderError)? else {
+            continue
+        };
+        if info.balance == revm::U256::ZERO {
+            continue
+        }
+        drained_accounts.insert(address, Some(to_reth_acc(&info)));
+        drained_balance += info.balance;
+        info.balance = revm::U256::ZERO;
+        db.insert_account_info(B160(address.0), info);
+    }
+
+    if drained_balance != revm::U256::ZERO {
+        let beneficiary = B160(DAO_HARDFORK_BENEFICIARY.0);
+        let info = db.basic(beneficiary).map_err(|_| Error::ProviderError)?;
+        drained_accounts.insert(DAO_HARDFORK_BENEFICIARY, info.as_ref().map(to_reth_acc));
+        let mut info = info.unwrap_or_default();
+        info.balance += drained_balance;
+        db.insert_account_info(beneficiary, info);
+    }
+    Ok(drained_accounts)
+}
+
+/// Adds the changes of the accounts of the DAO hardfork to the changeset of the block reward.
+///
+/// The changes are relative to the state before the block, as the DAO hardfork is applied before
+/// the transactions, while the block reward changeset comes after them. The new values are the
+/// values at the end of the block, so they include the changes of the transactions and of the
+/// block reward.
+fn dao_fork_changeset<DB: StateProvider>(
+    db: &mut SubState<DB>,
+    drained_accounts: BTreeMap<Address, Option<Account>>,
+    changeset: &mut BTreeMap<Address, AccountInfoChangeSet>,
+) -> Result<(), Error> {
+    for (address, old) in drained_accounts {
+        let new = match changeset.get(&address) {
+            Some(
+                AccountInfoChangeSet::Changed { new, .. } | AccountInfoChangeSet::Created { new },
+            ) => Some(*new),
+            _ => db
+                .basic(B160(address.0))
+                .map_err(|_| Error::ProviderError)?
+                .map(|info| to_reth_acc(&info)),
+        };
+        let account_changeset = match (old, new) {
+            (Some(old), Some(new)) if old != new => AccountInfoChangeSet::Changed { old, new },
+            (None, Some(new)) => AccountInfoChangeSet::Created { new },
+            (Some(old), None) => AccountInfoChangeSet::Destroyed { old },
+            _ => continue,
+        };
+        changeset.insert(address, account_changeset);
+    }
+    Ok(())
 }
 
 /// Calculate Block reward changeset
@@ -408,7 +711,7 @@ pub fn block_reward_changeset<DB: StateProvider>(
     header: &Header,
     ommers: &[Header],
     db: &mut SubState<DB>,
-    config: &Config,
+    chain_spec: &ChainSpec,
 ) -> Result<Option<BTreeMap<H160, AccountInfoChangeSet>>, Error> {
     // NOTE: Related to Ethereum reward change, for other network this is probably going to be moved
     // to config.
@@ -420,9 +723,9 @@ pub fn block_reward_changeset<DB: StateProvider>(
     // block’s beneficiary by an additional 1/32 of the block reward and the beneficiary of the
     // ommer gets rewarded depending on the blocknumber. Formally we define the function Ω:
     match header.number {
-        n if n >= config.spec_upgrades.paris => None,
-        n if n >= config.spec_upgrades.petersburg => Some(WEI_2ETH),
-        n if n >= config.spec_upgrades.byzantium => Some(WEI_3ETH),
+        n if !chain_spec.has_block_reward(n) => None,
+        n if chain_spec.is_fork_active_at_block(Hardfork::Petersburg, n) => Some(WEI_2ETH),
+        n if chain_spec.is_fork_active_at_block(Hardfork::Byzantium, n) => Some(WEI_3ETH),
         _ => Some(WEI_5ETH),
     }
     .map(|reward| -> Result<_, _> {
@@ -470,17 +773,12 @@ pub fn block_reward_changeset<DB: StateProvider>(
 #[cfg(test)]
 mod tests {
 
-    use std::{collections::HashMap, sync::Arc};
+    use std::collections::HashMap;
 
-    use crate::{config::SpecUpgrades, revm_wrap::State};
-    use reth_db::{
-        database::Database,
-        mdbx::{test_utils, Env, EnvKind, WriteMap},
-        transaction::DbTx,
-    };
+    use crate::revm_wrap::State;
     use reth_primitives::{
-        hex_literal::hex, keccak256, Account, Address, Bytes, SealedBlock, StorageKey, H160, H256,
-        U256,
+        hex_literal::hex, keccak256, Account, Address, Bytes, ChainSpecBuilder, ForkCondition,
+        SealedBlock, StorageKey, TxType, Withdrawal, H160, H256, U256,
     };
     use reth_provider::{AccountProvider, StateProvider};
     use reth_rlp::Decodable;
@@ -572,17 +870,25 @@ mod tests {
             HashMap::new(),
         );
 
-        let mut config = Config::new_ethereum();
         // make it berlin fork
-        config.spec_upgrades = SpecUpgrades::new_berlin_activated();
+        let chain_spec = ChainSpecBuilder::mainnet().berlin_activated().build();
 
+        let pre_state = db.clone();
         let db = SubState::new(State::new(db));
         let transactions: Vec<TransactionSignedEcRecovered> =
             block.body.iter().map(|tx| tx.try_ecrecovered().unwrap()).collect();
 
         // execute chain and verify receipts
-        let out =
-            execute_and_verify_receipt(&block.header, &transactions, &ommers, &config, db).unwrap();
+        let out = execute_and_verify_receipt(
+            &block.header,
+            &transactions,
+            &ommers,
+            None,
+            &chain_spec,
+            db,
+            &EthEvmConfig,
+        )
+        .unwrap();
 
         assert_eq!(out.changesets.len(), 1, "Should executed one transaction");
 
@@ -655,43 +961,203 @@ mod tests {
             Some(&(0.into(), 2.into())),
             "Storage change from 0 to 2 on slot 1"
         );
+
+        // an inspector executes every transaction in place of the executor
+        let mut inspected = Vec::new();
+        let inspected_out = execute_with_inspector(
+            &block.header,
+            &transactions,
+            &ommers,
+            None,
+            &chain_spec,
+            SubState::new(State::new(pre_state)),
+            &mut |index, transaction: &TransactionSignedEcRecovered, evm: &mut EVM<_>| {
+                inspected.push((index, transaction.hash()));
+                evm.transact()
+            },
+        )
+        .unwrap();
+        assert_eq!(inspected, vec![(0, transactions[0].hash())]);
+        assert_eq!(inspected_out.changesets[0].receipt, out.changesets[0].receipt);
+    }
+
+    #[test]
+    fn dao_hardfork_irregular_state_change() {
+        let header = Header { number: 1, ..Default::default() };
+
+        let mut db = StateProviderTest::default();
+        for (index, address) in DAO_HARDFORK_ACCOUNTS.iter().enumerate() {
+            let account = Account { balance: (index + 1).into(), nonce: 0, bytecode_hash: None };
+            db.insert_account(*address, account, None, HashMap::new());
+        }
+        let beneficiary = Account { balance: 100.into(), nonce: 0, bytecode_hash: None };
+        db.insert_account(DAO_HARDFORK_BENEFICIARY, beneficiary, None, HashMap::new());
+
+        let chain_spec =
+            ChainSpecBuilder::mainnet().with_fork(Hardfork::Dao, ForkCondition::Block(1)).build();
+        let out =
+            execute(&header, &[], &[], None, &chain_spec, SubState::new(State::new(db))).unwrap();
+        let changeset = out.block_reward.unwrap();
+
+        // every drained account and the refund contract, plus the block reward of the miner
+        assert_eq!(changeset.len(), DAO_HARDFORK_ACCOUNTS.len() + 2);
+        assert_eq!(
+            changeset[&DAO_HARDFORK_ACCOUNTS[1]],
+            AccountInfoChangeSet::Changed {
+                old: Account { balance: 2.into(), nonce: 0, bytecode_hash: None },
+                new: Account { balance: 0.into(), nonce: 0, bytecode_hash: None },
+            }
+        );
+        let drained: u64 = (1..=DAO_HARDFORK_ACCOUNTS.len() as u64).sum();
+        assert_eq!(
+            changeset[&DAO_HARDFORK_BENEFICIARY],
+            AccountInfoChangeSet::Changed {
+                old: beneficiary,
+                new: Account { balance: (100 + drained).into(), ..beneficiary },
+            }
+        );
+        assert_eq!(
+            changeset[&header.beneficiary],
+            AccountInfoChangeSet::Created {
+                new: Account { balance: WEI_5ETH.into(), nonce: 0, bytecode_hash: None }
+            }
+        );
+    }
+
+    #[test]
+    fn withdrawals_balance_increments() {
+        let header = Header { number: 1, ..Default::default() };
+        let existing = Address::from_low_u64_be(1);
+        let new = Address::from_low_u64_be(2);
+        let withdrawal =
+            |index, address, amount| Withdrawal { index, validator_index: index, address, amount };
+        let withdrawals = [
+            withdrawal(0, existing, 1),
+            withdrawal(1, new, 2),
+            withdrawal(2, existing, 3),
+            withdrawal(3, Address::from_low_u64_be(3), 0),
+        ];
+
+        let mut db = StateProviderTest::default();
+        let account = Account { balance: 5.into(), nonce: 1, bytecode_hash: None };
+        db.insert_account(existing, account, None, HashMap::new());
+
+        let chain_spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        let db = SubState::new(State::new(db));
+        let out = execute(&header, &[], &[], Some(&withdrawals[..]), &chain_spec, db).unwrap();
+
+        // the withdrawals are applied cumulatively, and zero withdrawals don't touch the account
+        let gwei = U256::from(1_000_000_000u64);
+        assert_eq!(
+            out.block_reward,
+            Some(BTreeMap::from([
+                (
+                    existing,
+                    AccountInfoChangeSet::Changed {
+                        old: account,
+                        new: Account { balance: U256::from(5) + gwei * 4, ..account },
+                    }
+                ),
+                (
+                    new,
+                    AccountInfoChangeSet::Created {
+                        new: Account { balance: gwei * 2, nonce: 0, bytecode_hash: None }
+                    }
+                ),
+            ]))
+        );
+
+        // a block without withdrawals has no changes after the merge
+        let db = SubState::new(State::new(StateProviderTest::default()));
+        let out = execute(&header, &[], &[], None, &chain_spec, db).unwrap();
+        assert_eq!(out.block_reward, None);
     }
 
     #[test]
-    fn apply_account_info_changeset() {
-        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
-        let address = H160::zero();
-        let tx_num = 0;
-        let acc1 = Account { balance: 1.into(), nonce: 2, bytecode_hash: Some(H256::zero()) };
-        let acc2 = Account { balance: 3.into(), nonce: 4, bytecode_hash: Some(H256::zero()) };
-
-        let tx = db.tx_mut().unwrap();
-
-        // check Changed changeset
-        AccountInfoChangeSet::Changed { new: acc1, old: acc2 }
-            .apply_to_db(&tx, address, tx_num)
-            .unwrap();
+    fn beacon_root_contract_call() {
+        let parent_beacon_block_root = H256::random();
+        let timestamp = BEACON_ROOTS_HISTORY_BUFFER_LENGTH + 5;
+        let header = Header {
+            number: 1,
+            timestamp,
+            parent_beacon_block_root: Some(parent_beacon_block_root),
+            ..Default::default()
+        };
+
+        // nothing is stored if the contract is not deployed
+        let mut db = SubState::new(State::new(StateProviderTest::default()));
+        assert_eq!(apply_beacon_root_contract_call(&header, &mut db), Ok(BTreeMap::new()));
+
+        let mut db = StateProviderTest::default();
+        let storage = HashMap::from([(H256::from_low_u64_be(5), U256::from(1))]);
+        db.insert_account(
+            BEACON_ROOTS_ADDRESS,
+            Account::default(),
+            Some(Bytes::from(hex!("6000").to_vec())),
+            storage,
+        );
+        let mut db = SubState::new(State::new(db));
+
+        let root = U256::from_big_endian(parent_beacon_block_root.as_bytes());
+        let slots = BTreeMap::from([
+            (U256::from(5), (U256::from(1), U256::from(timestamp))),
+            (U256::from(5 + BEACON_ROOTS_HISTORY_BUFFER_LENGTH), (U256::zero(), root)),
+        ]);
         assert_eq!(
-            tx.get::<tables::AccountChangeSet>(tx_num),
-            Ok(Some(AccountBeforeTx { address, info: Some(acc2) }))
+            apply_beacon_root_contract_call(&header, &mut db),
+            Ok(BTreeMap::from([(BEACON_ROOTS_ADDRESS, slots)]))
         );
-        assert_eq!(tx.get::<tables::PlainAccountState>(address), Ok(Some(acc1)));
+    }
+
+    #[test]
+    fn reject_cancun_blocks() {
+        let header = Header { number: 1, ..Default::default() };
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
 
-        AccountInfoChangeSet::Created { new: acc1 }.apply_to_db(&tx, address, tx_num).unwrap();
+        let db = SubState::new(State::new(StateProviderTest::default()));
         assert_eq!(
-            tx.get::<tables::AccountChangeSet>(tx_num),
-            Ok(Some(AccountBeforeTx { address, info: None }))
+            execute(&header, &[], &[], Some(&[]), &chain_spec, db).unwrap_err(),
+            Error::CancunNotSupported { block_number: 1 }
         );
-        assert_eq!(tx.get::<tables::PlainAccountState>(address), Ok(Some(acc1)));
+    }
 
-        // delete old value, as it is dupsorted
-        tx.delete::<tables::AccountChangeSet>(tx_num, None).unwrap();
+    #[test]
+    fn verify_receipts_root_and_bloom() {
+        let log = Log { address: Address::from_low_u64_be(1), topics: vec![], data: vec![].into() };
+        let receipts = vec![Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21000,
+            bloom: logs_bloom([&log]),
+            logs: vec![log],
+        }];
+        let receipts_root = reth_primitives::proofs::calculate_receipt_root(receipts.iter());
+        let bloom = receipts_bloom(&receipts);
+
+        assert_eq!(verify_receipts_root(receipts_root, receipts.iter()), Ok(()));
+        assert_eq!(verify_logs_bloom(bloom, receipts.iter()), Ok(()));
+        assert_eq!(
+            verify_receipts_root(H256::zero(), receipts.iter()),
+            Err(BlockValidationError::ReceiptRootDiff {
+                got: receipts_root,
+                expected: H256::zero()
+            })
+        );
+        assert_eq!(
+            verify_logs_bloom(Bloom::zero(), receipts.iter()),
+            Err(BlockValidationError::TransactionLogsNotInBloom { transaction_index: 0 })
+        );
 
-        AccountInfoChangeSet::Destroyed { old: acc2 }.apply_to_db(&tx, address, tx_num).unwrap();
-        assert_eq!(tx.get::<tables::PlainAccountState>(address), Ok(None));
+        // a bloom with additional bits doesn't match the logs of any transaction
+        let other_log =
+            Log { address: Address::from_low_u64_be(2), topics: vec![], data: vec![].into() };
+        let extra_bloom = bloom | logs_bloom([&other_log]);
         assert_eq!(
-            tx.get::<tables::AccountChangeSet>(tx_num),
-            Ok(Some(AccountBeforeTx { address, info: Some(acc2) }))
+            verify_logs_bloom(extra_bloom, receipts.iter()),
+            Err(BlockValidationError::BloomLogDiff {
+                got: Box::new(bloom),
+                expected: Box::new(extra_bloom)
+            })
         );
     }
 }
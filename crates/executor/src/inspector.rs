@@ -0,0 +1,54 @@
+//! Hooks to inspect the transactions of an executed block.
+
+use hashbrown::HashMap;
+use reth_primitives::TransactionSignedEcRecovered;
+use revm::{Account as RevmAccount, Database, ExecutionResult, B160, EVM};
+
+/// The result and the state changes of an executed transaction, as returned by [EVM::transact].
+pub type TransactionOutput = (ExecutionResult, HashMap<B160, RevmAccount>);
+
+/// Executes the transactions of a block on behalf of the executor, e.g. to trace them with a revm
+/// [Inspector](revm::Inspector).
+///
+/// The environment of the [EVM] is filled for the transaction before the call, and the returned
+/// result and state changes are handled by the executor just like the output of
+/// [EVM::transact].
+pub trait BlockInspector<DB: Database> {
+    /// Executes the transaction with the given index in the block.
+    fn inspect_transaction(
+        &mut self,
+        transaction_index: usize,
+        transaction: &TransactionSignedEcRecovered,
+        evm: &mut EVM<DB>,
+    ) -> TransactionOutput;
+}
+
+impl<DB, F> BlockInspector<DB> for F
+where
+    DB: Database,
+    F: FnMut(usize, &TransactionSignedEcRecovered, &mut EVM<DB>) -> TransactionOutput,
+{
+    fn inspect_transaction(
+        &mut self,
+        transaction_index: usize,
+        transaction: &TransactionSignedEcRecovered,
+        evm: &mut EVM<DB>,
+    ) -> TransactionOutput {
+        self(transaction_index, transaction, evm)
+    }
+}
+
+/// Executes the transactions without inspecting them.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopBlockInspector;
+
+impl<DB: Database> BlockInspector<DB> for NoopBlockInspector {
+    fn inspect_transaction(
+        &mut self,
+        _transaction_index: usize,
+        _transaction: &TransactionSignedEcRecovered,
+        evm: &mut EVM<DB>,
+    ) -> TransactionOutput {
+        evm.transact()
+    }
+}
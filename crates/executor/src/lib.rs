@@ -8,8 +8,16 @@
 //! Reth executor executes transaction in block of data.
 
 pub mod config;
+pub mod dao_fork;
+pub mod evm_config;
 /// Executor
 pub mod executor;
+pub mod inspector;
+pub mod metrics;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 /// Wrapper around revm database and types
 pub mod revm_wrap;
-pub use config::{Config, SpecUpgrades};
+/// State changes of executed blocks that are not committed to the database
+pub mod state;
+pub use config::revm_spec;
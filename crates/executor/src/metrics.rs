@@ -0,0 +1,61 @@
+//! Execution metrics.
+
+use metrics::{
+    describe_gauge, describe_histogram, register_gauge, register_histogram, Gauge, Histogram,
+};
+use std::time::Duration;
+
+/// Metrics of the blocks executed by an executor, labeled with the executor, e.g. the execution
+/// stage.
+#[derive(Debug, Clone)]
+pub struct ExecutionMetrics {
+    /// Gas executed per second by the last executed block in millions
+    mgas_per_second: Gauge,
+    /// Transactions executed per second by the last executed block
+    transactions_per_second: Gauge,
+    /// Duration of the execution of a single block in seconds
+    block_execution_duration: Histogram,
+}
+
+impl ExecutionMetrics {
+    /// Register the metrics of the executor.
+    pub fn new(executor: &'static str) -> Self {
+        Self {
+            mgas_per_second: register_gauge!("execution_mgas_per_second", "executor" => executor),
+            transactions_per_second: register_gauge!(
+                "execution_transactions_per_second",
+                "executor" => executor
+            ),
+            block_execution_duration: register_histogram!(
+                "execution_block_duration",
+                "executor" => executor
+            ),
+        }
+    }
+
+    /// Describe the metrics of all executors.
+    pub fn describe() {
+        describe_gauge!(
+            "execution_mgas_per_second",
+            "Gas executed per second by the last executed block in millions"
+        );
+        describe_gauge!(
+            "execution_transactions_per_second",
+            "Transactions executed per second by the last executed block"
+        );
+        describe_histogram!(
+            "execution_block_duration",
+            "Duration of the execution of a single block in seconds"
+        );
+    }
+
+    /// Record the execution of a block with the given gas used and number of transactions.
+    pub fn record_block(&self, gas_used: u64, transactions: usize, elapsed: Duration) {
+        self.block_execution_duration.record(elapsed.as_secs_f64());
+        let seconds = elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.mgas_per_second.set(gas_used as f64 / 1_000_000.0 / seconds);
+            self.transactions_per_second.set(transactions as f64 / seconds);
+        }
+    }
+}
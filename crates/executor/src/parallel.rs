@@ -0,0 +1,503 @@
+//! Experimental block executor that executes the transactions of a block in parallel.
+//!
+//! Every transaction is first executed speculatively against the state before the block, in
+//! parallel, while the accounts and storage slots it reads are tracked. The speculative results
+//! are then committed serially in the order of the block. A speculative result is only used if
+//! none of the keys it read were written by the preceding transactions of the block, otherwise
+//! the transaction is executed again on top of the state of its predecessors. The result is
+//! therefore the same as the result of [execute](crate::executor::execute), which makes the
+//! executor suited for historical sync, where most transactions of a block are independent.
+//!
+//! Every transaction pays the beneficiary of the block, so reads of the beneficiary are not
+//! tracked as conflicts. The balance increment of a speculative result is added to the balance of
+//! the beneficiary instead, and transactions that otherwise access the beneficiary are executed
+//! serially.
+
+use crate::{
+    executor::{
+        apply_beacon_root_contract_call, apply_dao_fork, execute_with_inspector, verify_receipts,
+        ExecutionResult,
+    },
+    inspector::BlockInspector,
+    revm_wrap::{self, State, SubState},
+};
+use hashbrown::{HashMap, HashSet};
+use reth_interfaces::executor::Error;
+use reth_primitives::{
+    Account, Address, Bytes, ChainSpec, Hardfork, Header, StorageKey, StorageValue,
+    TransactionKind, TransactionSignedEcRecovered, Withdrawal, H256, U256,
+};
+use reth_provider::{AccountProvider, StateProvider};
+use revm::{
+    Account as RevmAccount, AccountInfo, Bytecode, Database, EVMData, Inspector, Interpreter,
+    Return, SpecId, B160, B256, EVM,
+};
+use tracing::debug;
+
+/// Executes the transactions of blocks in parallel on a dedicated thread pool.
+#[derive(Debug)]
+pub struct ParallelExecutor {
+    pool: rayon::ThreadPool,
+}
+
+impl ParallelExecutor {
+    /// Creates an executor with the given number of threads, or one thread per core if it is
+    /// zero.
+    pub fn new(threads: usize) -> Result<Self, rayon::ThreadPoolBuildError> {
+        // the EVM needs a large stack, see the execution stage
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .stack_size(50 * 1024 * 1024)
+            .thread_name(|index| format!("parallel-executor-{index}"))
+            .build()?;
+        Ok(Self { pool })
+    }
+
+    /// Executes the block on top of the given state, same as
+    /// [execute](crate::executor::execute).
+    pub fn execute<SP: StateProvider>(
+        &self,
+        header: &Header,
+        transactions: &[TransactionSignedEcRecovered],
+        ommers: &[Header],
+        withdrawals: Option<&[Withdrawal]>,
+        chain_spec: &ChainSpec,
+        state: &SP,
+    ) -> Result<ExecutionResult, Error> {
+        let (result, reexecuted) =
+            self.execute_block(header, transactions, ommers, withdrawals, chain_spec, state)?;
+        debug!(
+            target: "executor::parallel",
+            block_number = header.number,
+            transactions = transactions.len(),
+            reexecuted,
+            "Executed block in parallel"
+        );
+        Ok(result)
+    }
+
+    /// Executes the block and verifies the receipts, same as
+    /// [execute_and_verify_receipt](crate::executor::execute_and_verify_receipt).
+    pub fn execute_and_verify_receipt<SP: StateProvider>(
+        &self,
+        header: &Header,
+        transactions: &[TransactionSignedEcRecovered],
+        ommers: &[Header],
+        withdrawals: Option<&[Withdrawal]>,
+        chain_spec: &ChainSpec,
+        state: &SP,
+    ) -> Result<ExecutionResult, Error> {
+        let result = self.execute(header, transactions, ommers, withdrawals, chain_spec, state)?;
+        verify_receipts(header, chain_spec, &result)?;
+        Ok(result)
+    }
+
+    /// Executes the block and returns the result with the number of re-executed transactions.
+    fn execute_block<SP: StateProvider>(
+        &self,
+        header: &Header,
+        transactions: &[TransactionSignedEcRecovered],
+        ommers: &[Header],
+        withdrawals: Option<&[Withdrawal]>,
+        chain_spec: &ChainSpec,
+        state: &SP,
+    ) -> Result<(ExecutionResult, usize), Error> {
+        use rayon::prelude::*;
+
+        let speculations = self.pool.install(|| {
+            transactions
+                .par_iter()
+                .map(|transaction| speculate(header, transaction, chain_spec, state))
+                .collect()
+        });
+
+        let mut validator = Validator {
+            speculations,
+            coinbase: B160(header.beneficiary.0),
+            writes: AccessSet::default(),
+            reexecuted: 0,
+        };
+        let result = execute_with_inspector(
+            header,
+            transactions,
+            ommers,
+            withdrawals,
+            chain_spec,
+            SubState::new(State::new(SharedProvider(state))),
+            &mut validator,
+        )?;
+        Ok((result, validator.reexecuted))
+    }
+}
+
+/// The result of the speculative execution of a transaction against the state before the block.
+struct Speculation {
+    result: revm::ExecutionResult,
+    state: HashMap<B160, RevmAccount>,
+    reads: AccessSet,
+    /// The balance of the beneficiary before the transaction.
+    coinbase_balance: revm::U256,
+}
+
+/// Executes the transaction against the state before the block.
+///
+/// Returns `None` if the transaction has to be executed serially.
+fn speculate<SP: StateProvider>(
+    header: &Header,
+    transaction: &TransactionSignedEcRecovered,
+    chain_spec: &ChainSpec,
+    state: &SP,
+) -> Option<Speculation> {
+    let coinbase = B160(header.beneficiary.0);
+    let sends_to_coinbase =
+        matches!(transaction.kind(), TransactionKind::Call(to) if *to == header.beneficiary);
+    // the blob fee is burned by the executor after the transaction
+    if transaction.signer() == header.beneficiary ||
+        sends_to_coinbase ||
+        transaction.max_fee_per_blob_gas().is_some()
+    {
+        return None
+    }
+
+    // the state before the transactions of the block
+    let mut db = SubState::new(State::new(SharedProvider(state)));
+    if chain_spec.fork(Hardfork::Dao).as_block() == Some(header.number) {
+        apply_dao_fork(&mut db).ok()?;
+    }
+    if chain_spec.is_fork_active_at_timestamp(Hardfork::Cancun, header.timestamp) {
+        apply_beacon_root_contract_call(header, &mut db).ok()?;
+    }
+    let coinbase_balance = db.basic(coinbase).ok()?.map(|info| info.balance).unwrap_or_default();
+
+    let mut evm = EVM::new();
+    evm.database(ReadTracker { db, reads: AccessSet::default() });
+    revm_wrap::fill_cfg_env(&mut evm.env.cfg, chain_spec, header);
+    let after_merge = evm.env.cfg.spec_id >= SpecId::MERGE;
+    revm_wrap::fill_block_env(&mut evm.env.block, header, after_merge);
+    revm_wrap::fill_tx_env(&mut evm.env.tx, transaction);
+
+    let mut coinbase_accessed = false;
+    let (result, state) =
+        evm.inspect(CoinbaseAccess { coinbase, accessed: &mut coinbase_accessed });
+    if coinbase_accessed || result.exit_reason == Return::FatalExternalError {
+        return None
+    }
+
+    let mut reads = evm.db.expect("database is set").reads;
+    reads.accounts.remove(&coinbase);
+    Some(Speculation { result, state, reads, coinbase_balance })
+}
+
+/// Commits the speculative results that don't conflict with the preceding transactions, and
+/// executes the other transactions.
+struct Validator {
+    speculations: Vec<Option<Speculation>>,
+    coinbase: B160,
+    /// The keys written by the committed transactions.
+    writes: AccessSet,
+    /// The number of transactions whose speculative result was not used.
+    reexecuted: usize,
+}
+
+impl Validator {
+    /// Adds the keys written by the state changes of a transaction to the write set.
+    fn record_writes<DB: Database>(
+        &mut self,
+        db: &mut DB,
+        state: &HashMap<B160, RevmAccount>,
+    ) -> Result<(), DB::Error> {
+        for (address, account) in state {
+            if *address == self.coinbase {
+                continue
+            }
+            if account.is_destroyed || account.storage_cleared {
+                self.writes.accounts.insert(*address);
+                self.writes.wiped.insert(*address);
+            } else {
+                let current = db.basic(*address)?.unwrap_or_default();
+                if current.balance != account.info.balance ||
+                    current.nonce != account.info.nonce ||
+                    current.code_hash != account.info.code_hash
+                {
+                    self.writes.accounts.insert(*address);
+                }
+            }
+            self.writes.storage.extend(
+                account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.original_value() != slot.present_value())
+                    .map(|(index, _)| (*address, *index)),
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the speculative result of the transaction if it can be committed on top of the
+    /// given state, with the balance of the beneficiary adjusted to that state.
+    fn validate<DB: Database>(
+        &self,
+        db: &mut DB,
+        speculation: Speculation,
+    ) -> Result<Option<(revm::ExecutionResult, HashMap<B160, RevmAccount>)>, DB::Error> {
+        let Speculation { result, mut state, reads, coinbase_balance } = speculation;
+        if reads.is_affected_by(&self.writes) ||
+            state.get(&self.coinbase).map_or(false, |account| account.is_destroyed)
+        {
+            return Ok(None)
+        }
+
+        // the changes are committed against the accounts in the database, so they are loaded
+        for address in state.keys() {
+            db.basic(*address)?;
+        }
+        if let Some(account) = state.get_mut(&self.coinbase) {
+            let increment = account.info.balance.saturating_sub(coinbase_balance);
+            let mut info = db.basic(self.coinbase)?.unwrap_or_default();
+            info.balance += increment;
+            account.info = info;
+        }
+        Ok(Some((result, state)))
+    }
+}
+
+impl<DB: Database> BlockInspector<DB> for Validator {
+    fn inspect_transaction(
+        &mut self,
+        transaction_index: usize,
+        _transaction: &TransactionSignedEcRecovered,
+        evm: &mut EVM<DB>,
+    ) -> (revm::ExecutionResult, HashMap<B160, RevmAccount>) {
+        let speculation = self.speculations.get_mut(transaction_index).and_then(Option::take);
+        let db = evm.db().expect("database is set");
+        let validated = match speculation.map(|speculation| self.validate(db, speculation)) {
+            Some(Ok(validated)) => validated,
+            Some(Err(_)) | None => None,
+        };
+        let (mut result, state) = match validated {
+            Some(out) => out,
+            None => {
+                self.reexecuted += 1;
+                evm.transact()
+            }
+        };
+
+        let db = evm.db().expect("database is set");
+        if self.record_writes(db, &state).is_err() {
+            // fails the block, like a database error during the execution
+            result.exit_reason = Return::FatalExternalError;
+        }
+        (result, state)
+    }
+}
+
+/// Accounts and storage slots that are read or written by transactions.
+#[derive(Debug, Default)]
+struct AccessSet {
+    accounts: HashSet<B160>,
+    storage: HashSet<(B160, revm::U256)>,
+    /// Accounts whose whole storage is written.
+    wiped: HashSet<B160>,
+}
+
+impl AccessSet {
+    /// Returns `true` if any of the keys of this set are written by the given writes.
+    fn is_affected_by(&self, writes: &AccessSet) -> bool {
+        self.accounts.iter().any(|address| writes.accounts.contains(address)) ||
+            self.storage.iter().any(|key| writes.wiped.contains(&key.0)) ||
+            self.storage.iter().any(|key| writes.storage.contains(key))
+    }
+}
+
+/// A revm [Database] that records the accounts and storage slots that are read.
+struct ReadTracker<DB> {
+    db: DB,
+    reads: AccessSet,
+}
+
+impl<DB: Database> Database for ReadTracker<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: B160) -> Result<Option<AccountInfo>, Self::Error> {
+        self.reads.accounts.insert(address);
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: B160, index: revm::U256) -> Result<revm::U256, Self::Error> {
+        self.reads.storage.insert((address, index));
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: revm::U256) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+/// An inspector that detects if the executed code accesses the beneficiary of the block, other
+/// than by paying it the fees of the transaction.
+struct CoinbaseAccess<'a> {
+    coinbase: B160,
+    accessed: &'a mut bool,
+}
+
+impl<'a> CoinbaseAccess<'a> {
+    /// Returns `true` if the address at the given depth of the stack is the beneficiary.
+    fn is_coinbase_on_stack(&self, interp: &Interpreter, depth: usize) -> bool {
+        interp.stack.peek(depth).map_or(false, |value| {
+            let value: [u8; 32] = value.to_be_bytes();
+            value[12..] == self.coinbase.0[..]
+        })
+    }
+}
+
+impl<'a, DB: Database> Inspector<DB> for CoinbaseAccess<'a> {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        let accessed = interp.contract.address == self.coinbase ||
+            match interp.current_opcode() {
+                // BALANCE, EXTCODESIZE, EXTCODECOPY, EXTCODEHASH and SELFDESTRUCT
+                0x31 | 0x3b | 0x3c | 0x3f | 0xff => self.is_coinbase_on_stack(interp, 0),
+                // CALL, CALLCODE, DELEGATECALL and STATICCALL
+                0xf1 | 0xf2 | 0xf4 | 0xfa => self.is_coinbase_on_stack(interp, 1),
+                _ => false,
+            };
+        if accessed {
+            *self.accessed = true;
+            // the result is discarded
+            return Return::FatalExternalError
+        }
+        Return::Continue
+    }
+}
+
+/// A [StateProvider] that reads from a shared provider, so the provider can be used by the
+/// speculative executions on all threads.
+struct SharedProvider<'a, SP>(&'a SP);
+
+impl<'a, SP: StateProvider> AccountProvider for SharedProvider<'a, SP> {
+    fn basic_account(&self, address: Address) -> reth_interfaces::Result<Option<Account>> {
+        self.0.basic_account(address)
+    }
+}
+
+impl<'a, SP: StateProvider> StateProvider for SharedProvider<'a, SP> {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> reth_interfaces::Result<Option<StorageValue>> {
+        self.0.storage(account, storage_key)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> reth_interfaces::Result<Option<Bytes>> {
+        self.0.bytecode_by_hash(code_hash)
+    }
+
+    fn block_hash(&self, number: U256) -> reth_interfaces::Result<Option<H256>> {
+        self.0.block_hash(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::execute;
+    use reth_primitives::{ChainSpecBuilder, Signature, Transaction, TransactionSigned, TxLegacy};
+    use std::collections::HashMap as StdHashMap;
+
+    /// A state with funded accounts.
+    struct FundedState(StdHashMap<Address, Account>);
+
+    impl AccountProvider for FundedState {
+        fn basic_account(&self, address: Address) -> reth_interfaces::Result<Option<Account>> {
+            Ok(self.0.get(&address).copied())
+        }
+    }
+
+    impl StateProvider for FundedState {
+        fn storage(
+            &self,
+            _: Address,
+            _: StorageKey,
+        ) -> reth_interfaces::Result<Option<StorageValue>> {
+            Ok(None)
+        }
+
+        fn bytecode_by_hash(&self, _: H256) -> reth_interfaces::Result<Option<Bytes>> {
+            Ok(None)
+        }
+
+        fn block_hash(&self, _: U256) -> reth_interfaces::Result<Option<H256>> {
+            Ok(None)
+        }
+    }
+
+    fn transfer(signer: Address, nonce: u64, to: Address) -> TransactionSignedEcRecovered {
+        let transaction = Transaction::Legacy(TxLegacy {
+            nonce,
+            gas_price: 1,
+            gas_limit: 21_000,
+            to: TransactionKind::Call(to),
+            value: 1,
+            ..Default::default()
+        });
+        let signed =
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default());
+        TransactionSignedEcRecovered::from_signed_transaction(signed, signer)
+    }
+
+    #[test]
+    fn reexecute_conflicting_transactions() {
+        let (alice, bob) = (Address::random(), Address::random());
+        let funded = Account { balance: U256::from(u64::MAX), ..Default::default() };
+        let state = FundedState(StdHashMap::from([(alice, funded), (bob, funded)]));
+        let header = Header {
+            beneficiary: Address::random(),
+            gas_limit: 1_000_000,
+            gas_used: 3 * 21_000,
+            ..Default::default()
+        };
+        let chain_spec = ChainSpecBuilder::mainnet().berlin_activated().build();
+        // the third transaction reads the nonce of alice, written by the first one
+        let transactions = vec![
+            transfer(alice, 0, Address::random()),
+            transfer(bob, 0, Address::random()),
+            transfer(alice, 1, Address::random()),
+        ];
+
+        let executor = ParallelExecutor::new(2).unwrap();
+        let (result, reexecuted) =
+            executor.execute_block(&header, &transactions, &[], None, &chain_spec, &state).unwrap();
+        assert_eq!(reexecuted, 1);
+
+        let expected = execute(
+            &header,
+            &transactions,
+            &[],
+            None,
+            &chain_spec,
+            SubState::new(State::new(SharedProvider(&state))),
+        )
+        .unwrap();
+        assert_eq!(result.changesets.len(), expected.changesets.len());
+        for (changeset, expected) in result.changesets.iter().zip(&expected.changesets) {
+            assert_eq!(changeset.receipt, expected.receipt);
+            let accounts = |changeset: &crate::executor::TransactionChangeSet| {
+                changeset
+                    .changeset
+                    .iter()
+                    .map(|(address, change)| (*address, change.account.clone()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(accounts(changeset), accounts(expected));
+        }
+    }
+}
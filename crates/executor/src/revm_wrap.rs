@@ -1,12 +1,13 @@
+use crate::revm_spec;
 use reth_interfaces::Error;
 use reth_primitives::{
-    Account, Header, Transaction, TransactionKind, TransactionSignedEcRecovered, TxEip1559,
-    TxEip2930, TxLegacy, H160, H256, KECCAK_EMPTY, U256,
+    Account, ChainSpec, Header, Transaction, TransactionKind, TransactionSignedEcRecovered,
+    TxEip1559, TxEip2930, TxEip4844, TxLegacy, H160, H256, KECCAK_EMPTY, U256,
 };
 use reth_provider::StateProvider;
 use revm::{
     db::{CacheDB, DatabaseRef},
-    BlockEnv, TransactTo, TxEnv, B160, B256, U256 as evmU256,
+    AnalysisKind, BlockEnv, CfgEnv, TransactTo, TxEnv, B160, B256, U256 as evmU256,
 };
 
 /// SubState of database. Uses revm internal cache with binding to reth StateProvider trait.
@@ -66,6 +67,14 @@ impl<DB: StateProvider> DatabaseRef for State<DB> {
     }
 }
 
+/// Fill the cfg environment for the block from the [ChainSpec].
+pub fn fill_cfg_env(cfg_env: &mut CfgEnv, chain_spec: &ChainSpec, header: &Header) {
+    cfg_env.chain_id = evmU256::from(chain_spec.chain_id());
+    cfg_env.spec_id = revm_spec(chain_spec, header.number, header.timestamp);
+    cfg_env.perf_all_precompiles_have_balance = false;
+    cfg_env.perf_analyse_created_bytecodes = AnalysisKind::Raw;
+}
+
 /// Fill block environment from Block.
 pub fn fill_block_env(block_env: &mut BlockEnv, header: &Header, after_merge: bool) {
     block_env.number = evmU256::from(header.number);
@@ -152,6 +161,19 @@ pub fn fill_tx_env(tx_env: &mut TxEnv, transaction: &TransactionSignedEcRecovere
             value,
             input,
             access_list,
+        }) |
+        // the blob fee and versioned hashes are not part of the transaction environment
+        Transaction::Eip4844(TxEip4844 {
+            nonce,
+            chain_id,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to,
+            value,
+            input,
+            access_list,
+            ..
         }) => {
             tx_env.gas_limit = *gas_limit;
             tx_env.gas_price = evmU256::from(*max_fee_per_gas);
@@ -0,0 +1,203 @@
+use crate::executor::{AccountChangeSet, AccountInfoChangeSet, ExecutionResult};
+use reth_interfaces::Result;
+use reth_primitives::{
+    Account, Address, BigEndianHash, BlockHash, BlockNumber, Bytes, StorageKey, StorageValue, H256,
+    U256,
+};
+use reth_provider::{AccountProvider, StateProvider};
+use revm::Bytecode;
+use std::collections::{BTreeMap, HashMap};
+
+/// The state changes of blocks that are not committed to the database.
+///
+/// Only the latest value of every account and storage slot is kept, so the changes of a block can
+/// be layered on top of the changes of its ancestors (see [ChainState::extend]).
+#[derive(Debug, Clone, Default)]
+pub struct ChainState {
+    /// The changed accounts, `None` if the account was destroyed.
+    accounts: HashMap<Address, Option<Account>>,
+    /// The changed storage of the accounts.
+    storage: HashMap<Address, ChainStorage>,
+    /// The bytecodes that were deployed.
+    bytecodes: HashMap<H256, Bytes>,
+    /// The hashes of the blocks.
+    block_hashes: BTreeMap<BlockNumber, BlockHash>,
+}
+
+/// The changed storage of an account.
+#[derive(Debug, Clone, Default)]
+struct ChainStorage {
+    /// Whether the storage before the changes was wiped, e.g. by a selfdestruct.
+    wiped: bool,
+    /// The changed slots.
+    slots: HashMap<StorageKey, StorageValue>,
+}
+
+impl ChainState {
+    /// Collects the state changes of the execution of a block.
+    pub fn new(number: BlockNumber, hash: BlockHash, result: ExecutionResult) -> Self {
+        let mut state = Self::default();
+        state.block_hashes.insert(number, hash);
+
+        for changeset in result.changesets {
+            state.apply_changeset(changeset.changeset, changeset.new_bytecodes);
+        }
+
+        for (address, account) in result.block_reward.into_iter().flatten() {
+            state.apply_account(address, account);
+        }
+        for (address, slots) in result.block_storage {
+            let storage = state.storage.entry(address).or_default();
+            storage.slots.extend(
+                slots.into_iter().map(|(key, (_, new_value))| (H256::from_uint(&key), new_value)),
+            );
+        }
+        state
+    }
+
+    /// Records the changes of a transaction on top of these changes.
+    pub fn apply_changeset(
+        &mut self,
+        changeset: BTreeMap<Address, AccountChangeSet>,
+        new_bytecodes: BTreeMap<H256, Bytecode>,
+    ) {
+        for (address, account_changeset) in changeset {
+            self.apply_account(address, account_changeset.account);
+
+            let storage = self.storage.entry(address).or_default();
+            if account_changeset.wipe_storage {
+                storage.wiped = true;
+                storage.slots.clear();
+            }
+            for (key, (_, new_value)) in account_changeset.storage {
+                let mut hkey = H256::zero();
+                key.to_big_endian(&mut hkey.0);
+                storage.slots.insert(hkey, new_value);
+            }
+        }
+        for (hash, bytecode) in new_bytecodes {
+            self.bytecodes.insert(hash, Bytes::from(bytecode.bytes().to_vec()));
+        }
+    }
+
+    /// The changed accounts, `None` if the account was destroyed.
+    pub fn accounts(&self) -> impl Iterator<Item = (Address, Option<Account>)> + '_ {
+        self.accounts.iter().map(|(address, account)| (*address, *account))
+    }
+
+    /// The changed storage of the accounts, with whether the storage before the changes was wiped
+    /// and the new values of the changed slots.
+    pub fn storage(
+        &self,
+    ) -> impl Iterator<Item = (Address, bool, &HashMap<StorageKey, StorageValue>)> + '_ {
+        self.storage.iter().map(|(address, storage)| (*address, storage.wiped, &storage.slots))
+    }
+
+    /// Layers the changes of a descendant on top of these changes.
+    pub fn extend(&mut self, other: &ChainState) {
+        self.accounts.extend(other.accounts.iter().map(|(address, account)| (*address, *account)));
+        for (address, other_storage) in &other.storage {
+            let storage = self.storage.entry(*address).or_default();
+            if other_storage.wiped {
+                *storage = other_storage.clone();
+            } else {
+                storage.slots.extend(other_storage.slots.iter().map(|(key, value)| (*key, *value)));
+            }
+        }
+        self.bytecodes.extend(other.bytecodes.iter().map(|(hash, code)| (*hash, code.clone())));
+        self.block_hashes.extend(other.block_hashes.iter().map(|(number, hash)| (*number, *hash)));
+    }
+
+    /// Records the new value of an account.
+    fn apply_account(&mut self, address: Address, account: AccountInfoChangeSet) {
+        match account {
+            AccountInfoChangeSet::Created { new } | AccountInfoChangeSet::Changed { new, .. } => {
+                self.accounts.insert(address, Some(new));
+            }
+            AccountInfoChangeSet::Destroyed { .. } => {
+                self.accounts.insert(address, None);
+            }
+            AccountInfoChangeSet::NoChange => {}
+        }
+    }
+}
+
+/// State provider that layers a [ChainState] on top of the state of the block it builds on, e.g.
+/// the state at the fork point of a side chain.
+pub struct ChainStateProvider<'a, SP: StateProvider> {
+    /// The changes on top of the parent state.
+    state: &'a ChainState,
+    /// The state of the block the changes build on.
+    parent: SP,
+}
+
+impl<'a, SP: StateProvider> ChainStateProvider<'a, SP> {
+    /// Create a new provider on top of the state of the block the changes build on.
+    pub fn new(state: &'a ChainState, parent: SP) -> Self {
+        Self { state, parent }
+    }
+}
+
+impl<'a, SP: StateProvider> AccountProvider for ChainStateProvider<'a, SP> {
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        match self.state.accounts.get(&address) {
+            Some(account) => Ok(*account),
+            None => self.parent.basic_account(address),
+        }
+    }
+}
+
+impl<'a, SP: StateProvider> StateProvider for ChainStateProvider<'a, SP> {
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        if let Some(storage) = self.state.storage.get(&account) {
+            if let Some(value) = storage.slots.get(&storage_key) {
+                return Ok(Some(*value))
+            }
+            if storage.wiped {
+                return Ok(None)
+            }
+        }
+        self.parent.storage(account, storage_key)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        match self.state.bytecodes.get(&code_hash) {
+            Some(code) => Ok(Some(code.clone())),
+            None => self.parent.bytecode_by_hash(code_hash),
+        }
+    }
+
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        let hash =
+            u64::try_from(number).ok().and_then(|number| self.state.block_hashes.get(&number));
+        match hash {
+            Some(hash) => Ok(Some(*hash)),
+            None => self.parent.block_hash(number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extend_wiped_storage() {
+        let address = Address::random();
+        let (slot, other_slot) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+
+        let mut state = ChainState::default();
+        state.storage.entry(address).or_default().slots.insert(slot, U256::from(1));
+
+        let mut wiped = ChainState::default();
+        let storage = wiped.storage.entry(address).or_default();
+        storage.wiped = true;
+        storage.slots.insert(other_slot, U256::from(2));
+        state.extend(&wiped);
+
+        let storage = &state.storage[&address];
+        assert!(storage.wiped);
+        assert_eq!(storage.slots.get(&slot), None);
+        assert_eq!(storage.slots.get(&other_slot), Some(&U256::from(2)));
+    }
+}
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use reth_primitives::{BlockHash, BlockNumber, SealedBlock, SealedHeader, H256};
+use reth_primitives::{Address, BlockHash, BlockNumber, SealedBlock, SealedHeader, H256, U256};
 use tokio::sync::watch::Receiver;
 
 /// Re-export forkchoice state
@@ -17,6 +17,22 @@ pub trait Consensus: Send + Sync {
     /// **This should not be called for the genesis block**.
     fn validate_header(&self, header: &SealedHeader, parent: &SealedHeader) -> Result<(), Error>;
 
+    /// Validate the rules of the header that depend on all of its ancestors, e.g. the authorized
+    /// signers of clique.
+    ///
+    /// The headers are downloaded in reverse, so the ancestors are not known while they are
+    /// [validated](Consensus::validate_header). This is called in ascending order once the
+    /// ancestors of the header are stored or were validated with this method before.
+    ///
+    /// **This should not be called for the genesis block**.
+    fn validate_header_with_ancestors(
+        &self,
+        _header: &SealedHeader,
+        _parent: &SealedHeader,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Validate a block disregarding world state, i.e. things that can be checked before sender
     /// recovery and execution.
     ///
@@ -46,6 +62,12 @@ pub enum Error {
     BodyTransactionRootDiff { got: H256, expected: H256 },
     #[error("Block receipts root ({got:?}) is different then expected: ({expected:?}).")]
     BodyReceiptsRootDiff { got: H256, expected: H256 },
+    #[error("Block withdrawals root ({got:?}) is different then expected: ({expected:?}).")]
+    BodyWithdrawalsRootDiff { got: H256, expected: H256 },
+    #[error("Block withdrawals missing.")]
+    BodyWithdrawalsMissing,
+    #[error("Block withdrawals are not expected before shanghai hardfork.")]
+    BodyWithdrawalsUnexpected,
     #[error("Block with [hash:{hash:?},number: {number:}] is already known.")]
     BlockKnown { hash: BlockHash, number: BlockNumber },
     #[error("Block parent [hash:{hash:?}] is not known.")]
@@ -66,6 +88,30 @@ pub enum Error {
     BaseFeeMissing,
     #[error("Block base fee ({got:?}) is different then expected: ({expected:?}).")]
     BaseFeeDiff { expected: u64, got: u64 },
+    #[error("Withdrawals root missing.")]
+    WithdrawalsRootMissing,
+    #[error("Withdrawals root is not expected before shanghai hardfork.")]
+    WithdrawalsRootUnexpected,
+    #[error("Blob gas used missing.")]
+    BlobGasUsedMissing,
+    #[error("Blob gas used is not expected before cancun hardfork.")]
+    BlobGasUsedUnexpected,
+    #[error("Excess blob gas missing.")]
+    ExcessBlobGasMissing,
+    #[error("Excess blob gas is not expected before cancun hardfork.")]
+    ExcessBlobGasUnexpected,
+    #[error("Parent beacon block root missing.")]
+    ParentBeaconBlockRootMissing,
+    #[error("Parent beacon block root is not expected before cancun hardfork.")]
+    ParentBeaconBlockRootUnexpected,
+    #[error("Blob gas used {blob_gas_used} exceeds the max blob gas per block {max_blob_gas_per_block}.")]
+    BlobGasUsedExceedsMaxBlobGasPerBlock { blob_gas_used: u64, max_blob_gas_per_block: u64 },
+    #[error("Blob gas used {blob_gas_used} is not a multiple of the blob gas per blob {blob_gas_per_blob}.")]
+    BlobGasUsedNotMultipleOfBlobGasPerBlob { blob_gas_used: u64, blob_gas_per_blob: u64 },
+    #[error("Block blob gas used ({got:?}) is different then expected: ({expected:?}).")]
+    BlobGasUsedDiff { got: u64, expected: u64 },
+    #[error("Block excess blob gas ({got:?}) is different then expected: ({expected:?}).")]
+    ExcessBlobGasDiff { got: u64, expected: u64 },
     #[error("Transaction eip1559 priority fee is more then max fee.")]
     TransactionPriorityFeeMoreThenMaxFee,
     #[error("Transaction chain_id does not match.")]
@@ -86,6 +132,8 @@ pub enum Error {
     TransactionOldLegacyChainId,
     #[error("Eip2930 transaction is enabled after london hardfork.")]
     TransactionEip1559Disabled,
+    #[error("Eip4844 transaction is enabled after cancun hardfork.")]
+    TransactionEip4844Disabled,
     #[error("Transaction signer recovery error.")]
     TransactionSignerRecoveryError,
     #[error(
@@ -96,12 +144,42 @@ pub enum Error {
     TransactionTypeReceiptTypeDiff,
     #[error("Extra data {len} exceeds max length: ")]
     ExtraDataExceedsMax { len: usize },
+    #[error("Block difficulty ({got:?}) is different than expected: ({expected:?}).")]
+    DifficultyDiff { got: U256, expected: U256 },
     #[error("Difficulty after merge is not zero")]
     TheMergeDifficultyIsNotZero,
     #[error("Nonce after merge is not zero")]
     TheMergeNonceIsNotZero,
     #[error("Ommer root after merge is not empty")]
     TheMergeOmmerRootIsNotEmpty,
-    #[error("Mix hash after merge is not zero")]
-    TheMergeMixHashIsNotZero,
+    #[error("Ethash mix hash ({got:?}) is different then expected: ({expected:?}).")]
+    EthashMixHashDiff { got: H256, expected: H256 },
+    #[error("Ethash proof of work does not satisfy the difficulty {difficulty:?}.")]
+    EthashInsufficientWork { difficulty: U256 },
+    #[error("Clique extra data length {len} is invalid.")]
+    CliqueExtraDataInvalid { len: usize },
+    #[error("Clique checkpoint block has a non-zero beneficiary.")]
+    CliqueCheckpointBeneficiary,
+    #[error("Clique vote nonce {nonce:#x} is invalid.")]
+    CliqueInvalidVote { nonce: u64 },
+    #[error("Clique mix hash is not zero.")]
+    CliqueMixHashIsNotZero,
+    #[error("Clique ommer root is not empty.")]
+    CliqueOmmerRootIsNotEmpty,
+    #[error("Clique difficulty ({got:?}) is different then expected: ({expected:?}).")]
+    CliqueDifficultyDiff { got: U256, expected: U256 },
+    #[error("Clique block timestamp {timestamp} is less than {period}s after the parent timestamp {parent_timestamp}.")]
+    CliqueTimestampTooEarly { parent_timestamp: u64, timestamp: u64, period: u64 },
+    #[error("Clique checkpoint signers are different then the authorized signers.")]
+    CliqueCheckpointSignersDiff,
+    #[error("Clique signer recovery error.")]
+    CliqueSignerRecoveryError,
+    #[error("Clique signer {signer:?} is not authorized.")]
+    CliqueUnauthorizedSigner { signer: Address },
+    #[error("Clique signer {signer:?} signed one of the recent blocks.")]
+    CliqueRecentlySigned { signer: Address },
+    #[error("Clique difficulty {difficulty:?} is neither in turn nor out of turn.")]
+    CliqueInvalidDifficulty { difficulty: U256 },
+    #[error("Clique snapshot of block {hash:?} can't be read: {error}")]
+    CliqueSnapshotUnavailable { hash: BlockHash, error: String },
 }
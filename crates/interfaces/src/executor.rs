@@ -27,19 +27,41 @@ pub enum Error {
     ReceiptLogDiff,
     #[error("Receipt log is different.")]
     ExecutionSuccessDiff { got: bool, expected: bool },
-    #[error("Receipt root {got:?} is different than expected {expected:?}.")]
-    ReceiptRootDiff { got: H256, expected: H256 },
-    #[error("Header bloom filter {got:?} is different than expected {expected:?}.")]
-    BloomLogDiff { got: Box<Bloom>, expected: Box<Bloom> },
     #[error("Transaction gas limit {transaction_gas_limit} is more than blocks available gas {block_available_gas}")]
     TransactionGasLimitMoreThenAvailableBlockGas {
         transaction_gas_limit: u64,
         block_available_gas: u64,
     },
-    #[error("Block gas used {got} is different from expected gas used {expected}.")]
-    BlockGasUsed { got: u64, expected: u64 },
+    #[error(transparent)]
+    BlockValidation(#[from] BlockValidationError),
+    #[error("Blob gas price {blob_gasprice} is more than the max fee per blob gas {max_fee_per_blob_gas} of the transaction.")]
+    BlobGasPriceMoreThanMaxFee { max_fee_per_blob_gas: u128, blob_gasprice: u128 },
+    #[error("Transaction sender can't pay for the execution and the blob gas at their max fees.")]
+    BlobTransactionInsufficientFunds,
+    #[error("Block {block_number} can't be executed, the EVM has no Cancun spec.")]
+    CancunNotSupported { block_number: u64 },
     #[error("Revm error {error_code}")]
     EVMError { error_code: u32 },
     #[error("Provider error")]
     ProviderError,
 }
+
+/// The mismatches between the header of a block and the result of its execution.
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BlockValidationError {
+    #[error("Cumulative gas used {cumulative_gas_used} after transaction {transaction_index} exceeds the block gas used {block_gas_used}.")]
+    TransactionGasUsedExceedsBlockGasUsed {
+        transaction_index: usize,
+        cumulative_gas_used: u64,
+        block_gas_used: u64,
+    },
+    #[error("Block gas used {got} is different from expected gas used {expected}.")]
+    BlockGasUsed { got: u64, expected: u64 },
+    #[error("Logs of transaction {transaction_index} are not part of the header bloom filter.")]
+    TransactionLogsNotInBloom { transaction_index: usize },
+    #[error("Header bloom filter {got:?} is different than expected {expected:?}.")]
+    BloomLogDiff { got: Box<Bloom>, expected: Box<Bloom> },
+    #[error("Receipt root {got:?} is different than expected {expected:?}.")]
+    ReceiptRootDiff { got: H256, expected: H256 },
+}
@@ -91,6 +91,14 @@ pub enum DownloadError {
     /// Received empty response while expecting headers
     #[error("Received empty header response.")]
     EmptyResponse,
+    /// Received headers that don't start at the requested block
+    #[error("Received headers starting at block {received}. Expected block {expected}.")]
+    InvalidHeadersStart {
+        /// The number of the first received header
+        received: u64,
+        /// The number of the requested block
+        expected: u64,
+    },
     /// Received an invalid tip
     #[error("Received invalid tip: {received:?}. Expected {expected:?}.")]
     InvalidTip {
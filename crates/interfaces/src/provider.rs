@@ -1,4 +1,4 @@
-use reth_primitives::{BlockHash, BlockNumber};
+use reth_primitives::{BlockHash, BlockNumber, TransitionId, TxNumber};
 
 /// KV error type. They are using u32 to represent error code.
 #[allow(missing_docs)]
@@ -12,4 +12,18 @@ pub enum Error {
     BlockBody { block_number: BlockNumber, block_hash: BlockHash },
     #[error("Block transition does not exist for block #{block_number} ({block_hash:?})")]
     BlockTransition { block_number: BlockNumber, block_hash: BlockHash },
+    #[error("Changeset of transition {transition} does not exist in database")]
+    Changeset { transition: TransitionId },
+    #[error("Transaction #{id} does not exist in database")]
+    Transaction { id: TxNumber },
+    #[error("Sender of transaction #{id} could not be recovered")]
+    TransactionSender { id: TxNumber },
+    #[error("Senders of block #{block_number} ({block_hash:?}) could not be recovered")]
+    BlockSenders { block_number: BlockNumber, block_hash: BlockHash },
+    #[error("Block #{block_number} has {transactions} transactions but {senders} senders")]
+    BlockSendersMismatch { block_number: BlockNumber, transactions: usize, senders: usize },
+    #[error("Receipt of transaction #{id} does not exist in database")]
+    Receipt { id: TxNumber },
+    #[error("Static file error: {0}")]
+    StaticFile(String),
 }
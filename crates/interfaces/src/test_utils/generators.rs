@@ -132,6 +132,7 @@ pub fn random_block(number: u64, parent: Option<H256>, tx_count: Option<u8>) ->
         .seal(),
         body: transactions,
         ommers: ommers.into_iter().map(|ommer| ommer.seal()).collect(),
+        withdrawals: None,
     }
 }
 
@@ -13,7 +13,7 @@ use crate::{
 use futures::{Future, FutureExt, Stream};
 use reth_eth_wire::BlockHeaders;
 use reth_primitives::{
-    BlockNumber, Header, HeadersDirection, PeerId, SealedBlock, SealedHeader, H256,
+    BlockNumber, Head, Header, HeadersDirection, PeerId, SealedBlock, SealedHeader, H256,
 };
 use reth_rpc_types::engine::ForkchoiceState;
 use std::{
@@ -239,7 +239,7 @@ impl TestConsensus {
 pub struct TestStatusUpdater;
 
 impl StatusUpdater for TestStatusUpdater {
-    fn update_status(&self, _height: u64, _hash: H256, _total_difficulty: reth_primitives::U256) {}
+    fn update_status(&self, _head: Head) {}
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,601 @@
+use futures_util::stream::{FuturesUnordered, Stream, StreamExt};
+use reth_eth_wire::BlockBody;
+use reth_interfaces::{
+    consensus::Consensus as ConsensusTrait,
+    p2p::{
+        bodies::{
+            client::BodiesClient,
+            downloader::{BlockResponse, BodyDownloader},
+        },
+        downloader::{DownloadStream, Downloader},
+        error::{DownloadError, DownloadResult, PeerRequestResult, RequestError},
+    },
+};
+use reth_primitives::{PeerId, SealedBlock, SealedHeader, H256};
+use reth_rlp::Encodable;
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tracing::trace;
+
+/// Downloads bodies in batches from many peers at once, keeping the downloaded bodies in a
+/// bounded buffer.
+///
+/// Batches are requested in order, but their responses may arrive in any order. Responses are held
+/// back until all batches before them are complete, so the bodies are always emitted in the order
+/// of the headers. Once the buffered bodies exceed `max_buffered_bytes`, no new batches are
+/// requested until the buffer is drained, which keeps memory in check when blocks are huge.
+///
+/// Peers that respond with fewer bodies than requested keep the bodies they sent, only the missing
+/// bodies are requested again.
+// ANCHOR: struct-BufferedDownloader
+#[derive(Debug)]
+pub struct BufferedDownloader<Client, Consensus> {
+    /// The bodies client
+    client: Arc<Client>,
+    /// The consensus client
+    consensus: Arc<Consensus>,
+    /// The number of retries for each request.
+    retries: usize,
+    /// The batch size per one request
+    batch_size: usize,
+    /// The maximum number of requests to send concurrently.
+    concurrency: usize,
+    /// The approximate number of bytes of bodies to buffer before pausing new requests.
+    max_buffered_bytes: usize,
+}
+// ANCHOR_END: struct-BufferedDownloader
+
+impl<Client, Consensus> Downloader for BufferedDownloader<Client, Consensus>
+where
+    Client: BodiesClient,
+    Consensus: ConsensusTrait,
+{
+    type Client = Client;
+    type Consensus = Consensus;
+
+    fn client(&self) -> &Self::Client {
+        self.client.borrow()
+    }
+
+    fn consensus(&self) -> &Self::Consensus {
+        self.consensus.borrow()
+    }
+}
+
+impl<Client, Consensus> BodyDownloader for BufferedDownloader<Client, Consensus>
+where
+    Client: BodiesClient + 'static,
+    Consensus: ConsensusTrait,
+{
+    fn bodies_stream<'a, 'b, I>(&'a self, headers: I) -> DownloadStream<'a, BlockResponse>
+    where
+        I: IntoIterator<Item = &'b SealedHeader>,
+        <I as IntoIterator>::IntoIter: Send + 'b,
+        'b: 'a,
+    {
+        Box::pin(self.new_download(headers.into_iter().cloned()))
+    }
+}
+
+impl<Client, Consensus> BufferedDownloader<Client, Consensus>
+where
+    Client: BodiesClient,
+    Consensus: ConsensusTrait,
+{
+    /// Create a new buffered downloader instance.
+    pub fn new(client: Arc<Client>, consensus: Arc<Consensus>) -> Self {
+        Self {
+            client,
+            consensus,
+            retries: 3,
+            batch_size: 100,
+            concurrency: 5,
+            max_buffered_bytes: 256 * 1024 * 1024,
+        }
+    }
+
+    /// Set the number of blocks to request from a peer at a time.
+    ///
+    /// Defaults to 100.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the maximum number of requests to send concurrently.
+    ///
+    /// Defaults to 5.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Set the number of times to retry body fetch requests.
+    ///
+    /// Defaults to 3.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set the approximate number of bytes of downloaded bodies to buffer before pausing new
+    /// requests.
+    ///
+    /// The bodies of the requests in flight are not accounted for, so the buffer may exceed this
+    /// by up to `concurrency` responses.
+    ///
+    /// Defaults to 256 MiB.
+    pub fn with_max_buffered_bytes(mut self, max_buffered_bytes: usize) -> Self {
+        self.max_buffered_bytes = max_buffered_bytes;
+        self
+    }
+
+    fn new_download<'a>(
+        &'a self,
+        headers: impl Iterator<Item = SealedHeader> + Send + 'a,
+    ) -> BufferedBodiesDownload<'a, Client, Consensus> {
+        BufferedBodiesDownload {
+            downloader: self,
+            headers: Box::new(headers),
+            headers_exhausted: false,
+            next_batch: 0,
+            next_emitted: 0,
+            batches: BTreeMap::new(),
+            buffered_bytes: 0,
+            retries: VecDeque::new(),
+            in_flight: FuturesUnordered::new(),
+            queued: VecDeque::new(),
+            encountered_error: false,
+        }
+    }
+}
+
+/// The approximate number of bytes the response occupies in memory.
+fn response_size(response: &BlockResponse) -> usize {
+    match response {
+        BlockResponse::Full(block) => {
+            std::mem::size_of::<SealedBlock>() +
+                block.body.iter().map(|transaction| transaction.length()).sum::<usize>() +
+                block.ommers.len() * std::mem::size_of::<SealedHeader>()
+        }
+        BlockResponse::Empty(_) => std::mem::size_of::<SealedHeader>(),
+    }
+}
+
+type BodiesFut = Pin<Box<dyn Future<Output = (usize, PeerRequestResult<Vec<BlockBody>>)> + Send>>;
+
+/// A batch of headers and the responses received for them so far.
+struct Batch {
+    headers: Vec<SealedHeader>,
+    /// The responses for the first headers of the batch.
+    responses: Vec<BlockResponse>,
+    /// The approximate size of the responses.
+    size: usize,
+    /// The number of failed requests for the batch.
+    failures: usize,
+}
+
+impl Batch {
+    /// Add the responses of the empty blocks up to the next block with a body.
+    fn push_empty(&mut self) {
+        while let Some(header) = self.headers.get(self.responses.len()) {
+            if !header.is_empty() {
+                break
+            }
+            self.push(BlockResponse::Empty(header.clone()));
+        }
+    }
+
+    fn push(&mut self, response: BlockResponse) {
+        self.size += response_size(&response);
+        self.responses.push(response);
+    }
+
+    fn is_complete(&self) -> bool {
+        self.responses.len() == self.headers.len()
+    }
+
+    /// The hashes of the blocks with bodies that are still missing.
+    fn missing_bodies(&self) -> Vec<H256> {
+        self.headers[self.responses.len()..]
+            .iter()
+            .filter(|header| !header.is_empty())
+            .map(|header| header.hash())
+            .collect()
+    }
+}
+
+/// An in progress buffered bodies download.
+struct BufferedBodiesDownload<'a, Client, Consensus> {
+    downloader: &'a BufferedDownloader<Client, Consensus>,
+    /// The headers that were not requested yet.
+    headers: Box<dyn Iterator<Item = SealedHeader> + Send + 'a>,
+    /// Whether all headers were requested.
+    headers_exhausted: bool,
+    /// The index of the next batch to create.
+    next_batch: usize,
+    /// The index of the next batch to emit.
+    next_emitted: usize,
+    /// The batches that were not emitted yet, by their index.
+    batches: BTreeMap<usize, Batch>,
+    /// The approximate size of the responses of the batches.
+    buffered_bytes: usize,
+    /// The batches that need to be requested again.
+    retries: VecDeque<usize>,
+    /// The requests in flight, by the index of their batch.
+    in_flight: FuturesUnordered<BodiesFut>,
+    /// The responses that are ready to be emitted.
+    queued: VecDeque<BlockResponse>,
+    /// Flag whether the stream encountered an error
+    encountered_error: bool,
+}
+
+impl<'a, Client, Consensus> BufferedBodiesDownload<'a, Client, Consensus>
+where
+    Client: BodiesClient + 'static,
+    Consensus: ConsensusTrait,
+{
+    // ANCHOR: fn-send_requests
+    /// Send requests until the maximum number of requests is in flight.
+    ///
+    /// Failed batches are requested first. New batches are only created while the buffer is below
+    /// its limit.
+    fn send_requests(&mut self) {
+        while self.in_flight.len() < self.downloader.concurrency {
+            if let Some(index) = self.retries.pop_front() {
+                self.send_request(index);
+                continue
+            }
+
+            if self.headers_exhausted || self.buffered_bytes >= self.downloader.max_buffered_bytes {
+                break
+            }
+            let headers =
+                self.headers.by_ref().take(self.downloader.batch_size).collect::<Vec<_>>();
+            if headers.len() < self.downloader.batch_size {
+                self.headers_exhausted = true;
+            }
+            if headers.is_empty() {
+                break
+            }
+
+            let index = self.next_batch;
+            self.next_batch += 1;
+            let mut batch = Batch { headers, responses: Vec::new(), size: 0, failures: 0 };
+            batch.push_empty();
+            let complete = batch.is_complete();
+            self.buffered_bytes += batch.size;
+            self.batches.insert(index, batch);
+
+            if complete {
+                // Let the batch be emitted before creating more batches without requests
+                break
+            }
+            self.send_request(index);
+        }
+    }
+
+    fn send_request(&mut self, index: usize) {
+        let hashes = self.batches[&index].missing_bodies();
+        trace!(target: "downloaders::bodies", batch = index, len = hashes.len(), "Requesting bodies");
+        let client = Arc::clone(&self.downloader.client);
+        self.in_flight
+            .push(Box::pin(async move { (index, client.get_block_bodies(hashes).await) }));
+    }
+    // ANCHOR_END: fn-send_requests
+
+    /// Penalize the peer, if any, and queue the batch to be requested again.
+    ///
+    /// Returns the error if the batch ran out of retries.
+    #[allow(clippy::result_large_err)]
+    fn retry(
+        &mut self,
+        index: usize,
+        peer_id: Option<PeerId>,
+        err: DownloadError,
+    ) -> DownloadResult<()> {
+        if let Some(peer_id) = peer_id {
+            trace!(target: "downloaders::bodies", ?peer_id, ?err, "Penalizing peer");
+            self.downloader.client.report_bad_message(peer_id);
+        }
+        let batch = self.batches.get_mut(&index).expect("batch of request exists");
+        batch.failures += 1;
+        if batch.failures > self.downloader.retries {
+            return Err(err)
+        }
+        self.retries.push_back(index);
+        Ok(())
+    }
+
+    /// Validate the bodies and add them to their batch.
+    #[allow(clippy::result_large_err)]
+    fn on_response(
+        &mut self,
+        index: usize,
+        result: PeerRequestResult<Vec<BlockBody>>,
+    ) -> DownloadResult<()> {
+        let (peer_id, bodies) = match result {
+            Ok(response) => response.split(),
+            Err(err) => return self.retry(index, None, err.into()),
+        };
+        if bodies.is_empty() {
+            return self.retry(index, Some(peer_id), DownloadError::EmptyResponse)
+        }
+
+        let batch = self.batches.get_mut(&index).expect("batch of request exists");
+        let (len_before, size_before) = (batch.responses.len(), batch.size);
+        let mut result = Ok(());
+        for body in bodies {
+            // The peer sent bodies that were not requested
+            let header = match batch.headers.get(batch.responses.len()) {
+                Some(header) => header.clone(),
+                None => {
+                    result = Err(DownloadError::RequestError(RequestError::BadResponse));
+                    break
+                }
+            };
+
+            let block = SealedBlock {
+                header,
+                body: body.transactions,
+                ommers: body.ommers.into_iter().map(|header| header.seal()).collect(),
+                withdrawals: body.withdrawals,
+            };
+            // This ensures that the TxRoot and OmmersRoot from the header match the
+            // ones calculated manually from the block body.
+            if let Err(error) = self.downloader.consensus.pre_validate_block(&block) {
+                result = Err(DownloadError::BlockValidation { hash: block.header.hash(), error });
+                break
+            }
+            batch.push(BlockResponse::Full(block));
+            batch.push_empty();
+        }
+
+        if let Err(err) = result {
+            // Discard the whole response of a misbehaving peer
+            batch.responses.truncate(len_before);
+            batch.size = size_before;
+            return self.retry(index, Some(peer_id), err)
+        }
+
+        self.buffered_bytes += batch.size - size_before;
+        if !batch.is_complete() {
+            // Request the bodies that the peer didn't send
+            self.retries.push_back(index);
+        }
+        Ok(())
+    }
+
+    /// Queue the complete batches that are next in order.
+    fn queue_complete_batches(&mut self) {
+        while self.batches.get(&self.next_emitted).map_or(false, Batch::is_complete) {
+            let batch = self.batches.remove(&self.next_emitted).expect("batch exists");
+            self.buffered_bytes -= batch.size;
+            self.queued.extend(batch.responses);
+            self.next_emitted += 1;
+        }
+    }
+}
+
+impl<'a, Client, Consensus> Stream for BufferedBodiesDownload<'a, Client, Consensus>
+where
+    Client: BodiesClient + 'static,
+    Consensus: ConsensusTrait,
+{
+    type Item = DownloadResult<BlockResponse>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(response) = this.queued.pop_front() {
+                return Poll::Ready(Some(Ok(response)))
+            }
+
+            if this.encountered_error {
+                return Poll::Ready(None)
+            }
+
+            this.send_requests();
+            this.queue_complete_batches();
+            if !this.queued.is_empty() {
+                continue
+            }
+
+            let (index, result) = match ready!(this.in_flight.poll_next_unpin(cx)) {
+                Some(response) => response,
+                // All batches were emitted
+                None => return Poll::Ready(None),
+            };
+            if let Err(err) = this.on_response(index, result) {
+                this.encountered_error = true;
+                this.batches.clear();
+                return Poll::Ready(Some(Err(err)))
+            }
+            this.queue_complete_batches();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{generate_bodies, TestBodiesClient};
+    use assert_matches::assert_matches;
+    use futures_util::stream::TryStreamExt;
+    use reth_interfaces::test_utils::TestConsensus;
+    use reth_primitives::Header;
+    use std::{
+        collections::HashMap,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    /// The responses expected for the headers.
+    fn expected_responses(
+        headers: &[SealedHeader],
+        bodies: &HashMap<H256, BlockBody>,
+    ) -> Vec<BlockResponse> {
+        headers
+            .iter()
+            .map(|header| {
+                let body = bodies[&header.hash()].clone();
+                if header.is_empty() {
+                    BlockResponse::Empty(header.clone())
+                } else {
+                    BlockResponse::Full(SealedBlock {
+                        header: header.clone(),
+                        body: body.transactions,
+                        ommers: body.ommers.into_iter().map(|o| o.seal()).collect(),
+                        withdrawals: body.withdrawals,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    // Check that the blocks are emitted in order of block number, not in order of
+    // first-downloaded
+    #[tokio::test]
+    async fn emits_bodies_in_order() {
+        let (headers, bodies) = generate_bodies(0..20);
+        let client_bodies = bodies.clone();
+        let downloader = BufferedDownloader::new(
+            Arc::new(TestBodiesClient::new(move |hashes: Vec<H256>| {
+                let bodies = client_bodies.clone();
+                async move {
+                    // Simulate that the request for this (random) block takes 0-100ms
+                    tokio::time::sleep(Duration::from_millis(hashes[0].to_low_u64_be() % 100))
+                        .await;
+                    Ok((
+                        PeerId::default(),
+                        hashes.iter().map(|hash| bodies[hash].clone()).collect(),
+                    )
+                        .into())
+                }
+            })),
+            Arc::new(TestConsensus::default()),
+        )
+        .with_batch_size(3)
+        .with_concurrency(4);
+
+        assert_matches!(
+            downloader.bodies_stream(headers.iter()).try_collect::<Vec<_>>().await,
+            Ok(responses) => assert_eq!(responses, expected_responses(&headers, &bodies))
+        );
+    }
+
+    /// Checks that only the missing bodies are requested again after a partial response
+    #[tokio::test]
+    async fn requests_missing_bodies() {
+        let (headers, bodies) = generate_bodies(0..20);
+        let client_bodies = bodies.clone();
+        let downloader = BufferedDownloader::new(
+            Arc::new(TestBodiesClient::new(move |hashes: Vec<H256>| {
+                // Only send the first body of every request
+                let body = client_bodies[&hashes[0]].clone();
+                async move { Ok((PeerId::default(), vec![body]).into()) }
+            })),
+            Arc::new(TestConsensus::default()),
+        )
+        .with_batch_size(5)
+        .with_retries(0);
+
+        assert_matches!(
+            downloader.bodies_stream(headers.iter()).try_collect::<Vec<_>>().await,
+            Ok(responses) => assert_eq!(responses, expected_responses(&headers, &bodies))
+        );
+    }
+
+    /// Checks that no new batches are requested while the buffer is full
+    #[tokio::test]
+    async fn pauses_when_buffer_is_full() {
+        let downloader = BufferedDownloader::new(
+            Arc::new(TestBodiesClient::new(|_: Vec<H256>| async {
+                Ok((
+                    PeerId::default(),
+                    vec![BlockBody { transactions: vec![], ommers: vec![], withdrawals: None }],
+                )
+                    .into())
+            })),
+            Arc::new(TestConsensus::default()),
+        )
+        .with_batch_size(1)
+        .with_concurrency(2)
+        .with_max_buffered_bytes(1);
+
+        let headers = (0..3)
+            .map(|number| Header { number, ommers_hash: H256::default(), ..Default::default() })
+            .map(Header::seal)
+            .collect::<Vec<_>>();
+        let mut download = downloader.new_download(headers.clone().into_iter());
+
+        // A response for a later batch fills the buffer
+        download.buffered_bytes = 1;
+        download.send_requests();
+        assert!(download.in_flight.is_empty());
+
+        download.buffered_bytes = 0;
+        download.send_requests();
+        assert_eq!(download.in_flight.len(), 2);
+
+        let responses = download.try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(
+            responses.iter().map(BlockResponse::header).collect::<Vec<_>>(),
+            headers.iter().collect::<Vec<_>>()
+        );
+    }
+
+    /// Checks that responses with more bodies than requested are rejected
+    #[tokio::test]
+    async fn rejects_unrequested_bodies() {
+        let downloader = BufferedDownloader::new(
+            Arc::new(TestBodiesClient::new(|_: Vec<H256>| async {
+                Ok((
+                    PeerId::default(),
+                    vec![
+                        BlockBody { transactions: vec![], ommers: vec![], withdrawals: None },
+                        BlockBody { transactions: vec![], ommers: vec![], withdrawals: None },
+                    ],
+                )
+                    .into())
+            })),
+            Arc::new(TestConsensus::default()),
+        )
+        .with_retries(0);
+
+        let headers = &[Header { ommers_hash: H256::default(), ..Default::default() }.seal()];
+        assert_matches!(
+            downloader.bodies_stream(headers).next().await,
+            Some(Err(DownloadError::RequestError(RequestError::BadResponse)))
+        );
+    }
+
+    /// Checks that the error bubbles up if we've retried too many times
+    #[tokio::test]
+    async fn too_many_retries() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let client_requests = requests.clone();
+        let downloader = BufferedDownloader::new(
+            Arc::new(TestBodiesClient::new(move |_: Vec<H256>| {
+                client_requests.fetch_add(1, Ordering::SeqCst);
+                async { Err(RequestError::Timeout) }
+            })),
+            Arc::new(TestConsensus::default()),
+        )
+        .with_retries(2);
+
+        let headers = &[Header { ommers_hash: H256::default(), ..Default::default() }.seal()];
+        assert_matches!(
+            downloader.bodies_stream(headers).next().await,
+            Some(Err(DownloadError::RequestError(RequestError::Timeout)))
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+}
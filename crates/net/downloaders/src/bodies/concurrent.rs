@@ -115,6 +115,7 @@ where
     /// 3. Request the bodies for the non-empty headers from a peer chosen by the network client
     /// 4. For any non-empty headers, it proceeds to validate the corresponding body from the peer
     /// and return it as part of the response via the [`BlockResponse::Full`] variant.
+    /// 5. Reject the response if the peer sent more bodies than requested.
     ///
     /// NB: This assumes that peers respond with bodies in the order that they were requested.
     /// This is a reasonable assumption to make as that's [what Geth
@@ -164,6 +165,7 @@ where
                     header: header.clone(),
                     body: body.transactions,
                     ommers: body.ommers.into_iter().map(|header| header.seal()).collect(),
+                    withdrawals: body.withdrawals,
                 };
 
                 // This ensures that the TxRoot and OmmersRoot from the header match the
@@ -177,6 +179,12 @@ where
             }
         }
 
+        // The peer sent bodies that were not requested
+        if bodies.next().is_some() {
+            self.client.report_bad_message(peer_id);
+            return Err(DownloadError::RequestError(RequestError::BadResponse))
+        }
+
         Ok(responses)
     }
 }
@@ -253,6 +261,7 @@ mod tests {
                                     header,
                                     body: body.transactions,
                                     ommers: body.ommers.into_iter().map(|o| o.seal()).collect(),
+                                    withdrawals: body.withdrawals,
                                 })
                             }
                         })
@@ -262,6 +271,31 @@ mod tests {
         );
     }
 
+    /// Checks that responses with more bodies than requested are rejected
+    #[tokio::test]
+    async fn rejects_unrequested_bodies() {
+        let downloader = ConcurrentDownloader::new(
+            Arc::new(TestBodiesClient::new(|_: Vec<H256>| async {
+                Ok((
+                    PeerId::default(),
+                    vec![
+                        BlockBody { transactions: vec![], ommers: vec![], withdrawals: None },
+                        BlockBody { transactions: vec![], ommers: vec![], withdrawals: None },
+                    ],
+                )
+                    .into())
+            })),
+            Arc::new(TestConsensus::default()),
+        )
+        .with_retries(0);
+
+        let headers = &[Header { ommers_hash: H256::default(), ..Default::default() }.seal()];
+        assert_matches!(
+            downloader.bodies_stream(headers).next().await,
+            Some(Err(DownloadError::RequestError(RequestError::BadResponse)))
+        );
+    }
+
     /// Checks that non-retryable errors bubble up
     #[tokio::test]
     async fn client_failure() {
@@ -295,7 +329,11 @@ mod tests {
                     } else {
                         Ok((
                             PeerId::default(),
-                            vec![BlockBody { transactions: vec![], ommers: vec![] }],
+                            vec![BlockBody {
+                                transactions: vec![],
+                                ommers: vec![],
+                                withdrawals: None,
+                            }],
                         )
                             .into())
                     }
@@ -329,7 +367,11 @@ mod tests {
                     } else {
                         Ok((
                             PeerId::default(),
-                            vec![BlockBody { transactions: vec![], ommers: vec![] }],
+                            vec![BlockBody {
+                                transactions: vec![],
+                                ommers: vec![],
+                                withdrawals: None,
+                            }],
                         )
                             .into())
                     }
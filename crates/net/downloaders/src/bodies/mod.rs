@@ -1,2 +1,5 @@
 /// A naive concurrent downloader.
 pub mod concurrent;
+
+/// A concurrent downloader with a bounded buffer of bodies.
+pub mod buffered;
@@ -0,0 +1,439 @@
+use async_trait::async_trait;
+use reth_eth_wire::{BlockBody, BlockHeaders};
+use reth_interfaces::p2p::{
+    bodies::client::BodiesClient,
+    downloader::DownloadClient,
+    error::PeerRequestResult,
+    headers::client::{HeadersClient, HeadersRequest},
+};
+use reth_primitives::{
+    Block, BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId, SealedHeader, H256,
+};
+use reth_rlp::{Decodable, DecodeError};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+use thiserror::Error;
+use tracing::{trace, warn};
+
+/// Serves the blocks of a local RLP export file, so they can be imported with the same
+/// downloaders and stages that sync from the network.
+///
+/// The file contains RLP encoded blocks back to back, as written by `geth export`. The blocks must
+/// form a chain in ascending order.
+///
+/// The requests are answered with the blocks that are in the file, all of them from the same
+/// default peer.
+///
+/// Files that don't fit into memory are read in chunks with a [ChunkedFileReader].
+#[derive(Debug, Default)]
+pub struct FileClient {
+    /// The headers of the file, by their number.
+    headers: HashMap<BlockNumber, Header>,
+    /// The block numbers of the headers, by their hash.
+    hash_to_number: HashMap<H256, BlockNumber>,
+    /// The bodies of the file, by their block hash.
+    bodies: HashMap<H256, BlockBody>,
+    /// The last block of the file.
+    tip: Option<SealedHeader>,
+}
+
+/// An error that can occur when reading an export file.
+#[derive(Debug, Error)]
+pub enum FileClientError {
+    /// The file could not be read.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The file contains a block that could not be decoded.
+    #[error("Failed to decode block {index} of the file: {error}")]
+    Rlp {
+        /// The position of the block in the file.
+        index: usize,
+        /// The decoding error.
+        error: DecodeError,
+    },
+    /// The blocks of the file do not form a chain.
+    #[error("Block {number} of the file is not a child of the previous block")]
+    Disconnected {
+        /// The number of the block.
+        number: BlockNumber,
+    },
+}
+
+impl FileClient {
+    /// Read the blocks of the export file at the given path.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, FileClientError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Decode the blocks of the content of an export file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FileClientError> {
+        let mut client = Self::default();
+        let blocks = client.extend_from_bytes(bytes, 0)?;
+        trace!(target: "downloaders::file", blocks, "Read export file");
+        Ok(client)
+    }
+
+    /// Decode the blocks of the bytes, which contain whole blocks only, and append them to the
+    /// blocks of the client. The first block is at the given position of the file.
+    ///
+    /// Returns the number of decoded blocks.
+    fn extend_from_bytes(
+        &mut self,
+        mut bytes: &[u8],
+        first_index: usize,
+    ) -> Result<usize, FileClientError> {
+        let mut index = first_index;
+        while !bytes.is_empty() {
+            let block =
+                Block::decode(&mut bytes).map_err(|error| FileClientError::Rlp { index, error })?;
+            self.push(block)?;
+            index += 1;
+        }
+        Ok(index - first_index)
+    }
+
+    /// Append the block, which has to be a child of the tip.
+    fn push(&mut self, block: Block) -> Result<(), FileClientError> {
+        let number = block.header.number;
+        let header = block.header.seal();
+        if let Some(parent) = &self.tip {
+            if header.parent_hash != parent.hash() || number != parent.number + 1 {
+                return Err(FileClientError::Disconnected { number })
+            }
+        }
+
+        self.hash_to_number.insert(header.hash(), number);
+        self.bodies.insert(
+            header.hash(),
+            BlockBody {
+                transactions: block.body,
+                ommers: block.ommers,
+                withdrawals: block.withdrawals,
+            },
+        );
+        self.headers.insert(number, header.as_ref().clone());
+        self.tip = Some(header);
+        Ok(())
+    }
+
+    /// The last block of the file, if the file is not empty.
+    pub fn tip(&self) -> Option<&SealedHeader> {
+        self.tip.as_ref()
+    }
+
+    /// The number of blocks in the file.
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Whether the file contains no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+}
+
+/// The default size of the chunks of a [ChunkedFileReader], 1 GB.
+pub const DEFAULT_CHUNK_LEN: usize = 1_000_000_000;
+
+/// Reads an export file in chunks of whole blocks, so files that don't fit into memory can be
+/// imported one [FileClient] at a time.
+///
+/// The blocks of each chunk continue the chain of the previous chunk. A chunk is larger than the
+/// configured size only if a single block is.
+#[derive(Debug)]
+pub struct ChunkedFileReader {
+    /// The export file.
+    file: File,
+    /// The size of the file in bytes.
+    file_len: u64,
+    /// The bytes that were read from the file but not decoded yet.
+    buf: Vec<u8>,
+    /// The size of the chunks in bytes.
+    chunk_len: usize,
+    /// The number of bytes that were decoded.
+    decoded_len: u64,
+    /// The number of blocks that were decoded.
+    decoded_blocks: usize,
+    /// The last block of the previous chunk.
+    tip: Option<SealedHeader>,
+}
+
+impl ChunkedFileReader {
+    /// Open the export file at the given path, to be read in chunks of about the given size.
+    pub fn new<P: AsRef<Path>>(path: P, chunk_len: usize) -> Result<Self, FileClientError> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        Ok(Self {
+            file,
+            file_len,
+            buf: Vec::new(),
+            chunk_len: chunk_len.max(1),
+            decoded_len: 0,
+            decoded_blocks: 0,
+            tip: None,
+        })
+    }
+
+    /// Returns the number of bytes that were decoded so far and the size of the file.
+    pub fn progress(&self) -> (u64, u64) {
+        (self.decoded_len, self.file_len)
+    }
+
+    /// Read and decode the next chunk of the file, `None` once the whole file is read.
+    pub fn next_chunk(&mut self) -> Result<Option<FileClient>, FileClientError> {
+        let mut eof = self.fill_buf(self.chunk_len)?;
+        if self.buf.is_empty() {
+            return Ok(None)
+        }
+
+        // the length of the whole blocks at the start of the buffer
+        let mut len = 0;
+        while len < self.buf.len() {
+            match encoded_len(&self.buf[len..]) {
+                Some(block_len) if len + block_len <= self.buf.len() => len += block_len,
+                // the next block is part of the next chunk
+                _ if len > 0 => break,
+                // the first block is larger than the chunk
+                _ if !eof => eof = self.fill_buf(self.buf.len() * 2)?,
+                // the file ends in the middle of the block, which fails to decode
+                _ => len = self.buf.len(),
+            }
+        }
+
+        let mut client = FileClient { tip: self.tip.take(), ..Default::default() };
+        let blocks = client.extend_from_bytes(&self.buf[..len], self.decoded_blocks)?;
+        trace!(target: "downloaders::file", blocks, "Read chunk of export file");
+
+        self.buf.drain(..len);
+        self.decoded_len += len as u64;
+        self.decoded_blocks += blocks;
+        self.tip = client.tip.clone();
+        Ok(Some(client))
+    }
+
+    /// Read from the file until the buffer holds the given number of bytes.
+    ///
+    /// Returns `true` if the end of the file was reached.
+    fn fill_buf(&mut self, len: usize) -> io::Result<bool> {
+        while self.buf.len() < len {
+            let start = self.buf.len();
+            self.buf.resize(len, 0);
+            let read = self.file.read(&mut self.buf[start..])?;
+            self.buf.truncate(start + read);
+            if read == 0 {
+                return Ok(true)
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Returns the length of the RLP encoded block at the start of the bytes, or `None` if the bytes
+/// end before the length is known.
+///
+/// Bytes that don't start with a list are counted as a single byte, which fails to decode.
+fn encoded_len(bytes: &[u8]) -> Option<usize> {
+    let (&first, rest) = bytes.split_first()?;
+    match first {
+        0..=0xBF => Some(1),
+        0xC0..=0xF7 => Some(1 + (first - 0xC0) as usize),
+        _ => {
+            let len_of_len = (first - 0xF7) as usize;
+            let payload_len =
+                rest.get(..len_of_len)?.iter().fold(0u64, |len, byte| len << 8 | *byte as u64);
+            (1 + len_of_len).checked_add(usize::try_from(payload_len).ok()?)
+        }
+    }
+}
+
+impl DownloadClient for FileClient {
+    fn report_bad_message(&self, _peer_id: PeerId) {
+        warn!(target: "downloaders::file", "Reported a bad message on a file client, the file may be corrupted");
+    }
+}
+
+#[async_trait]
+impl HeadersClient for FileClient {
+    async fn get_headers(&self, request: HeadersRequest) -> PeerRequestResult<BlockHeaders> {
+        let start = match request.start {
+            BlockHashOrNumber::Hash(hash) => self.hash_to_number.get(&hash).copied(),
+            BlockHashOrNumber::Number(number) => Some(number),
+        };
+
+        let mut headers = Vec::new();
+        if let Some(start) = start {
+            let mut number = start;
+            while let Some(header) = self.headers.get(&number) {
+                if headers.len() as u64 >= request.limit {
+                    break
+                }
+                headers.push(header.clone());
+                number = match request.direction {
+                    HeadersDirection::Rising => number + 1,
+                    HeadersDirection::Falling => match number.checked_sub(1) {
+                        Some(number) => number,
+                        None => break,
+                    },
+                };
+            }
+        }
+
+        Ok((PeerId::default(), BlockHeaders(headers)).into())
+    }
+}
+
+#[async_trait]
+impl BodiesClient for FileClient {
+    async fn get_block_bodies(&self, hashes: Vec<H256>) -> PeerRequestResult<Vec<BlockBody>> {
+        // The response ends at the first body that is not in the file, like a peer's response
+        let bodies = hashes.iter().map_while(|hash| self.bodies.get(hash).cloned()).collect();
+        Ok((PeerId::default(), bodies).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bodies::buffered::BufferedDownloader, headers::reverse::ReverseHeadersDownloaderBuilder,
+    };
+    use assert_matches::assert_matches;
+    use futures::TryStreamExt;
+    use reth_interfaces::{
+        p2p::{
+            bodies::downloader::{BlockResponse, BodyDownloader},
+            headers::downloader::HeaderDownloader,
+        },
+        test_utils::{generators::random_block_range, TestConsensus},
+    };
+    use reth_primitives::SealedBlock;
+    use reth_rlp::Encodable;
+    use std::sync::Arc;
+
+    /// Encode the blocks like an export file.
+    fn export(blocks: &[SealedBlock]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for block in blocks {
+            let block = Block {
+                header: block.header.as_ref().clone(),
+                body: block.body.clone(),
+                ommers: block.ommers.iter().map(|header| header.as_ref().clone()).collect(),
+                withdrawals: block.withdrawals.clone(),
+            };
+            block.encode(&mut bytes);
+        }
+        bytes
+    }
+
+    #[tokio::test]
+    async fn download_from_file() {
+        let blocks = random_block_range(0..20, H256::zero(), 0..3);
+        let client = Arc::new(FileClient::from_bytes(&export(&blocks)).unwrap());
+        assert_eq!(client.len(), 20);
+        assert_eq!(client.tip(), Some(&blocks[19].header));
+
+        let consensus = Arc::new(TestConsensus::default());
+        let headers = ReverseHeadersDownloaderBuilder::default()
+            .request_limit(3)
+            .build(consensus.clone(), client.clone())
+            .stream(blocks[0].header.clone(), blocks[19].hash())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let expected =
+            blocks[1..].iter().rev().map(|block| block.header.clone()).collect::<Vec<_>>();
+        assert_eq!(headers, expected);
+
+        let responses = BufferedDownloader::new(client, consensus)
+            .with_batch_size(3)
+            .bodies_stream(blocks[1..].iter().map(|block| &block.header))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            responses.iter().map(BlockResponse::header).collect::<Vec<_>>(),
+            blocks[1..].iter().map(|block| &block.header).collect::<Vec<_>>()
+        );
+        for (response, block) in responses.iter().zip(&blocks[1..]) {
+            if let BlockResponse::Full(response) = response {
+                assert_eq!(response, block);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn request_beyond_file() {
+        let blocks = random_block_range(0..5, H256::zero(), 0..3);
+        let client = FileClient::from_bytes(&export(&blocks)).unwrap();
+
+        let request = HeadersRequest {
+            start: BlockHashOrNumber::Number(3),
+            limit: 10,
+            direction: HeadersDirection::Rising,
+        };
+        let (_, BlockHeaders(headers)) = client.get_headers(request).await.unwrap().split();
+        assert_eq!(
+            headers,
+            vec![blocks[3].header.as_ref().clone(), blocks[4].header.as_ref().clone()]
+        );
+
+        let bodies =
+            client.get_block_bodies(vec![blocks[2].hash(), H256::zero(), blocks[3].hash()]).await;
+        assert_eq!(bodies.unwrap().split().1.len(), 1);
+    }
+
+    #[test]
+    fn reject_disconnected_blocks() {
+        let mut blocks = random_block_range(0..5, H256::zero(), 0..3);
+        blocks.remove(2);
+        assert_matches!(
+            FileClient::from_bytes(&export(&blocks)),
+            Err(FileClientError::Disconnected { number: 3 })
+        );
+    }
+
+    #[test]
+    fn reject_truncated_file() {
+        let blocks = random_block_range(0..2, H256::zero(), 0..3);
+        let bytes = export(&blocks);
+        assert_matches!(
+            FileClient::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(FileClientError::Rlp { index: 1, .. })
+        );
+    }
+    #[test]
+    fn read_file_in_chunks() {
+        let blocks = random_block_range(0..20, H256::zero(), 0..3);
+        let bytes = export(&blocks);
+        let path = std::env::temp_dir().join(format!("reth-export-{}.rlp", H256::random()));
+        std::fs::write(&path, &bytes).unwrap();
+
+        for chunk_len in [1, bytes.len() / 7, bytes.len() * 2] {
+            let mut reader = ChunkedFileReader::new(&path, chunk_len).unwrap();
+            let mut chunks = Vec::new();
+            while let Some(chunk) = reader.next_chunk().unwrap() {
+                chunks.push(chunk);
+            }
+            assert_eq!(reader.progress(), (bytes.len() as u64, bytes.len() as u64));
+
+            let tips = chunks.iter().map(|chunk| chunk.tip().unwrap().clone()).collect::<Vec<_>>();
+            assert_eq!(tips.last(), Some(&blocks[19].header));
+            assert_eq!(chunks.iter().map(FileClient::len).sum::<usize>(), 20);
+            if chunk_len == 1 {
+                assert_eq!(chunks.len(), 20);
+            }
+            // every chunk starts with the child of the tip of the previous chunk
+            for (chunk, parent) in chunks.iter().skip(1).zip(&tips) {
+                let first = chunk.tip().unwrap().number + 1 - chunk.len() as u64;
+                assert_eq!(first, parent.number + 1);
+                assert_eq!(chunk.headers[&first].parent_hash, parent.hash());
+            }
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
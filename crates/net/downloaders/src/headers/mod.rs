@@ -1,2 +1,5 @@
 /// A Linear downloader implementation.
 pub mod linear;
+
+/// A reverse downloader implementation with concurrent requests.
+pub mod reverse;
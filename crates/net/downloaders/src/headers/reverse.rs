@@ -0,0 +1,609 @@
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use reth_interfaces::{
+    consensus::Consensus,
+    p2p::{
+        downloader::{DownloadStream, Downloader},
+        error::{DownloadError, DownloadResult, PeerRequestResult, RequestError},
+        headers::{
+            client::{BlockHeaders, HeadersClient, HeadersRequest},
+            downloader::{validate_header_download, HeaderDownloader},
+        },
+    },
+};
+use reth_primitives::{
+    BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId, SealedHeader, H256,
+};
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tracing::trace;
+
+/// Download headers in reverse, from the tip down to the local head, with concurrent requests.
+///
+/// The tip is requested by its hash first to learn its number. The range between the tip and the
+/// local head is then split into requests of up to `request_limit` headers by block number, of
+/// which up to `concurrent_requests` are in flight at a time. Every response is validated on its
+/// own and attached below the headers of the tip once all batches above it have arrived, which
+/// validates that it links to them.
+///
+/// Peers that respond with invalid or empty batches are penalized, so the client picks a different
+/// peer for the retried request.
+// ANCHOR: struct-ReverseHeadersDownloader
+#[derive(Debug)]
+pub struct ReverseHeadersDownloader<C, H> {
+    /// The consensus client
+    consensus: Arc<C>,
+    /// The headers client
+    client: Arc<H>,
+    /// The maximum number of headers per request
+    pub request_limit: u64,
+    /// The maximum number of requests in flight
+    pub concurrent_requests: usize,
+    /// The time after which an unanswered request is retried
+    pub request_timeout: Duration,
+    /// The number of attempts per request
+    pub request_retries: usize,
+}
+// ANCHOR_END: struct-ReverseHeadersDownloader
+
+impl<C, H> Downloader for ReverseHeadersDownloader<C, H>
+where
+    C: Consensus,
+    H: HeadersClient,
+{
+    type Consensus = C;
+    type Client = H;
+
+    fn consensus(&self) -> &Self::Consensus {
+        self.consensus.borrow()
+    }
+
+    fn client(&self) -> &Self::Client {
+        self.client.borrow()
+    }
+}
+
+impl<C, H> HeaderDownloader for ReverseHeadersDownloader<C, H>
+where
+    C: Consensus + 'static,
+    H: HeadersClient + 'static,
+{
+    fn stream(&self, head: SealedHeader, tip: H256) -> DownloadStream<'_, SealedHeader> {
+        Box::pin(self.new_download(head, tip))
+    }
+}
+
+impl<C: Consensus, H: HeadersClient> Clone for ReverseHeadersDownloader<C, H> {
+    fn clone(&self) -> Self {
+        Self {
+            consensus: Arc::clone(&self.consensus),
+            client: Arc::clone(&self.client),
+            request_limit: self.request_limit,
+            concurrent_requests: self.concurrent_requests,
+            request_timeout: self.request_timeout,
+            request_retries: self.request_retries,
+        }
+    }
+}
+
+impl<C: Consensus, H: HeadersClient> ReverseHeadersDownloader<C, H> {
+    fn new_download(&self, head: SealedHeader, tip: H256) -> ReverseHeadersDownload<C, H> {
+        let tip_request = PendingRequest {
+            request: HeadersRequest {
+                start: tip.into(),
+                limit: 1,
+                direction: HeadersDirection::Falling,
+            },
+            attempts: 0,
+        };
+        ReverseHeadersDownload {
+            head,
+            consensus: Arc::clone(&self.consensus),
+            client: Arc::clone(&self.client),
+            request_limit: self.request_limit,
+            concurrent_requests: self.concurrent_requests,
+            request_timeout: self.request_timeout,
+            request_retries: self.request_retries,
+            lowest: None,
+            next_request: 0,
+            retries: VecDeque::from([tip_request]),
+            in_flight: FuturesUnordered::new(),
+            responses: BTreeMap::new(),
+            queued: VecDeque::new(),
+            encountered_error: false,
+        }
+    }
+}
+
+/// A request with the number of times it was sent.
+#[derive(Debug, Clone)]
+struct PendingRequest {
+    request: HeadersRequest,
+    attempts: usize,
+}
+
+type HeadersFut =
+    Pin<Box<dyn Future<Output = (PendingRequest, PeerRequestResult<BlockHeaders>)> + Send>>;
+
+/// A validated response that is not attached to the downloaded chain yet.
+struct BufferedResponse {
+    request: PendingRequest,
+    peer_id: PeerId,
+    /// The headers in descending order.
+    headers: Vec<SealedHeader>,
+}
+
+/// An in progress reverse headers download.
+pub struct ReverseHeadersDownload<C, H> {
+    /// The local head of the chain.
+    head: SealedHeader,
+    consensus: Arc<C>,
+    client: Arc<H>,
+    request_limit: u64,
+    concurrent_requests: usize,
+    request_timeout: Duration,
+    request_retries: usize,
+    /// The lowest header that is attached to the tip, or `None` if the tip wasn't received yet.
+    lowest: Option<SealedHeader>,
+    /// The highest block that was not requested yet.
+    next_request: BlockNumber,
+    /// The requests to send before any new ones.
+    retries: VecDeque<PendingRequest>,
+    /// The requests in flight.
+    in_flight: FuturesUnordered<HeadersFut>,
+    /// The responses that can't be attached yet, by their highest block.
+    responses: BTreeMap<BlockNumber, BufferedResponse>,
+    /// The attached headers that are ready to be returned, in descending order.
+    queued: VecDeque<SealedHeader>,
+    /// Flag whether the stream encountered an error
+    encountered_error: bool,
+}
+
+impl<C, H> ReverseHeadersDownload<C, H>
+where
+    C: Consensus + 'static,
+    H: HeadersClient + 'static,
+{
+    /// Check if all headers down to the local head were attached.
+    fn is_complete(&self) -> bool {
+        self.lowest.as_ref().map_or(false, |lowest| lowest.number <= self.head.number + 1)
+    }
+
+    /// Send requests until the maximum number of requests is in flight.
+    ///
+    /// Failed requests are retried first. New requests are only sent while fewer than
+    /// `concurrent_requests` responses are waiting to be attached, which bounds the memory used
+    /// when a request for higher blocks is slow.
+    fn send_requests(&mut self) {
+        while self.in_flight.len() < self.concurrent_requests {
+            let pending = match self.retries.pop_front() {
+                Some(pending) => pending,
+                None => match self.next_batch_request() {
+                    Some(pending) => pending,
+                    None => break,
+                },
+            };
+            self.send_request(pending);
+        }
+    }
+
+    /// The request for the next highest batch of headers, if there are blocks left to request.
+    fn next_batch_request(&mut self) -> Option<PendingRequest> {
+        if self.lowest.is_none() ||
+            self.next_request <= self.head.number ||
+            self.responses.len() >= self.concurrent_requests
+        {
+            return None
+        }
+
+        let start = self.next_request;
+        let limit = self.request_limit.min(start - self.head.number);
+        self.next_request -= limit;
+        Some(PendingRequest {
+            request: HeadersRequest {
+                start: start.into(),
+                limit,
+                direction: HeadersDirection::Falling,
+            },
+            attempts: 0,
+        })
+    }
+
+    // ANCHOR: fn-send_request
+    /// Send the request, failing it with a timeout if it isn't answered within `request_timeout`.
+    fn send_request(&mut self, mut pending: PendingRequest) {
+        trace!(target: "downloaders::headers", request = ?pending.request, attempt = pending.attempts, "Requesting headers");
+        pending.attempts += 1;
+        let client = Arc::clone(&self.client);
+        let timeout = self.request_timeout;
+        self.in_flight.push(Box::pin(async move {
+            let request = pending.request.clone();
+            let result = tokio::time::timeout(timeout, client.get_headers(request))
+                .await
+                .unwrap_or(Err(RequestError::Timeout));
+            (pending, result)
+        }));
+    }
+    // ANCHOR_END: fn-send_request
+
+    /// Penalize the peer, if any, and queue the request to be sent again.
+    ///
+    /// Returns the error if the request ran out of retries.
+    #[allow(clippy::result_large_err)]
+    fn retry(
+        &mut self,
+        pending: PendingRequest,
+        peer_id: Option<PeerId>,
+        err: DownloadError,
+    ) -> DownloadResult<()> {
+        if let Some(peer_id) = peer_id {
+            trace!(target: "downloaders::headers", ?peer_id, ?err, "Penalizing peer");
+            self.client.report_bad_message(peer_id);
+        }
+        if pending.attempts >= self.request_retries {
+            trace!(target: "downloaders::headers", request = ?pending.request, "Ran out of retries");
+            return Err(err)
+        }
+        self.retries.push_back(pending);
+        Ok(())
+    }
+
+    /// Validate the response and buffer its headers.
+    #[allow(clippy::result_large_err)]
+    fn on_response(
+        &mut self,
+        pending: PendingRequest,
+        result: PeerRequestResult<BlockHeaders>,
+    ) -> DownloadResult<()> {
+        let (peer_id, BlockHeaders(headers)) = match result {
+            Ok(response) => response.split(),
+            Err(RequestError::Timeout) => return self.retry(pending, None, DownloadError::Timeout),
+            Err(err) => return self.retry(pending, None, err.into()),
+        };
+
+        let headers = match self.validate_response(&pending.request, headers) {
+            Ok(headers) => headers,
+            Err(err) => return self.retry(pending, Some(peer_id), err),
+        };
+
+        match pending.request.start {
+            BlockHashOrNumber::Hash(_) => {
+                let tip = headers.into_iter().next().expect("validated response is not empty");
+                self.next_request = tip.number.saturating_sub(1);
+                if tip.hash() != self.head.hash() {
+                    self.queued.push_back(tip.clone());
+                }
+                self.lowest = Some(tip);
+            }
+            BlockHashOrNumber::Number(start) => {
+                // Request the headers that the peer didn't return
+                let received = headers.len() as u64;
+                if received < pending.request.limit {
+                    self.retries.push_back(PendingRequest {
+                        request: HeadersRequest {
+                            start: (start - received).into(),
+                            limit: pending.request.limit - received,
+                            direction: HeadersDirection::Falling,
+                        },
+                        attempts: 0,
+                    });
+                }
+
+                let mut request = pending;
+                request.request.limit = received;
+                self.responses.insert(start, BufferedResponse { request, peer_id, headers });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the headers are the requested ones and that each of them is the parent of the
+    /// previous one.
+    ///
+    /// Returns the headers in descending order.
+    #[allow(clippy::result_large_err)]
+    fn validate_response(
+        &self,
+        request: &HeadersRequest,
+        headers: Vec<Header>,
+    ) -> DownloadResult<Vec<SealedHeader>> {
+        let mut headers = headers.into_iter().map(|header| header.seal()).collect::<Vec<_>>();
+        headers.sort_unstable_by_key(|header| std::cmp::Reverse(header.number));
+        headers.truncate(request.limit as usize);
+
+        let first = headers.first().ok_or(DownloadError::EmptyResponse)?;
+        match request.start {
+            BlockHashOrNumber::Hash(hash) if first.hash() != hash => {
+                return Err(DownloadError::InvalidTip { received: first.hash(), expected: hash })
+            }
+            BlockHashOrNumber::Number(number) if first.number != number => {
+                return Err(DownloadError::InvalidHeadersStart {
+                    received: first.number,
+                    expected: number,
+                })
+            }
+            _ => {}
+        }
+
+        for pair in headers.windows(2) {
+            validate_header_download(&self.consensus, &pair[0], &pair[1])?;
+        }
+        Ok(headers)
+    }
+
+    /// Attach the buffered responses that continue the downloaded chain.
+    #[allow(clippy::result_large_err)]
+    fn attach_responses(&mut self) -> DownloadResult<()> {
+        while !self.is_complete() {
+            let lowest = match &self.lowest {
+                Some(lowest) => lowest,
+                None => break,
+            };
+            let response = match self.responses.remove(&(lowest.number - 1)) {
+                Some(response) => response,
+                None => break,
+            };
+
+            // The downloaded chain is linked to the tip by hash, so a batch that doesn't attach to
+            // it is invalid
+            if let Err(err) =
+                validate_header_download(&self.consensus, lowest, &response.headers[0])
+            {
+                self.retry(response.request, Some(response.peer_id), err)?;
+                break
+            }
+
+            self.lowest = response.headers.last().cloned();
+            self.queued.extend(response.headers);
+        }
+        Ok(())
+    }
+}
+
+impl<C, H> Stream for ReverseHeadersDownload<C, H>
+where
+    C: Consensus + 'static,
+    H: HeadersClient + 'static,
+{
+    type Item = DownloadResult<SealedHeader>;
+
+    /// Reverse header downloader implemented as a [Stream]. The downloader keeps up to
+    /// `concurrent_requests` requests in flight and returns the headers in descending order,
+    /// starting with the tip, as soon as they are attached to the downloaded chain.
+    ///
+    /// Failed requests are retried. If a request runs out of retries, the downloader streams the
+    /// error, drops the buffered headers and terminates.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(header) = this.queued.pop_front() {
+                return Poll::Ready(Some(Ok(header)))
+            }
+
+            if this.encountered_error || this.is_complete() {
+                return Poll::Ready(None)
+            }
+
+            this.send_requests();
+            let (pending, result) = match ready!(this.in_flight.poll_next_unpin(cx)) {
+                Some(response) => response,
+                // There is nothing left to request
+                None => return Poll::Ready(None),
+            };
+
+            if let Err(err) =
+                this.on_response(pending, result).and_then(|_| this.attach_responses())
+            {
+                this.encountered_error = true;
+                this.queued.clear();
+                this.responses.clear();
+                return Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+}
+
+/// The builder for [ReverseHeadersDownloader] with
+/// some default settings
+#[derive(Debug)]
+pub struct ReverseHeadersDownloaderBuilder {
+    /// The maximum number of headers per request
+    request_limit: u64,
+    /// The maximum number of requests in flight
+    concurrent_requests: usize,
+    /// The time after which an unanswered request is retried
+    request_timeout: Duration,
+    /// The number of attempts per request
+    request_retries: usize,
+}
+
+impl Default for ReverseHeadersDownloaderBuilder {
+    fn default() -> Self {
+        Self {
+            request_limit: 1_000,
+            concurrent_requests: 10,
+            request_timeout: Duration::from_secs(10),
+            request_retries: 5,
+        }
+    }
+}
+
+impl ReverseHeadersDownloaderBuilder {
+    /// Set the maximum number of headers per request
+    pub fn request_limit(mut self, limit: u64) -> Self {
+        self.request_limit = limit;
+        self
+    }
+
+    /// Set the maximum number of requests in flight
+    pub fn concurrent_requests(mut self, concurrent_requests: usize) -> Self {
+        self.concurrent_requests = concurrent_requests;
+        self
+    }
+
+    /// Set the time after which an unanswered request is retried
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Set the number of attempts per request
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.request_retries = retries;
+        self
+    }
+
+    /// Build [ReverseHeadersDownloader] with provided consensus
+    /// and header client implementations
+    pub fn build<C: Consensus, H: HeadersClient>(
+        self,
+        consensus: Arc<C>,
+        client: Arc<H>,
+    ) -> ReverseHeadersDownloader<C, H> {
+        ReverseHeadersDownloader {
+            consensus,
+            client,
+            request_limit: self.request_limit,
+            concurrent_requests: self.concurrent_requests,
+            request_timeout: self.request_timeout,
+            request_retries: self.request_retries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestHeadersClient;
+    use futures::TryStreamExt;
+    use once_cell::sync::Lazy;
+    use reth_interfaces::test_utils::TestConsensus;
+
+    static CONSENSUS: Lazy<Arc<TestConsensus>> = Lazy::new(|| Arc::new(TestConsensus::default()));
+
+    /// A chain of headers starting with the genesis header.
+    fn chain(len: u64) -> Vec<SealedHeader> {
+        let mut headers = vec![Header::default().seal()];
+        for _ in 1..len {
+            let parent = headers.last().unwrap();
+            let mut child = parent.as_ref().clone();
+            child.number += 1;
+            child.parent_hash = parent.hash_slow();
+            headers.push(child.seal());
+        }
+        headers
+    }
+
+    /// Answer the request from the headers of the chain.
+    fn respond(chain: &[SealedHeader], request: &HeadersRequest) -> Vec<Header> {
+        let start = match request.start {
+            BlockHashOrNumber::Hash(hash) => {
+                match chain.iter().find(|header| header.hash() == hash) {
+                    Some(header) => header.number,
+                    None => return vec![],
+                }
+            }
+            BlockHashOrNumber::Number(number) => number,
+        };
+        chain
+            .iter()
+            .rev()
+            .skip_while(|header| header.number > start)
+            .take(request.limit as usize)
+            .map(|header| header.as_ref().clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn download_concurrently() {
+        let headers = chain(20);
+        let chain = headers.clone();
+        let client = Arc::new(TestHeadersClient::new(move |request| respond(&chain, &request)));
+        let downloader = ReverseHeadersDownloaderBuilder::default()
+            .request_limit(3)
+            .concurrent_requests(4)
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let result = downloader
+            .stream(headers[5].clone(), headers[19].hash())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let expected = headers[6..].iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(result, expected);
+        // one request for the tip and five batches for the blocks 6 to 18
+        assert_eq!(client.request_attempts(), 6);
+    }
+
+    #[tokio::test]
+    async fn download_at_head() {
+        let headers = chain(3);
+        let chain = headers.clone();
+        let client = Arc::new(TestHeadersClient::new(move |request| respond(&chain, &request)));
+        let downloader = ReverseHeadersDownloaderBuilder::default()
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let result = downloader
+            .stream(headers[2].clone(), headers[2].hash())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(result.is_empty());
+        assert_eq!(client.request_attempts(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_invalid_responses() {
+        let headers = chain(10);
+        let chain = headers.clone();
+        let mut attempts = 0;
+        let client = Arc::new(TestHeadersClient::new(move |request| {
+            attempts += 1;
+            match attempts {
+                // an empty response
+                2 => vec![],
+                // a partial response
+                3 => respond(&chain, &request).into_iter().take(1).collect(),
+                // a response that doesn't link
+                4 => respond(&chain, &request).into_iter().step_by(2).collect(),
+                _ => respond(&chain, &request),
+            }
+        }));
+        let downloader = ReverseHeadersDownloaderBuilder::default()
+            .request_limit(4)
+            .concurrent_requests(1)
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let result = downloader
+            .stream(headers[0].clone(), headers[9].hash())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let expected = headers[1..].iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(result, expected);
+        // the peers of the empty and the unlinked response are penalized
+        assert_eq!(client.bad_messages(), 2);
+    }
+
+    #[tokio::test]
+    async fn run_out_of_retries() {
+        let client = Arc::new(TestHeadersClient::new(|_| vec![]));
+        let downloader = ReverseHeadersDownloaderBuilder::default()
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let result = downloader
+            .stream(SealedHeader::default(), H256::default())
+            .try_collect::<Vec<_>>()
+            .await;
+        assert!(matches!(result, Err(DownloadError::EmptyResponse)));
+        assert_eq!(client.request_attempts(), downloader.request_retries as u64);
+        assert_eq!(client.bad_messages(), downloader.request_retries);
+    }
+}
@@ -13,5 +13,8 @@ pub mod bodies;
 /// The collection of alhgorithms for downloading block headers.
 pub mod headers;
 
+/// A client that serves the blocks of an export file.
+pub mod file;
+
 #[cfg(test)]
 mod test_utils;
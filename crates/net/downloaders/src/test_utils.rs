@@ -3,15 +3,23 @@
 use async_trait::async_trait;
 use reth_eth_wire::BlockBody;
 use reth_interfaces::{
-    p2p::{bodies::client::BodiesClient, downloader::DownloadClient, error::PeerRequestResult},
+    p2p::{
+        bodies::client::BodiesClient,
+        downloader::DownloadClient,
+        error::PeerRequestResult,
+        headers::client::{BlockHeaders, HeadersClient, HeadersRequest},
+    },
     test_utils::generators::random_block_range,
 };
-use reth_primitives::{PeerId, SealedHeader, H256};
+use reth_primitives::{Header, PeerId, SealedHeader, H256};
 use std::{
     collections::HashMap,
     fmt::{Debug, Formatter},
     future::Future,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 use tokio::sync::Mutex;
 
@@ -30,6 +38,7 @@ pub(crate) fn generate_bodies(
                 BlockBody {
                     transactions: block.body,
                     ommers: block.ommers.into_iter().map(|header| header.unseal()).collect(),
+                    withdrawals: block.withdrawals,
                 },
             )
         })
@@ -70,3 +79,54 @@ where
         (f)(hash).await
     }
 }
+
+/// A [HeadersClient] for testing that responds with the headers returned by a closure.
+pub(crate) struct TestHeadersClient<F> {
+    f: Mutex<F>,
+    request_attempts: AtomicU64,
+    bad_messages: AtomicUsize,
+}
+
+impl<F> Debug for TestHeadersClient<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestHeadersClient").finish_non_exhaustive()
+    }
+}
+
+impl<F> TestHeadersClient<F> {
+    pub(crate) fn new(f: F) -> Self {
+        Self {
+            f: Mutex::new(f),
+            request_attempts: AtomicU64::new(0),
+            bad_messages: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of requests sent to the client.
+    pub(crate) fn request_attempts(&self) -> u64 {
+        self.request_attempts.load(Ordering::SeqCst)
+    }
+
+    /// The number of times a peer was penalized.
+    pub(crate) fn bad_messages(&self) -> usize {
+        self.bad_messages.load(Ordering::SeqCst)
+    }
+}
+
+impl<F: Send + Sync> DownloadClient for TestHeadersClient<F> {
+    fn report_bad_message(&self, _peer_id: PeerId) {
+        self.bad_messages.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl<F> HeadersClient for TestHeadersClient<F>
+where
+    F: FnMut(HeadersRequest) -> Vec<Header> + Send + Sync,
+{
+    async fn get_headers(&self, request: HeadersRequest) -> PeerRequestResult<BlockHeaders> {
+        self.request_attempts.fetch_add(1, Ordering::SeqCst);
+        let f = &mut *self.f.lock().await;
+        Ok((PeerId::default(), BlockHeaders((f)(request))).into())
+    }
+}
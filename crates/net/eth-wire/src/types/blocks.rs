@@ -1,8 +1,13 @@
 //! Implements the `GetBlockHeaders`, `GetBlockBodies`, `BlockHeaders`, and `BlockBodies` message
 //! types.
 use super::RawBlockBody;
-use reth_primitives::{BlockHashOrNumber, Header, HeadersDirection, TransactionSigned, H256};
-use reth_rlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use reth_primitives::{
+    BlockHashOrNumber, Header, HeadersDirection, TransactionSigned, Withdrawal, H256,
+};
+use reth_rlp::{
+    length_of_length, Decodable, DecodeError, Encodable, RlpDecodable, RlpDecodableWrapper,
+    RlpEncodable, RlpEncodableWrapper,
+};
 use serde::{Deserialize, Serialize};
 
 /// A request for a peer to return block headers starting at the requested block.
@@ -86,17 +91,25 @@ impl From<Vec<H256>> for GetBlockBodies {
 
 // TODO(onbjerg): We should have this type in primitives
 /// A response to [`GetBlockBodies`], containing bodies if any bodies were found.
-#[derive(
-    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
-)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct BlockBody {
     /// Transactions in the block
     pub transactions: Vec<TransactionSigned>,
     /// Uncle headers for the given block
     pub ommers: Vec<Header>,
+    /// Withdrawals in the block, `None` for blocks before Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 impl BlockBody {
+    fn payload_length(&self) -> usize {
+        let mut length = 0;
+        length += self.transactions.length();
+        length += self.ommers.length();
+        length += self.withdrawals.as_ref().map(|w| w.length()).unwrap_or_default();
+        length
+    }
+
     /// Create a [`Block`] from the body and its header.
     pub fn create_block(&self, header: &Header) -> RawBlockBody {
         RawBlockBody {
@@ -107,6 +120,52 @@ impl BlockBody {
     }
 }
 
+impl Encodable for BlockBody {
+    fn encode(&self, out: &mut dyn bytes::BufMut) {
+        let list_header = reth_rlp::Header { list: true, payload_length: self.payload_length() };
+        list_header.encode(out);
+        self.transactions.encode(out);
+        self.ommers.encode(out);
+        if let Some(ref withdrawals) = self.withdrawals {
+            withdrawals.encode(out);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let mut length = 0;
+        length += self.payload_length();
+        length += length_of_length(length);
+        length
+    }
+}
+
+impl Decodable for BlockBody {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let rlp_head = reth_rlp::Header::decode(buf)?;
+        if !rlp_head.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let started_len = buf.len();
+        let mut this = Self {
+            transactions: Decodable::decode(buf)?,
+            ommers: Decodable::decode(buf)?,
+            withdrawals: None,
+        };
+        let consumed = started_len - buf.len();
+        if consumed < rlp_head.payload_length {
+            this.withdrawals = Some(Decodable::decode(buf)?);
+        }
+        let consumed = started_len - buf.len();
+        if consumed != rlp_head.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: rlp_head.payload_length,
+                got: consumed,
+            })
+        }
+        Ok(this)
+    }
+}
+
 /// The response to [`GetBlockBodies`], containing the block bodies that the peer knows about if
 /// any were found.
 #[derive(
@@ -138,8 +197,8 @@ mod test {
     };
     use hex_literal::hex;
     use reth_primitives::{
-        BlockHashOrNumber, Header, Signature, Transaction, TransactionKind, TransactionSigned,
-        TxLegacy, U256,
+        Address, BlockHashOrNumber, Header, Signature, Transaction, TransactionKind,
+        TransactionSigned, TxLegacy, Withdrawal, U256,
     };
     use reth_rlp::{Decodable, Encodable};
     use std::str::FromStr;
@@ -300,6 +359,10 @@ mod test {
                     mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                     nonce: 0x0000000000000000u64,
                     base_fee_per_gas: None,
+                    withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
                 },
             ]),
         }.encode(&mut data);
@@ -330,6 +393,10 @@ mod test {
                     mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                     nonce: 0x0000000000000000u64,
                     base_fee_per_gas: None,
+                    withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
                 },
             ]),
         };
@@ -441,8 +508,13 @@ mod test {
     hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                             nonce: 0x0000000000000000u64,
                             base_fee_per_gas: None,
+                            withdrawals_root: None,
+                            blob_gas_used: None,
+                            excess_blob_gas: None,
+                            parent_beacon_block_root: None,
                         },
                     ],
+                    withdrawals: None,
                 }
             ]),
         };
@@ -523,12 +595,42 @@ mod test {
     hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                             nonce: 0x0000000000000000u64,
                             base_fee_per_gas: None,
+                            withdrawals_root: None,
+                            blob_gas_used: None,
+                            excess_blob_gas: None,
+                            parent_beacon_block_root: None,
                         },
                     ],
+                    withdrawals: None,
                 }
             ]),
         };
         let result = RequestPair::decode(&mut &data[..]).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn block_body_with_withdrawals_roundtrip() {
+        let body = BlockBody {
+            transactions: vec![],
+            ommers: vec![],
+            withdrawals: Some(vec![Withdrawal {
+                index: 1,
+                validator_index: 2,
+                address: Address::from_low_u64_be(3),
+                amount: 4,
+            }]),
+        };
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(data.len(), body.length());
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap(), body);
+
+        // bodies without withdrawals are encoded as before shanghai
+        let body = BlockBody { withdrawals: None, ..body };
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(data, hex!("c2c0c0"));
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap(), body);
+    }
 }
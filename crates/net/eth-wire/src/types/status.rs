@@ -1,6 +1,6 @@
 use crate::{EthVersion, StatusBuilder};
 
-use reth_primitives::{Chain, ForkId, Hardfork, H256, MAINNET_GENESIS, U256};
+use reth_primitives::{Chain, ChainSpec, ForkId, Head, H256, MAINNET, MAINNET_GENESIS, U256};
 use reth_rlp::{RlpDecodable, RlpEncodable};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Display};
@@ -42,6 +42,19 @@ impl Status {
     pub fn builder() -> StatusBuilder {
         Default::default()
     }
+
+    /// Returns a builder for the status message of the chain at the given head.
+    ///
+    /// The chain, genesis and fork id are derived from the [ChainSpec], the block hash and total
+    /// difficulty from the head.
+    pub fn spec_builder(spec: &ChainSpec, head: &Head) -> StatusBuilder {
+        Status::builder()
+            .chain(spec.chain)
+            .genesis(spec.genesis_hash())
+            .blockhash(head.hash)
+            .total_difficulty(head.total_difficulty)
+            .forkid(spec.fork_id(head))
+    }
 }
 
 impl Display for Status {
@@ -100,7 +113,7 @@ impl Default for Status {
             total_difficulty: 17_179_869_184u64.into(),
             blockhash: MAINNET_GENESIS,
             genesis: MAINNET_GENESIS,
-            forkid: Hardfork::Frontier.fork_id(),
+            forkid: MAINNET.fork_id(&Head::default()),
         }
     }
 }
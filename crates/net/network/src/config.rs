@@ -6,7 +6,7 @@ use crate::{
     session::SessionsConfig,
 };
 use reth_discv4::{Discv4Config, Discv4ConfigBuilder, DEFAULT_DISCOVERY_PORT};
-use reth_primitives::{Chain, ForkFilter, Hardfork, NodeRecord, PeerId, H256, MAINNET_GENESIS};
+use reth_primitives::{Chain, ChainSpec, ForkFilter, Head, NodeRecord, PeerId, H256, MAINNET};
 use reth_tasks::TaskExecutor;
 use secp256k1::{SecretKey, SECP256K1};
 use std::{
@@ -56,8 +56,7 @@ pub struct NetworkConfig<C> {
     ///
     /// See also <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-2124.md#stale-software-examples>
     ///
-    /// For sync from block `0`, this should be the default chain [`ForkFilter`] beginning at the
-    /// first hardfork, `Frontier` for mainnet.
+    /// By default, this is the [`ForkFilter`] of the chain spec at the configured head.
     pub fork_filter: ForkFilter,
     /// The block importer type.
     pub block_import: Box<dyn BlockImport>,
@@ -117,10 +116,8 @@ pub struct NetworkConfigBuilder<C> {
     peers_config: Option<PeersConfig>,
     /// How to configure the sessions manager
     sessions_config: Option<SessionsConfig>,
-    /// The network's chain id
-    chain: Chain,
-    /// Network genesis hash
-    genesis_hash: H256,
+    /// The chain spec the network follows.
+    chain_spec: ChainSpec,
     /// The block importer type.
     block_import: Box<dyn BlockImport>,
     /// The default mode of the network.
@@ -133,8 +130,8 @@ pub struct NetworkConfigBuilder<C> {
     hello_message: Option<HelloMessage>,
     /// The [`ForkFilter`] to use at launch for authenticating sessions.
     fork_filter: Option<ForkFilter>,
-    /// Head used to start set for the fork filter
-    head: Option<u64>,
+    /// The head of the local chain at launch, used for the fork filter and the `Status`.
+    head: Option<Head>,
 }
 
 // === impl NetworkConfigBuilder ===
@@ -151,8 +148,7 @@ impl<C> NetworkConfigBuilder<C> {
             listener_addr: None,
             peers_config: None,
             sessions_config: None,
-            chain: Chain::Named(reth_primitives::rpc::Chain::Mainnet),
-            genesis_hash: MAINNET_GENESIS,
+            chain_spec: MAINNET.clone(),
             block_import: Box::<ProofOfStakeBlockImport>::default(),
             network_mode: Default::default(),
             executor: None,
@@ -184,9 +180,19 @@ impl<C> NetworkConfigBuilder<C> {
         self
     }
 
-    /// Sets the chain ID.
-    pub fn chain_id<Id: Into<Chain>>(mut self, chain_id: Id) -> Self {
-        self.chain = chain_id.into();
+    /// Sets the chain spec the network follows.
+    ///
+    /// The chain id, the genesis hash and the fork filter are derived from it.
+    pub fn chain_spec(mut self, chain_spec: ChainSpec) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Sets the head of the local chain at launch.
+    ///
+    /// Defaults to the genesis block.
+    pub fn set_head(mut self, head: Head) -> Self {
+        self.head = Some(head);
         self
     }
 
@@ -225,12 +231,6 @@ impl<C> NetworkConfigBuilder<C> {
         self
     }
 
-    /// Sets the genesis hash for the network.
-    pub fn genesis_hash(mut self, genesis_hash: H256) -> Self {
-        self.genesis_hash = genesis_hash;
-        self
-    }
-
     /// Sets the [`BlockImport`] type to configure.
     pub fn block_import<T: BlockImport + 'static>(mut self, block_import: T) -> Self {
         self.block_import = Box::new(block_import);
@@ -273,8 +273,7 @@ impl<C> NetworkConfigBuilder<C> {
             listener_addr,
             peers_config,
             sessions_config,
-            chain,
-            genesis_hash,
+            chain_spec,
             block_import,
             network_mode,
             executor,
@@ -292,13 +291,17 @@ impl<C> NetworkConfigBuilder<C> {
             hello_message.unwrap_or_else(|| HelloMessage::builder(peer_id).build());
         hello_message.port = listener_addr.port();
 
-        // get the fork filter
-        let fork_filter = fork_filter.unwrap_or_else(|| {
-            let head = head.unwrap_or_default();
-            // TODO(mattsse): this should be chain agnostic: <https://github.com/paradigmxyz/reth/issues/485>
-            ForkFilter::new(head, genesis_hash, Hardfork::all_forks())
+        let head = head.unwrap_or_else(|| Head {
+            hash: chain_spec.genesis_hash(),
+            total_difficulty: chain_spec.genesis.difficulty,
+            timestamp: chain_spec.genesis.timestamp,
+            ..Default::default()
         });
 
+        // get the fork filter
+        let fork_filter = fork_filter.unwrap_or_else(|| chain_spec.fork_filter(head));
+        let status = status.unwrap_or_else(|| Status::spec_builder(&chain_spec, &head).build());
+
         NetworkConfig {
             client,
             secret_key,
@@ -310,12 +313,12 @@ impl<C> NetworkConfigBuilder<C> {
             listener_addr,
             peers_config: peers_config.unwrap_or_default(),
             sessions_config: sessions_config.unwrap_or_default(),
-            chain,
-            genesis_hash,
+            chain: chain_spec.chain,
+            genesis_hash: chain_spec.genesis_hash(),
             block_import,
             network_mode,
             executor,
-            status: status.unwrap_or_default(),
+            status,
             hello_message,
             fork_filter,
         }
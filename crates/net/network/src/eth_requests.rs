@@ -167,7 +167,11 @@ where
 
         for hash in request.0 {
             if let Some(block) = self.client.block(hash.into()).unwrap_or_default() {
-                let body = BlockBody { transactions: block.body, ommers: block.ommers };
+                let body = BlockBody {
+                    transactions: block.body,
+                    ommers: block.ommers,
+                    withdrawals: block.withdrawals,
+                };
 
                 bodies.push(body);
 
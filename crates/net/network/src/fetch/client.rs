@@ -20,6 +20,8 @@ use tokio::sync::{mpsc::UnboundedSender, oneshot};
 pub struct FetchClient {
     /// Sender half of the request channel.
     pub(crate) request_tx: UnboundedSender<DownloadRequest>,
+    /// Sender half of the channel for reporting peers that sent invalid responses.
+    pub(crate) bad_messages_tx: UnboundedSender<PeerId>,
     /// The handle to the peers
     pub(crate) peers_handle: PeersHandle,
 }
@@ -27,6 +29,7 @@ pub struct FetchClient {
 
 impl DownloadClient for FetchClient {
     fn report_bad_message(&self, peer_id: PeerId) {
+        let _ = self.bad_messages_tx.send(peer_id);
         self.peers_handle.reputation_change(peer_id, ReputationChangeKind::BadMessage);
     }
 }
@@ -0,0 +1,20 @@
+//! Fetcher metrics.
+
+use metrics::{Counter, Histogram};
+use reth_metrics_derive::Metrics;
+
+/// Metrics of the requests the [`FetchClient`](super::FetchClient) sends to peers
+#[derive(Metrics)]
+#[metrics(scope = "network_fetch")]
+pub struct FetchMetrics {
+    /// Number of requests sent to peers
+    pub(crate) requests: Counter,
+    /// Duration between sending a request and receiving its response in seconds
+    pub(crate) response_latency: Histogram,
+    /// Number of requests that timed out
+    pub(crate) timeouts: Counter,
+    /// Number of requests that failed for other reasons than a timeout
+    pub(crate) failed_responses: Counter,
+    /// Number of responses that were reported invalid by the downloaders
+    pub(crate) bad_messages: Counter,
+}
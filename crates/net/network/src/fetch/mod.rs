@@ -11,20 +11,27 @@ use reth_primitives::{Header, PeerId, H256};
 use std::{
     collections::{HashMap, VecDeque},
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 mod client;
+mod metrics;
 use crate::peers::ReputationChangeKind;
 pub use client::FetchClient;
+pub use self::metrics::FetchMetrics;
+
+/// How much a failed request weighs compared to a successful one when scoring a peer.
+const FAILURE_PENALTY: u128 = 10;
 
 /// Manages data fetching operations.
 ///
 /// This type is hooked into the staged sync pipeline and delegates download request to available
 /// peers and sends the response once ready.
 ///
-/// This type maintains a list of connected peers that are available for requests.
+/// This type maintains a list of connected peers that are available for requests. Requests are
+/// sent to the idle peer that responded the best so far, see [`PeerQuality`].
 // ANCHOR: struct-StateFetcher
 pub struct StateFetcher {
     /// Currently active [`GetBlockHeaders`] requests
@@ -43,6 +50,12 @@ pub struct StateFetcher {
     download_requests_rx: UnboundedReceiverStream<DownloadRequest>,
     /// Sender for download requests, used to detach a [`FetchClient`]
     download_requests_tx: UnboundedSender<DownloadRequest>,
+    /// Receiver for the peers whose responses were reported invalid by a [`FetchClient`]
+    bad_messages_rx: UnboundedReceiverStream<PeerId>,
+    /// Sender for bad message reports, used to detach a [`FetchClient`]
+    bad_messages_tx: UnboundedSender<PeerId>,
+    /// Metrics of the requests
+    metrics: FetchMetrics,
 }
 // ANCHOR_END: struct-StateFetcher
 
@@ -51,6 +64,7 @@ pub struct StateFetcher {
 impl StateFetcher {
     pub(crate) fn new(peers_handle: PeersHandle) -> Self {
         let (download_requests_tx, download_requests_rx) = mpsc::unbounded_channel();
+        let (bad_messages_tx, bad_messages_rx) = mpsc::unbounded_channel();
         Self {
             inflight_headers_requests: Default::default(),
             inflight_bodies_requests: Default::default(),
@@ -59,12 +73,18 @@ impl StateFetcher {
             queued_requests: Default::default(),
             download_requests_rx: UnboundedReceiverStream::new(download_requests_rx),
             download_requests_tx,
+            bad_messages_rx: UnboundedReceiverStream::new(bad_messages_rx),
+            bad_messages_tx,
+            metrics: FetchMetrics::default(),
         }
     }
 
     /// Invoked when connected to a new peer.
     pub(crate) fn new_active_peer(&mut self, peer_id: PeerId, best_hash: H256, best_number: u64) {
-        self.peers.insert(peer_id, Peer { state: PeerState::Idle, best_hash, best_number });
+        self.peers.insert(
+            peer_id,
+            Peer { state: PeerState::Idle, best_hash, best_number, quality: Default::default() },
+        );
     }
 
     /// Removes the peer from the peer list, after which it is no longer available for future
@@ -104,9 +124,47 @@ impl StateFetcher {
         }
     }
 
-    /// Returns the _next_ idle peer that's ready to accept a request.
+    /// Returns the idle peer with the best [`PeerQuality`] that's ready to accept a request.
     fn next_peer(&mut self) -> Option<(&PeerId, &mut Peer)> {
-        self.peers.iter_mut().find(|(_, peer)| peer.state.is_idle())
+        self.peers
+            .iter_mut()
+            .filter(|(_, peer)| peer.state.is_idle())
+            .min_by_key(|(_, peer)| peer.quality.score())
+    }
+
+    /// Invoked when a [`FetchClient`] reports a response of the peer as invalid.
+    fn on_bad_message(&mut self, peer_id: PeerId) {
+        self.metrics.bad_messages.increment(1);
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.quality.on_bad_response();
+        }
+    }
+
+    /// Records the outcome of a request that was sent at `started` to the peer.
+    fn on_request_finished<T>(
+        &mut self,
+        peer_id: PeerId,
+        started: Option<Instant>,
+        res: &RequestResult<T>,
+    ) {
+        let latency = started.map(|started| started.elapsed()).unwrap_or_default();
+        self.metrics.response_latency.record(latency.as_secs_f64());
+        match res {
+            Ok(_) => {}
+            Err(RequestError::Timeout) => self.metrics.timeouts.increment(1),
+            Err(_) => self.metrics.failed_responses.increment(1),
+        }
+
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            match res {
+                Ok(_) => peer.quality.on_response(latency),
+                Err(RequestError::Timeout) => peer.quality.on_timeout(latency),
+                Err(_) => {
+                    peer.quality.on_response(latency);
+                    peer.quality.on_bad_response();
+                }
+            }
+        }
     }
 
     /// Returns the next action to return
@@ -130,6 +188,11 @@ impl StateFetcher {
 
     /// Advance the state the syncer
     pub(crate) fn poll(&mut self, cx: &mut Context<'_>) -> Poll<FetchAction> {
+        // apply the reports of bad messages first, so they affect the choice of the next peer
+        while let Poll::Ready(Some(peer_id)) = self.bad_messages_rx.poll_next_unpin(cx) {
+            self.on_bad_message(peer_id);
+        }
+
         // drain buffered actions first
         loop {
             let no_peers_available = match self.poll_action() {
@@ -165,10 +228,12 @@ impl StateFetcher {
         if let Some(peer) = self.peers.get_mut(&peer_id) {
             peer.state = req.peer_state();
         }
+        self.metrics.requests.increment(1);
+        let started = Instant::now();
 
         match req {
             DownloadRequest::GetBlockHeaders { request, response } => {
-                let inflight = Request { request: request.clone(), response };
+                let inflight = Request { request: request.clone(), response, started };
                 self.inflight_headers_requests.insert(peer_id, inflight);
                 let HeadersRequest { start, limit, direction } = request;
                 BlockRequest::GetBlockHeaders(GetBlockHeaders {
@@ -179,7 +244,7 @@ impl StateFetcher {
                 })
             }
             DownloadRequest::GetBlockBodies { request, response } => {
-                let inflight = Request { request: request.clone(), response };
+                let inflight = Request { request: request.clone(), response, started };
                 self.inflight_bodies_requests.insert(peer_id, inflight);
                 BlockRequest::GetBlockBodies(GetBlockBodies(request))
             }
@@ -202,7 +267,9 @@ impl StateFetcher {
         res: RequestResult<Vec<Header>>,
     ) -> Option<BlockResponseOutcome> {
         let is_error = res.is_err();
-        if let Some(resp) = self.inflight_headers_requests.remove(&peer_id) {
+        let resp = self.inflight_headers_requests.remove(&peer_id);
+        self.on_request_finished(peer_id, resp.as_ref().map(|resp| resp.started), &res);
+        if let Some(resp) = resp {
             let _ = resp.response.send(res.map(|h| (peer_id, h).into()));
         }
 
@@ -230,7 +297,9 @@ impl StateFetcher {
         peer_id: PeerId,
         res: RequestResult<Vec<BlockBody>>,
     ) -> Option<BlockResponseOutcome> {
-        if let Some(resp) = self.inflight_bodies_requests.remove(&peer_id) {
+        let resp = self.inflight_bodies_requests.remove(&peer_id);
+        self.on_request_finished(peer_id, resp.as_ref().map(|resp| resp.started), &res);
+        if let Some(resp) = resp {
             let _ = resp.response.send(res.map(|b| (peer_id, b).into()));
         }
         if let Some(peer) = self.peers.get_mut(&peer_id) {
@@ -245,6 +314,7 @@ impl StateFetcher {
     pub(crate) fn client(&self) -> FetchClient {
         FetchClient {
             request_tx: self.download_requests_tx.clone(),
+            bad_messages_tx: self.bad_messages_tx.clone(),
             peers_handle: self.peers_handle.clone(),
         }
     }
@@ -265,6 +335,65 @@ struct Peer {
     best_hash: H256,
     /// Tracks the best number of the peer.
     best_number: u64,
+    /// How well the peer responded to requests so far.
+    quality: PeerQuality,
+}
+
+/// Tracks how well a peer responds to requests.
+///
+/// Peers are ranked by their average response latency, which is inflated by the share of their
+/// requests that timed out or were answered with an invalid response. Peers without any responses
+/// yet are ranked first, so every peer gets a chance to prove itself.
+#[derive(Debug, Default)]
+struct PeerQuality {
+    /// Moving average of the response latency.
+    latency: Duration,
+    /// Number of requests that were answered.
+    responses: u64,
+    /// Number of requests that timed out.
+    timeouts: u64,
+    /// Number of responses that were erroneous or reported invalid.
+    bad_responses: u64,
+}
+
+// === impl PeerQuality ===
+
+impl PeerQuality {
+    /// Records a response received after `latency`.
+    fn on_response(&mut self, latency: Duration) {
+        self.record_latency(latency);
+        self.responses += 1;
+    }
+
+    /// Records a request that timed out after `latency`.
+    fn on_timeout(&mut self, latency: Duration) {
+        self.record_latency(latency);
+        self.timeouts += 1;
+    }
+
+    /// Records an invalid response.
+    fn on_bad_response(&mut self) {
+        self.bad_responses += 1;
+    }
+
+    fn record_latency(&mut self, latency: Duration) {
+        if self.responses + self.timeouts == 0 {
+            self.latency = latency;
+        } else {
+            self.latency = (self.latency * 4 + latency) / 5;
+        }
+    }
+
+    /// The score of the peer, lower is better.
+    fn score(&self) -> u128 {
+        let requests = (self.responses + self.timeouts) as u128;
+        if requests == 0 {
+            return 0
+        }
+        // bad responses are also counted as responses
+        let failures = (self.timeouts + self.bad_responses) as u128;
+        self.latency.as_micros() * (requests + FAILURE_PENALTY * failures) / requests
+    }
 }
 
 /// Tracks the state of an individual peer
@@ -309,6 +438,8 @@ struct Request<Req, Resp> {
     #[allow(unused)]
     request: Req,
     response: oneshot::Sender<Resp>,
+    /// When the request was sent to the peer.
+    started: Instant,
 }
 
 /// Requests that can be sent to the Syncer from a [`FetchClient`]
@@ -364,6 +495,7 @@ mod tests {
     use crate::{peers::PeersManager, PeersConfig};
 
     use super::*;
+    use reth_interfaces::p2p::downloader::DownloadClient;
     use std::future::poll_fn;
 
     #[tokio::test(flavor = "multi_thread")]
@@ -383,4 +515,42 @@ mod tests {
         })
         .await;
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_next_peer_by_quality() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle());
+
+        let (fast, slow, new) = (PeerId::random(), PeerId::random(), PeerId::random());
+        for peer_id in [fast, slow] {
+            fetcher.new_active_peer(peer_id, H256::random(), 1);
+        }
+        fetcher.peers.get_mut(&fast).unwrap().quality.on_response(Duration::from_millis(10));
+        fetcher.peers.get_mut(&slow).unwrap().quality.on_response(Duration::from_millis(100));
+        assert_eq!(fetcher.next_peer().map(|(peer_id, _)| *peer_id), Some(fast));
+
+        // timeouts demote the fast peer
+        fetcher.peers.get_mut(&fast).unwrap().quality.on_timeout(Duration::from_millis(100));
+        assert_eq!(fetcher.next_peer().map(|(peer_id, _)| *peer_id), Some(slow));
+
+        // peers without responses are tried first
+        fetcher.new_active_peer(new, H256::random(), 1);
+        assert_eq!(fetcher.next_peer().map(|(peer_id, _)| *peer_id), Some(new));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_report_bad_message() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle());
+        let peer_id = PeerId::random();
+        fetcher.new_active_peer(peer_id, H256::random(), 1);
+
+        fetcher.client().report_bad_message(peer_id);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(fetcher.peers[&peer_id].quality.bad_responses, 1);
+    }
 }
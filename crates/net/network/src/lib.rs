@@ -129,8 +129,9 @@ pub mod transactions;
 
 pub use builder::NetworkBuilder;
 pub use config::{NetworkConfig, NetworkConfigBuilder};
-pub use fetch::FetchClient;
+pub use fetch::{FetchClient, FetchMetrics};
 pub use manager::{NetworkEvent, NetworkManager};
 pub use message::PeerRequest;
 pub use network::NetworkHandle;
 pub use peers::PeersConfig;
+pub use session::{SessionLimits, SessionsConfig};
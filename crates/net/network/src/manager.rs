@@ -43,7 +43,7 @@ use std::{
     net::SocketAddr,
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     task::{Context, Poll},
@@ -103,6 +103,10 @@ pub struct NetworkManager<C> {
     /// This is updated via internal events and shared via `Arc` with the [`NetworkHandle`]
     /// Updated by the `NetworkWorker` and loaded by the `NetworkService`.
     num_active_peers: Arc<AtomicUsize>,
+    /// Tracks the highest block number announced by any peer.
+    ///
+    /// This is shared via `Arc` with the [`NetworkHandle`].
+    highest_observed_block: Arc<AtomicU64>,
 }
 // ANCHOR_END: struct-NetworkManager
 
@@ -185,8 +189,10 @@ where
         let (to_manager_tx, from_handle_rx) = mpsc::unbounded_channel();
 
         let num_active_peers = Arc::new(AtomicUsize::new(0));
+        let highest_observed_block = Arc::new(AtomicU64::new(0));
         let handle = NetworkHandle::new(
             Arc::clone(&num_active_peers),
+            Arc::clone(&highest_observed_block),
             listener_address,
             to_manager_tx,
             local_peer_id,
@@ -203,6 +209,7 @@ where
             to_transactions_manager: None,
             to_eth_request_handler: None,
             num_active_peers,
+            highest_observed_block,
         })
     }
 
@@ -352,16 +359,23 @@ where
         }
     }
 
+    /// Keeps track of the highest block number announced by the network.
+    fn on_observed_block(&self, number: u64) {
+        self.highest_observed_block.fetch_max(number, Ordering::Relaxed);
+    }
+
     /// Invoked after a `NewBlock` message from the peer was validated
     fn on_block_import_result(&mut self, outcome: BlockImportOutcome) {
         let BlockImportOutcome { peer, result } = outcome;
         match result {
             Ok(validated_block) => match validated_block {
                 BlockValidation::ValidHeader { block } => {
+                    self.on_observed_block(block.number());
                     self.swarm.state_mut().update_peer_block(&peer, block.hash, block.number());
                     self.swarm.state_mut().announce_new_block(block);
                 }
                 BlockValidation::ValidBlock { block } => {
+                    self.on_observed_block(block.number());
                     self.swarm.state_mut().announce_new_block_hash(block);
                 }
             },
@@ -399,6 +413,9 @@ where
         match msg {
             PeerMessage::NewBlockHashes(hashes) => {
                 self.within_pow_or_disconnect(peer_id, |this| {
+                    if let Some(number) = hashes.0.iter().map(|block| block.number).max() {
+                        this.on_observed_block(number);
+                    }
                     // update peer's state, to track what blocks this peer has seen
                     this.swarm.state_mut().on_new_block_hashes(peer_id, hashes.0)
                 })
@@ -470,10 +487,8 @@ where
             NetworkHandleMessage::FetchClient(tx) => {
                 let _ = tx.send(self.fetch_client());
             }
-            NetworkHandleMessage::StatusUpdate { height, hash, total_difficulty } => {
-                if let Some(transition) =
-                    self.swarm.sessions_mut().on_status_update(height, hash, total_difficulty)
-                {
+            NetworkHandleMessage::StatusUpdate { head } => {
+                if let Some(transition) = self.swarm.sessions_mut().on_status_update(head) {
                     self.swarm.state_mut().update_fork_id(transition.current);
                 }
             }
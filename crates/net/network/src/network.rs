@@ -8,11 +8,11 @@ use crate::{
 use parking_lot::Mutex;
 use reth_eth_wire::{DisconnectReason, NewBlock, NewPooledTransactionHashes, SharedTransactions};
 use reth_interfaces::p2p::headers::client::StatusUpdater;
-use reth_primitives::{PeerId, TransactionSigned, TxHash, H256, U256};
+use reth_primitives::{Head, PeerId, TransactionSigned, TxHash, H256};
 use std::{
     net::SocketAddr,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
 };
@@ -34,6 +34,7 @@ impl NetworkHandle {
     /// Creates a single new instance.
     pub(crate) fn new(
         num_active_peers: Arc<AtomicUsize>,
+        highest_observed_block: Arc<AtomicU64>,
         listener_address: Arc<Mutex<SocketAddr>>,
         to_manager_tx: UnboundedSender<NetworkHandleMessage>,
         local_peer_id: PeerId,
@@ -42,6 +43,7 @@ impl NetworkHandle {
     ) -> Self {
         let inner = NetworkInner {
             num_active_peers,
+            highest_observed_block,
             to_manager_tx,
             listener_address,
             local_peer_id,
@@ -56,6 +58,13 @@ impl NetworkHandle {
         self.inner.num_active_peers.load(Ordering::Relaxed)
     }
 
+    /// Returns the highest block number that was announced by any peer.
+    ///
+    /// Note: this is only updated by block announcements, which are not sent after the merge.
+    pub fn highest_observed_block(&self) -> u64 {
+        self.inner.highest_observed_block.load(Ordering::Relaxed)
+    }
+
     /// Returns the [`SocketAddr`] that listens for incoming connections.
     pub fn local_addr(&self) -> SocketAddr {
         *self.inner.listener_address.lock()
@@ -104,8 +113,8 @@ impl NetworkHandle {
     }
 
     /// Update the status of the node.
-    pub fn update_status(&self, height: u64, hash: H256, total_difficulty: U256) {
-        self.send_message(NetworkHandleMessage::StatusUpdate { height, hash, total_difficulty });
+    pub fn update_status(&self, head: Head) {
+        self.send_message(NetworkHandleMessage::StatusUpdate { head });
     }
 
     /// Announce a block over devp2p
@@ -162,8 +171,8 @@ impl NetworkHandle {
 
 impl StatusUpdater for NetworkHandle {
     /// Update the status of the node.
-    fn update_status(&self, height: u64, hash: H256, total_difficulty: U256) {
-        self.send_message(NetworkHandleMessage::StatusUpdate { height, hash, total_difficulty });
+    fn update_status(&self, head: Head) {
+        self.send_message(NetworkHandleMessage::StatusUpdate { head });
     }
 }
 
@@ -172,6 +181,8 @@ impl StatusUpdater for NetworkHandle {
 struct NetworkInner {
     /// Number of active peer sessions the node's currently handling.
     num_active_peers: Arc<AtomicUsize>,
+    /// The highest block number announced by any peer.
+    highest_observed_block: Arc<AtomicU64>,
     /// Sender half of the message channel to the [`NetworkManager`].
     to_manager_tx: UnboundedSender<NetworkHandleMessage>,
     /// The local address that accepts incoming connections.
@@ -212,5 +223,5 @@ pub(crate) enum NetworkHandleMessage {
     /// Returns the client that can be used to interact with the network.
     FetchClient(oneshot::Sender<FetchClient>),
     /// Apply a status update.
-    StatusUpdate { height: u64, hash: H256, total_difficulty: U256 },
+    StatusUpdate { head: Head },
 }
@@ -19,7 +19,7 @@ use reth_eth_wire::{
     errors::EthStreamError,
     DisconnectReason, HelloMessage, Status, UnauthedEthStream, UnauthedP2PStream,
 };
-use reth_primitives::{ForkFilter, ForkId, ForkTransition, PeerId, H256, U256};
+use reth_primitives::{ForkFilter, ForkId, ForkTransition, Head, PeerId};
 use reth_tasks::TaskExecutor;
 use secp256k1::SecretKey;
 use std::{
@@ -32,6 +32,7 @@ use std::{
 };
 use tokio::{
     net::TcpStream,
+    runtime::Handle,
     sync::{mpsc, oneshot},
 };
 use tokio_stream::wrappers::ReceiverStream;
@@ -40,7 +41,7 @@ use tracing::{instrument, trace, warn};
 mod active;
 mod config;
 mod handle;
-pub use config::SessionsConfig;
+pub use config::{SessionLimits, SessionsConfig};
 
 /// Internal identifier for active sessions.
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
@@ -138,14 +139,16 @@ impl SessionManager {
     }
 
     /// Spawns the given future onto a new task that is tracked in the `spawned_tasks` [`JoinSet`].
-    fn spawn<F>(&self, f: F)
+    ///
+    /// The name identifies the task in tokio-console.
+    fn spawn<F>(&self, name: &str, f: F)
     where
         F: Future<Output = ()> + Send + 'static,
     {
         if let Some(ref executor) = self.executor {
-            executor.spawn(async move { f.await })
+            executor.spawn_named(name, f)
         } else {
-            tokio::task::spawn(async move { f.await });
+            reth_tasks::spawn_named(&Handle::current(), name, f);
         }
     }
 
@@ -153,15 +156,14 @@ impl SessionManager {
     ///
     /// If the updated activated another fork, this will return a [`ForkTransition`] and updates the
     /// active [`ForkId`](reth_primitives::ForkId). See also [`ForkFilter::set_head`].
-    pub(crate) fn on_status_update(
-        &mut self,
-        height: u64,
-        hash: H256,
-        total_difficulty: U256,
-    ) -> Option<ForkTransition> {
-        self.status.blockhash = hash;
-        self.status.total_difficulty = total_difficulty;
-        self.fork_filter.set_head(height)
+    pub(crate) fn on_status_update(&mut self, head: Head) -> Option<ForkTransition> {
+        self.status.blockhash = head.hash;
+        self.status.total_difficulty = head.total_difficulty;
+        let transition = self.fork_filter.set_head(head);
+        if let Some(transition) = &transition {
+            self.status.forkid = transition.current;
+        }
+        transition
     }
 
     /// An incoming TCP connection was received. This starts the authentication process to turn this
@@ -186,17 +188,20 @@ impl SessionManager {
 
         let (disconnect_tx, disconnect_rx) = oneshot::channel();
         let pending_events = self.pending_sessions_tx.clone();
-        self.spawn(start_pending_incoming_session(
-            disconnect_rx,
-            session_id,
-            stream,
-            pending_events,
-            remote_addr,
-            self.secret_key,
-            self.hello_message.clone(),
-            self.status,
-            self.fork_filter.clone(),
-        ));
+        self.spawn(
+            "pending incoming session",
+            start_pending_incoming_session(
+                disconnect_rx,
+                session_id,
+                stream,
+                pending_events,
+                remote_addr,
+                self.secret_key,
+                self.hello_message.clone(),
+                self.status,
+                self.fork_filter.clone(),
+            ),
+        );
 
         let handle =
             PendingSessionHandle { _disconnect_tx: disconnect_tx, direction: Direction::Incoming };
@@ -210,17 +215,20 @@ impl SessionManager {
         let session_id = self.next_id();
         let (disconnect_tx, disconnect_rx) = oneshot::channel();
         let pending_events = self.pending_sessions_tx.clone();
-        self.spawn(start_pending_outbound_session(
-            disconnect_rx,
-            pending_events,
-            session_id,
-            remote_addr,
-            remote_peer_id,
-            self.secret_key,
-            self.hello_message.clone(),
-            self.status,
-            self.fork_filter.clone(),
-        ));
+        self.spawn(
+            "pending outbound session",
+            start_pending_outbound_session(
+                disconnect_rx,
+                pending_events,
+                session_id,
+                remote_addr,
+                remote_peer_id,
+                self.secret_key,
+                self.hello_message.clone(),
+                self.status,
+                self.fork_filter.clone(),
+            ),
+        );
 
         let handle = PendingSessionHandle {
             _disconnect_tx: disconnect_tx,
@@ -338,7 +346,7 @@ impl SessionManager {
                         "already connected"
                     );
 
-                    self.spawn(async move {
+                    self.spawn("session disconnect", async move {
                         // send a disconnect message
                         let _ =
                             conn.into_inner().disconnect(DisconnectReason::AlreadyConnected).await;
@@ -374,7 +382,7 @@ impl SessionManager {
                     request_timeout: self.request_timeout,
                 };
 
-                self.spawn(session);
+                self.spawn("active session", session);
 
                 let handle = ActiveSessionHandle {
                     direction,
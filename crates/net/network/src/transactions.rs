@@ -39,6 +39,7 @@ pub type PoolImportFuture = Pin<Box<dyn Future<Output = PoolResult<TxHash>> + Se
 
 /// Api to interact with [`TransactionsManager`] task.
 // ANCHOR: struct-TransactionsHandle
+#[derive(Debug, Clone)]
 pub struct TransactionsHandle {
     /// Command channel to the [`TransactionsManager`]
     manager_tx: mpsc::UnboundedSender<TransactionsCommand>,
@@ -56,6 +57,14 @@ impl TransactionsHandle {
     pub fn propagate(&self, hash: TxHash) {
         self.send(TransactionsCommand::PropagateHash(hash))
     }
+
+    /// Propagates the full transaction that belongs to the hash to _all_ connected peers.
+    ///
+    /// This is intended for local transactions, which should reach the network as fast as
+    /// possible.
+    pub fn propagate_local(&self, hash: TxHash) {
+        self.send(TransactionsCommand::PropagateLocal(hash))
+    }
 }
 
 /// Manages transactions on top of the p2p network.
@@ -187,8 +196,8 @@ where
     /// transactions to a fraction of peers usually ensures that all nodes receive the transaction
     /// and won't need to request it.
     // ANCHOR: fn-on_new_transactions-propagate_transactions
-    fn on_new_transactions(&mut self, hashes: impl IntoIterator<Item = TxHash>) {
-        trace!(target: "net::tx", "Start propagating transactions");
+    fn on_new_transactions(&mut self, hashes: impl IntoIterator<Item = TxHash>, local: bool) {
+        trace!(target: "net::tx", local, "Start propagating transactions");
 
         let propagated = self.propagate_transactions(
             self.pool
@@ -198,21 +207,30 @@ where
                     (*tx.hash(), Arc::new(tx.transaction.to_recovered_transaction().into_signed()))
                 })
                 .collect(),
+            local,
         );
 
         // notify pool so events get fired
         self.pool.on_propagated(propagated);
     }
 
+    /// Propagates the transactions to the connected peers.
+    ///
+    /// If `full_to_all` is set, the full transactions are sent to all peers instead of a fraction.
     fn propagate_transactions(
         &mut self,
         txs: Vec<(TxHash, Arc<TransactionSigned>)>,
+        full_to_all: bool,
     ) -> PropagatedTransactions {
         let mut propagated = PropagatedTransactions::default();
 
         // send full transactions to a fraction fo the connected peers (square root of the total
         // number of connected peers)
-        let max_num_full = (self.peers.len() as f64).sqrt() as usize + 1;
+        let max_num_full = if full_to_all {
+            self.peers.len()
+        } else {
+            (self.peers.len() as f64).sqrt() as usize + 1
+        };
 
         // Note: Assuming ~random~ order due to random state of the peers map hasher
         for (idx, (peer_id, peer)) in self.peers.iter_mut().enumerate() {
@@ -290,7 +308,10 @@ where
     fn on_command(&mut self, cmd: TransactionsCommand) {
         match cmd {
             TransactionsCommand::PropagateHash(hash) => {
-                self.on_new_transactions(std::iter::once(hash))
+                self.on_new_transactions(std::iter::once(hash), false)
+            }
+            TransactionsCommand::PropagateLocal(hash) => {
+                self.on_new_transactions(std::iter::once(hash), true)
             }
         }
     }
@@ -457,7 +478,7 @@ where
             new_txs.push(hash);
         }
         if !new_txs.is_empty() {
-            this.on_new_transactions(new_txs);
+            this.on_new_transactions(new_txs, false);
         }
 
         // all channels are fully drained and import futures pending
@@ -488,8 +509,10 @@ struct Peer {
 
 /// Commands to send to the [`TransactionManager`]
 // ANCHOR: enum-TransactionsCommand
+#[derive(Debug)]
 enum TransactionsCommand {
     PropagateHash(H256),
+    PropagateLocal(H256),
 }
 // ANCHOR_END: enum-TransactionsCommand
 
@@ -69,7 +69,11 @@ async fn test_get_body() {
 
         let blocks = res.unwrap().1;
         assert_eq!(blocks.len(), 1);
-        let expected = BlockBody { transactions: block.body, ommers: block.ommers };
+        let expected = BlockBody {
+            transactions: block.body,
+            ommers: block.ommers,
+            withdrawals: block.withdrawals,
+        };
         assert_eq!(blocks[0], expected);
     }
 }
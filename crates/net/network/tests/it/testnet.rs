@@ -10,7 +10,7 @@ use reth_network::{
 };
 use reth_primitives::{
     rpc::{BlockId, BlockNumber},
-    Block, BlockHash, Header, PeerId, H256, U256,
+    Block, BlockHash, BlockHashOrNumber, Header, PeerId, Withdrawal, H256, U256,
 };
 use reth_provider::{test_utils::TestApi, BlockProvider, ChainInfo, HeaderProvider};
 use secp256k1::SecretKey;
@@ -388,6 +388,17 @@ impl BlockProvider for MockEthProvider {
         }
     }
 
+    fn ommers(&self, _block: BlockHashOrNumber) -> reth_interfaces::Result<Option<Vec<Header>>> {
+        todo!()
+    }
+
+    fn withdrawals(
+        &self,
+        _block: BlockHashOrNumber,
+    ) -> reth_interfaces::Result<Option<Vec<Withdrawal>>> {
+        todo!()
+    }
+
     fn block_number(
         &self,
         hash: H256,
@@ -14,11 +14,18 @@ pub trait AdminApi {
     #[method(name = "admin_removePeer")]
     async fn remove_peer(&self, record: String) -> Result<bool>;
 
+    /// Replaces the log filter of the node with the given directives, like
+    /// `net=trace,stages=debug`.
+    ///
+    /// Returns true if the filter was applied.
+    #[method(name = "admin_setLogFilter")]
+    async fn set_log_filter(&self, filter: String) -> Result<bool>;
+
     /// Creates an RPC subscription which serves events received from the network.
     #[subscription(
         name = "admin_peerEvents",
         unsubscribe = "admin_peerEvents_unsubscribe",
         item = String
     )]
-    fn subscribe(&self);
+    async fn subscribe(&self);
 }
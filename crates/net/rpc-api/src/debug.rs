@@ -3,7 +3,10 @@ use reth_primitives::{
     rpc::{BlockId, Bytes},
     H256,
 };
-use reth_rpc_types::RichBlock;
+use reth_rpc_types::{
+    trace::geth::{GethDebugTracingOptions, GethTraceFrame},
+    CallRequest, RichBlock,
+};
 
 /// Debug rpc interface.
 #[cfg_attr(not(feature = "client"), rpc(server))]
@@ -28,4 +31,26 @@ pub trait DebugApi {
     /// Returns an array of recent bad blocks that the client has seen on the network.
     #[method(name = "debug_getBadBlocks")]
     async fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+    /// Re-executes the transaction with the given hash and returns its trace.
+    ///
+    /// The trace format depends on the selected tracer in the `opts`, if no tracer is selected the
+    /// default struct logger is used.
+    #[method(name = "debug_traceTransaction")]
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: H256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame>;
+
+    /// Executes the given call on top of the state of the given block and returns its trace.
+    ///
+    /// Same as `debug_traceTransaction` but the call is not required to be mined.
+    #[method(name = "debug_traceCall")]
+    async fn debug_trace_call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame>;
 }
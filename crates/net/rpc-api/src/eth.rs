@@ -1,11 +1,12 @@
 use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
 use reth_primitives::{
-    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId},
+    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId, BlockNumber as RpcBlockNumber},
     Address, BlockNumber, Bytes, H256, H64, U256, U64,
 };
 use reth_rpc_types::{
-    CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock, SyncStatus,
-    Transaction, TransactionReceipt, TransactionRequest, Work,
+    BlockOverrides, CallRequest, EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Index,
+    RichBlock, StateOverride, SyncStatus, Transaction, TransactionConditional, TransactionReceipt,
+    TransactionRequest, Work,
 };
 
 /// Eth rpc interface: <https://ethereum.github.io/execution-apis/api-documentation/>
@@ -43,7 +44,11 @@ pub trait EthApi {
 
     /// Returns information about a block by number.
     #[method(name = "eth_getBlockByNumber")]
-    async fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>>;
+    async fn block_by_number(
+        &self,
+        number: RpcBlockNumber,
+        full: bool,
+    ) -> Result<Option<RichBlock>>;
 
     /// Returns the number of transactions in a block from a block matching the given block hash.
     #[method(name = "eth_getBlockTransactionCountByHash")]
@@ -101,6 +106,10 @@ pub trait EthApi {
     #[method(name = "eth_getTransactionReceipt")]
     async fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>>;
 
+    /// Returns the receipts of all transactions of the block.
+    #[method(name = "eth_getBlockReceipts")]
+    async fn block_receipts(&self, block_id: BlockId) -> Result<Option<Vec<TransactionReceipt>>>;
+
     /// Returns the balance of the account of given address.
     #[method(name = "eth_getBalance")]
     async fn balance(&self, address: Address, block_number: Option<BlockId>) -> Result<U256>;
@@ -127,8 +136,30 @@ pub trait EthApi {
     async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> Result<Bytes>;
 
     /// Executes a new message call immediately without creating a transaction on the block chain.
+    ///
+    /// The state and block overrides are applied on top of the state of the given block before
+    /// the call.
     #[method(name = "eth_call")]
-    async fn call(&self, request: CallRequest, block_number: Option<BlockId>) -> Result<Bytes>;
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<Box<BlockOverrides>>,
+    ) -> Result<Bytes>;
+
+    /// Simulates an ordered list of calls on top of the state of the given block.
+    ///
+    /// Every call is executed on top of the state changes of the preceding calls. The state and
+    /// block overrides are applied before the first call. Returns the result of every call.
+    #[method(name = "eth_callMany")]
+    async fn call_many(
+        &self,
+        calls: Vec<CallRequest>,
+        block_number: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<Vec<EthCallResponse>>;
 
     /// Generates an access list for a transaction.
     ///
@@ -213,6 +244,15 @@ pub trait EthApi {
     #[method(name = "eth_sendRawTransaction")]
     async fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256>;
 
+    /// Sends signed transaction that is only accepted if the given conditions hold, returning its
+    /// hash.
+    #[method(name = "eth_sendRawTransactionConditional")]
+    async fn send_raw_transaction_conditional(
+        &self,
+        bytes: Bytes,
+        conditional: TransactionConditional,
+    ) -> Result<H256>;
+
     /// Returns an Ethereum specific signature with: sign(keccak256("\x19Ethereum Signed Message:\n"
     /// + len(message) + message))).
     #[method(name = "eth_sign")]
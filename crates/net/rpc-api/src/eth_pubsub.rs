@@ -3,6 +3,7 @@ use reth_rpc_types::pubsub::{Kind, Params};
 
 /// Ethereum pub-sub rpc interface.
 #[rpc(server)]
+#[async_trait::async_trait]
 pub trait EthPubSubApi {
     /// Create an ethereum subscription.
     #[subscription(
@@ -10,5 +11,5 @@ pub trait EthPubSubApi {
         unsubscribe = "eth_unsubscribe",
         item = reth_rpc_types::pubsub::SubscriptionResult
     )]
-    fn subscribe(&self, kind: Kind, params: Option<Params>);
+    async fn subscribe(&self, kind: Kind, params: Option<Params>);
 }
@@ -0,0 +1,218 @@
+//! JWT authentication of the Engine API, see the
+//! [spec](https://github.com/ethereum/execution-apis/blob/main/src/engine/authentication.md).
+
+use hyper::{header, Body, Request, Response, StatusCode};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    future::Future,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tower::{Layer, Service};
+
+/// The length of a JWT secret in bytes.
+const JWT_SECRET_LEN: usize = 32;
+
+/// The maximum difference between the issuance time of a token and the clock of the node.
+const JWT_MAX_IAT_DIFF: Duration = Duration::from_secs(60);
+
+/// Errors of the JWT secret and of the tokens that are signed with it.
+#[derive(Debug, thiserror::Error)]
+pub enum JwtError {
+    /// The secret is not a hex encoded 32 byte value.
+    #[error("jwt secret must be 32 hex encoded bytes")]
+    InvalidSecret,
+    /// The secret file couldn't be read or written.
+    #[error("failed to access jwt secret file {path}: {source}")]
+    Io {
+        /// The path of the secret file.
+        path: String,
+        /// The error of the file system.
+        source: std::io::Error,
+    },
+    /// The token is not signed with the secret or is malformed.
+    #[error(transparent)]
+    InvalidToken(#[from] jsonwebtoken::errors::Error),
+    /// The token was not issued within a minute of the current time.
+    #[error("jwt was not issued within {} seconds of the current time", JWT_MAX_IAT_DIFF.as_secs())]
+    InvalidIssuanceTime,
+}
+
+/// The claims of a token, only the issuance time is required.
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// The time the token was issued at, in seconds since the unix epoch.
+    iat: u64,
+}
+
+/// The 256 bit secret the consensus layer signs the tokens of its Engine API requests with.
+#[derive(Clone, PartialEq, Eq)]
+pub struct JwtSecret([u8; JWT_SECRET_LEN]);
+
+impl JwtSecret {
+    /// Parses a hex encoded secret, with or without `0x` prefix.
+    pub fn from_hex(hex: impl AsRef<str>) -> Result<Self, JwtError> {
+        let hex = hex.as_ref().trim();
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let bytes = hex::decode(hex).map_err(|_| JwtError::InvalidSecret)?;
+        Ok(Self(bytes.try_into().map_err(|_| JwtError::InvalidSecret)?))
+    }
+
+    /// Creates a random secret.
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// Reads the hex encoded secret from the file at the given path.
+    ///
+    /// If the file doesn't exist, a random secret is created and written to it, so the consensus
+    /// layer can be configured with it.
+    pub fn load_or_generate(path: &Path) -> Result<Self, JwtError> {
+        let io_err = |source| JwtError::Io { path: path.display().to_string(), source };
+        if path.exists() {
+            return Self::from_hex(std::fs::read_to_string(path).map_err(io_err)?)
+        }
+
+        let secret = Self::random();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        std::fs::write(path, hex::encode(secret.0)).map_err(io_err)?;
+        Ok(secret)
+    }
+
+    /// Checks that the token is signed with the secret using HS256 and was issued within a
+    /// minute of the current time.
+    pub fn validate(&self, token: &str) -> Result<(), JwtError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_required_spec_claims(&["iat"]);
+        validation.validate_exp = false;
+
+        let key = DecodingKey::from_secret(&self.0);
+        let claims = jsonwebtoken::decode::<Claims>(token, &key, &validation)?.claims;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        if now.as_secs().abs_diff(claims.iat) > JWT_MAX_IAT_DIFF.as_secs() {
+            return Err(JwtError::InvalidIssuanceTime)
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for JwtSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JwtSecret").field(&"<redacted>").finish()
+    }
+}
+
+/// A [Layer] that rejects HTTP requests without a bearer token that is signed with the
+/// [JwtSecret].
+#[derive(Debug, Clone)]
+pub(crate) struct AuthLayer {
+    secret: JwtSecret,
+}
+
+impl AuthLayer {
+    /// Creates a new layer that validates the tokens with the given secret.
+    pub(crate) fn new(secret: JwtSecret) -> Self {
+        Self { secret }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = Auth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Auth { inner, secret: self.secret.clone() }
+    }
+}
+
+/// The [Service] of the [AuthLayer].
+#[derive(Debug, Clone)]
+pub(crate) struct Auth<S> {
+    /// The wrapped service.
+    inner: S,
+    /// The secret the tokens are validated with.
+    secret: JwtSecret,
+}
+
+impl<S> Service<Request<Body>> for Auth<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let token = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match token.map(|token| self.secret.validate(token)) {
+            Some(Ok(())) => Box::pin(self.inner.call(request)),
+            _ => Box::pin(async {
+                Ok(Response::builder()
+                    .status(StatusCode::UNAUTHORIZED)
+                    .body(Body::empty())
+                    .expect("valid response"))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn token(secret: &JwtSecret, iat: u64) -> String {
+        let claims = Claims { iat };
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(&secret.0))
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_secret() {
+        let hex = "f79ae8046bc11c9927afe911db7143c51a806c4a537cc08e0d37140b0192f430";
+        let secret = JwtSecret::from_hex(hex).unwrap();
+        assert_eq!(JwtSecret::from_hex(format!("0x{hex}\n")).unwrap(), secret);
+        assert!(matches!(JwtSecret::from_hex(&hex[2..]), Err(JwtError::InvalidSecret)));
+        assert!(matches!(JwtSecret::from_hex("0xzz"), Err(JwtError::InvalidSecret)));
+    }
+
+    #[test]
+    fn validate_tokens() {
+        let secret = JwtSecret::random();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        assert!(secret.validate(&token(&secret, now)).is_ok());
+        assert!(matches!(
+            secret.validate(&token(&secret, now - 120)),
+            Err(JwtError::InvalidIssuanceTime)
+        ));
+        assert!(matches!(
+            secret.validate(&token(&JwtSecret::random(), now)),
+            Err(JwtError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn generate_missing_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwt.hex");
+
+        let secret = JwtSecret::load_or_generate(&path).unwrap();
+        assert_eq!(JwtSecret::load_or_generate(&path).unwrap(), secret);
+    }
+}
@@ -0,0 +1,68 @@
+//! CORS configuration of the HTTP and WS transports.
+
+use hyper::{http::HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+/// Error returned when the configured CORS domains are invalid.
+#[derive(Debug, thiserror::Error)]
+pub enum CorsDomainError {
+    /// A domain is not a valid header value.
+    #[error("{domain} is an invalid header value")]
+    InvalidHeader {
+        /// The invalid domain.
+        domain: String,
+    },
+    /// The wildcard was mixed with a list of domains.
+    #[error("wildcard origin (`*`) cannot be passed as part of a list: {input}")]
+    WildCardNotAllowed {
+        /// The configured domains.
+        input: String,
+    },
+}
+
+/// Creates a [CorsLayer] from the given comma separated list of domains.
+///
+/// `*` allows requests from any origin.
+pub(crate) fn create_cors_layer(domains: &str) -> Result<CorsLayer, CorsDomainError> {
+    let cors = match domains.trim() {
+        "*" => CorsLayer::new().allow_origin(Any),
+        _ => {
+            let origins = domains
+                .split(',')
+                .map(str::trim)
+                .filter(|domain| !domain.is_empty())
+                .map(|domain| {
+                    if domain == "*" {
+                        return Err(CorsDomainError::WildCardNotAllowed {
+                            input: domains.to_string(),
+                        })
+                    }
+                    domain
+                        .parse::<HeaderValue>()
+                        .map_err(|_| CorsDomainError::InvalidHeader { domain: domain.to_string() })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            CorsLayer::new().allow_origin(AllowOrigin::list(origins))
+        }
+    };
+    Ok(cors.allow_methods([Method::GET, Method::POST]).allow_headers(Any))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cors_domains() {
+        assert!(create_cors_layer("*").is_ok());
+        assert!(create_cors_layer("http://localhost:3000, https://example.com").is_ok());
+        assert!(matches!(
+            create_cors_layer("http://localhost:3000,*"),
+            Err(CorsDomainError::WildCardNotAllowed { .. })
+        ));
+        assert!(matches!(
+            create_cors_layer("http://local\nhost"),
+            Err(CorsDomainError::InvalidHeader { .. })
+        ));
+    }
+}
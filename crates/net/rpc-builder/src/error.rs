@@ -0,0 +1,19 @@
+use crate::cors::CorsDomainError;
+use std::net::SocketAddr;
+
+/// Errors that can occur while configuring or launching the RPC servers.
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    /// An unknown module name was selected.
+    #[error("unknown rpc module: {0}")]
+    UnknownModule(String),
+    /// The CORS domains are invalid.
+    #[error(transparent)]
+    Cors(#[from] CorsDomainError),
+    /// HTTP and WS were configured on the same socket address.
+    #[error("http and ws servers can't be launched on the same address: {0}")]
+    AddressConflict(SocketAddr),
+    /// Error from the jsonrpsee server.
+    #[error(transparent)]
+    Server(#[from] jsonrpsee::core::Error),
+}
@@ -0,0 +1,88 @@
+#![warn(missing_debug_implementations, missing_docs, unreachable_pub, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Configure reth RPC
+//!
+//! This crate contains several builder and config types that allow to configure which RPC
+//! namespaces are served on which transport, and to launch the servers.
+//!
+//! Every transport has its own module selection, address and CORS settings. If no modules are
+//! selected explicitly, only the [STANDARD_MODULES] are served. The [RpcServerLimits] of each
+//! transport bound the number of connections and the size of requests, responses and batches.
+//!
+//! The servers record the number of calls, failed calls and the latency of every method as
+//! metrics, and emit a `debug` span for every call with the `rpc::server` target.
+//!
+//! ## Example
+//!
+//! ```
+//! use reth_network::{transactions::TransactionsHandle, NetworkHandle};
+//! use reth_provider::{
+//!     BlockProvider, HeaderProvider, ReceiptProvider, StageCheckpointProvider,
+//!     StateProviderFactory, TransactionsProvider,
+//! };
+//! use reth_rpc_builder::{
+//!     RethRpcModule, RpcModuleBuilder, RpcModuleSelection, RpcServerConfig, TransportConfig,
+//!     TransportRpcModuleConfig,
+//! };
+//! use reth_transaction_pool::TransactionPool;
+//! use std::sync::Arc;
+//!
+//! async fn launch<Client, Pool>(
+//!     client: Arc<Client>,
+//!     pool: Pool,
+//!     network: NetworkHandle,
+//!     transactions: TransactionsHandle,
+//! ) where
+//!     Client: BlockProvider +
+//!         HeaderProvider +
+//!         ReceiptProvider +
+//!         StageCheckpointProvider +
+//!         StateProviderFactory +
+//!         TransactionsProvider +
+//!         'static,
+//!     Pool: TransactionPool + Clone + 'static,
+//! {
+//!     // serve the standard modules over http and `eth` and `debug` over ws
+//!     let config = TransportRpcModuleConfig::default()
+//!         .with_http(RpcModuleSelection::Standard)
+//!         .with_ws([RethRpcModule::Eth, RethRpcModule::Debug]);
+//!     let modules =
+//!         RpcModuleBuilder::new(client, pool, network, transactions).build(config).unwrap();
+//!
+//!     let handle = RpcServerConfig::default()
+//!         .with_http(TransportConfig::default_http().with_cors("*"))
+//!         .with_ws(TransportConfig::default_ws())
+//!         .start(modules)
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+
+mod auth;
+mod cors;
+mod error;
+mod limits;
+mod metrics;
+mod module;
+mod server;
+
+pub use auth::{JwtError, JwtSecret};
+pub use cors::CorsDomainError;
+pub use error::RpcError;
+pub use limits::{
+    RpcServerLimits, DEFAULT_MAX_BATCH_LEN, DEFAULT_MAX_CONNECTIONS, DEFAULT_MAX_REQUEST_SIZE,
+    DEFAULT_MAX_RESPONSE_SIZE,
+};
+pub use module::{
+    RethRpcModule, RpcModuleBuilder, RpcModuleSelection, TransportRpcModuleConfig,
+    TransportRpcModules, STANDARD_MODULES,
+};
+pub use server::{
+    start_engine_api, RpcServerConfig, RpcServerHandle, TransportConfig, DEFAULT_ENGINE_API_PORT,
+    DEFAULT_HTTP_RPC_PORT, DEFAULT_WS_RPC_PORT,
+};
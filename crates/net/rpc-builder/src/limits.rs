@@ -0,0 +1,39 @@
+//! Limits that protect the RPC servers from expensive requests.
+
+/// The default maximum number of concurrent connections.
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 100;
+
+/// The default maximum size of a request body: 15 MiB.
+pub const DEFAULT_MAX_REQUEST_SIZE: u32 = 15 * 1024 * 1024;
+
+/// The default maximum size of a response body: 150 MiB.
+pub const DEFAULT_MAX_RESPONSE_SIZE: u32 = 150 * 1024 * 1024;
+
+/// The default maximum number of calls in a batch request.
+pub const DEFAULT_MAX_BATCH_LEN: u32 = 100;
+
+/// Limits of a server.
+///
+/// Requests that exceed a limit are answered with a JSON-RPC error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcServerLimits {
+    /// The maximum number of concurrent connections.
+    pub max_connections: u32,
+    /// The maximum size of a request body in bytes.
+    pub max_request_size: u32,
+    /// The maximum size of a response body in bytes.
+    pub max_response_size: u32,
+    /// The maximum number of calls in a batch request.
+    pub max_batch_len: u32,
+}
+
+impl Default for RpcServerLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            max_batch_len: DEFAULT_MAX_BATCH_LEN,
+        }
+    }
+}
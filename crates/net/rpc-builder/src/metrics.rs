@@ -0,0 +1,150 @@
+//! Per method metrics and tracing of the RPC servers.
+
+use jsonrpsee::{
+    server::logger::{HttpRequest, Logger, MethodKind, SuccessOrError, TransportProtocol},
+    types::Params,
+    RpcModule,
+};
+use metrics::{register_counter, register_histogram, Counter, Histogram};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::Arc,
+    time::Instant,
+};
+use tracing::{debug, debug_span, trace, Span};
+
+/// The maximum number of spans of unfinished calls that are kept per method, the spans of calls
+/// that never finish, e.g. because the connection was closed, are dropped after that.
+const MAX_PENDING_CALL_SPANS: usize = 1024;
+
+/// Records the calls of every method that is served by a server.
+///
+/// All metrics are labeled with the `method` and the `transport` of the server. Calls of methods
+/// that are not registered are not recorded, so arbitrary method names can't inflate the number
+/// of metrics.
+///
+/// The `call` span of a call is opened when the call starts and closed when it finishes. The hooks
+/// of the server can't tell concurrent calls of the same method apart, so these are paired with
+/// their spans in the order they started.
+#[derive(Debug, Clone)]
+pub(crate) struct RpcServerMetrics {
+    inner: Arc<HashMap<&'static str, RpcMethodMetrics>>,
+}
+
+impl RpcServerMetrics {
+    /// Registers the metrics of all methods of the module.
+    pub(crate) fn new(module: &RpcModule<()>, transport: &'static str) -> Self {
+        let methods = module
+            .method_names()
+            .map(|method| (method, RpcMethodMetrics::new(method, transport)))
+            .collect();
+        Self { inner: Arc::new(methods) }
+    }
+}
+
+impl Logger for RpcServerMetrics {
+    type Instant = Instant;
+
+    fn on_connect(&self, remote_addr: SocketAddr, _request: &HttpRequest, _t: TransportProtocol) {
+        trace!(target: "rpc::server", %remote_addr, "Connection opened");
+    }
+
+    fn on_request(&self, _transport: TransportProtocol) -> Self::Instant {
+        Instant::now()
+    }
+
+    fn on_call(
+        &self,
+        method_name: &str,
+        _params: Params<'_>,
+        _kind: MethodKind,
+        _transport: TransportProtocol,
+    ) {
+        if let Some(metrics) = self.inner.get(method_name) {
+            metrics.calls_started.increment(1);
+
+            let span = debug_span!(target: "rpc::server", "call", method = method_name);
+            let mut pending_calls = metrics.pending_calls.lock();
+            if pending_calls.len() == MAX_PENDING_CALL_SPANS {
+                pending_calls.pop_front();
+            }
+            pending_calls.push_back(span);
+        }
+    }
+
+    fn on_result(
+        &self,
+        method_name: &str,
+        success_or_error: SuccessOrError,
+        started_at: Self::Instant,
+        _transport: TransportProtocol,
+    ) {
+        // calls of a batch are measured from the start of the batch
+        let elapsed = started_at.elapsed();
+
+        if let Some(metrics) = self.inner.get(method_name) {
+            let success = success_or_error.is_success();
+            if let Some(span) = metrics.pending_calls.lock().pop_front() {
+                span.in_scope(|| debug!(target: "rpc::server", success, ?elapsed, "Call finished"));
+            }
+
+            if success {
+                metrics.calls_successful.increment(1);
+            } else {
+                metrics.calls_failed.increment(1);
+            }
+            metrics.call_latency.record(elapsed.as_secs_f64());
+        }
+    }
+
+    fn on_response(&self, _result: &str, _started_at: Self::Instant, _t: TransportProtocol) {}
+
+    fn on_disconnect(&self, remote_addr: SocketAddr, _transport: TransportProtocol) {
+        trace!(target: "rpc::server", %remote_addr, "Connection closed");
+    }
+}
+
+/// Metrics of a single method.
+#[derive(Debug)]
+struct RpcMethodMetrics {
+    /// The number of calls started.
+    calls_started: Counter,
+    /// The number of calls that returned a result.
+    calls_successful: Counter,
+    /// The number of calls that returned an error.
+    calls_failed: Counter,
+    /// The duration of a call in seconds.
+    call_latency: Histogram,
+    /// The spans of the calls that didn't finish yet, in the order they started.
+    pending_calls: Mutex<VecDeque<Span>>,
+}
+
+impl RpcMethodMetrics {
+    fn new(method: &'static str, transport: &'static str) -> Self {
+        let labels = [("method", method), ("transport", transport)];
+        Self {
+            calls_started: register_counter!("rpc_server_calls_started", &labels),
+            calls_successful: register_counter!("rpc_server_calls_successful", &labels),
+            calls_failed: register_counter!("rpc_server_calls_failed", &labels),
+            call_latency: register_histogram!("rpc_server_call_latency", &labels),
+            pending_calls: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_registered_methods() {
+        let mut module = RpcModule::new(());
+        module.register_method("eth_chainId", |_, _| 1u64).unwrap();
+
+        let metrics = RpcServerMetrics::new(&module, "http");
+        assert!(metrics.inner.contains_key("eth_chainId"));
+        assert!(!metrics.inner.contains_key("eth_unknown"));
+    }
+}
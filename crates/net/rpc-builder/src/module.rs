@@ -0,0 +1,430 @@
+//! Selection of the RPC namespaces that are served on each transport.
+
+use crate::error::RpcError;
+use jsonrpsee::{Methods, RpcModule};
+use reth_network::{transactions::TransactionsHandle, NetworkHandle};
+use reth_primitives::ChainSpec;
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StageCheckpointProvider, StateProviderFactory,
+    TransactionsProvider,
+};
+use reth_rpc::{
+    AdminApi, DebugApi, EthApi, EthApiConfig, EthFilter, EthFilterConfig, LogFilterReloader,
+    NetApi, Web3Api,
+};
+use reth_rpc_api::{
+    AdminApiServer, DebugApiServer, EthApiServer, EthFilterApiServer, NetApiServer, Web3ApiServer,
+};
+use reth_transaction_pool::TransactionPool;
+use std::{fmt, str::FromStr, sync::Arc};
+
+/// The modules that are served if no modules are selected explicitly.
+///
+/// These do not expose anything beyond the public chain data, so they're safe to serve on a
+/// public endpoint.
+pub const STANDARD_MODULES: [RethRpcModule; 3] =
+    [RethRpcModule::Eth, RethRpcModule::Net, RethRpcModule::Web3];
+
+/// The named RPC namespaces that can be served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RethRpcModule {
+    /// `admin_` module
+    Admin,
+    /// `debug_` module
+    Debug,
+    /// `eth_` module
+    Eth,
+    /// `net_` module
+    Net,
+    /// `web3_` module
+    Web3,
+}
+
+impl RethRpcModule {
+    /// All available modules.
+    pub const ALL: [RethRpcModule; 5] = [
+        RethRpcModule::Admin,
+        RethRpcModule::Debug,
+        RethRpcModule::Eth,
+        RethRpcModule::Net,
+        RethRpcModule::Web3,
+    ];
+
+    /// Returns the name of the namespace.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RethRpcModule::Admin => "admin",
+            RethRpcModule::Debug => "debug",
+            RethRpcModule::Eth => "eth",
+            RethRpcModule::Net => "net",
+            RethRpcModule::Web3 => "web3",
+        }
+    }
+}
+
+impl fmt::Display for RethRpcModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RethRpcModule {
+    type Err = RpcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RethRpcModule::ALL
+            .into_iter()
+            .find(|module| module.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| RpcError::UnknownModule(s.to_string()))
+    }
+}
+
+/// Describes which modules are served on a transport.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RpcModuleSelection {
+    /// All available modules.
+    All,
+    /// The [STANDARD_MODULES].
+    #[default]
+    Standard,
+    /// Only the given modules.
+    Selection(Vec<RethRpcModule>),
+}
+
+impl RpcModuleSelection {
+    /// Returns the selected modules, without duplicates.
+    pub fn into_selection(self) -> Vec<RethRpcModule> {
+        match self {
+            RpcModuleSelection::All => RethRpcModule::ALL.to_vec(),
+            RpcModuleSelection::Standard => STANDARD_MODULES.to_vec(),
+            RpcModuleSelection::Selection(modules) => {
+                let mut selection = Vec::with_capacity(modules.len());
+                for module in modules {
+                    if !selection.contains(&module) {
+                        selection.push(module);
+                    }
+                }
+                selection
+            }
+        }
+    }
+}
+
+impl<I: IntoIterator<Item = RethRpcModule>> From<I> for RpcModuleSelection {
+    fn from(modules: I) -> Self {
+        RpcModuleSelection::Selection(modules.into_iter().collect())
+    }
+}
+
+/// Formats the selection as it is parsed, see the [FromStr] implementation.
+impl fmt::Display for RpcModuleSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == RpcModuleSelection::All {
+            return f.write_str("all")
+        }
+        let modules = self.clone().into_selection();
+        let modules = modules.iter().map(RethRpcModule::as_str).collect::<Vec<_>>();
+        f.write_str(&modules.join(","))
+    }
+}
+
+/// Parses a comma separated list of modules, like `eth,net,web3`, or `all`.
+impl FromStr for RpcModuleSelection {
+    type Err = RpcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("all") {
+            return Ok(RpcModuleSelection::All)
+        }
+        s.split(',')
+            .map(str::trim)
+            .filter(|module| !module.is_empty())
+            .map(RethRpcModule::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map(RpcModuleSelection::Selection)
+    }
+}
+
+/// Holds the modules that are served on each transport.
+///
+/// A transport without a selection is not served.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransportRpcModuleConfig {
+    /// The modules served over HTTP.
+    http: Option<RpcModuleSelection>,
+    /// The modules served over WS.
+    ws: Option<RpcModuleSelection>,
+}
+
+impl TransportRpcModuleConfig {
+    /// Serves the given modules over HTTP.
+    pub fn with_http(mut self, http: impl Into<RpcModuleSelection>) -> Self {
+        self.http = Some(http.into());
+        self
+    }
+
+    /// Serves the given modules over WS.
+    pub fn with_ws(mut self, ws: impl Into<RpcModuleSelection>) -> Self {
+        self.ws = Some(ws.into());
+        self
+    }
+
+    /// Returns the modules served over HTTP.
+    pub fn http(&self) -> Option<&RpcModuleSelection> {
+        self.http.as_ref()
+    }
+
+    /// Returns the modules served over WS.
+    pub fn ws(&self) -> Option<&RpcModuleSelection> {
+        self.ws.as_ref()
+    }
+}
+
+/// The configured [RpcModule] of each transport.
+#[derive(Debug, Default)]
+pub struct TransportRpcModules {
+    /// The module served over HTTP.
+    pub(crate) http: Option<RpcModule<()>>,
+    /// The module served over WS.
+    pub(crate) ws: Option<RpcModule<()>>,
+}
+
+impl TransportRpcModules {
+    /// Returns the module served over HTTP.
+    pub fn http(&self) -> Option<&RpcModule<()>> {
+        self.http.as_ref()
+    }
+
+    /// Returns the module served over WS.
+    pub fn ws(&self) -> Option<&RpcModule<()>> {
+        self.ws.as_ref()
+    }
+}
+
+/// Builds the [TransportRpcModules] from the components of the node.
+#[derive(Debug)]
+pub struct RpcModuleBuilder<Client, Pool> {
+    /// The client that can interact with the chain.
+    client: Arc<Client>,
+    /// The transaction pool.
+    pool: Pool,
+    /// Handle to the network.
+    network: NetworkHandle,
+    /// Handle to the transactions task of the network.
+    transactions: TransactionsHandle,
+    /// Settings of the `eth` namespace.
+    eth_config: EthApiConfig,
+    /// Settings of the `eth` filter methods.
+    filter_config: EthFilterConfig,
+    /// The version returned by `web3_clientVersion`.
+    client_version: String,
+    /// Changes the log filter on `admin_setLogFilter`.
+    log_filter: Option<Arc<dyn LogFilterReloader>>,
+}
+
+impl<Client, Pool> RpcModuleBuilder<Client, Pool>
+where
+    Client: BlockProvider +
+        HeaderProvider +
+        ReceiptProvider +
+        StageCheckpointProvider +
+        StateProviderFactory +
+        TransactionsProvider +
+        'static,
+    Pool: TransactionPool + Clone + 'static,
+{
+    /// Creates a new builder with the given components.
+    pub fn new(
+        client: Arc<Client>,
+        pool: Pool,
+        network: NetworkHandle,
+        transactions: TransactionsHandle,
+    ) -> Self {
+        Self {
+            client,
+            pool,
+            network,
+            transactions,
+            eth_config: Default::default(),
+            filter_config: Default::default(),
+            client_version: format!("reth/v{}", env!("CARGO_PKG_VERSION")),
+            log_filter: None,
+        }
+    }
+
+    /// Sets the chain spec used to execute calls and to re-execute transactions.
+    pub fn with_chain_spec(mut self, chain_spec: ChainSpec) -> Self {
+        self.eth_config.chain_spec = chain_spec;
+        self
+    }
+
+    /// Sets the settings of the `eth` namespace.
+    ///
+    /// This includes the chain spec, which is also used by the `debug` namespace.
+    pub fn with_eth_config(mut self, eth_config: EthApiConfig) -> Self {
+        self.eth_config = eth_config;
+        self
+    }
+
+    /// Sets the settings of the `eth` filter methods, e.g. the limits of log queries.
+    pub fn with_filter_config(mut self, filter_config: EthFilterConfig) -> Self {
+        self.filter_config = filter_config;
+        self
+    }
+
+    /// Sets the version returned by `web3_clientVersion`.
+    pub fn with_client_version(mut self, client_version: impl Into<String>) -> Self {
+        self.client_version = client_version.into();
+        self
+    }
+
+    /// Sets the handle that changes the log filter of the node on `admin_setLogFilter`.
+    pub fn with_log_filter(mut self, log_filter: Arc<dyn LogFilterReloader>) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    /// Builds the [RpcModule] of every configured transport.
+    ///
+    /// The handlers are shared between the transports, so for example a filter that was installed
+    /// over HTTP can also be polled over WS.
+    pub fn build(self, config: TransportRpcModuleConfig) -> Result<TransportRpcModules, RpcError> {
+        let TransportRpcModuleConfig { http, ws } = config;
+        let mut registry = RethModuleRegistry { builder: self, eth_api: None, eth_filter: None };
+
+        let http = http.map(|selection| registry.module_for(selection)).transpose()?;
+        let ws = ws.map(|selection| registry.module_for(selection)).transpose()?;
+
+        Ok(TransportRpcModules { http, ws })
+    }
+}
+
+/// Creates the handlers of the modules and keeps the ones that hold state, so they can be shared
+/// between the transports.
+#[derive(Debug)]
+struct RethModuleRegistry<Client, Pool> {
+    /// The components of the node.
+    builder: RpcModuleBuilder<Client, Pool>,
+    /// The `eth` handler, created on first use.
+    eth_api: Option<EthApi<Pool, Client>>,
+    /// The `eth` filter handler, created on first use.
+    eth_filter: Option<EthFilter<Pool, Client>>,
+}
+
+impl<Client, Pool> RethModuleRegistry<Client, Pool>
+where
+    Client: BlockProvider +
+        HeaderProvider +
+        ReceiptProvider +
+        StageCheckpointProvider +
+        StateProviderFactory +
+        TransactionsProvider +
+        'static,
+    Pool: TransactionPool + Clone + 'static,
+{
+    /// Merges the methods of all selected modules into a single [RpcModule].
+    fn module_for(&mut self, selection: RpcModuleSelection) -> Result<RpcModule<()>, RpcError> {
+        let mut module = RpcModule::new(());
+        for namespace in selection.into_selection() {
+            module.merge(self.methods(namespace))?;
+        }
+        Ok(module)
+    }
+
+    /// Returns the methods of the given namespace.
+    fn methods(&mut self, namespace: RethRpcModule) -> Methods {
+        match namespace {
+            RethRpcModule::Admin => {
+                AdminApi::new(self.builder.network.clone(), self.builder.log_filter.clone())
+                    .into_rpc()
+                    .into()
+            }
+            RethRpcModule::Debug => DebugApi::new(
+                Arc::clone(&self.builder.client),
+                self.builder.eth_config.chain_spec.clone(),
+            )
+            .into_rpc()
+            .into(),
+            RethRpcModule::Eth => {
+                let mut methods: Methods = self.eth_api().into_rpc().into();
+                let filter = self.eth_filter().into_rpc();
+                methods.merge(filter).expect("eth and eth filter methods don't overlap");
+                methods
+            }
+            RethRpcModule::Net => {
+                NetApi::new(self.builder.network.clone(), Box::new(self.eth_api()))
+                    .into_rpc()
+                    .into()
+            }
+            RethRpcModule::Web3 => {
+                Web3Api::new(self.builder.client_version.clone()).into_rpc().into()
+            }
+        }
+    }
+
+    /// Returns the shared `eth` handler.
+    fn eth_api(&mut self) -> EthApi<Pool, Client> {
+        let builder = &self.builder;
+        self.eth_api
+            .get_or_insert_with(|| {
+                EthApi::with_config(
+                    Arc::clone(&builder.client),
+                    builder.pool.clone(),
+                    builder.network.clone(),
+                    builder.transactions.clone(),
+                    builder.eth_config.clone(),
+                )
+            })
+            .clone()
+    }
+
+    /// Returns the shared `eth` filter handler.
+    fn eth_filter(&mut self) -> EthFilter<Pool, Client> {
+        let builder = &self.builder;
+        self.eth_filter
+            .get_or_insert_with(|| {
+                EthFilter::with_config(
+                    Arc::clone(&builder.client),
+                    builder.pool.clone(),
+                    builder.filter_config,
+                )
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_module_selection() {
+        assert_eq!("all".parse::<RpcModuleSelection>().unwrap(), RpcModuleSelection::All);
+        assert_eq!(
+            "eth, net,WEB3".parse::<RpcModuleSelection>().unwrap(),
+            RpcModuleSelection::Selection(vec![
+                RethRpcModule::Eth,
+                RethRpcModule::Net,
+                RethRpcModule::Web3
+            ])
+        );
+        assert_eq!("admin".parse::<RethRpcModule>().unwrap(), RethRpcModule::Admin);
+        assert!("eth,txpool".parse::<RpcModuleSelection>().is_err());
+    }
+
+    #[test]
+    fn display_module_selection() {
+        assert_eq!(RpcModuleSelection::All.to_string(), "all");
+        assert_eq!(RpcModuleSelection::Standard.to_string(), "eth,net,web3");
+        let selection = RpcModuleSelection::from([RethRpcModule::Debug, RethRpcModule::Eth]);
+        assert_eq!(selection.to_string().parse::<RpcModuleSelection>().unwrap(), selection);
+    }
+
+    #[test]
+    fn dedup_selection() {
+        let selection =
+            RpcModuleSelection::from([RethRpcModule::Eth, RethRpcModule::Debug, RethRpcModule::Eth]);
+        assert_eq!(selection.into_selection(), vec![RethRpcModule::Eth, RethRpcModule::Debug]);
+        assert_eq!(RpcModuleSelection::default().into_selection(), STANDARD_MODULES.to_vec());
+    }
+}
@@ -0,0 +1,315 @@
+//! Configuration and launch of the HTTP and WS servers.
+
+use crate::{
+    auth::{AuthLayer, JwtSecret},
+    cors::create_cors_layer,
+    error::RpcError,
+    limits::RpcServerLimits,
+    metrics::RpcServerMetrics,
+    module::TransportRpcModules,
+};
+use jsonrpsee::{
+    server::{BatchRequestConfig, ServerBuilder, ServerHandle},
+    RpcModule,
+};
+use reth_rpc::EngineApi;
+use reth_rpc_api::EngineApiServer;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tracing::info;
+
+/// The default port of the HTTP server.
+pub const DEFAULT_HTTP_RPC_PORT: u16 = 8545;
+
+/// The default port of the WS server.
+pub const DEFAULT_WS_RPC_PORT: u16 = 8546;
+
+/// The default port of the Engine API server.
+pub const DEFAULT_ENGINE_API_PORT: u16 = 8551;
+
+/// Settings of a single transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportConfig {
+    /// The address the server listens on.
+    addr: SocketAddr,
+    /// Comma separated list of domains from which cross-origin requests are accepted.
+    ///
+    /// If not set, no CORS headers are sent.
+    cors_domains: Option<String>,
+    /// Limits of the server.
+    limits: RpcServerLimits,
+}
+
+impl TransportConfig {
+    /// Creates a new config that listens on the given address.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self { addr, cors_domains: None, limits: Default::default() }
+    }
+
+    /// The default HTTP config, which listens on `127.0.0.1:8545`.
+    pub fn default_http() -> Self {
+        Self::new(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_HTTP_RPC_PORT))
+    }
+
+    /// The default WS config, which listens on `127.0.0.1:8546`.
+    pub fn default_ws() -> Self {
+        Self::new(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_WS_RPC_PORT))
+    }
+
+    /// The default Engine API config, which listens on `127.0.0.1:8551`.
+    pub fn default_engine() -> Self {
+        Self::new(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_ENGINE_API_PORT))
+    }
+
+    /// Accepts cross-origin requests from the given comma separated list of domains.
+    ///
+    /// `*` accepts requests from any origin.
+    pub fn with_cors(mut self, cors_domains: impl Into<String>) -> Self {
+        self.cors_domains = Some(cors_domains.into());
+        self
+    }
+
+    /// Sets the limits of the server.
+    pub fn with_limits(mut self, limits: RpcServerLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+/// Configures the HTTP and WS servers.
+///
+/// Each transport is launched on its own address with its own settings. A transport is only
+/// launched if it is configured here _and_ has a module in the [TransportRpcModules].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcServerConfig {
+    /// Settings of the HTTP server.
+    http: Option<TransportConfig>,
+    /// Settings of the WS server.
+    ws: Option<TransportConfig>,
+}
+
+impl RpcServerConfig {
+    /// Enables the HTTP server with the given settings.
+    pub fn with_http(mut self, http: TransportConfig) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// Enables the WS server with the given settings.
+    pub fn with_ws(mut self, ws: TransportConfig) -> Self {
+        self.ws = Some(ws);
+        self
+    }
+
+    /// Launches the configured servers.
+    pub async fn start(self, modules: TransportRpcModules) -> Result<RpcServerHandle, RpcError> {
+        let TransportRpcModules { http: http_module, ws: ws_module } = modules;
+        let http = self.http.zip(http_module);
+        let ws = self.ws.zip(ws_module);
+
+        if let (Some((http, _)), Some((ws, _))) = (&http, &ws) {
+            if http.addr == ws.addr {
+                return Err(RpcError::AddressConflict(http.addr))
+            }
+        }
+
+        let mut handle = RpcServerHandle::default();
+        if let Some((config, module)) = http {
+            let (addr, server) = start_server(config, module, Transport::Http, None).await?;
+            info!(target: "rpc", %addr, "HTTP server started");
+            handle.http = Some((addr, server));
+        }
+        if let Some((config, module)) = ws {
+            let (addr, server) = start_server(config, module, Transport::Ws, None).await?;
+            info!(target: "rpc", %addr, "WS server started");
+            handle.ws = Some((addr, server));
+        }
+
+        Ok(handle)
+    }
+}
+
+/// Launches the HTTP server of the Engine API.
+///
+/// The Engine API drives the consensus of the node, so it is served on its own address and never
+/// together with the other modules. Requests are rejected with `401 Unauthorized` unless they
+/// carry a bearer token that the consensus layer signed with the shared [JwtSecret].
+///
+/// The address of the server is the HTTP address of the returned handle.
+pub async fn start_engine_api(
+    config: TransportConfig,
+    secret: JwtSecret,
+    engine_api: EngineApi,
+) -> Result<RpcServerHandle, RpcError> {
+    let mut module = RpcModule::new(());
+    module.merge(engine_api.into_rpc())?;
+    let (addr, server) = start_server(config, module, Transport::Engine, Some(secret)).await?;
+    info!(target: "rpc", %addr, "Engine API server started");
+    Ok(RpcServerHandle { http: Some((addr, server)), ws: None })
+}
+
+/// The transport a server accepts.
+#[derive(Debug, Clone, Copy)]
+enum Transport {
+    Http,
+    Ws,
+    /// HTTP for the Engine API.
+    Engine,
+}
+
+impl Transport {
+    /// Returns the name of the transport, used as metrics label.
+    fn as_str(&self) -> &'static str {
+        match self {
+            Transport::Http => "http",
+            Transport::Ws => "ws",
+            Transport::Engine => "engine",
+        }
+    }
+}
+
+/// Launches a server that only accepts the given transport.
+///
+/// If a [JwtSecret] is given, only requests with a token signed with the secret are accepted.
+async fn start_server(
+    config: TransportConfig,
+    module: RpcModule<()>,
+    transport: Transport,
+    secret: Option<JwtSecret>,
+) -> Result<(SocketAddr, ServerHandle), RpcError> {
+    let TransportConfig { addr, cors_domains, limits } = config;
+    let cors = cors_domains.as_deref().map(create_cors_layer).transpose()?;
+    let middleware =
+        tower::ServiceBuilder::new().option_layer(cors).option_layer(secret.map(AuthLayer::new));
+
+    let builder = ServerBuilder::new()
+        .max_connections(limits.max_connections)
+        .max_request_body_size(limits.max_request_size)
+        .max_response_body_size(limits.max_response_size)
+        .set_batch_request_config(BatchRequestConfig::Limit(limits.max_batch_len))
+        .set_middleware(middleware)
+        .set_logger(RpcServerMetrics::new(&module, transport.as_str()));
+    let builder = match transport {
+        Transport::Http | Transport::Engine => builder.http_only(),
+        Transport::Ws => builder.ws_only(),
+    };
+    let server = builder.build(addr).await?;
+    let addr = server.local_addr()?;
+
+    Ok((addr, server.start(module)))
+}
+
+/// A handle to the launched servers.
+///
+/// The servers keep running if the handle is dropped.
+#[derive(Debug, Default)]
+pub struct RpcServerHandle {
+    /// The address and handle of the HTTP server.
+    http: Option<(SocketAddr, ServerHandle)>,
+    /// The address and handle of the WS server.
+    ws: Option<(SocketAddr, ServerHandle)>,
+}
+
+impl RpcServerHandle {
+    /// Returns the address of the HTTP server, if it was launched.
+    pub fn http_local_addr(&self) -> Option<SocketAddr> {
+        self.http.as_ref().map(|(addr, _)| *addr)
+    }
+
+    /// Returns the address of the WS server, if it was launched.
+    pub fn ws_local_addr(&self) -> Option<SocketAddr> {
+        self.ws.as_ref().map(|(addr, _)| *addr)
+    }
+
+    /// Tells all servers to stop.
+    pub fn stop(self) -> Result<(), RpcError> {
+        for (_, handle) in self.http.into_iter().chain(self.ws) {
+            handle.stop()?;
+        }
+        Ok(())
+    }
+
+    /// Tells all servers to stop and waits until they stopped.
+    pub async fn stop_and_wait(self) -> Result<(), RpcError> {
+        for (_, handle) in self.http.into_iter().chain(self.ws) {
+            handle.stop()?;
+            handle.stopped().await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_util::compat::TokioAsyncReadCompatExt;
+
+    fn local_config() -> TransportConfig {
+        TransportConfig::new(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+    }
+
+    #[tokio::test]
+    async fn start_configured_transports() {
+        let modules =
+            TransportRpcModules { http: Some(RpcModule::new(())), ws: Some(RpcModule::new(())) };
+        let handle = RpcServerConfig::default()
+            .with_http(local_config().with_cors("*"))
+            .start(modules)
+            .await
+            .unwrap();
+
+        // ws has a module but is not configured
+        assert!(handle.http_local_addr().is_some());
+        assert!(handle.ws_local_addr().is_none());
+        handle.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn reject_same_address() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_HTTP_RPC_PORT);
+        let modules =
+            TransportRpcModules { http: Some(RpcModule::new(())), ws: Some(RpcModule::new(())) };
+        let res = RpcServerConfig::default()
+            .with_http(TransportConfig::new(addr))
+            .with_ws(TransportConfig::new(addr))
+            .start(modules)
+            .await;
+        assert!(matches!(res, Err(RpcError::AddressConflict(_))));
+    }
+
+    #[tokio::test]
+    async fn limit_ws_batches() {
+        let limits = RpcServerLimits { max_batch_len: 1, ..Default::default() };
+        let modules = TransportRpcModules { http: None, ws: Some(RpcModule::new(())) };
+        let handle = RpcServerConfig::default()
+            .with_ws(local_config().with_limits(limits))
+            .start(modules)
+            .await
+            .unwrap();
+        let addr = handle.ws_local_addr().unwrap();
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let host = addr.to_string();
+        let mut client = soketto::handshake::Client::new(stream.compat(), &host, "/");
+        assert!(matches!(
+            client.handshake().await.unwrap(),
+            soketto::handshake::ServerResponse::Accepted { .. }
+        ));
+        let (mut sender, mut receiver) = client.into_builder().finish();
+
+        let batch =
+            r#"[{"jsonrpc":"2.0","method":"a","id":1},{"jsonrpc":"2.0","method":"b","id":2}]"#;
+        sender.send_text(batch).await.unwrap();
+        sender.flush().await.unwrap();
+        let mut response = Vec::new();
+        receiver.receive_data(&mut response).await.unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.contains("Exceeded max limit of 1"));
+
+        handle.stop().unwrap();
+    }
+}
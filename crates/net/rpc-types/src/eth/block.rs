@@ -1,5 +1,9 @@
 use crate::Transaction;
-use reth_primitives::{rpc::H64, Address, Bloom, Bytes, H256, U256};
+use reth_primitives::{
+    rpc::H64, Address, Block as PrimitiveBlock, Bloom, Bytes, Header as PrimitiveHeader,
+    TransactionSignedEcRecovered, H256, U256,
+};
+use reth_rlp::Encodable;
 use serde::{ser::Error, Deserialize, Serialize, Serializer};
 use std::{collections::BTreeMap, ops::Deref};
 
@@ -33,6 +37,54 @@ pub struct Block {
     pub base_fee_per_gas: Option<U256>,
 }
 
+impl Block {
+    /// Converts the block into its RPC representation.
+    ///
+    /// If `full` is set the block contains the full transactions, which requires the `senders` of
+    /// the transactions, otherwise only their hashes.
+    ///
+    /// The hash of a pending block is not final, so `block_hash` is optional.
+    pub fn from_block(
+        block: PrimitiveBlock,
+        senders: Vec<Address>,
+        block_hash: Option<H256>,
+        total_difficulty: U256,
+        full: bool,
+    ) -> Self {
+        let size = U256::from(block.length());
+        let PrimitiveBlock { header, body, ommers, .. } = block;
+
+        let transactions = if full {
+            BlockTransactions::Full(
+                body.into_iter()
+                    .zip(senders)
+                    .enumerate()
+                    .map(|(index, (tx, sender))| {
+                        Transaction::from_recovered_with_block_context(
+                            TransactionSignedEcRecovered::from_signed_transaction(tx, sender),
+                            block_hash,
+                            header.number,
+                            header.base_fee_per_gas,
+                            index,
+                        )
+                    })
+                    .collect(),
+            )
+        } else {
+            BlockTransactions::Hashes(body.iter().map(|tx| tx.hash()).collect())
+        };
+
+        Self {
+            base_fee_per_gas: header.base_fee_per_gas.map(U256::from),
+            header: Header::from_primitive_with_hash(header, block_hash, Some(size)),
+            total_difficulty,
+            uncles: ommers.iter().map(|ommer| ommer.hash_slow()).collect(),
+            transactions,
+            size: Some(size),
+        }
+    }
+}
+
 /// Block header representation.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -72,6 +124,53 @@ pub struct Header {
     pub nonce: Option<H64>,
     /// Size in bytes
     pub size: Option<U256>,
+    /// Withdrawals root hash, for post-Shanghai blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    /// Blob gas used, for post-Cancun blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U256>,
+    /// Excess blob gas, for post-Cancun blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U256>,
+    /// Parent beacon block root, for post-Cancun blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+}
+
+impl Header {
+    /// Converts the header into its RPC representation.
+    ///
+    /// The nonce is only set if the hash of the block is known, i.e. the block is not pending.
+    pub fn from_primitive_with_hash(
+        header: PrimitiveHeader,
+        block_hash: Option<H256>,
+        size: Option<U256>,
+    ) -> Self {
+        Self {
+            hash: block_hash,
+            parent_hash: header.parent_hash,
+            uncles_hash: header.ommers_hash,
+            author: header.beneficiary,
+            miner: header.beneficiary,
+            state_root: header.state_root,
+            transactions_root: header.transactions_root,
+            receipts_root: header.receipts_root,
+            number: Some(header.number.into()),
+            gas_used: header.gas_used.into(),
+            gas_limit: header.gas_limit.into(),
+            extra_data: header.extra_data.into(),
+            logs_bloom: header.logs_bloom,
+            timestamp: header.timestamp.into(),
+            difficulty: header.difficulty,
+            nonce: block_hash.map(|_| H64::from_low_u64_be(header.nonce)),
+            size,
+            withdrawals_root: header.withdrawals_root,
+            blob_gas_used: header.blob_gas_used.map(U256::from),
+            excess_blob_gas: header.excess_blob_gas.map(U256::from),
+            parent_beacon_block_root: header.parent_beacon_block_root,
+        }
+    }
 }
 
 /// A Block representation that allows to include additional fields
@@ -90,6 +189,12 @@ pub struct Rich<T> {
     pub extra_info: BTreeMap<String, serde_json::Value>,
 }
 
+impl<T> From<T> for Rich<T> {
+    fn from(inner: T) -> Self {
+        Self { inner, extra_info: Default::default() }
+    }
+}
+
 impl<T> Deref for Rich<T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
@@ -1,4 +1,4 @@
-use reth_primitives::{rpc::transaction::eip2930::AccessListItem, Address, Bytes, U256};
+use reth_primitives::{rpc::transaction::eip2930::AccessListItem, Address, Bytes, H256, U256, U64};
 use serde::{Deserialize, Serialize};
 
 /// Call request
@@ -29,3 +29,44 @@ pub struct CallRequest {
     #[serde(rename = "type")]
     pub transaction_type: Option<U256>,
 }
+
+/// Custom block fields that replace the block environment of calls.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockOverrides {
+    /// Fake block number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<U256>,
+    /// Fake difficulty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<U256>,
+    /// Fake timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<U64>,
+    /// Fake gas limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U64>,
+    /// Fake block author.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coinbase: Option<Address>,
+    /// Fake prevrandao value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub random: Option<H256>,
+    /// Fake base fee.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee: Option<U256>,
+}
+
+/// Result of a single call of `eth_callMany`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthCallResponse {
+    /// The return data of the call, or the revert data if it reverted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Bytes>,
+    /// The reason the call failed or reverted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The gas used by the call.
+    pub gas_used: U256,
+}
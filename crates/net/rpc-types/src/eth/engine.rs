@@ -2,7 +2,10 @@
 
 #![allow(missing_docs)]
 
-use reth_primitives::{Address, BlockNumber, Bloom, Bytes, H256, H64, U256, U64};
+use reth_primitives::{
+    Address, BlockNumber, Bloom, Bytes, SealedBlock, Withdrawal as PrimitiveWithdrawal, H256, H64,
+    U256, U64,
+};
 use serde::{Deserialize, Serialize};
 
 /// This structure maps on the ExecutionPayload structure of the beacon chain spec.
@@ -28,7 +31,40 @@ pub struct ExecutionPayload {
     /// Array of [`Withdrawal`] enabled with V2
     /// See <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#executionpayloadv2>
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub withdrawal: Option<Withdrawal>,
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl From<SealedBlock> for ExecutionPayload {
+    fn from(block: SealedBlock) -> Self {
+        let transactions = block
+            .body
+            .iter()
+            .map(|transaction| {
+                let mut encoded = Vec::new();
+                transaction.encode_enveloped(&mut encoded);
+                encoded.into()
+            })
+            .collect();
+        ExecutionPayload {
+            parent_hash: block.parent_hash,
+            fee_recipient: block.beneficiary,
+            state_root: block.state_root,
+            receipts_root: block.receipts_root,
+            logs_bloom: block.logs_bloom,
+            prev_randao: block.mix_hash,
+            block_number: block.number.into(),
+            gas_limit: block.gas_limit.into(),
+            gas_used: block.gas_used.into(),
+            timestamp: block.timestamp.into(),
+            extra_data: block.extra_data.clone().into(),
+            base_fee_per_gas: U256::from(block.base_fee_per_gas.unwrap_or_default()),
+            block_hash: block.hash(),
+            transactions,
+            withdrawals: block
+                .withdrawals
+                .map(|withdrawals| withdrawals.into_iter().map(Into::into).collect()),
+        }
+    }
 }
 
 /// This structure maps onto the validator withdrawal object from the beacon chain spec.
@@ -40,10 +76,30 @@ pub struct Withdrawal {
     pub index: U64,
     pub validator_index: U64,
     pub address: Address,
-    /// Note: the amount value is represented on the beacon chain as a little-endian value in units
-    /// of Gwei, whereas the amount in this structure MUST be converted to a big-endian value in
-    /// units of Wei.
-    pub amount: U256,
+    /// The amount of the withdrawal in Gwei.
+    pub amount: U64,
+}
+
+impl From<PrimitiveWithdrawal> for Withdrawal {
+    fn from(withdrawal: PrimitiveWithdrawal) -> Self {
+        Withdrawal {
+            index: withdrawal.index.into(),
+            validator_index: withdrawal.validator_index.into(),
+            address: withdrawal.address,
+            amount: withdrawal.amount.into(),
+        }
+    }
+}
+
+impl From<Withdrawal> for PrimitiveWithdrawal {
+    fn from(withdrawal: Withdrawal) -> Self {
+        PrimitiveWithdrawal {
+            index: withdrawal.index.as_u64(),
+            validator_index: withdrawal.validator_index.as_u64(),
+            address: withdrawal.address,
+            amount: withdrawal.amount.as_u64(),
+        }
+    }
 }
 
 /// This structure encapsulates the fork choice state
@@ -66,7 +122,7 @@ pub struct PayloadAttributes {
     /// Array of [`Withdrawal`] enabled with V2
     /// See <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#executionpayloadv2>
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub withdrawal: Option<Withdrawal>, // TODO: should be a vec
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 /// This structure contains the result of processing a payload
@@ -1,4 +1,4 @@
-use reth_primitives::{Address, Bytes, H256, U256};
+use reth_primitives::{Address, Bytes, Log as PrimitiveLog, H256, U256};
 use serde::{Deserialize, Serialize};
 
 /// Ethereum Log emitted by a transaction
@@ -27,3 +27,21 @@ pub struct Log {
     #[serde(default)]
     pub removed: bool,
 }
+
+/// Converts a log without any information about the block or transaction it was emitted in.
+impl From<PrimitiveLog> for Log {
+    fn from(log: PrimitiveLog) -> Self {
+        Self {
+            address: log.address,
+            topics: log.topics,
+            data: log.data.into(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            removed: false,
+        }
+    }
+}
@@ -1,7 +1,11 @@
 //! Ethereum types for pub-sub
 
 use crate::{Log, RichHeader};
-use reth_primitives::{rpc::Filter, H256};
+use reth_primitives::{
+    rpc::Filter,
+    serde_helper::num::{u64_hex, u64_hex_opt},
+    H256,
+};
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Subscription result.
@@ -34,9 +38,11 @@ pub enum PubSubSyncStatus {
 #[allow(missing_docs)]
 pub struct SyncStatusMetadata {
     pub syncing: bool,
+    #[serde(with = "u64_hex")]
     pub starting_block: u64,
+    #[serde(with = "u64_hex")]
     pub current_block: u64,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, with = "u64_hex_opt", skip_serializing_if = "Option::is_none")]
     pub highest_block: Option<u64>,
 }
 
@@ -0,0 +1,55 @@
+//! Types for overriding the state of calls.
+
+use reth_primitives::{Address, Bytes, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A set of account overrides, applied before a call is executed.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Custom account fields that replace the state of the account for a call.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountOverride {
+    /// Fake balance to set for the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Fake nonce to set for the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    /// Fake code to set for the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Fake storage that replaces the entire storage of the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<H256, H256>>,
+    /// Fake storage slots that are set on top of the storage of the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_state_override() {
+        let s = r#"{
+            "0x0000000000000000000000000000000000000124": {
+                "balance": "0x1",
+                "code": "0x6001",
+                "stateDiff": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+                }
+            }
+        }"#;
+        let state_override: StateOverride = serde_json::from_str(s).unwrap();
+        let account = &state_override[&Address::from_low_u64_be(0x124)];
+        assert_eq!(account.balance, Some(U256::from(1)));
+        assert!(account.nonce.is_none());
+        assert_eq!(
+            account.state_diff.as_ref().unwrap()[&H256::from_low_u64_be(1)],
+            H256::from_low_u64_be(2)
+        );
+    }
+}
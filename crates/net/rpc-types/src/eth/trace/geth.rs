@@ -0,0 +1,282 @@
+//! Geth tracing types: Ref <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
+
+use reth_primitives::{Address, Bytes, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Options for `debug_traceTransaction` and `debug_traceCall`
+///
+/// See <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-debug#debugtracetransaction>
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDebugTracingOptions {
+    /// Config for the default struct logger.
+    #[serde(default, flatten)]
+    pub config: GethDefaultTracingOptions,
+    /// The built-in tracer to use, if `None` the struct logger is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer: Option<GethDebugBuiltInTracerType>,
+    /// Config specific to the selected built-in tracer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer_config: Option<GethDebugTracerConfig>,
+    /// Timeout for the trace, formatted as a go duration string, e.g. `"5s"` or `"300ms"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+}
+
+/// Default options of the struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDefaultTracingOptions {
+    /// Disables capturing of storage changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_storage: Option<bool>,
+    /// Disables capturing of the stack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_stack: Option<bool>,
+    /// Enables capturing of memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_memory: Option<bool>,
+    /// Enables capturing of the return data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_return_data: Option<bool>,
+    /// Print output during capture end.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<bool>,
+    /// Maximum length of output, but zero means unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// Available built-in tracers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GethDebugBuiltInTracerType {
+    /// Tracks all call frames of the transaction.
+    #[serde(rename = "callTracer")]
+    CallTracer,
+    /// Tracks the state of all accounts touched by the transaction before execution.
+    #[serde(rename = "prestateTracer")]
+    PreStateTracer,
+}
+
+/// Config of the built-in tracers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GethDebugTracerConfig {
+    /// Config of the `callTracer`.
+    CallTracer(CallConfig),
+    /// Config of the `prestateTracer`.
+    PreStateTracer(PreStateConfig),
+}
+
+/// Config of the `callTracer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CallConfig {
+    /// Only trace the top-level call and skip all sub-calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_top_call: Option<bool>,
+    /// Include the emitted logs in the call frames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_log: Option<bool>,
+}
+
+/// Config of the `prestateTracer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PreStateConfig {
+    /// Return the pre and post state of all touched accounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_mode: Option<bool>,
+}
+
+/// The result of a geth trace, depending on the selected tracer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GethTraceFrame {
+    /// Output of the default struct logger.
+    Default(DefaultFrame),
+    /// Output of the `callTracer`.
+    CallTracer(CallFrame),
+    /// Output of the `prestateTracer`.
+    PreStateTracer(PreStateFrame),
+}
+
+impl From<DefaultFrame> for GethTraceFrame {
+    fn from(frame: DefaultFrame) -> Self {
+        GethTraceFrame::Default(frame)
+    }
+}
+
+impl From<CallFrame> for GethTraceFrame {
+    fn from(frame: CallFrame) -> Self {
+        GethTraceFrame::CallTracer(frame)
+    }
+}
+
+impl From<PreStateFrame> for GethTraceFrame {
+    fn from(frame: PreStateFrame) -> Self {
+        GethTraceFrame::PreStateTracer(frame)
+    }
+}
+
+/// The default frame returned by the struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultFrame {
+    /// Whether the transaction failed.
+    pub failed: bool,
+    /// Total gas used by the transaction.
+    pub gas: u64,
+    /// The output of the transaction.
+    pub return_value: Bytes,
+    /// All recorded opcode steps.
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// A single opcode step recorded by the struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    /// Program counter.
+    pub pc: u64,
+    /// Opcode name.
+    pub op: String,
+    /// Remaining gas.
+    pub gas: u64,
+    /// Cost of the opcode.
+    pub gas_cost: u64,
+    /// Call depth.
+    pub depth: u64,
+    /// Error message, if the step failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Current stack, if not disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// Current return data, if enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<Bytes>,
+    /// Current memory in 32 byte words, if enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<String>>,
+    /// Size of the memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mem_size: Option<u64>,
+    /// Storage slots of the current contract that were read or written, if not disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// A call frame returned by the `callTracer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// The type of the call, e.g. `CALL`, `DELEGATECALL` or `CREATE2`.
+    #[serde(rename = "type")]
+    pub typ: String,
+    /// The caller.
+    pub from: Address,
+    /// The callee, `None` if a create failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// Transferred value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// Gas provided to the call.
+    pub gas: U256,
+    /// Gas used by the call.
+    pub gas_used: U256,
+    /// The call data.
+    pub input: Bytes,
+    /// The output of the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// Error message, if the call failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Nested calls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+    /// Logs emitted by this call, only included if `withLog` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<CallLogFrame>,
+}
+
+/// A log emitted inside a [CallFrame].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallLogFrame {
+    /// The emitting contract.
+    pub address: Address,
+    /// The log topics.
+    pub topics: Vec<H256>,
+    /// The log data.
+    pub data: Bytes,
+}
+
+/// The output of the `prestateTracer`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PreStateFrame {
+    /// The state of all touched accounts before the transaction.
+    Default(PreStateMode),
+    /// The pre and post state of all changed accounts.
+    Diff(DiffMode),
+}
+
+/// All touched accounts and their state before the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreStateMode(pub BTreeMap<Address, AccountState>);
+
+/// Pre and post state of all changed accounts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffMode {
+    /// The state before the transaction.
+    pub pre: BTreeMap<Address, AccountState>,
+    /// The state after the transaction.
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+/// The state of a single account as reported by the `prestateTracer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountState {
+    /// Balance of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Code of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Nonce of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Touched storage slots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_tracing_options() {
+        let s =
+            r#"{"tracer": "callTracer", "tracerConfig": {"onlyTopCall": true}, "timeout": "5s"}"#;
+        let opts: GethDebugTracingOptions = serde_json::from_str(s).unwrap();
+        assert_eq!(opts.tracer, Some(GethDebugBuiltInTracerType::CallTracer));
+        assert_eq!(
+            opts.tracer_config,
+            Some(GethDebugTracerConfig::CallTracer(CallConfig {
+                only_top_call: Some(true),
+                with_log: None
+            }))
+        );
+        assert_eq!(opts.timeout.as_deref(), Some("5s"));
+
+        let s = r#"{"disableStorage": true, "enableMemory": true}"#;
+        let opts: GethDebugTracingOptions = serde_json::from_str(s).unwrap();
+        assert!(opts.tracer.is_none());
+        assert_eq!(opts.config.disable_storage, Some(true));
+        assert_eq!(opts.config.enable_memory, Some(true));
+    }
+}
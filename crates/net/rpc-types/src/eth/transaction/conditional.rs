@@ -0,0 +1,54 @@
+use reth_primitives::{Address, H256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Conditions that must hold for a transaction submitted via `eth_sendRawTransactionConditional`
+/// to be accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionConditional {
+    /// Expected storage of accounts the transaction depends on.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub known_accounts: HashMap<Address, KnownAccountState>,
+    /// Minimal block number of the block the transaction is included in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number_min: Option<U64>,
+    /// Maximal block number of the block the transaction is included in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number_max: Option<U64>,
+    /// Minimal timestamp of the block the transaction is included in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_min: Option<U64>,
+    /// Maximal timestamp of the block the transaction is included in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_max: Option<U64>,
+}
+
+/// The expected state of an account, either its storage root or a set of storage slots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KnownAccountState {
+    /// The storage root of the account.
+    StorageRoot(H256),
+    /// The values of individual storage slots.
+    Slots(HashMap<H256, H256>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_transaction_conditional() {
+        let s = r#"{"knownAccounts":{"0x6b3a8798e5fb9fc5603f3ab5ea2e8136694e55d0":"0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563","0x8a8eafb1cf62bfbeb1741769dae1a9dd47996192":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000002"}},"blockNumberMin":"0x1","timestampMax":"0x64"}"#;
+        let conditional: TransactionConditional = serde_json::from_str(s).unwrap();
+        assert_eq!(conditional.known_accounts.len(), 2);
+        assert_eq!(conditional.block_number_min, Some(1u64.into()));
+        assert_eq!(conditional.timestamp_max, Some(100u64.into()));
+        assert!(conditional.block_number_max.is_none());
+
+        let serialized = serde_json::to_string(&conditional).unwrap();
+        let deserialized: TransactionConditional = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(conditional, deserialized);
+    }
+}
@@ -1,13 +1,17 @@
+mod conditional;
 mod receipt;
 mod request;
 mod typed;
 
+pub use conditional::{KnownAccountState, TransactionConditional};
 pub use receipt::TransactionReceipt;
 pub use request::TransactionRequest;
 pub use typed::*;
 
 use reth_primitives::{
-    rpc::transaction::eip2930::AccessListItem, Address, Bytes, H256, H512, U256, U64,
+    rpc::transaction::eip2930::AccessListItem, Address, BlockNumber, Bytes,
+    Transaction as PrimitiveTransaction, TransactionKind, TransactionSignedEcRecovered, H256, H512,
+    U256, U64,
 };
 use serde::{Deserialize, Serialize};
 
@@ -40,6 +44,12 @@ pub struct Transaction {
     /// The miner's tip.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_priority_fee_per_gas: Option<U256>,
+    /// Max fee per unit of blob gas the user is willing to pay, for EIP-4844 transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// The versioned hashes of the blobs of an EIP-4844 transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_versioned_hashes: Option<Vec<H256>>,
     /// Gas
     pub gas: U256,
     /// Data
@@ -67,3 +77,98 @@ pub struct Transaction {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub transaction_type: Option<U256>,
 }
+
+impl Transaction {
+    /// Create a new rpc transaction result for a pending transaction, i.e. one that is not
+    /// included in a block yet.
+    ///
+    /// The gas price of such a transaction is its maximum fee per gas.
+    pub fn from_recovered(tx: TransactionSignedEcRecovered) -> Self {
+        Self::fill(tx, None, None, None, None)
+    }
+
+    /// Create a new rpc transaction result for a transaction of a block, using the block's hash,
+    /// number and base fee, and the index of the transaction in the block.
+    ///
+    /// The hash of a pending block is not final, so `block_hash` is optional.
+    pub fn from_recovered_with_block_context(
+        tx: TransactionSignedEcRecovered,
+        block_hash: Option<H256>,
+        block_number: BlockNumber,
+        base_fee: Option<u64>,
+        tx_index: usize,
+    ) -> Self {
+        Self::fill(tx, block_hash, Some(block_number), base_fee, Some(tx_index))
+    }
+
+    fn fill(
+        tx: TransactionSignedEcRecovered,
+        block_hash: Option<H256>,
+        block_number: Option<BlockNumber>,
+        base_fee: Option<u64>,
+        tx_index: Option<usize>,
+    ) -> Self {
+        let to = match tx.kind() {
+            TransactionKind::Call(to) => Some(*to),
+            TransactionKind::Create => None,
+        };
+
+        let (chain_id, access_list) = match &tx.transaction {
+            PrimitiveTransaction::Legacy(tx) => (tx.chain_id, None),
+            PrimitiveTransaction::Eip2930(tx) => (Some(tx.chain_id), Some(&tx.access_list)),
+            PrimitiveTransaction::Eip1559(tx) => (Some(tx.chain_id), Some(&tx.access_list)),
+            PrimitiveTransaction::Eip4844(tx) => (Some(tx.chain_id), Some(&tx.access_list)),
+        };
+        let max_priority_fee_per_gas = tx.max_priority_fee_per_gas();
+        let max_fee_per_gas = max_priority_fee_per_gas.map(|_| tx.max_fee_per_gas());
+
+        let signature = &tx.signature;
+        let standard_v = signature.odd_y_parity as u64;
+        let v = match &tx.transaction {
+            PrimitiveTransaction::Legacy(tx) => signature.v(tx.chain_id),
+            _ => standard_v,
+        };
+
+        let mut raw = Vec::new();
+        tx.encode_enveloped(&mut raw);
+
+        Self {
+            hash: tx.hash(),
+            nonce: tx.nonce().into(),
+            block_hash,
+            block_number: block_number.map(Into::into),
+            transaction_index: tx_index.map(Into::into),
+            from: tx.signer(),
+            to,
+            value: U256::from(*tx.value()),
+            gas_price: Some(U256::from(tx.effective_gas_price(base_fee))),
+            max_fee_per_gas: max_fee_per_gas.map(U256::from),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.map(U256::from),
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas().map(U256::from),
+            blob_versioned_hashes: tx
+                .max_fee_per_blob_gas()
+                .map(|_| tx.blob_versioned_hashes().to_vec()),
+            gas: tx.gas_limit().into(),
+            input: tx.input().clone(),
+            creates: tx.contract_address(),
+            raw: raw.into(),
+            public_key: None,
+            chain_id: chain_id.map(Into::into),
+            standard_v: standard_v.into(),
+            v: v.into(),
+            r: signature.r,
+            s: signature.s,
+            access_list: access_list.map(|access_list| {
+                access_list
+                    .0
+                    .iter()
+                    .map(|item| AccessListItem {
+                        address: item.address,
+                        storage_keys: item.storage_keys.clone(),
+                    })
+                    .collect()
+            }),
+            transaction_type: Some((tx.tx_type() as u64).into()),
+        }
+    }
+}
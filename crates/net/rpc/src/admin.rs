@@ -0,0 +1,91 @@
+//! `admin` namespace handler implementation.
+
+use crate::result::{internal_rpc_err, invalid_params_rpc_err};
+use async_trait::async_trait;
+use futures::StreamExt;
+use jsonrpsee::{
+    core::{RpcResult as Result, SubscriptionResult},
+    PendingSubscriptionSink, SubscriptionMessage,
+};
+use reth_network::{NetworkEvent, NetworkHandle};
+use reth_primitives::NodeRecord;
+use reth_rpc_api::AdminApiServer;
+use std::{error::Error, fmt, net::SocketAddr, str::FromStr, sync::Arc};
+
+/// Replaces the log filter of the running node.
+pub trait LogFilterReloader: fmt::Debug + Send + Sync {
+    /// Applies the filter directives, like `net=trace,stages=debug`.
+    fn reload(&self, directives: &str) -> std::result::Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// `admin` API implementation.
+///
+/// This type provides the functionality for handling `admin` related requests.
+#[derive(Debug)]
+pub struct AdminApi {
+    /// An interface to interact with the network
+    network: NetworkHandle,
+    /// Changes the log filter, if the node supports it.
+    log_filter: Option<Arc<dyn LogFilterReloader>>,
+}
+
+impl AdminApi {
+    /// Creates a new instance.
+    pub fn new(network: NetworkHandle, log_filter: Option<Arc<dyn LogFilterReloader>>) -> Self {
+        Self { network, log_filter }
+    }
+}
+
+#[async_trait]
+impl AdminApiServer for AdminApi {
+    async fn add_peer(&self, record: String) -> Result<bool> {
+        let node = parse_node_record(&record)?;
+        self.network.add_peer(node.id, SocketAddr::new(node.address, node.tcp_port));
+        Ok(true)
+    }
+
+    async fn remove_peer(&self, record: String) -> Result<bool> {
+        let node = parse_node_record(&record)?;
+        self.network.disconnect_peer(node.id);
+        Ok(true)
+    }
+
+    async fn set_log_filter(&self, filter: String) -> Result<bool> {
+        let log_filter = self
+            .log_filter
+            .as_ref()
+            .ok_or_else(|| internal_rpc_err("log filter can't be changed"))?;
+        log_filter.reload(&filter).map_err(|err| invalid_params_rpc_err(err.to_string()))?;
+        Ok(true)
+    }
+
+    async fn subscribe(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        let sink = pending.accept().await?;
+        let events = self.network.event_listener().filter_map(|event| async move {
+            match event {
+                NetworkEvent::SessionEstablished { peer_id, .. } => {
+                    Some(format!("connected {peer_id}"))
+                }
+                NetworkEvent::SessionClosed { peer_id, reason } => match reason {
+                    Some(reason) => Some(format!("disconnected {peer_id}: {reason}")),
+                    None => Some(format!("disconnected {peer_id}")),
+                },
+                NetworkEvent::PeerAdded(_) | NetworkEvent::PeerRemoved(_) => None,
+            }
+        });
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            let message = SubscriptionMessage::from_json(&event)?;
+            if sink.send(message).await.is_err() {
+                // the subscriber is gone
+                break
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses an enode URL.
+fn parse_node_record(record: &str) -> Result<NodeRecord> {
+    NodeRecord::from_str(record).map_err(|err| invalid_params_rpc_err(err.to_string()))
+}
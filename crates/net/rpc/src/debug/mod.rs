@@ -0,0 +1,369 @@
+//! `debug` namespace handler implementation.
+
+use crate::{
+    eth::revm_utils::{fill_tx_env_with_call, prepare_evm},
+    result::{internal_rpc_err, invalid_params_rpc_err, rpc_err, ToRpcResult},
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult as Result;
+use reth_executor::{
+    executor::execute_with_inspector,
+    inspector::TransactionOutput,
+    revm_wrap::{State, SubState},
+};
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber as RpcBlockNumber, Bytes},
+    BlockHashOrNumber, ChainSpec, TransactionSignedEcRecovered, H256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
+use reth_rlp::Encodable;
+use reth_rpc_api::DebugApiServer;
+use reth_rpc_types::{
+    trace::geth::{
+        DefaultFrame, GethDebugBuiltInTracerType, GethDebugTracerConfig, GethDebugTracingOptions,
+        GethTraceFrame,
+    },
+    CallRequest, RichBlock,
+};
+use revm::{Database, TransactOut, EVM};
+use std::sync::Arc;
+
+mod tracers;
+
+use tracers::{
+    parse_go_duration, trace_prestate, CallTracer, StructLogger, TraceDeadline,
+    DEFAULT_TRACE_TIMEOUT,
+};
+
+/// `debug` API implementation.
+///
+/// This type provides the functionality for handling `debug` related requests.
+#[derive(Debug, Clone)]
+pub struct DebugApi<Client> {
+    /// All nested fields bundled together.
+    inner: Arc<DebugApiInner<Client>>,
+}
+
+impl<Client> DebugApi<Client>
+where
+    Client: BlockProvider +
+        HeaderProvider +
+        ReceiptProvider +
+        StateProviderFactory +
+        TransactionsProvider +
+        'static,
+{
+    /// Creates a new, shareable instance.
+    pub fn new(client: Arc<Client>, chain_spec: ChainSpec) -> Self {
+        let inner = DebugApiInner { client, chain_spec };
+        Self { inner: Arc::new(inner) }
+    }
+
+    /// Re-executes the transaction with the given hash on top of the state of its parent block
+    /// and returns the trace.
+    ///
+    /// The block is executed by the executor up to and including the traced transaction, so the
+    /// pre-block changes like the DAO fork and the EIP-4788 beacon root are applied as well.
+    fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<GethTraceFrame> {
+        let client = &self.inner.client;
+
+        let (_, meta) = client
+            .transaction_by_hash_with_meta(tx_hash)
+            .with_message("failed to read transaction")?
+            .ok_or_else(|| invalid_params_rpc_err("transaction not found"))?;
+        let header = client
+            .header_by_number(meta.block_number)
+            .with_message("failed to read header")?
+            .ok_or_else(|| internal_rpc_err("header not found"))?;
+        let transactions = client
+            .transactions_with_senders_by_block(BlockHashOrNumber::Number(meta.block_number))
+            .with_message("failed to read block transactions")?
+            .ok_or_else(|| internal_rpc_err("block body not found"))?;
+
+        let index = meta.index as usize;
+        if index >= transactions.len() {
+            return Err(internal_rpc_err("transaction not found in block"))
+        }
+
+        let state = client
+            .history_by_block_hash(header.parent_hash)
+            .with_message("failed to read state")?;
+
+        let mut opts = Some(opts);
+        let mut traced = None;
+        let executed = execute_with_inspector(
+            &header,
+            &transactions[..=index],
+            &[],
+            None,
+            &self.inner.chain_spec,
+            SubState::new(State::new(state)),
+            &mut |transaction_index,
+                  _: &TransactionSignedEcRecovered,
+                  evm: &mut EVM<SubState<_>>| {
+                if transaction_index != index {
+                    return evm.transact()
+                }
+                let Some(opts) = opts.take() else { return evm.transact() };
+                match trace(evm, opts) {
+                    Ok((frame, output)) => {
+                        traced = Some(Ok(frame));
+                        output
+                    }
+                    Err(err) => {
+                        traced = Some(Err(err));
+                        evm.transact()
+                    }
+                }
+            },
+        );
+
+        // the block is executed only up to the traced transaction, so the executor rejects its
+        // gas usage once the trace is done
+        match (traced, executed) {
+            (Some(traced), _) => traced,
+            (None, Err(err)) => Err(internal_rpc_err(format!("failed to execute block: {err}"))),
+            (None, Ok(_)) => Err(internal_rpc_err("transaction was not traced")),
+        }
+    }
+
+    /// Executes the call on top of the state of the given block and returns the trace.
+    fn trace_call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> Result<GethTraceFrame> {
+        let client = &self.inner.client;
+
+        let block_hash = client
+            .block_hash_for_id(block_id)
+            .with_message("failed to read block hash")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+        let header = client
+            .header(&block_hash)
+            .with_message("failed to read header")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+
+        let state =
+            client.history_by_block_hash(block_hash).with_message("failed to read state")?;
+        let mut evm = prepare_evm(state, &header, &self.inner.chain_spec);
+        let EVM { env, .. } = &mut evm;
+        fill_tx_env_with_call(&mut env.tx, &mut env.block, request)?;
+
+        trace(&mut evm, opts).map(|(frame, _)| frame)
+    }
+}
+
+#[async_trait]
+impl<Client> DebugApiServer for DebugApi<Client>
+where
+    Client: BlockProvider +
+        HeaderProvider +
+        ReceiptProvider +
+        StateProviderFactory +
+        TransactionsProvider +
+        'static,
+{
+    /// Handler for `debug_getRawHeader`
+    async fn raw_header(&self, block_id: BlockId) -> Result<Bytes> {
+        let client = &self.inner.client;
+        let block_hash = client
+            .block_hash_for_id(block_id)
+            .with_message("failed to read block hash")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+        let header = client
+            .header(&block_hash)
+            .with_message("failed to read header")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+
+        let mut res = Vec::new();
+        header.encode(&mut res);
+        Ok(res.into())
+    }
+
+    /// Handler for `debug_getRawBlock`
+    async fn raw_block(&self, block_id: BlockId) -> Result<Bytes> {
+        let block = self
+            .inner
+            .client
+            .block(block_id)
+            .with_message("failed to read block")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+
+        let mut res = Vec::new();
+        block.encode(&mut res);
+        Ok(res.into())
+    }
+
+    /// Handler for `debug_getRawTransaction`
+    ///
+    /// Returns the EIP-2718 enveloped transaction.
+    async fn raw_transaction(&self, hash: H256) -> Result<Bytes> {
+        let transaction = self
+            .inner
+            .client
+            .transaction_by_hash(hash)
+            .with_message("failed to read transaction")?
+            .ok_or_else(|| invalid_params_rpc_err("transaction not found"))?;
+
+        let mut res = Vec::new();
+        transaction.encode_enveloped(&mut res);
+        Ok(res.into())
+    }
+
+    /// Handler for `debug_getRawReceipts`
+    ///
+    /// Returns the EIP-2718 enveloped receipts of all transactions of the block.
+    async fn raw_receipts(&self, block_id: BlockId) -> Result<Vec<Bytes>> {
+        let client = &self.inner.client;
+        let block_hash = client
+            .block_hash_for_id(block_id)
+            .with_message("failed to read block hash")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+        let receipts = client
+            .receipts_by_block(block_hash.into())
+            .with_message("failed to read receipts")?
+            .ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+
+        Ok(receipts
+            .into_iter()
+            .map(|receipt| {
+                let mut res = Vec::new();
+                receipt.encode_inner(&mut res, false);
+                res.into()
+            })
+            .collect())
+    }
+
+    /// Handler for `debug_getBadBlocks`
+    ///
+    /// The invalid blocks are only remembered by their hash, so they can't be returned.
+    async fn bad_blocks(&self) -> Result<Vec<RichBlock>> {
+        Err(rpc_err(
+            jsonrpsee::types::error::METHOD_NOT_FOUND_CODE,
+            "debug_getBadBlocks is not supported",
+            None,
+        ))
+    }
+
+    /// Handler for `debug_traceTransaction`
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: H256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame> {
+        let this = Self { inner: Arc::clone(&self.inner) };
+        tokio::task::spawn_blocking(move || {
+            this.trace_transaction(tx_hash, opts.unwrap_or_default())
+        })
+        .await
+        .map_err(|err| internal_rpc_err(err.to_string()))?
+    }
+
+    /// Handler for `debug_traceCall`
+    async fn debug_trace_call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame> {
+        let this = Self { inner: Arc::clone(&self.inner) };
+        let block_id = block_number.unwrap_or(BlockId::Number(RpcBlockNumber::Latest));
+        tokio::task::spawn_blocking(move || {
+            this.trace_call(request, block_id, opts.unwrap_or_default())
+        })
+        .await
+        .map_err(|err| internal_rpc_err(err.to_string()))?
+    }
+}
+
+/// Container type `DebugApi`
+#[derive(Debug)]
+struct DebugApiInner<Client> {
+    /// The client that can interact with the chain.
+    client: Arc<Client>,
+    /// The chain spec used to re-execute transactions.
+    chain_spec: ChainSpec,
+}
+
+/// Executes the transaction that is configured in the [EVM] with the tracer selected in the
+/// `opts`.
+///
+/// The state changes of the transaction are not committed, but returned together with the trace.
+fn trace<DB>(
+    evm: &mut EVM<DB>,
+    opts: GethDebugTracingOptions,
+) -> Result<(GethTraceFrame, TransactionOutput)>
+where
+    DB: Database,
+    DB::Error: std::fmt::Debug,
+{
+    let GethDebugTracingOptions { config, tracer, tracer_config, timeout } = opts;
+
+    let timeout = match timeout {
+        Some(timeout) => parse_go_duration(&timeout)
+            .ok_or_else(|| invalid_params_rpc_err(format!("invalid timeout: {timeout}")))?,
+        None => DEFAULT_TRACE_TIMEOUT,
+    };
+    let deadline = TraceDeadline::new(timeout);
+
+    let (frame, output) = match tracer {
+        None => {
+            let mut struct_logs = Vec::new();
+            let (res, state) = evm.inspect(StructLogger::new(config, deadline, &mut struct_logs));
+            let return_value = match res.out {
+                TransactOut::Call(out) | TransactOut::Create(out, _) => out.into(),
+                TransactOut::None => Default::default(),
+            };
+            let frame = DefaultFrame {
+                failed: !matches!(res.exit_reason, revm::return_ok!()),
+                gas: res.gas_used,
+                return_value,
+                struct_logs,
+            };
+            (frame.into(), (res, state))
+        }
+        Some(GethDebugBuiltInTracerType::CallTracer) => {
+            let call_config = match tracer_config {
+                Some(GethDebugTracerConfig::CallTracer(call_config)) => call_config,
+                None => Default::default(),
+                Some(_) => return Err(invalid_params_rpc_err("invalid callTracer config")),
+            };
+            let gas_limit = evm.env.tx.gas_limit;
+            let mut frame = None;
+            let (res, state) = evm.inspect(CallTracer::new(call_config, deadline, &mut frame));
+            let mut frame = frame.unwrap_or_default();
+            // the top-level frame reports the gas of the transaction, including intrinsic gas
+            frame.gas = gas_limit.into();
+            frame.gas_used = res.gas_used.into();
+            (frame.into(), (res, state))
+        }
+        Some(GethDebugBuiltInTracerType::PreStateTracer) => {
+            let diff_mode = match tracer_config {
+                Some(GethDebugTracerConfig::PreStateTracer(prestate_config)) => {
+                    prestate_config.diff_mode.unwrap_or_default()
+                }
+                None => false,
+                Some(_) => return Err(invalid_params_rpc_err("invalid prestateTracer config")),
+            };
+            let (res, state) = evm.transact();
+            let db = evm.db().expect("database is set");
+            let frame = trace_prestate(db, &state, diff_mode)
+                .map_err(|err| internal_rpc_err(format!("failed to read state: {err:?}")))?;
+            (frame.into(), (res, state))
+        }
+    };
+
+    if deadline.is_exceeded() {
+        return Err(internal_rpc_err("execution timeout"))
+    }
+
+    Ok((frame, output))
+}
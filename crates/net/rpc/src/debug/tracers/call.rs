@@ -0,0 +1,177 @@
+//! The geth `callTracer`.
+
+use super::TraceDeadline;
+use reth_primitives::{Bytes, H160, H256, U256};
+use reth_rpc_types::trace::geth::{CallConfig, CallFrame, CallLogFrame};
+use revm::{
+    CallInputs, CallScheme, CreateInputs, CreateScheme, Database, EVMData, Gas, Inspector,
+    Interpreter, Return, B160, B256,
+};
+
+/// An inspector that records the call tree of a transaction.
+#[derive(Debug)]
+pub(crate) struct CallTracer<'a> {
+    /// The config of the tracer.
+    config: CallConfig,
+    /// Aborts execution once exceeded.
+    deadline: TraceDeadline,
+    /// All call frames that are currently being executed.
+    stack: Vec<CallFrame>,
+    /// The finished top-level call frame.
+    result: &'a mut Option<CallFrame>,
+}
+
+impl<'a> CallTracer<'a> {
+    /// Creates a new tracer that writes the top-level call frame to `result`.
+    pub(crate) fn new(
+        config: CallConfig,
+        deadline: TraceDeadline,
+        result: &'a mut Option<CallFrame>,
+    ) -> Self {
+        Self { config, deadline, stack: Vec::new(), result }
+    }
+
+    /// Finishes the current call frame and attaches it to its parent.
+    fn finish_frame(
+        &mut self,
+        ret: Return,
+        gas_limit: u64,
+        remaining_gas: &Gas,
+        out: &bytes::Bytes,
+    ) {
+        let mut frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        frame.gas_used = gas_limit.saturating_sub(remaining_gas.remaining()).into();
+        if !out.is_empty() {
+            frame.output = Some(Bytes::from(out.clone()));
+        }
+        match ret {
+            revm::return_ok!() => {}
+            revm::return_revert!() => frame.error = Some("execution reverted".to_string()),
+            err => frame.error = Some(format!("{err:?}")),
+        }
+
+        if self.config.only_top_call.unwrap_or_default() {
+            frame.calls.clear();
+        }
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => *self.result = Some(frame),
+        }
+    }
+}
+
+impl<'a, DB: Database> Inspector<DB> for CallTracer<'a> {
+    fn step(
+        &mut self,
+        _interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        if self.deadline.is_exceeded() {
+            return Return::FatalExternalError
+        }
+        Return::Continue
+    }
+
+    fn log(
+        &mut self,
+        _evm_data: &mut EVMData<'_, DB>,
+        address: &B160,
+        topics: &[B256],
+        data: &bytes::Bytes,
+    ) {
+        if !self.config.with_log.unwrap_or_default() {
+            return
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.logs.push(CallLogFrame {
+                address: H160(address.0),
+                topics: topics.iter().map(|topic| H256(topic.0)).collect(),
+                data: Bytes::from(data.clone()),
+            });
+        }
+    }
+
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (Return, Gas, bytes::Bytes) {
+        let (typ, from, value) = match inputs.context.scheme {
+            CallScheme::Call => ("CALL", inputs.context.caller, Some(inputs.transfer.value)),
+            CallScheme::CallCode => {
+                ("CALLCODE", inputs.context.address, Some(inputs.transfer.value))
+            }
+            CallScheme::DelegateCall => ("DELEGATECALL", inputs.context.address, None),
+            CallScheme::StaticCall => ("STATICCALL", inputs.context.caller, None),
+        };
+
+        self.stack.push(CallFrame {
+            typ: typ.to_string(),
+            from: H160(from.0),
+            to: Some(H160(inputs.context.code_address.0)),
+            value: value.map(|value| U256(*value.as_limbs())),
+            gas: inputs.gas_limit.into(),
+            input: Bytes::from(inputs.input.clone()),
+            ..Default::default()
+        });
+
+        (Return::Continue, Gas::new(0), bytes::Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: Return,
+        out: bytes::Bytes,
+        _is_static: bool,
+    ) -> (Return, Gas, bytes::Bytes) {
+        self.finish_frame(ret, inputs.gas_limit, &remaining_gas, &out);
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (Return, Option<B160>, Gas, bytes::Bytes) {
+        let typ = match inputs.scheme {
+            CreateScheme::Create => "CREATE",
+            CreateScheme::Create2 { .. } => "CREATE2",
+        };
+
+        self.stack.push(CallFrame {
+            typ: typ.to_string(),
+            from: H160(inputs.caller.0),
+            value: Some(U256(*inputs.value.as_limbs())),
+            gas: inputs.gas_limit.into(),
+            input: Bytes::from(inputs.init_code.clone()),
+            ..Default::default()
+        });
+
+        (Return::Continue, None, Gas::new(0), bytes::Bytes::new())
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &CreateInputs,
+        ret: Return,
+        address: Option<B160>,
+        remaining_gas: Gas,
+        out: bytes::Bytes,
+    ) -> (Return, Option<B160>, Gas, bytes::Bytes) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.to = address.map(|address| H160(address.0));
+        }
+        self.finish_frame(ret, inputs.gas_limit, &remaining_gas, &out);
+        (ret, address, remaining_gas, out)
+    }
+}
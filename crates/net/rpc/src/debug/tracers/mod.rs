@@ -0,0 +1,83 @@
+//! Standard geth tracers implemented as revm inspectors.
+
+use std::time::{Duration, Instant};
+
+mod call;
+mod prestate;
+mod struct_log;
+
+pub(crate) use call::CallTracer;
+pub(crate) use prestate::trace_prestate;
+pub(crate) use struct_log::StructLogger;
+
+/// The default timeout of a trace, same as geth.
+pub(crate) const DEFAULT_TRACE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A deadline after which a running trace is aborted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceDeadline(Instant);
+
+impl TraceDeadline {
+    /// Creates a new deadline that expires after the given timeout.
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    /// Returns true if the deadline is exceeded.
+    pub(crate) fn is_exceeded(&self) -> bool {
+        Instant::now() >= self.0
+    }
+}
+
+/// Parses a go duration string, like `"300ms"`, `"1.5s"` or `"2m30s"`.
+///
+/// See also <https://pkg.go.dev/time#ParseDuration>
+pub(crate) fn parse_go_duration(s: &str) -> Option<Duration> {
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None
+    }
+    if rest == "0" {
+        return Some(Duration::ZERO)
+    }
+
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let num_len = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (num, tail) = rest.split_at(num_len);
+        let value: f64 = num.parse().ok()?;
+
+        let unit_len = tail.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let nanos_per_unit = match unit {
+            "ns" => 1f64,
+            "us" | "µs" => 1e3,
+            "ms" => 1e6,
+            "s" => 1e9,
+            "m" => 60e9,
+            "h" => 3600e9,
+            _ => return None,
+        };
+
+        total += Duration::from_nanos((value * nanos_per_unit) as u64);
+        rest = tail;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_go_duration("5s"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_go_duration("300ms"), Some(Duration::from_millis(300)));
+        assert_eq!(parse_go_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_go_duration("2m30s"), Some(Duration::from_secs(150)));
+        assert_eq!(parse_go_duration("0"), Some(Duration::ZERO));
+        assert_eq!(parse_go_duration(""), None);
+        assert_eq!(parse_go_duration("5"), None);
+        assert_eq!(parse_go_duration("5x"), None);
+    }
+}
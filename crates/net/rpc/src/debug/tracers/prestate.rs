@@ -0,0 +1,79 @@
+//! The geth `prestateTracer`.
+
+use reth_primitives::{Bytes, H160, H256, KECCAK_EMPTY, U256};
+use reth_rpc_types::trace::geth::{AccountState, DiffMode, PreStateFrame, PreStateMode};
+use revm::{Account, AccountInfo, Database, B160, B256};
+use std::collections::BTreeMap;
+
+/// Builds the [PreStateFrame] from the state changes of an executed but not yet committed
+/// transaction.
+///
+/// `db` must still hold the state before the transaction, so the original values of all touched
+/// accounts can be loaded from it.
+pub(crate) fn trace_prestate<'a, DB: Database>(
+    db: &mut DB,
+    state: impl IntoIterator<Item = (&'a B160, &'a Account)>,
+    diff_mode: bool,
+) -> Result<PreStateFrame, DB::Error> {
+    let mut pre = BTreeMap::new();
+    let mut post = BTreeMap::new();
+
+    for (address, account) in state {
+        let info = db.basic(*address)?.unwrap_or_default();
+
+        let mut pre_account = account_state(db, &info)?;
+        let storage = account
+            .storage
+            .iter()
+            .map(|(slot, value)| {
+                (H256(slot.to_be_bytes()), H256(value.original_value().to_be_bytes()))
+            })
+            .collect::<BTreeMap<_, _>>();
+        if !storage.is_empty() {
+            pre_account.storage = Some(storage);
+        }
+
+        if diff_mode {
+            let mut post_account = account_state(db, &account.info)?;
+            let changed_storage = account
+                .storage
+                .iter()
+                .filter(|(_, value)| value.original_value() != value.present_value())
+                .map(|(slot, value)| {
+                    (H256(slot.to_be_bytes()), H256(value.present_value().to_be_bytes()))
+                })
+                .collect::<BTreeMap<_, _>>();
+            if !changed_storage.is_empty() {
+                post_account.storage = Some(changed_storage);
+            }
+            if post_account != pre_account || account.is_destroyed {
+                post.insert(H160(address.0), post_account);
+            }
+        }
+
+        pre.insert(H160(address.0), pre_account);
+    }
+
+    if diff_mode {
+        Ok(PreStateFrame::Diff(DiffMode { pre, post }))
+    } else {
+        Ok(PreStateFrame::Default(PreStateMode(pre)))
+    }
+}
+
+/// Converts the revm [AccountInfo] into the [AccountState] without storage.
+fn account_state<DB: Database>(db: &mut DB, info: &AccountInfo) -> Result<AccountState, DB::Error> {
+    let code = match &info.code {
+        Some(code) => Some(Bytes::from(code.original_bytes())),
+        None if info.code_hash != B256(KECCAK_EMPTY.0) => {
+            Some(Bytes::from(db.code_by_hash(info.code_hash)?.original_bytes()))
+        }
+        None => None,
+    };
+    Ok(AccountState {
+        balance: Some(U256(*info.balance.as_limbs())),
+        code: code.filter(|code| !code.is_empty()),
+        nonce: Some(info.nonce),
+        storage: None,
+    })
+}
@@ -0,0 +1,150 @@
+//! The default geth struct/opcode logger.
+
+use super::TraceDeadline;
+use reth_primitives::{Bytes, H256, U256};
+use reth_rpc_types::trace::geth::{GethDefaultTracingOptions, StructLog};
+use revm::{opcode, Database, EVMData, Inspector, Interpreter, Return, B160};
+use std::collections::{BTreeMap, HashMap};
+
+/// An inspector that records every executed opcode as a [StructLog].
+#[derive(Debug)]
+pub(crate) struct StructLogger<'a> {
+    /// The config of the logger.
+    config: GethDefaultTracingOptions,
+    /// Aborts execution once exceeded.
+    deadline: TraceDeadline,
+    /// All recorded steps.
+    logs: &'a mut Vec<StructLog>,
+    /// Indices of the steps that are currently being executed, one for each depth, and the slot
+    /// if the step is an `SLOAD`. `None` if the step was not recorded because the limit was hit.
+    ///
+    /// The gas cost of a step and the loaded value are only known after the step was executed.
+    pending: Vec<Option<(usize, Option<H256>)>>,
+    /// All storage slots that were accessed so far, per contract.
+    storage: HashMap<B160, BTreeMap<H256, H256>>,
+}
+
+impl<'a> StructLogger<'a> {
+    /// Creates a new logger that writes all steps to `logs`.
+    pub(crate) fn new(
+        config: GethDefaultTracingOptions,
+        deadline: TraceDeadline,
+        logs: &'a mut Vec<StructLog>,
+    ) -> Self {
+        Self { config, deadline, logs, pending: Vec::new(), storage: HashMap::new() }
+    }
+
+    fn stack_enabled(&self) -> bool {
+        !self.config.disable_stack.unwrap_or_default()
+    }
+
+    fn storage_enabled(&self) -> bool {
+        !self.config.disable_storage.unwrap_or_default()
+    }
+
+    fn memory_enabled(&self) -> bool {
+        self.config.enable_memory.unwrap_or_default()
+    }
+
+    fn return_data_enabled(&self) -> bool {
+        self.config.enable_return_data.unwrap_or_default()
+    }
+
+    /// Returns true if the configured limit of recorded steps is reached.
+    fn is_limit_reached(&self) -> bool {
+        matches!(self.config.limit, Some(limit) if limit > 0 && self.logs.len() as u64 >= limit)
+    }
+}
+
+impl<'a, DB: Database> Inspector<DB> for StructLogger<'a> {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        if self.deadline.is_exceeded() {
+            return Return::FatalExternalError
+        }
+        if self.is_limit_reached() {
+            self.pending.push(None);
+            return Return::Continue
+        }
+
+        let op = interp.current_opcode();
+        let stack = self.stack_enabled().then(|| {
+            interp.stack.data().iter().map(|value| U256(*value.as_limbs())).collect::<Vec<_>>()
+        });
+        let memory = self
+            .memory_enabled()
+            .then(|| interp.memory.data().chunks(32).map(hex::encode).collect::<Vec<_>>());
+        let return_data =
+            self.return_data_enabled().then(|| Bytes::from(interp.return_data_buffer.clone()));
+
+        let mut sload_slot = None;
+        let storage = if self.storage_enabled() {
+            let contract = interp.contract.address;
+            if op == opcode::SSTORE {
+                // SSTORE writes are known before the step is executed
+                if let (Ok(key), Ok(value)) = (interp.stack.peek(0), interp.stack.peek(1)) {
+                    self.storage
+                        .entry(contract)
+                        .or_default()
+                        .insert(H256(key.to_be_bytes()), H256(value.to_be_bytes()));
+                }
+            } else if op == opcode::SLOAD {
+                // SLOAD reads are recorded in `step_end`
+                sload_slot = interp.stack.peek(0).ok().map(|key| H256(key.to_be_bytes()));
+            }
+            (op == opcode::SSTORE || op == opcode::SLOAD)
+                .then(|| self.storage.get(&contract).cloned().unwrap_or_default())
+        } else {
+            None
+        };
+
+        self.pending.push(Some((self.logs.len(), sload_slot)));
+        self.logs.push(StructLog {
+            pc: interp.program_counter() as u64,
+            op: opcode::OPCODE_JUMPMAP[op as usize].unwrap_or("INVALID").to_string(),
+            gas: interp.gas.remaining(),
+            gas_cost: 0,
+            depth: data.journaled_state.depth() as u64,
+            error: None,
+            stack,
+            return_data,
+            mem_size: memory.as_ref().map(|_| interp.memory.len() as u64),
+            memory,
+            storage,
+        });
+
+        Return::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+        eval: Return,
+    ) -> Return {
+        let (idx, sload_slot) = match self.pending.pop().flatten() {
+            Some(pending) => pending,
+            None => return Return::Continue,
+        };
+
+        let log = &mut self.logs[idx];
+        log.gas_cost = log.gas.saturating_sub(interp.gas.remaining());
+
+        if !matches!(eval, revm::return_ok!()) {
+            log.error = Some(format!("{eval:?}"));
+        }
+
+        if let (Some(slot), Ok(value)) = (sload_slot, interp.stack.peek(0)) {
+            let storage = self.storage.entry(interp.contract.address).or_default();
+            storage.insert(slot, H256(value.to_be_bytes()));
+            log.storage = Some(storage.clone());
+        }
+
+        Return::Continue
+    }
+}
@@ -1,6 +1,6 @@
-use crate::result::rpc_err;
+use crate::result::{internal_rpc_err, invalid_params_rpc_err, rpc_err};
 use async_trait::async_trait;
-use jsonrpsee::core::{Error, RpcResult as Result};
+use jsonrpsee::core::RpcResult as Result;
 use reth_consensus::engine::{EngineApiError, EngineApiResult, EngineMessage};
 use reth_interfaces::consensus::ForkchoiceState;
 use reth_primitives::H64;
@@ -26,15 +26,26 @@ impl std::fmt::Debug for EngineApi {
 }
 
 impl EngineApi {
+    /// Creates a new API that forwards the requests to the consensus engine.
+    pub fn new(engine_tx: UnboundedSender<EngineMessage>) -> Self {
+        Self { engine_tx }
+    }
+
     async fn delegate_request<T>(
         &self,
         msg: EngineMessage,
         rx: Receiver<EngineApiResult<T>>,
     ) -> Result<T> {
         let _ = self.engine_tx.send(msg);
-        rx.await.map_err(|err| Error::Custom(err.to_string()))?.map_err(|err| {
+        rx.await.map_err(|err| internal_rpc_err(err.to_string()))?.map_err(|err| {
             let code = match err {
                 EngineApiError::PayloadUnknown => -38001,
+                // Invalid payload attributes
+                EngineApiError::PayloadTimestamp { .. } => -38003,
+                EngineApiError::UnsupportedFork { .. } => -38005,
+                EngineApiError::PayloadWithdrawals { .. } => {
+                    jsonrpsee::types::error::INVALID_PARAMS_CODE
+                }
                 // Any other server error
                 _ => jsonrpsee::types::error::INTERNAL_ERROR_CODE,
             };
@@ -48,13 +59,17 @@ impl EngineApiServer for EngineApi {
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_newpayloadv1>
     /// Caution: This should not accept the `withdrawals` field
     async fn new_payload_v1(&self, payload: ExecutionPayload) -> Result<PayloadStatus> {
+        if payload.withdrawals.is_some() {
+            return Err(invalid_params_rpc_err("withdrawals are not supported by V1"))
+        }
         let (tx, rx) = oneshot::channel();
         self.delegate_request(EngineMessage::NewPayload(payload, tx), rx).await
     }
 
-    /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_newpayloadv1>
-    async fn new_payload_v2(&self, _payload: ExecutionPayload) -> Result<PayloadStatus> {
-        todo!()
+    /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/shanghai.md#engine_newpayloadv2>
+    async fn new_payload_v2(&self, payload: ExecutionPayload) -> Result<PayloadStatus> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(EngineMessage::NewPayload(payload, tx), rx).await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_forkchoiceUpdatedV1>
@@ -65,6 +80,10 @@ impl EngineApiServer for EngineApi {
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
     ) -> Result<ForkchoiceUpdated> {
+        if payload_attributes.as_ref().map_or(false, |attributes| attributes.withdrawals.is_some())
+        {
+            return Err(invalid_params_rpc_err("withdrawals are not supported by V1"))
+        }
         let (tx, rx) = oneshot::channel();
         self.delegate_request(
             EngineMessage::ForkchoiceUpdated(fork_choice_state, payload_attributes, tx),
@@ -76,10 +95,15 @@ impl EngineApiServer for EngineApi {
     /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/specification.md#engine_forkchoiceupdatedv2>
     async fn fork_choice_updated_v2(
         &self,
-        _fork_choice_state: ForkchoiceState,
-        _payload_attributes: Option<PayloadAttributes>,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
     ) -> Result<ForkchoiceUpdated> {
-        todo!()
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(
+            EngineMessage::ForkchoiceUpdated(fork_choice_state, payload_attributes, tx),
+            rx,
+        )
+        .await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_getPayloadV1>
@@ -91,8 +115,9 @@ impl EngineApiServer for EngineApi {
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/specification.md#engine_getpayloadv2>
-    async fn get_payload_v2(&self, _payload_id: H64) -> Result<ExecutionPayload> {
-        todo!()
+    async fn get_payload_v2(&self, payload_id: H64) -> Result<ExecutionPayload> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(EngineMessage::GetPayload(payload_id, tx), rx).await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_exchangeTransitionConfigurationV1>
@@ -0,0 +1,188 @@
+//! An inspector that collects the accounts and storage slots accessed by a transaction.
+
+use reth_primitives::{
+    rpc::transaction::eip2930::{AccessList, AccessListItem},
+    H160, H256,
+};
+use revm::{
+    opcode, CreateInputs, Database, EVMData, Gas, Inspector, Interpreter, Return, B160,
+    U256 as RevmU256,
+};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// The accessed storage slots, per account.
+pub(crate) type AccessSet = BTreeMap<B160, BTreeSet<RevmU256>>;
+
+/// Converts the [AccessSet] into the representation of the [revm::TxEnv].
+pub(crate) fn to_revm_access_list(access_set: &AccessSet) -> Vec<(B160, Vec<RevmU256>)> {
+    access_set.iter().map(|(address, slots)| (*address, slots.iter().copied().collect())).collect()
+}
+
+/// Converts the [AccessSet] into an [AccessList].
+pub(crate) fn to_access_list(access_set: AccessSet) -> AccessList {
+    AccessList(
+        access_set
+            .into_iter()
+            .map(|(address, slots)| AccessListItem {
+                address: H160(address.0),
+                storage_keys: slots.into_iter().map(|slot| H256(slot.to_be_bytes())).collect(),
+            })
+            .collect(),
+    )
+}
+
+/// An inspector that adds all accounts and storage slots that are accessed during execution to
+/// an [AccessSet].
+///
+/// Accounts that are warm anyway, like the sender, the recipient and precompiles, are not added on
+/// their own, but the storage slots they access are, like geth does.
+#[derive(Debug)]
+pub(crate) struct AccessListInspector<'a> {
+    /// Accounts that are only added to the access list with the storage slots they access.
+    excluded: &'a HashSet<B160>,
+    /// The collected access list.
+    access_set: &'a mut AccessSet,
+}
+
+impl<'a> AccessListInspector<'a> {
+    /// Creates a new inspector that adds all accessed accounts and slots to `access_set`.
+    pub(crate) fn new(excluded: &'a HashSet<B160>, access_set: &'a mut AccessSet) -> Self {
+        Self { excluded, access_set }
+    }
+
+    /// Adds the account to the access list, unless it is excluded.
+    fn insert_account(&mut self, address: B160) {
+        if !self.excluded.contains(&address) && !is_precompile(&address) {
+            self.access_set.entry(address).or_default();
+        }
+    }
+}
+
+impl<'a, DB: Database> Inspector<DB> for AccessListInspector<'a> {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.access_set.entry(interp.contract.address).or_default().insert(slot);
+                }
+            }
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::EXTCODESIZE |
+            opcode::BALANCE |
+            opcode::SELFDESTRUCT => {
+                if let Ok(address) = interp.stack.peek(0) {
+                    self.insert_account(to_address(address));
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                if let Ok(address) = interp.stack.peek(1) {
+                    self.insert_account(to_address(address));
+                }
+            }
+            _ => {}
+        }
+
+        Return::Continue
+    }
+
+    fn create_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: Return,
+        address: Option<B160>,
+        remaining_gas: Gas,
+        out: bytes::Bytes,
+    ) -> (Return, Option<B160>, Gas, bytes::Bytes) {
+        // the contract created by the transaction itself is warm
+        if let Some(address) = address.filter(|_| data.journaled_state.depth() == 0) {
+            self.access_set.remove(&address);
+        }
+        (ret, address, remaining_gas, out)
+    }
+}
+
+/// Converts a stack value into an address.
+fn to_address(value: RevmU256) -> B160 {
+    let bytes: [u8; 32] = value.to_be_bytes();
+    B160::from_slice(&bytes[12..])
+}
+
+/// Returns true if the address is one of the precompiles `0x01..=0x09`.
+fn is_precompile(address: &B160) -> bool {
+    address.0[..19].iter().all(|byte| *byte == 0) && (1..=9).contains(&address.0[19])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex_literal::hex, keccak256};
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        AccountInfo, Bytecode, TransactTo, B256, EVM,
+    };
+
+    #[test]
+    fn precompile_addresses() {
+        assert!(is_precompile(&B160::from_low_u64_be(1)));
+        assert!(is_precompile(&B160::from_low_u64_be(9)));
+        assert!(!is_precompile(&B160::from_low_u64_be(0)));
+        assert!(!is_precompile(&B160::from_low_u64_be(10)));
+        assert!(!is_precompile(&B160::from_low_u64_be(0x0100)));
+    }
+
+    #[test]
+    fn convert_access_set() {
+        let mut access_set = AccessSet::new();
+        access_set.entry(B160::from_low_u64_be(0x10)).or_default().insert(RevmU256::from(2u64));
+        access_set.entry(B160::from_low_u64_be(0x20)).or_default();
+
+        let access_list = to_access_list(access_set.clone());
+        assert_eq!(access_list.0.len(), 2);
+        assert_eq!(access_list.0[0].storage_keys, vec![H256::from_low_u64_be(2)]);
+        assert!(access_list.0[1].storage_keys.is_empty());
+        assert_eq!(to_revm_access_list(&access_set)[0].1, vec![RevmU256::from(2u64)]);
+    }
+
+    #[test]
+    fn storage_of_excluded_accounts() {
+        let sender = B160::from_low_u64_be(0x10);
+        let contract = B160::from_low_u64_be(0x20);
+        // PUSH1 0x01 PUSH1 0x00 SSTORE PUSH1 0x02 SLOAD STOP
+        let code = bytes::Bytes::from_static(&hex!("600160005560025400"));
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code_hash: B256(keccak256(&code).0),
+                code: Some(Bytecode::new_raw(code)),
+                ..Default::default()
+            },
+        );
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = sender;
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+
+        let excluded = HashSet::from([sender, contract]);
+        let mut access_set = AccessSet::new();
+        let (res, _) = evm.inspect(AccessListInspector::new(&excluded, &mut access_set));
+        assert!(matches!(res.exit_reason, revm::return_ok!()));
+
+        let access_list = to_access_list(access_set);
+        assert_eq!(access_list.0.len(), 1);
+        assert_eq!(access_list.0[0].address, H160(contract.0));
+        assert_eq!(
+            access_list.0[0].storage_keys,
+            vec![H256::from_low_u64_be(0), H256::from_low_u64_be(2)]
+        );
+    }
+}
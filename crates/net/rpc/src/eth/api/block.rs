@@ -0,0 +1,80 @@
+//! Contains RPC handler implementations for fetching blocks.
+
+use crate::{eth::api::EthApi, result::ToRpcResult};
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    Block,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory, TransactionsProvider};
+use reth_rpc_types::RichBlock;
+use reth_transaction_pool::TransactionPool;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + TransactionsProvider + 'static,
+{
+    /// Returns the block with the given id, where the `pending` tag resolves to the pending
+    /// block.
+    ///
+    /// If `full` is set the block contains the full transactions, otherwise only their hashes.
+    /// Returns `None` if the block is not found.
+    pub(crate) fn rich_block(&self, block_id: BlockId, full: bool) -> Result<Option<RichBlock>> {
+        let client = self.client();
+
+        if block_id == BlockId::Number(BlockNumber::Pending) {
+            let (block, senders) = self.pending_block()?.block.into_components();
+            let parent_td = client
+                .header_td(&block.parent_hash)
+                .with_message("failed to read total difficulty")?
+                .unwrap_or_default();
+            let total_difficulty = parent_td + block.difficulty;
+            let block = Block {
+                header: block.header.unseal(),
+                body: block.body,
+                ommers: block.ommers.into_iter().map(|ommer| ommer.unseal()).collect(),
+                withdrawals: block.withdrawals,
+            };
+            // the hash of the pending block is not final
+            let block =
+                reth_rpc_types::Block::from_block(block, senders, None, total_difficulty, full);
+            return Ok(Some(block.into()))
+        }
+
+        let block_hash =
+            match client.block_hash_for_id(block_id).with_message("failed to read block hash")? {
+                Some(hash) => hash,
+                None => return Ok(None),
+            };
+        let block =
+            match client.block(BlockId::Hash(block_hash)).with_message("failed to read block")? {
+                Some(block) => block,
+                None => return Ok(None),
+            };
+        let total_difficulty = client
+            .header_td(&block_hash)
+            .with_message("failed to read total difficulty")?
+            .unwrap_or_default();
+        let senders = if full {
+            client
+                .transactions_with_senders_by_block(block_hash.into())
+                .with_message("failed to read transactions")?
+                .unwrap_or_default()
+                .iter()
+                .map(|transaction| transaction.signer())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let block = reth_rpc_types::Block::from_block(
+            block,
+            senders,
+            Some(block_hash),
+            total_difficulty,
+            full,
+        );
+        Ok(Some(block.into()))
+    }
+}
@@ -0,0 +1,197 @@
+//! Contains RPC handler implementations for executing calls.
+
+use crate::{
+    eth::{
+        access_list::{to_access_list, to_revm_access_list, AccessListInspector, AccessSet},
+        api::EthApi,
+        revm_utils::{
+            apply_block_overrides, apply_state_overrides, fill_tx_env_with_call, prepare_evm,
+        },
+    },
+    result::{internal_rpc_err, invalid_params_rpc_err, rpc_err, ToRpcResult},
+};
+use jsonrpsee::{core::RpcResult as Result, types::error::CALL_EXECUTION_FAILED_CODE};
+use reth_executor::state::ChainStateProvider;
+use reth_primitives::{
+    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId},
+    Bytes,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
+use reth_rpc_types::{BlockOverrides, CallRequest, EthCallResponse, StateOverride};
+use reth_transaction_pool::TransactionPool;
+use revm::{Return, TransactOut, TransactTo, EVM};
+use std::{collections::HashSet, sync::Arc};
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Creates the access list of the call on top of the state of the given block.
+    ///
+    /// The call is executed repeatedly, each time with the access list of the previous run
+    /// applied, until the access list doesn't change anymore. The returned gas is the gas used by
+    /// the call with the final access list applied.
+    pub(crate) async fn create_access_list(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+    ) -> Result<AccessListWithGasUsed> {
+        let this = Self { inner: Arc::clone(&self.inner) };
+        tokio::task::spawn_blocking(move || this.create_access_list_blocking(request, block_id))
+            .await
+            .map_err(|err| internal_rpc_err(err.to_string()))?
+    }
+
+    /// Executes the call on top of the state of the given block and returns its output.
+    ///
+    /// The overrides are applied before the call. A call that fails or reverts is an error, with
+    /// the revert data as its data.
+    pub(crate) async fn call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<Bytes> {
+        let mut responses =
+            self.call_many(vec![request], block_id, state_overrides, block_overrides).await?;
+        let EthCallResponse { value, error, .. } = responses.remove(0);
+        match error {
+            Some(error) => Err(rpc_err(
+                CALL_EXECUTION_FAILED_CODE,
+                error,
+                value.as_ref().map(|value| value.as_ref()),
+            )),
+            None => Ok(value.unwrap_or_default()),
+        }
+    }
+
+    /// Executes the calls in order on top of the state of the given block.
+    ///
+    /// Every call sees the state changes of the preceding calls. The overrides are applied before
+    /// the first call. A failing call doesn't abort the bundle, its error is part of its response.
+    pub(crate) async fn call_many(
+        &self,
+        calls: Vec<CallRequest>,
+        block_id: BlockId,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<Vec<EthCallResponse>> {
+        let this = Self { inner: Arc::clone(&self.inner) };
+        tokio::task::spawn_blocking(move || {
+            this.call_many_blocking(calls, block_id, state_overrides, block_overrides)
+        })
+        .await
+        .map_err(|err| internal_rpc_err(err.to_string()))?
+    }
+
+    fn call_many_blocking(
+        &self,
+        calls: Vec<CallRequest>,
+        block_id: BlockId,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<Vec<EthCallResponse>> {
+        let block =
+            self.block_state(block_id)?.ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+        let parent = self
+            .client()
+            .history_by_block_hash(block.base_hash)
+            .with_message("failed to read state")?;
+        let state = ChainStateProvider::new(&block.changes, parent);
+
+        let mut evm = prepare_evm(state, &block.header, &self.inner.config.chain_spec);
+        if let Some(state_overrides) = state_overrides {
+            let db = evm.db().expect("database is set");
+            apply_state_overrides(state_overrides, db)?;
+        }
+        if let Some(block_overrides) = block_overrides {
+            apply_block_overrides(block_overrides, &mut evm.env.block);
+        }
+        // calls without fees disable the base fee, which must not leak into the following calls
+        let block_env = evm.env.block.clone();
+
+        let mut responses = Vec::with_capacity(calls.len());
+        for request in calls {
+            evm.env.block = block_env.clone();
+            let EVM { env, .. } = &mut evm;
+            fill_tx_env_with_call(&mut env.tx, &mut env.block, request)?;
+
+            let res = evm.transact_commit();
+            let out = match res.out {
+                TransactOut::Call(out) | TransactOut::Create(out, _) => out,
+                TransactOut::None => Default::default(),
+            };
+            let (value, error) = match res.exit_reason {
+                revm::return_ok!() => (Some(out.into()), None),
+                revm::return_revert!() => {
+                    (Some(out.into()), Some("execution reverted".to_string()))
+                }
+                Return::FatalExternalError => return Err(internal_rpc_err("failed to read state")),
+                err => (None, Some(format!("execution failed: {err:?}"))),
+            };
+            responses.push(EthCallResponse { value, error, gas_used: res.gas_used.into() });
+        }
+
+        Ok(responses)
+    }
+
+    fn create_access_list_blocking(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+    ) -> Result<AccessListWithGasUsed> {
+        let block =
+            self.block_state(block_id)?.ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+        let parent = self
+            .client()
+            .history_by_block_hash(block.base_hash)
+            .with_message("failed to read state")?;
+        let state = ChainStateProvider::new(&block.changes, parent);
+
+        let mut evm = prepare_evm(state, &block.header, &self.inner.config.chain_spec);
+        let EVM { env, .. } = &mut evm;
+        fill_tx_env_with_call(&mut env.tx, &mut env.block, request)?;
+
+        // the sender and the recipient are always warm
+        let mut excluded = HashSet::from([evm.env.tx.caller]);
+        if let TransactTo::Call(to) = evm.env.tx.transact_to {
+            excluded.insert(to);
+        }
+
+        let mut access_set = AccessSet::new();
+        for (address, slots) in evm.env.tx.access_list.drain(..) {
+            access_set.entry(address).or_default().extend(slots);
+        }
+
+        loop {
+            let applied = access_set.clone();
+            evm.env.tx.access_list = to_revm_access_list(&applied);
+
+            let (res, _) = evm.inspect(AccessListInspector::new(&excluded, &mut access_set));
+            match res.exit_reason {
+                revm::return_ok!() => {}
+                revm::return_revert!() => {
+                    return Err(rpc_err(CALL_EXECUTION_FAILED_CODE, "execution reverted", None))
+                }
+                Return::FatalExternalError => return Err(internal_rpc_err("failed to read state")),
+                err => {
+                    return Err(rpc_err(
+                        CALL_EXECUTION_FAILED_CODE,
+                        format!("execution failed: {err:?}"),
+                        None,
+                    ))
+                }
+            }
+
+            // the access list only grows, so it's stable once a run didn't add anything
+            if access_set == applied {
+                return Ok(AccessListWithGasUsed {
+                    access_list: to_access_list(access_set),
+                    gas_used: res.gas_used.into(),
+                })
+            }
+        }
+    }
+}
@@ -1,13 +1,23 @@
 //! Provides everything related to `eth_` namespace
 
+use parking_lot::Mutex;
 use reth_interfaces::Result;
-use reth_primitives::U64;
-use reth_provider::{BlockProvider, ChainInfo, StateProviderFactory};
-use reth_rpc_types::Transaction;
+use reth_network::{transactions::TransactionsHandle, NetworkHandle};
+use reth_primitives::{BlockNumber, ChainSpec, MAINNET, U64};
+use reth_provider::{
+    BlockProvider, ChainInfo, StageCheckpointProvider, StateCache, StateProviderFactory,
+};
+use reth_rpc_types::{SyncInfo, SyncStatus};
 use reth_transaction_pool::TransactionPool;
 use std::sync::Arc;
 
+mod block;
+mod call;
+mod pending_block;
+mod receipts;
 mod server;
+mod state;
+mod transactions;
 
 /// `Eth` API trait.
 ///
@@ -21,6 +31,9 @@ pub trait EthApiSpec: Send + Sync {
 
     /// Returns client chain info
     fn chain_info(&self) -> Result<ChainInfo>;
+
+    /// Returns the sync status of the node
+    fn sync_status(&self) -> Result<SyncStatus>;
 }
 
 /// `Eth` API implementation.
@@ -43,8 +56,31 @@ where
     Client: BlockProvider + StateProviderFactory + 'static,
 {
     /// Creates a new, shareable instance.
-    pub fn new(client: Arc<Client>, pool: Pool) -> Self {
-        let inner = EthApiInner { client, pool };
+    pub fn new(
+        client: Arc<Client>,
+        pool: Pool,
+        network: NetworkHandle,
+        transactions: TransactionsHandle,
+    ) -> Self {
+        Self::with_config(client, pool, network, transactions, Default::default())
+    }
+
+    /// Creates a new, shareable instance with the given config.
+    pub fn with_config(
+        client: Arc<Client>,
+        pool: Pool,
+        network: NetworkHandle,
+        transactions: TransactionsHandle,
+        config: EthApiConfig,
+    ) -> Self {
+        let inner = EthApiInner {
+            client,
+            pool,
+            network,
+            transactions,
+            config,
+            sync_starting_block: Default::default(),
+        };
         Self { inner: Arc::new(inner) }
     }
 
@@ -52,12 +88,27 @@ where
     fn client(&self) -> &Arc<Client> {
         &self.inner.client
     }
+
+    /// Returns the inner `Pool`
+    fn pool(&self) -> &Pool {
+        &self.inner.pool
+    }
+
+    /// Returns the handle to the network
+    fn network(&self) -> &NetworkHandle {
+        &self.inner.network
+    }
+
+    /// Returns the handle to the transactions task of the network
+    fn transactions_handle(&self) -> &TransactionsHandle {
+        &self.inner.transactions
+    }
 }
 
 impl<Pool, Client> EthApiSpec for EthApi<Pool, Client>
 where
-    Pool: TransactionPool<Transaction = Transaction> + Clone + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Pool: TransactionPool + Clone + 'static,
+    Client: BlockProvider + StageCheckpointProvider + StateProviderFactory + 'static,
 {
     /// Returns the current ethereum protocol version.
     ///
@@ -75,6 +126,37 @@ where
     fn chain_info(&self) -> Result<ChainInfo> {
         self.client().chain_info()
     }
+
+    /// Returns the sync status, derived from the stage checkpoints and the highest block seen on
+    /// the network.
+    ///
+    /// The node is fully synced up to the checkpoint of the stage that is furthest behind.
+    fn sync_status(&self) -> Result<SyncStatus> {
+        let checkpoints = self.client().stage_checkpoints()?;
+        let current_block =
+            checkpoints.iter().map(|(_, progress)| *progress).min().unwrap_or_default();
+        let highest_block = checkpoints
+            .iter()
+            .map(|(_, progress)| *progress)
+            .max()
+            .unwrap_or_default()
+            .max(self.network().highest_observed_block());
+
+        let mut sync_starting_block = self.inner.sync_starting_block.lock();
+        if current_block >= highest_block {
+            *sync_starting_block = None;
+            return Ok(SyncStatus::None)
+        }
+        let starting_block = *sync_starting_block.get_or_insert(current_block);
+
+        Ok(SyncStatus::Info(SyncInfo {
+            starting_block: starting_block.into(),
+            current_block: current_block.into(),
+            highest_block: highest_block.into(),
+            warp_chunks_amount: None,
+            warp_chunks_processed: None,
+        }))
+    }
 }
 
 /// Container type `EthApi`
@@ -84,5 +166,36 @@ struct EthApiInner<Pool, Client> {
     pool: Pool,
     /// The client that can interact with the chain.
     client: Arc<Client>,
-    // TODO needs network access to handle things like `eth_syncing`
+    /// Handle to the network.
+    network: NetworkHandle,
+    /// Handle to the transactions task, used to propagate local transactions.
+    transactions: TransactionsHandle,
+    /// Settings of the `eth` namespace.
+    config: EthApiConfig,
+    /// The block at which the current sync started, if the node is syncing.
+    ///
+    /// This is recorded when the sync status is first requested during a sync.
+    sync_starting_block: Mutex<Option<BlockNumber>>,
+}
+
+/// Settings for the [EthApi].
+#[derive(Debug, Clone)]
+pub struct EthApiConfig {
+    /// Whether `eth_sendRawTransactionConditional` is enabled.
+    pub allow_conditional_transactions: bool,
+    /// The chain spec used to execute calls.
+    pub chain_spec: ChainSpec,
+    /// The cache of the state at the tip the pending block is built on, shared with the
+    /// execution of the blocks.
+    pub state_cache: Option<Arc<StateCache>>,
+}
+
+impl Default for EthApiConfig {
+    fn default() -> Self {
+        Self {
+            allow_conditional_transactions: false,
+            chain_spec: MAINNET.clone(),
+            state_cache: None,
+        }
+    }
 }
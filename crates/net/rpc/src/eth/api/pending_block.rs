@@ -0,0 +1,203 @@
+//! Contains the speculative pending block that is built from the transaction pool.
+
+use crate::{
+    eth::{api::EthApi, revm_utils::prepare_evm},
+    result::{internal_rpc_err, ToRpcResult},
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_consensus::verification::calculate_next_block_base_fee;
+use reth_executor::{
+    executor::{block_reward_changeset, commit_changes, ExecutionResult, TransactionChangeSet},
+    revm_wrap,
+    state::ChainState,
+};
+use reth_primitives::{
+    bloom::{logs_bloom, receipts_bloom},
+    proofs,
+    rpc::{BlockId, BlockNumber},
+    Hardfork, Header, IntoRecoveredTransaction, Log, Receipt, SealedBlock, SealedBlockWithSenders,
+    TransactionSigned, H160, H256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProvider, StateProviderFactory};
+use reth_transaction_pool::{BestTransactions, PoolTransaction, TransactionPool};
+use revm::Return;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A speculative block on top of the latest block, built from the best transactions of the pool.
+///
+/// The state root of the block is not computed, so its hash is not final either.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingBlock {
+    /// The block with the senders of its transactions.
+    pub(crate) block: SealedBlockWithSenders,
+    /// The receipts of the transactions of the block.
+    pub(crate) receipts: Vec<Receipt>,
+    /// The state changes of the block on top of the latest block.
+    pub(crate) state: ChainState,
+}
+
+/// The header of a block and the state after it, as changes on top of the state of a canonical
+/// block.
+#[derive(Debug)]
+pub(crate) struct BlockState {
+    /// The header of the block.
+    pub(crate) header: Header,
+    /// The hash of the canonical block the changes build on.
+    pub(crate) base_hash: H256,
+    /// The state changes on top of the base block, empty for canonical blocks.
+    pub(crate) changes: ChainState,
+}
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Returns the header and the state of the block, where the `pending` tag resolves to the
+    /// pending block.
+    ///
+    /// Returns `None` if the block is not found.
+    pub(crate) fn block_state(&self, block_id: BlockId) -> Result<Option<BlockState>> {
+        if block_id == BlockId::Number(BlockNumber::Pending) {
+            let PendingBlock { block, state, .. } = self.pending_block()?;
+            return Ok(Some(BlockState {
+                base_hash: block.parent_hash,
+                header: block.block.header.unseal(),
+                changes: state,
+            }))
+        }
+
+        let client = self.client();
+        let block_hash =
+            match client.block_hash_for_id(block_id).with_message("failed to read block hash")? {
+                Some(hash) => hash,
+                None => return Ok(None),
+            };
+        let header = client.header(&block_hash).with_message("failed to read header")?;
+        Ok(header.map(|header| BlockState {
+            header,
+            base_hash: block_hash,
+            changes: ChainState::default(),
+        }))
+    }
+
+    /// Builds the pending block on top of the latest block from the best transactions of the
+    /// pool.
+    ///
+    /// Transactions that can't be included, because they don't fit into the block or are invalid
+    /// on top of the preceding transactions, are skipped together with their descendants.
+    pub(crate) fn pending_block(&self) -> Result<PendingBlock> {
+        let client = self.client();
+        let parent_hash = client.chain_info().with_message("failed to read chain info")?.best_hash;
+        let parent = client
+            .header(&parent_hash)
+            .with_message("failed to read header")?
+            .ok_or_else(|| internal_rpc_err("latest block not found"))?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let header = Header {
+            parent_hash,
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp: now.max(parent.timestamp + 1),
+            base_fee_per_gas: parent.base_fee_per_gas.map(|base_fee| {
+                calculate_next_block_base_fee(parent.gas_used, parent.gas_limit, base_fee)
+            }),
+            ..Default::default()
+        };
+
+        let state =
+            client.history_by_block_hash(parent_hash).with_message("failed to read state")?;
+        match &self.inner.config.state_cache {
+            Some(cache) => self.build_pending_block(header, cache.provider(state, parent_hash)),
+            None => self.build_pending_block(header, state),
+        }
+    }
+
+    /// Builds the pending block with the given header on top of the given state.
+    fn build_pending_block<SP: StateProvider>(
+        &self,
+        mut header: Header,
+        state: SP,
+    ) -> Result<PendingBlock> {
+        let chain_spec = &self.inner.config.chain_spec;
+        let mut evm = prepare_evm(state, &header, chain_spec);
+
+        let mut cumulative_gas_used = 0;
+        let mut body = Vec::new();
+        let mut senders = Vec::new();
+        let mut changesets = Vec::new();
+        let mut best_transactions = self.pool().best_transactions();
+        while let Some(pool_transaction) = best_transactions.next() {
+            // smaller transactions may still fit into the rest of the block
+            if pool_transaction.transaction.gas_limit() > header.gas_limit - cumulative_gas_used {
+                best_transactions.mark_invalid(&pool_transaction);
+                continue
+            }
+
+            let transaction = pool_transaction.transaction.to_recovered_transaction();
+            revm_wrap::fill_tx_env(&mut evm.env.tx, &transaction);
+            let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = evm.transact();
+            if exit_reason == Return::FatalExternalError {
+                return Err(internal_rpc_err("failed to read state"))
+            }
+            // transactions that are rejected, e.g. because of their nonce or the balance of the
+            // sender, don't use any gas, while every executed transaction does
+            if gas_used == 0 {
+                best_transactions.mark_invalid(&pool_transaction);
+                continue
+            }
+
+            cumulative_gas_used += gas_used;
+            let logs: Vec<Log> = logs
+                .into_iter()
+                .map(|log| Log {
+                    address: H160(log.address.0),
+                    topics: log.topics.into_iter().map(|topic| H256(topic.0)).collect(),
+                    data: log.data,
+                })
+                .collect();
+            let (changeset, new_bytecodes) =
+                commit_changes(evm.db().expect("database is set"), state);
+            changesets.push(TransactionChangeSet {
+                receipt: Receipt {
+                    tx_type: transaction.tx_type(),
+                    success: matches!(exit_reason, revm::return_ok!()),
+                    cumulative_gas_used,
+                    bloom: logs_bloom(logs.iter()),
+                    logs,
+                },
+                changeset,
+                new_bytecodes,
+            });
+            senders.push(transaction.signer());
+            body.push(TransactionSigned::from(transaction));
+        }
+
+        let block_reward =
+            block_reward_changeset(&header, &[], evm.db().expect("database is set"), chain_spec)
+                .map_err(|err| internal_rpc_err(err.to_string()))?;
+
+        let receipts =
+            changesets.iter().map(|changeset| changeset.receipt.clone()).collect::<Vec<_>>();
+        header.gas_used = cumulative_gas_used;
+        header.transactions_root = proofs::calculate_transaction_root(body.iter());
+        header.receipts_root = proofs::calculate_receipt_root(receipts.iter());
+        header.logs_bloom = receipts_bloom(&receipts);
+        // the block doesn't process any withdrawals, but must commit to them after Shanghai
+        let withdrawals = chain_spec
+            .is_fork_active_at_timestamp(Hardfork::Shanghai, header.timestamp)
+            .then(Vec::new);
+        header.withdrawals_root = withdrawals.as_ref().map(proofs::calculate_withdrawals_root);
+        let header = header.seal();
+
+        let state = ChainState::new(
+            header.number,
+            header.hash(),
+            ExecutionResult { changesets, block_reward, block_storage: Default::default() },
+        );
+        let block = SealedBlock { header, body, ommers: Vec::new(), withdrawals };
+        let block = SealedBlockWithSenders { block, senders };
+        Ok(PendingBlock { block, receipts, state })
+    }
+}
@@ -0,0 +1,149 @@
+//! Contains RPC handler implementations for fetching receipts.
+
+use crate::{
+    eth::api::{pending_block::PendingBlock, EthApi},
+    result::ToRpcResult,
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    Header, Receipt, TransactionKind, TransactionSignedEcRecovered, H256, U256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
+use reth_rpc_types::{Log, TransactionReceipt};
+use reth_transaction_pool::TransactionPool;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider +
+        HeaderProvider +
+        ReceiptProvider +
+        StateProviderFactory +
+        TransactionsProvider +
+        'static,
+{
+    /// Returns the receipts of all transactions of the block, where the `pending` tag resolves to
+    /// the pending block.
+    ///
+    /// Returns `None` if the block is not canonical.
+    pub(crate) fn block_receipts(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Option<Vec<TransactionReceipt>>> {
+        if block_id == BlockId::Number(BlockNumber::Pending) {
+            let PendingBlock { block, receipts, .. } = self.pending_block()?;
+            let header = block.header.clone();
+            let transactions = block.into_transactions_ecrecovered().collect();
+            return Ok(Some(build_block_receipts(&header, header.hash(), transactions, receipts)))
+        }
+
+        let client = self.client();
+
+        let block_hash =
+            match client.block_hash_for_id(block_id).with_message("failed to read block hash")? {
+                Some(hash) => hash,
+                None => return Ok(None),
+            };
+        let header = match client.header(&block_hash).with_message("failed to read header")? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let (transactions, receipts) = match (
+            client
+                .transactions_with_senders_by_block(block_hash.into())
+                .with_message("failed to read transactions")?,
+            client.receipts_by_block(block_hash.into()).with_message("failed to read receipts")?,
+        ) {
+            (Some(transactions), Some(receipts)) => (transactions, receipts),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(build_block_receipts(&header, block_hash, transactions, receipts)))
+    }
+
+    /// Returns the receipt of the canonical transaction with the given hash.
+    pub(crate) fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        let meta = match self
+            .client()
+            .transaction_by_hash_with_meta(hash)
+            .with_message("failed to read transaction")?
+        {
+            Some((_, meta)) => meta,
+            None => return Ok(None),
+        };
+
+        // the gas used and the log indices depend on the preceding receipts of the block
+        let receipt = self
+            .block_receipts(BlockId::Hash(meta.block_hash))?
+            .and_then(|receipts| receipts.into_iter().nth(meta.index as usize));
+        Ok(receipt)
+    }
+}
+
+/// Converts the receipts of a block into their RPC representation.
+fn build_block_receipts(
+    header: &Header,
+    block_hash: H256,
+    transactions: Vec<TransactionSignedEcRecovered>,
+    receipts: Vec<Receipt>,
+) -> Vec<TransactionReceipt> {
+    let mut cumulative_gas_used = 0;
+    let mut log_index = 0u64;
+
+    transactions
+        .into_iter()
+        .zip(receipts)
+        .enumerate()
+        .map(|(index, (transaction, receipt))| {
+            let gas_used = receipt.cumulative_gas_used - cumulative_gas_used;
+            cumulative_gas_used = receipt.cumulative_gas_used;
+
+            let to = match transaction.kind() {
+                TransactionKind::Call(to) => Some(*to),
+                TransactionKind::Create => None,
+            };
+
+            let logs = receipt
+                .logs
+                .into_iter()
+                .enumerate()
+                .map(|(tx_log_index, log)| {
+                    let log = Log {
+                        block_hash: Some(block_hash),
+                        block_number: Some(header.number.into()),
+                        transaction_hash: Some(transaction.hash()),
+                        transaction_index: Some(index.into()),
+                        log_index: Some(log_index.into()),
+                        transaction_log_index: Some(tx_log_index.into()),
+                        ..log.into()
+                    };
+                    log_index += 1;
+                    log
+                })
+                .collect();
+
+            TransactionReceipt {
+                transaction_hash: Some(transaction.hash()),
+                transaction_index: Some(index.into()),
+                block_hash: Some(block_hash),
+                block_number: Some(header.number.into()),
+                from: transaction.signer(),
+                to,
+                cumulative_gas_used: receipt.cumulative_gas_used.into(),
+                gas_used: Some(gas_used.into()),
+                contract_address: transaction.contract_address(),
+                logs,
+                state_root: None,
+                logs_bloom: receipt.bloom,
+                status_code: Some((receipt.success as u64).into()),
+                effective_gas_price: U256::from(
+                    transaction.effective_gas_price(header.base_fee_per_gas),
+                ),
+                transaction_type: (receipt.tx_type as u64).into(),
+            }
+        })
+        .collect()
+}
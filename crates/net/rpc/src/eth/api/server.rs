@@ -4,14 +4,17 @@
 use crate::{eth::api::EthApi, result::ToRpcResult};
 use jsonrpsee::core::RpcResult as Result;
 use reth_primitives::{
-    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId},
+    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId, BlockNumber as RpcBlockNumber},
     Address, BlockNumber, Bytes, H256, H64, U256, U64,
 };
-use reth_provider::{BlockProvider, StateProviderFactory};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
 use reth_rpc_api::EthApiServer;
 use reth_rpc_types::{
-    CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock, SyncStatus,
-    TransactionReceipt, TransactionRequest, Work,
+    BlockOverrides, CallRequest, EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Index,
+    RichBlock, StateOverride, SyncStatus, TransactionConditional, TransactionReceipt,
+    TransactionRequest, Work,
 };
 use reth_transaction_pool::TransactionPool;
 use serde_json::Value;
@@ -23,14 +26,19 @@ impl<Pool, Client> EthApiServer for EthApi<Pool, Client>
 where
     Self: EthApiSpec,
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Client: BlockProvider +
+        HeaderProvider +
+        ReceiptProvider +
+        StateProviderFactory +
+        TransactionsProvider +
+        'static,
 {
     fn protocol_version(&self) -> Result<U64> {
         Ok(EthApiSpec::protocol_version(self))
     }
 
     fn syncing(&self) -> Result<SyncStatus> {
-        todo!()
+        EthApiSpec::sync_status(self).with_message("failed to read sync status")
     }
 
     async fn author(&self) -> Result<Address> {
@@ -52,16 +60,16 @@ where
         Ok(Some(EthApiSpec::chain_id(self)))
     }
 
-    async fn block_by_hash(&self, _hash: H256, _full: bool) -> Result<Option<RichBlock>> {
-        todo!()
+    async fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
+        EthApi::rich_block(self, BlockId::Hash(hash), full)
     }
 
     async fn block_by_number(
         &self,
-        _number: BlockNumber,
-        _full: bool,
+        number: RpcBlockNumber,
+        full: bool,
     ) -> Result<Option<RichBlock>> {
-        todo!()
+        EthApi::rich_block(self, BlockId::Number(number), full)
     }
 
     async fn block_transaction_count_by_hash(&self, _hash: H256) -> Result<Option<U256>> {
@@ -122,12 +130,20 @@ where
         todo!()
     }
 
-    async fn transaction_receipt(&self, _hash: H256) -> Result<Option<TransactionReceipt>> {
-        todo!()
+    async fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        EthApi::transaction_receipt(self, hash)
     }
 
-    async fn balance(&self, _address: Address, _block_number: Option<BlockId>) -> Result<U256> {
-        todo!()
+    async fn block_receipts(&self, block_id: BlockId) -> Result<Option<Vec<TransactionReceipt>>> {
+        EthApi::block_receipts(self, block_id)
+    }
+
+    async fn balance(&self, address: Address, block_number: Option<BlockId>) -> Result<U256> {
+        EthApi::balance(
+            self,
+            address,
+            block_number.unwrap_or(BlockId::Number(RpcBlockNumber::Latest)),
+        )
     }
 
     async fn storage_at(
@@ -151,16 +167,51 @@ where
         todo!()
     }
 
-    async fn call(&self, _request: CallRequest, _block_number: Option<BlockId>) -> Result<Bytes> {
-        todo!()
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<Box<BlockOverrides>>,
+    ) -> Result<Bytes> {
+        EthApi::call(
+            self,
+            request,
+            block_number.unwrap_or(BlockId::Number(RpcBlockNumber::Latest)),
+            state_overrides,
+            block_overrides.map(|overrides| *overrides),
+        )
+        .await
+    }
+
+    async fn call_many(
+        &self,
+        calls: Vec<CallRequest>,
+        block_number: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<Vec<EthCallResponse>> {
+        EthApi::call_many(
+            self,
+            calls,
+            block_number.unwrap_or(BlockId::Number(RpcBlockNumber::Latest)),
+            state_overrides,
+            block_overrides,
+        )
+        .await
     }
 
     async fn create_access_list(
         &self,
-        _request: CallRequest,
-        _block_number: Option<BlockId>,
+        request: CallRequest,
+        block_number: Option<BlockId>,
     ) -> Result<AccessListWithGasUsed> {
-        todo!()
+        EthApi::create_access_list(
+            self,
+            request,
+            block_number.unwrap_or(BlockId::Number(RpcBlockNumber::Latest)),
+        )
+        .await
     }
 
     async fn estimate_gas(
@@ -212,8 +263,16 @@ where
         todo!()
     }
 
-    async fn send_raw_transaction(&self, _bytes: Bytes) -> Result<H256> {
-        todo!()
+    async fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256> {
+        EthApi::send_raw_transaction(self, bytes).await
+    }
+
+    async fn send_raw_transaction_conditional(
+        &self,
+        bytes: Bytes,
+        conditional: TransactionConditional,
+    ) -> Result<H256> {
+        EthApi::send_raw_transaction_conditional(self, bytes, conditional).await
     }
 
     async fn sign(&self, _address: Address, _message: Bytes) -> Result<Bytes> {
@@ -0,0 +1,32 @@
+//! Contains RPC handler implementations for reading the state.
+
+use crate::{
+    eth::api::EthApi,
+    result::{invalid_params_rpc_err, ToRpcResult},
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_executor::state::ChainStateProvider;
+use reth_primitives::{rpc::BlockId, Address, U256};
+use reth_provider::{AccountProvider, BlockProvider, HeaderProvider, StateProviderFactory};
+use reth_transaction_pool::TransactionPool;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Returns the balance of the account at the given block, which is zero if the account
+    /// doesn't exist.
+    pub(crate) fn balance(&self, address: Address, block_id: BlockId) -> Result<U256> {
+        let block =
+            self.block_state(block_id)?.ok_or_else(|| invalid_params_rpc_err("block not found"))?;
+        let parent = self
+            .client()
+            .history_by_block_hash(block.base_hash)
+            .with_message("failed to read state")?;
+        let state = ChainStateProvider::new(&block.changes, parent);
+        let account = state.basic_account(address).with_message("failed to read account")?;
+
+        Ok(account.map(|account| account.balance).unwrap_or_default())
+    }
+}
@@ -0,0 +1,114 @@
+//! Contains RPC handler implementations for submitting transactions.
+
+use crate::{
+    eth::api::EthApi,
+    result::{invalid_params_rpc_err, rpc_err, ToRpcResult},
+};
+use jsonrpsee::{core::RpcResult as Result, types::error::CALL_EXECUTION_FAILED_CODE};
+use reth_primitives::{Bytes, FromRecoveredTransaction, TransactionSigned, H256, U256};
+use reth_provider::{BlockProvider, HeaderProvider, StateProvider, StateProviderFactory};
+use reth_rpc_types::{KnownAccountState, TransactionConditional};
+use reth_transaction_pool::{TransactionOrigin, TransactionPool};
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Decodes and recovers the transaction and submits it to the pool as a local transaction.
+    ///
+    /// If the pool accepted the transaction it is propagated to all connected peers.
+    pub(crate) async fn send_raw_transaction(&self, tx: Bytes) -> Result<H256> {
+        let transaction = TransactionSigned::decode_enveloped(tx).map_err(|err| {
+            invalid_params_rpc_err(format!("failed to decode transaction: {err}"))
+        })?;
+        let recovered = transaction
+            .into_ecrecovered()
+            .ok_or_else(|| invalid_params_rpc_err("invalid transaction signature"))?;
+
+        let pool_transaction =
+            <Pool::Transaction as FromRecoveredTransaction>::from_recovered_transaction(recovered);
+        let hash = self
+            .pool()
+            .add_transaction(TransactionOrigin::Local, pool_transaction)
+            .await
+            .map_err(|err| rpc_err(CALL_EXECUTION_FAILED_CODE, err.to_string(), None))?;
+
+        // local transactions are propagated to all peers right away
+        self.transactions_handle().propagate_local(hash);
+
+        Ok(hash)
+    }
+
+    /// Same as [Self::send_raw_transaction], but the transaction is rejected if any of the
+    /// given conditions does not hold for the current head of the chain.
+    pub(crate) async fn send_raw_transaction_conditional(
+        &self,
+        tx: Bytes,
+        conditional: TransactionConditional,
+    ) -> Result<H256> {
+        if !self.inner.config.allow_conditional_transactions {
+            return Err(rpc_err(
+                jsonrpsee::types::error::METHOD_NOT_FOUND_CODE,
+                "eth_sendRawTransactionConditional is disabled",
+                None,
+            ))
+        }
+        self.check_conditional(&conditional)?;
+        self.send_raw_transaction(tx).await
+    }
+
+    /// Checks the conditions against the latest block and state.
+    fn check_conditional(&self, conditional: &TransactionConditional) -> Result<()> {
+        let client = self.client();
+
+        let best_number =
+            client.chain_info().with_message("failed to read chain info")?.best_number;
+        if conditional.block_number_min.map_or(false, |min| best_number < min.as_u64()) ||
+            conditional.block_number_max.map_or(false, |max| best_number > max.as_u64())
+        {
+            return Err(invalid_params_rpc_err("block number out of range"))
+        }
+
+        if conditional.timestamp_min.is_some() || conditional.timestamp_max.is_some() {
+            let timestamp = client
+                .header_by_number(best_number)
+                .with_message("failed to read header")?
+                .map(|header| header.timestamp)
+                .unwrap_or_default();
+            if conditional.timestamp_min.map_or(false, |min| timestamp < min.as_u64()) ||
+                conditional.timestamp_max.map_or(false, |max| timestamp > max.as_u64())
+            {
+                return Err(invalid_params_rpc_err("timestamp out of range"))
+            }
+        }
+
+        if conditional.known_accounts.is_empty() {
+            return Ok(())
+        }
+
+        let state = client.latest().with_message("failed to read state")?;
+        for (address, expected) in &conditional.known_accounts {
+            match expected {
+                KnownAccountState::StorageRoot(_) => {
+                    return Err(invalid_params_rpc_err("storage root conditions are not supported"))
+                }
+                KnownAccountState::Slots(slots) => {
+                    for (slot, value) in slots {
+                        let current = state
+                            .storage(*address, *slot)
+                            .with_message("failed to read storage")?
+                            .unwrap_or_default();
+                        if current != U256::from_big_endian(value.as_bytes()) {
+                            return Err(invalid_params_rpc_err(format!(
+                                "storage slot {slot:?} of {address:?} does not match"
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+//! `eth_` filter RPC handler implementation
+
+use crate::result::{invalid_params_rpc_err, ToRpcResult};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult as Result;
+use parking_lot::Mutex;
+use reth_primitives::{
+    rpc::{BlockNumber, Filter, FilterBlockOption},
+    BlockHashOrNumber, TxHash, H256, U256,
+};
+use reth_provider::{BlockProvider, LogWithMeta, ReceiptProvider};
+use reth_rpc_api::EthFilterApiServer;
+use reth_rpc_types::{FilterChanges, Index, Log};
+use reth_transaction_pool::TransactionPool;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::Receiver;
+
+/// The default duration after which a filter that was not polled is removed, same as geth.
+pub const DEFAULT_FILTER_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// The default maximum number of blocks a log query can span.
+pub const DEFAULT_MAX_BLOCKS_PER_FILTER: u64 = 100_000;
+
+/// The default maximum number of logs a log query can return.
+pub const DEFAULT_MAX_LOGS_PER_RESPONSE: usize = 20_000;
+
+/// Settings for the [EthFilter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthFilterConfig {
+    /// Duration after which a filter that was not polled is removed.
+    pub idle_timeout: Duration,
+    /// The maximum number of blocks a log query can span, larger queries are rejected.
+    pub max_blocks_per_filter: u64,
+    /// The maximum number of logs a log query can return, queries that match more logs are
+    /// rejected.
+    pub max_logs_per_response: usize,
+}
+
+impl Default for EthFilterConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: DEFAULT_FILTER_IDLE_TIMEOUT,
+            max_blocks_per_filter: DEFAULT_MAX_BLOCKS_PER_FILTER,
+            max_logs_per_response: DEFAULT_MAX_LOGS_PER_RESPONSE,
+        }
+    }
+}
+
+/// `Eth` filter RPC implementation.
+///
+/// All installed filters are kept in a registry that is shared by all clones of this type, so the
+/// same instance can be registered for multiple transports (HTTP and WS).
+#[derive(Debug, Clone)]
+pub struct EthFilter<Pool, Client> {
+    /// All nested fields bundled together.
+    inner: Arc<EthFilterInner<Pool, Client>>,
+}
+
+// === impl EthFilter ===
+
+impl<Pool, Client> EthFilter<Pool, Client> {
+    /// Creates a new, shareable instance.
+    pub fn new(client: Arc<Client>, pool: Pool) -> Self {
+        Self::with_config(client, pool, EthFilterConfig::default())
+    }
+
+    /// Creates a new, shareable instance with the given settings.
+    pub fn with_config(client: Arc<Client>, pool: Pool, config: EthFilterConfig) -> Self {
+        let inner = EthFilterInner {
+            client,
+            pool,
+            active_filters: Default::default(),
+            next_id: AtomicUsize::new(1),
+            config,
+        };
+        Self { inner: Arc::new(inner) }
+    }
+}
+
+impl<Pool, Client> EthFilter<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + ReceiptProvider + 'static,
+{
+    /// Installs a new filter and returns its id.
+    fn install_filter(&self, kind: FilterKind) -> Result<U256> {
+        let best_number =
+            self.inner.client.chain_info().with_message("failed to read chain info")?.best_number;
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+
+        let mut filters = self.inner.active_filters.lock();
+        self.inner.evict_idle_filters(&mut filters);
+        filters.insert(
+            id,
+            ActiveFilter {
+                last_poll_block_number: best_number,
+                last_poll_timestamp: Instant::now(),
+                kind,
+            },
+        );
+
+        Ok(id.into())
+    }
+
+    /// Returns all logs in the given block range that match the filter.
+    ///
+    /// Returns an error if the range spans more blocks or matches more logs than configured.
+    fn logs_in_range(&self, filter: &Filter, from: u64, to: u64) -> Result<Vec<Log>> {
+        let EthFilterConfig { max_blocks_per_filter, max_logs_per_response, .. } =
+            self.inner.config;
+        if to - from >= max_blocks_per_filter {
+            return Err(invalid_params_rpc_err(format!(
+                "query exceeds max block range {max_blocks_per_filter}"
+            )))
+        }
+
+        let client = &self.inner.client;
+        let mut logs = Vec::new();
+
+        for number in from..=to {
+            let block_logs = client
+                .logs_by_block(BlockHashOrNumber::Number(number), filter)
+                .with_message("failed to read logs")?;
+            logs.extend(block_logs.into_iter().flatten().map(
+                |LogWithMeta { log, meta, log_index, transaction_log_index }| Log {
+                    block_hash: Some(meta.block_hash),
+                    block_number: Some(meta.block_number.into()),
+                    transaction_hash: Some(meta.tx_hash),
+                    transaction_index: Some(meta.index.into()),
+                    log_index: Some(log_index.into()),
+                    transaction_log_index: Some(transaction_log_index.into()),
+                    ..log.into()
+                },
+            ));
+            if logs.len() > max_logs_per_response {
+                return Err(invalid_params_rpc_err(format!(
+                    "query exceeds max results {max_logs_per_response}"
+                )))
+            }
+        }
+
+        Ok(logs)
+    }
+
+    /// Returns the block range of the filter, bounded by the given head of the chain.
+    fn filter_block_range(&self, filter: &Filter, best_number: u64) -> Result<(u64, u64)> {
+        let client = &self.inner.client;
+        match filter.block_option {
+            FilterBlockOption::AtBlockHash(hash) => {
+                let number = client
+                    .block_number(hash)
+                    .with_message("failed to read block number")?
+                    .ok_or_else(|| invalid_params_rpc_err("unknown block"))?;
+                Ok((number, number))
+            }
+            FilterBlockOption::Range { from_block, to_block } => {
+                let convert = |num: Option<BlockNumber>| -> Result<u64> {
+                    Ok(match num {
+                        Some(num) => client
+                            .convert_block_number(num)
+                            .with_message("failed to convert block number")?
+                            .unwrap_or(best_number),
+                        None => best_number,
+                    })
+                };
+                let from = convert(from_block)?;
+                let to = convert(to_block)?.min(best_number);
+                Ok((from, to))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<Pool, Client> EthFilterApiServer for EthFilter<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider + ReceiptProvider + 'static,
+{
+    fn new_filter(&self, filter: Filter) -> Result<U256> {
+        self.install_filter(FilterKind::Log(Box::new(filter)))
+    }
+
+    fn new_block_filter(&self) -> Result<U256> {
+        self.install_filter(FilterKind::Block)
+    }
+
+    fn new_pending_transaction_filter(&self) -> Result<U256> {
+        let listener = self.inner.pool.pending_transactions_listener();
+        self.install_filter(FilterKind::PendingTransaction(listener))
+    }
+
+    async fn filter_changes(&self, index: Index) -> Result<FilterChanges> {
+        let best_number =
+            self.inner.client.chain_info().with_message("failed to read chain info")?.best_number;
+
+        // take a snapshot of the filter and advance its poll position
+        let (kind, from) = {
+            let mut filters = self.inner.active_filters.lock();
+            self.inner.evict_idle_filters(&mut filters);
+            let filter = filters
+                .get_mut(&usize::from(index))
+                .ok_or_else(|| invalid_params_rpc_err("filter not found"))?;
+            filter.last_poll_timestamp = Instant::now();
+
+            let from = filter.last_poll_block_number + 1;
+            let kind = match &mut filter.kind {
+                FilterKind::PendingTransaction(listener) => {
+                    let mut hashes = Vec::new();
+                    while let Ok(hash) = listener.try_recv() {
+                        hashes.push(hash);
+                    }
+                    return Ok(to_filter_changes(hashes))
+                }
+                FilterKind::Block => None,
+                FilterKind::Log(filter) => Some(filter.clone()),
+            };
+            if from > best_number {
+                return Ok(FilterChanges::Empty)
+            }
+            filter.last_poll_block_number = best_number;
+            (kind, from)
+        };
+
+        match kind {
+            None => {
+                let mut hashes = Vec::new();
+                for number in from..=best_number {
+                    if let Some(hash) = self
+                        .inner
+                        .client
+                        .block_hash(number.into())
+                        .with_message("failed to read block hash")?
+                    {
+                        hashes.push(hash);
+                    }
+                }
+                Ok(to_filter_changes(hashes))
+            }
+            Some(filter) => {
+                let (filter_from, filter_to) = self.filter_block_range(&filter, best_number)?;
+                let from = from.max(filter_from);
+                if from > filter_to {
+                    return Ok(FilterChanges::Empty)
+                }
+                let logs = self.logs_in_range(&filter, from, filter_to)?;
+                if logs.is_empty() {
+                    Ok(FilterChanges::Empty)
+                } else {
+                    Ok(FilterChanges::Logs(logs))
+                }
+            }
+        }
+    }
+
+    async fn filter_logs(&self, index: Index) -> Result<Vec<Log>> {
+        let filter = {
+            let mut filters = self.inner.active_filters.lock();
+            self.inner.evict_idle_filters(&mut filters);
+            let filter = filters
+                .get_mut(&usize::from(index))
+                .ok_or_else(|| invalid_params_rpc_err("filter not found"))?;
+            filter.last_poll_timestamp = Instant::now();
+            match &filter.kind {
+                FilterKind::Log(filter) => filter.clone(),
+                _ => return Err(invalid_params_rpc_err("filter is not a log filter")),
+            }
+        };
+        self.logs(*filter).await
+    }
+
+    fn uninstall_filter(&self, index: Index) -> Result<bool> {
+        let mut filters = self.inner.active_filters.lock();
+        self.inner.evict_idle_filters(&mut filters);
+        Ok(filters.remove(&usize::from(index)).is_some())
+    }
+
+    async fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
+        let best_number =
+            self.inner.client.chain_info().with_message("failed to read chain info")?.best_number;
+        let (from, to) = self.filter_block_range(&filter, best_number)?;
+        if from > to {
+            return Ok(Vec::new())
+        }
+        self.logs_in_range(&filter, from, to)
+    }
+}
+
+/// Container type `EthFilter`
+#[derive(Debug)]
+struct EthFilterInner<Pool, Client> {
+    /// The transaction pool.
+    pool: Pool,
+    /// The client that can interact with the chain.
+    client: Arc<Client>,
+    /// All currently installed filters.
+    active_filters: Mutex<HashMap<usize, ActiveFilter>>,
+    /// The id of the next installed filter.
+    next_id: AtomicUsize,
+    /// The settings of the handler.
+    config: EthFilterConfig,
+}
+
+impl<Pool, Client> EthFilterInner<Pool, Client> {
+    /// Removes all filters that were not polled within the idle timeout.
+    fn evict_idle_filters(&self, filters: &mut HashMap<usize, ActiveFilter>) {
+        filters.retain(|_, filter| filter.last_poll_timestamp.elapsed() < self.config.idle_timeout);
+    }
+}
+
+/// An installed filter.
+#[derive(Debug)]
+struct ActiveFilter {
+    /// The block number of the head of the chain at the last poll.
+    last_poll_block_number: u64,
+    /// When the filter was polled the last time.
+    last_poll_timestamp: Instant,
+    /// What the filter tracks.
+    kind: FilterKind,
+}
+
+/// The kind of an installed filter.
+#[derive(Debug)]
+enum FilterKind {
+    /// Tracks logs matching the filter.
+    Log(Box<Filter>),
+    /// Tracks new blocks.
+    Block,
+    /// Tracks new pending transactions.
+    PendingTransaction(Receiver<TxHash>),
+}
+
+/// Converts the hashes into [FilterChanges].
+fn to_filter_changes(hashes: Vec<H256>) -> FilterChanges {
+    if hashes.is_empty() {
+        FilterChanges::Empty
+    } else {
+        FilterChanges::Hashes(hashes)
+    }
+}
@@ -1,7 +1,14 @@
 //! `eth` namespace handler implementation.
 
+mod access_list;
 mod api;
+mod filter;
 mod pubsub;
+pub(crate) mod revm_utils;
 
-pub use api::{EthApi, EthApiSpec};
+pub use api::{EthApi, EthApiConfig, EthApiSpec};
+pub use filter::{
+    EthFilter, EthFilterConfig, DEFAULT_FILTER_IDLE_TIMEOUT, DEFAULT_MAX_BLOCKS_PER_FILTER,
+    DEFAULT_MAX_LOGS_PER_RESPONSE,
+};
 pub use pubsub::EthPubSub;
@@ -1,6 +1,7 @@
 //! `eth_` PubSub RPC handler implementation
 
-use jsonrpsee::{types::SubscriptionResult, SubscriptionSink};
+use async_trait::async_trait;
+use jsonrpsee::{core::SubscriptionResult, PendingSubscriptionSink, SubscriptionSink};
 use reth_provider::BlockProvider;
 use reth_rpc_api::EthPubSubApiServer;
 use reth_rpc_types::pubsub::{Kind, Params};
@@ -26,18 +27,19 @@ impl<Pool, Client> EthPubSub<Pool, Client> {
     }
 }
 
+#[async_trait]
 impl<Pool, Client> EthPubSubApiServer for EthPubSub<Pool, Client>
 where
     Pool: TransactionPool + 'static,
     Client: BlockProvider + 'static,
 {
-    fn subscribe(
+    async fn subscribe(
         &self,
-        mut sink: SubscriptionSink,
+        pending: PendingSubscriptionSink,
         _kind: Kind,
         _params: Option<Params>,
     ) -> SubscriptionResult {
-        sink.accept()?;
+        let _sink = pending.accept().await?;
         todo!()
     }
 }
@@ -0,0 +1,194 @@
+//! Utilities for executing calls with revm.
+
+use crate::result::{internal_rpc_err, invalid_params_rpc_err};
+use jsonrpsee::core::RpcResult as Result;
+use reth_executor::revm_wrap::{self, State, SubState};
+use reth_primitives::{keccak256, ChainSpec, Header, H256, U256};
+use reth_provider::StateProvider;
+use reth_rpc_types::{BlockOverrides, CallRequest, StateOverride};
+use revm::{
+    BlockEnv, Bytecode, Database, SpecId, TransactTo, TxEnv, B160, B256, EVM, U256 as RevmU256,
+};
+
+/// Converts a [U256] into its revm representation.
+pub(crate) fn to_revm_u256(value: U256) -> RevmU256 {
+    RevmU256::from_limbs(value.0)
+}
+
+/// Converts a storage slot or value into its revm representation.
+fn h256_to_revm_u256(value: H256) -> RevmU256 {
+    RevmU256::from_be_bytes(value.to_fixed_bytes())
+}
+
+/// Creates a new [EVM] on top of the given state with the cfg and block environment of the given
+/// block.
+pub(crate) fn prepare_evm<SP: StateProvider>(
+    state: SP,
+    header: &Header,
+    chain_spec: &ChainSpec,
+) -> EVM<SubState<SP>> {
+    let mut evm = EVM::new();
+    evm.database(SubState::new(State::new(state)));
+    revm_wrap::fill_cfg_env(&mut evm.env.cfg, chain_spec, header);
+    let after_merge = evm.env.cfg.spec_id >= SpecId::MERGE;
+    revm_wrap::fill_block_env(&mut evm.env.block, header, after_merge);
+    evm
+}
+
+/// Fills the [TxEnv] with the fields of the given [CallRequest].
+///
+/// If no gas limit is set, the gas limit of the block is used. If no gas price is set the block's
+/// base fee is disabled, so that calls without any fees can be executed.
+pub(crate) fn fill_tx_env_with_call(
+    tx_env: &mut TxEnv,
+    block_env: &mut BlockEnv,
+    request: CallRequest,
+) -> Result<()> {
+    let CallRequest {
+        from,
+        to,
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        gas,
+        value,
+        data,
+        nonce,
+        access_list,
+        ..
+    } = request;
+
+    let (gas_price, gas_priority_fee) = match (gas_price, max_fee_per_gas) {
+        (Some(_), Some(_)) => {
+            return Err(invalid_params_rpc_err(
+                "both gasPrice and (maxFeePerGas or maxPriorityFeePerGas) specified",
+            ))
+        }
+        (Some(gas_price), None) => (to_revm_u256(gas_price), None),
+        (None, Some(max_fee)) => {
+            let max_priority_fee = max_priority_fee_per_gas.unwrap_or_default();
+            if max_priority_fee > max_fee {
+                return Err(invalid_params_rpc_err(
+                    "maxPriorityFeePerGas is higher than maxFeePerGas",
+                ))
+            }
+            (to_revm_u256(max_fee), Some(to_revm_u256(max_priority_fee)))
+        }
+        (None, None) => {
+            // executing a call without fees requires a zero base fee
+            block_env.basefee = RevmU256::ZERO;
+            (RevmU256::ZERO, max_priority_fee_per_gas.map(to_revm_u256))
+        }
+    };
+
+    let gas_limit = match gas {
+        Some(gas) => {
+            if gas > U256::from(u64::MAX) {
+                return Err(invalid_params_rpc_err("gas limit exceeds u64::MAX"))
+            }
+            gas.as_u64()
+        }
+        None => block_env.gas_limit.try_into().unwrap_or(u64::MAX),
+    };
+
+    tx_env.caller = B160(from.unwrap_or_default().0);
+    tx_env.gas_limit = gas_limit;
+    tx_env.gas_price = gas_price;
+    tx_env.gas_priority_fee = gas_priority_fee;
+    tx_env.transact_to = match to {
+        Some(to) => TransactTo::Call(B160(to.0)),
+        None => TransactTo::create(),
+    };
+    tx_env.value = value.map(to_revm_u256).unwrap_or_default();
+    tx_env.data = data.map(|data| data.0).unwrap_or_default();
+    tx_env.nonce = nonce.map(|nonce| nonce.as_u64());
+    tx_env.access_list = access_list
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| {
+            (B160(item.address.0), item.storage_keys.into_iter().map(h256_to_revm_u256).collect())
+        })
+        .collect();
+
+    Ok(())
+}
+
+/// Applies the [StateOverride] to the database.
+///
+/// `state` replaces the entire storage of an account, `stateDiff` only replaces the given slots.
+pub(crate) fn apply_state_overrides<SP: StateProvider>(
+    overrides: StateOverride,
+    db: &mut SubState<SP>,
+) -> Result<()> {
+    for (address, account) in overrides {
+        let address = B160(address.0);
+        let mut info = db
+            .basic(address)
+            .map_err(|_| internal_rpc_err("failed to read state"))?
+            .unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = to_revm_u256(balance);
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce.as_u64();
+        }
+        if let Some(code) = account.code {
+            info.code_hash = B256(keccak256(&code).0);
+            info.code = Some(Bytecode::new_raw(code.0));
+        }
+        db.insert_account_info(address, info);
+
+        match (account.state, account.state_diff) {
+            (Some(_), Some(_)) => {
+                return Err(invalid_params_rpc_err(format!(
+                    "both state and stateDiff set for account {address:?}"
+                )))
+            }
+            (Some(state), None) => db.replace_account_storage(
+                address,
+                state
+                    .into_iter()
+                    .map(|(slot, value)| (h256_to_revm_u256(slot), h256_to_revm_u256(value)))
+                    .collect(),
+            ),
+            (None, Some(state_diff)) => state_diff.into_iter().try_for_each(|(slot, value)| {
+                db.insert_account_storage(
+                    address,
+                    h256_to_revm_u256(slot),
+                    h256_to_revm_u256(value),
+                )
+            }),
+            (None, None) => Ok(()),
+        }
+        .map_err(|_| internal_rpc_err("failed to read state"))?;
+    }
+    Ok(())
+}
+
+/// Applies the [BlockOverrides] to the [BlockEnv].
+pub(crate) fn apply_block_overrides(overrides: BlockOverrides, block_env: &mut BlockEnv) {
+    let BlockOverrides { number, difficulty, time, gas_limit, coinbase, random, base_fee } =
+        overrides;
+
+    if let Some(number) = number {
+        block_env.number = to_revm_u256(number);
+    }
+    if let Some(difficulty) = difficulty {
+        block_env.difficulty = to_revm_u256(difficulty);
+    }
+    if let Some(time) = time {
+        block_env.timestamp = RevmU256::from(time.as_u64());
+    }
+    if let Some(gas_limit) = gas_limit {
+        block_env.gas_limit = RevmU256::from(gas_limit.as_u64());
+    }
+    if let Some(coinbase) = coinbase {
+        block_env.coinbase = B160(coinbase.0);
+    }
+    if let Some(random) = random {
+        block_env.prevrandao = Some(B256(random.0));
+    }
+    if let Some(base_fee) = base_fee {
+        block_env.basefee = to_revm_u256(base_fee);
+    }
+}
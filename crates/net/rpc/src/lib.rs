@@ -11,12 +11,18 @@
 //!
 //! Provides the implementation of all RPC interfaces.
 
+mod admin;
+mod debug;
 mod engine;
 mod eth;
 mod net;
+mod web3;
 
+pub use admin::{AdminApi, LogFilterReloader};
+pub use debug::DebugApi;
 pub use engine::EngineApi;
-pub use eth::{EthApi, EthApiSpec, EthPubSub};
+pub use eth::{EthApi, EthApiConfig, EthApiSpec, EthFilter, EthFilterConfig, EthPubSub};
 pub use net::NetApi;
+pub use web3::Web3Api;
 
 pub(crate) mod result;
@@ -20,6 +20,13 @@ impl std::fmt::Debug for NetApi {
     }
 }
 
+impl NetApi {
+    /// Creates a new instance.
+    pub fn new(network: NetworkHandle, eth: Box<dyn EthApiSpec>) -> Self {
+        Self { network, eth }
+    }
+}
+
 /// Net rpc implementation
 impl NetApiServer for NetApi {
     fn version(&self) -> Result<String> {
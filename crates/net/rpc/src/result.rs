@@ -1,6 +1,6 @@
 //! Additional helpers for converting errors.
 
-use jsonrpsee::core::{Error as RpcError, RpcResult};
+use jsonrpsee::{core::RpcResult, types::ErrorObject};
 
 /// Helper trait to easily convert various `Result` types into [`RpcResult`]
 pub(crate) trait ToRpcResult<Ok, Err> {
@@ -86,30 +86,37 @@ impl<Ok> ToRpcResult<Ok, reth_interfaces::Error> for reth_interfaces::Result<Ok>
 }
 
 /// Constructs an internal JSON-RPC error.
-pub(crate) fn internal_rpc_err(msg: impl Into<String>) -> jsonrpsee::core::Error {
+pub(crate) fn internal_rpc_err(msg: impl Into<String>) -> ErrorObject<'static> {
     rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, msg, None)
 }
 
+/// Constructs an invalid params JSON-RPC error.
+pub(crate) fn invalid_params_rpc_err(msg: impl Into<String>) -> ErrorObject<'static> {
+    rpc_err(jsonrpsee::types::error::INVALID_PARAMS_CODE, msg, None)
+}
+
 /// Constructs an internal JSON-RPC error with data
 pub(crate) fn internal_rpc_err_with_data(
     msg: impl Into<String>,
     data: &[u8],
-) -> jsonrpsee::core::Error {
+) -> ErrorObject<'static> {
     rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, msg, Some(data))
 }
 
 /// Constructs a JSON-RPC error, consisting of `code`, `message` and optional `data`.
-pub(crate) fn rpc_err(code: i32, msg: impl Into<String>, data: Option<&[u8]>) -> RpcError {
-    RpcError::Call(jsonrpsee::types::error::CallError::Custom(
-        jsonrpsee::types::error::ErrorObject::owned(
-            code,
-            msg.into(),
-            data.map(|data| {
-                jsonrpsee::core::to_json_raw_value(&format!("0x{}", hex::encode(data)))
-                    .expect("serializing String does fail")
-            }),
-        ),
-    ))
+pub(crate) fn rpc_err(
+    code: i32,
+    msg: impl Into<String>,
+    data: Option<&[u8]>,
+) -> ErrorObject<'static> {
+    ErrorObject::owned(
+        code,
+        msg.into(),
+        data.map(|data| {
+            jsonrpsee::core::to_json_raw_value(&format!("0x{}", hex::encode(data)))
+                .expect("serializing String does fail")
+        }),
+    )
 }
 
 #[cfg(test)]
@@ -0,0 +1,29 @@
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{keccak256, Bytes, H256};
+use reth_rpc_api::Web3ApiServer;
+
+/// `web3` API implementation.
+///
+/// This type provides the functionality for handling `web3` related requests.
+#[derive(Debug, Clone)]
+pub struct Web3Api {
+    /// The version of the client, returned by `web3_clientVersion`.
+    client_version: String,
+}
+
+impl Web3Api {
+    /// Creates a new instance that reports the given client version.
+    pub fn new(client_version: impl Into<String>) -> Self {
+        Self { client_version: client_version.into() }
+    }
+}
+
+impl Web3ApiServer for Web3Api {
+    fn client_version(&self) -> Result<String> {
+        Ok(self.client_version.clone())
+    }
+
+    fn sha3(&self, input: Bytes) -> Result<H256> {
+        Ok(keccak256(input))
+    }
+}
@@ -1,10 +1,14 @@
-use crate::{Header, SealedHeader, TransactionSigned, H256};
-use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
+use crate::{
+    Address, Header, SealedHeader, TransactionSigned, TransactionSignedEcRecovered, Withdrawal,
+    H256,
+};
+use bytes::BufMut;
+use reth_rlp::{length_of_length, Decodable, DecodeError, Encodable};
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 
 /// Ethereum full block.
-#[derive(Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Block {
     /// Block header.
     pub header: Header,
@@ -12,6 +16,8 @@ pub struct Block {
     pub body: Vec<TransactionSigned>,
     /// Ommers/uncles header
     pub ommers: Vec<Header>,
+    /// Withdrawals in this block, `None` before Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 impl Deref for Block {
@@ -23,7 +29,7 @@ impl Deref for Block {
 
 /// Sealed Ethereum full block.
 // ANCHOR: struct-SealedBlock
-#[derive(Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SealedBlock {
     /// Locked block header.
     pub header: SealedHeader,
@@ -31,6 +37,8 @@ pub struct SealedBlock {
     pub body: Vec<TransactionSigned>,
     /// Ommer/uncle headers
     pub ommers: Vec<SealedHeader>,
+    /// Withdrawals in this block, `None` before Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 // ANCHOR_END: struct-SealedBlock
 
@@ -39,6 +47,23 @@ impl SealedBlock {
     pub fn hash(&self) -> H256 {
         self.header.hash()
     }
+
+    /// Recover the senders of the transactions of the block.
+    ///
+    /// Returns `None` if the signature of any of the transactions is invalid.
+    pub fn senders(&self) -> Option<Vec<Address>> {
+        TransactionSigned::recover_signers(&self.body)
+    }
+
+    /// Recover the senders of the transactions and attach them to the block.
+    ///
+    /// Returns the block back if the signature of any of the transactions is invalid.
+    pub fn try_seal_with_senders(self) -> Result<SealedBlockWithSenders, Self> {
+        match self.senders() {
+            Some(senders) => Ok(SealedBlockWithSenders { block: self, senders }),
+            None => Err(self),
+        }
+    }
 }
 
 impl Deref for SealedBlock {
@@ -48,6 +73,122 @@ impl Deref for SealedBlock {
     }
 }
 
+/// Sealed block together with the senders of its transactions, which were recovered once so
+/// that they don't need to be recovered again by every consumer of the block.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SealedBlockWithSenders {
+    /// The sealed block.
+    pub block: SealedBlock,
+    /// The senders of the transactions of the block, in the same order.
+    pub senders: Vec<Address>,
+}
+
+impl SealedBlockWithSenders {
+    /// Attach the senders to the block.
+    ///
+    /// Returns `None` if the number of senders does not match the number of transactions.
+    pub fn new(block: SealedBlock, senders: Vec<Address>) -> Option<Self> {
+        (block.body.len() == senders.len()).then_some(Self { block, senders })
+    }
+
+    /// Split the block into the block and the senders of its transactions.
+    pub fn into_components(self) -> (SealedBlock, Vec<Address>) {
+        (self.block, self.senders)
+    }
+
+    /// An iterator over the transactions of the block and their senders.
+    pub fn transactions_with_sender(
+        &self,
+    ) -> impl Iterator<Item = (&Address, &TransactionSigned)> + '_ {
+        self.senders.iter().zip(self.block.body.iter())
+    }
+
+    /// Consume the block and return its transactions with the recovered senders.
+    pub fn into_transactions_ecrecovered(
+        self,
+    ) -> impl Iterator<Item = TransactionSignedEcRecovered> {
+        self.block.body.into_iter().zip(self.senders).map(|(transaction, sender)| {
+            TransactionSignedEcRecovered::from_signed_transaction(transaction, sender)
+        })
+    }
+}
+
+impl Deref for SealedBlockWithSenders {
+    type Target = SealedBlock;
+    fn deref(&self) -> &Self::Target {
+        &self.block
+    }
+}
+
+/// Implements the RLP encoding of a block, where the withdrawals are an optional trailing field
+/// like the base fee of the [`Header`].
+macro_rules! impl_block_rlp {
+    ($block:ident) => {
+        impl $block {
+            fn payload_length(&self) -> usize {
+                let mut length = 0;
+                length += self.header.length();
+                length += self.body.length();
+                length += self.ommers.length();
+                length += self.withdrawals.as_ref().map(|w| w.length()).unwrap_or_default();
+                length
+            }
+        }
+
+        impl Encodable for $block {
+            fn encode(&self, out: &mut dyn BufMut) {
+                let list_header =
+                    reth_rlp::Header { list: true, payload_length: self.payload_length() };
+                list_header.encode(out);
+                self.header.encode(out);
+                self.body.encode(out);
+                self.ommers.encode(out);
+                if let Some(ref withdrawals) = self.withdrawals {
+                    withdrawals.encode(out);
+                }
+            }
+
+            fn length(&self) -> usize {
+                let mut length = 0;
+                length += self.payload_length();
+                length += length_of_length(length);
+                length
+            }
+        }
+
+        impl Decodable for $block {
+            fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                let rlp_head = reth_rlp::Header::decode(buf)?;
+                if !rlp_head.list {
+                    return Err(DecodeError::UnexpectedString)
+                }
+                let started_len = buf.len();
+                let mut this = Self {
+                    header: Decodable::decode(buf)?,
+                    body: Decodable::decode(buf)?,
+                    ommers: Decodable::decode(buf)?,
+                    withdrawals: None,
+                };
+                let consumed = started_len - buf.len();
+                if consumed < rlp_head.payload_length {
+                    this.withdrawals = Some(Decodable::decode(buf)?);
+                }
+                let consumed = started_len - buf.len();
+                if consumed != rlp_head.payload_length {
+                    return Err(DecodeError::ListLengthMismatch {
+                        expected: rlp_head.payload_length,
+                        got: consumed,
+                    })
+                }
+                Ok(this)
+            }
+        }
+    };
+}
+
+impl_block_rlp!(Block);
+impl_block_rlp!(SealedBlock);
+
 /// Either a block hash _or_ a block number
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BlockHashOrNumber {
@@ -106,3 +247,57 @@ impl Decodable for BlockHashOrNumber {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Address;
+
+    #[test]
+    fn block_rlp_with_withdrawals() {
+        let mut block = Block {
+            header: Header { number: 1, base_fee_per_gas: Some(7), ..Default::default() },
+            ..Default::default()
+        };
+
+        let mut pre_shanghai = Vec::new();
+        block.encode(&mut pre_shanghai);
+        assert_eq!(pre_shanghai.len(), block.length());
+        assert_eq!(Block::decode(&mut pre_shanghai.as_slice()).unwrap(), block);
+
+        block.withdrawals = Some(vec![Withdrawal {
+            index: 1,
+            validator_index: 2,
+            address: Address::random(),
+            amount: 3,
+        }]);
+        let mut shanghai = Vec::new();
+        block.encode(&mut shanghai);
+        assert_eq!(shanghai.len(), block.length());
+        assert_eq!(Block::decode(&mut shanghai.as_slice()).unwrap(), block);
+
+        // an empty list of withdrawals is not the same as no withdrawals
+        block.withdrawals = Some(vec![]);
+        let mut empty = Vec::new();
+        block.encode(&mut empty);
+        assert_eq!(Block::decode(&mut empty.as_slice()).unwrap(), block);
+    }
+
+    #[test]
+    fn sealed_block_with_senders() {
+        let block = SealedBlock::default();
+        assert_eq!(
+            block.clone().try_seal_with_senders(),
+            Ok(SealedBlockWithSenders { block: block.clone(), senders: vec![] })
+        );
+        assert_eq!(SealedBlockWithSenders::new(block, vec![Address::random()]), None);
+
+        // a transaction with an invalid signature
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            Default::default(),
+            Default::default(),
+        );
+        let block = SealedBlock { body: vec![transaction], ..Default::default() };
+        assert_eq!(block.clone().try_seal_with_senders(), Err(block));
+    }
+}
@@ -1,6 +1,6 @@
 //! Bloom related utilities.
 
-use crate::{keccak256, Bloom, Log};
+use crate::{keccak256, Bloom, Log, Receipt};
 
 /// Length of bloom filter used for Ethereum.
 pub const BLOOM_BYTE_LENGTH: usize = 256;
@@ -30,6 +30,23 @@ where
     bloom
 }
 
+/// Calculate the logs bloom of a block, the union of the blooms of its receipts.
+pub fn receipts_bloom<'a, It>(receipts: It) -> Bloom
+where
+    It: IntoIterator<Item = &'a Receipt>,
+{
+    receipts.into_iter().fold(Bloom::zero(), |bloom, receipt| bloom | receipt.bloom)
+}
+
+/// Returns true if the bloom may contain the input, e.g. the address or a topic of a log.
+///
+/// A bloom can have false positives, but never false negatives.
+pub fn bloom_contains(bloom: &Bloom, input: &[u8]) -> bool {
+    let mut input_bloom = Bloom::zero();
+    m3_2048(&mut input_bloom, input);
+    input_bloom.0.iter().zip(bloom.0.iter()).all(|(input, bloom)| input & bloom == *input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +86,51 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn contains_logs() {
+        let log = Log {
+            address: hex!("22341ae42d6dd7384bc8584e50419ea3ac75b83f").into(),
+            topics: vec![
+                hex!("04491edcd115127caedbd478e2e7895ed80c7847e903431f94f9cfa579cad47f").into()
+            ],
+            data: vec![].into(),
+        };
+        let bloom = logs_bloom([&log]);
+
+        assert!(bloom_contains(&bloom, log.address.as_bytes()));
+        assert!(bloom_contains(&bloom, log.topics[0].as_bytes()));
+        assert!(!bloom_contains(&bloom, &hex!("e7fb22dfef11920312e4989a3a2b81e2ebf05986")));
+        assert!(!bloom_contains(&Bloom::zero(), log.address.as_bytes()));
+    }
+
+    #[test]
+    fn receipts_bloom_is_union_of_logs() {
+        let logs = vec![
+            Log {
+                address: hex!("22341ae42d6dd7384bc8584e50419ea3ac75b83f").into(),
+                topics: vec![],
+                data: vec![].into(),
+            },
+            Log {
+                address: hex!("e7fb22dfef11920312e4989a3a2b81e2ebf05986").into(),
+                topics: vec![hex!(
+                    "7f1fef85c4b037150d3675218e0cdb7cf38fea354759471e309f3354918a442f"
+                )
+                .into()],
+                data: vec![].into(),
+            },
+        ];
+        let receipts = logs
+            .iter()
+            .map(|log| Receipt {
+                bloom: logs_bloom([log]),
+                logs: vec![log.clone()],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(receipts_bloom(&receipts), logs_bloom(&logs));
+        assert_eq!(receipts_bloom(&[]), Bloom::zero());
+    }
 }
@@ -4,6 +4,12 @@ use reth_rlp::{Decodable, Encodable};
 use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
 
+mod spec;
+pub use spec::{
+    ChainSpec, ChainSpecBuilder, CliqueConfig, DepositContract, ForkCondition, DEV, GOERLI,
+    MAINNET, SEPOLIA,
+};
+
 /// Either a named or chain id or the actual id value
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Chain {
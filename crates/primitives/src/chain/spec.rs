@@ -0,0 +1,673 @@
+use crate::{
+    constants::EIP1559_INITIAL_BASE_FEE, proofs::EMPTY_ROOT, Address, BlockNumber, Chain,
+    ForkFilter, ForkFilterKey, ForkId, Genesis, Hardfork, Head, Header, H256, MAINNET_GENESIS,
+    U256,
+};
+use hex_literal::hex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The Ethereum mainnet spec.
+pub static MAINNET: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
+    chain: Chain::from(1u64),
+    genesis: serde_json::from_str(include_str!("../../res/genesis/mainnet.json"))
+        .expect("mainnet genesis is valid"),
+    genesis_hash: Some(MAINNET_GENESIS),
+    hardforks: BTreeMap::from([
+        (Hardfork::Frontier, ForkCondition::Block(0)),
+        (Hardfork::Homestead, ForkCondition::Block(1150000)),
+        (Hardfork::Dao, ForkCondition::Block(1920000)),
+        (Hardfork::Tangerine, ForkCondition::Block(2463000)),
+        (Hardfork::SpuriousDragon, ForkCondition::Block(2675000)),
+        (Hardfork::Byzantium, ForkCondition::Block(4370000)),
+        (Hardfork::Constantinople, ForkCondition::Block(7280000)),
+        (Hardfork::Petersburg, ForkCondition::Block(7280000)),
+        (Hardfork::Istanbul, ForkCondition::Block(9069000)),
+        (Hardfork::Muirglacier, ForkCondition::Block(9200000)),
+        (Hardfork::Berlin, ForkCondition::Block(12244000)),
+        (Hardfork::London, ForkCondition::Block(12965000)),
+        (Hardfork::ArrowGlacier, ForkCondition::Block(13773000)),
+        (Hardfork::GrayGlacier, ForkCondition::Block(15050000)),
+        (
+            Hardfork::Paris,
+            ForkCondition::TTD {
+                fork_block: Some(15537394),
+                total_difficulty: U256::from(58_750_000_000_000_000_000_000_u128),
+            },
+        ),
+    ]),
+    merge_netsplit_block: None,
+    dao_fork_support: true,
+    deposit_contract: Some(DepositContract {
+        address: Address::from(hex!("00000000219ab540356cbb839cbe05303d7705fa")),
+        block: 11052984,
+        topic: DEPOSIT_EVENT_TOPIC,
+    }),
+    clique: None,
+});
+
+/// The Goerli spec.
+pub static GOERLI: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
+    chain: Chain::from(5u64),
+    genesis: serde_json::from_str(include_str!("../../res/genesis/goerli.json"))
+        .expect("goerli genesis is valid"),
+    genesis_hash: Some(H256(hex!(
+        "bf7e331f7f7c1dd2e05159666b3bf8bc7a8a3a9eb1d518969eab529dd9b88c1a"
+    ))),
+    hardforks: BTreeMap::from([
+        (Hardfork::Frontier, ForkCondition::Block(0)),
+        (Hardfork::Homestead, ForkCondition::Block(0)),
+        (Hardfork::Tangerine, ForkCondition::Block(0)),
+        (Hardfork::SpuriousDragon, ForkCondition::Block(0)),
+        (Hardfork::Byzantium, ForkCondition::Block(0)),
+        (Hardfork::Constantinople, ForkCondition::Block(0)),
+        (Hardfork::Petersburg, ForkCondition::Block(0)),
+        (Hardfork::Istanbul, ForkCondition::Block(1561651)),
+        (Hardfork::Berlin, ForkCondition::Block(4460644)),
+        (Hardfork::London, ForkCondition::Block(5062605)),
+        (
+            Hardfork::Paris,
+            ForkCondition::TTD {
+                fork_block: Some(7382818),
+                total_difficulty: U256::from(10790000),
+            },
+        ),
+    ]),
+    merge_netsplit_block: None,
+    dao_fork_support: true,
+    deposit_contract: Some(DepositContract {
+        address: Address::from(hex!("ff50ed3d0ec03ac01d4c79aad74928bff48a7b2b")),
+        block: 4367322,
+        topic: DEPOSIT_EVENT_TOPIC,
+    }),
+    clique: Some(CliqueConfig { period: 15, epoch: 30000 }),
+});
+
+/// The Sepolia spec.
+pub static SEPOLIA: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
+    chain: Chain::from(11155111u64),
+    genesis: serde_json::from_str(include_str!("../../res/genesis/sepolia.json"))
+        .expect("sepolia genesis is valid"),
+    genesis_hash: Some(H256(hex!(
+        "25a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9"
+    ))),
+    hardforks: BTreeMap::from([
+        (Hardfork::Frontier, ForkCondition::Block(0)),
+        (Hardfork::Homestead, ForkCondition::Block(0)),
+        (Hardfork::Tangerine, ForkCondition::Block(0)),
+        (Hardfork::SpuriousDragon, ForkCondition::Block(0)),
+        (Hardfork::Byzantium, ForkCondition::Block(0)),
+        (Hardfork::Constantinople, ForkCondition::Block(0)),
+        (Hardfork::Petersburg, ForkCondition::Block(0)),
+        (Hardfork::Istanbul, ForkCondition::Block(0)),
+        (Hardfork::Muirglacier, ForkCondition::Block(0)),
+        (Hardfork::Berlin, ForkCondition::Block(0)),
+        (Hardfork::London, ForkCondition::Block(0)),
+        (
+            Hardfork::Paris,
+            ForkCondition::TTD {
+                fork_block: Some(1450409),
+                total_difficulty: U256::from(17_000_000_000_000_000_u64),
+            },
+        ),
+    ]),
+    merge_netsplit_block: Some(1735371),
+    dao_fork_support: true,
+    deposit_contract: Some(DepositContract {
+        address: Address::from(hex!("7f02c3e3c98b133055b8b348b2ac625669ed295d")),
+        block: 1273020,
+        topic: DEPOSIT_EVENT_TOPIC,
+    }),
+    clique: None,
+});
+
+/// The spec of the local development chain, which activates all forks up to Paris at genesis and
+/// prefunds the default development accounts.
+pub static DEV: Lazy<ChainSpec> = Lazy::new(|| {
+    ChainSpecBuilder::default()
+        .chain(1337u64)
+        .genesis(
+            serde_json::from_str(include_str!("../../res/genesis/dev.json"))
+                .expect("dev genesis is valid"),
+        )
+        .paris_activated()
+        .build()
+});
+
+/// The topic of the `DepositEvent` of the beacon chain deposit contract.
+const DEPOSIT_EVENT_TOPIC: H256 =
+    H256(hex!("649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5"));
+
+/// Describes a chain: its genesis, the activation of its forks and the specifics of the chain,
+/// e.g. the DAO fork and the deposit contract.
+///
+/// Use one of the built-in specs, e.g. [MAINNET], convert a custom [Genesis] or build one with
+/// the [ChainSpecBuilder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainSpec {
+    /// The chain id.
+    pub chain: Chain,
+    /// The genesis block and the initial state.
+    pub genesis: Genesis,
+    /// The hash of the genesis block, computed from the genesis if not set.
+    pub genesis_hash: Option<H256>,
+    /// The activation of the forks. Forks that are not listed are never activated.
+    pub hardforks: BTreeMap<Hardfork, ForkCondition>,
+    /// The block at which the nodes that passed the merge split off the network, if the chain has
+    /// one. Only used as a fork in the [ForkId].
+    pub merge_netsplit_block: Option<BlockNumber>,
+    /// Whether the chain supports the DAO fork, as opposed to Ethereum Classic.
+    pub dao_fork_support: bool,
+    /// The beacon chain deposit contract, if the chain has one.
+    pub deposit_contract: Option<DepositContract>,
+    /// The clique proof of authority configuration, if the chain is sealed by clique.
+    pub clique: Option<CliqueConfig>,
+}
+
+impl ChainSpec {
+    /// The id of the chain.
+    pub fn chain_id(&self) -> u64 {
+        self.chain.id()
+    }
+
+    /// The genesis block and the initial state.
+    pub fn genesis(&self) -> &Genesis {
+        &self.genesis
+    }
+
+    /// The header of the genesis block.
+    ///
+    /// The base fee defaults to the initial base fee of EIP-1559 if London is activated at
+    /// genesis, and the withdrawals root to the root of an empty trie if Shanghai is. If Cancun
+    /// is activated at genesis, the blob gas fields are zero and the parent beacon block root is
+    /// the zero hash.
+    pub fn genesis_header(&self) -> Header {
+        let london_at_genesis = self.fork(Hardfork::London).active_at_block(0);
+        let shanghai_at_genesis =
+            self.fork(Hardfork::Shanghai).active_at_timestamp(self.genesis.timestamp);
+        let cancun_at_genesis =
+            self.fork(Hardfork::Cancun).active_at_timestamp(self.genesis.timestamp);
+        Header {
+            gas_limit: self.genesis.gas_limit,
+            difficulty: self.genesis.difficulty,
+            nonce: self.genesis.nonce,
+            extra_data: self.genesis.extra_data.0.clone(),
+            state_root: self
+                .genesis
+                .state_root
+                .unwrap_or_else(|| self.genesis.compute_state_root()),
+            timestamp: self.genesis.timestamp,
+            mix_hash: self.genesis.mix_hash,
+            beneficiary: self.genesis.coinbase,
+            base_fee_per_gas: self
+                .genesis
+                .base_fee_per_gas
+                .map(|base_fee| base_fee.as_u64())
+                .or_else(|| london_at_genesis.then_some(EIP1559_INITIAL_BASE_FEE)),
+            withdrawals_root: shanghai_at_genesis.then_some(EMPTY_ROOT),
+            blob_gas_used: cancun_at_genesis.then_some(0),
+            excess_blob_gas: cancun_at_genesis.then_some(0),
+            parent_beacon_block_root: cancun_at_genesis.then_some(H256::zero()),
+            ..Default::default()
+        }
+    }
+
+    /// The hash of the genesis block.
+    pub fn genesis_hash(&self) -> H256 {
+        self.genesis_hash.unwrap_or_else(|| self.genesis_header().hash_slow())
+    }
+
+    /// The activation of the fork, which is [ForkCondition::Never] if the fork is not listed.
+    pub fn fork(&self, fork: Hardfork) -> ForkCondition {
+        self.hardforks.get(&fork).copied().unwrap_or(ForkCondition::Never)
+    }
+
+    /// Returns `true` if the fork is activated at the block.
+    pub fn is_fork_active_at_block(&self, fork: Hardfork, block: BlockNumber) -> bool {
+        self.fork(fork).active_at_block(block)
+    }
+
+    /// Returns `true` if the fork is activated at the timestamp.
+    pub fn is_fork_active_at_timestamp(&self, fork: Hardfork, timestamp: u64) -> bool {
+        self.fork(fork).active_at_timestamp(timestamp)
+    }
+
+    /// The total difficulty at which the merge happens, if the chain merges.
+    pub fn terminal_total_difficulty(&self) -> Option<U256> {
+        self.fork(Hardfork::Paris).ttd()
+    }
+
+    /// Returns `true` if the beneficiary of the block is rewarded, which is only the case for
+    /// proof of work blocks.
+    pub fn has_block_reward(&self, block: BlockNumber) -> bool {
+        self.clique.is_none() && !self.is_fork_active_at_block(Hardfork::Paris, block)
+    }
+
+    /// The [ForkFilter] of the chain at the given head, see `EIP-2124` and `EIP-6122`.
+    ///
+    /// All forks that are activated by block number or timestamp are part of the filter, plus the
+    /// merge netsplit block. The merge itself is not, as it is activated by the total difficulty.
+    pub fn fork_filter(&self, head: Head) -> ForkFilter {
+        let forks = self
+            .hardforks
+            .values()
+            .filter_map(|condition| match condition {
+                ForkCondition::Block(block) => Some(ForkFilterKey::Block(*block)),
+                ForkCondition::Timestamp(time) => Some(ForkFilterKey::Time(*time)),
+                ForkCondition::TTD { .. } | ForkCondition::Never => None,
+            })
+            .chain(self.merge_netsplit_block.map(ForkFilterKey::Block));
+
+        ForkFilter::new(head, self.genesis_hash(), self.genesis.timestamp, forks)
+    }
+
+    /// The [ForkId] of the chain at the given head.
+    pub fn fork_id(&self, head: &Head) -> ForkId {
+        self.fork_filter(*head).current()
+    }
+}
+
+impl From<Genesis> for ChainSpec {
+    /// Create the spec of a custom chain from the config of its genesis.
+    fn from(genesis: Genesis) -> Self {
+        let config = &genesis.config;
+        let blocks = [
+            (Hardfork::Frontier, Some(0)),
+            (Hardfork::Homestead, config.homestead_block),
+            (Hardfork::Dao, config.dao_fork_block),
+            (Hardfork::Tangerine, config.eip150_block),
+            (Hardfork::SpuriousDragon, config.eip158_block),
+            (Hardfork::Byzantium, config.byzantium_block),
+            (Hardfork::Constantinople, config.constantinople_block),
+            (Hardfork::Petersburg, config.petersburg_block),
+            (Hardfork::Istanbul, config.istanbul_block),
+            (Hardfork::Muirglacier, config.muir_glacier_block),
+            (Hardfork::Berlin, config.berlin_block),
+            (Hardfork::London, config.london_block),
+            (Hardfork::ArrowGlacier, config.arrow_glacier_block),
+            (Hardfork::GrayGlacier, config.gray_glacier_block),
+        ];
+        let mut hardforks = blocks
+            .into_iter()
+            .filter_map(|(fork, block)| Some((fork, ForkCondition::Block(block?))))
+            .collect::<BTreeMap<_, _>>();
+        if let Some(ttd) = config.terminal_total_difficulty {
+            hardforks.insert(
+                Hardfork::Paris,
+                ForkCondition::TTD {
+                    fork_block: config.merge_netsplit_block,
+                    total_difficulty: U256::from(ttd),
+                },
+            );
+        }
+        if let Some(time) = config.shanghai_time {
+            hardforks.insert(Hardfork::Shanghai, ForkCondition::Timestamp(time));
+        }
+        if let Some(time) = config.cancun_time {
+            hardforks.insert(Hardfork::Cancun, ForkCondition::Timestamp(time));
+        }
+
+        Self {
+            chain: config.chain_id.into(),
+            dao_fork_support: config.dao_fork_support,
+            clique: config.clique.clone(),
+            hardforks,
+            genesis,
+            genesis_hash: None,
+            merge_netsplit_block: config.merge_netsplit_block,
+            deposit_contract: None,
+        }
+    }
+}
+
+/// The condition at which a fork is activated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkCondition {
+    /// The fork is activated at the block.
+    Block(BlockNumber),
+    /// The fork is activated once the total difficulty reaches the terminal total difficulty,
+    /// i.e. the merge.
+    TTD {
+        /// The first block after the merge, if known.
+        fork_block: Option<BlockNumber>,
+        /// The terminal total difficulty.
+        total_difficulty: U256,
+    },
+    /// The fork is activated at the first block with at least this timestamp.
+    Timestamp(u64),
+    /// The fork is never activated.
+    Never,
+}
+
+impl ForkCondition {
+    /// Returns `true` if the fork is activated at the block.
+    ///
+    /// A fork that is activated by the total difficulty is only known to be active from its fork
+    /// block on.
+    pub fn active_at_block(&self, block: BlockNumber) -> bool {
+        match self {
+            ForkCondition::Block(fork_block) |
+            ForkCondition::TTD { fork_block: Some(fork_block), .. } => block >= *fork_block,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the fork is activated at the timestamp.
+    pub fn active_at_timestamp(&self, timestamp: u64) -> bool {
+        matches!(self, ForkCondition::Timestamp(time) if timestamp >= *time)
+    }
+
+    /// Returns `true` if the fork is activated at the total difficulty.
+    pub fn active_at_ttd(&self, total_difficulty: U256) -> bool {
+        matches!(self, ForkCondition::TTD { total_difficulty: ttd, .. } if total_difficulty >= *ttd)
+    }
+
+    /// The block the fork is activated at, if it is activated by block.
+    pub fn as_block(&self) -> Option<BlockNumber> {
+        match self {
+            ForkCondition::Block(block) | ForkCondition::TTD { fork_block: Some(block), .. } => {
+                Some(*block)
+            }
+            _ => None,
+        }
+    }
+
+    /// The terminal total difficulty, if the fork is activated by the total difficulty.
+    pub fn ttd(&self) -> Option<U256> {
+        match self {
+            ForkCondition::TTD { total_difficulty, .. } => Some(*total_difficulty),
+            _ => None,
+        }
+    }
+}
+
+/// The beacon chain deposit contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositContract {
+    /// The address of the contract.
+    pub address: Address,
+    /// The block the contract was deployed in.
+    pub block: BlockNumber,
+    /// The topic of the deposit event.
+    pub topic: H256,
+}
+
+/// Configuration of the clique proof of authority consensus, see EIP-225.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CliqueConfig {
+    /// Minimum number of seconds between two blocks.
+    pub period: u64,
+    /// Number of blocks after which the votes are reset and the signers are checkpointed.
+    pub epoch: u64,
+}
+
+/// Builds a [ChainSpec], mostly for testing.
+///
+/// The `*_activated` methods activate the fork and all forks before it at genesis.
+#[derive(Debug, Default)]
+pub struct ChainSpecBuilder {
+    chain: Option<Chain>,
+    genesis: Option<Genesis>,
+    hardforks: BTreeMap<Hardfork, ForkCondition>,
+    dao_fork_support: bool,
+}
+
+impl ChainSpecBuilder {
+    /// Start from the mainnet spec.
+    pub fn mainnet() -> Self {
+        Self {
+            chain: Some(MAINNET.chain),
+            genesis: Some(MAINNET.genesis.clone()),
+            hardforks: MAINNET.hardforks.clone(),
+            dao_fork_support: MAINNET.dao_fork_support,
+        }
+    }
+
+    /// Set the chain id.
+    pub fn chain(mut self, chain: impl Into<Chain>) -> Self {
+        self.chain = Some(chain.into());
+        self
+    }
+
+    /// Set the genesis.
+    pub fn genesis(mut self, genesis: Genesis) -> Self {
+        self.genesis = Some(genesis);
+        self
+    }
+
+    /// Set the activation of a fork.
+    pub fn with_fork(mut self, fork: Hardfork, condition: ForkCondition) -> Self {
+        self.hardforks.insert(fork, condition);
+        self
+    }
+
+    /// Activate Frontier at genesis.
+    pub fn frontier_activated(self) -> Self {
+        self.activated(Hardfork::Frontier)
+    }
+
+    /// Activate Homestead and the forks before it at genesis.
+    pub fn homestead_activated(self) -> Self {
+        self.activated(Hardfork::Homestead)
+    }
+
+    /// Activate Tangerine Whistle and the forks before it at genesis.
+    pub fn tangerine_whistle_activated(self) -> Self {
+        self.activated(Hardfork::Tangerine)
+    }
+
+    /// Activate Spurious Dragon and the forks before it at genesis.
+    pub fn spurious_dragon_activated(self) -> Self {
+        self.activated(Hardfork::SpuriousDragon)
+    }
+
+    /// Activate Byzantium and the forks before it at genesis.
+    pub fn byzantium_activated(self) -> Self {
+        self.activated(Hardfork::Byzantium)
+    }
+
+    /// Activate Petersburg and the forks before it at genesis.
+    pub fn petersburg_activated(self) -> Self {
+        self.activated(Hardfork::Petersburg)
+    }
+
+    /// Activate Istanbul and the forks before it at genesis.
+    pub fn istanbul_activated(self) -> Self {
+        self.activated(Hardfork::Istanbul)
+    }
+
+    /// Activate Berlin and the forks before it at genesis.
+    pub fn berlin_activated(self) -> Self {
+        self.activated(Hardfork::Berlin)
+    }
+
+    /// Activate London and the forks before it at genesis.
+    pub fn london_activated(self) -> Self {
+        self.activated(Hardfork::London)
+    }
+
+    /// Activate Paris and the forks before it at genesis.
+    pub fn paris_activated(self) -> Self {
+        self.activated(Hardfork::Paris)
+    }
+
+    /// Activate Shanghai and the forks before it at genesis.
+    pub fn shanghai_activated(self) -> Self {
+        self.activated(Hardfork::Shanghai)
+    }
+
+    /// Activate Cancun and the forks before it at genesis.
+    pub fn cancun_activated(self) -> Self {
+        self.activated(Hardfork::Cancun)
+    }
+
+    /// Activate the fork and the forks before it at genesis, skipping the DAO fork.
+    fn activated(mut self, fork: Hardfork) -> Self {
+        for hardfork in Hardfork::CHRONOLOGICAL.into_iter().take_while(|hardfork| *hardfork <= fork)
+        {
+            let condition = match hardfork {
+                Hardfork::Dao => continue,
+                Hardfork::Paris => {
+                    ForkCondition::TTD { fork_block: Some(0), total_difficulty: U256::zero() }
+                }
+                Hardfork::Shanghai | Hardfork::Cancun => ForkCondition::Timestamp(0),
+                _ => ForkCondition::Block(0),
+            };
+            self.hardforks.insert(hardfork, condition);
+        }
+        self
+    }
+
+    /// Build the [ChainSpec].
+    ///
+    /// The chain id defaults to mainnet and the genesis to an empty one.
+    pub fn build(self) -> ChainSpec {
+        ChainSpec {
+            chain: self.chain.unwrap_or_default(),
+            genesis: self.genesis.unwrap_or_default(),
+            genesis_hash: None,
+            hardforks: self.hardforks,
+            merge_netsplit_block: None,
+            dao_fork_support: self.dao_fork_support,
+            deposit_contract: None,
+            clique: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ForkHash;
+
+    #[test]
+    fn genesis_hashes() {
+        for spec in [&MAINNET, &GOERLI, &SEPOLIA] {
+            assert_eq!(spec.genesis_header().hash_slow(), spec.genesis_hash());
+        }
+    }
+
+    #[test]
+    fn genesis_state_roots() {
+        for spec in [&GOERLI, &SEPOLIA, &DEV] {
+            assert_eq!(spec.genesis.compute_state_root(), spec.genesis_header().state_root);
+        }
+    }
+
+    #[test]
+    fn fork_activation() {
+        assert!(!MAINNET.is_fork_active_at_block(Hardfork::London, 12964999));
+        assert!(MAINNET.is_fork_active_at_block(Hardfork::London, 12965000));
+        assert!(MAINNET.is_fork_active_at_block(Hardfork::Paris, 15537394));
+        assert!(!MAINNET.is_fork_active_at_block(Hardfork::Shanghai, u64::MAX));
+        assert!(MAINNET.has_block_reward(15537393));
+        assert!(!MAINNET.has_block_reward(15537394));
+        assert!(!GOERLI.has_block_reward(1));
+
+        let spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        assert!(spec.is_fork_active_at_timestamp(Hardfork::Shanghai, 0));
+        assert!(!spec.is_fork_active_at_block(Hardfork::Shanghai, 0));
+
+        // the genesis of a chain with Shanghai at genesis has an empty withdrawals trie
+        assert_eq!(spec.genesis_header().withdrawals_root, Some(EMPTY_ROOT));
+        assert_eq!(MAINNET.genesis_header().withdrawals_root, None);
+    }
+
+    #[test]
+    fn fork_ids() {
+        let head = |number, timestamp| Head { number, timestamp, ..Default::default() };
+        let fork_id = |hash, next| ForkId { hash: ForkHash(hash), next };
+
+        let cases = [
+            (0, fork_id(hex!("fc64ec04"), 1150000)),
+            (1150000, fork_id(hex!("97c2c34c"), 1920000)),
+            (1920000, fork_id(hex!("91d1f948"), 2463000)),
+            (4370000, fork_id(hex!("a00bc324"), 7280000)),
+            (9069000, fork_id(hex!("879d6e30"), 9200000)),
+            (12965000, fork_id(hex!("b715077d"), 13773000)),
+            (15050000, fork_id(hex!("f0afd0e3"), 0)),
+            (20000000, fork_id(hex!("f0afd0e3"), 0)),
+        ];
+        for (number, expected) in cases {
+            assert_eq!(MAINNET.fork_id(&head(number, 0)), expected);
+        }
+
+        // the merge is activated by the total difficulty and is not part of the fork id
+        let cases = [
+            (0, fork_id(hex!("a3f5ab08"), 1561651)),
+            (1561651, fork_id(hex!("c25efa5c"), 4460644)),
+            (4460644, fork_id(hex!("757a1c47"), 5062605)),
+            (7382818, fork_id(hex!("b8c6299d"), 0)),
+        ];
+        for (number, expected) in cases {
+            assert_eq!(GOERLI.fork_id(&head(number, 0)), expected);
+        }
+
+        // but the merge netsplit block is
+        let cases = [
+            (0, fork_id(hex!("fe3366e7"), 1735371)),
+            (1450409, fork_id(hex!("fe3366e7"), 1735371)),
+            (1735371, fork_id(hex!("b96cbd13"), 0)),
+        ];
+        for (number, expected) in cases {
+            assert_eq!(SEPOLIA.fork_id(&head(number, 0)), expected);
+        }
+
+        // timestamp forks follow the block forks
+        let spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1681338455))
+            .build();
+        assert_eq!(
+            spec.fork_id(&head(20000000, 1681338454)),
+            fork_id(hex!("f0afd0e3"), 1681338455)
+        );
+        assert_eq!(spec.fork_id(&head(20000000, 1681338455)), fork_id(hex!("dce96c2d"), 0));
+    }
+
+    #[test]
+    fn custom_genesis() {
+        let genesis: Genesis = serde_json::from_str(
+            r#"{
+                "config": {
+                    "chainId": 1337,
+                    "homesteadBlock": 0,
+                    "eip150Block": 0,
+                    "eip155Block": 0,
+                    "eip158Block": 0,
+                    "byzantiumBlock": 0,
+                    "constantinopleBlock": 0,
+                    "petersburgBlock": 0,
+                    "istanbulBlock": 0,
+                    "berlinBlock": 0,
+                    "londonBlock": 10,
+                    "terminalTotalDifficulty": 100,
+                    "shanghaiTime": 1681338455,
+                    "clique": { "period": 5, "epoch": 30000 }
+                },
+                "nonce": "0x0",
+                "timestamp": "0x0",
+                "extraData": "0x",
+                "gasLimit": "0x1c9c380",
+                "difficulty": "0x1",
+                "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "coinbase": "0x0000000000000000000000000000000000000000",
+                "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "alloc": {}
+            }"#,
+        )
+        .unwrap();
+        let spec = ChainSpec::from(genesis);
+
+        assert_eq!(spec.chain_id(), 1337);
+        assert_eq!(spec.fork(Hardfork::Dao), ForkCondition::Never);
+        assert_eq!(spec.fork(Hardfork::London), ForkCondition::Block(10));
+        assert_eq!(
+            spec.fork(Hardfork::Paris),
+            ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) }
+        );
+        assert_eq!(spec.fork(Hardfork::Shanghai), ForkCondition::Timestamp(1681338455));
+        assert_eq!(spec.clique, Some(CliqueConfig { period: 5, epoch: 30000 }));
+        assert_eq!(spec.genesis_header().base_fee_per_gas, None);
+    }
+}
@@ -12,3 +12,27 @@ pub const KECCAK_EMPTY: H256 =
 /// Ommer root of empty list.
 pub const EMPTY_OMMER_ROOT: H256 =
     H256(hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"));
+
+/// Initial base fee as defined in: https://eips.ethereum.org/EIPS/eip-1559
+pub const EIP1559_INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+/// Base fee max change denominator as defined in: https://eips.ethereum.org/EIPS/eip-1559
+pub const EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Elasticity multiplier as defined in: https://eips.ethereum.org/EIPS/eip-1559
+pub const EIP1559_ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// The amount of blob gas consumed by a single blob as defined in: https://eips.ethereum.org/EIPS/eip-4844
+pub const BLOB_GAS_PER_BLOB: u64 = 131_072;
+
+/// The target amount of blob gas consumed per block as defined in: https://eips.ethereum.org/EIPS/eip-4844
+pub const TARGET_BLOB_GAS_PER_BLOCK: u64 = 393_216;
+
+/// The maximum amount of blob gas consumed per block as defined in: https://eips.ethereum.org/EIPS/eip-4844
+pub const MAX_BLOB_GAS_PER_BLOCK: u64 = 786_432;
+
+/// The minimum price of a unit of blob gas as defined in: https://eips.ethereum.org/EIPS/eip-4844
+pub const MIN_BLOB_GASPRICE: u128 = 1;
+
+/// The denominator of the blob gas price update as defined in: https://eips.ethereum.org/EIPS/eip-4844
+pub const BLOB_GASPRICE_UPDATE_FRACTION: u128 = 3_338_477;
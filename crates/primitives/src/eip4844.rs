@@ -0,0 +1,74 @@
+//! Helpers for the blob gas of [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844).
+
+use crate::constants::{
+    BLOB_GASPRICE_UPDATE_FRACTION, MIN_BLOB_GASPRICE, TARGET_BLOB_GAS_PER_BLOCK,
+};
+
+/// Calculates the excess blob gas of a block from the excess blob gas and the blob gas used of
+/// its parent.
+pub fn calculate_excess_blob_gas(parent_excess_blob_gas: u64, parent_blob_gas_used: u64) -> u64 {
+    (parent_excess_blob_gas + parent_blob_gas_used).saturating_sub(TARGET_BLOB_GAS_PER_BLOCK)
+}
+
+/// Calculates the price of a unit of blob gas from the excess blob gas of the block.
+pub fn calculate_blob_gasprice(excess_blob_gas: u64) -> u128 {
+    fake_exponential(MIN_BLOB_GASPRICE, excess_blob_gas as u128, BLOB_GASPRICE_UPDATE_FRACTION)
+}
+
+/// Approximates `factor * e ** (numerator / denominator)` with a Taylor expansion.
+fn fake_exponential(factor: u128, numerator: u128, denominator: u128) -> u128 {
+    let mut output = 0;
+    let mut accum = factor * denominator;
+    let mut i = 1;
+    while accum > 0 {
+        output += accum;
+        accum = accum * numerator / (denominator * i);
+        i += 1;
+    }
+    output / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::BLOB_GAS_PER_BLOB;
+
+    #[test]
+    fn excess_blob_gas() {
+        assert_eq!(calculate_excess_blob_gas(0, 0), 0);
+        assert_eq!(calculate_excess_blob_gas(0, TARGET_BLOB_GAS_PER_BLOCK), 0);
+        assert_eq!(
+            calculate_excess_blob_gas(BLOB_GAS_PER_BLOB, TARGET_BLOB_GAS_PER_BLOCK),
+            BLOB_GAS_PER_BLOB
+        );
+        assert_eq!(
+            calculate_excess_blob_gas(3 * BLOB_GAS_PER_BLOB, 2 * BLOB_GAS_PER_BLOB),
+            2 * BLOB_GAS_PER_BLOB
+        );
+    }
+
+    #[test]
+    fn fake_exp() {
+        // test vectors of the execution specs
+        for (factor, numerator, denominator, expected) in [
+            (1u128, 0u128, 1u128, 1u128),
+            (38493, 0, 1000, 38493),
+            (0, 1234, 2345, 0),
+            (1, 2, 1, 6),
+            (1, 4, 2, 6),
+            (1, 3, 1, 16),
+            (1, 6, 2, 18),
+            (1, 4, 1, 49),
+            (1, 8, 2, 50),
+            (10, 8, 2, 542),
+            (11, 8, 2, 596),
+            (1, 5, 1, 136),
+            (1, 5, 2, 11),
+            (2, 5, 2, 23),
+            (1, 50000000, 2225652, 5709098764),
+        ] {
+            assert_eq!(fake_exponential(factor, numerator, denominator), expected);
+        }
+        assert_eq!(calculate_blob_gasprice(0), MIN_BLOB_GASPRICE);
+    }
+}
@@ -3,7 +3,7 @@
 
 #![deny(missing_docs)]
 
-use crate::{BlockNumber, H256};
+use crate::{BlockNumber, Head, H256};
 use crc::crc32;
 use reth_rlp::*;
 use serde::{Deserialize, Serialize};
@@ -101,52 +101,94 @@ pub enum ValidationError {
     },
 }
 
+/// The timestamp before which a `FORK_NEXT` of a [`ForkId`] is a block number, and from which it is
+/// a timestamp, see [EIP-6122](https://eips.ethereum.org/EIPS/eip-6122).
+///
+/// Block numbers are far below this, timestamps of forks far above it.
+const TIMESTAMP_BEFORE_ETHEREUM_MAINNET: u64 = 1_300_000_000;
+
+/// A fork in the [`ForkFilter`], which is activated either at a block or, since
+/// [EIP-6122](https://eips.ethereum.org/EIPS/eip-6122), at a timestamp.
+///
+/// Forks activated by block always precede the forks activated by timestamp.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ForkFilterKey {
+    /// The fork is activated at the block.
+    Block(BlockNumber),
+    /// The fork is activated at the timestamp.
+    Time(u64),
+}
+
+impl ForkFilterKey {
+    /// Returns `true` if the fork is activated at the head.
+    fn is_active(&self, head: &Head) -> bool {
+        match self {
+            ForkFilterKey::Block(block) => *block <= head.number,
+            ForkFilterKey::Time(time) => *time <= head.timestamp,
+        }
+    }
+
+    /// The block or the timestamp, which is what the fork hash and `FORK_NEXT` are made of.
+    fn value(&self) -> u64 {
+        match self {
+            ForkFilterKey::Block(value) | ForkFilterKey::Time(value) => *value,
+        }
+    }
+}
+
 /// Filter that describes the state of blockchain and can be used to check incoming `ForkId`s for
 /// compatibility.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ForkFilter {
-    forks: BTreeMap<BlockNumber, ForkHash>,
+    forks: BTreeMap<ForkFilterKey, ForkHash>,
 
-    head: BlockNumber,
+    head: Head,
 
     cache: Cache,
 }
 
 impl ForkFilter {
-    /// Create the filter from provided head, genesis block hash, past forks and expected future
-    /// forks.
-    pub fn new<F, B>(head: BlockNumber, genesis: H256, forks: F) -> Self
+    /// Create the filter from provided head, genesis block hash and timestamp, past forks and
+    /// expected future forks.
+    ///
+    /// Forks at genesis are not part of the fork hash: forks activated at block 0 and, as defined
+    /// by EIP-6122, forks activated at or before the genesis timestamp.
+    pub fn new<F>(head: Head, genesis: H256, genesis_timestamp: u64, forks: F) -> Self
     where
-        F: IntoIterator<Item = B>,
-        B: Into<BlockNumber>,
+        F: IntoIterator<Item = ForkFilterKey>,
     {
         let genesis_fork_hash = ForkHash::from(genesis);
-        let mut forks = forks.into_iter().map(Into::into).collect::<BTreeSet<_>>();
-        forks.remove(&0);
+        let forks = forks
+            .into_iter()
+            .filter(|key| match key {
+                ForkFilterKey::Block(block) => *block > 0,
+                ForkFilterKey::Time(time) => *time > genesis_timestamp,
+            })
+            .collect::<BTreeSet<_>>();
 
         let forks = forks
             .into_iter()
             .fold(
-                (BTreeMap::from([(0, genesis_fork_hash)]), genesis_fork_hash),
-                |(mut acc, base_hash), block| {
-                    let fork_hash = base_hash + block;
-                    acc.insert(block, fork_hash);
+                (BTreeMap::from([(ForkFilterKey::Block(0), genesis_fork_hash)]), genesis_fork_hash),
+                |(mut acc, base_hash), key| {
+                    let fork_hash = base_hash + key.value();
+                    acc.insert(key, fork_hash);
                     (acc, fork_hash)
                 },
             )
             .0;
 
-        let cache = Cache::compute_cache(&forks, head);
+        let cache = Cache::compute_cache(&forks, &head);
 
         Self { forks, head, cache }
     }
 
-    fn set_head_priv(&mut self, head: BlockNumber) -> Option<ForkTransition> {
+    fn set_head_priv(&mut self, head: Head) -> Option<ForkTransition> {
         let recompute_cache = {
-            if head < self.cache.epoch_start {
+            if !self.cache.epoch_start.is_active(&head) {
                 true
             } else if let Some(epoch_end) = self.cache.epoch_end {
-                head >= epoch_end
+                epoch_end.is_active(&head)
             } else {
                 false
             }
@@ -158,7 +200,7 @@ impl ForkFilter {
         if recompute_cache {
             let past = self.current();
 
-            self.cache = Cache::compute_cache(&self.forks, head);
+            self.cache = Cache::compute_cache(&self.forks, &head);
 
             transition = Some(ForkTransition { current: self.current(), past })
         }
@@ -171,7 +213,7 @@ impl ForkFilter {
     /// Set the current head.
     ///
     /// If the update updates the current [`ForkId`] it returns a [`ForkTransition`]
-    pub fn set_head(&mut self, head: BlockNumber) -> Option<ForkTransition> {
+    pub fn set_head(&mut self, head: Head) -> Option<ForkTransition> {
         self.set_head_priv(head)
     }
 
@@ -197,8 +239,15 @@ impl ForkFilter {
                 return Ok(())
             }
 
+            // a FORK_NEXT this large is a timestamp, see EIP-6122
+            let head = if fork_id.next > TIMESTAMP_BEFORE_ETHEREUM_MAINNET {
+                self.head.timestamp
+            } else {
+                self.head.number
+            };
+
             //... compare local head to FORK_NEXT.
-            return if self.head >= fork_id.next {
+            return if head >= fork_id.next {
                 // 1a) A remotely announced but remotely not passed block is already passed locally,
                 // disconnect, since the chains are incompatible.
                 Err(ValidationError::LocalIncompatibleOrStale {
@@ -215,10 +264,10 @@ impl ForkFilter {
         let mut it = self.cache.past.iter();
         while let Some((_, hash)) = it.next() {
             if *hash == fork_id.hash {
-                // ...and the remote FORK_NEXT matches with the locally following fork block number,
-                // connect.
-                if let Some((actual_fork_block, _)) = it.next() {
-                    return if *actual_fork_block == fork_id.next {
+                // ...and the remote FORK_NEXT matches with the locally following fork block number
+                // or timestamp, connect.
+                if let Some((actual_fork, _)) = it.next() {
+                    return if actual_fork.value() == fork_id.next {
                         Ok(())
                     } else {
                         Err(ValidationError::RemoteStale { local: self.current(), remote: fork_id })
@@ -257,28 +306,28 @@ pub struct ForkTransition {
 struct Cache {
     // An epoch is a period between forks.
     // When we progress from one fork to the next one we move to the next epoch.
-    epoch_start: BlockNumber,
-    epoch_end: Option<BlockNumber>,
-    past: Vec<(BlockNumber, ForkHash)>,
+    epoch_start: ForkFilterKey,
+    epoch_end: Option<ForkFilterKey>,
+    past: Vec<(ForkFilterKey, ForkHash)>,
     future: Vec<ForkHash>,
     fork_id: ForkId,
 }
 
 impl Cache {
     /// Compute cache.
-    fn compute_cache(forks: &BTreeMap<BlockNumber, ForkHash>, head: BlockNumber) -> Self {
+    fn compute_cache(forks: &BTreeMap<ForkFilterKey, ForkHash>, head: &Head) -> Self {
         let mut past = Vec::with_capacity(forks.len());
         let mut future = Vec::with_capacity(forks.len());
 
-        let mut epoch_start = 0;
+        let mut epoch_start = ForkFilterKey::Block(0);
         let mut epoch_end = None;
-        for (block, hash) in forks {
-            if *block <= head {
-                epoch_start = *block;
-                past.push((*block, *hash));
+        for (key, hash) in forks {
+            if key.is_active(head) {
+                epoch_start = *key;
+                past.push((*key, *hash));
             } else {
                 if epoch_end.is_none() {
-                    epoch_end = Some(*block);
+                    epoch_end = Some(*key);
                 }
                 future.push(*hash);
             }
@@ -286,7 +335,7 @@ impl Cache {
 
         let fork_id = ForkId {
             hash: past.last().expect("there is always at least one - genesis - fork hash; qed").1,
-            next: epoch_end.unwrap_or(0),
+            next: epoch_end.map(|key| key.value()).unwrap_or(0),
         };
 
         Self { epoch_start, epoch_end, past, future, fork_id }
@@ -300,6 +349,11 @@ mod tests {
     const GENESIS_HASH: H256 =
         H256(hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"));
 
+    /// A head at the given block, with a timestamp before any timestamp fork.
+    fn block(number: BlockNumber) -> Head {
+        Head { number, ..Default::default() }
+    }
+
     // EIP test vectors.
 
     #[test]
@@ -317,18 +371,20 @@ mod tests {
     #[test]
     fn compatibility_check() {
         let mut filter = ForkFilter::new(
-            0,
+            Head::default(),
             GENESIS_HASH,
-            vec![1_150_000u64, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000],
+            0,
+            [1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000]
+                .map(ForkFilterKey::Block),
         );
 
         // Local is mainnet Petersburg, remote announces the same. No future fork is announced.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(filter.validate(ForkId { hash: ForkHash(hex!("668db0af")), next: 0 }), Ok(()));
 
         // Local is mainnet Petersburg, remote announces the same. Remote also announces a next fork
         // at block 0xffffffff, but that is uncertain.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId { hash: ForkHash(hex!("668db0af")), next: BlockNumber::MAX }),
             Ok(())
@@ -338,13 +394,13 @@ mod tests {
         // announces also Byzantium, but it's not yet aware of Petersburg (e.g. non updated
         // node before the fork). In this case we don't know if Petersburg passed yet or
         // not.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(filter.validate(ForkId { hash: ForkHash(hex!("a00bc324")), next: 0 }), Ok(()));
 
         // Local is mainnet currently in Byzantium only (so it's aware of Petersburg), remote
         // announces also Byzantium, and it's also aware of Petersburg (e.g. updated node
         // before the fork). We don't know if Petersburg passed yet (will pass) or not.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId { hash: ForkHash(hex!("a00bc324")), next: 7_280_000 }),
             Ok(())
@@ -354,7 +410,7 @@ mod tests {
         // announces also Byzantium, and it's also aware of some random fork (e.g.
         // misconfigured Petersburg). As neither forks passed at neither nodes, they may
         // mismatch, but we still connect for now.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId { hash: ForkHash(hex!("a00bc324")), next: BlockNumber::MAX }),
             Ok(())
@@ -362,7 +418,7 @@ mod tests {
 
         // Local is mainnet Petersburg, remote announces Byzantium + knowledge about Petersburg.
         // Remote is simply out of sync, accept.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId { hash: ForkHash(hex!("a00bc324")), next: 7_280_000 }),
             Ok(())
@@ -371,25 +427,25 @@ mod tests {
         // Local is mainnet Petersburg, remote announces Spurious + knowledge about Byzantium.
         // Remote is definitely out of sync. It may or may not need the Petersburg update,
         // we don't know yet.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId { hash: ForkHash(hex!("3edd5b10")), next: 4_370_000 }),
             Ok(())
         );
 
         // Local is mainnet Byzantium, remote announces Petersburg. Local is out of sync, accept.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(filter.validate(ForkId { hash: ForkHash(hex!("668db0af")), next: 0 }), Ok(()));
 
         // Local is mainnet Spurious, remote announces Byzantium, but is not aware of Petersburg.
         // Local out of sync. Local also knows about a future fork, but that is uncertain
         // yet.
-        filter.set_head(4_369_999);
+        filter.set_head(block(4_369_999));
         assert_eq!(filter.validate(ForkId { hash: ForkHash(hex!("a00bc324")), next: 0 }), Ok(()));
 
         // Local is mainnet Petersburg. remote announces Byzantium but is not aware of further
         // forks. Remote needs software update.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         let remote = ForkId { hash: ForkHash(hex!("a00bc324")), next: 0 };
         assert_eq!(
             filter.validate(remote),
@@ -398,7 +454,7 @@ mod tests {
 
         // Local is mainnet Petersburg, and isn't aware of more forks. Remote announces Petersburg +
         // 0xffffffff. Local needs software update, reject.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         let remote = ForkId { hash: ForkHash(hex!("5cddc0e1")), next: 0 };
         assert_eq!(
             filter.validate(remote),
@@ -407,7 +463,7 @@ mod tests {
 
         // Local is mainnet Byzantium, and is aware of Petersburg. Remote announces Petersburg +
         // 0xffffffff. Local needs software update, reject.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         let remote = ForkId { hash: ForkHash(hex!("5cddc0e1")), next: 0 };
         assert_eq!(
             filter.validate(remote),
@@ -415,7 +471,7 @@ mod tests {
         );
 
         // Local is mainnet Petersburg, remote is Rinkeby Petersburg.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         let remote = ForkId { hash: ForkHash(hex!("afec6b27")), next: 0 };
         assert_eq!(
             filter.validate(remote),
@@ -427,7 +483,7 @@ mod tests {
         // is incompatible.
         //
         // This case detects non-upgraded nodes with majority hash power (typical Ropsten mess).
-        filter.set_head(88_888_888);
+        filter.set_head(block(88_888_888));
         let remote = ForkId { hash: ForkHash(hex!("668db0af")), next: 88_888_888 };
         assert_eq!(
             filter.validate(remote),
@@ -436,7 +492,7 @@ mod tests {
 
         // Local is mainnet Byzantium. Remote is also in Byzantium, but announces Gopherium (non
         // existing fork) at block 7279999, before Petersburg. Local is incompatible.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         let remote = ForkId { hash: ForkHash(hex!("a00bc324")), next: 7_279_999 };
         assert_eq!(
             filter.validate(remote),
@@ -482,36 +538,88 @@ mod tests {
         let h1 = ForkId { hash: ForkHash(hex!("97c2c34c")), next: b2 };
         let h2 = ForkId { hash: ForkHash(hex!("91d1f948")), next: 0 };
 
-        let mut fork_filter = ForkFilter::new(0, GENESIS_HASH, vec![b1, b2]);
+        let mut fork_filter =
+            ForkFilter::new(Head::default(), GENESIS_HASH, 0, [b1, b2].map(ForkFilterKey::Block));
 
-        assert!(fork_filter.set_head_priv(0).is_none());
+        assert!(fork_filter.set_head_priv(block(0)).is_none());
         assert_eq!(fork_filter.current(), h0);
 
-        assert!(fork_filter.set_head_priv(1).is_none());
+        assert!(fork_filter.set_head_priv(block(1)).is_none());
         assert_eq!(fork_filter.current(), h0);
 
         assert_eq!(
-            fork_filter.set_head_priv(b1 + 1).unwrap(),
+            fork_filter.set_head_priv(block(b1 + 1)).unwrap(),
             ForkTransition { current: h1, past: h0 }
         );
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(fork_filter.set_head_priv(b1).is_none());
+        assert!(fork_filter.set_head_priv(block(b1)).is_none());
         assert_eq!(fork_filter.current(), h1);
 
         assert_eq!(
-            fork_filter.set_head_priv(b1 - 1).unwrap(),
+            fork_filter.set_head_priv(block(b1 - 1)).unwrap(),
             ForkTransition { current: h0, past: h1 }
         );
         assert_eq!(fork_filter.current(), h0);
 
-        assert!(fork_filter.set_head_priv(b1).is_some());
+        assert!(fork_filter.set_head_priv(block(b1)).is_some());
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(fork_filter.set_head_priv(b2 - 1).is_none());
+        assert!(fork_filter.set_head_priv(block(b2 - 1)).is_none());
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(fork_filter.set_head_priv(b2).is_some());
+        assert!(fork_filter.set_head_priv(block(b2)).is_some());
         assert_eq!(fork_filter.current(), h2);
     }
+
+    #[test]
+    fn timestamp_forks() {
+        let shanghai = 1_681_338_455;
+        let mut filter = ForkFilter::new(
+            Head::default(),
+            GENESIS_HASH,
+            0,
+            [1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000, 9_069_000]
+                .map(ForkFilterKey::Block)
+                .into_iter()
+                .chain(
+                    [9_200_000, 12_244_000, 12_965_000, 13_773_000, 15_050_000]
+                        .map(ForkFilterKey::Block),
+                )
+                .chain([ForkFilterKey::Time(shanghai)]),
+        );
+
+        // Last Gray Glacier block, Shanghai is announced by its timestamp
+        let gray_glacier = ForkId { hash: ForkHash(hex!("f0afd0e3")), next: shanghai };
+        assert!(filter
+            .set_head(Head { number: 20_000_000, timestamp: shanghai - 1, ..Default::default() })
+            .is_some());
+        assert_eq!(filter.current(), gray_glacier);
+
+        // First Shanghai block
+        let transition =
+            filter.set_head(Head { number: 20_000_001, timestamp: shanghai, ..Default::default() });
+        let shanghai_id = ForkId { hash: ForkHash(hex!("dce96c2d")), next: 0 };
+        assert_eq!(transition, Some(ForkTransition { current: shanghai_id, past: gray_glacier }));
+
+        // Remote is in Gray Glacier and aware of Shanghai, but Shanghai is passed locally, so the
+        // remote is stale.
+        assert_eq!(filter.validate(gray_glacier), Ok(()));
+        let remote = ForkId { hash: ForkHash(hex!("f0afd0e3")), next: shanghai + 1 };
+        assert_eq!(
+            filter.validate(remote),
+            Err(ValidationError::RemoteStale { local: shanghai_id, remote })
+        );
+
+        // Remote announces a future timestamp fork that is already passed locally.
+        let remote = ForkId { hash: shanghai_id.hash, next: shanghai };
+        assert_eq!(
+            filter.validate(remote),
+            Err(ValidationError::LocalIncompatibleOrStale { local: shanghai_id, remote })
+        );
+
+        // Forks at or before the genesis timestamp are not part of the fork hash.
+        let filter = ForkFilter::new(Head::default(), GENESIS_HASH, 10, [ForkFilterKey::Time(10)]);
+        assert_eq!(filter.current(), ForkId { hash: ForkHash(hex!("fc64ec04")), next: 0 });
+    }
 }
@@ -0,0 +1,213 @@
+use crate::{
+    keccak256,
+    proofs::{calculate_state_root, calculate_storage_root},
+    serde_helper::{
+        deserialize_json_u256,
+        num::{u64_hex, u64_hex_opt},
+    },
+    Account, Address, BlockNumber, Bytes, CliqueConfig, H256, U256,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The genesis block specification, in the format of a geth genesis file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Genesis {
+    /// The chain configuration of a custom genesis, which is converted into a
+    /// [ChainSpec](crate::ChainSpec).
+    #[serde(default)]
+    pub config: ChainConfig,
+    /// The genesis header nonce.
+    #[serde(default, with = "u64_hex")]
+    pub nonce: u64,
+    /// The genesis header timestamp.
+    #[serde(default, with = "u64_hex")]
+    pub timestamp: u64,
+    /// The genesis header extra data.
+    #[serde(default)]
+    pub extra_data: Bytes,
+    /// The genesis header gas limit.
+    #[serde(with = "u64_hex")]
+    pub gas_limit: u64,
+    /// The genesis header difficulty.
+    #[serde(deserialize_with = "deserialize_json_u256")]
+    pub difficulty: U256,
+    /// The genesis header mix hash.
+    #[serde(default)]
+    pub mix_hash: H256,
+    /// The genesis header coinbase address.
+    #[serde(default)]
+    pub coinbase: Address,
+    /// The genesis header base fee, required if London is activated at genesis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    /// The genesis state root, computed from the [alloc](Genesis::alloc) if not set.
+    ///
+    /// Geth genesis files don't contain the state root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_root: Option<H256>,
+    /// The initial state of accounts in the genesis block.
+    #[serde(default)]
+    pub alloc: HashMap<Address, GenesisAccount>,
+}
+
+impl Genesis {
+    /// Compute the state root of the [alloc](Genesis::alloc).
+    pub fn compute_state_root(&self) -> H256 {
+        calculate_state_root(self.alloc.iter().map(|(address, account)| {
+            (keccak256(address), account.account(), account.storage_root())
+        }))
+    }
+}
+
+/// An account in the state of the genesis block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisAccount {
+    /// The nonce of the account at genesis.
+    #[serde(default, with = "u64_hex_opt", skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// The balance of the account at genesis.
+    pub balance: U256,
+    /// The code of the account at genesis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The storage of the account at genesis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<HashMap<H256, H256>>,
+}
+
+impl GenesisAccount {
+    /// The account as it is stored in the database.
+    pub fn account(&self) -> Account {
+        Account {
+            nonce: self.nonce.unwrap_or_default(),
+            balance: self.balance,
+            bytecode_hash: self.code.as_ref().map(keccak256),
+        }
+    }
+
+    /// The storage slots of the account, with the values as numbers.
+    pub fn storage_slots(&self) -> impl Iterator<Item = (H256, U256)> + '_ {
+        self.storage
+            .iter()
+            .flatten()
+            .map(|(key, value)| (*key, U256::from_big_endian(value.as_bytes())))
+    }
+
+    /// Compute the storage root of the account.
+    pub fn storage_root(&self) -> H256 {
+        calculate_storage_root(self.storage_slots().map(|(key, value)| (keccak256(key), value)))
+    }
+}
+
+/// The fork activations of a custom chain, in the format of the `config` of a geth genesis file.
+///
+/// Forks that are not set are never activated.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ChainConfig {
+    /// Blockchain identifier introduced in EIP-155: Simple replay attack protection.
+    pub chain_id: u64,
+    /// Homestead switch block.
+    pub homestead_block: Option<BlockNumber>,
+    /// TheDAO hard-fork switch block.
+    pub dao_fork_block: Option<BlockNumber>,
+    /// Whether the node supports or opposes the DAO hard-fork.
+    pub dao_fork_support: bool,
+    /// EIP150 implements gas price changes.
+    #[serde(rename = "eip150Block")]
+    pub eip150_block: Option<BlockNumber>,
+    /// EIP155 hard-fork block (Spurious Dragon).
+    #[serde(rename = "eip155Block")]
+    pub eip155_block: Option<BlockNumber>,
+    /// EIP158 hard-fork block.
+    #[serde(rename = "eip158Block")]
+    pub eip158_block: Option<BlockNumber>,
+    /// Byzantium switch block.
+    pub byzantium_block: Option<BlockNumber>,
+    /// Constantinople switch block.
+    pub constantinople_block: Option<BlockNumber>,
+    /// Petersburg switch block.
+    pub petersburg_block: Option<BlockNumber>,
+    /// Istanbul switch block.
+    pub istanbul_block: Option<BlockNumber>,
+    /// Muir Glacier switch block.
+    pub muir_glacier_block: Option<BlockNumber>,
+    /// Berlin switch block.
+    pub berlin_block: Option<BlockNumber>,
+    /// London switch block.
+    pub london_block: Option<BlockNumber>,
+    /// Arrow Glacier switch block.
+    pub arrow_glacier_block: Option<BlockNumber>,
+    /// Gray Glacier switch block.
+    pub gray_glacier_block: Option<BlockNumber>,
+    /// The block at which the network splits after the merge, if known.
+    #[serde(alias = "parisBlock")]
+    pub merge_netsplit_block: Option<BlockNumber>,
+    /// Shanghai switch time.
+    pub shanghai_time: Option<u64>,
+    /// Cancun switch time.
+    pub cancun_time: Option<u64>,
+    /// Terminal total difficulty to reach before the merge.
+    pub terminal_total_difficulty: Option<u128>,
+    /// The clique proof of authority configuration, if the chain is sealed by clique.
+    pub clique: Option<CliqueConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn geth_genesis() {
+        let genesis: Genesis = serde_json::from_str(
+            r#"{
+                "config": { "chainId": 1337, "londonBlock": 0 },
+                "difficulty": "0x1",
+                "gasLimit": "0x1c9c380",
+                "extraData": "0x1234",
+                "alloc": {
+                    "0x000000000000000000000000000000000000aaaa": {
+                        "balance": "0x1",
+                        "nonce": "0x1",
+                        "code": "0x600160005500",
+                        "storage": {
+                            "0x0000000000000000000000000000000000000000000000000000000000000000": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                            "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                        }
+                    },
+                    "71562b71999873db5b286df957af199ec94617f7": {
+                        "balance": "0x3635c9adc5dea00000"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(genesis.config.chain_id, 1337);
+        assert_eq!(genesis.gas_limit, 30_000_000);
+        assert_eq!(genesis.extra_data, Bytes::from(hex!("1234")));
+        assert_eq!(genesis.state_root, None);
+
+        let account =
+            &genesis.alloc[&Address::from(hex!("000000000000000000000000000000000000aaaa"))];
+        assert_eq!(
+            account.account(),
+            Account {
+                nonce: 1,
+                balance: U256::from(1),
+                bytecode_hash: Some(H256(hex!(
+                    "0dd9fc23b9e0972b48f0c93ebb805660239a90ba27a337a9f24333a8703975cd"
+                ))),
+            }
+        );
+        assert_eq!(account.storage_slots().filter(|(_, value)| !value.is_zero()).count(), 1);
+
+        assert_eq!(
+            genesis.compute_state_root(),
+            H256(hex!("ab45140644ee69592e22ec08c4611eaec873cab0eb040c8aaf68b9e0f6afaa41"))
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{BlockNumber, H256, U256};
+
+/// The head of the local chain, i.e. the block the node announces in its `Status` and derives its
+/// current fork from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Head {
+    /// The number of the head block.
+    pub number: BlockNumber,
+    /// The hash of the head block.
+    pub hash: H256,
+    /// The total difficulty at the head block.
+    pub total_difficulty: U256,
+    /// The timestamp of the head block.
+    pub timestamp: u64,
+}
@@ -1,4 +1,5 @@
 use crate::{
+    eip4844::{calculate_blob_gasprice, calculate_excess_blob_gas},
     keccak256,
     proofs::{EMPTY_LIST_HASH, EMPTY_ROOT},
     BlockHash, BlockNumber, Bloom, H160, H256, U256,
@@ -64,6 +65,18 @@ pub struct Header {
     /// above the gas target, and decreasing when blocks are below the gas target. The base fee per
     /// gas is burned.
     pub base_fee_per_gas: Option<u64>,
+    /// The Keccak 256-bit hash of the root node of the trie structure populated with each
+    /// withdrawal in the withdrawals list portion of the block, `None` before Shanghai
+    /// (EIP-4895).
+    pub withdrawals_root: Option<H256>,
+    /// The total amount of blob gas consumed by the transactions within the block, `None` before
+    /// Cancun (EIP-4844).
+    pub blob_gas_used: Option<u64>,
+    /// A running total of the blob gas consumed in excess of the target, prior to the block,
+    /// `None` before Cancun (EIP-4844). It determines the price of the blob gas of the block.
+    pub excess_blob_gas: Option<u64>,
+    /// The hash of the parent beacon block's root, `None` before Cancun (EIP-4788).
+    pub parent_beacon_block_root: Option<H256>,
     /// An arbitrary byte array containing data relevant to this block. This must be 32 bytes or
     /// fewer; formally Hx.
     pub extra_data: bytes::Bytes,
@@ -88,6 +101,10 @@ impl Default for Header {
             mix_hash: Default::default(),
             nonce: 0,
             base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         }
     }
 }
@@ -101,9 +118,21 @@ impl Header {
         keccak256(&out)
     }
 
-    /// Checks if the header is empty - has no transactions and no ommers
+    /// Checks if the header is empty - has no transactions, no ommers and no withdrawals
     pub fn is_empty(&self) -> bool {
-        self.ommers_hash == EMPTY_LIST_HASH && self.transactions_root == EMPTY_ROOT
+        self.ommers_hash == EMPTY_LIST_HASH &&
+            self.transactions_root == EMPTY_ROOT &&
+            self.withdrawals_root.map_or(true, |root| root == EMPTY_ROOT)
+    }
+
+    /// Returns the excess blob gas of the next block, `None` before Cancun.
+    pub fn next_block_excess_blob_gas(&self) -> Option<u64> {
+        Some(calculate_excess_blob_gas(self.excess_blob_gas?, self.blob_gas_used?))
+    }
+
+    /// Returns the price of a unit of blob gas in this block, `None` before Cancun.
+    pub fn blob_gasprice(&self) -> Option<u128> {
+        self.excess_blob_gas.map(calculate_blob_gasprice)
     }
 
     /// Calculate hash and seal the Header so that it can't be changed.
@@ -130,6 +159,10 @@ impl Header {
         length += self.mix_hash.length();
         length += H64::from_low_u64_be(self.nonce).length();
         length += self.base_fee_per_gas.map(|fee| U256::from(fee).length()).unwrap_or_default();
+        length += self.withdrawals_root.map(|root| root.length()).unwrap_or_default();
+        length += self.blob_gas_used.map(|gas| U256::from(gas).length()).unwrap_or_default();
+        length += self.excess_blob_gas.map(|gas| U256::from(gas).length()).unwrap_or_default();
+        length += self.parent_beacon_block_root.map(|root| root.length()).unwrap_or_default();
         length
     }
 }
@@ -157,6 +190,20 @@ impl Encodable for Header {
         if let Some(ref base_fee) = self.base_fee_per_gas {
             U256::from(*base_fee).encode(out);
         }
+        // the withdrawals root follows the base fee, which is always set after Shanghai
+        if let Some(ref root) = self.withdrawals_root {
+            root.encode(out);
+        }
+        // the Cancun fields follow the withdrawals root, which is always set after Cancun
+        if let Some(gas) = self.blob_gas_used {
+            U256::from(gas).encode(out);
+        }
+        if let Some(gas) = self.excess_blob_gas {
+            U256::from(gas).encode(out);
+        }
+        if let Some(ref root) = self.parent_beacon_block_root {
+            root.encode(out);
+        }
     }
 
     fn length(&self) -> usize {
@@ -191,12 +238,26 @@ impl Decodable for Header {
             mix_hash: Decodable::decode(buf)?,
             nonce: H64::decode(buf)?.to_low_u64_be(),
             base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         };
         let consumed = started_len - buf.len();
         if consumed < rlp_head.payload_length {
             this.base_fee_per_gas = Some(U256::decode(buf)?.as_u64());
         }
         let consumed = started_len - buf.len();
+        if consumed < rlp_head.payload_length {
+            this.withdrawals_root = Some(Decodable::decode(buf)?);
+        }
+        let consumed = started_len - buf.len();
+        if consumed < rlp_head.payload_length {
+            this.blob_gas_used = Some(U256::decode(buf)?.as_u64());
+            this.excess_blob_gas = Some(U256::decode(buf)?.as_u64());
+            this.parent_beacon_block_root = Some(Decodable::decode(buf)?);
+        }
+        let consumed = started_len - buf.len();
         if consumed != rlp_head.payload_length {
             return Err(reth_rlp::DecodeError::ListLengthMismatch {
                 expected: rlp_head.payload_length,
@@ -412,6 +473,10 @@ mod tests {
             mix_hash: H256::from_str("0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
             nonce: 0,
             base_fee_per_gas: Some(0x036b_u64),
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         };
         assert_eq!(header.hash_slow(), expected_hash);
     }
@@ -436,4 +501,45 @@ mod tests {
         let header = <Header as Decodable>::decode(&mut data.as_slice()).unwrap();
         assert_eq!(header, expected);
     }
+
+    #[test]
+    fn header_rlp_with_withdrawals_root() {
+        let header = Header {
+            number: 1,
+            base_fee_per_gas: Some(7),
+            withdrawals_root: Some(H256::random()),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        header.encode(&mut data);
+        assert_eq!(header.length(), data.len());
+        assert_eq!(<Header as Decodable>::decode(&mut data.as_slice()).unwrap(), header);
+
+        // the withdrawals root is an optional trailing field
+        let pre_shanghai = Header { withdrawals_root: None, ..header };
+        let mut pre_shanghai_data = vec![];
+        pre_shanghai.encode(&mut pre_shanghai_data);
+        assert_eq!(pre_shanghai_data.len(), data.len() - 33);
+        assert_eq!(
+            <Header as Decodable>::decode(&mut pre_shanghai_data.as_slice()).unwrap(),
+            pre_shanghai
+        );
+    }
+
+    #[test]
+    fn header_rlp_with_cancun_fields() {
+        let header = Header {
+            number: 1,
+            base_fee_per_gas: Some(7),
+            withdrawals_root: Some(H256::random()),
+            blob_gas_used: Some(0x20000),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(H256::random()),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        header.encode(&mut data);
+        assert_eq!(header.length(), data.len());
+        assert_eq!(<Header as Decodable>::decode(&mut data.as_slice()).unwrap(), header);
+    }
 }
@@ -0,0 +1,65 @@
+use crate::H256;
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+/// The number of inputs above which [keccak256_all] hashes them in parallel.
+const PARALLEL_HASHING_THRESHOLD: usize = 64;
+
+/// Returns the keccak256 hash for the given data.
+#[cfg(not(feature = "keccak-asm"))]
+#[inline]
+pub fn keccak256(data: impl AsRef<[u8]>) -> H256 {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut buf = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(data.as_ref());
+    hasher.finalize(&mut buf);
+    buf.into()
+}
+
+/// Returns the keccak256 hash for the given data.
+///
+/// Uses the assembly implementation of the Keccak permutation on platforms that support it.
+#[cfg(feature = "keccak-asm")]
+#[inline]
+pub fn keccak256(data: impl AsRef<[u8]>) -> H256 {
+    use sha3::{Digest, Keccak256};
+
+    H256(Keccak256::digest(data.as_ref()).into())
+}
+
+/// Returns the keccak256 hashes of all inputs, in order.
+///
+/// Large batches are hashed in parallel.
+pub fn keccak256_all<T: AsRef<[u8]> + Sync>(data: &[T]) -> Vec<H256> {
+    if data.len() < PARALLEL_HASHING_THRESHOLD {
+        data.iter().map(keccak256).collect()
+    } else {
+        data.par_iter().map(keccak256).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hex_literal::hex, Address, KECCAK_EMPTY};
+
+    #[test]
+    fn keccak256_known_values() {
+        assert_eq!(keccak256(b""), KECCAK_EMPTY);
+        assert_eq!(
+            keccak256(b"hello world"),
+            H256(hex!("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad"))
+        );
+    }
+
+    #[test]
+    fn keccak256_all_preserves_order() {
+        // below and above the threshold for parallel hashing
+        for count in [0, 1, PARALLEL_HASHING_THRESHOLD * 4] {
+            let addresses: Vec<_> = (0..count as u64).map(Address::from_low_u64_be).collect();
+            let expected: Vec<_> = addresses.iter().map(keccak256).collect();
+            assert_eq!(keccak256_all(&addresses), expected);
+        }
+    }
+}
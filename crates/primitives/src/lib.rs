@@ -14,44 +14,62 @@ mod block;
 pub mod bloom;
 mod chain;
 mod constants;
+pub mod eip4844;
 mod error;
 mod forkid;
+mod genesis;
 mod hardfork;
+mod head;
 mod header;
 mod hex_bytes;
 mod integer_list;
-mod jsonu256;
+mod keccak;
 mod log;
 mod net;
 mod peer;
 mod receipt;
 mod storage;
 mod transaction;
+mod trie;
+mod withdrawal;
 
 /// Helper function for calculating Merkle proofs and hashes
 pub mod proofs;
+pub mod serde_helper;
 
 pub use account::Account;
-pub use block::{Block, BlockHashOrNumber, SealedBlock};
-pub use chain::Chain;
-pub use constants::{EMPTY_OMMER_ROOT, KECCAK_EMPTY, MAINNET_GENESIS};
+pub use block::{Block, BlockHashOrNumber, SealedBlock, SealedBlockWithSenders};
+pub use chain::{
+    Chain, ChainSpec, ChainSpecBuilder, CliqueConfig, DepositContract, ForkCondition, DEV, GOERLI,
+    MAINNET, SEPOLIA,
+};
+pub use constants::{
+    BLOB_GASPRICE_UPDATE_FRACTION, BLOB_GAS_PER_BLOB, EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR,
+    EIP1559_ELASTICITY_MULTIPLIER, EIP1559_INITIAL_BASE_FEE, EMPTY_OMMER_ROOT, KECCAK_EMPTY,
+    MAINNET_GENESIS, MAX_BLOB_GAS_PER_BLOCK, MIN_BLOB_GASPRICE, TARGET_BLOB_GAS_PER_BLOCK,
+};
 pub use ethbloom::Bloom;
-pub use forkid::{ForkFilter, ForkHash, ForkId, ForkTransition, ValidationError};
+pub use forkid::{ForkFilter, ForkFilterKey, ForkHash, ForkId, ForkTransition, ValidationError};
+pub use genesis::{ChainConfig, Genesis, GenesisAccount};
 pub use hardfork::Hardfork;
+pub use head::Head;
 pub use header::{Header, HeadersDirection, SealedHeader};
 pub use hex_bytes::Bytes;
 pub use integer_list::IntegerList;
-pub use jsonu256::JsonU256;
+pub use keccak::{keccak256, keccak256_all};
 pub use log::Log;
 pub use net::{NodeRecord, Octets};
 pub use peer::{PeerId, WithPeerId};
 pub use receipt::Receipt;
+pub use serde_helper::JsonU256;
 pub use storage::StorageEntry;
 pub use transaction::{
-    AccessList, AccessListItem, FromRecoveredTransaction, IntoRecoveredTransaction, Signature,
-    Transaction, TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
-    TxEip2930, TxLegacy, TxType,
+    AccessList, AccessListItem, BlobTransaction, BlobTransactionSidecar, FromRecoveredTransaction,
+    IntoRecoveredTransaction, Signature, Transaction, TransactionKind, TransactionSigned,
+    TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxEip4844, TxLegacy, TxType,
 };
+pub use trie::BranchNodeCompact;
+pub use withdrawal::Withdrawal;
 
 /// A block hash.
 pub type BlockHash = H256;
@@ -94,15 +112,3 @@ pub mod utils {
 
 // Useful reexports
 pub use __reexport::*;
-
-/// Returns the keccak256 hash for the given data.
-#[inline]
-pub fn keccak256(data: impl AsRef<[u8]>) -> H256 {
-    use tiny_keccak::{Hasher, Keccak};
-
-    let mut buf = [0u8; 32];
-    let mut hasher = Keccak::v256();
-    hasher.update(data.as_ref());
-    hasher.finalize(&mut buf);
-    buf.into()
-}
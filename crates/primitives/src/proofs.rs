@@ -1,8 +1,12 @@
-use crate::{keccak256, Header, Log, Receipt, TransactionSigned, H256};
+use crate::{
+    keccak256, Account, Header, Log, Receipt, TransactionSigned, Withdrawal, H256, KECCAK_EMPTY,
+    U256,
+};
 use hash_db::Hasher;
 use hex_literal::hex;
 use plain_hasher::PlainHasher;
-use triehash::ordered_trie_root;
+use reth_rlp::{Encodable, RlpEncodable};
+use triehash::trie_root;
 
 /// Keccak-256 hash of the RLP of an empty list, KEC("\xc0").
 pub const EMPTY_LIST_HASH: H256 =
@@ -27,6 +31,32 @@ impl Hasher for KeccakHasher {
     }
 }
 
+/// Calculates the root of an ordered trie of RLP encoded items.
+///
+/// The key of each item in the trie is its RLP encoded index in the list.
+pub fn ordered_trie_root<T: Encodable>(items: impl IntoIterator<Item = T>) -> H256 {
+    ordered_trie_root_with_encoder(items, |item, out| item.encode(out))
+}
+
+/// Calculates the root of an ordered trie of items that are encoded with the given function.
+///
+/// The key of each item in the trie is its RLP encoded index in the list.
+pub fn ordered_trie_root_with_encoder<T, F>(
+    items: impl IntoIterator<Item = T>,
+    mut encode: F,
+) -> H256
+where
+    F: FnMut(&T, &mut Vec<u8>),
+{
+    trie_root::<KeccakHasher, _, _, _>(items.into_iter().enumerate().map(|(index, item)| {
+        let mut index_rlp = Vec::new();
+        index.encode(&mut index_rlp);
+        let mut item_rlp = Vec::new();
+        encode(&item, &mut item_rlp);
+        (index_rlp, item_rlp)
+    }))
+}
+
 /// Calculate a transaction root.
 ///
 /// Iterates over the given transactions and the merkle merkle trie root of
@@ -34,20 +64,22 @@ impl Hasher for KeccakHasher {
 pub fn calculate_transaction_root<'a>(
     transactions: impl IntoIterator<Item = &'a TransactionSigned>,
 ) -> H256 {
-    ordered_trie_root::<KeccakHasher, _>(transactions.into_iter().map(|tx| {
-        let mut tx_rlp = Vec::new();
-        tx.encode_inner(&mut tx_rlp, false);
-        tx_rlp
-    }))
+    ordered_trie_root_with_encoder(transactions, |tx, out| tx.encode_inner(out, false))
+}
+
+/// Calculates the withdrawals root for a header (EIP-4895).
+///
+/// Iterates over the given withdrawals and calculates the merkle trie root of
+/// `(rlp(index), rlp(withdrawal))` pairs.
+pub fn calculate_withdrawals_root<'a>(
+    withdrawals: impl IntoIterator<Item = &'a Withdrawal>,
+) -> H256 {
+    ordered_trie_root(withdrawals)
 }
 
 /// Calculates the receipt root for a header.
 pub fn calculate_receipt_root<'a>(receipts: impl Iterator<Item = &'a Receipt>) -> H256 {
-    ordered_trie_root::<KeccakHasher, _>(receipts.into_iter().map(|receipt| {
-        let mut receipt_rlp = Vec::new();
-        receipt.encode_inner(&mut receipt_rlp, false);
-        receipt_rlp
-    }))
+    ordered_trie_root_with_encoder(receipts, |receipt, out| receipt.encode_inner(out, false))
 }
 
 /// Calculates the log root for headers.
@@ -66,16 +98,72 @@ pub fn calculate_ommers_root<'a>(ommers: impl Iterator<Item = &'a Header> + Clon
     keccak256(ommers_rlp)
 }
 
+/// An account as it is encoded in the state trie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, RlpEncodable)]
+pub struct TrieAccount {
+    /// The nonce of the account.
+    pub nonce: u64,
+    /// The balance of the account.
+    pub balance: U256,
+    /// The root of the storage trie of the account.
+    pub storage_root: H256,
+    /// The hash of the code of the account.
+    pub code_hash: H256,
+}
+
+impl TrieAccount {
+    /// Creates the trie representation of an account with the given storage root.
+    pub fn new(account: Account, storage_root: H256) -> Self {
+        Self {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root,
+            code_hash: account.bytecode_hash.unwrap_or(KECCAK_EMPTY),
+        }
+    }
+}
+
+/// Calculates the storage root of an account.
+///
+/// Takes the `(keccak256(slot), value)` pairs of the storage. Zero values are not part of the trie
+/// and are skipped.
+pub fn calculate_storage_root(storage: impl IntoIterator<Item = (H256, U256)>) -> H256 {
+    trie_root::<KeccakHasher, _, _, _>(
+        storage.into_iter().filter(|(_, value)| !value.is_zero()).map(|(hashed_slot, value)| {
+            let mut value_rlp = Vec::new();
+            value.encode(&mut value_rlp);
+            (hashed_slot, value_rlp)
+        }),
+    )
+}
+
+/// Calculates the state root.
+///
+/// Takes the `(keccak256(address), account, storage root)` triples of all accounts.
+pub fn calculate_state_root(accounts: impl IntoIterator<Item = (H256, Account, H256)>) -> H256 {
+    trie_root::<KeccakHasher, _, _, _>(accounts.into_iter().map(
+        |(hashed_address, account, storage_root)| {
+            let mut account_rlp = Vec::new();
+            TrieAccount::new(account, storage_root).encode(&mut account_rlp);
+            (hashed_address, account_rlp)
+        },
+    ))
+}
+
 #[cfg(test)]
 mod tests {
 
     use crate::{
         hex_literal::hex,
-        proofs::{calculate_receipt_root, calculate_transaction_root},
-        Block, Bloom, Log, Receipt, TxType, H160, H256,
+        keccak256,
+        proofs::{
+            calculate_receipt_root, calculate_state_root, calculate_storage_root,
+            calculate_transaction_root, calculate_withdrawals_root, ordered_trie_root, EMPTY_ROOT,
+        },
+        Account, Block, Bloom, Log, Receipt, TxType, Withdrawal, H160, H256, U256,
     };
     use bytes::Bytes;
-    use reth_rlp::Decodable;
+    use reth_rlp::{Decodable, Encodable};
 
     #[test]
     fn check_transaction_root() {
@@ -105,4 +193,77 @@ mod tests {
             H256(hex!("fe70ae4a136d98944951b2123859698d59ad251a381abc9960fa81cae3d0d4a0"))
         );
     }
+
+    #[test]
+    fn check_withdrawals_root() {
+        assert_eq!(calculate_withdrawals_root(&[]), EMPTY_ROOT);
+
+        let withdrawals = [
+            Withdrawal {
+                index: 0,
+                validator_index: 1,
+                address: H160::from_low_u64_be(1),
+                amount: 1_000_000_000,
+            },
+            Withdrawal {
+                index: 1,
+                validator_index: 2,
+                address: H160::from_low_u64_be(2),
+                amount: 2,
+            },
+        ];
+        assert_eq!(
+            calculate_withdrawals_root(&withdrawals),
+            H256(hex!("56347f0529be22235392e480b5cb757bcea2090e0f3663caa7c600d6e208918a"))
+        );
+    }
+
+    #[test]
+    fn check_ordered_trie_root() {
+        assert_eq!(ordered_trie_root(Vec::<u64>::new()), EMPTY_ROOT);
+
+        // the keys of more than 128 items have multi-byte encodings
+        let items: Vec<u64> = (0..300).map(|item| item * 1_000_000).collect();
+        let expected =
+            triehash::ordered_trie_root::<super::KeccakHasher, _>(items.iter().map(|item| {
+                let mut item_rlp = Vec::new();
+                item.encode(&mut item_rlp);
+                item_rlp
+            }));
+        assert_eq!(ordered_trie_root(&items), expected);
+    }
+
+    #[test]
+    fn check_state_root() {
+        assert_eq!(calculate_storage_root([]), EMPTY_ROOT);
+        assert_eq!(calculate_state_root([]), EMPTY_ROOT);
+
+        let storage = [
+            (keccak256(H256::from_low_u64_be(0)), U256::from(1)),
+            (keccak256(H256::from_low_u64_be(1)), U256::from(2)),
+            (keccak256(H256::from_low_u64_be(2)), U256::zero()),
+        ];
+        let storage_root = calculate_storage_root(storage);
+        assert_eq!(
+            storage_root,
+            H256(hex!("41d5fe9f5915af65d9f3d9981ce2229311329a177963f6fe7d448b100cb085ce"))
+        );
+
+        let accounts = [
+            (
+                keccak256(H160::from_low_u64_be(1)),
+                Account { nonce: 1, balance: U256::from(10), bytecode_hash: None },
+                storage_root,
+            ),
+            (
+                keccak256(H160::from_low_u64_be(2)),
+                Account { nonce: 0, balance: U256::from(1000), bytecode_hash: None },
+                EMPTY_ROOT,
+            ),
+        ];
+        assert_eq!(
+            calculate_state_root(accounts),
+            H256(hex!("576f96e88cec39edf4a7930be104526f3161ad53f717663bbdce1abc7c390cea"))
+        );
+    }
 }
@@ -67,6 +67,9 @@ impl Receipt {
             TxType::EIP1559 => {
                 out.put_u8(0x02);
             }
+            TxType::EIP4844 => {
+                out.put_u8(0x03);
+            }
             _ => unreachable!("legacy handled; qed."),
         }
         out.put_slice(payload.as_ref());
@@ -109,10 +112,10 @@ impl Encodable for Receipt {
     fn length(&self) -> usize {
         let mut payload_len = self.receipt_length();
         // account for eip-2718 type prefix and set the list
-        if matches!(self.tx_type, TxType::EIP1559 | TxType::EIP2930) {
+        if !matches!(self.tx_type, TxType::Legacy) {
             payload_len += 1;
             // we include a string header for typed receipts, so include the length here
-            payload_len = length_of_length(payload_len);
+            payload_len += length_of_length(payload_len);
         }
 
         payload_len
@@ -144,6 +147,9 @@ impl Decodable for Receipt {
                 } else if receipt_type == 0x02 {
                     buf.advance(1);
                     Self::decode_receipt(buf, TxType::EIP1559)
+                } else if receipt_type == 0x03 {
+                    buf.advance(1);
+                    Self::decode_receipt(buf, TxType::EIP4844)
                 } else {
                     Err(reth_rlp::DecodeError::Custom("invalid receipt type"))
                 }
@@ -228,4 +234,22 @@ mod tests {
         let receipt = Receipt::decode(&mut &data[..]).unwrap();
         assert_eq!(receipt, expected);
     }
+
+    #[test]
+    fn typed_receipt_roundtrip() {
+        let log =
+            Log { address: Address::from_low_u64_be(0x11), topics: vec![], data: vec![1].into() };
+        let receipt = Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21000,
+            bloom: crate::bloom::logs_bloom([&log]),
+            logs: vec![log],
+        };
+
+        let mut data = vec![];
+        receipt.encode(&mut data);
+        assert_eq!(receipt.length(), data.len());
+        assert_eq!(Receipt::decode(&mut &data[..]).unwrap(), receipt);
+    }
 }
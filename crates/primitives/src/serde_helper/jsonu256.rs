@@ -5,7 +5,10 @@ use serde::{
 };
 use std::{fmt, str::FromStr};
 
-/// Wrapper around primitive U256 type to handle edge cases of json parser
+/// Wrapper around primitive U256 type to handle edge cases of json parser.
+///
+/// Deserializes from a hex quantity, a decimal string, an empty string or a number, and serializes
+/// as a hex quantity.
 #[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct JsonU256(pub U256);
 
@@ -14,7 +17,7 @@ impl Serialize for JsonU256 {
     where
         S: Serializer,
     {
-        self.0.to_string().serialize(serializer)
+        format!("{:#x}", self.0).serialize(serializer)
     }
 }
 
@@ -27,6 +30,23 @@ impl<'a> Deserialize<'a> for JsonU256 {
     }
 }
 
+/// Deserializes a [U256] leniently, see [JsonU256].
+pub fn deserialize_json_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    JsonU256::deserialize(deserializer).map(|JsonU256(value)| value)
+}
+
+/// Deserializes a `u64` leniently, see [JsonU256].
+pub fn deserialize_json_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = deserialize_json_u256(deserializer)?;
+    u64::try_from(value).map_err(|_| Error::custom(format!("{value} does not fit into a u64")))
+}
+
 struct JsonU256Visitor;
 
 impl<'a> Visitor<'a> for JsonU256Visitor {
@@ -90,4 +110,11 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn jsonu256_serialize_quantity() {
+        let serialized =
+            serde_json::to_string(&[JsonU256(U256::zero()), JsonU256(U256::from(0x1b4))]).unwrap();
+        assert_eq!(serialized, r#"["0x0","0x1b4"]"#);
+    }
 }
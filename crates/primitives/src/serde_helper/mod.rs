@@ -0,0 +1,39 @@
+//! Serde helpers for the hex encodings of the JSON-RPC, geth genesis files and the Ethereum tests.
+//!
+//! Quantities are serialized as `0x`-prefixed hex strings without leading zeros, byte strings as
+//! `0x`-prefixed hex strings.
+
+mod jsonu256;
+pub use jsonu256::{deserialize_json_u256, deserialize_json_u64, JsonU256};
+
+pub mod num;
+
+/// Serde functions for `0x`-prefixed hex byte strings, to be used with
+/// `#[serde(with = "hex_bytes")]`.
+pub mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes the bytes as a `0x`-prefixed hex string.
+    pub fn serialize<S, T>(x: T, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        s.serialize_str(&format!("0x{}", hex::encode(x.as_ref())))
+    }
+
+    /// Deserializes bytes from a hex string, with or without the `0x` prefix.
+    pub fn deserialize<'de, D>(d: D) -> Result<bytes::Bytes, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(d)?;
+        if let Some(value) = value.strip_prefix("0x") {
+            hex::decode(value)
+        } else {
+            hex::decode(&value)
+        }
+        .map(Into::into)
+        .map_err(|e| serde::de::Error::custom(e.to_string()))
+    }
+}
@@ -0,0 +1,75 @@
+//! Serde functions for numbers encoded as hex quantities.
+//!
+//! Deserialization is lenient and also accepts decimal strings and JSON numbers, see [JsonU256].
+
+use super::{deserialize_json_u64, JsonU256};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Serde functions for a `u64` encoded as a hex quantity, to be used with
+/// `#[serde(with = "u64_hex")]`.
+pub mod u64_hex {
+    use super::*;
+
+    /// Serializes the `u64` as a hex quantity.
+    pub fn serialize<S: Serializer>(value: &u64, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("{value:#x}"))
+    }
+
+    /// Deserializes a `u64` from a hex quantity, a decimal string or a number.
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+        deserialize_json_u64(d)
+    }
+}
+
+/// Serde functions for an optional `u64` encoded as a hex quantity, to be used with
+/// `#[serde(with = "u64_hex_opt")]`.
+pub mod u64_hex_opt {
+    use super::*;
+
+    /// Serializes the `u64`, if any, as a hex quantity.
+    pub fn serialize<S: Serializer>(value: &Option<u64>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::u64_hex::serialize(value, s),
+            None => s.serialize_none(),
+        }
+    }
+
+    /// Deserializes an optional `u64` from a hex quantity, a decimal string or a number.
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+        Option::<JsonU256>::deserialize(d)?
+            .map(|JsonU256(value)| {
+                u64::try_from(value).map_err(|_| serde::de::Error::custom("u64 overflow"))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Quantities {
+        #[serde(with = "u64_hex")]
+        number: u64,
+        #[serde(with = "u64_hex_opt", default)]
+        optional: Option<u64>,
+    }
+
+    #[test]
+    fn u64_quantities_roundtrip() {
+        let value = Quantities { number: 0x1b4, optional: Some(0) };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"number":"0x1b4","optional":"0x0"}"#);
+        assert_eq!(serde_json::from_str::<Quantities>(&json).unwrap(), value);
+
+        let lenient: Quantities = serde_json::from_str(r#"{"number":"436"}"#).unwrap();
+        assert_eq!(lenient, Quantities { number: 436, optional: None });
+        let lenient: Quantities =
+            serde_json::from_str(r#"{"number":436,"optional":null}"#).unwrap();
+        assert_eq!(lenient, Quantities { number: 436, optional: None });
+
+        assert!(serde_json::from_str::<Quantities>(r#"{"number":"0x10000000000000000"}"#).is_err());
+    }
+}
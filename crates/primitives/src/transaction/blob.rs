@@ -0,0 +1,189 @@
+use crate::{keccak256, Bytes, Transaction, TransactionSigned, TxHash};
+use bytes::Buf;
+use reth_rlp::{length_of_length, Decodable, DecodeError, Encodable, Header};
+
+/// The type byte of EIP-4844 transactions.
+const BLOB_TX_TYPE: u8 = 3;
+
+/// The blobs of an [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844) transaction, together with
+/// the KZG commitments and proofs that bind them to the versioned hashes of the transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BlobTransactionSidecar {
+    /// The blobs of the transaction.
+    pub blobs: Vec<Bytes>,
+    /// The KZG commitments to the blobs.
+    pub commitments: Vec<Bytes>,
+    /// The KZG proofs of the blobs.
+    pub proofs: Vec<Bytes>,
+}
+
+impl BlobTransactionSidecar {
+    /// Outputs the length of the sidecar's fields, without a RLP header.
+    fn fields_len(&self) -> usize {
+        self.blobs.length() + self.commitments.length() + self.proofs.length()
+    }
+
+    /// Encodes the sidecar's fields into the desired buffer, without a RLP header.
+    fn encode_fields(&self, out: &mut dyn bytes::BufMut) {
+        self.blobs.encode(out);
+        self.commitments.encode(out);
+        self.proofs.encode(out);
+    }
+
+    /// Decodes the sidecar's fields, without a RLP header.
+    fn decode_fields(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self {
+            blobs: Decodable::decode(buf)?,
+            commitments: Decodable::decode(buf)?,
+            proofs: Decodable::decode(buf)?,
+        })
+    }
+}
+
+/// An EIP-4844 transaction in its network form, i.e. together with the blobs it carries.
+///
+/// Blocks only contain the canonical form of a blob transaction, which is a regular
+/// [`TransactionSigned`]. When a blob transaction is gossiped it is sent in the network form
+/// `0x03 || rlp([tx_payload_body, blobs, commitments, proofs])`, where `tx_payload_body` is the
+/// list of fields and the signature of the canonical form.
+///
+/// The hash of the transaction is the hash of its canonical form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlobTransaction {
+    /// The canonical form of the transaction.
+    pub transaction: TransactionSigned,
+    /// The blobs of the transaction.
+    pub sidecar: BlobTransactionSidecar,
+}
+
+impl BlobTransaction {
+    /// Creates the network form of a signed EIP-4844 transaction.
+    ///
+    /// Returns `None` if the transaction is not an EIP-4844 transaction, or if the number of blobs,
+    /// commitments and proofs does not match the number of versioned hashes of the transaction.
+    pub fn new(transaction: TransactionSigned, sidecar: BlobTransactionSidecar) -> Option<Self> {
+        let Transaction::Eip4844(tx) = &transaction.transaction else { return None };
+        let blobs = tx.blob_versioned_hashes.len();
+        if sidecar.blobs.len() != blobs ||
+            sidecar.commitments.len() != blobs ||
+            sidecar.proofs.len() != blobs
+        {
+            return None
+        }
+        Some(Self { transaction, sidecar })
+    }
+
+    /// Transaction hash, the hash of the canonical form of the transaction.
+    pub fn hash(&self) -> TxHash {
+        self.transaction.hash
+    }
+
+    /// Splits the transaction into its canonical form and its blobs.
+    pub fn into_parts(self) -> (TransactionSigned, BlobTransactionSidecar) {
+        (self.transaction, self.sidecar)
+    }
+
+    /// Decodes the network form of a blob transaction without the p2p string header, as used by
+    /// `eth_sendRawTransaction`.
+    pub fn decode_enveloped(tx: Bytes) -> Result<Self, DecodeError> {
+        Self::decode_inner(&mut tx.as_ref())
+    }
+
+    /// Encodes the network form of the transaction without the p2p string header.
+    ///
+    /// This is the inverse of [Self::decode_enveloped].
+    pub fn encode_enveloped(&self, out: &mut dyn bytes::BufMut) {
+        out.put_u8(BLOB_TX_TYPE);
+        let header = Header { list: true, payload_length: self.fields_len() };
+        header.encode(out);
+        self.transaction.encode_typed_fields(out);
+        self.sidecar.encode_fields(out);
+    }
+
+    /// Output the length of the canonical transaction list and the sidecar fields, without a RLP
+    /// header.
+    fn fields_len(&self) -> usize {
+        // the payload of the canonical form without the type byte
+        let tx_len = self.transaction.payload_len() - 1;
+        tx_len + self.sidecar.fields_len()
+    }
+
+    /// Output the length of the network form without the p2p string header.
+    fn payload_len(&self) -> usize {
+        let len = self.fields_len();
+        // add the list header and the type byte
+        len + length_of_length(len) + 1
+    }
+
+    /// Decodes the network form, starting at the type byte.
+    fn decode_inner(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let tx_type = *buf.first().ok_or(DecodeError::InputTooShort)?;
+        if tx_type != BLOB_TX_TYPE {
+            return Err(DecodeError::Custom("blob transaction must be of type 3"))
+        }
+        buf.advance(1);
+
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let started_len = buf.len();
+
+        // keep the encoding of the canonical transaction around to calculate the hash
+        let tx_encoding = *buf;
+        let tx_header = Header::decode(buf)?;
+        if !tx_header.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let (transaction, signature) = TransactionSigned::decode_typed_fields(tx_type, buf)?;
+        let tx_len = tx_encoding.len() - buf.len();
+        if tx_len != tx_header.length() + tx_header.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: tx_header.payload_length,
+                got: tx_len - tx_header.length(),
+            })
+        }
+
+        let sidecar = BlobTransactionSidecar::decode_fields(buf)?;
+        let consumed = started_len - buf.len();
+        if consumed != header.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: header.payload_length,
+                got: consumed,
+            })
+        }
+
+        let mut canonical = Vec::with_capacity(tx_len + 1);
+        canonical.push(tx_type);
+        canonical.extend_from_slice(&tx_encoding[..tx_len]);
+        let transaction = TransactionSigned { transaction, signature, hash: keccak256(&canonical) };
+
+        Self::new(transaction, sidecar)
+            .ok_or(DecodeError::Custom("blob count does not match the versioned hashes"))
+    }
+}
+
+impl Encodable for BlobTransaction {
+    fn encode(&self, out: &mut dyn bytes::BufMut) {
+        let header = Header { list: false, payload_length: self.payload_len() };
+        header.encode(out);
+        self.encode_enveloped(out);
+    }
+
+    fn length(&self) -> usize {
+        let len = self.payload_len();
+        // add the length of the string header
+        len + length_of_length(len)
+    }
+}
+
+/// This `Decodable` implementation only supports decoding the network form sent over p2p.
+impl Decodable for BlobTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(buf)?;
+        if header.list {
+            return Err(DecodeError::Custom("blob transaction must be encoded as a string"))
+        }
+        Self::decode_inner(buf)
+    }
+}
@@ -1,17 +1,25 @@
 use crate::{keccak256, Address, Bytes, ChainId, TxHash, H256};
 pub use access_list::{AccessList, AccessListItem};
+pub use blob::{BlobTransaction, BlobTransactionSidecar};
 use bytes::{Buf, BytesMut};
 use derive_more::{AsRef, Deref};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use reth_codecs::{main_codec, Compact};
-use reth_rlp::{length_of_length, Decodable, DecodeError, Encodable, Header, EMPTY_STRING_CODE};
+use reth_rlp::{
+    length_of_length, Decodable, DecodeError, Encodable, Header, EMPTY_LIST_CODE, EMPTY_STRING_CODE,
+};
 pub use signature::Signature;
 pub use tx_type::TxType;
 
 mod access_list;
+mod blob;
 mod signature;
 mod tx_type;
 mod util;
 
+/// The number of transactions above which their signers are recovered in parallel.
+const PARALLEL_SENDER_RECOVERY_THRESHOLD: usize = 10;
+
 /// Legacy transaction.
 #[main_codec]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
@@ -158,6 +166,66 @@ pub struct TxEip1559 {
     pub input: Bytes,
 }
 
+/// A transaction carrying blobs ([EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)).
+///
+/// The blobs themselves are not part of the transaction, they are only sent along with it over the
+/// network, see [`BlobTransaction`].
+#[main_codec]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TxEip4844 {
+    /// Added as EIP-pub 155: Simple replay attack protection
+    pub chain_id: u64,
+    /// A scalar value equal to the number of transactions sent by the sender; formally Tn.
+    pub nonce: u64,
+    /// A scalar value equal to the maximum
+    /// amount of gas that should be used in executing
+    /// this transaction. This is paid up-front, before any
+    /// computation is done and may not be increased
+    /// later; formally Tg.
+    pub gas_limit: u64,
+    /// The maximum total fee per unit of gas the sender is willing to pay, including the base
+    /// fee.
+    ///
+    /// As ethereum circulation is around 120mil eth as of 2022 that is around
+    /// 120000000000000000000000000 wei we are safe to use u128 as its max number is:
+    /// 340282366920938463463374607431768211455
+    pub max_fee_per_gas: u128,
+    /// Max Priority fee that transaction is paying
+    ///
+    /// As ethereum circulation is around 120mil eth as of 2022 that is around
+    /// 120000000000000000000000000 wei we are safe to use u128 as its max number is:
+    /// 340282366920938463463374607431768211455
+    pub max_priority_fee_per_gas: u128,
+    /// The 160-bit address of the message call’s recipient or, for a contract creation
+    /// transaction, ∅, used here to denote the only member of B0 ; formally Tt.
+    pub to: TransactionKind,
+    /// A scalar value equal to the number of Wei to
+    /// be transferred to the message call’s recipient or,
+    /// in the case of contract creation, as an endowment
+    /// to the newly created account; formally Tv.
+    ///
+    /// As ethereum circulation is around 120mil eth as of 2022 that is around
+    /// 120000000000000000000000000 wei we are safe to use u128 as its max number is:
+    /// 340282366920938463463374607431768211455
+    pub value: u128,
+    /// The accessList specifies a list of addresses and storage keys;
+    /// these addresses and storage keys are added into the `accessed_addresses`
+    /// and `accessed_storage_keys` global sets (introduced in EIP-2929).
+    /// A gas cost is charged, though at a discount relative to the cost of
+    /// accessing outside the list.
+    pub access_list: AccessList,
+    /// The versioned hashes of the KZG commitments to the blobs of the transaction.
+    pub blob_versioned_hashes: Vec<H256>,
+    /// The maximum fee per unit of blob gas the sender is willing to pay.
+    pub max_fee_per_blob_gas: u128,
+    /// Input has two uses depending if transaction is Create or Call (if `to` field is None or
+    /// Some). pub init: An unlimited size byte array specifying the
+    /// EVM-code for the account initialisation procedure CREATE,
+    /// data: An unlimited size byte array specifying the
+    /// input data of the message call, formally Td.
+    pub input: Bytes,
+}
+
 /// A raw transaction.
 ///
 /// Transaction types were introduced in [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718).
@@ -170,6 +238,8 @@ pub enum Transaction {
     Eip2930(TxEip2930),
     /// A transaction with a priority fee ([EIP-1559](https://eips.ethereum.org/EIPS/eip-1559)).
     Eip1559(TxEip1559),
+    /// A transaction carrying blobs ([EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)).
+    Eip4844(TxEip4844),
 }
 
 impl Default for Transaction {
@@ -193,6 +263,7 @@ impl Transaction {
             Transaction::Legacy(TxLegacy { chain_id: ref mut c, .. }) => *c = Some(chain_id),
             Transaction::Eip2930(TxEip2930 { chain_id: ref mut c, .. }) => *c = chain_id,
             Transaction::Eip1559(TxEip1559 { chain_id: ref mut c, .. }) => *c = chain_id,
+            Transaction::Eip4844(TxEip4844 { chain_id: ref mut c, .. }) => *c = chain_id,
         }
     }
 
@@ -202,7 +273,8 @@ impl Transaction {
         match self {
             Transaction::Legacy(TxLegacy { to, .. }) |
             Transaction::Eip2930(TxEip2930 { to, .. }) |
-            Transaction::Eip1559(TxEip1559 { to, .. }) => to,
+            Transaction::Eip1559(TxEip1559 { to, .. }) |
+            Transaction::Eip4844(TxEip4844 { to, .. }) => to,
         }
     }
 
@@ -212,6 +284,7 @@ impl Transaction {
             Transaction::Legacy { .. } => TxType::Legacy,
             Transaction::Eip2930 { .. } => TxType::EIP2930,
             Transaction::Eip1559 { .. } => TxType::EIP1559,
+            Transaction::Eip4844 { .. } => TxType::EIP4844,
         }
     }
 
@@ -221,6 +294,7 @@ impl Transaction {
             Transaction::Legacy(TxLegacy { value, .. }) => value,
             Transaction::Eip2930(TxEip2930 { value, .. }) => value,
             Transaction::Eip1559(TxEip1559 { value, .. }) => value,
+            Transaction::Eip4844(TxEip4844 { value, .. }) => value,
         }
     }
 
@@ -230,6 +304,7 @@ impl Transaction {
             Transaction::Legacy(TxLegacy { nonce, .. }) => *nonce,
             Transaction::Eip2930(TxEip2930 { nonce, .. }) => *nonce,
             Transaction::Eip1559(TxEip1559 { nonce, .. }) => *nonce,
+            Transaction::Eip4844(TxEip4844 { nonce, .. }) => *nonce,
         }
     }
 
@@ -238,7 +313,8 @@ impl Transaction {
         match self {
             Transaction::Legacy(TxLegacy { gas_limit, .. }) |
             Transaction::Eip2930(TxEip2930 { gas_limit, .. }) |
-            Transaction::Eip1559(TxEip1559 { gas_limit, .. }) => *gas_limit,
+            Transaction::Eip1559(TxEip1559 { gas_limit, .. }) |
+            Transaction::Eip4844(TxEip4844 { gas_limit, .. }) => *gas_limit,
         }
     }
 
@@ -247,8 +323,72 @@ impl Transaction {
         match self {
             Transaction::Legacy(TxLegacy { gas_price, .. }) |
             Transaction::Eip2930(TxEip2930 { gas_price, .. }) => *gas_price,
-            Transaction::Eip1559(TxEip1559 { max_fee_per_gas, .. }) => *max_fee_per_gas,
+            Transaction::Eip1559(TxEip1559 { max_fee_per_gas, .. }) |
+            Transaction::Eip4844(TxEip4844 { max_fee_per_gas, .. }) => *max_fee_per_gas,
+        }
+    }
+
+    /// Max priority fee per gas for eip1559 and eip4844 transactions, `None` for transactions
+    /// with a gas price.
+    pub fn max_priority_fee_per_gas(&self) -> Option<u128> {
+        match self {
+            Transaction::Legacy(_) | Transaction::Eip2930(_) => None,
+            Transaction::Eip1559(TxEip1559 { max_priority_fee_per_gas, .. }) |
+            Transaction::Eip4844(TxEip4844 { max_priority_fee_per_gas, .. }) => {
+                Some(*max_priority_fee_per_gas)
+            }
+        }
+    }
+
+    /// Max fee per unit of blob gas for eip4844 transactions, `None` for all other transactions.
+    pub fn max_fee_per_blob_gas(&self) -> Option<u128> {
+        match self {
+            Transaction::Eip4844(TxEip4844 { max_fee_per_blob_gas, .. }) => {
+                Some(*max_fee_per_blob_gas)
+            }
+            _ => None,
+        }
+    }
+
+    /// The versioned hashes of the blobs of an eip4844 transaction, empty for all other
+    /// transactions.
+    pub fn blob_versioned_hashes(&self) -> &[H256] {
+        match self {
+            Transaction::Eip4844(TxEip4844 { blob_versioned_hashes, .. }) => blob_versioned_hashes,
+            _ => &[],
+        }
+    }
+
+    /// Returns the gas price the sender pays per unit of gas in a block with the given base fee.
+    ///
+    /// For transactions with a priority fee this is `min(max_fee, base_fee + max_priority_fee)`,
+    /// or the max fee if the base fee is not known. For all other transactions it is the gas
+    /// price.
+    pub fn effective_gas_price(&self, base_fee: Option<u64>) -> u128 {
+        match (self.max_priority_fee_per_gas(), base_fee) {
+            (Some(max_priority_fee_per_gas), Some(base_fee)) => self
+                .max_fee_per_gas()
+                .min((base_fee as u128).saturating_add(max_priority_fee_per_gas)),
+            _ => self.max_fee_per_gas(),
+        }
+    }
+
+    /// Returns the tip the block producer receives per unit of gas in a block with the given base
+    /// fee.
+    ///
+    /// Returns `None` if the transaction cannot pay the base fee.
+    pub fn effective_tip_per_gas(&self, base_fee: u64) -> Option<u128> {
+        let base_fee = base_fee as u128;
+        let max_fee_per_gas = self.max_fee_per_gas();
+        if max_fee_per_gas < base_fee {
+            return None
         }
+
+        let tip = max_fee_per_gas - base_fee;
+        Some(match self.max_priority_fee_per_gas() {
+            Some(max_priority_fee_per_gas) => tip.min(max_priority_fee_per_gas),
+            None => tip,
+        })
     }
 
     /// Get the transaction's input field.
@@ -257,6 +397,7 @@ impl Transaction {
             Transaction::Legacy(TxLegacy { input, .. }) => input,
             Transaction::Eip2930(TxEip2930 { input, .. }) => input,
             Transaction::Eip1559(TxEip1559 { input, .. }) => input,
+            Transaction::Eip4844(TxEip4844 { input, .. }) => input,
         }
     }
 
@@ -276,6 +417,12 @@ impl Transaction {
                 list_header.encode(out);
                 self.encode_fields(out);
             }
+            Transaction::Eip4844 { .. } => {
+                out.put_u8(3);
+                let list_header = Header { list: true, payload_length: self.fields_len() };
+                list_header.encode(out);
+                self.encode_fields(out);
+            }
         }
     }
 
@@ -387,6 +534,33 @@ impl Transaction {
                 len += access_list.length();
                 len
             }
+            Transaction::Eip4844(TxEip4844 {
+                chain_id,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                to,
+                value,
+                access_list,
+                blob_versioned_hashes,
+                max_fee_per_blob_gas,
+                input,
+            }) => {
+                let mut len = 0;
+                len += chain_id.length();
+                len += nonce.length();
+                len += max_priority_fee_per_gas.length();
+                len += max_fee_per_gas.length();
+                len += gas_limit.length();
+                len += to.length();
+                len += value.length();
+                len += input.0.length();
+                len += access_list.length();
+                len += max_fee_per_blob_gas.length();
+                len += blob_versioned_hashes.length();
+                len
+            }
         }
     }
 
@@ -449,6 +623,31 @@ impl Transaction {
                 input.0.encode(out);
                 access_list.encode(out);
             }
+            Transaction::Eip4844(TxEip4844 {
+                chain_id,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                to,
+                value,
+                access_list,
+                blob_versioned_hashes,
+                max_fee_per_blob_gas,
+                input,
+            }) => {
+                chain_id.encode(out);
+                nonce.encode(out);
+                max_priority_fee_per_gas.encode(out);
+                max_fee_per_gas.encode(out);
+                gas_limit.encode(out);
+                to.encode(out);
+                value.encode(out);
+                input.0.encode(out);
+                access_list.encode(out);
+                max_fee_per_blob_gas.encode(out);
+                blob_versioned_hashes.encode(out);
+            }
         }
     }
 }
@@ -472,10 +671,9 @@ impl Encodable for Transaction {
                 self.encode_inner(out);
                 self.encode_eip155_fields(out);
             }
-            Transaction::Eip2930 { .. } => {
-                self.encode_inner(out);
-            }
-            Transaction::Eip1559 { .. } => {
+            Transaction::Eip2930 { .. } |
+            Transaction::Eip1559 { .. } |
+            Transaction::Eip4844 { .. } => {
                 self.encode_inner(out);
             }
         }
@@ -580,37 +778,7 @@ impl Decodable for TransactionSigned {
                 return Err(DecodeError::Custom("typed tx fields must be encoded as a list"))
             }
 
-            // decode common fields
-            let transaction = match tx_type {
-                1 => Transaction::Eip2930(TxEip2930 {
-                    chain_id: Decodable::decode(buf)?,
-                    nonce: Decodable::decode(buf)?,
-                    gas_price: Decodable::decode(buf)?,
-                    gas_limit: Decodable::decode(buf)?,
-                    to: Decodable::decode(buf)?,
-                    value: Decodable::decode(buf)?,
-                    input: Bytes(Decodable::decode(buf)?),
-                    access_list: Decodable::decode(buf)?,
-                }),
-                2 => Transaction::Eip1559(TxEip1559 {
-                    chain_id: Decodable::decode(buf)?,
-                    nonce: Decodable::decode(buf)?,
-                    max_priority_fee_per_gas: Decodable::decode(buf)?,
-                    max_fee_per_gas: Decodable::decode(buf)?,
-                    gas_limit: Decodable::decode(buf)?,
-                    to: Decodable::decode(buf)?,
-                    value: Decodable::decode(buf)?,
-                    input: Bytes(Decodable::decode(buf)?),
-                    access_list: Decodable::decode(buf)?,
-                }),
-                _ => return Err(DecodeError::Custom("unsupported typed transaction type")),
-            };
-
-            let signature = Signature {
-                odd_y_parity: Decodable::decode(buf)?,
-                r: Decodable::decode(buf)?,
-                s: Decodable::decode(buf)?,
-            };
+            let (transaction, signature) = TransactionSigned::decode_typed_fields(tx_type, buf)?;
 
             let mut signed = TransactionSigned { transaction, hash: Default::default(), signature };
             signed.hash = keccak256(&original_encoding[..first_header.payload_length]);
@@ -657,6 +825,17 @@ impl TransactionSigned {
         self.signature.recover_signer(signature_hash)
     }
 
+    /// Recover the signers of the transactions, in parallel if there are enough of them.
+    ///
+    /// Returns `None` if the signature of any of the transactions is invalid.
+    pub fn recover_signers(transactions: &[Self]) -> Option<Vec<Address>> {
+        if transactions.len() < PARALLEL_SENDER_RECOVERY_THRESHOLD {
+            transactions.iter().map(|tx| tx.recover_signer()).collect()
+        } else {
+            transactions.par_iter().map(|tx| tx.recover_signer()).collect()
+        }
+    }
+
     /// Devour Self, recover signer and return [`TransactionSignedEcRecovered`]
     pub fn into_ecrecovered(self) -> Option<TransactionSignedEcRecovered> {
         let signer = self.recover_signer()?;
@@ -689,28 +868,82 @@ impl TransactionSigned {
                 header.encode(out);
             }
             match self.transaction {
-                Transaction::Eip2930 { .. } => {
-                    out.put_u8(1);
-                    let list_header = Header { list: true, payload_length: self.inner_tx_len() };
-                    list_header.encode(out);
-                }
-                Transaction::Eip1559 { .. } => {
-                    out.put_u8(2);
-                    let list_header = Header { list: true, payload_length: self.inner_tx_len() };
-                    list_header.encode(out);
-                }
+                Transaction::Eip2930 { .. } => out.put_u8(1),
+                Transaction::Eip1559 { .. } => out.put_u8(2),
+                Transaction::Eip4844 { .. } => out.put_u8(3),
                 Transaction::Legacy { .. } => {
                     unreachable!("Legacy transaction should be handled above")
                 }
             }
-
-            self.transaction.encode_fields(out);
-            self.signature.odd_y_parity.encode(out);
-            self.signature.r.encode(out);
-            self.signature.s.encode(out);
+            self.encode_typed_fields(out);
         }
     }
 
+    /// Encodes the fields and the signature of a typed transaction as a RLP list, i.e. everything
+    /// that follows the transaction type byte.
+    pub(crate) fn encode_typed_fields(&self, out: &mut dyn bytes::BufMut) {
+        let list_header = Header { list: true, payload_length: self.inner_tx_len() };
+        list_header.encode(out);
+        self.transaction.encode_fields(out);
+        self.signature.odd_y_parity.encode(out);
+        self.signature.r.encode(out);
+        self.signature.s.encode(out);
+    }
+
+    /// Decodes the fields and the signature of a typed transaction of the given type, without the
+    /// RLP list header.
+    pub(crate) fn decode_typed_fields(
+        tx_type: u8,
+        buf: &mut &[u8],
+    ) -> Result<(Transaction, Signature), DecodeError> {
+        let transaction = match tx_type {
+            1 => Transaction::Eip2930(TxEip2930 {
+                chain_id: Decodable::decode(buf)?,
+                nonce: Decodable::decode(buf)?,
+                gas_price: Decodable::decode(buf)?,
+                gas_limit: Decodable::decode(buf)?,
+                to: Decodable::decode(buf)?,
+                value: Decodable::decode(buf)?,
+                input: Bytes(Decodable::decode(buf)?),
+                access_list: Decodable::decode(buf)?,
+            }),
+            2 => Transaction::Eip1559(TxEip1559 {
+                chain_id: Decodable::decode(buf)?,
+                nonce: Decodable::decode(buf)?,
+                max_priority_fee_per_gas: Decodable::decode(buf)?,
+                max_fee_per_gas: Decodable::decode(buf)?,
+                gas_limit: Decodable::decode(buf)?,
+                to: Decodable::decode(buf)?,
+                value: Decodable::decode(buf)?,
+                input: Bytes(Decodable::decode(buf)?),
+                access_list: Decodable::decode(buf)?,
+            }),
+            3 => Transaction::Eip4844(TxEip4844 {
+                chain_id: Decodable::decode(buf)?,
+                nonce: Decodable::decode(buf)?,
+                max_priority_fee_per_gas: Decodable::decode(buf)?,
+                max_fee_per_gas: Decodable::decode(buf)?,
+                gas_limit: Decodable::decode(buf)?,
+                to: Decodable::decode(buf)?,
+                value: Decodable::decode(buf)?,
+                input: Bytes(Decodable::decode(buf)?),
+                access_list: Decodable::decode(buf)?,
+                max_fee_per_blob_gas: Decodable::decode(buf)?,
+                blob_versioned_hashes: Decodable::decode(buf)?,
+            }),
+            _ => return Err(DecodeError::Custom("unsupported typed transaction type")),
+        };
+
+        // typed transactions only encode the parity of the signature, which must be 0 or 1
+        let signature = Signature {
+            odd_y_parity: Decodable::decode(buf)?,
+            r: Decodable::decode(buf)?,
+            s: Decodable::decode(buf)?,
+        };
+
+        Ok((transaction, signature))
+    }
+
     /// Calculate transaction hash, eip2728 transaction does not contain rlp header and start with
     /// tx type.
     pub fn recalculate_hash(&self) -> H256 {
@@ -727,6 +960,34 @@ impl TransactionSigned {
         initial_tx
     }
 
+    /// Decodes the enveloped EIP-2718 encoding of a transaction, as used by
+    /// `eth_sendRawTransaction`.
+    ///
+    /// Unlike the p2p format, typed transactions are not wrapped in an RLP string header.
+    pub fn decode_enveloped(tx: Bytes) -> Result<Self, DecodeError> {
+        let mut data = tx.as_ref();
+
+        let first = *data.first().ok_or(DecodeError::InputTooShort)?;
+        if first >= EMPTY_LIST_CODE {
+            // legacy transaction
+            return TransactionSigned::decode(&mut data)
+        }
+
+        // wrap the typed transaction in a string header, so it can be decoded like the p2p format
+        let mut buf = Vec::with_capacity(data.len() + length_of_length(data.len()));
+        Header { list: false, payload_length: data.len() }.encode(&mut buf);
+        buf.extend_from_slice(data);
+        TransactionSigned::decode(&mut &buf[..])
+    }
+
+    /// Encodes the transaction in its enveloped EIP-2718 format, as returned by
+    /// `debug_getRawTransaction`.
+    ///
+    /// This is the inverse of [Self::decode_enveloped].
+    pub fn encode_enveloped(&self, out: &mut dyn bytes::BufMut) {
+        self.encode_inner(out, false)
+    }
+
     /// Output the length of the inner transaction and signature fields.
     pub(crate) fn inner_tx_len(&self) -> usize {
         let mut len = self.transaction.fields_len();
@@ -797,6 +1058,26 @@ impl TransactionSignedEcRecovered {
     pub fn from_signed_transaction(signed_transaction: TransactionSigned, signer: Address) -> Self {
         Self { signed_transaction, signer }
     }
+
+    /// Returns the address of the contract created by the transaction, or `None` if it is a call.
+    ///
+    /// The address is derived from the signer and the nonce of the transaction:
+    /// `keccak256(rlp([signer, nonce]))[12..]`.
+    pub fn contract_address(&self) -> Option<Address> {
+        match self.kind() {
+            TransactionKind::Call(_) => None,
+            TransactionKind::Create => Some(create_address(self.signer, self.nonce())),
+        }
+    }
+}
+
+/// Returns the address of the contract created by `sender` with the given nonce.
+fn create_address(sender: Address, nonce: u64) -> Address {
+    let mut out = Vec::new();
+    Header { list: true, payload_length: sender.length() + nonce.length() }.encode(&mut out);
+    sender.encode(&mut out);
+    nonce.encode(&mut out);
+    Address::from_slice(&keccak256(out)[12..])
 }
 
 /// A transaction type that can be created from a [`TransactionSignedEcRecovered`] transaction.
@@ -835,8 +1116,13 @@ impl IntoRecoveredTransaction for TransactionSignedEcRecovered {
 #[cfg(test)]
 mod tests {
     use crate::{
-        transaction::{signature::Signature, TransactionKind, TxEip1559, TxEip2930, TxLegacy},
-        AccessList, Address, Bytes, Transaction, TransactionSigned, H256, U256,
+        keccak256,
+        transaction::{
+            create_address, signature::Signature, TransactionKind, TxEip1559, TxEip2930, TxEip4844,
+            TxLegacy, PARALLEL_SENDER_RECOVERY_THRESHOLD,
+        },
+        AccessList, Address, BlobTransaction, BlobTransactionSidecar, Bytes, Transaction,
+        TransactionSigned, H256, U256,
     };
     use bytes::BytesMut;
     use ethers_core::utils::hex;
@@ -875,6 +1161,34 @@ mod tests {
         let _decoded = TransactionSigned::decode(&mut &tx_bytes[..]).unwrap();
     }
 
+    #[test]
+    fn test_decode_enveloped() {
+        // typed transaction without the p2p string header
+        let tx_bytes = hex::decode("02f872041a8459682f008459682f0d8252089461815774383099e24810ab832a5b2a5425c154d58829a2241af62c000080c001a059e6b67f48fb32e7e570dfb11e042b5ad2e55e3ce3ce9cd989c7e06e07feeafda0016b83f4f980694ed2eee4d10667242b1f40dc406901b34125b008d334d47469").unwrap();
+        let decoded = TransactionSigned::decode_enveloped(tx_bytes.clone().into()).unwrap();
+        assert_eq!(decoded.hash(), keccak256(&tx_bytes));
+        let mut encoded = Vec::new();
+        decoded.encode_enveloped(&mut encoded);
+        assert_eq!(encoded, tx_bytes);
+
+        // legacy transaction
+        let tx_bytes = hex::decode("f86b02843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba00eb96ca19e8a77102767a41fc85a36afd5c61ccb09911cec5d3e86e193d9c5aea03a456401896b1b6055311536bf00a718568c744d8c1f9df59879e8350220ca18").unwrap();
+        let decoded = TransactionSigned::decode_enveloped(tx_bytes.clone().into()).unwrap();
+        assert_eq!(decoded.hash(), keccak256(&tx_bytes));
+        let mut encoded = Vec::new();
+        decoded.encode_enveloped(&mut encoded);
+        assert_eq!(encoded, tx_bytes);
+
+        assert!(TransactionSigned::decode_enveloped(Bytes::default()).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_legacy_v() {
+        // same as the legacy transaction above, but with v = 1
+        let tx_bytes = hex::decode("f86b02843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c680008001a00eb96ca19e8a77102767a41fc85a36afd5c61ccb09911cec5d3e86e193d9c5aea03a456401896b1b6055311536bf00a718568c744d8c1f9df59879e8350220ca18").unwrap();
+        assert!(TransactionSigned::decode(&mut &tx_bytes[..]).is_err());
+    }
+
     #[test]
     fn test_decode_call() {
         let request = Transaction::Eip2930(TxEip2930 {
@@ -899,6 +1213,145 @@ mod tests {
         assert_eq!(decoded, tx);
     }
 
+    fn blob_transaction() -> TransactionSigned {
+        let request = Transaction::Eip4844(TxEip4844 {
+            chain_id: 1,
+            nonce: 7,
+            gas_limit: 21000,
+            max_fee_per_gas: 0x4a817c800,
+            max_priority_fee_per_gas: 0x3b9aca00,
+            to: TransactionKind::Call(Address::random()),
+            value: 3,
+            access_list: Default::default(),
+            blob_versioned_hashes: vec![H256::random(), H256::random()],
+            max_fee_per_blob_gas: 10,
+            input: Bytes::from(vec![1, 2]),
+        });
+        let signature = Signature { odd_y_parity: true, r: U256::from(1), s: U256::from(2) };
+        TransactionSigned::from_transaction_and_signature(request, signature)
+    }
+
+    #[test]
+    fn test_decode_eip4844() {
+        let tx = blob_transaction();
+
+        let mut encoded = BytesMut::new();
+        tx.encode(&mut encoded);
+        assert_eq!(encoded.len(), tx.length());
+        let decoded = TransactionSigned::decode(&mut &*encoded).unwrap();
+        assert_eq!(decoded, tx);
+
+        let mut enveloped = Vec::new();
+        tx.encode_enveloped(&mut enveloped);
+        assert_eq!(enveloped[0], 3);
+        assert_eq!(tx.hash(), keccak256(&enveloped));
+        assert_eq!(TransactionSigned::decode_enveloped(enveloped.into()).unwrap(), tx);
+    }
+
+    #[test]
+    fn test_blob_transaction_network_form() {
+        let tx = blob_transaction();
+        let sidecar = BlobTransactionSidecar {
+            blobs: vec![Bytes::from(vec![1; 64]), Bytes::from(vec![2; 64])],
+            commitments: vec![Bytes::from(vec![3; 48]), Bytes::from(vec![4; 48])],
+            proofs: vec![Bytes::from(vec![5; 48]), Bytes::from(vec![6; 48])],
+        };
+
+        // the number of blobs must match the versioned hashes
+        let mut missing = sidecar.clone();
+        missing.proofs.pop();
+        assert!(BlobTransaction::new(tx.clone(), missing).is_none());
+        // only blob transactions can carry blobs
+        assert!(BlobTransaction::new(TransactionSigned::default(), sidecar.clone()).is_none());
+
+        let blob_tx = BlobTransaction::new(tx.clone(), sidecar).unwrap();
+
+        let mut encoded = BytesMut::new();
+        blob_tx.encode(&mut encoded);
+        assert_eq!(encoded.len(), blob_tx.length());
+        let decoded = BlobTransaction::decode(&mut &*encoded).unwrap();
+        assert_eq!(decoded, blob_tx);
+        // the hash is the hash of the canonical form
+        assert_eq!(decoded.hash(), tx.hash());
+
+        let mut enveloped = Vec::new();
+        blob_tx.encode_enveloped(&mut enveloped);
+        let decoded = BlobTransaction::decode_enveloped(enveloped.clone().into()).unwrap();
+        assert_eq!(decoded, blob_tx);
+
+        // the network form is not the canonical form
+        assert!(TransactionSigned::decode_enveloped(enveloped.into()).is_err());
+        let mut canonical = Vec::new();
+        tx.encode_enveloped(&mut canonical);
+        assert!(BlobTransaction::decode_enveloped(canonical.into()).is_err());
+    }
+
+    #[test_fuzz::test_fuzz]
+    fn test_encode_decode_roundtrip(transaction: Transaction, signature: Signature) {
+        let tx = TransactionSigned::from_transaction_and_signature(transaction, signature);
+
+        let mut encoded = BytesMut::new();
+        tx.encode(&mut encoded);
+        assert_eq!(encoded.len(), tx.length());
+        let decoded = TransactionSigned::decode(&mut &*encoded).unwrap();
+        assert_eq!(decoded, tx);
+
+        let mut enveloped = Vec::new();
+        tx.encode_enveloped(&mut enveloped);
+        assert_eq!(tx.hash(), keccak256(&enveloped));
+        assert_eq!(TransactionSigned::decode_enveloped(enveloped.into()).unwrap(), tx);
+    }
+
+    #[test]
+    fn test_encode_decode_all_types() {
+        let transactions = [
+            Transaction::Legacy(TxLegacy::default()),
+            Transaction::Legacy(TxLegacy { chain_id: Some(1), ..Default::default() }),
+            Transaction::Eip2930(TxEip2930::default()),
+            Transaction::Eip1559(TxEip1559::default()),
+            Transaction::Eip4844(TxEip4844::default()),
+            blob_transaction().transaction,
+        ];
+        for transaction in transactions {
+            for odd_y_parity in [false, true] {
+                let signature = Signature { odd_y_parity, r: U256::from(1), s: U256::from(2) };
+                test_encode_decode_roundtrip(transaction.clone(), signature);
+            }
+        }
+    }
+
+    #[test]
+    fn test_effective_gas_price() {
+        let legacy = Transaction::Legacy(TxLegacy { gas_price: 10, ..Default::default() });
+        assert_eq!(legacy.effective_gas_price(Some(7)), 10);
+        assert_eq!(legacy.effective_gas_price(None), 10);
+        assert_eq!(legacy.effective_tip_per_gas(7), Some(3));
+        assert_eq!(legacy.effective_tip_per_gas(11), None);
+        assert_eq!(legacy.max_priority_fee_per_gas(), None);
+
+        let eip1559 = Transaction::Eip1559(TxEip1559 {
+            max_fee_per_gas: 10,
+            max_priority_fee_per_gas: 2,
+            ..Default::default()
+        });
+        assert_eq!(eip1559.effective_gas_price(Some(7)), 9);
+        assert_eq!(eip1559.effective_gas_price(Some(9)), 10);
+        assert_eq!(eip1559.effective_gas_price(None), 10);
+        assert_eq!(eip1559.effective_tip_per_gas(7), Some(2));
+        assert_eq!(eip1559.effective_tip_per_gas(9), Some(1));
+        assert_eq!(eip1559.effective_tip_per_gas(11), None);
+
+        let eip4844 = Transaction::Eip4844(TxEip4844 {
+            max_fee_per_gas: 10,
+            max_priority_fee_per_gas: 2,
+            max_fee_per_blob_gas: 5,
+            ..Default::default()
+        });
+        assert_eq!(eip4844.effective_gas_price(Some(7)), 9);
+        assert_eq!(eip4844.max_fee_per_blob_gas(), Some(5));
+        assert!(eip4844.blob_versioned_hashes().is_empty());
+    }
+
     #[test]
     fn decode_transaction_consumes_buffer() {
         let bytes = &mut &hex::decode("b87502f872041a8459682f008459682f0d8252089461815774383099e24810ab832a5b2a5425c154d58829a2241af62c000080c001a059e6b67f48fb32e7e570dfb11e042b5ad2e55e3ce3ce9cd989c7e06e07feeafda0016b83f4f980694ed2eee4d10667242b1f40dc406901b34125b008d334d47469").unwrap()[..];
@@ -1086,6 +1539,55 @@ mod tests {
         assert_eq!(signed_tx.recover_signer(), Some(signer), "Recovering signer should pass.");
     }
 
+    #[test]
+    fn recover_signers_in_parallel() {
+        use crate::hex_literal::hex;
+
+        let signer: Address = hex!("398137383b3d25c92898c656696e41950e47316b").into();
+        let tx = Transaction::Legacy(TxLegacy {
+            chain_id: Some(1),
+            nonce: 0x18,
+            gas_price: 0xfa56ea00,
+            gas_limit: 119902,
+            to: TransactionKind::Call(hex!("06012c8cf97bead5deae237070f9587f8e7a266d").into()),
+            value: 0x1c6bf526340000u64.into(),
+            input: hex!("f7d8c88300000000000000000000000000000000000000000000000000000000000cee6100000000000000000000000000000000000000000000000000000000000ac3e1").into(),
+        });
+        let sig = Signature {
+            r: hex!("2a378831cf81d99a3f06a18ae1b6ca366817ab4d88a70053c41d7a8f0368e031").into(),
+            s: hex!("450d831a05b6e418724436c05c155e0a1b7b921015d0fbc2f667aed709ac4fb5").into(),
+            odd_y_parity: false,
+        };
+        let signed_tx = TransactionSigned::from_transaction_and_signature(tx.clone(), sig);
+
+        // below and above the threshold for parallel recovery
+        for count in [0, 1, PARALLEL_SENDER_RECOVERY_THRESHOLD * 2] {
+            let transactions = vec![signed_tx.clone(); count];
+            assert_eq!(
+                TransactionSigned::recover_signers(&transactions),
+                Some(vec![signer; count])
+            );
+        }
+
+        let invalid = TransactionSigned::from_transaction_and_signature(tx, Signature::default());
+        let mut transactions = vec![signed_tx; PARALLEL_SENDER_RECOVERY_THRESHOLD * 2];
+        transactions.push(invalid);
+        assert_eq!(TransactionSigned::recover_signers(&transactions), None);
+    }
+
+    #[test]
+    fn contract_address() {
+        let sender = Address::from_str("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        assert_eq!(
+            create_address(sender, 0),
+            Address::from_str("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d").unwrap()
+        );
+        assert_eq!(
+            create_address(sender, 1),
+            Address::from_str("343c43a37d37dff08ae8c4a11544c718abb4fcf8").unwrap()
+        );
+    }
+
     #[test]
     fn recover_signer_eip1559() {
         use crate::hex_literal::hex;
@@ -18,10 +18,22 @@ pub struct Signature {
 
 #[allow(dead_code)]
 impl Signature {
+    /// Returns the `v` value of a legacy transaction signed with this signature.
+    ///
+    /// With a chain ID this is the EIP-155 value `{0, 1} + CHAIN_ID * 2 + 35`, otherwise
+    /// `{0, 1} + 27`. Typed transactions only encode the parity of the signature.
+    pub fn v(&self, chain_id: Option<u64>) -> u64 {
+        match chain_id {
+            // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
+            Some(chain_id) => chain_id * 2 + 35 + self.odd_y_parity as u64,
+            None => self.odd_y_parity as u64 + 27,
+        }
+    }
+
     /// Encode the `v`, `r`, `s` values without a RLP header.
     /// Encodes the `v` value using the legacy scheme without EIP-155.
     pub(crate) fn encode_inner_legacy(&self, out: &mut dyn reth_rlp::BufMut) {
-        (self.odd_y_parity as u8 + 27).encode(out);
+        self.v(None).encode(out);
         self.r.encode(out);
         self.s.encode(out);
     }
@@ -29,15 +41,13 @@ impl Signature {
     /// Output the length of the signature without the length of the RLP header, using the legacy
     /// scheme without EIP-155.
     pub(crate) fn payload_len_legacy(&self) -> usize {
-        (self.odd_y_parity as u8 + 27).length() + self.r.length() + self.s.length()
+        self.v(None).length() + self.r.length() + self.s.length()
     }
 
     /// Encode the `v`, `r`, `s` values without a RLP header.
     /// Encodes the `v` value with EIP-155 support, using the specified chain ID.
     pub(crate) fn encode_eip155_inner(&self, out: &mut dyn reth_rlp::BufMut, chain_id: u64) {
-        // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
-        let v = chain_id * 2 + 35 + self.odd_y_parity as u64;
-        v.encode(out);
+        self.v(Some(chain_id)).encode(out);
         self.r.encode(out);
         self.s.encode(out);
     }
@@ -45,26 +55,27 @@ impl Signature {
     /// Output the length of the signature without the length of the RLP header, with EIP-155
     /// support.
     pub(crate) fn eip155_payload_len(&self, chain_id: u64) -> usize {
-        // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
-        let v = chain_id * 2 + 35 + self.odd_y_parity as u64;
-        v.length() + self.r.length() + self.s.length()
+        self.v(Some(chain_id)).length() + self.r.length() + self.s.length()
     }
 
     /// Decodes the `v`, `r`, `s` values without a RLP header.
     /// This will return a chain ID if the `v` value is EIP-155 compatible.
+    ///
+    /// Returns an error if `v` is neither 27, 28 nor an EIP-155 value.
     pub(crate) fn decode_eip155_inner(buf: &mut &[u8]) -> Result<(Self, Option<u64>), DecodeError> {
         let v = u64::decode(buf)?;
         let r = Decodable::decode(buf)?;
         let s = Decodable::decode(buf)?;
-        if v >= 35 {
-            // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
-            let odd_y_parity = ((v - 35) % 2) != 0;
-            let chain_id = (v - 35) >> 1;
-            Ok((Signature { r, s, odd_y_parity }, Some(chain_id)))
-        } else {
+        match v {
             // non-EIP-155 legacy scheme
-            let odd_y_parity = (v - 27) != 0;
-            Ok((Signature { r, s, odd_y_parity }, None))
+            27 | 28 => Ok((Signature { r, s, odd_y_parity: v == 28 }, None)),
+            // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
+            35.. => {
+                let odd_y_parity = ((v - 35) % 2) != 0;
+                let chain_id = (v - 35) >> 1;
+                Ok((Signature { r, s, odd_y_parity }, Some(chain_id)))
+            }
+            _ => Err(DecodeError::Custom("invalid v value of legacy signature")),
         }
     }
 
@@ -11,6 +11,8 @@ pub enum TxType {
     EIP2930 = 1_isize,
     /// Transaction with Priority fee
     EIP1559 = 2_isize,
+    /// Transaction carrying blobs
+    EIP4844 = 3_isize,
 }
 
 impl Compact for TxType {
@@ -18,7 +20,8 @@ impl Compact for TxType {
         match self {
             TxType::Legacy => 0,
             TxType::EIP2930 => 1,
-            _ => 2,
+            TxType::EIP1559 => 2,
+            TxType::EIP4844 => 3,
         }
     }
 
@@ -27,7 +30,8 @@ impl Compact for TxType {
             match identifier {
                 0 => TxType::Legacy,
                 1 => TxType::EIP2930,
-                _ => TxType::EIP1559,
+                2 => TxType::EIP1559,
+                _ => TxType::EIP4844,
             },
             buf,
         )
@@ -0,0 +1,115 @@
+use crate::H256;
+use bytes::Buf;
+use reth_codecs::Compact;
+
+/// A branch node of the Merkle Patricia Trie in the compact form it is stored in the database.
+///
+/// Only the branch nodes that have at least one child that is a stored branch node or the hash of
+/// a branch node are stored, so the trie can be updated without walking the unchanged subtrees.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BranchNodeCompact {
+    /// The bitmask of the children of the node.
+    pub state_mask: u16,
+    /// The bitmask of the children that are stored branch nodes.
+    pub tree_mask: u16,
+    /// The bitmask of the children whose hashes are stored in [Self::hashes].
+    pub hash_mask: u16,
+    /// The hashes of the children in [Self::hash_mask], in the order of their nibbles.
+    pub hashes: Vec<H256>,
+    /// The hash of the node itself, only stored for the root node of a trie.
+    pub root_hash: Option<H256>,
+}
+
+impl BranchNodeCompact {
+    /// Creates a new branch node.
+    ///
+    /// The tree and hash masks must be subsets of the state mask, and there must be one hash for
+    /// each bit of the hash mask.
+    pub fn new(
+        state_mask: u16,
+        tree_mask: u16,
+        hash_mask: u16,
+        hashes: Vec<H256>,
+        root_hash: Option<H256>,
+    ) -> Self {
+        debug_assert_eq!(tree_mask & !state_mask, 0, "tree mask must be a subset of state mask");
+        debug_assert_eq!(hash_mask & !state_mask, 0, "hash mask must be a subset of state mask");
+        debug_assert_eq!(hash_mask.count_ones() as usize, hashes.len());
+        Self { state_mask, tree_mask, hash_mask, hashes, root_hash }
+    }
+
+    /// Returns the stored hash of the child at the given nibble.
+    ///
+    /// # Panics
+    ///
+    /// If the bit of the nibble is not set in the hash mask.
+    pub fn hash_for_nibble(&self, nibble: u8) -> H256 {
+        let preceding = self.hash_mask & ((1u16 << nibble) - 1);
+        self.hashes[preceding.count_ones() as usize]
+    }
+}
+
+// NOTE: The number of hashes follows from the hash mask, so a trailing hash is the root hash.
+impl Compact for BranchNodeCompact {
+    fn to_compact(self, buf: &mut impl bytes::BufMut) -> usize {
+        buf.put_u16(self.state_mask);
+        buf.put_u16(self.tree_mask);
+        buf.put_u16(self.hash_mask);
+        let hashes = self.hashes.len() + self.root_hash.is_some() as usize;
+        for hash in self.hashes.into_iter().chain(self.root_hash) {
+            buf.put_slice(hash.as_bytes());
+        }
+        6 + hashes * 32
+    }
+
+    fn from_compact(mut buf: &[u8], len: usize) -> (Self, &[u8])
+    where
+        Self: Sized,
+    {
+        let state_mask = buf.get_u16();
+        let tree_mask = buf.get_u16();
+        let hash_mask = buf.get_u16();
+
+        let count = hash_mask.count_ones() as usize;
+        let mut hashes = Vec::with_capacity(count);
+        for _ in 0..count {
+            hashes.push(H256::from_slice(&buf[..32]));
+            buf.advance(32);
+        }
+
+        let mut root_hash = None;
+        if len > 6 + count * 32 {
+            root_hash = Some(H256::from_slice(&buf[..32]));
+            buf.advance(32);
+        }
+
+        (Self { state_mask, tree_mask, hash_mask, hashes, root_hash }, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_node_compact_roundtrip() {
+        let hashes = vec![H256::random(), H256::random()];
+        for root_hash in [None, Some(H256::random())] {
+            let node =
+                BranchNodeCompact::new(0b1010_0110, 0b100, 0b1000_0010, hashes.clone(), root_hash);
+
+            let mut buf = vec![];
+            let len = node.clone().to_compact(&mut buf);
+            assert_eq!(len, buf.len());
+            assert_eq!(BranchNodeCompact::from_compact(&buf, len), (node, &[][..]));
+        }
+    }
+
+    #[test]
+    fn hash_for_nibble() {
+        let hashes = vec![H256::random(), H256::random()];
+        let node = BranchNodeCompact::new(0b1010_0110, 0, 0b1000_0010, hashes.clone(), None);
+        assert_eq!(node.hash_for_nibble(1), hashes[0]);
+        assert_eq!(node.hash_for_nibble(7), hashes[1]);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{Address, U256};
+use reth_codecs::{main_codec, Compact};
+use reth_rlp::{RlpDecodable, RlpEncodable};
+
+/// The number of wei in one gwei.
+const GWEI_TO_WEI: u64 = 1_000_000_000;
+
+/// Withdrawal of ether from the beacon chain to the execution layer (EIP-4895).
+#[main_codec]
+#[derive(Clone, Debug, PartialEq, Eq, RlpDecodable, RlpEncodable, Default)]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier issued by the consensus layer.
+    pub index: u64,
+    /// Index of the validator associated with the withdrawal.
+    pub validator_index: u64,
+    /// Target address of the withdrawn ether.
+    pub address: Address,
+    /// Value of the withdrawal in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Returns the value of the withdrawal in wei.
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount) * U256::from(GWEI_TO_WEI)
+    }
+}
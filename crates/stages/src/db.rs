@@ -7,9 +7,9 @@ use std::{
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::{Database, DatabaseGAT},
-    models::{BlockNumHash, StoredBlockBody},
+    models::{BlockNumHash, ShardedKey, StoredBlockBody, NUM_OF_INDICES_IN_SHARD},
     table::Table,
-    tables,
+    tables::{self, TransitionList},
     transaction::{DbTx, DbTxMut},
     Error,
 };
@@ -233,4 +233,63 @@ where
         }
         Ok(())
     }
+
+    /// Appends the transition to the history index of the key.
+    ///
+    /// The transitions are appended to the last shard of the key, `(key, u64::MAX)`. Once the last
+    /// shard is full, it is stored under its highest transition and a new last shard is started.
+    pub(crate) fn insert_history_index<T, K>(
+        &self,
+        key: K,
+        transition: TransitionId,
+    ) -> Result<(), Error>
+    where
+        DB: Database,
+        T: Table<Key = ShardedKey<K>, Value = TransitionList>,
+        K: Clone,
+    {
+        let last_shard = ShardedKey::new(key.clone(), u64::MAX);
+        let mut transitions = self
+            .get::<T>(last_shard.clone())?
+            .map(|list| list.iter(0).collect::<Vec<_>>())
+            .unwrap_or_default();
+        transitions.push(transition as usize);
+
+        if transitions.len() > NUM_OF_INDICES_IN_SHARD {
+            let full_shard = transitions.drain(..NUM_OF_INDICES_IN_SHARD).collect::<Vec<_>>();
+            let highest = *full_shard.last().expect("shard is not empty") as u64;
+            self.put::<T>(ShardedKey::new(key, highest), full_shard.into())?;
+        }
+        self.put::<T>(last_shard, transitions.into())
+    }
+
+    /// Removes the transitions from `transition` onwards from the history index of the key.
+    pub(crate) fn unwind_history_index<T, K>(
+        &self,
+        key: K,
+        transition: TransitionId,
+    ) -> Result<(), Error>
+    where
+        DB: Database,
+        T: Table<Key = ShardedKey<K>, Value = TransitionList>,
+        K: Clone + PartialEq,
+    {
+        // The shards with transitions from `transition` onwards, only the first one can also
+        // contain transitions before it.
+        let mut cursor = self.cursor::<T>()?;
+        let shards = cursor
+            .walk(ShardedKey::new(key.clone(), transition))?
+            .take_while(|entry| entry.as_ref().map_or(true, |(shard, _)| shard.key == key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut kept = Vec::new();
+        for (shard, list) in shards {
+            kept.extend(list.iter(0).filter(|id| (*id as u64) < transition));
+            self.delete::<T>(shard, None)?;
+        }
+        if !kept.is_empty() {
+            self.put::<T>(ShardedKey::new(key, u64::MAX), kept.into())?;
+        }
+        Ok(())
+    }
 }
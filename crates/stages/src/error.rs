@@ -135,3 +135,70 @@ pub enum PipelineError {
     #[error(transparent)]
     Internal(Box<dyn std::error::Error + Send + Sync>),
 }
+
+/// A blockchain tree error.
+#[derive(Error, Debug)]
+pub enum BlockchainTreeError {
+    /// The block is invalid according to the consensus rules.
+    #[error("Block #{block} failed validation: {error}.")]
+    Validation {
+        /// The invalid block.
+        block: BlockNumber,
+        /// The underlying consensus error.
+        #[source]
+        error: consensus::Error,
+    },
+    /// The block failed execution.
+    #[error("Block #{block} failed execution: {error}.")]
+    Execution {
+        /// The block that failed execution.
+        block: BlockNumber,
+        /// The underlying execution error.
+        #[source]
+        error: executor::Error,
+    },
+    /// The sender of a transaction of the block could not be recovered.
+    #[error("Could not recover the sender of a transaction in block #{block}.")]
+    SenderRecovery {
+        /// The block with the invalid transaction.
+        block: BlockNumber,
+    },
+    /// The block is neither canonical nor in the tree.
+    #[error("Block {hash:?} is unknown.")]
+    UnknownBlock {
+        /// The hash of the block.
+        hash: BlockHash,
+    },
+    /// The parent of the block is canonical, but its state is not available yet.
+    #[error("Parent {parent:?} of block #{block} is not executed yet.")]
+    ParentNotExecuted {
+        /// The block.
+        block: BlockNumber,
+        /// The hash of the parent.
+        parent: BlockHash,
+    },
+    /// The block can't become canonical, since it is not after the finalized block.
+    #[error("Block #{block} is not after the finalized block #{finalized}.")]
+    BelowFinalized {
+        /// The block.
+        block: BlockNumber,
+        /// The finalized block.
+        finalized: BlockNumber,
+    },
+    /// The canonical chain can't be unwound to the block a side chain forks from, since a stage
+    /// of the pipeline is behind that block.
+    #[error("The canonical chain can't be unwound to block #{fork}, a stage is behind it.")]
+    StageBehindFork {
+        /// The block the side chain forks from.
+        fork: BlockNumber,
+    },
+    /// The tree encountered a database error.
+    #[error("A database error occurred: {0}")]
+    Database(#[from] DbError),
+    /// The tree encountered a provider error.
+    #[error("A provider error occurred: {0}")]
+    Provider(#[from] reth_interfaces::Error),
+    /// The pipeline failed to commit the blocks.
+    #[error(transparent)]
+    Pipeline(#[from] PipelineError),
+}
@@ -0,0 +1,228 @@
+//! An external sort (ETL) collector for stages that write large unsorted data sets.
+//!
+//! Inserting entries into MDBX in random key order touches a different page for nearly every
+//! entry. The [Collector] buffers the entries in memory and spills them to temporary files in
+//! sorted runs once the buffer is full. The runs are then merged, so the entries are loaded into
+//! the database in sorted order, which allows appending them.
+
+use crate::StageError;
+use reth_db::table::{Compress, Decode, Decompress, Encode, Key, Value};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+};
+
+/// The default number of bytes buffered in memory before a sorted run is written to disk.
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 256 * 1024 * 1024;
+
+/// An encoded key and compressed value.
+type Entry = (Vec<u8>, Vec<u8>);
+
+fn etl_error(err: io::Error) -> StageError {
+    StageError::Fatal(Box::new(err))
+}
+
+/// Collects key-value pairs in any order and returns them sorted by their encoded key.
+///
+/// Entries with the same key are sorted by their compressed value, which is the order of the
+/// values of a key in [DupSort][reth_db::table::DupSort] tables.
+#[derive(Debug)]
+pub(crate) struct Collector<K, V> {
+    /// The entries that were not written to a file yet.
+    buffer: Vec<Entry>,
+    /// The number of bytes of the buffered entries.
+    buffer_size: usize,
+    /// The number of bytes that are buffered before a sorted run is written to a file.
+    buffer_capacity: usize,
+    /// The sorted runs written to temporary files, with their number of entries.
+    files: Vec<(File, usize)>,
+    /// The total number of collected entries.
+    len: usize,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K: Key, V: Value> Collector<K, V> {
+    /// Create a collector that buffers up to `buffer_capacity` bytes in memory.
+    pub(crate) fn new(buffer_capacity: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            buffer_size: 0,
+            buffer_capacity,
+            files: Vec::new(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of collected entries.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Collect an entry, writing the buffer to a temporary file if it's full.
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Result<(), StageError> {
+        let entry = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        self.buffer_size += entry.0.len() + entry.1.len();
+        self.buffer.push(entry);
+        self.len += 1;
+        if self.buffer_size >= self.buffer_capacity {
+            self.flush().map_err(etl_error)?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffer as a sorted run to a new temporary file.
+    fn flush(&mut self) -> io::Result<()> {
+        self.buffer.sort_unstable();
+
+        let mut writer = BufWriter::new(tempfile::tempfile()?);
+        for (key, value) in &self.buffer {
+            writer.write_all(&(key.len() as u32).to_be_bytes())?;
+            writer.write_all(&(value.len() as u32).to_be_bytes())?;
+            writer.write_all(key)?;
+            writer.write_all(value)?;
+        }
+        let mut file = writer.into_inner().map_err(|err| err.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+
+        self.files.push((file, self.buffer.len()));
+        self.buffer.clear();
+        self.buffer_size = 0;
+        Ok(())
+    }
+
+    /// Return an iterator over the collected entries in sorted order.
+    ///
+    /// The sorted runs are merged lazily, so only one entry per run is held in memory.
+    pub(crate) fn into_sorted_iter(mut self) -> Result<CollectorIter<K, V>, StageError> {
+        self.buffer.sort_unstable();
+        let mut sources = vec![Source::Memory(std::mem::take(&mut self.buffer).into_iter())];
+        sources.extend(
+            self.files
+                .drain(..)
+                .map(|(file, remaining)| Source::File { reader: BufReader::new(file), remaining }),
+        );
+
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (index, source) in sources.iter_mut().enumerate() {
+            if let Some(entry) = source.next_entry().map_err(etl_error)? {
+                heap.push(Reverse((entry, index)));
+            }
+        }
+
+        Ok(CollectorIter { sources, heap, _marker: PhantomData })
+    }
+}
+
+/// A sorted run of entries.
+#[derive(Debug)]
+enum Source {
+    /// The entries that were still buffered in memory.
+    Memory(std::vec::IntoIter<Entry>),
+    /// The entries of a temporary file.
+    File { reader: BufReader<File>, remaining: usize },
+}
+
+impl Source {
+    fn next_entry(&mut self) -> io::Result<Option<Entry>> {
+        match self {
+            Source::Memory(entries) => Ok(entries.next()),
+            Source::File { remaining: 0, .. } => Ok(None),
+            Source::File { reader, remaining } => {
+                let mut lengths = [0; 8];
+                reader.read_exact(&mut lengths)?;
+                let key_len = u32::from_be_bytes(lengths[..4].try_into().unwrap()) as usize;
+                let value_len = u32::from_be_bytes(lengths[4..].try_into().unwrap()) as usize;
+
+                let mut key = vec![0; key_len];
+                reader.read_exact(&mut key)?;
+                let mut value = vec![0; value_len];
+                reader.read_exact(&mut value)?;
+
+                *remaining -= 1;
+                Ok(Some((key, value)))
+            }
+        }
+    }
+}
+
+/// Iterator over the entries of a [Collector] in sorted order.
+#[derive(Debug)]
+pub(crate) struct CollectorIter<K, V> {
+    sources: Vec<Source>,
+    /// The next entry of every source that isn't exhausted, smallest first.
+    heap: BinaryHeap<Reverse<(Entry, usize)>>,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K: Key, V: Value> CollectorIter<K, V> {
+    fn next_entry(&mut self) -> Result<Option<(K, V)>, StageError> {
+        let Reverse(((key, value), index)) = match self.heap.pop() {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+        if let Some(entry) = self.sources[index].next_entry().map_err(etl_error)? {
+            self.heap.push(Reverse((entry, index)));
+        }
+        Ok(Some((K::decode(key)?, V::decompress(value)?)))
+    }
+}
+
+impl<K: Key, V: Value> Iterator for CollectorIter<K, V> {
+    type Item = Result<(K, V), StageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::models::StoredTxLocation;
+    use reth_primitives::H256;
+
+    #[test]
+    fn sorts_across_files() {
+        // a tiny buffer spills every few entries to a file
+        let mut collector = Collector::<H256, StoredTxLocation>::new(100);
+        let mut expected = Vec::new();
+        for i in 0..100u64 {
+            let hash = H256::from_low_u64_be(i.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let location = StoredTxLocation { tx_number: i, block_number: i / 10 };
+            collector.insert(hash, location).unwrap();
+            expected.push((hash, location));
+        }
+        assert_eq!(collector.len(), 100);
+        assert!(collector.files.len() > 1);
+        assert!(!collector.buffer.is_empty());
+
+        expected.sort_by_key(|(hash, _)| *hash);
+        let sorted = collector.into_sorted_iter().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn sorts_duplicate_keys_by_value() {
+        let mut collector = Collector::<H256, StoredTxLocation>::new(DEFAULT_BUFFER_CAPACITY);
+        let key = H256::from_low_u64_be(1);
+        for tx_number in [3, 1, 2] {
+            collector.insert(key, StoredTxLocation { tx_number, block_number: 0 }).unwrap();
+        }
+        assert!(collector.files.is_empty());
+
+        let tx_numbers =
+            collector.into_sorted_iter().unwrap().map(|entry| entry.unwrap().1.tx_number);
+        assert_eq!(tx_numbers.collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn empty() {
+        let collector = Collector::<H256, StoredTxLocation>::new(DEFAULT_BUFFER_CAPACITY);
+        assert_eq!(collector.len(), 0);
+        assert_eq!(collector.into_sorted_iter().unwrap().count(), 0);
+    }
+}
@@ -16,6 +16,7 @@
 
 mod db;
 mod error;
+mod etl;
 mod id;
 mod pipeline;
 mod stage;
@@ -30,6 +31,12 @@ pub mod stages;
 /// Stage metrics.
 pub mod metrics;
 
+/// Pruning of historical data.
+pub mod prune;
+
+/// The tree of the side chains that fork from the canonical chain.
+pub mod tree;
+
 pub use db::Transaction;
 pub use error::*;
 pub use id::*;
@@ -1,6 +1,8 @@
-use metrics::Counter;
+use crate::StageId;
+use metrics::{register_counter, register_histogram, Counter, Histogram};
 use reth_interfaces::p2p::error::DownloadError;
 use reth_metrics_derive::Metrics;
+use std::time::Duration;
 
 /// Stagedsync header metrics
 #[derive(Metrics)]
@@ -28,3 +30,49 @@ impl HeaderMetrics {
         }
     }
 }
+
+/// Pipeline metrics of a single stage, labeled with the stage id.
+///
+/// The throughput of a stage is the rate of [StageMetrics::blocks_processed].
+#[derive(Debug)]
+pub(crate) struct StageMetrics {
+    /// Number of blocks the stage progressed over
+    blocks_processed: Counter,
+    /// Duration of a single execution of the stage in seconds
+    execution_duration: Histogram,
+    /// Number of blocks the stage unwound
+    blocks_unwound: Counter,
+    /// Number of errors the stage encountered during execution or unwinding
+    errors: Counter,
+}
+
+impl StageMetrics {
+    /// Register the metrics of the stage.
+    pub(crate) fn new(stage_id: StageId) -> Self {
+        Self {
+            blocks_processed: register_counter!("stage_blocks_processed", "stage" => stage_id.0),
+            execution_duration: register_histogram!(
+                "stage_execution_duration",
+                "stage" => stage_id.0
+            ),
+            blocks_unwound: register_counter!("stage_blocks_unwound", "stage" => stage_id.0),
+            errors: register_counter!("stage_errors", "stage" => stage_id.0),
+        }
+    }
+
+    /// Record a single execution of the stage that progressed from `from` to `to`.
+    pub(crate) fn record_execution(&self, from: u64, to: u64, elapsed: Duration) {
+        self.blocks_processed.increment(to.saturating_sub(from));
+        self.execution_duration.record(elapsed.as_secs_f64());
+    }
+
+    /// Record an unwind of the stage from `from` to `to`.
+    pub(crate) fn record_unwind(&self, from: u64, to: u64) {
+        self.blocks_unwound.increment(from.saturating_sub(to));
+    }
+
+    /// Record an error of the stage.
+    pub(crate) fn record_error(&self) {
+        self.errors.increment(1);
+    }
+}
@@ -1,15 +1,25 @@
 use crate::{
-    db::Transaction, error::*, util::opt::MaybeSender, ExecInput, ExecOutput, Stage, StageError,
-    StageId, UnwindInput,
+    db::Transaction,
+    error::*,
+    metrics::StageMetrics,
+    prune::Pruner,
+    util::{opt, opt::MaybeSender},
+    ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
+};
+use reth_db::{database::Database, tables, transaction::DbTx, Error as DbError};
+use reth_interfaces::consensus::ForkchoiceState;
+use reth_primitives::{BlockNumber, SealedHeader, H256};
+use reth_provider::{
+    canon_blocks, static_file::StaticFileProducer, CanonBlock, CanonStateNotification,
+    CanonStateNotificationSender, CanonStateNotifications, CanonicalHead, ChainInfoTracker,
 };
-use reth_db::{database::Database, transaction::DbTx};
-use reth_primitives::BlockNumber;
 use std::{
     fmt::{Debug, Formatter},
     ops::Deref,
     sync::Arc,
+    time::Instant,
 };
-use tokio::sync::mpsc::Sender;
+use tokio::sync::{broadcast, mpsc::Sender, watch};
 use tracing::*;
 
 mod ctrl;
@@ -29,7 +39,8 @@ use state::*;
 /// tip.
 ///
 /// After the entire pipeline has been run, it will run again unless asked to stop (see
-/// [Pipeline::set_max_block]).
+/// [Pipeline::set_max_block]). If the pipeline follows the fork choice of the consensus layer (see
+/// [Pipeline::set_tip_receiver]), it idles until a new tip is announced before running again.
 ///
 /// ```mermaid
 /// graph TB
@@ -63,6 +74,7 @@ use state::*;
 ///   UnwindStage --> |Unwound| NextStageToUnwind
 ///   LoopDone --> |Target block reached| Done
 ///   LoopDone --> |Target block not reached| RunLoop
+///   LoopDone --> |New tip announced| RunLoop
 /// ```
 ///
 /// # Unwinding
@@ -70,17 +82,59 @@ use state::*;
 /// In case of a validation error (as determined by the consensus engine) in one of the stages, the
 /// pipeline will unwind the stages in reverse order of execution. It is also possible to
 /// request an unwind manually (see [Pipeline::unwind]).
+///
+/// # Events and metrics
+///
+/// The progress of the stages is reported as [PipelineEvent]s, which are broadcast to all
+/// subscribers (see [Pipeline::events]). Additionally, the pipeline records the processed blocks,
+/// the execution duration, the unwound blocks and the errors of each stage as metrics labeled with
+/// the stage id.
+///
+/// # Canonical state notifications
+///
+/// The tip of the canonical chain is the lowest progress of all stages. Whenever it advances, the
+/// new blocks are announced with their receipts as a [CanonStateNotification::Commit], and
+/// whenever it is unwound, the removed blocks are announced as a
+/// [CanonStateNotification::Revert] (see [Pipeline::canon_state_notifications]).
+///
+/// If the pipeline has a [ChainInfoTracker] (see [Pipeline::set_chain_info_tracker]), the tracker
+/// is updated with the tip of the canonical chain and the safe and finalized blocks of the fork
+/// choice state whenever the canonical chain changes.
+///
+/// # Pruning
+///
+/// If the pipeline has a [Pruner] (see [Pipeline::set_pruner]), the historical data of old blocks
+/// is pruned after every pass in which all stages completed. Likewise, the finalized blocks are
+/// moved to static files if the pipeline has a [StaticFileProducer] (see
+/// [Pipeline::set_static_file_producer]).
 // ANCHOR: struct-Pipeline
 pub struct Pipeline<DB: Database> {
     stages: Vec<QueuedStage<DB>>,
     max_block: Option<BlockNumber>,
     events_sender: MaybeSender<PipelineEvent>,
+    tip_receiver: Option<watch::Receiver<ForkchoiceState>>,
+    pruner: Option<Pruner>,
+    static_file_producer: Option<StaticFileProducer>,
+    canon_state_sender: CanonStateNotificationSender,
+    chain_info: Option<ChainInfoTracker>,
 }
 // ANCHOR_END: struct-Pipeline
 
+/// The capacity of the broadcast channel of the [CanonStateNotification]s.
+const CANON_STATE_NOTIFICATION_CAPACITY: usize = 256;
+
 impl<DB: Database> Default for Pipeline<DB> {
     fn default() -> Self {
-        Self { stages: Vec::new(), max_block: None, events_sender: MaybeSender::new(None) }
+        Self {
+            stages: Vec::new(),
+            max_block: None,
+            events_sender: MaybeSender::new(None),
+            tip_receiver: None,
+            pruner: None,
+            static_file_producer: None,
+            canon_state_sender: broadcast::channel(CANON_STATE_NOTIFICATION_CAPACITY).0,
+            chain_info: None,
+        }
     }
 }
 impl<DB: Database> Debug for Pipeline<DB> {
@@ -105,7 +159,8 @@ impl<DB: Database> Pipeline<DB> {
     where
         S: Stage<DB> + 'static,
     {
-        self.stages.push(QueuedStage { stage: Box::new(stage) });
+        let metrics = StageMetrics::new(stage.id());
+        self.stages.push(QueuedStage { stage: Box::new(stage), metrics });
         self
     }
 
@@ -117,37 +172,145 @@ impl<DB: Database> Pipeline<DB> {
         self
     }
 
+    /// Set the receiver of the fork choice state announced by the consensus layer.
+    ///
+    /// Once all stages reached the tip, the pipeline idles until the head of the fork choice state
+    /// changes and then runs again to sync up to the new tip. The pipeline stops if the sender is
+    /// dropped. Without a receiver, the pipeline starts the next run immediately.
+    pub fn set_tip_receiver(mut self, receiver: watch::Receiver<ForkchoiceState>) -> Self {
+        self.tip_receiver = Some(receiver);
+        self
+    }
+
+    /// Set the pruner that runs after every pass of the pipeline.
+    pub fn set_pruner(mut self, pruner: Pruner) -> Self {
+        self.pruner = Some(pruner);
+        self
+    }
+
+    /// Set the producer that moves the finalized blocks to static files after every pass of the
+    /// pipeline.
+    pub fn set_static_file_producer(mut self, producer: StaticFileProducer) -> Self {
+        self.static_file_producer = Some(producer);
+        self
+    }
+
+    /// Set the tracker that is updated with the head, safe and finalized blocks of the canonical
+    /// chain.
+    pub fn set_chain_info_tracker(mut self, chain_info: ChainInfoTracker) -> Self {
+        self.chain_info = Some(chain_info);
+        self
+    }
+
     /// Set a channel the pipeline will transmit events over (see [PipelineEvent]).
+    ///
+    /// Unlike [Pipeline::events], the channel receives every event, and the pipeline waits for
+    /// capacity if the channel is full.
     pub fn set_channel(mut self, sender: Sender<PipelineEvent>) -> Self {
         self.events_sender.set(Some(sender));
         self
     }
 
+    /// Subscribe to the events of the pipeline (see [PipelineEvent]).
+    ///
+    /// The receiver gets all events that are emitted after subscribing. A receiver that falls too
+    /// far behind misses events instead of blocking the pipeline.
+    pub fn events(&self) -> broadcast::Receiver<PipelineEvent> {
+        self.events_sender.subscribe()
+    }
+
+    /// Subscribe to the changes of the canonical chain (see [CanonStateNotification]).
+    ///
+    /// The blocks are only read from the database while there are subscribers. A receiver that
+    /// falls too far behind misses notifications instead of blocking the pipeline.
+    pub fn canon_state_notifications(&self) -> CanonStateNotifications {
+        self.canon_state_sender.subscribe()
+    }
+
     /// Run the pipeline in an infinite loop. Will terminate early if the user has specified
-    /// a `max_block` in the pipeline.
+    /// a `max_block` in the pipeline, or if the sender of the tip receiver is dropped.
     pub async fn run(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
+        self.update_chain_info(db.as_ref())?;
         loop {
-            let mut state = PipelineState {
-                events_sender: self.events_sender.clone(),
-                max_block: self.max_block,
-                maximum_progress: None,
-                minimum_progress: None,
-            };
-            let next_action = self.run_loop(&mut state, db.as_ref()).await?;
+            // Tips announced from here on are synced by this run
+            if let Some(tip_receiver) = self.tip_receiver.as_mut() {
+                tip_receiver.borrow_and_update();
+            }
+
+            let (next_action, minimum_progress) = self.run_pass(db.as_ref()).await?;
 
             // Terminate the loop early if it's reached the maximum user
             // configured block.
             if matches!(next_action, ControlFlow::Continue) &&
-                state
-                    .minimum_progress
+                minimum_progress
                     .zip(self.max_block)
                     .map_or(false, |(progress, target)| progress >= target)
             {
                 return Ok(())
             }
+
+            if let (ControlFlow::Continue, Some(tip_receiver)) =
+                (next_action, self.tip_receiver.as_mut())
+            {
+                info!(target: "sync::pipeline", "Reached the tip, waiting for a new tip");
+                if !next_tip(tip_receiver).await {
+                    info!(target: "sync::pipeline", "Tip sender dropped, stopping");
+                    return Ok(())
+                }
+            }
         }
     }
 
+    /// Run the pipeline until all stages reached the target block, regardless of the configured
+    /// maximum block and tip receiver.
+    ///
+    /// This is used to process blocks that were committed to the database by another component,
+    /// e.g. the [BlockchainTree][crate::tree::BlockchainTree].
+    pub async fn run_to(&mut self, db: Arc<DB>, target: BlockNumber) -> Result<(), PipelineError> {
+        let max_block = self.max_block.replace(target);
+        let tip_receiver = self.tip_receiver.take();
+        let result = self.run(db).await;
+        self.max_block = max_block;
+        self.tip_receiver = tip_receiver;
+        result
+    }
+
+    /// Run a single pass of the pipeline across all stages, regardless of the tip receiver.
+    ///
+    /// This is used by components that decide themselves when the pipeline should sync, e.g. the
+    /// beacon consensus engine, which announces the head of the fork choice to the header stage
+    /// before each pass.
+    pub async fn run_once(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
+        self.update_chain_info(db.as_ref())?;
+        self.run_pass(db.as_ref()).await?;
+        Ok(())
+    }
+
+    /// Performs one pass of the pipeline and, if no stage requested an unwind, announces the new
+    /// canonical blocks and prunes.
+    ///
+    /// Returns the next action and the lowest progress of all stages.
+    async fn run_pass(
+        &mut self,
+        db: &DB,
+    ) -> Result<(ControlFlow, Option<BlockNumber>), PipelineError> {
+        let mut state = PipelineState {
+            events_sender: self.events_sender.clone(),
+            max_block: self.max_block,
+            maximum_progress: None,
+            minimum_progress: None,
+        };
+        let canonical_tip = self.read_canonical_tip(db)?;
+        let next_action = self.run_loop(&mut state, db).await?;
+
+        if matches!(next_action, ControlFlow::Continue) {
+            self.notify_commit(db, canonical_tip)?;
+            self.update_chain_info(db)?;
+            self.prune(db)?;
+        }
+        Ok((next_action, state.minimum_progress))
+    }
+
     /// Performs one pass of the pipeline across all stages. After successful
     /// execution of each stage, it proceeds to commit it to the database.
     ///
@@ -190,6 +353,105 @@ impl<DB: Database> Pipeline<DB> {
         Ok(ControlFlow::Continue)
     }
 
+    /// Prune the historical data and move the finalized blocks to static files, up to the lowest
+    /// progress of all stages.
+    fn prune(&mut self, db: &DB) -> Result<(), PipelineError> {
+        if self.pruner.is_none() && self.static_file_producer.is_none() {
+            return Ok(())
+        }
+
+        let mut tx = Transaction::new(db)?;
+        let tip = match canonical_tip(&self.stages, tx.deref())? {
+            Some(tip) => tip,
+            None => return Ok(()),
+        };
+
+        if let Some(pruner) = self.pruner.as_mut() {
+            pruner.run(&tx, tip)?;
+        }
+        if let Some(producer) = &self.static_file_producer {
+            producer.run(tx.deref(), tip).map_err(|err| PipelineError::Internal(Box::new(err)))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the tip of the canonical chain, which is the lowest progress of all stages.
+    fn read_canonical_tip(&self, db: &DB) -> Result<Option<BlockNumber>, PipelineError> {
+        let tx = db.tx()?;
+        let tip = canonical_tip(&self.stages, &tx)?;
+        tx.commit()?;
+        Ok(tip)
+    }
+
+    /// Announces the blocks after the previous tip of the canonical chain up to the current tip.
+    pub(crate) fn notify_commit(
+        &self,
+        db: &DB,
+        previous_tip: Option<BlockNumber>,
+    ) -> Result<(), PipelineError> {
+        if self.canon_state_sender.receiver_count() == 0 {
+            return Ok(())
+        }
+
+        let tx = db.tx()?;
+        let tip = canonical_tip(&self.stages, &tx)?;
+        if let (Some(previous_tip), Some(tip)) = (previous_tip, tip) {
+            if tip > previous_tip {
+                let new = canon_blocks(&tx, previous_tip + 1..=tip)
+                    .map_err(|err| PipelineError::Internal(Box::new(err)))?;
+                // there might be no subscribers anymore, which is fine
+                let _ = self
+                    .canon_state_sender
+                    .send(CanonStateNotification::Commit { new: Arc::new(new) });
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Updates the [ChainInfoTracker] with the tip of the canonical chain and, if the pipeline
+    /// follows the fork choice state, with its safe and finalized blocks.
+    fn update_chain_info(&self, db: &DB) -> Result<(), PipelineError> {
+        let chain_info = match &self.chain_info {
+            Some(chain_info) => chain_info,
+            None => return Ok(()),
+        };
+
+        let tx = db.tx()?;
+        let tip = match canonical_tip(&self.stages, &tx)? {
+            Some(tip) => tip,
+            None => return Ok(()),
+        };
+        // the header of the tip is never moved to static files
+        if let Some(hash) = tx.get::<tables::CanonicalHeaders>(tip)? {
+            let key = (tip, hash).into();
+            if let (Some(header), Some(total_difficulty)) =
+                (tx.get::<tables::Headers>(key)?, tx.get::<tables::HeaderTD>(key)?)
+            {
+                chain_info.set_canonical_head(CanonicalHead {
+                    header: SealedHeader::new(header, hash),
+                    total_difficulty: total_difficulty.0,
+                });
+            }
+        }
+
+        if let Some(tip_receiver) = &self.tip_receiver {
+            let fork_choice = tip_receiver.borrow().clone();
+            let safe = fork_choice.safe_block_hash;
+            chain_info.set_safe_block(fork_choice_header(&tx, safe, tip, chain_info.safe_block())?);
+            let finalized = fork_choice.finalized_block_hash;
+            chain_info.set_finalized_block(fork_choice_header(
+                &tx,
+                finalized,
+                tip,
+                chain_info.finalized_block(),
+            )?);
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Unwind the stages to the target block.
     ///
     /// If the unwind is due to a bad block the number of that block should be specified.
@@ -199,12 +461,39 @@ impl<DB: Database> Pipeline<DB> {
         to: BlockNumber,
         bad_block: Option<BlockNumber>,
     ) -> Result<(), PipelineError> {
+        let mut tx = Transaction::new(db)?;
+        if let Some(unwound) = self.unwind_in(&mut tx, to, bad_block).await? {
+            tx.commit()?;
+            self.notify_unwound(unwound);
+            self.update_chain_info(db)?;
+        }
+        Ok(())
+    }
+
+    /// Unwind the stages to the target block within the transaction, without committing it.
+    ///
+    /// Returns `None` if a stage is behind the target block, in which case the transaction must
+    /// not be committed. Otherwise the unwound blocks are returned, which are announced with
+    /// [Pipeline::notify_unwound] once the transaction is committed.
+    pub(crate) async fn unwind_in(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        to: BlockNumber,
+        bad_block: Option<BlockNumber>,
+    ) -> Result<Option<Unwound>, PipelineError> {
+        // read the blocks that are removed from the canonical chain before they are unwound
+        let reverted = match canonical_tip(&self.stages, tx.deref())? {
+            Some(tip) if tip > to && self.canon_state_sender.receiver_count() > 0 => Some(
+                canon_blocks(tx.deref(), to + 1..=tip)
+                    .map_err(|err| PipelineError::Internal(Box::new(err)))?,
+            ),
+            _ => None,
+        };
+
         // Unwind stages in reverse order of execution
         let unwind_pipeline = self.stages.iter_mut().rev();
 
-        let mut tx = Transaction::new(db)?;
-
-        for QueuedStage { stage, .. } in unwind_pipeline {
+        for QueuedStage { stage, metrics } in unwind_pipeline {
             let stage_id = stage.id();
             let span = info_span!("Unwinding", stage = %stage_id);
             let _enter = span.enter();
@@ -213,7 +502,7 @@ impl<DB: Database> Pipeline<DB> {
             if stage_progress < to {
                 debug!(from = %stage_progress, %to, "Unwind point too far for stage");
                 self.events_sender.send(PipelineEvent::Skipped { stage_id }).await?;
-                return Ok(())
+                return Ok(None)
             }
 
             debug!(from = %stage_progress, %to, ?bad_block, "Starting unwind");
@@ -221,9 +510,10 @@ impl<DB: Database> Pipeline<DB> {
                 let input = UnwindInput { stage_progress, unwind_to: to, bad_block };
                 self.events_sender.send(PipelineEvent::Unwinding { stage_id, input }).await?;
 
-                let output = stage.unwind(&mut tx, input).await;
+                let output = stage.unwind(tx, input).await;
                 match output {
                     Ok(unwind_output) => {
+                        metrics.record_unwind(stage_progress, unwind_output.stage_progress);
                         stage_progress = unwind_output.stage_progress;
                         stage_id.save_progress(tx.deref(), stage_progress)?;
 
@@ -232,6 +522,7 @@ impl<DB: Database> Pipeline<DB> {
                             .await?;
                     }
                     Err(err) => {
+                        metrics.record_error();
                         self.events_sender.send(PipelineEvent::Error { stage_id }).await?;
                         return Err(PipelineError::Stage(StageError::Fatal(err)))
                     }
@@ -239,8 +530,78 @@ impl<DB: Database> Pipeline<DB> {
             }
         }
 
-        tx.commit()?;
-        Ok(())
+        Ok(Some(Unwound(reverted)))
+    }
+
+    /// Announces the blocks that were removed from the canonical chain by a committed unwind.
+    pub(crate) fn notify_unwound(&self, unwound: Unwound) {
+        if let Unwound(Some(old)) = unwound {
+            // there might be no subscribers anymore, which is fine
+            let _ =
+                self.canon_state_sender.send(CanonStateNotification::Revert { old: Arc::new(old) });
+        }
+    }
+}
+
+/// The blocks that were removed from the canonical chain by [Pipeline::unwind_in], `None` if
+/// there are no subscribers to announce them to.
+#[derive(Debug)]
+#[must_use = "the unwound blocks must be announced once the unwind is committed"]
+pub(crate) struct Unwound(Option<Vec<CanonBlock>>);
+
+/// Returns the tip of the canonical chain, which is the lowest progress of all stages, or `None`
+/// if there are no stages.
+fn canonical_tip<'a, DB: Database, TX: DbTx<'a>>(
+    stages: &[QueuedStage<DB>],
+    tx: &TX,
+) -> Result<Option<BlockNumber>, PipelineError> {
+    let mut tip = None;
+    for QueuedStage { stage, .. } in stages {
+        tip = opt::min(tip, stage.id().get_progress(tx)?.unwrap_or_default());
+    }
+    Ok(tip)
+}
+
+/// Returns the header of a block of the fork choice state if it is canonical and not after the
+/// tip.
+///
+/// The header of the block that is tracked already is reused, since it might have been moved to
+/// static files in the meantime.
+fn fork_choice_header<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    hash: H256,
+    tip: BlockNumber,
+    tracked: Option<SealedHeader>,
+) -> Result<Option<SealedHeader>, DbError> {
+    if hash.is_zero() {
+        return Ok(None)
+    }
+    if let Some(tracked) = tracked.filter(|tracked| tracked.hash() == hash && tracked.number <= tip)
+    {
+        return Ok(Some(tracked))
+    }
+    let number = match tx.get::<tables::HeaderNumbers>(hash)? {
+        Some(number) if number <= tip => number,
+        _ => return Ok(None),
+    };
+    if tx.get::<tables::CanonicalHeaders>(number)? != Some(hash) {
+        return Ok(None)
+    }
+    let header = tx.get::<tables::Headers>((number, hash).into())?;
+    Ok(header.map(|header| SealedHeader::new(header, hash)))
+}
+
+/// Waits until the head of the fork choice state changes to a non-zero hash.
+///
+/// Returns `false` if the sender was dropped.
+async fn next_tip(receiver: &mut watch::Receiver<ForkchoiceState>) -> bool {
+    loop {
+        if receiver.changed().await.is_err() {
+            return false
+        }
+        if !receiver.borrow().head_block_hash.is_zero() {
+            return true
+        }
     }
 }
 
@@ -248,6 +609,8 @@ impl<DB: Database> Pipeline<DB> {
 struct QueuedStage<DB: Database> {
     /// The actual stage to execute.
     stage: Box<dyn Stage<DB>>,
+    /// The metrics of the stage.
+    metrics: StageMetrics,
 }
 
 impl<DB: Database> QueuedStage<DB> {
@@ -284,19 +647,27 @@ impl<DB: Database> QueuedStage<DB> {
                 .send(PipelineEvent::Running { stage_id, stage_progress: prev_progress })
                 .await?;
 
+            let started_at = Instant::now();
             match self
                 .stage
                 .execute(&mut tx, ExecInput { previous_stage, stage_progress: prev_progress })
                 .await
             {
                 Ok(out @ ExecOutput { stage_progress, done }) => {
+                    let elapsed = started_at.elapsed();
                     info!(
                         target: "sync::pipeline",
                         stage = %stage_id,
                         %stage_progress,
                         %done,
+                        ?elapsed,
                         "Stage made progress"
                     );
+                    self.metrics.record_execution(
+                        prev_progress.unwrap_or_default(),
+                        stage_progress,
+                        elapsed,
+                    );
                     stage_id.save_progress(tx.deref(), stage_progress)?;
 
                     state
@@ -314,6 +685,7 @@ impl<DB: Database> QueuedStage<DB> {
                     }
                 }
                 Err(err) => {
+                    self.metrics.record_error();
                     state.events_sender.send(PipelineEvent::Error { stage_id }).await?;
 
                     return if let StageError::Validation { block, error } = err {
@@ -360,7 +732,10 @@ mod tests {
     use crate::{StageId, UnwindOutput};
     use assert_matches::assert_matches;
     use reth_db::mdbx::{self, test_utils, Env, EnvKind, WriteMap};
-    use reth_interfaces::consensus;
+    use reth_db::{tables, transaction::DbTxMut};
+    use reth_interfaces::{consensus, test_utils::generators::random_block_range};
+    use reth_primitives::{Receipt, H256, U256};
+    use reth_provider::{insert_canonical_block, CanonBlock};
     use tokio::sync::mpsc::channel;
     use tokio_stream::{wrappers::ReceiverStream, StreamExt};
     use utils::TestStage;
@@ -405,6 +780,177 @@ mod tests {
         );
     }
 
+    /// Broadcasts the events of a pipeline to all subscribers.
+    #[tokio::test]
+    async fn subscribe_to_events() {
+        let db = test_utils::create_test_db(EnvKind::RW);
+
+        let mut pipeline = Pipeline::<Env<WriteMap>>::new()
+            .push(
+                TestStage::new(StageId("A"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 10, done: true })),
+            )
+            .set_max_block(Some(10));
+        let (mut first, mut second) = (pipeline.events(), pipeline.events());
+
+        // Run pipeline
+        tokio::spawn(async move { pipeline.run(db).await });
+
+        let expected = vec![
+            PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None },
+            PipelineEvent::Ran {
+                stage_id: StageId("A"),
+                result: ExecOutput { stage_progress: 10, done: true },
+            },
+        ];
+        for rx in [&mut first, &mut second] {
+            let mut events = Vec::new();
+            while let Ok(event) = rx.recv().await {
+                events.push(event);
+            }
+            assert_eq!(events, expected);
+        }
+    }
+
+    /// Announces the blocks that are added to and removed from the canonical chain.
+    #[tokio::test]
+    async fn notify_canon_state_changes() {
+        let db = test_utils::create_test_db(EnvKind::RW);
+        let blocks = random_block_range(0..3, H256::zero(), 0..3);
+        let tx = db.tx_mut().unwrap();
+        for block in &blocks {
+            insert_canonical_block(&tx, block, None, false).unwrap();
+        }
+        let tx_count = blocks.iter().map(|block| block.body.len() as u64).sum::<u64>();
+        for id in 0..tx_count {
+            tx.put::<tables::Receipts>(id, Receipt::default()).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let mut pipeline = Pipeline::<Env<WriteMap>>::new()
+            .push(
+                TestStage::new(StageId("A"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 2, done: true }))
+                    .add_unwind(Ok(UnwindOutput { stage_progress: 1 })),
+            )
+            .set_max_block(Some(2));
+        let mut notifications = pipeline.canon_state_notifications();
+
+        pipeline.run(db.clone()).await.expect("Could not run pipeline");
+        pipeline.unwind(&db, 1, None).await.expect("Could not unwind pipeline");
+
+        let hashes = |blocks: &[CanonBlock]| {
+            blocks.iter().map(|canon| canon.block.hash()).collect::<Vec<_>>()
+        };
+        let commit = notifications.recv().await.unwrap();
+        assert_eq!(hashes(commit.committed()), vec![blocks[1].hash(), blocks[2].hash()]);
+        assert_eq!(commit.committed()[1].block, blocks[2]);
+        assert_eq!(commit.committed()[1].receipts.len(), blocks[2].body.len());
+
+        let revert = notifications.recv().await.unwrap();
+        assert_eq!(hashes(revert.reverted()), vec![blocks[2].hash()]);
+    }
+
+    /// Updates the chain info tracker with the tip and the canonical blocks of the fork choice.
+    #[tokio::test]
+    async fn track_chain_info() {
+        let db = test_utils::create_test_db(EnvKind::RW);
+        let blocks = random_block_range(0..3, H256::zero(), 0..3);
+        let tx = db.tx_mut().unwrap();
+        for block in &blocks {
+            insert_canonical_block(&tx, block, None, false).unwrap();
+            let td = U256::from(block.number);
+            tx.put::<tables::HeaderTD>((block.number, block.hash()).into(), td.into()).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let (_tip_tx, tip_rx) = watch::channel(ForkchoiceState {
+            head_block_hash: blocks[2].hash(),
+            safe_block_hash: blocks[2].hash(),
+            finalized_block_hash: blocks[1].hash(),
+        });
+        let chain_info = ChainInfoTracker::default();
+        let mut pipeline = Pipeline::<Env<WriteMap>>::new()
+            .push(
+                TestStage::new(StageId("A"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 1, done: true }))
+                    .add_unwind(Ok(UnwindOutput { stage_progress: 0 })),
+            )
+            .set_max_block(Some(1))
+            .set_tip_receiver(tip_rx)
+            .set_chain_info_tracker(chain_info.clone());
+
+        // the safe block is not canonical yet
+        pipeline.run(db.clone()).await.expect("Could not run pipeline");
+        let head = chain_info.canonical_head();
+        assert_eq!((head.header, head.total_difficulty), (blocks[1].header.clone(), U256::from(1)));
+        assert_eq!(chain_info.safe_block(), None);
+        assert_eq!(chain_info.finalized_block(), Some(blocks[1].header.clone()));
+
+        pipeline.unwind(&db, 0, None).await.expect("Could not unwind pipeline");
+        assert_eq!(chain_info.canonical_head().header, blocks[0].header);
+        assert_eq!(chain_info.finalized_block(), None);
+    }
+
+    /// Runs the pipeline again whenever a new tip is announced.
+    #[tokio::test]
+    async fn run_pipeline_on_new_tip() {
+        let (tx, mut rx) = channel(2);
+        let (tip_tx, tip_rx) = watch::channel(ForkchoiceState::default());
+        let db = test_utils::create_test_db(EnvKind::RW);
+
+        // Run pipeline
+        let handle = tokio::spawn(async move {
+            Pipeline::<Env<WriteMap>>::new_with_channel(tx)
+                .push(
+                    TestStage::new(StageId("A"))
+                        .add_exec(Ok(ExecOutput { stage_progress: 10, done: true }))
+                        .add_exec(Ok(ExecOutput { stage_progress: 20, done: true })),
+                )
+                .set_tip_receiver(tip_rx)
+                .run(db)
+                .await
+        });
+
+        assert_eq!(
+            rx.recv().await,
+            Some(PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None })
+        );
+        assert_eq!(
+            rx.recv().await,
+            Some(PipelineEvent::Ran {
+                stage_id: StageId("A"),
+                result: ExecOutput { stage_progress: 10, done: true },
+            })
+        );
+
+        // Announce a new tip
+        let tip = H256::from_low_u64_be(1);
+        tip_tx
+            .send(ForkchoiceState {
+                head_block_hash: tip,
+                safe_block_hash: tip,
+                finalized_block_hash: tip,
+            })
+            .unwrap();
+        assert_eq!(
+            rx.recv().await,
+            Some(PipelineEvent::Running { stage_id: StageId("A"), stage_progress: Some(10) })
+        );
+        assert_eq!(
+            rx.recv().await,
+            Some(PipelineEvent::Ran {
+                stage_id: StageId("A"),
+                result: ExecOutput { stage_progress: 20, done: true },
+            })
+        );
+
+        // The pipeline stops once the tip sender is dropped
+        drop(tip_tx);
+        assert_matches!(handle.await.unwrap(), Ok(()));
+        assert_eq!(rx.recv().await, None);
+    }
+
     /// Unwinds a simple pipeline.
     #[tokio::test]
     async fn unwind_pipeline() {
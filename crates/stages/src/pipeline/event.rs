@@ -6,6 +6,9 @@ use reth_primitives::BlockNumber;
 
 /// An event emitted by a [Pipeline][crate::Pipeline].
 ///
+/// Events can be received by subscribing to the pipeline (see
+/// [Pipeline::events][crate::Pipeline::events]).
+///
 /// It is possible for multiple of these events to be emitted over the duration of a pipeline's
 /// execution since:
 ///
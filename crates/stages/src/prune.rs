@@ -0,0 +1,356 @@
+use crate::{db::Transaction, DatabaseIntegrityError, StageError};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::TransitionIdAddress,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_primitives::{Address, BlockNumber, TransitionId, TxNumber};
+use reth_provider::static_file::StaticFileProvider;
+use std::{
+    fmt::Display,
+    ops::{Deref, Range, RangeInclusive},
+    sync::Arc,
+};
+use tracing::*;
+
+/// A segment of the historical data that can be pruned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PruneSegment {
+    /// The [`Receipts`][tables::Receipts] and [`Logs`][tables::Logs] of the transactions.
+    Receipts,
+    /// The transaction hash index in [`TxHashNumber`][tables::TxHashNumber].
+    TransactionLookup,
+    /// The recovered senders in [`TxSenders`][tables::TxSenders].
+    SenderRecovery,
+    /// The account changesets in [`AccountChangeSet`][tables::AccountChangeSet].
+    AccountHistory,
+    /// The storage changesets in [`StorageChangeSet`][tables::StorageChangeSet].
+    StorageHistory,
+}
+
+impl PruneSegment {
+    /// All segments in the order they are pruned.
+    pub const ALL: [PruneSegment; 5] = [
+        PruneSegment::Receipts,
+        PruneSegment::TransactionLookup,
+        PruneSegment::SenderRecovery,
+        PruneSegment::AccountHistory,
+        PruneSegment::StorageHistory,
+    ];
+
+    /// The id of the segment.
+    pub fn id(&self) -> &'static str {
+        match self {
+            PruneSegment::Receipts => "Receipts",
+            PruneSegment::TransactionLookup => "TransactionLookup",
+            PruneSegment::SenderRecovery => "SenderRecovery",
+            PruneSegment::AccountHistory => "AccountHistory",
+            PruneSegment::StorageHistory => "StorageHistory",
+        }
+    }
+
+    /// Get the highest block whose data of this segment was pruned.
+    pub fn get_checkpoint<'db>(&self, tx: &impl DbTx<'db>) -> Result<Option<BlockNumber>, DbError> {
+        tx.get::<tables::PruneCheckpoints>(self.id().as_bytes().to_vec())
+    }
+
+    /// Save the highest block whose data of this segment was pruned.
+    pub fn save_checkpoint<'db>(
+        &self,
+        tx: &impl DbTxMut<'db>,
+        block: BlockNumber,
+    ) -> Result<(), DbError> {
+        tx.put::<tables::PruneCheckpoints>(self.id().as_bytes().to_vec(), block)
+    }
+}
+
+impl Display for PruneSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+/// The retention of each [PruneSegment], as the number of most recent blocks whose data is kept.
+///
+/// Segments without a retention are never pruned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneModes {
+    /// The retention of the receipts.
+    pub receipts: Option<u64>,
+    /// The retention of the transaction hash index.
+    pub transaction_lookup: Option<u64>,
+    /// The retention of the recovered senders.
+    pub sender_recovery: Option<u64>,
+    /// The retention of the account changesets.
+    pub account_history: Option<u64>,
+    /// The retention of the storage changesets.
+    pub storage_history: Option<u64>,
+}
+
+impl PruneModes {
+    /// Get the retention of the segment.
+    pub fn retention(&self, segment: PruneSegment) -> Option<u64> {
+        match segment {
+            PruneSegment::Receipts => self.receipts,
+            PruneSegment::TransactionLookup => self.transaction_lookup,
+            PruneSegment::SenderRecovery => self.sender_recovery,
+            PruneSegment::AccountHistory => self.account_history,
+            PruneSegment::StorageHistory => self.storage_history,
+        }
+    }
+}
+
+/// The pruner deletes the historical data of old blocks according to the [PruneModes].
+///
+/// It runs after every pass of the [Pipeline][crate::Pipeline] (see
+/// [Pipeline::set_pruner][crate::Pipeline::set_pruner]). Each segment is pruned up to and including
+/// the block `retention` blocks below the tip, and the highest pruned block is saved as the
+/// checkpoint of the segment, so that the next run only prunes the blocks synced since.
+///
+/// The stages can't be unwound below the checkpoints of the account and storage history, so their
+/// retention should exceed the depth of any expected reorg.
+///
+/// Data that was moved to static files is not pruned.
+#[derive(Debug)]
+pub struct Pruner {
+    modes: PruneModes,
+    /// The static files with the transactions that were moved out of the database.
+    static_files: Option<Arc<StaticFileProvider>>,
+}
+
+impl Pruner {
+    /// Create a new pruner with the retention of each segment.
+    pub fn new(modes: PruneModes) -> Self {
+        Self { modes, static_files: None }
+    }
+
+    /// Look up the hashes of the transactions that were moved out of the database in the static
+    /// files.
+    pub fn with_static_files(mut self, static_files: Arc<StaticFileProvider>) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Prune all segments relative to the `tip`, which must have been reached by all stages.
+    pub fn run<DB: Database>(
+        &mut self,
+        tx: &Transaction<'_, DB>,
+        tip: BlockNumber,
+    ) -> Result<(), StageError> {
+        for segment in PruneSegment::ALL {
+            let target = match self.modes.retention(segment).and_then(|r| tip.checked_sub(r)) {
+                Some(target) => target,
+                None => continue,
+            };
+            let from = segment.get_checkpoint(tx.deref())?.map_or(0, |checkpoint| checkpoint + 1);
+            if from > target {
+                continue
+            }
+
+            let blocks = from..=target;
+            match segment {
+                PruneSegment::Receipts => {
+                    for tx_number in tx_range(tx, &blocks)? {
+                        tx.delete::<tables::Receipts>(tx_number, None)?;
+                        tx.delete::<tables::Logs>(tx_number, None)?;
+                    }
+                }
+                PruneSegment::TransactionLookup => {
+                    for tx_number in tx_range(tx, &blocks)? {
+                        // moved transactions are read from the static files
+                        let transaction = match tx.get::<tables::Transactions>(tx_number)? {
+                            Some(transaction) => Some(transaction),
+                            None => match &self.static_files {
+                                Some(static_files) => static_files
+                                    .transaction(tx_number)
+                                    .map_err(|err| StageError::Fatal(Box::new(err)))?,
+                                None => None,
+                            },
+                        }
+                        .ok_or(DatabaseIntegrityError::Transaction { id: tx_number })?;
+                        tx.delete::<tables::TxHashNumber>(transaction.hash, None)?;
+                    }
+                }
+                PruneSegment::SenderRecovery => {
+                    for tx_number in tx_range(tx, &blocks)? {
+                        tx.delete::<tables::TxSenders>(tx_number, None)?;
+                    }
+                }
+                PruneSegment::AccountHistory => {
+                    for transition_id in transition_range(tx, &blocks)? {
+                        tx.delete::<tables::AccountChangeSet>(transition_id, None)?;
+                    }
+                }
+                PruneSegment::StorageHistory => {
+                    let transitions = transition_range(tx, &blocks)?;
+                    let start = TransitionIdAddress((*transitions.start(), Address::zero()));
+
+                    // Collect the keys first, deleting a key removes all of its entries
+                    let mut keys: Vec<TransitionIdAddress> = Vec::new();
+                    let mut cursor = tx.cursor::<tables::StorageChangeSet>()?;
+                    let mut walker = cursor.walk(start)?;
+                    while let Some((key, _)) = walker.next().transpose()? {
+                        if key.transition_id() > *transitions.end() {
+                            break
+                        }
+                        if keys.last() != Some(&key) {
+                            keys.push(key);
+                        }
+                    }
+                    for key in keys {
+                        tx.delete::<tables::StorageChangeSet>(key, None)?;
+                    }
+                }
+            }
+
+            segment.save_checkpoint(tx.deref(), target)?;
+            info!(target: "sync::prune", %segment, from, to = target, "Segment pruned");
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the numbers of the transactions in the block range.
+fn tx_range<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: &RangeInclusive<BlockNumber>,
+) -> Result<Range<TxNumber>, StageError> {
+    let first = tx.get_block_body_by_num(*blocks.start())?;
+    let last = tx.get_block_body_by_num(*blocks.end())?;
+    Ok(first.start_tx_id..last.start_tx_id + last.tx_count)
+}
+
+/// Get the state transitions of the block range.
+fn transition_range<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: &RangeInclusive<BlockNumber>,
+) -> Result<RangeInclusive<TransitionId>, StageError> {
+    let (_, first) = tx.get_next_block_ids(*blocks.start())?;
+    let last = tx.get_block_transition_by_num(*blocks.end())?;
+    Ok(first..=last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestTransaction;
+    use reth_db::models::{AccountBeforeTx, StoredBlockBody, StoredTxLocation};
+    use reth_interfaces::test_utils::generators::random_signed_tx;
+    use reth_primitives::{StorageEntry, H256, U256};
+
+    /// Inserts `blocks` blocks with two transactions and two transitions each, and the data of all
+    /// segments for them.
+    fn seed(tx: &TestTransaction, blocks: u64) {
+        tx.commit(|tx| {
+            for number in 0..blocks {
+                let hash = H256::from_low_u64_be(number);
+                tx.put::<tables::CanonicalHeaders>(number, hash)?;
+                tx.put::<tables::BlockBodies>(
+                    (number, hash).into(),
+                    StoredBlockBody { start_tx_id: number * 2, tx_count: 2 },
+                )?;
+                tx.put::<tables::BlockTransitionIndex>((number, hash).into(), number * 2 + 1)?;
+
+                for tx_number in number * 2..number * 2 + 2 {
+                    let mut transaction = random_signed_tx();
+                    transaction.hash = H256::from_low_u64_be(tx_number);
+                    tx.put::<tables::TxHashNumber>(
+                        transaction.hash,
+                        StoredTxLocation { tx_number, block_number: number },
+                    )?;
+                    tx.put::<tables::Transactions>(tx_number, transaction)?;
+                    tx.put::<tables::TxSenders>(tx_number, Address::zero())?;
+                    tx.put::<tables::Receipts>(tx_number, Default::default())?;
+
+                    // one transition per transaction
+                    let address = Address::from_low_u64_be(tx_number);
+                    tx.put::<tables::AccountChangeSet>(
+                        tx_number,
+                        AccountBeforeTx { address, info: None },
+                    )?;
+                    for slot in 0..2 {
+                        tx.put::<tables::StorageChangeSet>(
+                            (tx_number, address).into(),
+                            StorageEntry { key: H256::from_low_u64_be(slot), value: U256::from(1) },
+                        )?;
+                    }
+                }
+            }
+            Ok(())
+        })
+        .expect("failed to seed");
+    }
+
+    /// Prunes the segments with a retention and records their checkpoints.
+    #[test]
+    fn prune_segments() {
+        let tx = TestTransaction::default();
+        seed(&tx, 10);
+
+        let modes = PruneModes {
+            receipts: Some(5),
+            transaction_lookup: Some(3),
+            account_history: Some(2),
+            storage_history: Some(2),
+            ..Default::default()
+        };
+        let mut db_tx = tx.inner();
+        Pruner::new(modes).run(&db_tx, 9).expect("failed to prune");
+        db_tx.commit().unwrap();
+
+        tx.query(|tx| {
+            // blocks up to 4 are pruned, which includes the transactions up to 9
+            assert_eq!(PruneSegment::Receipts.get_checkpoint(tx)?, Some(4));
+            assert!(tx.get::<tables::Receipts>(9)?.is_none());
+            assert!(tx.get::<tables::Receipts>(10)?.is_some());
+
+            // blocks up to 6 are pruned, which includes the transactions up to 13
+            assert_eq!(PruneSegment::TransactionLookup.get_checkpoint(tx)?, Some(6));
+            assert!(tx.get::<tables::TxHashNumber>(H256::from_low_u64_be(13))?.is_none());
+            assert!(tx.get::<tables::TxHashNumber>(H256::from_low_u64_be(14))?.is_some());
+
+            // senders have no retention
+            assert_eq!(PruneSegment::SenderRecovery.get_checkpoint(tx)?, None);
+            assert!(tx.get::<tables::TxSenders>(0)?.is_some());
+
+            // blocks up to 7 are pruned, which includes the transitions up to 15
+            assert_eq!(PruneSegment::AccountHistory.get_checkpoint(tx)?, Some(7));
+            assert_eq!(PruneSegment::StorageHistory.get_checkpoint(tx)?, Some(7));
+            assert!(tx.get::<tables::AccountChangeSet>(15)?.is_none());
+            assert!(tx.get::<tables::AccountChangeSet>(16)?.is_some());
+            let mut cursor = tx.cursor::<tables::StorageChangeSet>()?;
+            let (first, _) = cursor.first()?.expect("storage changes after the target");
+            assert_eq!(first.transition_id(), 16);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    /// Only the blocks synced since the last run are pruned.
+    #[test]
+    fn prune_from_checkpoint() {
+        let tx = TestTransaction::default();
+        seed(&tx, 10);
+
+        let mut db_tx = tx.inner();
+        PruneSegment::SenderRecovery.save_checkpoint(db_tx.deref(), 2).unwrap();
+        Pruner::new(PruneModes { sender_recovery: Some(5), ..Default::default() })
+            .run(&db_tx, 9)
+            .expect("failed to prune");
+        db_tx.commit().unwrap();
+
+        tx.query(|tx| {
+            assert_eq!(PruneSegment::SenderRecovery.get_checkpoint(tx)?, Some(4));
+            // the blocks up to the checkpoint were not touched
+            assert!(tx.get::<tables::TxSenders>(5)?.is_some());
+            assert!(tx.get::<tables::TxSenders>(6)?.is_none());
+            assert!(tx.get::<tables::TxSenders>(10)?.is_some());
+            Ok(())
+        })
+        .unwrap();
+    }
+}
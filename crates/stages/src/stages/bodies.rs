@@ -6,7 +6,7 @@ use futures_util::StreamExt;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::{Database, DatabaseGAT},
-    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers},
+    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals},
     tables,
     transaction::{DbTx, DbTxMut},
 };
@@ -18,7 +18,8 @@ use reth_primitives::{BlockNumber, SealedHeader};
 use std::{fmt::Debug, sync::Arc};
 use tracing::*;
 
-const BODIES: StageId = StageId("Bodies");
+/// The id of the bodies stage.
+pub const BODIES: StageId = StageId("Bodies");
 
 // TODO(onbjerg): Metrics and events (gradual status for e.g. CLI)
 /// The body stage downloads block bodies.
@@ -39,8 +40,12 @@ const BODIES: StageId = StageId("Bodies");
 /// The bodies are processed and data is inserted into these tables:
 ///
 /// - [`BlockOmmers`][reth_interfaces::db::tables::BlockOmmers]
+/// - [`BlockWithdrawals`][reth_interfaces::db::tables::BlockWithdrawals], for blocks after
+///   Shanghai
 /// - [`Transactions`][reth_interfaces::db::tables::Transactions]
-/// - [`TransactionHashNumber`][reth_interfaces::db::tables::TransactionHashNumber]
+///
+/// The transaction hash index is built by the
+/// [`TransactionLookupStage`][crate::stages::tx_lookup::TransactionLookupStage].
 ///
 /// # Genesis
 ///
@@ -50,7 +55,6 @@ const BODIES: StageId = StageId("Bodies");
 /// - The [`BlockOmmers`][reth_interfaces::db::tables::BlockOmmers] table
 /// - The [`CumulativeTxCount`][reth_interfaces::db::tables::CumulativeTxCount] table
 /// - The [`Transactions`][reth_interfaces::db::tables::Transactions] table
-/// - The [`TransactionHashNumber`][reth_interfaces::db::tables::TransactionHashNumber] table
 #[derive(Debug)]
 pub struct BodyStage<D: BodyDownloader, C: Consensus> {
     /// The body downloader.
@@ -99,9 +103,10 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
 
         let bodies_to_download = self.bodies_to_download::<DB>(tx, starting_block, target)?;
 
-        // Cursors used to write bodies, ommers and transactions
+        // Cursors used to write bodies, ommers, withdrawals and transactions
         let mut body_cursor = tx.cursor_mut::<tables::BlockBodies>()?;
         let mut ommers_cursor = tx.cursor_mut::<tables::BlockOmmers>()?;
+        let mut withdrawals_cursor = tx.cursor_mut::<tables::BlockWithdrawals>()?;
         let mut tx_cursor = tx.cursor_mut::<tables::Transactions>()?;
 
         // Cursors used to write state transition mapping
@@ -128,6 +133,7 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
             // Write block
             let block_header = response.header();
             let numhash: BlockNumHash = block_header.num_hash().into();
+            let has_withdrawals = block_header.withdrawals_root.is_some();
 
             match response {
                 BlockResponse::Full(block) => {
@@ -150,11 +156,13 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
                                 .collect(),
                         },
                     )?;
+                    if let Some(withdrawals) = block.withdrawals {
+                        withdrawals_cursor
+                            .append(numhash, StoredBlockWithdrawals { withdrawals })?;
+                    }
 
                     // Write transactions
                     for transaction in block.body {
-                        // Insert the transaction hash to number mapping
-                        tx.put::<tables::TxHashNumber>(transaction.hash(), current_tx_id)?;
                         // Append the transaction
                         tx_cursor.append(current_tx_id, transaction)?;
                         tx_transition_cursor.append(current_tx_id, transition_id)?;
@@ -172,12 +180,12 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
             };
 
             // The block transition marks the final state at the end of the block.
-            // Increment the transition if the block contains an addition block reward.
-            // If the block does not have a reward, the transition will be the same as the
-            // transition at the last transaction of this block.
+            // Increment the transition if the block contains an addition block reward or
+            // withdrawals. Otherwise, the transition will be the same as the transition at the last
+            // transaction of this block.
             let has_reward = self.consensus.has_block_reward(numhash.number());
-            trace!(target: "sync::stages::bodies", has_reward, ?numhash, "Block reward");
-            if has_reward {
+            trace!(target: "sync::stages::bodies", has_reward, has_withdrawals, ?numhash, "Block reward");
+            if has_reward || has_withdrawals {
                 transition_id += 1;
             }
             block_transition_cursor.append(numhash, transition_id)?;
@@ -201,11 +209,11 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
         tx: &mut Transaction<'_, DB>,
         input: UnwindInput,
     ) -> Result<UnwindOutput, Box<dyn std::error::Error + Send + Sync>> {
-        // Cursors to unwind bodies, ommers, transactions and tx hash to number
+        // Cursors to unwind bodies, ommers, withdrawals and transactions
         let mut body_cursor = tx.cursor_mut::<tables::BlockBodies>()?;
         let mut ommers_cursor = tx.cursor_mut::<tables::BlockOmmers>()?;
+        let mut withdrawals_cursor = tx.cursor_mut::<tables::BlockWithdrawals>()?;
         let mut transaction_cursor = tx.cursor_mut::<tables::Transactions>()?;
-        let mut tx_hash_number_cursor = tx.cursor_mut::<tables::TxHashNumber>()?;
         // Cursors to unwind transitions
         let mut block_transition_cursor = tx.cursor_mut::<tables::BlockTransitionIndex>()?;
         let mut tx_transition_cursor = tx.cursor_mut::<tables::TxTransitionIndex>()?;
@@ -222,6 +230,11 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
                 ommers_cursor.delete_current()?;
             }
 
+            // Delete the withdrawals value if any
+            if withdrawals_cursor.seek_exact(key)?.is_some() {
+                withdrawals_cursor.delete_current()?;
+            }
+
             // Delete the block transition if any
             if block_transition_cursor.seek_exact(key)?.is_some() {
                 block_transition_cursor.delete_current()?;
@@ -229,12 +242,9 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
 
             // Delete all transactions that belong to this block
             for tx_id in body.tx_id_range() {
-                // First delete the transaction and hash to id mapping
-                if let Some((_, transaction)) = transaction_cursor.seek_exact(tx_id)? {
+                // First delete the transaction
+                if transaction_cursor.seek_exact(tx_id)?.is_some() {
                     transaction_cursor.delete_current()?;
-                    if tx_hash_number_cursor.seek_exact(transaction.hash)?.is_some() {
-                        tx_hash_number_cursor.delete_current()?;
-                    }
                 }
                 // Delete the transaction transition if any
                 if tx_transition_cursor.seek_exact(tx_id)?.is_some() {
@@ -265,9 +275,7 @@ impl<D: BodyDownloader, C: Consensus> BodyStage<D, C> {
     ) -> Result<Vec<SealedHeader>, StageError> {
         let mut header_cursor = tx.cursor::<tables::Headers>()?;
         let mut header_hashes_cursor = tx.cursor::<tables::CanonicalHeaders>()?;
-        let mut walker = header_hashes_cursor
-            .walk(starting_block)?
-            .take_while(|item| item.as_ref().map_or(false, |(num, _)| *num <= target));
+        let mut walker = header_hashes_cursor.walk_range(starting_block..=target)?;
 
         let mut bodies_to_download = Vec::new();
         while let Some(Ok((block_number, header_hash))) = walker.next() {
@@ -473,9 +481,8 @@ mod tests {
             .tx()
             .commit(|tx| {
                 let mut tx_cursor = tx.cursor_mut::<tables::Transactions>()?;
-                let (_, transaction) = tx_cursor.last()?.expect("Could not read last transaction");
+                tx_cursor.last()?.expect("Could not read last transaction");
                 tx_cursor.delete_current()?;
-                tx.delete::<tables::TxHashNumber>(transaction.hash, None)?;
                 Ok(())
             })
             .expect("Could not delete a transaction");
@@ -570,6 +577,7 @@ mod tests {
                 Ok(BlockBody {
                     transactions: block.body.clone(),
                     ommers: block.ommers.iter().cloned().map(|ommer| ommer.unseal()).collect(),
+                    withdrawals: block.withdrawals.clone(),
                 }),
             )
         }
@@ -641,7 +649,6 @@ mod tests {
                         };
                         body.tx_id_range().try_for_each(|tx_id| {
                             let transaction = random_signed_tx();
-                            tx.put::<tables::TxHashNumber>(transaction.hash(), tx_id)?;
                             tx.put::<tables::Transactions>(tx_id, transaction)?;
                             tx.put::<tables::TxTransitionIndex>(tx_id, tx_id)
                         })?;
@@ -683,6 +690,10 @@ mod tests {
                 self.tx.check_no_entry_above::<tables::BlockOmmers, _>(input.unwind_to, |key| {
                     key.number()
                 })?;
+                self.tx.check_no_entry_above::<tables::BlockWithdrawals, _>(
+                    input.unwind_to,
+                    |key| key.number(),
+                )?;
                 self.tx.check_no_entry_above::<tables::BlockTransitionIndex, _>(
                     input.unwind_to,
                     |key| key.number(),
@@ -694,10 +705,6 @@ mod tests {
                         last_tx_id,
                         |key| key,
                     )?;
-                    self.tx.check_no_entry_above_by_value::<tables::TxHashNumber, _>(
-                        last_tx_id,
-                        |value| value,
-                    )?;
                 }
                 Ok(())
             }
@@ -730,7 +737,6 @@ mod tests {
                     let mut ommers_cursor = tx.cursor::<tables::BlockOmmers>()?;
                     let mut block_transition_cursor = tx.cursor::<tables::BlockTransitionIndex>()?;
                     let mut transaction_cursor = tx.cursor::<tables::Transactions>()?;
-                    let mut tx_hash_num_cursor = tx.cursor::<tables::TxHashNumber>()?;
                     let mut tx_transition_cursor = tx.cursor::<tables::TxTransitionIndex>()?;
 
                     let first_body_key = match bodies_cursor.first()? {
@@ -769,11 +775,6 @@ mod tests {
                             assert_matches!(
                                 tx_transition_cursor.seek_exact(tx_id), Ok(Some(_)), "Transaction transition is missing"
                             );
-                            assert_matches!(
-                                tx_hash_num_cursor.seek_exact(tx_entry.unwrap().1.hash),
-                                Ok(Some(_)),
-                                "Transaction hash to index mapping is missing."
-                            );
                         }
 
                         prev_key = Some(key);
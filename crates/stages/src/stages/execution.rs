@@ -3,24 +3,36 @@ use crate::{
     UnwindInput, UnwindOutput,
 };
 use reth_db::{
-    cursor::{DbCursorRO, DbCursorRW},
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
     database::Database,
-    models::{BlockNumHash, StoredBlockBody, TransitionIdAddress},
+    models::{
+        AccountBeforeTx, AddressStorageKey, BlockNumHash, StoredBlockBody, TransitionIdAddress,
+    },
     tables,
     transaction::{DbTx, DbTxMut},
 };
 use reth_executor::{
-    config::SpecUpgrades,
-    executor::AccountChangeSet,
+    evm_config::{EthEvmConfig, EvmConfig},
+    executor::{AccountChangeSet, AccountInfoChangeSet, ExecutionResult},
+    metrics::ExecutionMetrics,
     revm_wrap::{State, SubState},
-    Config,
 };
-use reth_primitives::{Address, Header, StorageEntry, TransactionSignedEcRecovered, H256, U256};
-use reth_provider::StateProviderImplRefLatest;
-use std::fmt::Debug;
+use reth_interfaces::executor::Error as ExecutorError;
+use reth_primitives::{
+    Address, BlockNumber, ChainSpec, Header, StorageEntry, TransactionSignedEcRecovered,
+    Withdrawal, H256, MAINNET, U256,
+};
+use reth_provider::{StateCache, StateProvider, StateProviderImplRefLatest};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    sync::Arc,
+    time::Instant,
+};
 use tracing::*;
 
-const EXECUTION: StageId = StageId("Execution");
+/// The id of the execution stage.
+pub const EXECUTION: StageId = StageId("Execution");
 
 /// The execution stage executes all transactions and
 /// update history indexes.
@@ -43,34 +55,92 @@ const EXECUTION: StageId = StageId("Execution");
 /// [tables::Bytecodes]
 /// [tables::AccountChangeSet]
 /// [tables::StorageChangeSet]
+/// [tables::AccountHistory]
+/// [tables::StorageHistory]
+/// [tables::Receipts]
 ///
 /// For unwinds we are accessing:
 /// [tables::CumulativeTxCount] get tx index to know what needs to be unwinded
 /// [tables::AccountHistory] to remove change set and apply old values to
 /// [tables::PlainAccountState] [tables::StorageHistory] to remove change set and apply old values
 /// to [tables::PlainStorageState]
+/// [tables::Receipts] to remove the receipts of the unwound blocks
+///
+/// The stage executes blocks until one of its [ExecutionStageThresholds] is reached and then hands
+/// back control to the pipeline, which commits the progress. The next execution resumes from the
+/// committed checkpoint.
+///
+/// With a [StateCache], the state is read through the cache, which is moved on to every executed
+/// block, so consecutive executions at the tip of the chain share the state they read.
 #[derive(Debug)]
 pub struct ExecutionStage {
-    /// Executor configuration.
-    pub config: Config,
+    /// The chain specification the blocks are executed with.
+    pub chain_spec: ChainSpec,
+    /// The thresholds at which the progress is committed.
+    pub thresholds: ExecutionStageThresholds,
+    /// The cache of the state at the last executed block.
+    pub state_cache: Option<Arc<StateCache>>,
+    /// The configuration of the EVM the blocks are executed with.
+    pub evm_config: Arc<dyn EvmConfig>,
+    /// The throughput and latency of the executed blocks.
+    pub metrics: ExecutionMetrics,
 }
 
 impl Default for ExecutionStage {
     fn default() -> Self {
-        Self { config: Config { chain_id: 1.into(), spec_upgrades: SpecUpgrades::new_ethereum() } }
+        Self::new(MAINNET.clone())
     }
 }
 
 impl ExecutionStage {
-    /// Create new execution stage with specified config.
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    /// Create new execution stage with specified chain spec.
+    pub fn new(chain_spec: ChainSpec) -> Self {
+        Self {
+            chain_spec,
+            thresholds: Default::default(),
+            state_cache: None,
+            evm_config: Arc::new(EthEvmConfig),
+            metrics: ExecutionMetrics::new("stage"),
+        }
+    }
+
+    /// Set the thresholds at which the progress is committed.
+    pub fn with_thresholds(mut self, thresholds: ExecutionStageThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Set the cache the state is read through.
+    pub fn with_state_cache(mut self, state_cache: Arc<StateCache>) -> Self {
+        self.state_cache = Some(state_cache);
+        self
+    }
+
+    /// Set the configuration of the EVM the blocks are executed with.
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.evm_config = evm_config;
+        self
     }
 }
 
-/// Specify batch sizes of block in execution
-/// TODO make this as config
-const BATCH_SIZE: u64 = 1000;
+/// The thresholds at which the [ExecutionStage] commits its progress.
+///
+/// The stage stops after the block that reaches any of the thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionStageThresholds {
+    /// The maximum number of blocks to execute before committing.
+    pub max_blocks: u64,
+    /// The maximum cumulative gas used by the executed blocks before committing.
+    pub max_gas: u64,
+    /// The maximum number of account changes before committing.
+    pub max_changed_accounts: usize,
+}
+
+impl Default for ExecutionStageThresholds {
+    fn default() -> Self {
+        Self { max_blocks: 1_000, max_gas: 10_000_000_000, max_changed_accounts: 500_000 }
+    }
+}
 
 #[async_trait::async_trait]
 impl<DB: Database> Stage<DB> for ExecutionStage {
@@ -103,10 +173,13 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         // Skip sender recovery and load signer from database.
         let mut tx_sender = tx.cursor::<tables::TxSenders>()?;
 
+        let ExecutionStageThresholds { max_blocks, max_gas, max_changed_accounts } =
+            self.thresholds;
+
         // get canonical blocks (num,hash)
         let canonical_batch = canonicals
             .walk(start_block)?
-            .take(BATCH_SIZE as usize) // TODO: commit_threshold
+            .take(max_blocks as usize)
             .map(|i| i.map(BlockNumHash))
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -116,31 +189,35 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
             return Ok(ExecOutput { stage_progress: last_block, done: true })
         }
 
-        // Get block headers and bodies from canonical hashes
-        let block_batch = canonical_batch
-            .iter()
-            .map(|key| -> Result<(Header, StoredBlockBody, Vec<Header>), StageError> {
-                // NOTE: It probably will be faster to fetch all items from one table with cursor,
-                // but to reduce complexity we are using `seek_exact` to skip some
-                // edge cases that can happen.
-                let (_, header) =
-                    headers.seek_exact(*key)?.ok_or(DatabaseIntegrityError::Header {
-                        number: key.number(),
-                        hash: key.hash(),
-                    })?;
-                let (_, body) = bodies_cursor
-                    .seek_exact(*key)?
-                    .ok_or(DatabaseIntegrityError::BlockBody { number: key.number() })?;
-                let (_, stored_ommers) = ommers_cursor
-                    .seek_exact(*key)?
-                    .ok_or(DatabaseIntegrityError::Ommers { number: key.number() })?;
-                Ok((header, body, stored_ommers.ommers))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        // The blocks are executed and applied one by one, so that every block is executed on top
+        // of the state changes of the previous block.
+        let mut executed_blocks = 0;
+        let mut cumulative_gas = 0;
+        let mut changed_accounts = 0;
+        for key in canonical_batch.iter() {
+            // NOTE: It probably will be faster to fetch all items from one table with cursor,
+            // but to reduce complexity we are using `seek_exact` to skip some
+            // edge cases that can happen.
+            let (_, header) = headers
+                .seek_exact(*key)?
+                .ok_or(DatabaseIntegrityError::Header { number: key.number(), hash: key.hash() })?;
+            let (_, body) = bodies_cursor
+                .seek_exact(*key)?
+                .ok_or(DatabaseIntegrityError::BlockBody { number: key.number() })?;
+            let (_, stored_ommers) = ommers_cursor
+                .seek_exact(*key)?
+                .ok_or(DatabaseIntegrityError::Ommers { number: key.number() })?;
+            let ommers = stored_ommers.ommers;
+            // the withdrawals of empty blocks are not stored
+            let withdrawals = match header.withdrawals_root {
+                Some(_) => Some(
+                    tx.get::<tables::BlockWithdrawals>(*key)?
+                        .map(|stored| stored.withdrawals)
+                        .unwrap_or_default(),
+                ),
+                None => None,
+            };
 
-        // Fetch transactions, execute them and generate results
-        let mut block_change_patches = Vec::with_capacity(canonical_batch.len());
-        for (header, body, ommers) in block_batch.iter() {
             let num = header.number;
             tracing::trace!(target: "sync::stages::execution", ?num, "Execute block.");
             // iterate over all transactions
@@ -181,118 +258,53 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
                 })
                 .collect();
 
-            // for now use default eth config
-            let state_provider = SubState::new(State::new(StateProviderImplRefLatest::new(&**tx)));
+            let state_provider = StateProviderImplRefLatest::new(&**tx);
 
             trace!(target: "sync::stages::execution", number = header.number, txs = recovered_transactions.len(), "Executing block");
 
-            // For ethereum tests that has MAX gas that calls contract until max depth (1024 calls)
-            // revm can take more then default allocated stack space. For this case we are using
-            // local thread with increased stack size. After this task is done https://github.com/bluealloy/revm/issues/305
-            // we can see to set more accurate stack size or even optimize revm to move more data to
-            // heap.
-            let changeset = std::thread::scope(|scope| {
-                let handle = std::thread::Builder::new()
-                    .stack_size(50 * 1024 * 1024)
-                    .spawn_scoped(scope, || {
-                        // execute and store output to results
-                        // ANCHOR: snippet-block_change_patches
-                        reth_executor::executor::execute_and_verify_receipt(
-                            header,
-                            &recovered_transactions,
-                            ommers,
-                            &self.config,
-                            state_provider,
-                        )
-                        // ANCHOR_END: snippet-block_change_patches
-                    })
-                    .expect("Expects that thread name is not null");
-                handle.join().expect("Expects for thread to not panic")
-            })
+            let (transactions, withdrawals) = (&recovered_transactions, withdrawals.as_deref());
+            let started_at = Instant::now();
+            let changeset = match &self.state_cache {
+                Some(cache) => execute_block(
+                    &header,
+                    transactions,
+                    &ommers,
+                    withdrawals,
+                    &self.chain_spec,
+                    self.evm_config.as_ref(),
+                    cache.provider(state_provider, header.parent_hash),
+                ),
+                None => execute_block(
+                    &header,
+                    transactions,
+                    &ommers,
+                    withdrawals,
+                    &self.chain_spec,
+                    self.evm_config.as_ref(),
+                    state_provider,
+                ),
+            }
             .map_err(|error| StageError::ExecutionError { block: header.number, error })?;
-            block_change_patches.push(changeset);
-        }
+            self.metrics.record_block(header.gas_used, transactions.len(), started_at.elapsed());
 
-        // Get last tx count so that we can know amount of transaction in the block.
-        let mut current_transition_id = tx.get_block_transition_by_num(last_block)? + 1;
-        info!(target: "sync::stages::execution", current_transition_id, blocks = block_change_patches.len(), "Inserting execution results");
-
-        // apply changes to plain database.
-        for results in block_change_patches.into_iter() {
-            // insert state change set
-            for result in results.changesets.into_iter() {
-                // TODO insert to transitionId to tx_index
-                for (address, account_change_set) in result.changeset.into_iter() {
-                    let AccountChangeSet { account, wipe_storage, storage } = account_change_set;
-                    // apply account change to db. Updates AccountChangeSet and PlainAccountState
-                    // tables.
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, ?account, wipe_storage, "Applying account changeset");
-                    account.apply_to_db(&**tx, address, current_transition_id)?;
-
-                    // wipe storage
-                    if wipe_storage {
-                        // TODO insert all changes to StorageChangeSet
-                        tx.delete::<tables::PlainStorageState>(address, None)?;
-                    }
-                    // insert storage changeset
-                    let storage_id = TransitionIdAddress((current_transition_id, address));
-                    for (key, (old_value, new_value)) in storage {
-                        let mut hkey = H256::zero();
-                        key.to_big_endian(&mut hkey.0);
-
-                        trace!(target: "sync::stages::execution", ?address, current_transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
-
-                        // insert into StorageChangeSet
-                        tx.put::<tables::StorageChangeSet>(
-                            storage_id.clone(),
-                            StorageEntry { key: hkey, value: old_value },
-                        )?;
-                        tracing::debug!(
-                            target = "sync::stages::execution",
-                            "{address} setting storage:{key} ({old_value} -> {new_value})"
-                        );
-
-                        // Always delete old value as duplicate table put will not override it
-                        tx.delete::<tables::PlainStorageState>(
-                            address,
-                            Some(StorageEntry { key: hkey, value: old_value }),
-                        )?;
-                        if !new_value.is_zero() {
-                            tx.put::<tables::PlainStorageState>(
-                                address,
-                                StorageEntry { key: hkey, value: new_value },
-                            )?;
-                        }
-                    }
-                    current_transition_id += 1;
-                }
-                // insert bytecode
-                for (hash, bytecode) in result.new_bytecodes.into_iter() {
-                    // make different types of bytecode. Checked and maybe even analyzed (needs to
-                    // be packed). Currently save only raw bytes.
-                    let bytecode = bytecode.bytes();
-                    trace!(target: "sync::stages::execution", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
-                    tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
-
-                    // NOTE: bytecode bytes are not inserted in change set and it stand in saparate
-                    // table
-                }
+            if let Some(cache) = &self.state_cache {
+                cache.advance(header.parent_hash, key.hash(), &changeset.changed_state_keys());
             }
 
-            // If there is block reward we will add account changeset to db
-            if let Some(block_reward_changeset) = results.block_reward {
-                // we are sure that block reward index is present.
-                for (address, changeset) in block_reward_changeset.into_iter() {
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, "Applying block reward");
-                    changeset.apply_to_db(&**tx, address, current_transition_id)?;
-                }
-                current_transition_id += 1;
+            changed_accounts += write_execution_result(tx, header.number, &body, changeset)?;
+            cumulative_gas += header.gas_used;
+            executed_blocks += 1;
+
+            // commit the progress before the transaction grows too large
+            if cumulative_gas >= max_gas || changed_accounts >= max_changed_accounts {
+                break
             }
         }
 
-        let stage_progress = last_block + canonical_batch.len() as u64;
-        let done = canonical_batch.len() < BATCH_SIZE as usize;
-        info!(target: "sync::stages::execution", done, stage_progress, "Sync iteration finished");
+        let stage_progress = last_block + executed_blocks as u64;
+        let done =
+            executed_blocks == canonical_batch.len() && canonical_batch.len() < max_blocks as usize;
+        info!(target: "sync::stages::execution", done, stage_progress, executed_blocks, cumulative_gas, changed_accounts, "Sync iteration finished");
         Ok(ExecOutput { done, stage_progress })
     }
 
@@ -306,13 +318,15 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         let mut account_changeset = tx.cursor_dup_mut::<tables::AccountChangeSet>()?;
         let mut storage_changeset = tx.cursor_dup_mut::<tables::StorageChangeSet>()?;
 
+        // remove the receipts of the transactions after the block we unwind to
+        let body = tx.get_block_body_by_num(input.unwind_to)?;
+        let first_unwound_tx = body.start_tx_id + body.tx_count;
+        tx.unwind_table::<tables::Receipts, _>(first_unwound_tx, |tx_id| tx_id + 1)?;
+
         let from_transition = tx.get_block_transition_by_num(input.stage_progress)?;
 
-        let to_transition = if input.unwind_to != 0 {
-            tx.get_block_transition_by_num(input.unwind_to - 1)?
-        } else {
-            0
-        };
+        // the changes of the block we unwind to are kept
+        let to_transition = tx.get_block_transition_by_num(input.unwind_to)? + 1;
 
         if to_transition > from_transition {
             panic!("Unwind transition {} (stage progress block #{}) is higher than the transition {} of (unwind block #{})", to_transition, input.stage_progress, from_transition, input.unwind_to);
@@ -329,12 +343,18 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         // Check if walk and walk_dup would do the same thing
         // TODO(dragan) test walking here
         let account_changeset_batch = account_changeset
-            .walk(to_transition)?
-            .take_while(|item| {
-                item.as_ref().map(|(num, _)| *num <= from_transition).unwrap_or_default()
-            })
+            .walk_range(to_transition..=from_transition)?
             .collect::<Result<Vec<_>, _>>()?;
 
+        // remove the unwound transitions from the history indices
+        let addresses = account_changeset_batch
+            .iter()
+            .map(|(_, changeset)| changeset.address)
+            .collect::<BTreeSet<_>>();
+        for address in addresses {
+            tx.unwind_history_index::<tables::AccountHistory, _>(address, to_transition)?;
+        }
+
         // revert all changes to PlainState
         for (_, changeset) in account_changeset_batch.into_iter().rev() {
             // TODO refactor in db fn called tx.aplly_account_changeset
@@ -347,23 +367,23 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
 
         // TODO(dragan) fix walking here
         // get all batches for storage change
-        let storage_chageset_batch = storage_changeset
-            .walk((to_transition, Address::zero()).into())?
-            .take_while(|item| {
-                item.as_ref()
-                    .map(|(key, _)| key.transition_id() <= from_transition)
-                    .unwrap_or_default()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let start = TransitionIdAddress::from((to_transition, Address::zero()));
+        let end = TransitionIdAddress::from((from_transition + 1, Address::zero()));
+        let storage_chageset_batch =
+            storage_changeset.walk_range(start..end)?.collect::<Result<Vec<_>, _>>()?;
+
+        let slots = storage_chageset_batch
+            .iter()
+            .map(|(key, storage)| AddressStorageKey((key.address(), storage.key)))
+            .collect::<BTreeSet<_>>();
+        for slot in slots {
+            tx.unwind_history_index::<tables::StorageHistory, _>(slot, to_transition)?;
+        }
 
         // revert all changes to PlainStorage
+        let mut plain_storage = tx.cursor_dup_mut::<tables::PlainStorageState>()?;
         for (key, storage) in storage_chageset_batch.into_iter().rev() {
-            let address = key.address();
-            tx.put::<tables::PlainStorageState>(address, storage.clone())?;
-            if storage.value == U256::zero() {
-                // delete value that is zero
-                tx.delete::<tables::PlainStorageState>(address, Some(storage))?;
-            }
+            set_storage_slot(&mut plain_storage, key.address(), storage)?;
         }
 
         // Discard unwinded changesets
@@ -389,13 +409,295 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
     }
 }
 
+/// Executes the block on top of the given state and verifies its receipts.
+fn execute_block<SP: StateProvider>(
+    header: &Header,
+    transactions: &[TransactionSignedEcRecovered],
+    ommers: &[Header],
+    withdrawals: Option<&[Withdrawal]>,
+    chain_spec: &ChainSpec,
+    evm_config: &dyn EvmConfig,
+    state_provider: SP,
+) -> Result<ExecutionResult, ExecutorError> {
+    let state_provider = SubState::new(State::new(state_provider));
+
+    // For ethereum tests that has MAX gas that calls contract until max depth (1024 calls)
+    // revm can take more then default allocated stack space. For this case we are using
+    // local thread with increased stack size. After this task is done https://github.com/bluealloy/revm/issues/305
+    // we can see to set more accurate stack size or even optimize revm to move more data to
+    // heap.
+    std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .stack_size(50 * 1024 * 1024)
+            .spawn_scoped(scope, || {
+                // execute and store output to results
+                // ANCHOR: snippet-block_change_patches
+                reth_executor::executor::execute_and_verify_receipt(
+                    header,
+                    transactions,
+                    ommers,
+                    withdrawals,
+                    chain_spec,
+                    state_provider,
+                    evm_config,
+                )
+                // ANCHOR_END: snippet-block_change_patches
+            })
+            .expect("Expects that thread name is not null");
+        handle.join().expect("Expects for thread to not panic")
+    })
+}
+
+/// Sets the value of a storage slot of the account in [tables::PlainStorageState], or removes the
+/// slot if the value is zero.
+///
+/// The slots are the duplicate values of the account, so the current value of the slot is looked
+/// up by its subkey and removed first, as a put would add a second value for the slot.
+fn set_storage_slot<'tx, C>(
+    cursor: &mut C,
+    address: Address,
+    entry: StorageEntry,
+) -> Result<(), reth_db::Error>
+where
+    C: DbDupCursorRO<'tx, tables::PlainStorageState> + DbCursorRW<'tx, tables::PlainStorageState>,
+{
+    if let Some(current) = cursor.seek_by_key_subkey(address, entry.key)? {
+        if current.key == entry.key {
+            cursor.delete_current()?;
+        }
+    }
+    if entry.value != U256::zero() {
+        cursor.upsert(address, entry)?;
+    }
+    Ok(())
+}
+
+/// Writes the receipts and the state changes of an executed block, whose header and body are
+/// stored already.
+///
+/// This is used by the stage and by the [BlockchainTree](crate::tree::BlockchainTree), which
+/// writes the results of the blocks it executed already when they become canonical.
+///
+/// Returns the number of changed accounts.
+pub(crate) fn write_execution_result<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    number: BlockNumber,
+    body: &StoredBlockBody,
+    result: ExecutionResult,
+) -> Result<usize, StageError> {
+    // store the receipts of the block by their transaction number
+    for (tx_id, changeset) in body.tx_id_range().zip(result.changesets.iter()) {
+        tx.put::<tables::Receipts>(tx_id, changeset.receipt.clone())?;
+    }
+
+    // the transitions of the block start after the last transition of its parent
+    let first_transition_id = tx.get_block_transition_by_num(number - 1)? + 1;
+    apply_changeset(tx, result, first_transition_id)
+}
+
+/// Applies the state changes of a block to the plain state tables and writes the changesets,
+/// i.e. the values before the changes, starting at the given transition.
+///
+/// Every transaction of the block has its own transition, followed by the transition of the
+/// block reward if there is one. The changesets hold every value that is changed at a
+/// transition, including the slots of wiped storage, so the block can be unwound from them.
+///
+/// Returns the number of changed accounts.
+fn apply_changeset<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    results: ExecutionResult,
+    mut current_transition_id: u64,
+) -> Result<usize, StageError> {
+    let mut changed_accounts = 0;
+    let mut plain_storage = tx.cursor_dup_mut::<tables::PlainStorageState>()?;
+    // The transitions are increasing and the accounts and slots of a transition are sorted, so
+    // the changesets are appended.
+    let mut account_changesets = tx.cursor_dup_mut::<tables::AccountChangeSet>()?;
+    let mut storage_changesets = tx.cursor_dup_mut::<tables::StorageChangeSet>()?;
+
+    // insert state change set
+    for result in results.changesets.into_iter() {
+        for (address, account_change_set) in result.changeset.into_iter() {
+            let AccountChangeSet { account, wipe_storage, mut storage } = account_change_set;
+            // apply account change to db. Updates AccountChangeSet and PlainAccountState
+            // tables.
+            trace!(target: "sync::stages::execution", ?address, current_transition_id, ?account, wipe_storage, "Applying account changeset");
+            if !matches!(account, AccountInfoChangeSet::NoChange) {
+                tx.insert_history_index::<tables::AccountHistory, _>(
+                    address,
+                    current_transition_id,
+                )?;
+            }
+            apply_account_changeset(
+                &**tx,
+                &mut account_changesets,
+                address,
+                current_transition_id,
+                account,
+            )?;
+            changed_accounts += 1;
+
+            // The wiped slots are cleared like any other changed slot, with their stored values
+            // as the values before the change. The executor doesn't know the stored slots, and
+            // the old values of the slots written after the wipe are not the stored ones.
+            if wipe_storage {
+                for entry in plain_storage.walk_dup(address, H256::zero())? {
+                    let (_, entry) = entry?;
+                    storage
+                        .entry(U256::from_big_endian(&entry.key.0))
+                        .and_modify(|(old_value, _)| *old_value = entry.value)
+                        .or_insert((entry.value, U256::zero()));
+                }
+            }
+            // insert storage changeset
+            apply_storage_changeset(
+                tx,
+                &mut plain_storage,
+                &mut storage_changesets,
+                address,
+                current_transition_id,
+                storage,
+            )?;
+        }
+        current_transition_id += 1;
+        // insert bytecode
+        for (hash, bytecode) in result.new_bytecodes.into_iter() {
+            // make different types of bytecode. Checked and maybe even analyzed (needs to
+            // be packed). Currently save only raw bytes.
+            let bytecode = bytecode.bytes();
+            trace!(target: "sync::stages::execution", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
+            tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
+
+            // NOTE: bytecode bytes are not inserted in change set and it stand in saparate
+            // table
+        }
+    }
+
+    // If there is block reward we will add account changeset to db
+    if let Some(block_reward_changeset) = results.block_reward {
+        // we are sure that block reward index is present.
+        for (address, changeset) in block_reward_changeset.into_iter() {
+            trace!(target: "sync::stages::execution", ?address, current_transition_id, "Applying block reward");
+            if !matches!(changeset, AccountInfoChangeSet::NoChange) {
+                tx.insert_history_index::<tables::AccountHistory, _>(
+                    address,
+                    current_transition_id,
+                )?;
+            }
+            apply_account_changeset(
+                &**tx,
+                &mut account_changesets,
+                address,
+                current_transition_id,
+                changeset,
+            )?;
+            changed_accounts += 1;
+        }
+    }
+
+    // The storage changes of the block that are not made by its transactions are at the
+    // transition of the block, which every block after Shanghai has for its withdrawals.
+    for (address, storage) in results.block_storage.into_iter() {
+        apply_storage_changeset(
+            tx,
+            &mut plain_storage,
+            &mut storage_changesets,
+            address,
+            current_transition_id,
+            storage,
+        )?;
+    }
+
+    Ok(changed_accounts)
+}
+
+/// Apply the change of an account at a transition to [tables::PlainAccountState].
+///
+/// The old account is appended to the [tables::AccountChangeSet] with the cursor, so the
+/// changesets must be applied in the order of their transitions and addresses.
+fn apply_account_changeset<'a, 'c, TX, C>(
+    tx: &TX,
+    changesets: &mut C,
+    address: Address,
+    transition_id: u64,
+    changeset: AccountInfoChangeSet,
+) -> Result<(), reth_db::Error>
+where
+    TX: DbTxMut<'a>,
+    C: DbDupCursorRW<'c, tables::AccountChangeSet>,
+{
+    match changeset {
+        AccountInfoChangeSet::Changed { old, new } => {
+            // insert old account in AccountChangeSet
+            // check for old != new was already done
+            changesets.append_dup(transition_id, AccountBeforeTx { address, info: Some(old) })?;
+            tx.put::<tables::PlainAccountState>(address, new)?;
+        }
+        AccountInfoChangeSet::Created { new } => {
+            changesets.append_dup(transition_id, AccountBeforeTx { address, info: None })?;
+            tx.put::<tables::PlainAccountState>(address, new)?;
+        }
+        AccountInfoChangeSet::Destroyed { old } => {
+            tx.delete::<tables::PlainAccountState>(address, None)?;
+            changesets.append_dup(transition_id, AccountBeforeTx { address, info: Some(old) })?;
+        }
+        AccountInfoChangeSet::NoChange => {
+            // do nothing storage account didn't change
+        }
+    }
+    Ok(())
+}
+
+/// Apply the storage changes of an account at a transition. Updates [tables::StorageChangeSet],
+/// [tables::StorageHistory] and [tables::PlainStorageState].
+fn apply_storage_changeset<'tx, DB, P, S>(
+    tx: &Transaction<'_, DB>,
+    plain_storage: &mut P,
+    storage_changesets: &mut S,
+    address: Address,
+    transition_id: u64,
+    storage: BTreeMap<U256, (U256, U256)>,
+) -> Result<(), StageError>
+where
+    DB: Database,
+    P: DbDupCursorRO<'tx, tables::PlainStorageState> + DbCursorRW<'tx, tables::PlainStorageState>,
+    S: DbDupCursorRW<'tx, tables::StorageChangeSet>,
+{
+    let storage_id = TransitionIdAddress((transition_id, address));
+    for (key, (old_value, new_value)) in storage {
+        let mut hkey = H256::zero();
+        key.to_big_endian(&mut hkey.0);
+
+        trace!(target: "sync::stages::execution", ?address, transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
+
+        // insert into StorageChangeSet
+        storage_changesets
+            .append_dup(storage_id.clone(), StorageEntry { key: hkey, value: old_value })?;
+        tx.insert_history_index::<tables::StorageHistory, _>(
+            AddressStorageKey((address, hkey)),
+            transition_id,
+        )?;
+        tracing::debug!(
+            target = "sync::stages::execution",
+            "{address} setting storage:{key} ({old_value} -> {new_value})"
+        );
+
+        set_storage_slot(plain_storage, address, StorageEntry { key: hkey, value: new_value })?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::{Deref, DerefMut};
 
     use super::*;
     use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
-    use reth_primitives::{hex_literal::hex, keccak256, Account, SealedBlock, H160, U256};
+    use reth_interfaces::test_utils::generators::random_block;
+    use reth_primitives::{
+        hex_literal::hex, keccak256, Account, ChainSpecBuilder, SealedBlock, EMPTY_OMMER_ROOT,
+        H160, H256, U256,
+    };
     use reth_provider::insert_canonical_block;
     use reth_rlp::Decodable;
 
@@ -414,8 +716,8 @@ mod tests {
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
         let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
-        insert_canonical_block(tx.deref_mut(), &genesis, true).unwrap();
-        insert_canonical_block(tx.deref_mut(), &block, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), &genesis, None, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), &block, None, true).unwrap();
         tx.commit().unwrap();
 
         // insert pre state
@@ -441,8 +743,8 @@ mod tests {
         tx.commit().unwrap();
 
         // execute
-        let mut execution_stage = ExecutionStage::default();
-        execution_stage.config.spec_upgrades = SpecUpgrades::new_berlin_activated();
+        let mut execution_stage =
+            ExecutionStage::new(ChainSpecBuilder::mainnet().berlin_activated().build());
         let output = execution_stage.execute(&mut tx, input).await.unwrap();
         tx.commit().unwrap();
         assert_eq!(output, ExecOutput { stage_progress: 1, done: true });
@@ -481,6 +783,27 @@ mod tests {
             Ok(Some(StorageEntry { key: H256::from_low_u64_be(1), value: 2.into() })),
             "Post changed of a account"
         );
+        // assert receipts
+        let receipt = tx.get::<tables::Receipts>(0).unwrap().expect("receipt is stored");
+        assert!(receipt.success, "Receipt of the transaction");
+        assert_eq!(receipt.cumulative_gas_used, block.header.gas_used);
+
+        // assert changesets: the transaction has the first transition of the block, the block
+        // reward the next one
+        let account_changesets = tx
+            .cursor_dup::<tables::AccountChangeSet>()
+            .unwrap()
+            .walk(0)
+            .unwrap()
+            .map(|entry| entry.map(|(transition_id, before)| (transition_id, before.address)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(account_changesets, vec![(2, account2), (2, account3), (3, account2)]);
+        assert_eq!(
+            tx.get::<tables::StorageChangeSet>(TransitionIdAddress((2, account1))),
+            Ok(Some(StorageEntry { key: H256::from_low_u64_be(1), value: 0.into() })),
+            "Storage value before the transaction"
+        );
     }
 
     #[tokio::test]
@@ -499,8 +822,8 @@ mod tests {
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
         let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
-        insert_canonical_block(tx.deref_mut(), &genesis, true).unwrap();
-        insert_canonical_block(tx.deref_mut(), &block, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), &genesis, None, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), &block, None, true).unwrap();
         tx.commit().unwrap();
 
         // variables
@@ -521,8 +844,8 @@ mod tests {
 
         // execute
 
-        let mut execution_stage = ExecutionStage::default();
-        execution_stage.config.spec_upgrades = SpecUpgrades::new_berlin_activated();
+        let mut execution_stage =
+            ExecutionStage::new(ChainSpecBuilder::mainnet().berlin_activated().build());
         let _ = execution_stage.execute(&mut tx, input).await.unwrap();
         tx.commit().unwrap();
 
@@ -552,5 +875,117 @@ mod tests {
             Ok(None),
             "Third account should be unwinded"
         );
+
+        let mut storage = db_tx.cursor_dup::<tables::PlainStorageState>().unwrap();
+        assert_eq!(
+            storage.walk_dup(acc1, H256::zero()).unwrap().next(),
+            None,
+            "Storage of the account should be unwinded"
+        );
+
+        assert_eq!(db_tx.get::<tables::Receipts>(0), Ok(None), "Receipt should be unwinded");
+    }
+
+    #[tokio::test]
+    async fn execute_until_threshold() {
+        let state_db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(state_db.as_ref()).unwrap();
+
+        // empty blocks, the block reward changes a single account per block
+        let mut blocks: Vec<SealedBlock> = Vec::new();
+        for number in 0..4 {
+            let mut block = random_block(number, blocks.last().map(|block| block.hash()), Some(0));
+            block.ommers.clear();
+            block.header = Header {
+                beneficiary: Address::from_low_u64_be(number + 1),
+                ommers_hash: EMPTY_OMMER_ROOT,
+                ..block.header.unseal()
+            }
+            .seal();
+            insert_canonical_block(tx.deref_mut(), &block, None, true).unwrap();
+            blocks.push(block);
+        }
+        tx.commit().unwrap();
+
+        let mut execution_stage =
+            ExecutionStage::new(ChainSpecBuilder::mainnet().berlin_activated().build())
+                .with_thresholds(ExecutionStageThresholds {
+                    max_blocks: 10,
+                    max_gas: u64::MAX,
+                    max_changed_accounts: 2,
+                });
+
+        // the batch stops after the block that reaches the threshold
+        let input = ExecInput { previous_stage: None, stage_progress: None };
+        let output = execution_stage.execute(&mut tx, input).await.unwrap();
+        tx.commit().unwrap();
+        assert_eq!(output, ExecOutput { stage_progress: 2, done: false });
+        assert!(tx.get::<tables::PlainAccountState>(blocks[2].beneficiary).unwrap().is_some());
+        assert_eq!(tx.get::<tables::PlainAccountState>(blocks[3].beneficiary), Ok(None));
+
+        // the next run continues from the checkpoint
+        let input = ExecInput { previous_stage: None, stage_progress: Some(output.stage_progress) };
+        let output = execution_stage.execute(&mut tx, input).await.unwrap();
+        tx.commit().unwrap();
+        assert_eq!(output, ExecOutput { stage_progress: 3, done: true });
+        assert!(tx.get::<tables::PlainAccountState>(blocks[3].beneficiary).unwrap().is_some());
+    }
+
+    #[test]
+    fn apply_account_info_changeset() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let address = H160::zero();
+        let acc1 = Account { balance: 1.into(), nonce: 2, bytecode_hash: Some(H256::zero()) };
+        let acc2 = Account { balance: 3.into(), nonce: 4, bytecode_hash: Some(H256::zero()) };
+
+        let tx = db.tx_mut().unwrap();
+        let mut changesets = tx.cursor_dup_mut::<tables::AccountChangeSet>().unwrap();
+
+        // check Changed changeset
+        apply_account_changeset(
+            &tx,
+            &mut changesets,
+            address,
+            0,
+            AccountInfoChangeSet::Changed { new: acc1, old: acc2 },
+        )
+        .unwrap();
+        assert_eq!(
+            tx.get::<tables::AccountChangeSet>(0),
+            Ok(Some(AccountBeforeTx { address, info: Some(acc2) }))
+        );
+        assert_eq!(tx.get::<tables::PlainAccountState>(address), Ok(Some(acc1)));
+
+        apply_account_changeset(
+            &tx,
+            &mut changesets,
+            address,
+            1,
+            AccountInfoChangeSet::Created { new: acc1 },
+        )
+        .unwrap();
+        assert_eq!(
+            tx.get::<tables::AccountChangeSet>(1),
+            Ok(Some(AccountBeforeTx { address, info: None }))
+        );
+        assert_eq!(tx.get::<tables::PlainAccountState>(address), Ok(Some(acc1)));
+
+        apply_account_changeset(
+            &tx,
+            &mut changesets,
+            address,
+            2,
+            AccountInfoChangeSet::Destroyed { old: acc2 },
+        )
+        .unwrap();
+        assert_eq!(tx.get::<tables::PlainAccountState>(address), Ok(None));
+        assert_eq!(
+            tx.get::<tables::AccountChangeSet>(2),
+            Ok(Some(AccountBeforeTx { address, info: Some(acc2) }))
+        );
+
+        // changesets can only be appended
+        let changeset = AccountInfoChangeSet::Created { new: acc1 };
+        assert!(apply_account_changeset(&tx, &mut changesets, address, 1, changeset).is_err());
     }
 }
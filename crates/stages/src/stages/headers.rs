@@ -16,15 +16,16 @@ use reth_interfaces::{
         error::DownloadError,
         headers::{
             client::{HeadersClient, StatusUpdater},
-            downloader::{ensure_parent, HeaderDownloader},
+            downloader::{ensure_parent, validate_header_download, HeaderDownloader},
         },
     },
 };
-use reth_primitives::{BlockNumber, Header, SealedHeader, H256, U256};
+use reth_primitives::{BlockNumber, Head, Header, SealedHeader, H256, U256};
 use std::{fmt::Debug, sync::Arc};
 use tracing::*;
 
-const HEADERS: StageId = StageId("Headers");
+/// The id of the headers stage.
+pub const HEADERS: StageId = StageId("Headers");
 
 /// The headers stage.
 ///
@@ -38,6 +39,12 @@ const HEADERS: StageId = StageId("Headers");
 /// - [`CanonicalHeaders`][reth_interfaces::db::tables::CanonicalHeaders]
 /// - [`HeaderTD`][reth_interfaces::db::tables::HeaderTD]
 ///
+/// Every batch of headers is validated before it is written: each header must be the parent of
+/// the previous one and valid in relation to it according to the [Consensus] rules. The last
+/// header must be the child of the local head. The rules that depend on all ancestors of a header,
+/// e.g. the authorized signers of clique, are validated in ascending order once the headers are
+/// written.
+///
 /// NOTE: This stage commits the header changes to the database (everything except the changes to
 /// [`HeaderTD`][reth_interfaces::db::tables::HeaderTD] table). The stage does not return the
 /// control flow to the pipeline in order to preserve the context of the chain tip.
@@ -81,6 +88,8 @@ impl<DB: Database, D: HeaderDownloader, C: Consensus, H: HeadersClient, S: Statu
         debug!(target: "sync::stages::headers", ?tip, head = ?head.hash(), "Commencing sync");
 
         let mut current_progress = stage_progress;
+        // The lowest header of the previous batch
+        let mut lowest = None;
         let mut stream =
             self.downloader.stream(head.clone(), tip).chunks(self.commit_threshold as usize);
         // The stage relies on the downloader to return the headers
@@ -93,7 +102,8 @@ impl<DB: Database, D: HeaderDownloader, C: Consensus, H: HeadersClient, S: Statu
                     self.metrics.headers_counter.increment(res.len() as u64);
 
                     // Perform basic response validation
-                    self.validate_header_response(&res)?;
+                    self.validate_header_response(&res, lowest.as_ref())?;
+                    lowest = res.last().cloned();
                     let write_progress =
                         self.write_headers::<DB>(tx, res).await?.unwrap_or_default();
                     current_progress = current_progress.max(write_progress);
@@ -118,6 +128,14 @@ impl<DB: Database, D: HeaderDownloader, C: Consensus, H: HeadersClient, S: Statu
             }
         }
 
+        // The downloaded range must be connected to the local head
+        if let Some(lowest) = lowest {
+            ensure_parent(&lowest, &head).map_err(|err| StageError::Download(err.to_string()))?;
+        }
+
+        // The rules that depend on the ancestors can only be validated in ascending order
+        self.validate_with_ancestors::<DB>(tx, &head)?;
+
         // Write total difficulty values after all headers have been inserted
         debug!(target: "sync::stages::headers", head = ?head.hash(), "Writing total difficulty");
         self.write_td::<DB>(tx, &head)?;
@@ -161,8 +179,16 @@ impl<D: HeaderDownloader, C: Consensus, H: HeadersClient, S: StatusUpdater>
         let td: U256 = *tx
             .get::<tables::HeaderTD>(block_key)?
             .ok_or(DatabaseIntegrityError::TotalDifficulty { number: height })?;
+        let header = tx
+            .get::<tables::Headers>(block_key)?
+            .ok_or(DatabaseIntegrityError::Header { number: height, hash: block_key.hash() })?;
         // TODO: This should happen in the last stage
-        self.network_handle.update_status(height, block_key.hash(), td);
+        self.network_handle.update_status(Head {
+            number: height,
+            hash: block_key.hash(),
+            total_difficulty: td,
+            timestamp: header.timestamp,
+        });
         Ok(())
     }
 
@@ -222,13 +248,27 @@ impl<D: HeaderDownloader, C: Consensus, H: HeadersClient, S: StatusUpdater>
     }
 
     /// Perform basic header response validation
-    fn validate_header_response(&self, headers: &[SealedHeader]) -> Result<(), StageError> {
-        let mut headers_iter = headers.iter().peekable();
-        while let Some(header) = headers_iter.next() {
-            if let Some(parent) = headers_iter.peek() {
-                ensure_parent(header, parent)
-                    .map_err(|err| StageError::Download(err.to_string()))?;
+    ///
+    /// The headers are expected in descending order. The first header of the batch must be the
+    /// parent of the lowest header of the previous batch, if any.
+    fn validate_header_response(
+        &self,
+        headers: &[SealedHeader],
+        lowest: Option<&SealedHeader>,
+    ) -> Result<(), StageError> {
+        let mut child = lowest;
+        for parent in headers {
+            if let Some(header) = child {
+                validate_header_download(&*self.consensus, header, parent).map_err(
+                    |err| match err {
+                        DownloadError::HeaderValidation { error, .. } => {
+                            StageError::Validation { block: header.number, error }
+                        }
+                        err => StageError::Download(err.to_string()),
+                    },
+                )?;
             }
+            child = Some(parent);
         }
 
         Ok(())
@@ -265,6 +305,26 @@ impl<D: HeaderDownloader, C: Consensus, H: HeadersClient, S: StatusUpdater>
         Ok(latest)
     }
 
+    /// Validate the inserted headers in ascending order against their ancestors, which are not
+    /// known while the headers are downloaded in reverse.
+    fn validate_with_ancestors<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        head: &SealedHeader,
+    ) -> Result<(), StageError> {
+        let mut parent = head.clone();
+        let start_key = tx.get_block_numhash(head.number + 1)?;
+        for entry in tx.cursor::<tables::Headers>()?.walk(start_key)? {
+            let (key, header) = entry?;
+            let header = SealedHeader::new(header, key.hash());
+            self.consensus
+                .validate_header_with_ancestors(&header, &parent)
+                .map_err(|error| StageError::Validation { block: header.number, error })?;
+            parent = header;
+        }
+        Ok(())
+    }
+
     /// Iterate over inserted headers and write td entries
     fn write_td<DB: Database>(
         &self,
@@ -301,7 +361,10 @@ mod tests {
         PREV_STAGE_ID,
     };
     use assert_matches::assert_matches;
-    use reth_interfaces::{p2p::error::RequestError, test_utils::generators::random_header};
+    use reth_interfaces::{
+        p2p::error::RequestError,
+        test_utils::generators::{random_header, random_header_range},
+    };
     use test_runner::HeadersTestRunner;
 
     stage_test_suite!(HeadersTestRunner);
@@ -396,6 +459,32 @@ mod tests {
         assert!(runner.validate_execution(input, result.ok()).is_ok(), "validation failed");
     }
 
+    /// Check that the headers are validated across batches
+    #[test]
+    fn validate_header_batches() {
+        let runner = HeadersTestRunner::default();
+        let stage = runner.stage();
+
+        let head = random_header(0, None);
+        let mut headers = random_header_range(1..5, head.hash());
+        headers.reverse();
+        let (upper, lower) = headers.split_at(2);
+
+        assert_matches!(stage.validate_header_response(upper, None), Ok(()));
+        assert_matches!(stage.validate_header_response(lower, upper.last()), Ok(()));
+        // the upper batch doesn't connect to the lowest header
+        assert_matches!(
+            stage.validate_header_response(upper, lower.last()),
+            Err(StageError::Download(_))
+        );
+
+        runner.consensus.set_fail_validation(true);
+        assert_matches!(
+            stage.validate_header_response(lower, upper.last()),
+            Err(StageError::Validation { block, .. }) if block == upper[1].number
+        );
+    }
+
     /// Test the head and tip range lookup
     #[tokio::test]
     async fn head_and_tip_lookup() {
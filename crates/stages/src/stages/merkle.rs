@@ -0,0 +1,446 @@
+use crate::{
+    db::Transaction,
+    etl::{Collector, DEFAULT_BUFFER_CAPACITY},
+    DatabaseIntegrityError, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
+    UnwindOutput,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+    database::Database,
+    models::TransitionIdAddress,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{
+    keccak256, keccak256_all, Account, Address, BlockNumber, StorageEntry, TransitionId, H256, U256,
+};
+use reth_trie::StateRoot;
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+use thiserror::Error;
+use tracing::*;
+
+/// The id of the merkle stage.
+pub const MERKLE: StageId = StageId("Merkle");
+
+/// The number of plain state entries that are hashed at once when the hashed state is rebuilt.
+const HASHING_BATCH_SIZE: usize = 10_000;
+
+/// The merkle stage hashes the state and verifies the state root of the executed blocks.
+///
+/// The hashed state is kept in the [tables::HashedAccount] and [tables::HashedStorage] tables.
+/// On the first run, the hashed state is built from the whole plain state. Afterwards, only the
+/// accounts and storage slots that appear in the [tables::AccountChangeSet] and
+/// [tables::StorageChangeSet] of the executed blocks are rehashed from the plain state.
+///
+/// The branch nodes of the tries are kept in the [tables::AccountsTrie] and
+/// [tables::StoragesTrie] tables, so the state root after the first run is updated incrementally
+/// from the changed keys.
+///
+/// Input tables:
+/// [tables::PlainAccountState]
+/// [tables::PlainStorageState]
+/// [tables::AccountChangeSet]
+/// [tables::StorageChangeSet]
+/// [tables::Headers] to get the expected state root
+///
+/// Tables updated:
+/// [tables::HashedAccount]
+/// [tables::HashedStorage]
+/// [tables::AccountsTrie]
+/// [tables::StoragesTrie]
+///
+/// The stage has to run after the [ExecutionStage][crate::stages::execution::ExecutionStage], but
+/// unwinds before it, while the changesets are still present.
+#[derive(Debug, Default)]
+pub struct MerkleStage;
+
+#[derive(Error, Debug)]
+enum MerkleStageError {
+    #[error("State root mismatch in block {block}: got {got:?}, expected {expected:?}.")]
+    StateRootMismatch { block: BlockNumber, got: H256, expected: H256 },
+}
+
+impl From<MerkleStageError> for StageError {
+    fn from(error: MerkleStageError) -> Self {
+        StageError::Fatal(Box::new(error))
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for MerkleStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        MERKLE
+    }
+
+    /// Update the hashed state up to the progress of the previous stage and compare the resulting
+    /// state root with the state root of the header.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let previous_stage_progress = input.previous_stage_progress();
+
+        let state_root = match input.stage_progress {
+            Some(stage_progress) if stage_progress >= previous_stage_progress => {
+                info!(target: "sync::stages::merkle", stage_progress, "Target block already reached");
+                return Ok(ExecOutput { stage_progress, done: true })
+            }
+            Some(stage_progress) if stage_progress > 0 => {
+                debug!(target: "sync::stages::merkle", from = stage_progress, to = previous_stage_progress, "Updating hashed state");
+                let from_transition = tx.get_block_transition_by_num(stage_progress)? + 1;
+                let to_transition = tx.get_block_transition_by_num(previous_stage_progress)?;
+                update_hashed_state(tx, from_transition, to_transition)?;
+                StateRoot::incremental(&**tx, from_transition..=to_transition)?
+            }
+            _ => {
+                debug!(target: "sync::stages::merkle", to = previous_stage_progress, "Rebuilding hashed state");
+                rebuild_hashed_state(tx)?;
+                tx.clear::<tables::AccountsTrie>()?;
+                tx.clear::<tables::StoragesTrie>()?;
+                StateRoot::new(&**tx)
+            }
+        };
+
+        let key = tx.get_block_numhash(previous_stage_progress)?;
+        let header = tx
+            .get::<tables::Headers>(key)?
+            .ok_or(DatabaseIntegrityError::Header { number: key.number(), hash: key.hash() })?;
+        let (root, updates) = state_root.root_with_updates()?;
+        if root != header.state_root {
+            error!(target: "sync::stages::merkle", block = previous_stage_progress, got = ?root, expected = ?header.state_root, "State root mismatch");
+            return Err(MerkleStageError::StateRootMismatch {
+                block: previous_stage_progress,
+                got: root,
+                expected: header.state_root,
+            }
+            .into())
+        }
+
+        updates.flush(&**tx)?;
+        info!(target: "sync::stages::merkle", stage_progress = previous_stage_progress, ?root, "State root verified");
+        Ok(ExecOutput { stage_progress: previous_stage_progress, done: true })
+    }
+
+    /// Revert the hashed state and the stored trie nodes to the state after the `unwind_to` block,
+    /// using the old values from the changesets.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, Box<dyn std::error::Error + Send + Sync>> {
+        if input.unwind_to >= input.stage_progress {
+            return Ok(UnwindOutput { stage_progress: input.unwind_to })
+        }
+
+        let from_transition = tx.get_block_transition_by_num(input.unwind_to)? + 1;
+        let to_transition = tx.get_block_transition_by_num(input.stage_progress)?;
+
+        // the first change of an account or slot holds its value at the `unwind_to` block
+        let mut accounts = BTreeMap::<Address, Option<Account>>::new();
+        let mut account_changeset = tx.cursor::<tables::AccountChangeSet>()?;
+        for entry in account_changeset.walk_range(from_transition..=to_transition)? {
+            let (_, changeset) = entry?;
+            if let Entry::Vacant(entry) = accounts.entry(changeset.address) {
+                entry.insert(changeset.info);
+            }
+        }
+
+        let mut storage = BTreeMap::<(Address, H256), U256>::new();
+        let mut storage_changeset = tx.cursor::<tables::StorageChangeSet>()?;
+        let start = TransitionIdAddress::from((from_transition, Address::zero()));
+        let end = TransitionIdAddress::from((to_transition + 1, Address::zero()));
+        for entry in storage_changeset.walk_range(start..end)? {
+            let (key, StorageEntry { key: slot, value }) = entry?;
+            if let Entry::Vacant(entry) = storage.entry((key.address(), slot)) {
+                entry.insert(value);
+            }
+        }
+
+        for (address, account) in accounts {
+            write_hashed_account(tx, address, account)?;
+        }
+        for ((address, slot), value) in storage {
+            write_hashed_storage(tx, address, slot, value)?;
+        }
+
+        let (_, updates) =
+            StateRoot::incremental(&**tx, from_transition..=to_transition)?.root_with_updates()?;
+        updates.flush(&**tx)?;
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+/// Clears the hashed state and rebuilds it from the plain state.
+///
+/// Hashing scatters the keys, so the hashed entries are sorted with an ETL [Collector] and then
+/// appended to the empty tables.
+fn rebuild_hashed_state<DB: Database>(tx: &Transaction<'_, DB>) -> Result<(), StageError> {
+    tx.clear::<tables::HashedAccount>()?;
+    tx.clear::<tables::HashedStorage>()?;
+
+    let mut collector = Collector::new(DEFAULT_BUFFER_CAPACITY);
+    let mut accounts = tx.cursor::<tables::PlainAccountState>()?;
+    let mut batch = Vec::with_capacity(HASHING_BATCH_SIZE);
+    for entry in accounts.walk(Address::zero())? {
+        batch.push(entry?);
+        if batch.len() == HASHING_BATCH_SIZE {
+            collect_hashed_accounts(&mut collector, &mut batch)?;
+        }
+    }
+    collect_hashed_accounts(&mut collector, &mut batch)?;
+    let mut hashed_accounts = tx.cursor_mut::<tables::HashedAccount>()?;
+    for entry in collector.into_sorted_iter()? {
+        let (hashed_address, account) = entry?;
+        hashed_accounts.append(hashed_address, account)?;
+    }
+
+    let mut collector = Collector::new(DEFAULT_BUFFER_CAPACITY);
+    let mut storage = tx.cursor::<tables::PlainStorageState>()?;
+    let mut batch = Vec::with_capacity(HASHING_BATCH_SIZE);
+    for entry in storage.walk(Address::zero())? {
+        batch.push(entry?);
+        if batch.len() == HASHING_BATCH_SIZE {
+            collect_hashed_storage(&mut collector, &mut batch)?;
+        }
+    }
+    collect_hashed_storage(&mut collector, &mut batch)?;
+    let mut hashed_storage = tx.cursor_dup_mut::<tables::HashedStorage>()?;
+    for entry in collector.into_sorted_iter()? {
+        let (hashed_address, entry) = entry?;
+        hashed_storage.append_dup(hashed_address, entry)?;
+    }
+
+    Ok(())
+}
+
+/// Hashes the addresses of a batch of plain accounts and drains them into the collector.
+fn collect_hashed_accounts(
+    collector: &mut Collector<H256, Account>,
+    batch: &mut Vec<(Address, Account)>,
+) -> Result<(), StageError> {
+    let addresses: Vec<_> = batch.iter().map(|(address, _)| *address).collect();
+    for (hashed_address, (_, account)) in keccak256_all(&addresses).into_iter().zip(batch.drain(..))
+    {
+        collector.insert(hashed_address, account)?;
+    }
+    Ok(())
+}
+
+/// Hashes the addresses and slots of a batch of plain storage entries and drains them into the
+/// collector.
+fn collect_hashed_storage(
+    collector: &mut Collector<H256, StorageEntry>,
+    batch: &mut Vec<(Address, StorageEntry)>,
+) -> Result<(), StageError> {
+    let addresses: Vec<_> = batch.iter().map(|(address, _)| *address).collect();
+    let slots: Vec<_> = batch.iter().map(|(_, entry)| entry.key).collect();
+    let hashed = keccak256_all(&addresses).into_iter().zip(keccak256_all(&slots));
+    for ((hashed_address, hashed_slot), (_, entry)) in hashed.zip(batch.drain(..)) {
+        collector.insert(hashed_address, StorageEntry { key: hashed_slot, value: entry.value })?;
+    }
+    Ok(())
+}
+
+/// Rehashes the accounts and storage slots that were changed in the given transition range from
+/// the plain state.
+fn update_hashed_state<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    from_transition: TransitionId,
+    to_transition: TransitionId,
+) -> Result<(), StageError> {
+    let mut changed_accounts = BTreeSet::new();
+    let mut account_changeset = tx.cursor::<tables::AccountChangeSet>()?;
+    for entry in account_changeset.walk_range(from_transition..=to_transition)? {
+        let (_, changeset) = entry?;
+        changed_accounts.insert(changeset.address);
+    }
+
+    let mut changed_storage = BTreeSet::new();
+    let mut storage_changeset = tx.cursor::<tables::StorageChangeSet>()?;
+    let start = TransitionIdAddress::from((from_transition, Address::zero()));
+    let end = TransitionIdAddress::from((to_transition + 1, Address::zero()));
+    for entry in storage_changeset.walk_range(start..end)? {
+        let (key, entry) = entry?;
+        changed_storage.insert((key.address(), entry.key));
+    }
+
+    trace!(target: "sync::stages::merkle", accounts = changed_accounts.len(), slots = changed_storage.len(), "Rehashing changed state");
+    for address in changed_accounts {
+        let account = tx.get::<tables::PlainAccountState>(address)?;
+        write_hashed_account(tx, address, account)?;
+    }
+
+    let mut plain_storage = tx.cursor_dup::<tables::PlainStorageState>()?;
+    for (address, slot) in changed_storage {
+        let value = plain_storage
+            .seek_by_key_subkey(address, slot)?
+            .filter(|entry| entry.key == slot)
+            .map(|entry| entry.value)
+            .unwrap_or_default();
+        write_hashed_storage(tx, address, slot, value)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the account to the hashed state. A removed account also loses its storage.
+fn write_hashed_account<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    address: Address,
+    account: Option<Account>,
+) -> Result<(), StageError> {
+    let hashed_address = keccak256(address);
+    match account {
+        Some(account) => tx.put::<tables::HashedAccount>(hashed_address, account)?,
+        None => {
+            tx.delete::<tables::HashedAccount>(hashed_address, None)?;
+            tx.delete::<tables::HashedStorage>(hashed_address, None)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the storage slot to the hashed state. Zero values are removed.
+fn write_hashed_storage<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    address: Address,
+    slot: H256,
+    value: U256,
+) -> Result<(), StageError> {
+    let hashed_address = keccak256(address);
+    let hashed_slot = keccak256(slot);
+
+    // Always delete old value as duplicate table put will not override it
+    let mut hashed_storage = tx.cursor_dup::<tables::HashedStorage>()?;
+    if let Some(old) = hashed_storage
+        .seek_by_key_subkey(hashed_address, hashed_slot)?
+        .filter(|entry| entry.key == hashed_slot)
+    {
+        tx.delete::<tables::HashedStorage>(hashed_address, Some(old))?;
+    }
+    if !value.is_zero() {
+        tx.put::<tables::HashedStorage>(hashed_address, StorageEntry { key: hashed_slot, value })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::AccountBeforeTx,
+    };
+    use reth_primitives::{
+        proofs::{calculate_state_root, calculate_storage_root, EMPTY_ROOT},
+        Header, KECCAK_EMPTY,
+    };
+
+    /// Inserts a canonical header with the given state root and last transition.
+    fn insert_header<DB: Database>(
+        tx: &Transaction<'_, DB>,
+        number: BlockNumber,
+        state_root: H256,
+        transition: TransitionId,
+    ) {
+        let header = Header { number, state_root, ..Default::default() };
+        let hash = header.hash_slow();
+        tx.put::<tables::CanonicalHeaders>(number, hash).unwrap();
+        tx.put::<tables::Headers>((number, hash).into(), header).unwrap();
+        tx.put::<tables::BlockTransitionIndex>((number, hash).into(), transition).unwrap();
+    }
+
+    /// Calculates the state root of the hashed state.
+    fn state_root<DB: Database>(tx: &Transaction<'_, DB>) -> Result<H256, StageError> {
+        Ok(StateRoot::new(&**tx).root()?)
+    }
+
+    fn expected_root(accounts: &[(Address, Account, Vec<(H256, U256)>)]) -> H256 {
+        calculate_state_root(accounts.iter().map(|(address, account, storage)| {
+            let storage = storage.iter().map(|(slot, value)| (keccak256(slot), *value));
+            (keccak256(address), *account, calculate_storage_root(storage))
+        }))
+    }
+
+    #[tokio::test]
+    async fn rebuild_update_and_unwind() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_low_u64_be(1);
+        let contract = Address::from_low_u64_be(2);
+        let slot = H256::from_low_u64_be(3);
+        let account = Account { nonce: 0, balance: U256::from(100), bytecode_hash: None };
+        let code = Account { nonce: 1, balance: U256::zero(), bytecode_hash: Some(KECCAK_EMPTY) };
+
+        // genesis with a funded account
+        tx.put::<tables::PlainAccountState>(address, account).unwrap();
+        let genesis_root = expected_root(&[(address, account, vec![])]);
+        insert_header(&tx, 0, genesis_root, 0);
+
+        let input =
+            ExecInput { previous_stage: Some((StageId("Execution"), 0)), stage_progress: None };
+        let output = MerkleStage.execute(&mut tx, input).await.unwrap();
+        assert_eq!(output, ExecOutput { stage_progress: 0, done: true });
+
+        // block 1 creates a contract with a storage slot
+        let sent = Account { nonce: 1, balance: U256::from(90), ..account };
+        tx.put::<tables::PlainAccountState>(address, sent).unwrap();
+        tx.put::<tables::PlainAccountState>(contract, code).unwrap();
+        tx.put::<tables::PlainStorageState>(
+            contract,
+            StorageEntry { key: slot, value: U256::from(7) },
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSet>(1, AccountBeforeTx { address, info: Some(account) })
+            .unwrap();
+        tx.put::<tables::AccountChangeSet>(1, AccountBeforeTx { address: contract, info: None })
+            .unwrap();
+        tx.put::<tables::StorageChangeSet>(
+            TransitionIdAddress((1, contract)),
+            StorageEntry { key: slot, value: U256::zero() },
+        )
+        .unwrap();
+        let root = expected_root(&[
+            (address, sent, vec![]),
+            (contract, code, vec![(slot, U256::from(7))]),
+        ]);
+        insert_header(&tx, 1, root, 1);
+
+        let input =
+            ExecInput { previous_stage: Some((StageId("Execution"), 1)), stage_progress: Some(0) };
+        let output = MerkleStage.execute(&mut tx, input).await.unwrap();
+        assert_eq!(output, ExecOutput { stage_progress: 1, done: true });
+
+        // the rebuilt and the updated hashed state are the same
+        rebuild_hashed_state(&tx).unwrap();
+        assert_eq!(state_root(&tx).unwrap(), root);
+
+        // the unwind reverts the hashed state to the genesis
+        let input = UnwindInput { stage_progress: 1, unwind_to: 0, bad_block: None };
+        let output = MerkleStage.unwind(&mut tx, input).await.unwrap();
+        assert_eq!(output, UnwindOutput { stage_progress: 0 });
+        assert_eq!(state_root(&tx).unwrap(), genesis_root);
+        assert!(tx.get::<tables::HashedStorage>(keccak256(contract)).unwrap().is_none());
+        assert_ne!(genesis_root, EMPTY_ROOT);
+    }
+
+    #[tokio::test]
+    async fn state_root_mismatch() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let account = Account { nonce: 0, balance: U256::from(100), bytecode_hash: None };
+        tx.put::<tables::PlainAccountState>(Address::from_low_u64_be(1), account).unwrap();
+        insert_header(&tx, 0, EMPTY_ROOT, 0);
+
+        let input =
+            ExecInput { previous_stage: Some((StageId("Execution"), 0)), stage_progress: None };
+        let err = MerkleStage.execute(&mut tx, input).await.unwrap_err();
+        assert!(err.is_fatal());
+        assert!(err.to_string().contains("State root mismatch in block 0"));
+    }
+}
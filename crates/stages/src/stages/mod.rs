@@ -1,8 +1,24 @@
+use crate::StageId;
+
 /// The bodies stage.
 pub mod bodies;
 /// The execution stage that generates state diff.
 pub mod execution;
 /// The headers stage.
 pub mod headers;
+/// The merkle stage that hashes the state and verifies the state root.
+pub mod merkle;
 /// The sender recovery stage.
 pub mod sender_recovery;
+/// The transaction lookup stage that indexes transactions by hash.
+pub mod tx_lookup;
+
+/// The ids of the stages of the sync pipeline, in the order they are run.
+pub const STAGE_IDS: [StageId; 6] = [
+    headers::HEADERS,
+    bodies::BODIES,
+    sender_recovery::SENDER_RECOVERY,
+    execution::EXECUTION,
+    merkle::MERKLE,
+    tx_lookup::TRANSACTION_LOOKUP,
+];
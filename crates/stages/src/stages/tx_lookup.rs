@@ -0,0 +1,317 @@
+use crate::{
+    db::Transaction,
+    etl::{Collector, DEFAULT_BUFFER_CAPACITY},
+    DatabaseIntegrityError, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
+    UnwindOutput,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    models::StoredTxLocation,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use tracing::*;
+
+/// The id of the transaction lookup stage.
+pub const TRANSACTION_LOOKUP: StageId = StageId("TransactionLookup");
+
+/// The transaction lookup stage builds the index of canonical transactions by their hash.
+///
+/// For every transaction of the synced blocks, the transaction number and the number of the
+/// including block are stored in the [`TxHashNumber`][reth_db::tables::TxHashNumber] table, which
+/// makes transactions and receipts retrievable by hash without scanning the chain. The hashes are
+/// sorted with an ETL [Collector] first, so they are inserted in key order.
+///
+/// If `prune` is set, the stage doesn't build the index and removes any existing entries. Blocks
+/// that were synced while pruning was enabled are not indexed retroactively.
+#[derive(Debug)]
+pub struct TransactionLookupStage {
+    /// The maximum number of blocks to process before committing.
+    pub commit_threshold: u64,
+    /// Whether the index should be pruned instead of built.
+    pub prune: bool,
+}
+
+impl Default for TransactionLookupStage {
+    fn default() -> Self {
+        Self { commit_threshold: 50_000, prune: false }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for TransactionLookupStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        TRANSACTION_LOOKUP
+    }
+
+    /// Insert the hash of every transaction in the block range into the
+    /// [`TxHashNumber`][reth_db::tables::TxHashNumber] table.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let stage_progress = input.stage_progress.unwrap_or_default();
+        let previous_stage_progress = input.previous_stage_progress();
+
+        if self.prune {
+            tx.clear::<tables::TxHashNumber>()?;
+            info!(target: "sync::stages::tx_lookup", stage_progress = previous_stage_progress, "Transaction index pruned");
+            return Ok(ExecOutput { stage_progress: previous_stage_progress, done: true })
+        }
+
+        let max_block_num = previous_stage_progress.min(stage_progress + self.commit_threshold);
+        if max_block_num <= stage_progress {
+            info!(target: "sync::stages::tx_lookup", target = max_block_num, stage_progress, "Target block already reached");
+            return Ok(ExecOutput { stage_progress, done: true })
+        }
+
+        let mut collector = Collector::new(DEFAULT_BUFFER_CAPACITY);
+        let mut tx_cursor = tx.cursor::<tables::Transactions>()?;
+        for block_number in stage_progress + 1..=max_block_num {
+            let body = tx.get_block_body_by_num(block_number)?;
+            for tx_number in body.tx_id_range() {
+                let (_, transaction) = tx_cursor
+                    .seek_exact(tx_number)?
+                    .ok_or(DatabaseIntegrityError::Transaction { id: tx_number })?;
+                trace!(target: "sync::stages::tx_lookup", block_number, tx_number, hash = ?transaction.hash, "Indexing transaction");
+                collector.insert(transaction.hash, StoredTxLocation { tx_number, block_number })?;
+            }
+        }
+
+        // Appending is only possible into an empty table, the later batches are upserted in key
+        // order instead.
+        trace!(target: "sync::stages::tx_lookup", transactions = collector.len(), "Inserting sorted transaction hashes");
+        let mut lookup_cursor = tx.cursor_mut::<tables::TxHashNumber>()?;
+        let append = lookup_cursor.last()?.is_none();
+        for entry in collector.into_sorted_iter()? {
+            let (hash, location) = entry?;
+            if append {
+                lookup_cursor.append(hash, location)?;
+            } else {
+                lookup_cursor.upsert(hash, location)?;
+            }
+        }
+
+        let done = max_block_num >= previous_stage_progress;
+        info!(target: "sync::stages::tx_lookup", stage_progress = max_block_num, done, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: max_block_num, done })
+    }
+
+    /// Remove the hashes of all transactions after the `unwind_to` block from the index.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, Box<dyn std::error::Error + Send + Sync>> {
+        let body = tx.get_block_body_by_num(input.unwind_to)?;
+        let first_unwound_tx = body.start_tx_id + body.tx_count;
+
+        let mut tx_cursor = tx.cursor::<tables::Transactions>()?;
+        for entry in tx_cursor.walk(first_unwound_tx)? {
+            let (_, transaction) = entry?;
+            tx.delete::<tables::TxHashNumber>(transaction.hash, None)?;
+        }
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::models::StoredBlockBody;
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_primitives::{BlockNumber, SealedBlock, H256};
+
+    stage_test_suite_ext!(TransactionLookupTestRunner);
+
+    /// Execute the stage twice with input range that exceeds the commit threshold
+    #[tokio::test]
+    async fn execute_intermediate_commit() {
+        let threshold = 50;
+        let mut runner = TransactionLookupTestRunner::default();
+        runner.threshold = threshold;
+        let (stage_progress, previous_stage) = (1000, 1100); // input exceeds threshold
+        let first_input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(stage_progress),
+        };
+
+        // Seed only once with full input range
+        runner.seed_execution(first_input).expect("failed to seed execution");
+
+        // Execute first time
+        let result = runner.execute(first_input).await.unwrap();
+        let expected_progress = stage_progress + threshold;
+        assert_matches!(
+            result,
+            Ok(ExecOutput { done: false, stage_progress })
+                if stage_progress == expected_progress
+        );
+
+        // Execute second time
+        let second_input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(expected_progress),
+        };
+        let result = runner.execute(second_input).await.unwrap();
+        assert_matches!(
+            result,
+            Ok(ExecOutput { done: true, stage_progress })
+                if stage_progress == previous_stage
+        );
+
+        assert!(runner.validate_execution(first_input, result.ok()).is_ok(), "validation failed");
+    }
+
+    /// Pruning removes the existing index and skips to the target
+    #[tokio::test]
+    async fn execute_prune() {
+        let (stage_progress, previous_stage) = (100, 200);
+        let mut runner = TransactionLookupTestRunner { prune: true, ..Default::default() };
+        let input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(stage_progress),
+        };
+        runner.seed_execution(input).expect("failed to seed execution");
+
+        let result = runner.execute(input).await.unwrap();
+        assert_matches!(
+            result,
+            Ok(ExecOutput { done: true, stage_progress }) if stage_progress == previous_stage
+        );
+        assert!(runner.tx.table_is_empty::<tables::TxHashNumber>().unwrap());
+    }
+
+    struct TransactionLookupTestRunner {
+        tx: TestTransaction,
+        threshold: u64,
+        prune: bool,
+    }
+
+    impl Default for TransactionLookupTestRunner {
+        fn default() -> Self {
+            Self { tx: TestTransaction::default(), threshold: 1000, prune: false }
+        }
+    }
+
+    impl StageTestRunner for TransactionLookupTestRunner {
+        type S = TransactionLookupStage;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            TransactionLookupStage { commit_threshold: self.threshold, prune: self.prune }
+        }
+    }
+
+    impl ExecuteStageTestRunner for TransactionLookupTestRunner {
+        type Seed = Vec<SealedBlock>;
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress() + 1;
+
+            let blocks = random_block_range(stage_progress..end, H256::zero(), 0..2);
+
+            let mut current_tx_id = 0;
+            blocks.iter().try_for_each(|b| -> Result<(), TestRunnerError> {
+                current_tx_id = self.insert_block(current_tx_id, b, b.number == stage_progress)?;
+                Ok(())
+            })?;
+            Ok(blocks)
+        }
+
+        fn validate_execution(
+            &self,
+            input: ExecInput,
+            output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            if let Some(output) = output {
+                self.tx.query(|tx| {
+                    let start_block = input.stage_progress.unwrap_or_default() + 1;
+                    for block_number in start_block..=output.stage_progress {
+                        let hash = tx.get::<tables::CanonicalHeaders>(block_number)?.unwrap();
+                        let body = tx.get::<tables::BlockBodies>((block_number, hash).into())?;
+                        for tx_number in body.expect("no body entry").tx_id_range() {
+                            let transaction = tx
+                                .get::<tables::Transactions>(tx_number)?
+                                .expect("no transaction entry");
+                            assert_eq!(
+                                tx.get::<tables::TxHashNumber>(transaction.hash)?,
+                                Some(StoredTxLocation { tx_number, block_number })
+                            );
+                        }
+                    }
+                    Ok(())
+                })?;
+            } else {
+                self.check_no_hashes_by_block(input.stage_progress.unwrap_or_default())?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl UnwindStageTestRunner for TransactionLookupTestRunner {
+        fn validate_unwind(&self, input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.check_no_hashes_by_block(input.unwind_to)
+        }
+    }
+
+    impl TransactionLookupTestRunner {
+        fn check_no_hashes_by_block(&self, block: BlockNumber) -> Result<(), TestRunnerError> {
+            self.tx.check_no_entry_above_by_value::<tables::TxHashNumber, _>(block, |value| {
+                value.block_number
+            })?;
+            Ok(())
+        }
+
+        fn insert_block(
+            &self,
+            tx_offset: u64,
+            block: &SealedBlock,
+            insert_hashes: bool,
+        ) -> Result<u64, TestRunnerError> {
+            let mut current_tx_id = tx_offset;
+            let txs = block.body.clone();
+
+            self.tx.commit(|tx| {
+                let numhash = block.header.num_hash().into();
+                tx.put::<tables::CanonicalHeaders>(block.number, block.hash())?;
+                tx.put::<tables::BlockBodies>(
+                    numhash,
+                    StoredBlockBody { start_tx_id: current_tx_id, tx_count: txs.len() as u64 },
+                )?;
+
+                for body_tx in txs {
+                    // Insert hashes for previous stage progress
+                    if insert_hashes {
+                        tx.put::<tables::TxHashNumber>(
+                            body_tx.hash,
+                            StoredTxLocation {
+                                tx_number: current_tx_id,
+                                block_number: block.number,
+                            },
+                        )?;
+                    }
+                    tx.put::<tables::Transactions>(current_tx_id, body_tx)?;
+                    current_tx_id += 1;
+                }
+                Ok(())
+            })?;
+
+            Ok(current_tx_id)
+        }
+    }
+}
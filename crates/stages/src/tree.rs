@@ -0,0 +1,756 @@
+use crate::{
+    db::Transaction,
+    stages::{
+        bodies::BODIES,
+        execution::{write_execution_result, EXECUTION},
+        headers::HEADERS,
+        sender_recovery::SENDER_RECOVERY,
+    },
+    BlockchainTreeError, Pipeline, PipelineError,
+};
+use lru::LruCache;
+use reth_db::{
+    database::Database,
+    models::BlockNumHash,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_executor::{
+    evm_config::{EthEvmConfig, EvmConfig},
+    executor::ExecutionResult,
+    metrics::ExecutionMetrics,
+    revm_wrap::{State, SubState},
+    state::ChainStateProvider,
+};
+use reth_interfaces::consensus::Consensus;
+use reth_primitives::{
+    BlockHash, BlockNumber, ChainSpec, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    TransactionSignedEcRecovered, U256,
+};
+use reth_provider::{
+    canon_blocks, insert_canonical_block, CanonBlock, ProviderImpl, StateProviderFactory,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    num::NonZeroUsize,
+    ops::Deref,
+    sync::Arc,
+    time::Instant,
+};
+use tracing::*;
+
+mod chain;
+
+use chain::SideChain;
+
+/// The identifier of a [SideChain] in the tree.
+type ChainId = u64;
+
+/// The default maximum number of blocks with an unknown parent that are buffered.
+pub const DEFAULT_MAX_BUFFERED_BLOCKS: usize = 256;
+
+/// The outcome of inserting a block into the [BlockchainTree].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    /// The block was executed on top of its parent and added to a side chain.
+    Executed,
+    /// The parent of the block is unknown, so the block is buffered until the parent is inserted.
+    Buffered,
+    /// The block is already canonical or in the tree.
+    Known,
+}
+
+/// A tree of the blocks that are not part of the canonical chain (yet).
+///
+/// The canonical chain is stored in the database and synced by the [Pipeline]. The tree keeps the
+/// blocks that extend the canonical chain or fork from it in memory, as side chains that fork
+/// from a block of the canonical chain. A block is validated and executed on top of the state of
+/// its parent when it is inserted, and buffered if its parent is unknown. Once the buffer is full,
+/// the least recently buffered block is dropped.
+///
+/// Once the fork choice changes, a side chain can be made canonical (see
+/// [BlockchainTree::make_canonical]): the canonical chain is unwound to the fork block and the
+/// blocks of the side chain are committed to the database together with the results of their
+/// execution, then the remaining stages of the pipeline are run. The blocks of the old canonical
+/// chain stay in the tree as a side chain.
+///
+/// The blocks below the finalized block can't become canonical anymore, so the side chains that
+/// fork from them are removed (see [BlockchainTree::finalize_block]).
+pub struct BlockchainTree<DB: Database, C: Consensus> {
+    /// The database of the canonical chain.
+    db: Arc<DB>,
+    /// Consensus client implementation.
+    consensus: Arc<C>,
+    /// The chain specification the blocks are executed with.
+    chain_spec: ChainSpec,
+    /// The configuration of the EVM the blocks are executed with.
+    evm_config: Arc<dyn EvmConfig>,
+    /// The throughput and latency of the executed blocks.
+    metrics: ExecutionMetrics,
+    /// The side chains.
+    chains: HashMap<ChainId, SideChain>,
+    /// The side chain each block was executed in.
+    block_chains: HashMap<BlockHash, ChainId>,
+    /// The blocks with an unknown parent by their hash.
+    buffered: LruCache<BlockHash, SealedBlockWithSenders>,
+    /// The hashes of the buffered blocks by the hash of their parent.
+    buffered_children: HashMap<BlockHash, HashSet<BlockHash>>,
+    /// The identifier of the next side chain.
+    next_chain_id: ChainId,
+    /// The last finalized block.
+    finalized: BlockNumber,
+}
+
+impl<DB: Database, C: Consensus> std::fmt::Debug for BlockchainTree<DB, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockchainTree")
+            .field("chains", &self.chains.len())
+            .field("buffered", &self.buffered.len())
+            .field("finalized", &self.finalized)
+            .finish()
+    }
+}
+
+impl<DB: Database, C: Consensus> BlockchainTree<DB, C> {
+    /// Create an empty tree on top of the canonical chain in the database.
+    pub fn new(db: Arc<DB>, consensus: Arc<C>, chain_spec: ChainSpec) -> Self {
+        Self {
+            db,
+            consensus,
+            chain_spec,
+            evm_config: Arc::new(EthEvmConfig),
+            metrics: ExecutionMetrics::new("tree"),
+            chains: HashMap::new(),
+            block_chains: HashMap::new(),
+            buffered: LruCache::new(
+                NonZeroUsize::new(DEFAULT_MAX_BUFFERED_BLOCKS).expect("limit is not zero"),
+            ),
+            buffered_children: HashMap::new(),
+            next_chain_id: 0,
+            finalized: 0,
+        }
+    }
+
+    /// Set the configuration of the EVM the blocks are executed with.
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.evm_config = evm_config;
+        self
+    }
+
+    /// Set the maximum number of blocks with an unknown parent that are buffered.
+    pub fn with_max_buffered_blocks(mut self, max_buffered_blocks: NonZeroUsize) -> Self {
+        self.buffered.resize(max_buffered_blocks);
+        self
+    }
+
+    /// The chain specification the blocks are executed with.
+    pub fn chain_spec(&self) -> &ChainSpec {
+        &self.chain_spec
+    }
+
+    /// Returns `true` if the block is in a side chain of the tree.
+    pub fn contains(&self, hash: BlockHash) -> bool {
+        self.block_chains.contains_key(&hash)
+    }
+
+    /// Insert a block into the tree.
+    ///
+    /// The block is executed if its parent is in the tree or a block of the canonical chain that
+    /// was executed already, and buffered otherwise. The buffered descendants of the block are
+    /// inserted after it, parents before their children.
+    ///
+    /// The senders of the transactions are recovered once, before the block is buffered or
+    /// executed.
+    pub fn insert_block(&mut self, block: SealedBlock) -> Result<BlockStatus, BlockchainTreeError> {
+        let number = block.number;
+        let block = block
+            .try_seal_with_senders()
+            .map_err(|_| BlockchainTreeError::SenderRecovery { block: number })?;
+        self.insert_block_with_senders(block)
+    }
+
+    /// Insert a block whose senders were recovered already into the tree, see
+    /// [BlockchainTree::insert_block].
+    pub fn insert_block_with_senders(
+        &mut self,
+        block: SealedBlockWithSenders,
+    ) -> Result<BlockStatus, BlockchainTreeError> {
+        let hash = block.hash();
+        let status = self.try_insert_block(block)?;
+        if status != BlockStatus::Executed {
+            return Ok(status)
+        }
+
+        // the buffered descendants can be executed now
+        let mut queue = self.take_buffered_children(hash);
+        while let Some(child) = queue.pop_front() {
+            let (number, child_hash) = (child.number, child.hash());
+            match self.try_insert_block(child) {
+                Ok(BlockStatus::Executed) => queue.extend(self.take_buffered_children(child_hash)),
+                Ok(_) => {}
+                Err(error) => {
+                    warn!(target: "sync::tree", number, hash = ?child_hash, %error, "Dropping invalid buffered block");
+                }
+            }
+        }
+        Ok(status)
+    }
+
+    /// Execute or buffer a single block, see [BlockchainTree::insert_block].
+    fn try_insert_block(
+        &mut self,
+        block: SealedBlockWithSenders,
+    ) -> Result<BlockStatus, BlockchainTreeError> {
+        let hash = block.hash();
+        // the canonical blocks below the finalized block are known as well
+        if self.contains(hash) || self.canonical_number(hash)?.is_some() {
+            return Ok(BlockStatus::Known)
+        }
+        if block.number <= self.finalized {
+            return Err(BlockchainTreeError::BelowFinalized {
+                block: block.number,
+                finalized: self.finalized,
+            })
+        }
+
+        if let Some(chain_id) = self.block_chains.get(&block.parent_hash).copied() {
+            self.insert_into_chain(chain_id, block)?;
+        } else if let Some(number) = self.canonical_number(block.parent_hash)? {
+            self.insert_on_canonical(BlockNumHash((number, block.parent_hash)), block)?;
+        } else {
+            trace!(target: "sync::tree", number = block.number, ?hash, "Buffering block with unknown parent");
+            self.buffer_block(block);
+            return Ok(BlockStatus::Buffered)
+        }
+        Ok(BlockStatus::Executed)
+    }
+
+    /// Remove the side chains and buffered blocks that can't become canonical anymore, because
+    /// they fork below the finalized block.
+    pub fn finalize_block(&mut self, finalized: BlockNumber) {
+        self.finalized = finalized;
+
+        let removed = self
+            .chains
+            .iter()
+            .filter(|(_, chain)| chain.fork.number() < finalized)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for chain_id in removed {
+            self.remove_chain(chain_id);
+        }
+        let removed = self
+            .buffered
+            .iter()
+            .filter(|(_, block)| block.number <= finalized)
+            .map(|(hash, block)| (*hash, block.parent_hash))
+            .collect::<Vec<_>>();
+        for (hash, parent_hash) in removed {
+            self.buffered.pop(&hash);
+            self.remove_buffered_child(parent_hash, hash);
+        }
+    }
+
+    /// Make the block and its ancestors canonical.
+    ///
+    /// The canonical chain is unwound to the block the side chain forks from and the side chain
+    /// is committed to the database with the results of its execution, in a single transaction.
+    /// If that fails, the database and the tree are left unchanged. Otherwise the unwound blocks
+    /// and the descendants of the block stay in the tree and the stages that didn't process the
+    /// committed blocks yet are run.
+    ///
+    /// The blocks of the unwound chain are executed again, so this should be run on a blocking
+    /// task.
+    pub async fn make_canonical(
+        &mut self,
+        pipeline: &mut Pipeline<DB>,
+        hash: BlockHash,
+    ) -> Result<(), BlockchainTreeError> {
+        let chain_id = match self.block_chains.get(&hash) {
+            Some(chain_id) => *chain_id,
+            None if self.canonical_number(hash)?.is_some() => return Ok(()),
+            None => return Err(BlockchainTreeError::UnknownBlock { hash }),
+        };
+        let mut chain = self.remove_chain(chain_id).expect("chain of the block exists");
+        let descendants = chain.split_after(hash);
+        let fork = chain.fork;
+        let tip = chain.blocks.last().expect("chain contains the block").block.number;
+
+        debug!(target: "sync::tree", fork = fork.number(), tip, ?hash, "Making side chain canonical");
+        let unwound = match self.commit_chain(pipeline, &chain).await {
+            Ok(unwound) => unwound,
+            Err(err) => {
+                // nothing was written, the side chain stays in the tree
+                chain.blocks.extend(descendants);
+                self.add_chain(chain);
+                return Err(err)
+            }
+        };
+
+        // re-anchor the side chains to the new canonical chain, the chains that forked from an
+        // unwound block are executed again on top of the unwound blocks
+        let mut reinserted = unwound
+            .into_iter()
+            .map(|block| SealedBlockWithSenders { block: block.block, senders: block.senders })
+            .collect::<Vec<_>>();
+        reinserted.extend(descendants.into_iter().map(|tree_block| tree_block.block));
+        let chain_ids = self.chains.keys().copied().collect::<Vec<_>>();
+        for chain_id in chain_ids {
+            if self.canonical_number(self.chains[&chain_id].fork.hash())?.is_none() {
+                let chain = self.remove_chain(chain_id).expect("chain exists");
+                reinserted.extend(chain.blocks.into_iter().map(|tree_block| tree_block.block));
+                continue
+            }
+
+            let tx = self.db.tx()?;
+            let chain = self.chains.get_mut(&chain_id).expect("chain exists");
+            let removed = chain.remove_canonical(|block| {
+                Ok::<_, BlockchainTreeError>(
+                    tx.get::<tables::CanonicalHeaders>(block.number)? == Some(block.hash()),
+                )
+            })?;
+            tx.commit()?;
+            for tree_block in removed {
+                self.block_chains.remove(&tree_block.block.hash());
+            }
+            if self.chains[&chain_id].blocks.is_empty() {
+                self.remove_chain(chain_id);
+            }
+        }
+
+        reinserted.sort_by_key(|block| block.number);
+        for block in reinserted {
+            let (number, block_hash) = (block.number, block.hash());
+            if let Err(error) = self.insert_block_with_senders(block) {
+                warn!(target: "sync::tree", number, hash = ?block_hash, %error, "Dropping block of an old chain");
+            }
+        }
+
+        pipeline.run_to(self.db.clone(), tip).await?;
+        Ok(())
+    }
+
+    /// Execute the block on top of a block of the side chain.
+    fn insert_into_chain(
+        &mut self,
+        chain_id: ChainId,
+        block: SealedBlockWithSenders,
+    ) -> Result<(), BlockchainTreeError> {
+        let chain = &self.chains[&chain_id];
+        let parent = chain.header(block.parent_hash).expect("parent is in the chain").clone();
+        let (chain_id, result) = if chain.tip_hash() == block.parent_hash {
+            let result = self.execute(chain, &parent, &block)?;
+            (chain_id, result)
+        } else {
+            // the block forks from the middle of the side chain
+            let forked = chain.fork_at(block.parent_hash);
+            let result = self.execute(&forked, &parent, &block)?;
+            (self.add_chain(forked), result)
+        };
+
+        self.block_chains.insert(block.hash(), chain_id);
+        self.chains.get_mut(&chain_id).expect("chain exists").push(block, result);
+        Ok(())
+    }
+
+    /// Execute the block on top of a block of the canonical chain.
+    fn insert_on_canonical(
+        &mut self,
+        fork: BlockNumHash,
+        block: SealedBlockWithSenders,
+    ) -> Result<(), BlockchainTreeError> {
+        let tx = self.db.tx()?;
+        let executed_tip = EXECUTION.get_progress(&tx)?.unwrap_or_default();
+        let parent = tx.get::<tables::Headers>(fork)?;
+        tx.commit()?;
+        let parent = match parent {
+            Some(parent) if fork.number() <= executed_tip => SealedHeader::new(parent, fork.hash()),
+            _ => {
+                return Err(BlockchainTreeError::ParentNotExecuted {
+                    block: block.number,
+                    parent: fork.hash(),
+                })
+            }
+        };
+
+        let mut chain = SideChain::new(fork);
+        let result = self.execute(&chain, &parent, &block)?;
+        chain.push(block, result);
+        self.add_chain(chain);
+        Ok(())
+    }
+
+    /// Validate the block and execute it on top of the state of the side chain.
+    fn execute(
+        &self,
+        chain: &SideChain,
+        parent: &SealedHeader,
+        block: &SealedBlockWithSenders,
+    ) -> Result<ExecutionResult, BlockchainTreeError> {
+        let validation = |error| BlockchainTreeError::Validation { block: block.number, error };
+        self.consensus.validate_header(&block.header, parent).map_err(validation)?;
+        self.consensus.validate_header_with_ancestors(&block.header, parent).map_err(validation)?;
+        self.consensus.pre_validate_block(block).map_err(validation)?;
+
+        let transactions = block
+            .transactions_with_sender()
+            .map(|(sender, transaction)| {
+                TransactionSignedEcRecovered::from_signed_transaction(transaction.clone(), *sender)
+            })
+            .collect::<Vec<_>>();
+        let ommers = block.ommers.iter().map(|ommer| ommer.as_ref().clone()).collect::<Vec<_>>();
+
+        let provider = ProviderImpl::new(self.db.clone());
+        let fork_state = provider.history_by_block_hash(chain.fork.hash())?;
+        let state_provider =
+            SubState::new(State::new(ChainStateProvider::new(&chain.state, fork_state)));
+
+        trace!(target: "sync::tree", number = block.number, txs = transactions.len(), "Executing block");
+        let started_at = Instant::now();
+        // revm can exceed the default stack size, see the execution stage
+        let result: ExecutionResult = std::thread::scope(|scope| {
+            let handle = std::thread::Builder::new()
+                .stack_size(50 * 1024 * 1024)
+                .spawn_scoped(scope, || {
+                    reth_executor::executor::execute_and_verify_receipt(
+                        &block.header,
+                        &transactions,
+                        &ommers,
+                        block.withdrawals.as_deref(),
+                        &self.chain_spec,
+                        state_provider,
+                        self.evm_config.as_ref(),
+                    )
+                })
+                .expect("Expects that thread name is not null");
+            handle.join().expect("Expects for thread to not panic")
+        })
+        .map_err(|error| BlockchainTreeError::Execution { block: block.number, error })?;
+        self.metrics.record_block(block.gas_used, transactions.len(), started_at.elapsed());
+
+        Ok(result)
+    }
+
+    /// Unwind the canonical chain to the block the side chain forks from and write the blocks of
+    /// the side chain with the results of their execution, in a single transaction.
+    ///
+    /// The blocks are marked as processed by the stages that store and execute them, the
+    /// remaining stages are run by the pipeline afterwards. Nothing is written if an error
+    /// occurs.
+    ///
+    /// Returns the blocks that were removed from the canonical chain.
+    async fn commit_chain(
+        &self,
+        pipeline: &mut Pipeline<DB>,
+        chain: &SideChain,
+    ) -> Result<Vec<CanonBlock>, BlockchainTreeError> {
+        let fork = chain.fork;
+        let mut tx = Transaction::new(self.db.as_ref())?;
+
+        // the blocks were executed on top of the state of the fork block
+        let executed_tip = EXECUTION.get_progress(tx.deref())?.unwrap_or_default();
+        if executed_tip < fork.number() {
+            return Err(BlockchainTreeError::ParentNotExecuted {
+                block: fork.number() + 1,
+                parent: fork.hash(),
+            })
+        }
+        let (unwound, reverted) = if executed_tip > fork.number() {
+            let unwound = canon_blocks(tx.deref(), fork.number() + 1..=executed_tip)?;
+            let reverted = pipeline
+                .unwind_in(&mut tx, fork.number(), None)
+                .await?
+                .ok_or(BlockchainTreeError::StageBehindFork { fork: fork.number() })?;
+            (unwound, Some(reverted))
+        } else {
+            (Vec::new(), None)
+        };
+        debug!(target: "sync::tree", fork = fork.number(), blocks = chain.blocks.len(), unwound = unwound.len(), "Committing side chain");
+
+        let mut td: U256 = *tx
+            .get::<tables::HeaderTD>(fork)?
+            .ok_or(BlockchainTreeError::UnknownBlock { hash: fork.hash() })?;
+        for tree_block in &chain.blocks {
+            let SealedBlockWithSenders { block, senders } = &tree_block.block;
+            let has_block_reward = self.consensus.has_block_reward(block.number);
+            insert_canonical_block(tx.deref(), block, Some(senders.clone()), has_block_reward)?;
+
+            td += block.difficulty;
+            let key = BlockNumHash(block.header.num_hash());
+            tx.put::<tables::HeaderTD>(key, td.into())?;
+
+            let body = tx.get::<tables::BlockBodies>(key)?.expect("body of the block is inserted");
+            write_execution_result(&tx, block.number, &body, tree_block.result.clone())
+                .map_err(PipelineError::from)?;
+        }
+
+        if let Some(tip) = chain.blocks.last() {
+            for stage_id in [HEADERS, BODIES, SENDER_RECOVERY, EXECUTION] {
+                stage_id.save_progress(tx.deref(), tip.block.number)?;
+            }
+        }
+        tx.commit()?;
+
+        if let Some(reverted) = reverted {
+            pipeline.notify_unwound(reverted);
+        }
+        // the blocks are announced here if no stage is left to process them
+        pipeline.notify_commit(self.db.as_ref(), Some(fork.number()))?;
+        Ok(unwound)
+    }
+
+    /// Returns the number of the block if it is part of the canonical chain.
+    fn canonical_number(
+        &self,
+        hash: BlockHash,
+    ) -> Result<Option<BlockNumber>, BlockchainTreeError> {
+        let tx = self.db.tx()?;
+        let number = match tx.get::<tables::HeaderNumbers>(hash)? {
+            Some(number) if tx.get::<tables::CanonicalHeaders>(number)? == Some(hash) => {
+                Some(number)
+            }
+            _ => None,
+        };
+        tx.commit()?;
+        Ok(number)
+    }
+
+    /// Buffer a block with an unknown parent, dropping the least recently buffered block if the
+    /// buffer is full.
+    fn buffer_block(&mut self, block: SealedBlockWithSenders) {
+        let (hash, parent_hash) = (block.hash(), block.parent_hash);
+        if let Some((dropped_hash, dropped)) = self.buffered.push(hash, block) {
+            if dropped_hash != hash {
+                debug!(target: "sync::tree", number = dropped.number, hash = ?dropped_hash, "Dropping buffered block");
+                self.remove_buffered_child(dropped.parent_hash, dropped_hash);
+            }
+        }
+        self.buffered_children.entry(parent_hash).or_default().insert(hash);
+    }
+
+    /// Remove the buffered children of the block from the buffer and return them.
+    fn take_buffered_children(
+        &mut self,
+        parent_hash: BlockHash,
+    ) -> VecDeque<SealedBlockWithSenders> {
+        self.buffered_children
+            .remove(&parent_hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|hash| self.buffered.pop(&hash))
+            .collect()
+    }
+
+    /// Remove a buffered block from the children of its parent.
+    fn remove_buffered_child(&mut self, parent_hash: BlockHash, hash: BlockHash) {
+        if let Entry::Occupied(mut children) = self.buffered_children.entry(parent_hash) {
+            children.get_mut().remove(&hash);
+            if children.get().is_empty() {
+                children.remove();
+            }
+        }
+    }
+
+    /// Add a side chain to the tree and return its identifier.
+    fn add_chain(&mut self, chain: SideChain) -> ChainId {
+        let chain_id = self.next_chain_id;
+        self.next_chain_id += 1;
+        for tree_block in &chain.blocks {
+            self.block_chains.entry(tree_block.block.hash()).or_insert(chain_id);
+        }
+        self.chains.insert(chain_id, chain);
+        chain_id
+    }
+
+    /// Remove a side chain and the blocks that were executed in it from the tree.
+    fn remove_chain(&mut self, chain_id: ChainId) -> Option<SideChain> {
+        let chain = self.chains.remove(&chain_id)?;
+        self.block_chains.retain(|_, id| *id != chain_id);
+        Some(chain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stages::{execution::ExecutionStage, tx_lookup::TransactionLookupStage};
+    use assert_matches::assert_matches;
+    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use reth_interfaces::test_utils::{generators::random_block, TestConsensus};
+    use reth_primitives::{Address, Header, EMPTY_OMMER_ROOT, MAINNET};
+
+    /// Insert a canonical genesis block into the database.
+    fn insert_genesis<DB: Database>(db: &DB) -> SealedBlock {
+        let genesis = random_block(0, None, Some(0));
+        let tx = db.tx_mut().unwrap();
+        insert_canonical_block(&tx, &genesis, None, false).unwrap();
+        tx.put::<tables::HeaderTD>(BlockNumHash(genesis.header.num_hash()), U256::zero().into())
+            .unwrap();
+        tx.commit().unwrap();
+        genesis
+    }
+
+    /// An empty block without ommers, the beneficiary tells the blocks of different chains apart.
+    fn block(number: BlockNumber, parent: BlockHash, beneficiary: Address) -> SealedBlock {
+        let mut block = random_block(number, Some(parent), Some(0));
+        block.ommers.clear();
+        block.header =
+            Header { beneficiary, ommers_hash: EMPTY_OMMER_ROOT, ..block.header.unseal() }.seal();
+        block
+    }
+
+    #[test]
+    fn insert_buffered_and_forked_blocks() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let genesis = insert_genesis(db.as_ref());
+
+        let mut tree = BlockchainTree::new(db, Arc::new(TestConsensus::default()), MAINNET.clone());
+
+        let block1 = random_block(1, Some(genesis.hash()), Some(0));
+        let block2 = random_block(2, Some(block1.hash()), Some(0));
+        let fork2 = random_block(2, Some(block1.hash()), Some(0));
+
+        // the child is buffered until the parent is inserted
+        assert_matches!(tree.insert_block(block2.clone()), Ok(BlockStatus::Buffered));
+        assert!(!tree.contains(block2.hash()));
+        assert_matches!(tree.insert_block(block1.clone()), Ok(BlockStatus::Executed));
+        assert!(tree.contains(block1.hash()));
+        assert!(tree.contains(block2.hash()));
+
+        // a fork from the middle of a side chain is a new side chain
+        assert_matches!(tree.insert_block(fork2.clone()), Ok(BlockStatus::Executed));
+        assert_eq!(tree.chains.len(), 2);
+        assert_eq!(tree.chains[&tree.block_chains[&fork2.hash()]].blocks.len(), 2);
+
+        assert_matches!(tree.insert_block(block2), Ok(BlockStatus::Known));
+        assert_matches!(tree.insert_block(genesis), Ok(BlockStatus::Known));
+        assert_matches!(
+            tree.insert_block(random_block(0, Some(BlockHash::from_low_u64_be(1)), Some(0))),
+            Err(BlockchainTreeError::BelowFinalized { block: 0, finalized: 0 })
+        );
+
+        // the side chains fork from genesis, which is below the finalized block now
+        tree.finalize_block(1);
+        assert!(tree.chains.is_empty());
+        assert!(!tree.contains(fork2.hash()));
+    }
+
+    #[test]
+    fn insert_buffered_descendants() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let genesis = insert_genesis(db.as_ref());
+        let mut tree = BlockchainTree::new(db, Arc::new(TestConsensus::default()), MAINNET.clone())
+            .with_max_buffered_blocks(NonZeroUsize::new(3).unwrap());
+
+        let mut blocks = vec![block(1, genesis.hash(), Address::zero())];
+        for number in 2..=4 {
+            blocks.push(block(number, blocks.last().unwrap().hash(), Address::zero()));
+        }
+
+        // all descendants are executed once the first block is inserted
+        for block in blocks.iter().skip(1).rev() {
+            assert_matches!(tree.insert_block(block.clone()), Ok(BlockStatus::Buffered));
+        }
+        assert_matches!(tree.insert_block(blocks[0].clone()), Ok(BlockStatus::Executed));
+        assert!(blocks.iter().all(|block| tree.contains(block.hash())));
+        assert_eq!(tree.buffered.len(), 0);
+        assert!(tree.buffered_children.is_empty());
+
+        // the least recently buffered block is dropped once the buffer is full
+        let unknown = (1..=4)
+            .map(|number| block(number, BlockHash::from_low_u64_be(number), Address::zero()))
+            .collect::<Vec<_>>();
+        for block in &unknown {
+            assert_matches!(tree.insert_block(block.clone()), Ok(BlockStatus::Buffered));
+        }
+        assert_eq!(tree.buffered.len(), 3);
+        assert!(!tree.buffered.contains(&unknown[0].hash()));
+        assert!(!tree.buffered_children.contains_key(&unknown[0].parent_hash));
+    }
+
+    #[tokio::test]
+    async fn reorg_to_longer_side_chain() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let genesis = insert_genesis(db.as_ref());
+        let mut pipeline = Pipeline::new().push(ExecutionStage::new(MAINNET.clone()));
+        let mut tree =
+            BlockchainTree::new(db.clone(), Arc::new(TestConsensus::default()), MAINNET.clone());
+
+        // the old chain pays the block rewards to `a`, the new chain to `b`
+        let (a, b) = (Address::from_low_u64_be(0xa), Address::from_low_u64_be(0xb));
+        let a1 = block(1, genesis.hash(), a);
+        let a2 = block(2, a1.hash(), a);
+        let b1 = block(1, genesis.hash(), b);
+        let b2 = block(2, b1.hash(), b);
+        let b3 = block(3, b2.hash(), b);
+
+        for block in [&a1, &a2] {
+            assert_matches!(tree.insert_block(block.clone()), Ok(BlockStatus::Executed));
+        }
+        tree.make_canonical(&mut pipeline, a2.hash()).await.unwrap();
+        assert!(!tree.contains(a1.hash()) && !tree.contains(a2.hash()));
+
+        for block in [&b1, &b2, &b3] {
+            assert_matches!(tree.insert_block(block.clone()), Ok(BlockStatus::Executed));
+        }
+        tree.make_canonical(&mut pipeline, b3.hash()).await.unwrap();
+
+        // the new chain is canonical and executed, the old chain is unwound
+        let tx = db.tx().unwrap();
+        for block in [&b1, &b2, &b3] {
+            assert_eq!(
+                tx.get::<tables::CanonicalHeaders>(block.number).unwrap(),
+                Some(block.hash())
+            );
+        }
+        assert_eq!(EXECUTION.get_progress(&tx).unwrap(), Some(3));
+        let block_reward = U256::from(5u64) * U256::exp10(18);
+        assert_eq!(
+            tx.get::<tables::PlainAccountState>(b).unwrap().map(|account| account.balance),
+            Some(block_reward * U256::from(3u64))
+        );
+        assert_eq!(tx.get::<tables::PlainAccountState>(a).unwrap(), None);
+        tx.commit().unwrap();
+
+        // the blocks of the old chain are side chain blocks now
+        assert!(tree.contains(a1.hash()) && tree.contains(a2.hash()));
+        assert!([&b1, &b2, &b3].iter().all(|block| !tree.contains(block.hash())));
+    }
+
+    #[tokio::test]
+    async fn failed_commit_keeps_side_chain() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let genesis = insert_genesis(db.as_ref());
+        let mut pipeline = Pipeline::new().push(ExecutionStage::new(MAINNET.clone()));
+        let mut tree =
+            BlockchainTree::new(db.clone(), Arc::new(TestConsensus::default()), MAINNET.clone());
+
+        let (a, c) = (Address::from_low_u64_be(0xa), Address::from_low_u64_be(0xc));
+        let a1 = block(1, genesis.hash(), a);
+        let a2 = block(2, a1.hash(), a);
+        let c2 = block(2, a1.hash(), c);
+
+        for block in [&a1, &a2] {
+            assert_matches!(tree.insert_block(block.clone()), Ok(BlockStatus::Executed));
+        }
+        tree.make_canonical(&mut pipeline, a2.hash()).await.unwrap();
+        assert_matches!(tree.insert_block(c2.clone()), Ok(BlockStatus::Executed));
+
+        // the transaction lookup stage is behind the fork block, so the chain can't be unwound
+        let mut pipeline = Pipeline::new()
+            .push(ExecutionStage::new(MAINNET.clone()))
+            .push(TransactionLookupStage::default());
+        assert_matches!(
+            tree.make_canonical(&mut pipeline, c2.hash()).await,
+            Err(BlockchainTreeError::StageBehindFork { fork: 1 })
+        );
+
+        // nothing was written and the side chain stays in the tree
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(2).unwrap(), Some(a2.hash()));
+        assert_eq!(EXECUTION.get_progress(&tx).unwrap(), Some(2));
+        assert_eq!(tx.get::<tables::PlainAccountState>(c).unwrap(), None);
+        tx.commit().unwrap();
+        assert!(tree.contains(c2.hash()));
+    }
+}
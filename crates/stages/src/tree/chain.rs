@@ -0,0 +1,98 @@
+use reth_db::models::BlockNumHash;
+use reth_executor::{executor::ExecutionResult, state::ChainState};
+use reth_primitives::{BlockHash, SealedBlock, SealedBlockWithSenders, SealedHeader};
+
+/// An executed block of a side chain.
+#[derive(Debug, Clone)]
+pub(crate) struct TreeBlock {
+    /// The block with the senders of its transactions.
+    pub(crate) block: SealedBlockWithSenders,
+    /// The result of the execution of the block, which is written to the database once the block
+    /// becomes canonical.
+    pub(crate) result: ExecutionResult,
+    /// The state changes of the block.
+    pub(crate) state: ChainState,
+}
+
+/// A chain of executed blocks that forks from a block of the canonical chain.
+#[derive(Debug, Clone)]
+pub(crate) struct SideChain {
+    /// The canonical block the chain forks from.
+    pub(crate) fork: BlockNumHash,
+    /// The blocks of the chain, in ascending order.
+    pub(crate) blocks: Vec<TreeBlock>,
+    /// The state changes of all blocks of the chain.
+    pub(crate) state: ChainState,
+}
+
+impl SideChain {
+    /// Create an empty chain that forks from the canonical block.
+    pub(crate) fn new(fork: BlockNumHash) -> Self {
+        Self { fork, blocks: Vec::new(), state: ChainState::default() }
+    }
+
+    /// The hash of the last block of the chain, or of the fork block if the chain is empty.
+    pub(crate) fn tip_hash(&self) -> BlockHash {
+        self.blocks.last().map_or(self.fork.hash(), |tip| tip.block.hash())
+    }
+
+    /// The header of a block of the chain.
+    pub(crate) fn header(&self, hash: BlockHash) -> Option<&SealedHeader> {
+        self.blocks.iter().map(|tree_block| &tree_block.block.header).find(|h| h.hash() == hash)
+    }
+
+    /// Append an executed block to the chain.
+    pub(crate) fn push(&mut self, block: SealedBlockWithSenders, result: ExecutionResult) {
+        let state = ChainState::new(block.number, block.hash(), result.clone());
+        self.push_tree_block(TreeBlock { block, result, state });
+    }
+
+    /// Append a block that was executed in another chain to the chain.
+    fn push_tree_block(&mut self, tree_block: TreeBlock) {
+        self.state.extend(&tree_block.state);
+        self.blocks.push(tree_block);
+    }
+
+    /// Create a new chain from the blocks of this chain up to and including the block.
+    pub(crate) fn fork_at(&self, hash: BlockHash) -> Self {
+        let mut chain = Self::new(self.fork);
+        for tree_block in &self.blocks {
+            chain.push_tree_block(tree_block.clone());
+            if tree_block.block.hash() == hash {
+                break
+            }
+        }
+        chain
+    }
+
+    /// Remove the blocks after the block and return them.
+    pub(crate) fn split_after(&mut self, hash: BlockHash) -> Vec<TreeBlock> {
+        let position = self.blocks.iter().position(|tree_block| tree_block.block.hash() == hash);
+        match position {
+            Some(position) => self.blocks.split_off(position + 1),
+            None => Vec::new(),
+        }
+    }
+
+    /// Remove the leading blocks that became canonical and fork the chain from the last of them.
+    ///
+    /// The state changes of the removed blocks are kept, since they are the same as the changes
+    /// that were committed to the database.
+    pub(crate) fn remove_canonical<E>(
+        &mut self,
+        mut is_canonical: impl FnMut(&SealedBlock) -> Result<bool, E>,
+    ) -> Result<Vec<TreeBlock>, E> {
+        let mut canonical = 0;
+        for tree_block in &self.blocks {
+            if !is_canonical(&tree_block.block.block)? {
+                break
+            }
+            canonical += 1;
+        }
+        let removed = self.blocks.drain(..canonical).collect::<Vec<_>>();
+        if let Some(last) = removed.last() {
+            self.fork = BlockNumHash(last.block.header.num_hash());
+        }
+        Ok(removed)
+    }
+}
@@ -1,5 +1,8 @@
 pub(crate) mod opt {
-    use tokio::sync::mpsc::{error::SendError, Sender};
+    use tokio::sync::{
+        broadcast,
+        mpsc::{error::SendError, Sender},
+    };
 
     /// Get an [Option] with the maximum value, compared between the passed in value and the inner
     /// value of the [Option]. If the [Option] is `None`, then an option containing the passed in
@@ -15,20 +18,37 @@ pub(crate) mod opt {
         a.map_or(Some(b), |v| Some(std::cmp::min(v, b)))
     }
 
-    /// The producing side of a [tokio::mpsc] channel that may or may not be set.
-    #[derive(Default, Clone)]
+    /// The capacity of the broadcast channel of a [MaybeSender].
+    const BROADCAST_CAPACITY: usize = 1024;
+
+    /// The producing side of a [tokio::sync::broadcast] channel and a [tokio::sync::mpsc] channel
+    /// that may or may not be set.
+    ///
+    /// Values are broadcast to all current subscribers, slow subscribers miss values instead of
+    /// blocking the sender. The [tokio::sync::mpsc] channel receives every value.
+    #[derive(Clone)]
     pub(crate) struct MaybeSender<T> {
         inner: Option<Sender<T>>,
+        broadcast: broadcast::Sender<T>,
+    }
+
+    impl<T: Clone> Default for MaybeSender<T> {
+        fn default() -> Self {
+            Self::new(None)
+        }
     }
 
-    impl<T> MaybeSender<T> {
+    impl<T: Clone> MaybeSender<T> {
         /// Create a new [MaybeSender]
         pub(crate) fn new(sender: Option<Sender<T>>) -> Self {
-            Self { inner: sender }
+            let (broadcast, _) = broadcast::channel(BROADCAST_CAPACITY);
+            Self { inner: sender, broadcast }
         }
 
-        /// Send a value over the channel if an internal sender has been set.
+        /// Broadcast a value and send it over the channel if an internal sender has been set.
         pub(crate) async fn send(&self, value: T) -> Result<(), SendError<T>> {
+            // there might be no subscribers, which is fine
+            let _ = self.broadcast.send(value.clone());
             if let Some(rx) = &self.inner {
                 rx.send(value).await
             } else {
@@ -40,6 +60,11 @@ pub(crate) mod opt {
         pub(crate) fn set(&mut self, sender: Option<Sender<T>>) {
             self.inner = sender;
         }
+
+        /// Subscribe to all values that are sent from now on.
+        pub(crate) fn subscribe(&self) -> broadcast::Receiver<T> {
+            self.broadcast.subscribe()
+        }
     }
 
     #[cfg(test)]
@@ -1,8 +1,11 @@
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
 
 use crate::{
     common::{IterPairResult, PairResult, ValueOnlyResult},
-    table::{DupSort, Table},
+    table::{DupSort, Encode, Table},
     Error,
 };
 
@@ -34,6 +37,67 @@ pub trait DbCursorRO<'tx, T: Table> {
     ) -> Result<Walker<'cursor, 'tx, T, Self>, Error>
     where
         Self: Sized;
+
+    /// Returns an iterator over the keys in the range, e.g. the transactions of a block.
+    fn walk_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<RangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+        T::Key: Ord + Clone,
+    {
+        let start = match range.start_bound().cloned() {
+            Bound::Included(key) => self.walk(key)?.start,
+            Bound::Excluded(key) => {
+                let start = self.walk(key.clone())?.start;
+                match start {
+                    Some(Ok((found, _))) if found == key => self.next().transpose(),
+                    start => start,
+                }
+            }
+            Bound::Unbounded => self.first().transpose(),
+        };
+
+        Ok(RangeWalker {
+            cursor: self,
+            start,
+            started: false,
+            end: range.end_bound().cloned(),
+            _tx_phantom: PhantomData {},
+        })
+    }
+
+    /// Returns an iterator over the keys whose encoding starts with the encoding of `prefix`,
+    /// starting at a key greater or equal than `start_key`, e.g. the history shards of an address.
+    fn walk_prefix<'cursor, P: Encode>(
+        &'cursor mut self,
+        start_key: T::Key,
+        prefix: P,
+    ) -> Result<PrefixWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+        T::Key: Clone,
+    {
+        let start = self.walk(start_key)?.start;
+
+        Ok(PrefixWalker {
+            cursor: self,
+            start,
+            started: false,
+            prefix: prefix.encode().as_ref().to_vec(),
+            _tx_phantom: PhantomData {},
+        })
+    }
+
+    /// Returns an iterator walking backwards, starting at the last key less or equal than
+    /// `start_key`, or at the last key of the table if there is no `start_key`.
+    fn walk_back<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized;
 }
 
 /// Read only cursor over DupSort table.
@@ -131,7 +195,11 @@ pub struct DupWalker<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> {
     /// Cursor to be used to walk through the table.
     pub cursor: &'cursor mut CURSOR,
     /// Value where to start the walk.
+    ///
+    /// The walk is empty if there is no start, i.e. the key has no values at or after the subkey.
     pub start: IterPairResult<T>,
+    /// Whether the start was already returned.
+    pub started: bool,
     /// Phantom data for 'tx. As it is only used for `DbDupCursorRO`.
     pub _tx_phantom: PhantomData<&'tx T>,
 }
@@ -141,10 +209,128 @@ impl<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> std::iter::Iterato
 {
     type Item = Result<(T::Key, T::Value), Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        let start = self.start.take();
-        if start.is_some() {
-            return start
+        if !self.started {
+            self.started = true;
+            return self.start.take()
         }
         self.cursor.next_dup().transpose()
     }
 }
+
+/// Provides an iterator to `Cursor` over the keys of a range, see [`DbCursorRO::walk_range`].
+pub struct RangeWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    pub cursor: &'cursor mut CURSOR,
+    /// `(key, value)` where to start the walk.
+    ///
+    /// The walk is empty if there is no start, i.e. no key is at or after the start of the range.
+    pub start: IterPairResult<T>,
+    /// Whether the start was already returned.
+    pub started: bool,
+    /// Where the walk ends.
+    pub end: Bound<T::Key>,
+    /// Phantom data for 'tx. As it is only used for `DbCursorRO`.
+    pub _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
+    for RangeWalker<'cursor, 'tx, T, CURSOR>
+where
+    T::Key: Ord,
+{
+    type Item = Result<(T::Key, T::Value), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = match self.start.take() {
+            Some(start) => {
+                self.started = true;
+                start
+            }
+            None if self.started => self.cursor.next().transpose()?,
+            None => return None,
+        };
+
+        match item {
+            Ok((key, _)) if !is_before_end(&key, &self.end) => None,
+            item => Some(item),
+        }
+    }
+}
+
+/// Whether the key is before the end of a range.
+fn is_before_end<K: Ord>(key: &K, end: &Bound<K>) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Provides an iterator to `Cursor` over the keys with a common encoded prefix, see
+/// [`DbCursorRO::walk_prefix`].
+pub struct PrefixWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    pub cursor: &'cursor mut CURSOR,
+    /// `(key, value)` where to start the walk.
+    ///
+    /// The walk is empty if there is no start.
+    pub start: IterPairResult<T>,
+    /// Whether the start was already returned.
+    pub started: bool,
+    /// The encoded prefix of the keys.
+    pub prefix: Vec<u8>,
+    /// Phantom data for 'tx. As it is only used for `DbCursorRO`.
+    pub _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
+    for PrefixWalker<'cursor, 'tx, T, CURSOR>
+where
+    T::Key: Clone,
+{
+    type Item = Result<(T::Key, T::Value), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = match self.start.take() {
+            Some(start) => {
+                self.started = true;
+                start
+            }
+            None if self.started => self.cursor.next().transpose()?,
+            None => return None,
+        };
+
+        match item {
+            Ok((key, _)) if !key.clone().encode().as_ref().starts_with(&self.prefix) => None,
+            item => Some(item),
+        }
+    }
+}
+
+/// Provides a reverse iterator to `Cursor`, see [`DbCursorRO::walk_back`].
+pub struct ReverseWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    pub cursor: &'cursor mut CURSOR,
+    /// `(key, value)` where to start the walk.
+    ///
+    /// The walk is empty if there is no start.
+    pub start: IterPairResult<T>,
+    /// Whether the start was already returned.
+    pub started: bool,
+    /// Phantom data for 'tx. As it is only used for `DbCursorRO`.
+    pub _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
+    for ReverseWalker<'cursor, 'tx, T, CURSOR>
+{
+    type Item = Result<(T::Key, T::Value), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.start.take() {
+            Some(start) => {
+                self.started = true;
+                Some(start)
+            }
+            None if self.started => self.cursor.prev().transpose(),
+            None => None,
+        }
+    }
+}
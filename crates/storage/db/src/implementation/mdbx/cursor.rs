@@ -3,7 +3,9 @@
 use std::{borrow::Cow, marker::PhantomData};
 
 use crate::{
-    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, Walker},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, ReverseWalker, Walker,
+    },
     table::{Compress, DupSort, Encode, Table},
     tables::utils::*,
     Error,
@@ -81,6 +83,40 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
 
         Ok(Walker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
     }
+
+    fn walk_back<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => {
+                let start_key = start_key.encode();
+                // the first key greater or equal than the start key, so the last key less or equal
+                // than the start key is either the found key or the one before it
+                match self
+                    .inner
+                    .set_range::<Cow<'_, [u8]>, Cow<'_, [u8]>>(start_key.as_ref())
+                    .map_err(|e| Error::Read(e.into()))?
+                {
+                    Some((key, value)) if key.as_ref() == start_key.as_ref() => Some((key, value)),
+                    Some(_) => self.inner.prev().map_err(|e| Error::Read(e.into()))?,
+                    None => self.inner.last().map_err(|e| Error::Read(e.into()))?,
+                }
+            }
+            None => self.inner.last().map_err(|e| Error::Read(e.into()))?,
+        }
+        .map(decoder::<T>);
+
+        Ok(ReverseWalker::<'cursor, 'tx, T, Self> {
+            cursor: self,
+            start,
+            started: false,
+            _tx_phantom: PhantomData {},
+        })
+    }
 }
 
 impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, K, T> {
@@ -130,7 +166,12 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
             .map_err(|e| Error::Read(e.into()))?
             .map(|val| decoder::<T>((Cow::Owned(key), val)));
 
-        Ok(DupWalker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
+        Ok(DupWalker::<'cursor, 'tx, T, Self> {
+            cursor: self,
+            start,
+            started: false,
+            _tx_phantom: PhantomData {},
+        })
     }
 }
 
@@ -26,6 +26,41 @@ pub enum EnvKind {
     RW,
 }
 
+/// Settings of the MDBX environment.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvConfig {
+    /// The maximum size of the database in bytes.
+    pub max_size: usize,
+    /// The number of bytes by which the database grows when it's full.
+    pub growth_step: usize,
+    /// How commits of a read-write environment are flushed to disk.
+    pub sync_mode: SyncMode,
+    /// The maximum number of concurrent read transactions of all processes, or the MDBX default.
+    pub max_readers: Option<u32>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 1024 * 1024 * 1024 * 1024 * 4, // TODO: reevaluate (4 tb)
+            growth_step: 1024 * 1024 * 256,          // TODO: reevaluate (256 mb)
+            sync_mode: SyncMode::Durable,
+            max_readers: None,
+        }
+    }
+}
+
+/// The state of the reader slots, see [`Env::check_readers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderStatus {
+    /// The number of reader slots of exited processes that were cleared.
+    pub cleared: usize,
+    /// The number of used reader slots.
+    pub readers: usize,
+    /// The number of transactions that were committed since the snapshot of the oldest reader.
+    pub oldest_reader_lag: usize,
+}
+
 /// Wrapper for the libmdbx environment.
 #[derive(Debug)]
 pub struct Env<E: EnvironmentKind> {
@@ -49,37 +84,66 @@ impl<E: EnvironmentKind> Database for Env<E> {
 }
 
 impl<E: EnvironmentKind> Env<E> {
-    /// Opens the database at the specified path with the given `EnvKind`.
+    /// Opens the database at the specified path with the given `EnvKind` and the default
+    /// [`EnvConfig`].
     ///
     /// It does not create the tables, for that call [`Env::create_tables`].
     pub fn open(path: &Path, kind: EnvKind) -> Result<Env<E>, Error> {
+        Self::open_with_config(path, kind, &EnvConfig::default())
+    }
+
+    /// Opens the database at the specified path with the given `EnvKind` and settings.
+    ///
+    /// It does not create the tables, for that call [`Env::create_tables`].
+    pub fn open_with_config(
+        path: &Path,
+        kind: EnvKind,
+        config: &EnvConfig,
+    ) -> Result<Env<E>, Error> {
         let mode = match kind {
             EnvKind::RO => Mode::ReadOnly,
-            EnvKind::RW => Mode::ReadWrite { sync_mode: SyncMode::Durable },
+            EnvKind::RW => Mode::ReadWrite { sync_mode: config.sync_mode },
         };
 
-        let env = Env {
-            inner: Environment::new()
-                .set_max_dbs(TABLES.len())
-                .set_geometry(Geometry {
-                    size: Some(0..(1024 * 1024 * 1024 * 1024 * 4)), // TODO: reevaluate (4 tb)
-                    growth_step: Some(1024 * 1024 * 256),           // TODO: reevaluate (256 mb)
-                    shrink_threshold: None,
-                    page_size: Some(PageSize::Set(default_page_size())),
-                })
-                .set_flags(EnvironmentFlags {
-                    mode,
-                    no_rdahead: true, // TODO: reevaluate
-                    coalesce: true,
-                    ..Default::default()
-                })
-                .open(path)
-                .map_err(|e| Error::DatabaseLocation(e.into()))?,
-        };
+        let mut builder = Environment::new();
+        builder
+            .set_max_dbs(TABLES.len())
+            .set_geometry(Geometry {
+                size: Some(0..config.max_size),
+                growth_step: Some(config.growth_step as isize),
+                shrink_threshold: None,
+                page_size: Some(PageSize::Set(default_page_size())),
+            })
+            .set_flags(EnvironmentFlags {
+                mode,
+                no_rdahead: true, // TODO: reevaluate
+                coalesce: true,
+                ..Default::default()
+            });
+        if let Some(max_readers) = config.max_readers {
+            builder.set_max_readers(max_readers);
+        }
+
+        let env = Env { inner: builder.open(path).map_err(|e| Error::DatabaseLocation(e.into()))? };
 
         Ok(env)
     }
 
+    /// Clears the reader slots of processes that exited without ending their read transactions,
+    /// and reports how far the oldest running read transaction is behind.
+    ///
+    /// The pages of old transactions can't be reused while a reader still sees them, so a long
+    /// running read transaction makes the database grow.
+    pub fn check_readers(&self) -> Result<ReaderStatus, Error> {
+        let cleared = self.inner.reader_check().map_err(|e| Error::Read(e.into()))?;
+        let info = self.inner.info().map_err(|e| Error::Read(e.into()))?;
+        Ok(ReaderStatus {
+            cleared,
+            readers: info.num_readers(),
+            oldest_reader_lag: info.last_txnid().saturating_sub(info.latter_reader_txnid()),
+        })
+    }
+
     /// Creates all the defined tables, if necessary.
     pub fn create_tables(&self) -> Result<(), Error> {
         let tx = self.inner.begin_rw_txn().map_err(|e| Error::InitTransaction(e.into()))?;
@@ -144,16 +208,14 @@ pub mod test_utils {
 mod tests {
     use super::{test_utils, Env, EnvKind};
     use crate::{
-        cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
         database::Database,
-        models::ShardedKey,
-        tables::{AccountHistory, CanonicalHeaders, Headers, PlainAccountState, PlainStorageState},
+        implementation::test_suite::table_test_suite,
+        tables::{Headers, PlainAccountState},
         transaction::{DbTx, DbTxMut},
-        Error,
     };
     use reth_libmdbx::{NoWriteMap, WriteMap};
-    use reth_primitives::{Account, Address, Header, IntegerList, StorageEntry, H256, U256};
-    use std::{str::FromStr, sync::Arc};
+    use reth_primitives::{Account, Address, Header, H256, U256};
+    use std::str::FromStr;
     use tempfile::TempDir;
 
     const ERROR_DB_CREATION: &str = "Not able to create the mdbx file.";
@@ -164,128 +226,28 @@ mod tests {
     const ERROR_INIT_TX: &str = "Failed to create a MDBX transaction.";
     const ERROR_ETH_ADDRESS: &str = "Invalid address.";
 
+    table_test_suite!(test_utils::create_test_db::<WriteMap>(EnvKind::RW));
+
     #[test]
     fn db_creation() {
         test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
     }
 
     #[test]
-    fn db_manual_put_get() {
+    fn db_check_readers() {
         let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        let reader = env.tx().expect(ERROR_INIT_TX);
 
-        let value = Header::default();
-        let key = (1u64, H256::zero());
-
-        // PUT
         let tx = env.tx_mut().expect(ERROR_INIT_TX);
-        tx.put::<Headers>(key.into(), value.clone()).expect(ERROR_PUT);
+        tx.put::<Headers>((1, H256::zero()).into(), Header::default()).expect(ERROR_PUT);
         tx.commit().expect(ERROR_COMMIT);
 
-        // GET
-        let tx = env.tx().expect(ERROR_INIT_TX);
-        let result = tx.get::<Headers>(key.into()).expect(ERROR_GET);
-        assert!(result.expect(ERROR_RETURN_VALUE) == value);
-        tx.commit().expect(ERROR_COMMIT);
-    }
-
-    #[test]
-    fn db_cursor_walk() {
-        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        let status = env.check_readers().unwrap();
+        assert_eq!(status.cleared, 0);
+        assert_eq!(status.oldest_reader_lag, 1);
 
-        let value = Header::default();
-        let key = (1u64, H256::zero());
-
-        // PUT
-        let tx = env.tx_mut().expect(ERROR_INIT_TX);
-        tx.put::<Headers>(key.into(), value.clone()).expect(ERROR_PUT);
-        tx.commit().expect(ERROR_COMMIT);
-
-        // Cursor
-        let tx = env.tx().expect(ERROR_INIT_TX);
-        let mut cursor = tx.cursor::<Headers>().unwrap();
-
-        let first = cursor.first().unwrap();
-        assert!(first.is_some(), "First should be our put");
-
-        // Walk
-        let walk = cursor.walk(key.into()).unwrap();
-        let first = walk.into_iter().next().unwrap().unwrap();
-        assert_eq!(first.1, value, "First next should be put value");
-    }
-
-    #[test]
-    fn db_cursor_seek_exact_or_previous_key() {
-        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
-
-        // PUT
-        let tx = db.tx_mut().expect(ERROR_INIT_TX);
-        vec![0, 1, 3]
-            .into_iter()
-            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
-            .expect(ERROR_PUT);
-        tx.commit().expect(ERROR_COMMIT);
-
-        // Cursor
-        let missing_key = 2;
-        let tx = db.tx().expect(ERROR_INIT_TX);
-        let mut cursor = tx.cursor::<CanonicalHeaders>().unwrap();
-        assert_eq!(cursor.current(), Ok(None));
-
-        // Seek exact
-        let exact = cursor.seek_exact(missing_key).unwrap();
-        assert_eq!(exact, None);
-        assert_eq!(cursor.current(), Ok(Some((missing_key + 1, H256::zero()))));
-        assert_eq!(cursor.prev(), Ok(Some((missing_key - 1, H256::zero()))));
-        assert_eq!(cursor.prev(), Ok(Some((missing_key - 2, H256::zero()))));
-    }
-
-    #[test]
-    fn db_cursor_insert() {
-        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
-
-        // PUT
-        let tx = db.tx_mut().expect(ERROR_INIT_TX);
-        vec![0, 1, 3, 4, 5]
-            .into_iter()
-            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
-            .expect(ERROR_PUT);
-        tx.commit().expect(ERROR_COMMIT);
-
-        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
-
-        let key_to_insert = 2;
-        let tx = db.tx_mut().expect(ERROR_INIT_TX);
-        let mut cursor = tx.cursor_mut::<CanonicalHeaders>().unwrap();
-
-        // INSERT
-        cursor.seek_exact(1).unwrap();
-        assert_eq!(cursor.insert(key_to_insert, H256::zero()), Ok(()));
-        assert_eq!(cursor.current(), Ok(Some((key_to_insert, H256::zero()))));
-
-        // INSERT (failure)
-        assert_eq!(cursor.insert(key_to_insert, H256::zero()), Err(Error::Write(4294936497)));
-        assert_eq!(cursor.current(), Ok(Some((key_to_insert, H256::zero()))));
-    }
-
-    #[test]
-    fn db_cursor_append_failure() {
-        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
-
-        // PUT
-        let tx = db.tx_mut().expect(ERROR_INIT_TX);
-        vec![0, 1, 3, 4, 5]
-            .into_iter()
-            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
-            .expect(ERROR_PUT);
-        tx.commit().expect(ERROR_COMMIT);
-
-        // APPEND
-        let key_to_append = 2;
-        let tx = db.tx_mut().expect(ERROR_INIT_TX);
-        let mut cursor = tx.cursor_mut::<CanonicalHeaders>().unwrap();
-        cursor.seek_exact(1).unwrap();
-        assert_eq!(cursor.append(key_to_append, H256::zero()), Err(Error::Write(4294936878)));
-        assert_eq!(cursor.current(), Ok(Some((5, H256::zero())))); // the end of table
+        drop(reader);
+        assert_eq!(env.check_readers().unwrap().oldest_reader_lag, 0);
     }
 
     #[test]
@@ -319,172 +281,4 @@ mod tests {
 
         assert!(result == Some(value))
     }
-
-    #[test]
-    fn db_dup_sort() {
-        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
-        let key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047")
-            .expect(ERROR_ETH_ADDRESS);
-
-        // PUT (0,0)
-        let value00 = StorageEntry::default();
-        env.update(|tx| tx.put::<PlainStorageState>(key, value00.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // PUT (2,2)
-        let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
-        env.update(|tx| tx.put::<PlainStorageState>(key, value22.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // PUT (1,1)
-        let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
-        env.update(|tx| tx.put::<PlainStorageState>(key, value11.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // Iterate with cursor
-        {
-            let tx = env.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
-
-            // Notice that value11 and value22 have been ordered in the DB.
-            assert!(Some(value00) == cursor.next_dup_val().unwrap());
-            assert!(Some(value11.clone()) == cursor.next_dup_val().unwrap());
-            assert!(Some(value22) == cursor.next_dup_val().unwrap());
-        }
-
-        // Seek value with exact subkey
-        {
-            let tx = env.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
-            let mut walker = cursor.walk_dup(key, H256::from_low_u64_be(1)).unwrap();
-            assert_eq!(
-                (key, value11),
-                walker
-                    .next()
-                    .expect("element should exist.")
-                    .expect("should be able to retrieve it.")
-            );
-        }
-    }
-
-    #[test]
-    fn db_iterate_over_all_dup_values() {
-        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
-        let key1 = Address::from_str("0x1111111111111111111111111111111111111111")
-            .expect(ERROR_ETH_ADDRESS);
-        let key2 = Address::from_str("0x2222222222222222222222222222222222222222")
-            .expect(ERROR_ETH_ADDRESS);
-
-        // PUT key1 (0,0)
-        let value00 = StorageEntry::default();
-        env.update(|tx| tx.put::<PlainStorageState>(key1, value00.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // PUT key1 (1,1)
-        let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
-        env.update(|tx| tx.put::<PlainStorageState>(key1, value11.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // PUT key2 (2,2)
-        let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
-        env.update(|tx| tx.put::<PlainStorageState>(key2, value22.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // Iterate with walk_dup
-        {
-            let tx = env.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
-            let first = cursor.first().unwrap().unwrap();
-            let mut walker = cursor.walk_dup(first.0, first.1.key).unwrap();
-
-            // Notice that value11 and value22 have been ordered in the DB.
-            assert_eq!(Some(Ok((key1, value00.clone()))), walker.next());
-            assert_eq!(Some(Ok((key1, value11.clone()))), walker.next());
-            // NOTE: Dup cursor does NOT iterates on all values but only on duplicated values of the
-            // same key. assert_eq!(Ok(Some(value22.clone())), walker.next());
-            assert_eq!(None, walker.next());
-        }
-
-        // Iterate by using `walk`
-        {
-            let tx = env.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
-            let first = cursor.first().unwrap().unwrap();
-            let mut walker = cursor.walk(first.0).unwrap();
-            assert_eq!(Some(Ok((key1, value00))), walker.next());
-            assert_eq!(Some(Ok((key1, value11))), walker.next());
-            assert_eq!(Some(Ok((key2, value22))), walker.next());
-        }
-    }
-
-    #[test]
-    fn dup_value_with_same_subkey() {
-        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
-        let key1 = Address::from_str("0x1111111111111111111111111111111111111111")
-            .expect(ERROR_ETH_ADDRESS);
-
-        // PUT key1 (0,1)
-        let value01 = StorageEntry { key: H256::from_low_u64_be(0), value: U256::from(1) };
-        env.update(|tx| tx.put::<PlainStorageState>(key1, value01.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // PUT key1 (0,0)
-        let value00 = StorageEntry::default();
-        env.update(|tx| tx.put::<PlainStorageState>(key1, value00.clone()).expect(ERROR_PUT))
-            .unwrap();
-
-        // Iterate with walk
-        {
-            let tx = env.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
-            let first = cursor.first().unwrap().unwrap();
-            let mut walker = cursor.walk(first.0).unwrap();
-
-            // NOTE: Both values are present
-            assert_eq!(Some(Ok((key1, value00.clone()))), walker.next());
-            assert_eq!(Some(Ok((key1, value01.clone()))), walker.next());
-            assert_eq!(None, walker.next());
-        }
-
-        // seek_by_key_subkey
-        {
-            let tx = env.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
-
-            // NOTE: There are two values with same SubKey but only first one is shown
-            assert_eq!(Ok(Some(value00.clone())), cursor.seek_by_key_subkey(key1, value00.key));
-        }
-    }
-
-    #[test]
-    fn db_sharded_key() {
-        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
-        let real_key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047").unwrap();
-
-        for i in 1..5 {
-            let key = ShardedKey::new(real_key, i * 100);
-            let list: IntegerList = vec![i * 100u64].into();
-
-            db.update(|tx| tx.put::<AccountHistory>(key.clone(), list.clone()).expect("")).unwrap();
-        }
-
-        // Seek value with non existing key.
-        {
-            let tx = db.tx().expect(ERROR_INIT_TX);
-            let mut cursor = tx.cursor::<AccountHistory>().unwrap();
-
-            // It will seek the one greater or equal to the query. Since we have `Address | 100`,
-            // `Address | 200` in the database and we're querying `Address | 150` it will return us
-            // `Address | 200`.
-            let mut walker = cursor.walk(ShardedKey::new(real_key, 150)).unwrap();
-            let (key, list) = walker
-                .next()
-                .expect("element should exist.")
-                .expect("should be able to retrieve it.");
-
-            assert_eq!(ShardedKey::new(real_key, 200), key);
-            let list200: IntegerList = vec![200u64].into();
-            assert_eq!(list200, list);
-        }
-    }
 }
@@ -0,0 +1,318 @@
+//! Cursor of the in-memory database.
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use super::{Entry, MemTable, TxTables, KEY_EXISTS, KEY_MISMATCH};
+use crate::{
+    common::{PairResult, ValueOnlyResult},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, ReverseWalker, Walker,
+    },
+    table::{Compress, DupSort, Encode, Table},
+    tables::utils::*,
+    Error,
+};
+
+/// Where a cursor is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Position {
+    /// The cursor was not moved yet.
+    Unset,
+    /// The cursor is at the entry, which was deleted if the table doesn't contain it anymore.
+    At(Entry),
+    /// The cursor moved past the last entry.
+    End,
+}
+
+/// Cursor over a table of the in-memory database.
+///
+/// Like a MDBX cursor, the cursor is moved by the seeks, even if they don't find the key, and by
+/// the writes.
+#[derive(Debug)]
+pub struct Cursor<'tx, T: Table> {
+    /// The tables of the transaction.
+    tables: &'tx TxTables,
+    /// Where the cursor is.
+    position: Position,
+    /// Phantom data to enforce encoding/decoding.
+    _table: PhantomData<T>,
+}
+
+impl<'tx, T: Table> Cursor<'tx, T> {
+    /// Creates a cursor over the table of the transaction.
+    pub(crate) fn new(tables: &'tx TxTables) -> Self {
+        Self { tables, position: Position::Unset, _table: PhantomData }
+    }
+
+    /// Moves the cursor to the entry found in the table, or past the last entry if there is none,
+    /// and decodes the entry.
+    fn move_to(&mut self, find: impl FnOnce(&MemTable) -> Option<Entry>) -> PairResult<T> {
+        let entry = self.tables.read(T::NAME, find);
+        self.position = entry.clone().map_or(Position::End, Position::At);
+        entry.map(decode_entry::<T>).transpose()
+    }
+
+    /// Moves the cursor to the entry found in the table, if there is one, and decodes the entry.
+    fn move_if_found(&mut self, find: impl FnOnce(&MemTable) -> Option<Entry>) -> PairResult<T> {
+        let entry = self.tables.read(T::NAME, find);
+        if let Some(entry) = &entry {
+            self.position = Position::At(entry.clone());
+        }
+        entry.map(decode_entry::<T>).transpose()
+    }
+
+    /// The entry the cursor is at, or the entry after it if it was deleted.
+    fn current_entry(&self) -> Option<Entry> {
+        match &self.position {
+            Position::At(entry) => self
+                .tables
+                .read(T::NAME, |table| table.entries.range(entry.clone()..).next().cloned()),
+            Position::Unset | Position::End => None,
+        }
+    }
+}
+
+/// Decodes an entry of the table.
+fn decode_entry<T: Table>((key, value): Entry) -> Result<(T::Key, T::Value), Error> {
+    decoder::<T>((Cow::Owned(key), Cow::Owned(value)))
+}
+
+impl<'tx, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, T> {
+    fn first(&mut self) -> PairResult<T> {
+        self.move_to(|table| table.entries.first().cloned())
+    }
+
+    fn seek_exact(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode();
+        let entry = self.move_to(|table| table.seek(key.as_ref()).cloned())?;
+        let found = matches!(&self.position, Position::At((found, _)) if found == key.as_ref());
+        Ok(entry.filter(|_| found))
+    }
+
+    fn next(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset => self.first(),
+            Position::At(entry) => self.move_to(|table| table.after(&entry).cloned()),
+            Position::End => Ok(None),
+        }
+    }
+
+    fn prev(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset | Position::End => self.last(),
+            Position::At(entry) => self.move_if_found(|table| table.before(&entry).cloned()),
+        }
+    }
+
+    fn last(&mut self) -> PairResult<T> {
+        self.move_to(|table| table.entries.last().cloned())
+    }
+
+    /// Returns the entry the cursor is at, or the entry after it if it was deleted.
+    fn current(&mut self) -> PairResult<T> {
+        self.current_entry().map(decode_entry::<T>).transpose()
+    }
+
+    fn walk<'cursor>(
+        &'cursor mut self,
+        start_key: T::Key,
+    ) -> Result<Walker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let key = start_key.encode();
+        let start = self.move_to(|table| table.seek(key.as_ref()).cloned()).transpose();
+
+        Ok(Walker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
+    }
+
+    fn walk_back<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => {
+                let key = start_key.encode();
+                // the first value of the start key, or else the last entry before it
+                self.move_if_found(|table| {
+                    table
+                        .seek(key.as_ref())
+                        .filter(|(found, _)| found == key.as_ref())
+                        .or_else(|| {
+                            table.entries.range(..(key.as_ref().to_vec(), Vec::new())).next_back()
+                        })
+                        .cloned()
+                })
+            }
+            None => self.last(),
+        }
+        .transpose();
+
+        Ok(ReverseWalker::<'cursor, 'tx, T, Self> {
+            cursor: self,
+            start,
+            started: false,
+            _tx_phantom: PhantomData {},
+        })
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, T> {
+    fn seek(&mut self, key: T::SubKey) -> PairResult<T> {
+        let key = key.encode();
+        self.move_to(|table| table.seek(key.as_ref()).cloned())
+    }
+
+    /// Returns the next `(key, value)` pair of a DUPSORT table.
+    fn next_dup(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset => self.first(),
+            Position::At(entry) => self.move_if_found(|table| {
+                table.after(&entry).filter(|(found, _)| *found == entry.0).cloned()
+            }),
+            Position::End => Ok(None),
+        }
+    }
+
+    /// Returns the next `(key, value)` pair skipping the duplicates.
+    fn next_no_dup(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset => self.first(),
+            Position::At(entry) => self.move_to(|table| {
+                table.entries.range(entry.clone()..).find(|(found, _)| *found != entry.0).cloned()
+            }),
+            Position::End => Ok(None),
+        }
+    }
+
+    /// Returns the next `value` of a duplicate `key`.
+    fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
+        Ok(self.next_dup()?.map(|(_, value)| value))
+    }
+
+    fn seek_by_key_subkey(&mut self, key: T::Key, subkey: T::SubKey) -> ValueOnlyResult<T> {
+        let (key, subkey) = (key.encode(), subkey.encode());
+        let entry =
+            self.move_if_found(|table| table.seek_value(key.as_ref(), subkey.as_ref()).cloned())?;
+        Ok(entry.map(|(_, value)| value))
+    }
+
+    /// Returns an iterator starting at a key greater or equal than `start_key` of a DUPSORT table.
+    fn walk_dup<'cursor>(
+        &'cursor mut self,
+        key: T::Key,
+        subkey: T::SubKey,
+    ) -> Result<DupWalker<'cursor, 'tx, T, Self>, Error> {
+        let (key, subkey) = (key.encode(), subkey.encode());
+        let start = self
+            .move_if_found(|table| table.seek_value(key.as_ref(), subkey.as_ref()).cloned())
+            .transpose();
+
+        Ok(DupWalker::<'cursor, 'tx, T, Self> {
+            cursor: self,
+            start,
+            started: false,
+            _tx_phantom: PhantomData {},
+        })
+    }
+}
+
+impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, T> {
+    fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        self.tables.write(T::NAME, Error::Write, |table| table.put(entry.clone()))?;
+        self.position = Position::At(entry);
+        Ok(())
+    }
+
+    /// Fails if the key already exists, and moves the cursor to its first value.
+    fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        let existing = self.tables.write(T::NAME, Error::Write, |table| {
+            match table.seek(&entry.0).filter(|(found, _)| *found == entry.0) {
+                Some(existing) => Some(existing.clone()),
+                None => {
+                    table.put(entry.clone());
+                    None
+                }
+            }
+        })?;
+
+        match existing {
+            Some(existing) => {
+                self.position = Position::At(existing);
+                Err(Error::Write(KEY_EXISTS))
+            }
+            None => {
+                self.position = Position::At(entry);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fails if the key is not after the last key of the table, and moves the cursor to the last
+    /// entry.
+    fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        self.append_entry(entry, |last, entry| last.0 < entry.0)
+    }
+
+    /// Deletes the entry returned by `current`, the cursor stays where the entry was.
+    fn delete_current(&mut self) -> Result<(), Error> {
+        if let Some(entry) = self.current_entry() {
+            self.tables.write(T::NAME, Error::Delete, |table| table.entries.remove(&entry))?;
+            self.position = Position::At(entry);
+        }
+        Ok(())
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRW<'tx, T> for Cursor<'tx, T> {
+    fn delete_current_duplicates(&mut self) -> Result<(), Error> {
+        if let Some(entry) = self.current_entry() {
+            self.tables.write(T::NAME, Error::Delete, |table| table.remove_key(&entry.0))?;
+            self.position = Position::At(entry);
+        }
+        Ok(())
+    }
+
+    /// Fails if the entry is not after the last entry of the table, and moves the cursor to the
+    /// last entry.
+    fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        self.append_entry(entry, |last, entry| last < entry)
+    }
+}
+
+impl<'tx, T: Table> Cursor<'tx, T> {
+    /// Inserts the entry if it's after the last entry of the table, as decided by `is_after`.
+    fn append_entry(
+        &mut self,
+        entry: Entry,
+        is_after: fn(&Entry, &Entry) -> bool,
+    ) -> Result<(), Error> {
+        let last = self.tables.write(T::NAME, Error::Write, |table| {
+            match table.entries.last().filter(|last| !is_after(last, &entry)) {
+                Some(last) => Some(last.clone()),
+                None => {
+                    table.put(entry.clone());
+                    None
+                }
+            }
+        })?;
+
+        match last {
+            Some(last) => {
+                self.position = Position::At(last);
+                Err(Error::Write(KEY_MISMATCH))
+            }
+            None => {
+                self.position = Position::At(entry);
+                Ok(())
+            }
+        }
+    }
+}
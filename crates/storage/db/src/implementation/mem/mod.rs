@@ -0,0 +1,245 @@
+//! In-memory database, e.g. for tests that don't need a database on disk.
+
+use crate::{
+    database::{Database, DatabaseGAT},
+    tables::{TableType, TABLES},
+    Error,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+    sync::{Arc, Condvar, Mutex, RwLock},
+};
+
+pub mod cursor;
+
+pub mod tx;
+use tx::Tx;
+
+/// Error code of a write in a read-only transaction, like `EACCES` of MDBX.
+pub(crate) const ACCESS_DENIED: u32 = 13;
+/// Error code of an insert of a key that already exists, like `MDBX_KEYEXIST`.
+pub(crate) const KEY_EXISTS: u32 = -30799i32 as u32;
+/// Error code of an append of an entry that is not after the last entry, like
+/// `MDBX_EKEYMISMATCH`.
+pub(crate) const KEY_MISMATCH: u32 = -30418i32 as u32;
+
+/// An encoded key and its compressed value.
+pub(crate) type Entry = (Vec<u8>, Vec<u8>);
+
+/// The entries of a table, sorted like MDBX sorts them: by key and then, for the duplicates of a
+/// key, by value.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemTable {
+    /// Whether a key can have multiple values.
+    pub(crate) dup_sort: bool,
+    /// The entries of the table.
+    pub(crate) entries: BTreeSet<Entry>,
+}
+
+impl MemTable {
+    /// The first entry with a key greater or equal than `key`.
+    pub(crate) fn seek(&self, key: &[u8]) -> Option<&Entry> {
+        self.entries.range((key.to_vec(), Vec::new())..).next()
+    }
+
+    /// The first value of `key` which is greater or equal than `value`.
+    pub(crate) fn seek_value(&self, key: &[u8], value: &[u8]) -> Option<&Entry> {
+        self.entries
+            .range((key.to_vec(), value.to_vec())..)
+            .next()
+            .filter(|(found, _)| found == key)
+    }
+
+    /// The entry after `entry`, which doesn't need to be in the table.
+    pub(crate) fn after(&self, entry: &Entry) -> Option<&Entry> {
+        self.entries.range((Bound::Excluded(entry), Bound::Unbounded)).next()
+    }
+
+    /// The entry before `entry`, which doesn't need to be in the table.
+    pub(crate) fn before(&self, entry: &Entry) -> Option<&Entry> {
+        self.entries.range(..entry).next_back()
+    }
+
+    /// Inserts the entry, replacing the value of the key unless the table is DupSort.
+    pub(crate) fn put(&mut self, entry: Entry) {
+        if !self.dup_sort {
+            self.remove_key(&entry.0);
+        }
+        self.entries.insert(entry);
+    }
+
+    /// Removes all values of the key, and returns whether there were any.
+    pub(crate) fn remove_key(&mut self, key: &[u8]) -> bool {
+        let values = self
+            .entries
+            .range((key.to_vec(), Vec::new())..)
+            .take_while(|(found, _)| found == key)
+            .cloned()
+            .collect::<Vec<_>>();
+        values.iter().for_each(|entry| {
+            self.entries.remove(entry);
+        });
+        !values.is_empty()
+    }
+}
+
+/// The tables of a snapshot of the database.
+///
+/// The tables are shared between snapshots and only copied when a transaction writes to them.
+pub(crate) type Tables = BTreeMap<&'static str, Arc<MemTable>>;
+
+/// The tables of a transaction, including its changes.
+#[derive(Debug)]
+pub(crate) struct TxTables {
+    /// The tables.
+    tables: RwLock<Tables>,
+    /// Whether the transaction is read-only.
+    read_only: bool,
+}
+
+impl TxTables {
+    /// Reads the table with the given name.
+    pub(crate) fn read<R>(&self, name: &str, f: impl FnOnce(&MemTable) -> R) -> R {
+        let tables = self.tables.read().expect("lock is not poisoned");
+        f(tables.get(name).expect("all tables are created"))
+    }
+
+    /// Writes the table with the given name, or fails with the given error if the transaction is
+    /// read-only.
+    pub(crate) fn write<R>(
+        &self,
+        name: &str,
+        error: fn(u32) -> Error,
+        f: impl FnOnce(&mut MemTable) -> R,
+    ) -> Result<R, Error> {
+        if self.read_only {
+            return Err(error(ACCESS_DENIED))
+        }
+        let mut tables = self.tables.write().expect("lock is not poisoned");
+        Ok(f(Arc::make_mut(tables.get_mut(name).expect("all tables are created"))))
+    }
+}
+
+/// In-memory database with the transaction semantics of MDBX.
+///
+/// A read-only transaction sees the database as it was at its start. There is only one read-write
+/// transaction at a time, and its changes are visible to the transactions that start after its
+/// commit.
+#[derive(Debug)]
+pub struct MemDb {
+    /// The tables as of the last commit.
+    tables: RwLock<Tables>,
+    /// Whether there is a read-write transaction.
+    writing: Mutex<bool>,
+    /// Notified when a read-write transaction ends.
+    write_done: Condvar,
+}
+
+impl<'a> DatabaseGAT<'a> for MemDb {
+    type TX = Tx<'a>;
+    type TXMut = Tx<'a>;
+}
+
+impl Database for MemDb {
+    fn tx(&self) -> Result<<Self as DatabaseGAT<'_>>::TX, Error> {
+        Ok(Tx::new(self, self.snapshot(true)))
+    }
+
+    /// Waits for the running read-write transaction to end, if there is one.
+    fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
+        let mut writing = self.writing.lock().expect("lock is not poisoned");
+        while *writing {
+            writing = self.write_done.wait(writing).expect("lock is not poisoned");
+        }
+        *writing = true;
+
+        Ok(Tx::new(self, self.snapshot(false)))
+    }
+}
+
+impl MemDb {
+    /// Creates an empty database with all the defined tables.
+    pub fn new() -> Self {
+        let tables = TABLES
+            .iter()
+            .map(|(table_type, name)| {
+                let dup_sort = matches!(table_type, TableType::DupSort);
+                (*name, Arc::new(MemTable { dup_sort, entries: BTreeSet::new() }))
+            })
+            .collect();
+
+        Self { tables: RwLock::new(tables), writing: Mutex::new(false), write_done: Condvar::new() }
+    }
+
+    /// The tables of a new transaction.
+    fn snapshot(&self, read_only: bool) -> TxTables {
+        let tables = self.tables.read().expect("lock is not poisoned").clone();
+        TxTables { tables: RwLock::new(tables), read_only }
+    }
+
+    /// Replaces the tables with the tables of a committed read-write transaction.
+    pub(crate) fn commit(&self, tables: Tables) {
+        *self.tables.write().expect("lock is not poisoned") = tables;
+    }
+
+    /// Ends the read-write transaction, so the next one can start.
+    pub(crate) fn end_write(&self) {
+        *self.writing.lock().expect("lock is not poisoned") = false;
+        self.write_done.notify_one();
+    }
+}
+
+impl Default for MemDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemDb, ACCESS_DENIED};
+    use crate::{
+        database::Database,
+        implementation::test_suite::table_test_suite,
+        tables::CanonicalHeaders,
+        transaction::{DbTx, DbTxMut},
+        Error,
+    };
+    use reth_primitives::H256;
+    use std::sync::Arc;
+
+    table_test_suite!(Arc::new(MemDb::new()));
+
+    #[test]
+    fn read_snapshot_of_start() {
+        let db = MemDb::new();
+        let reader = db.tx().unwrap();
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(1, H256::zero()).unwrap();
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(1), Ok(None));
+        tx.commit().unwrap();
+
+        assert_eq!(reader.get::<CanonicalHeaders>(1), Ok(None));
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(1), Ok(Some(H256::zero())));
+    }
+
+    #[test]
+    fn discard_uncommitted_changes() {
+        let db = MemDb::new();
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(1, H256::zero()).unwrap();
+        drop(tx);
+
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(1), Ok(None));
+    }
+
+    #[test]
+    fn reject_writes_of_read_only_transactions() {
+        let db = MemDb::new();
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.put::<CanonicalHeaders>(1, H256::zero()), Err(Error::Write(ACCESS_DENIED)));
+    }
+}
@@ -0,0 +1,111 @@
+//! Transaction of the in-memory database.
+
+use super::{cursor::Cursor, MemDb, TxTables};
+use crate::{
+    table::{Compress, DupSort, Encode, Table},
+    tables::utils::decode_one,
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    Error,
+};
+use std::borrow::Cow;
+
+/// Transaction of the in-memory database, on its own snapshot of the tables.
+#[derive(Debug)]
+pub struct Tx<'db> {
+    /// The database of the transaction.
+    db: &'db MemDb,
+    /// The tables of the transaction.
+    tables: TxTables,
+}
+
+impl<'db> Tx<'db> {
+    /// Creates a new transaction on the tables of a snapshot.
+    pub(crate) fn new(db: &'db MemDb, tables: TxTables) -> Self {
+        Self { db, tables }
+    }
+
+    /// Create db Cursor
+    pub fn new_cursor<T: Table>(&self) -> Result<Cursor<'_, T>, Error> {
+        Ok(Cursor::new(&self.tables))
+    }
+}
+
+impl Drop for Tx<'_> {
+    fn drop(&mut self) {
+        if !self.tables.read_only {
+            self.db.end_write();
+        }
+    }
+}
+
+impl<'a> DbTxGAT<'a> for Tx<'_> {
+    type Cursor<T: Table> = Cursor<'a, T>;
+    type DupCursor<T: DupSort> = Cursor<'a, T>;
+}
+
+impl<'a> DbTxMutGAT<'a> for Tx<'_> {
+    type CursorMut<T: Table> = Cursor<'a, T>;
+    type DupCursorMut<T: DupSort> = Cursor<'a, T>;
+}
+
+impl<'tx> DbTx<'tx> for Tx<'tx> {
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, Error> {
+        let key = key.encode();
+        self.tables
+            .read(T::NAME, |table| {
+                table.seek(key.as_ref()).filter(|(found, _)| found == key.as_ref()).cloned()
+            })
+            .map(|(_, value)| decode_one::<T>(Cow::Owned(value)))
+            .transpose()
+    }
+
+    /// Makes the changes of a read-write transaction visible to the transactions that start
+    /// afterwards.
+    fn commit(mut self) -> Result<bool, Error> {
+        if !self.tables.read_only {
+            let tables =
+                std::mem::take(self.tables.tables.get_mut().expect("lock is not poisoned"));
+            self.db.commit(tables);
+        }
+        Ok(false)
+    }
+
+    fn cursor<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error> {
+        self.new_cursor()
+    }
+}
+
+impl DbTxMut<'_> for Tx<'_> {
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        self.tables.write(T::NAME, Error::Write, |table| table.put(entry))
+    }
+
+    /// Deletes all values of the key, or only the given value.
+    fn delete<T: Table>(&self, key: T::Key, value: Option<T::Value>) -> Result<bool, Error> {
+        let key = key.encode().as_ref().to_vec();
+        let value = value.map(|value| value.compress().as_ref().to_vec());
+        self.tables.write(T::NAME, Error::Delete, |table| match value {
+            Some(value) => table.entries.remove(&(key, value)),
+            None => table.remove_key(&key),
+        })
+    }
+
+    fn clear<T: Table>(&self) -> Result<(), Error> {
+        self.tables.write(T::NAME, Error::Delete, |table| table.entries.clear())
+    }
+
+    fn cursor_mut<T: Table>(&self) -> Result<<Self as DbTxMutGAT<'_>>::CursorMut<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_mut<T: DupSort>(
+        &self,
+    ) -> Result<<Self as DbTxMutGAT<'_>>::DupCursorMut<T>, Error> {
+        self.new_cursor()
+    }
+}
@@ -1,2 +1,6 @@
 #[cfg(feature = "mdbx")]
 pub(crate) mod mdbx;
+#[cfg(feature = "mem")]
+pub(crate) mod mem;
+#[cfg(test)]
+mod test_suite;
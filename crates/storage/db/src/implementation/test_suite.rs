@@ -0,0 +1,393 @@
+//! Tests of the tables that every database implementation has to pass.
+//!
+//! The tests are generated for an implementation with [`table_test_suite`].
+
+use crate::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    database::Database,
+    models::ShardedKey,
+    tables::{AccountHistory, CanonicalHeaders, Headers, PlainStorageState},
+    transaction::{DbTx, DbTxMut},
+    Error,
+};
+use reth_primitives::{Address, Header, IntegerList, StorageEntry, H256, U256};
+use std::{ops::Bound, str::FromStr};
+
+const ERROR_PUT: &str = "Not able to insert value into table.";
+const ERROR_GET: &str = "Not able to get value from table.";
+const ERROR_COMMIT: &str = "Not able to commit transaction.";
+const ERROR_RETURN_VALUE: &str = "Mismatching result.";
+const ERROR_INIT_TX: &str = "Failed to create a transaction.";
+const ERROR_ETH_ADDRESS: &str = "Invalid address.";
+
+/// Generates a test for each test of the suite, on a new database that is created by the given
+/// expression, which returns an `Arc` of the database.
+macro_rules! table_test_suite {
+    ($create_db:expr) => {
+        $crate::implementation::test_suite::table_test_suite!(
+            $create_db,
+            [
+                db_manual_put_get,
+                db_cursor_walk,
+                db_cursor_seek_exact_or_previous_key,
+                db_cursor_walk_range,
+                db_cursor_walk_back,
+                db_cursor_insert,
+                db_cursor_append_failure,
+                db_dup_sort,
+                db_iterate_over_all_dup_values,
+                dup_value_with_same_subkey,
+                db_sharded_key,
+                db_sharded_key_walk_prefix,
+            ]
+        );
+    };
+    ($create_db:expr, [$($test:ident),* $(,)?]) => {
+        $(
+            #[test]
+            fn $test() {
+                let db = $create_db;
+                $crate::implementation::test_suite::$test(&*db);
+            }
+        )*
+    };
+}
+pub(crate) use table_test_suite;
+
+pub(crate) fn db_manual_put_get<DB: Database>(env: &DB) {
+    let value = Header::default();
+    let key = (1u64, H256::zero());
+
+    // PUT
+    let tx = env.tx_mut().expect(ERROR_INIT_TX);
+    tx.put::<Headers>(key.into(), value.clone()).expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    // GET
+    let tx = env.tx().expect(ERROR_INIT_TX);
+    let result = tx.get::<Headers>(key.into()).expect(ERROR_GET);
+    assert!(result.expect(ERROR_RETURN_VALUE) == value);
+    tx.commit().expect(ERROR_COMMIT);
+}
+
+pub(crate) fn db_cursor_walk<DB: Database>(env: &DB) {
+    let value = Header::default();
+    let key = (1u64, H256::zero());
+
+    // PUT
+    let tx = env.tx_mut().expect(ERROR_INIT_TX);
+    tx.put::<Headers>(key.into(), value.clone()).expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    // Cursor
+    let tx = env.tx().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor::<Headers>().unwrap();
+
+    let first = cursor.first().unwrap();
+    assert!(first.is_some(), "First should be our put");
+
+    // Walk
+    let walk = cursor.walk(key.into()).unwrap();
+    let first = walk.into_iter().next().unwrap().unwrap();
+    assert_eq!(first.1, value, "First next should be put value");
+}
+
+pub(crate) fn db_cursor_seek_exact_or_previous_key<DB: Database>(db: &DB) {
+    // PUT
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    vec![0, 1, 3]
+        .into_iter()
+        .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+        .expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    // Cursor
+    let missing_key = 2;
+    let tx = db.tx().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor::<CanonicalHeaders>().unwrap();
+    assert_eq!(cursor.current(), Ok(None));
+
+    // Seek exact
+    let exact = cursor.seek_exact(missing_key).unwrap();
+    assert_eq!(exact, None);
+    assert_eq!(cursor.current(), Ok(Some((missing_key + 1, H256::zero()))));
+    assert_eq!(cursor.prev(), Ok(Some((missing_key - 1, H256::zero()))));
+    assert_eq!(cursor.prev(), Ok(Some((missing_key - 2, H256::zero()))));
+}
+
+pub(crate) fn db_cursor_walk_range<DB: Database>(db: &DB) {
+    // PUT
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    vec![0, 1, 2, 3, 5]
+        .into_iter()
+        .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+        .expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    let tx = db.tx().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor::<CanonicalHeaders>().unwrap();
+    let mut walk_range = |range: (Bound<u64>, Bound<u64>)| {
+        cursor.walk_range(range).unwrap().map(|entry| entry.unwrap().0).collect::<Vec<_>>()
+    };
+
+    assert_eq!(walk_range((Bound::Included(1), Bound::Included(3))), vec![1, 2, 3]);
+    assert_eq!(walk_range((Bound::Included(1), Bound::Excluded(3))), vec![1, 2]);
+    assert_eq!(walk_range((Bound::Excluded(1), Bound::Unbounded)), vec![2, 3, 5]);
+    assert_eq!(walk_range((Bound::Unbounded, Bound::Included(1))), vec![0, 1]);
+    // the range starts at a missing key
+    assert_eq!(walk_range((Bound::Included(4), Bound::Included(10))), vec![5]);
+    assert_eq!(walk_range((Bound::Excluded(4), Bound::Unbounded)), vec![5]);
+    // the range has no keys
+    assert_eq!(walk_range((Bound::Included(6), Bound::Unbounded)), Vec::<u64>::new());
+    assert_eq!(walk_range((Bound::Excluded(5), Bound::Unbounded)), Vec::<u64>::new());
+}
+
+pub(crate) fn db_cursor_walk_back<DB: Database>(db: &DB) {
+    // PUT
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    vec![1, 2, 4]
+        .into_iter()
+        .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+        .expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    let tx = db.tx().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor::<CanonicalHeaders>().unwrap();
+    let mut walk_back = |start_key: Option<u64>| {
+        cursor.walk_back(start_key).unwrap().map(|entry| entry.unwrap().0).collect::<Vec<_>>()
+    };
+
+    assert_eq!(walk_back(None), vec![4, 2, 1]);
+    assert_eq!(walk_back(Some(2)), vec![2, 1]);
+    // the walk starts at the previous key of a missing key
+    assert_eq!(walk_back(Some(3)), vec![2, 1]);
+    assert_eq!(walk_back(Some(10)), vec![4, 2, 1]);
+    assert_eq!(walk_back(Some(0)), Vec::<u64>::new());
+}
+
+pub(crate) fn db_cursor_insert<DB: Database>(db: &DB) {
+    // PUT
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    vec![0, 1, 3, 4, 5]
+        .into_iter()
+        .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+        .expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    let key_to_insert = 2;
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor_mut::<CanonicalHeaders>().unwrap();
+
+    // INSERT
+    cursor.seek_exact(1).unwrap();
+    assert_eq!(cursor.insert(key_to_insert, H256::zero()), Ok(()));
+    assert_eq!(cursor.current(), Ok(Some((key_to_insert, H256::zero()))));
+
+    // INSERT (failure)
+    assert_eq!(cursor.insert(key_to_insert, H256::zero()), Err(Error::Write(4294936497)));
+    assert_eq!(cursor.current(), Ok(Some((key_to_insert, H256::zero()))));
+}
+
+pub(crate) fn db_cursor_append_failure<DB: Database>(db: &DB) {
+    // PUT
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    vec![0, 1, 3, 4, 5]
+        .into_iter()
+        .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+        .expect(ERROR_PUT);
+    tx.commit().expect(ERROR_COMMIT);
+
+    // APPEND
+    let key_to_append = 2;
+    let tx = db.tx_mut().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor_mut::<CanonicalHeaders>().unwrap();
+    cursor.seek_exact(1).unwrap();
+    assert_eq!(cursor.append(key_to_append, H256::zero()), Err(Error::Write(4294936878)));
+    assert_eq!(cursor.current(), Ok(Some((5, H256::zero())))); // the end of table
+}
+
+pub(crate) fn db_dup_sort<DB: Database>(env: &DB) {
+    let key =
+        Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047").expect(ERROR_ETH_ADDRESS);
+
+    // PUT (0,0)
+    let value00 = StorageEntry::default();
+    env.update(|tx| tx.put::<PlainStorageState>(key, value00.clone()).expect(ERROR_PUT)).unwrap();
+
+    // PUT (2,2)
+    let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
+    env.update(|tx| tx.put::<PlainStorageState>(key, value22.clone()).expect(ERROR_PUT)).unwrap();
+
+    // PUT (1,1)
+    let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
+    env.update(|tx| tx.put::<PlainStorageState>(key, value11.clone()).expect(ERROR_PUT)).unwrap();
+
+    // Iterate with cursor
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+
+        // Notice that value11 and value22 have been ordered in the DB.
+        assert!(Some(value00) == cursor.next_dup_val().unwrap());
+        assert!(Some(value11.clone()) == cursor.next_dup_val().unwrap());
+        assert!(Some(value22) == cursor.next_dup_val().unwrap());
+    }
+
+    // Seek value with exact subkey
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+        let mut walker = cursor.walk_dup(key, H256::from_low_u64_be(1)).unwrap();
+        assert_eq!(
+            (key, value11),
+            walker.next().expect("element should exist.").expect("should be able to retrieve it.")
+        );
+    }
+}
+
+pub(crate) fn db_iterate_over_all_dup_values<DB: Database>(env: &DB) {
+    let key1 =
+        Address::from_str("0x1111111111111111111111111111111111111111").expect(ERROR_ETH_ADDRESS);
+    let key2 =
+        Address::from_str("0x2222222222222222222222222222222222222222").expect(ERROR_ETH_ADDRESS);
+
+    // PUT key1 (0,0)
+    let value00 = StorageEntry::default();
+    env.update(|tx| tx.put::<PlainStorageState>(key1, value00.clone()).expect(ERROR_PUT)).unwrap();
+
+    // PUT key1 (1,1)
+    let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
+    env.update(|tx| tx.put::<PlainStorageState>(key1, value11.clone()).expect(ERROR_PUT)).unwrap();
+
+    // PUT key2 (2,2)
+    let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
+    env.update(|tx| tx.put::<PlainStorageState>(key2, value22.clone()).expect(ERROR_PUT)).unwrap();
+
+    // Iterate with walk_dup
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+        let first = cursor.first().unwrap().unwrap();
+        let mut walker = cursor.walk_dup(first.0, first.1.key).unwrap();
+
+        // Notice that value11 and value22 have been ordered in the DB.
+        assert_eq!(Some(Ok((key1, value00.clone()))), walker.next());
+        assert_eq!(Some(Ok((key1, value11.clone()))), walker.next());
+        // NOTE: Dup cursor does NOT iterates on all values but only on duplicated values of the
+        // same key. assert_eq!(Ok(Some(value22.clone())), walker.next());
+        assert_eq!(None, walker.next());
+    }
+
+    // Walk a key without values at or after the subkey
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+        let missing = Address::from_str("0x1515151515151515151515151515151515151515")
+            .expect(ERROR_ETH_ADDRESS);
+        assert_eq!(cursor.walk_dup(missing, H256::zero()).unwrap().next(), None);
+        assert_eq!(cursor.walk_dup(key1, H256::from_low_u64_be(2)).unwrap().next(), None);
+    }
+
+    // Iterate by using `walk`
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+        let first = cursor.first().unwrap().unwrap();
+        let mut walker = cursor.walk(first.0).unwrap();
+        assert_eq!(Some(Ok((key1, value00))), walker.next());
+        assert_eq!(Some(Ok((key1, value11))), walker.next());
+        assert_eq!(Some(Ok((key2, value22))), walker.next());
+    }
+}
+
+pub(crate) fn dup_value_with_same_subkey<DB: Database>(env: &DB) {
+    let key1 =
+        Address::from_str("0x1111111111111111111111111111111111111111").expect(ERROR_ETH_ADDRESS);
+
+    // PUT key1 (0,1)
+    let value01 = StorageEntry { key: H256::from_low_u64_be(0), value: U256::from(1) };
+    env.update(|tx| tx.put::<PlainStorageState>(key1, value01.clone()).expect(ERROR_PUT)).unwrap();
+
+    // PUT key1 (0,0)
+    let value00 = StorageEntry::default();
+    env.update(|tx| tx.put::<PlainStorageState>(key1, value00.clone()).expect(ERROR_PUT)).unwrap();
+
+    // Iterate with walk
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+        let first = cursor.first().unwrap().unwrap();
+        let mut walker = cursor.walk(first.0).unwrap();
+
+        // NOTE: Both values are present
+        assert_eq!(Some(Ok((key1, value00.clone()))), walker.next());
+        assert_eq!(Some(Ok((key1, value01.clone()))), walker.next());
+        assert_eq!(None, walker.next());
+    }
+
+    // seek_by_key_subkey
+    {
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup::<PlainStorageState>().unwrap();
+
+        // NOTE: There are two values with same SubKey but only first one is shown
+        assert_eq!(Ok(Some(value00.clone())), cursor.seek_by_key_subkey(key1, value00.key));
+    }
+}
+
+pub(crate) fn db_sharded_key<DB: Database>(db: &DB) {
+    let real_key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047").unwrap();
+
+    for i in 1..5 {
+        let key = ShardedKey::new(real_key, i * 100);
+        let list: IntegerList = vec![i * 100u64].into();
+
+        db.update(|tx| tx.put::<AccountHistory>(key.clone(), list.clone()).expect("")).unwrap();
+    }
+
+    // Seek value with non existing key.
+    {
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor::<AccountHistory>().unwrap();
+
+        // It will seek the one greater or equal to the query. Since we have `Address | 100`,
+        // `Address | 200` in the database and we're querying `Address | 150` it will return us
+        // `Address | 200`.
+        let mut walker = cursor.walk(ShardedKey::new(real_key, 150)).unwrap();
+        let (key, list) =
+            walker.next().expect("element should exist.").expect("should be able to retrieve it.");
+
+        assert_eq!(ShardedKey::new(real_key, 200), key);
+        let list200: IntegerList = vec![200u64].into();
+        assert_eq!(list200, list);
+    }
+}
+
+pub(crate) fn db_sharded_key_walk_prefix<DB: Database>(db: &DB) {
+    let real_key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047").unwrap();
+    let other_key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45048").unwrap();
+
+    for key in [real_key, other_key] {
+        for i in 1..4 {
+            let key = ShardedKey::new(key, i * 100);
+            let list: IntegerList = vec![i * 100u64].into();
+
+            db.update(|tx| tx.put::<AccountHistory>(key.clone(), list.clone()).expect("")).unwrap();
+        }
+    }
+
+    let tx = db.tx().expect(ERROR_INIT_TX);
+    let mut cursor = tx.cursor::<AccountHistory>().unwrap();
+
+    // The walk ends at the shards of the next address.
+    let shards = cursor
+        .walk_prefix(ShardedKey::new(real_key, 150), real_key)
+        .unwrap()
+        .map(|entry| entry.unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(shards, vec![ShardedKey::new(real_key, 200), ShardedKey::new(real_key, 300)]);
+
+    // There are no shards after the start.
+    let mut walker = cursor.walk_prefix(ShardedKey::new(other_key, 301), other_key).unwrap();
+    assert!(walker.next().is_none());
+}
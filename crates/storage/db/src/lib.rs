@@ -13,6 +13,7 @@ pub mod abstraction;
 mod implementation;
 pub mod tables;
 mod utils;
+pub mod version;
 
 #[cfg(feature = "mdbx")]
 /// Bindings for [MDBX](https://libmdbx.dqdkfa.ru/).
@@ -21,6 +22,12 @@ pub mod mdbx {
     pub use reth_libmdbx::*;
 }
 
+#[cfg(feature = "mem")]
+/// In-memory database, e.g. for tests.
+pub mod mem {
+    pub use crate::implementation::mem::*;
+}
+
 pub use abstraction::*;
 pub use reth_interfaces::db::Error;
 pub use tables::*;
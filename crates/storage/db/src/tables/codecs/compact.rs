@@ -37,14 +37,33 @@ impl_compression_for_compact!(
     Header,
     Account,
     Log,
-    Receipt,
     TxType,
     StorageEntry,
     StoredBlockBody,
-    StoredBlockOmmers
+    StoredBlockOmmers,
+    StoredBlockWithdrawals,
+    StoredTxLocation,
+    BranchNodeCompact
 );
-impl_compression_for_compact!(AccountBeforeTx, TransactionSigned);
+impl_compression_for_compact!(AccountBeforeTx);
 impl_compression_for_compact!(CompactU256);
+// Leading zeros of integers are stripped, hashes and addresses are stored as they are.
+impl_compression_for_compact!(u64, U256, H256, H160);
+
+/// Raw bytes, like bytecodes, are stored as they are.
+impl Compress for Vec<u8> {
+    type Compressed = Vec<u8>;
+
+    fn compress(self) -> Self::Compressed {
+        self
+    }
+}
+
+impl Decompress for Vec<u8> {
+    fn decompress<B: Into<bytes::Bytes>>(value: B) -> Result<Vec<u8>, Error> {
+        Ok(value.into().to_vec())
+    }
+}
 
 /// Adds wrapper structs for some primitive types so they can use StructFlags from Compact, when
 /// used as pure table values.
@@ -82,3 +101,30 @@ macro_rules! add_wrapper_struct {
 
 add_wrapper_struct!((U256, CompactU256));
 add_wrapper_struct!((u64, CompactU64));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_leading_zeros() {
+        assert!(0u64.compress().is_empty());
+        assert_eq!(1_000_000u64.compress(), vec![0x0f, 0x42, 0x40]);
+        assert_eq!(u64::decompress(vec![0x0f, 0x42, 0x40]).unwrap(), 1_000_000);
+        assert_eq!(u64::decompress(vec![]).unwrap(), 0);
+
+        assert_eq!(U256::from(255).compress(), vec![0xff]);
+        assert_eq!(U256::decompress(vec![0xff]).unwrap(), U256::from(255));
+    }
+
+    #[test]
+    fn keeps_fixed_size_values() {
+        let hash = H256::random();
+        assert_eq!(hash.compress(), hash.as_bytes());
+        assert_eq!(H256::decompress(hash.as_bytes().to_vec()).unwrap(), hash);
+
+        let bytecode = vec![0x60, 0x80, 0x60, 0x40];
+        assert_eq!(bytecode.clone().compress(), bytecode);
+        assert_eq!(Vec::<u8>::decompress(bytecode.clone()).unwrap(), bytecode);
+    }
+}
@@ -7,3 +7,4 @@ pub mod fuzz;
 
 mod postcard;
 mod scale;
+mod zstd;
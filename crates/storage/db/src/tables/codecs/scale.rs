@@ -3,7 +3,6 @@ use crate::{
     Error,
 };
 use parity_scale_codec::decode_from_bytes;
-use reth_primitives::*;
 
 mod sealed {
     pub trait Sealed {}
@@ -42,8 +41,4 @@ macro_rules! impl_compression_for_scale {
     };
 }
 
-impl ScaleValue for Vec<u8> {}
-impl sealed::Sealed for Vec<u8> {}
-
-impl_compression_for_scale!(U256, H256, H160);
-impl_compression_for_scale!(u8, u32, u16, u64);
+impl_compression_for_scale!(u8, u32, u16);
@@ -0,0 +1,219 @@
+//! Zstd compression of large table values, on top of their [Compact] encoding.
+//!
+//! Receipts and transactions repeat the same contracts, event topics, function selectors and zero
+//! paddings over and over, which compresses well with a shared dictionary even though every value
+//! is compressed on its own.
+//!
+//! Every value starts with a byte that tells how the rest of it is stored:
+//! - [UNCOMPRESSED]: the [Compact] encoding of the value, for values that don't shrink.
+//! - [ZSTD_V1]: the length of the [Compact] encoding as a big-endian `u32`, followed by a zstd
+//!   frame compressed with the version 1 dictionary of the type.
+//!
+//! A dictionary must never change once values were written with it. Better dictionaries, e.g.
+//! trained on a synced database, are added as a new version, so existing values stay readable.
+
+use crate::{
+    table::{Compress, Decompress},
+    Error,
+};
+use reth_codecs::Compact;
+use reth_primitives::{hex_literal::hex, Receipt, TransactionSigned};
+use std::cell::RefCell;
+use zstd::bulk::{Compressor, Decompressor};
+
+/// The value is stored without compression.
+const UNCOMPRESSED: u8 = 0;
+/// The value is compressed with the version 1 dictionary.
+const ZSTD_V1: u8 = 1;
+
+/// The zstd compression level.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Well-known contract addresses: WETH, USDT and USDC.
+const CONTRACTS: [[u8; 20]; 3] = [
+    hex!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+    hex!("dac17f958d2ee523a2206206994597c13d831ec7"),
+    hex!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+];
+
+/// Event topics of ERC-20 `Approval`, WETH `Deposit` and `Withdrawal`, Uniswap V2 `Sync` and
+/// `Swap`, Uniswap V3 `Swap`, and ERC-20 `Transfer`.
+const TOPICS: [[u8; 32]; 7] = [
+    hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"),
+    hex!("e1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c"),
+    hex!("7fcf532c15f0a6db0bd6d0e038bea71d30d808c7d98cb3bf7268a95bf5081b65"),
+    hex!("1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"),
+    hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822"),
+    hex!("c42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67"),
+    hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+];
+
+/// Function selectors of ERC-20 `transferFrom`, `approve` and `transfer`.
+const SELECTORS: [[u8; 4]; 3] = [hex!("23b872dd"), hex!("095ea7b3"), hex!("a9059cbb")];
+
+/// The version 1 receipt dictionary.
+///
+/// This is a raw content dictionary, the most frequent content is placed last because zstd matches
+/// closer content with shorter offsets.
+fn receipt_dictionary_v1() -> Vec<u8> {
+    let mut dictionary = Vec::new();
+    dictionary.extend_from_slice(&[0xff; 32]);
+    CONTRACTS.iter().for_each(|contract| dictionary.extend_from_slice(contract));
+    TOPICS.iter().for_each(|topic| dictionary.extend_from_slice(topic));
+    dictionary.extend_from_slice(&[0; 32]);
+    dictionary
+}
+
+/// The version 1 transaction dictionary, see [receipt_dictionary_v1].
+fn transaction_dictionary_v1() -> Vec<u8> {
+    let mut dictionary = Vec::new();
+    dictionary.extend_from_slice(&[0xff; 32]);
+    CONTRACTS.iter().for_each(|contract| dictionary.extend_from_slice(contract));
+    for selector in SELECTORS {
+        // selectors are followed by an address argument, which is padded to 32 bytes
+        dictionary.extend_from_slice(&selector);
+        dictionary.extend_from_slice(&[0; 12]);
+    }
+    dictionary.extend_from_slice(&[0; 32]);
+    dictionary
+}
+
+thread_local! {
+    static RECEIPT_COMPRESSOR: RefCell<Compressor<'static>> = RefCell::new(
+        Compressor::with_dictionary(COMPRESSION_LEVEL, &receipt_dictionary_v1())
+            .expect("valid dictionary")
+    );
+    static RECEIPT_DECOMPRESSOR: RefCell<Decompressor<'static>> = RefCell::new(
+        Decompressor::with_dictionary(&receipt_dictionary_v1()).expect("valid dictionary")
+    );
+    static TRANSACTION_COMPRESSOR: RefCell<Compressor<'static>> = RefCell::new(
+        Compressor::with_dictionary(COMPRESSION_LEVEL, &transaction_dictionary_v1())
+            .expect("valid dictionary")
+    );
+    static TRANSACTION_DECOMPRESSOR: RefCell<Decompressor<'static>> = RefCell::new(
+        Decompressor::with_dictionary(&transaction_dictionary_v1()).expect("valid dictionary")
+    );
+}
+
+/// Implements compression for Compact types, with zstd and the given thread local
+/// (de)compressors.
+macro_rules! impl_compression_for_zstd {
+    ($($name:tt => ($compressor:ident, $decompressor:ident)),+) => {
+        $(
+            impl Compress for $name {
+                type Compressed = Vec<u8>;
+
+                fn compress(self) -> Self::Compressed {
+                    let mut encoded = vec![];
+                    let _ = Compact::to_compact(self, &mut encoded);
+                    $compressor.with(|compressor| compress(&mut compressor.borrow_mut(), encoded))
+                }
+            }
+
+            impl Decompress for $name {
+                fn decompress<B: Into<bytes::Bytes>>(value: B) -> Result<$name, Error> {
+                    let value = value.into();
+                    let encoded = $decompressor.with(|decompressor| {
+                        decompress(&mut decompressor.borrow_mut(), &value)
+                    })?;
+                    let (obj, _) = Compact::from_compact(&encoded, encoded.len());
+                    Ok(obj)
+                }
+            }
+        )+
+    };
+}
+
+impl_compression_for_zstd!(
+    Receipt => (RECEIPT_COMPRESSOR, RECEIPT_DECOMPRESSOR),
+    TransactionSigned => (TRANSACTION_COMPRESSOR, TRANSACTION_DECOMPRESSOR)
+);
+
+/// Compresses the encoded value, unless that doesn't make it smaller.
+fn compress(compressor: &mut Compressor<'static>, encoded: Vec<u8>) -> Vec<u8> {
+    if let Ok(compressed) = compressor.compress(&encoded) {
+        if compressed.len() + 4 < encoded.len() {
+            let mut value = Vec::with_capacity(compressed.len() + 5);
+            value.push(ZSTD_V1);
+            value.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+            value.extend_from_slice(&compressed);
+            return value
+        }
+    }
+
+    let mut value = Vec::with_capacity(encoded.len() + 1);
+    value.push(UNCOMPRESSED);
+    value.extend_from_slice(&encoded);
+    value
+}
+
+/// Returns the encoded value of a stored value.
+fn decompress(decompressor: &mut Decompressor<'static>, value: &[u8]) -> Result<Vec<u8>, Error> {
+    match value.split_first() {
+        Some((&UNCOMPRESSED, encoded)) => Ok(encoded.to_vec()),
+        Some((&ZSTD_V1, rest)) if rest.len() >= 4 => {
+            let (len, compressed) = rest.split_at(4);
+            let len = u32::from_be_bytes(len.try_into().expect("4 bytes")) as usize;
+            decompressor.decompress(compressed, len).map_err(|_| Error::DecodeError)
+        }
+        _ => Err(Error::DecodeError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Log, TxType, H256};
+
+    fn transfer_receipt(logs: usize) -> Receipt {
+        Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21_000,
+            bloom: Default::default(),
+            logs: (0..logs)
+                .map(|i| Log {
+                    address: CONTRACTS[i % CONTRACTS.len()].into(),
+                    topics: vec![
+                        H256(TOPICS[TOPICS.len() - 1]),
+                        H256::from_low_u64_be(i as u64),
+                        H256::from_low_u64_be(i as u64 + 1),
+                    ],
+                    data: vec![0; 32].into(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn compresses_receipts() {
+        let receipt = transfer_receipt(10);
+        let mut encoded = vec![];
+        receipt.clone().to_compact(&mut encoded);
+
+        let compressed = receipt.clone().compress();
+        assert_eq!(compressed[0], ZSTD_V1);
+        assert!(compressed.len() < encoded.len() / 2);
+        assert_eq!(Receipt::decompress(compressed).unwrap(), receipt);
+    }
+
+    #[test]
+    fn stores_incompressible_values_uncompressed() {
+        let encoded = H256::random().as_bytes().to_vec();
+        let stored = RECEIPT_COMPRESSOR
+            .with(|compressor| compress(&mut compressor.borrow_mut(), encoded.clone()));
+        assert_eq!(stored[0], UNCOMPRESSED);
+        assert_eq!(stored.len(), encoded.len() + 1);
+
+        let decompressed = RECEIPT_DECOMPRESSOR
+            .with(|decompressor| decompress(&mut decompressor.borrow_mut(), &stored));
+        assert_eq!(decompressed.unwrap(), encoded);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert!(Receipt::decompress(vec![2, 0, 0, 0, 0]).is_err());
+        assert!(Receipt::decompress(vec![ZSTD_V1, 0]).is_err());
+        assert!(Receipt::decompress(vec![]).is_err());
+    }
+}
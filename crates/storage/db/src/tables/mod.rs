@@ -10,15 +10,15 @@ use crate::{
     tables::{
         codecs::CompactU256,
         models::{
-            accounts::{AccountBeforeTx, TransitionIdAddress},
-            blocks::{HeaderHash, StoredBlockOmmers},
-            BlockNumHash, ShardedKey,
+            accounts::{AccountBeforeTx, AddressStorageKey, TransitionIdAddress},
+            blocks::{HeaderHash, StoredBlockOmmers, StoredBlockWithdrawals, StoredTxLocation},
+            BlockNumHash, ShardedKey, StorageTrieKey,
         },
     },
 };
 use reth_primitives::{
-    Account, Address, BlockHash, BlockNumber, Header, IntegerList, Receipt, StorageEntry,
-    TransactionSigned, TransitionId, TxHash, TxNumber, H256,
+    Account, Address, BlockHash, BlockNumber, BranchNodeCompact, Header, IntegerList, Receipt,
+    StorageEntry, TransactionSigned, TransitionId, TxHash, TxNumber, H256,
 };
 
 use self::models::StoredBlockBody;
@@ -33,13 +33,14 @@ pub enum TableType {
 }
 
 /// Default tables that should be present inside database.
-pub const TABLES: [(TableType, &str); 23] = [
+pub const TABLES: [(TableType, &str); 30] = [
     (TableType::Table, CanonicalHeaders::const_name()),
     (TableType::Table, HeaderTD::const_name()),
     (TableType::Table, HeaderNumbers::const_name()),
     (TableType::Table, Headers::const_name()),
     (TableType::Table, BlockBodies::const_name()),
     (TableType::Table, BlockOmmers::const_name()),
+    (TableType::Table, BlockWithdrawals::const_name()),
     (TableType::Table, NonCanonicalTransactions::const_name()),
     (TableType::Table, Transactions::const_name()),
     (TableType::Table, TxHashNumber::const_name()),
@@ -54,9 +55,15 @@ pub const TABLES: [(TableType, &str); 23] = [
     (TableType::Table, StorageHistory::const_name()),
     (TableType::DupSort, AccountChangeSet::const_name()),
     (TableType::DupSort, StorageChangeSet::const_name()),
+    (TableType::Table, HashedAccount::const_name()),
+    (TableType::DupSort, HashedStorage::const_name()),
+    (TableType::Table, AccountsTrie::const_name()),
+    (TableType::Table, StoragesTrie::const_name()),
     (TableType::Table, TxSenders::const_name()),
     (TableType::Table, Config::const_name()),
     (TableType::Table, SyncStage::const_name()),
+    (TableType::Table, PruneCheckpoints::const_name()),
+    (TableType::Table, ForkChoice::const_name()),
 ];
 
 #[macro_export]
@@ -136,6 +143,11 @@ table!(
     ( BlockOmmers ) BlockNumHash | StoredBlockOmmers
 );
 
+table!(
+    /// Stores the withdrawals of the block, for blocks after Shanghai.
+    ( BlockWithdrawals ) BlockNumHash | StoredBlockWithdrawals
+);
+
 table!(
     /// Stores the transaction body from non canonical transactions.
     ( NonCanonicalTransactions ) BlockNumHashTxNumber | TransactionSigned
@@ -147,8 +159,9 @@ table!(
 );
 
 table!(
-    /// Stores the mapping of the transaction hash to the transaction number.
-    ( TxHashNumber ) TxHash | TxNumber
+    /// Stores the mapping of the transaction hash to the transaction number and the number of the
+    /// block that includes it.
+    ( TxHashNumber ) TxHash | StoredTxLocation
 );
 
 table!(
@@ -236,8 +249,9 @@ table!(
 );
 
 table!(
-    /// Stores pointers to transactions that changed each storage key.
-    ( StorageHistory ) AddressStorageKey | TransitionList
+    /// Stores the transitions that changed each storage key of an account, in shards like
+    /// [`AccountHistory`].
+    ( StorageHistory ) ShardedKey<AddressStorageKey> | TransitionList
 );
 
 dupsort!(
@@ -254,6 +268,30 @@ dupsort!(
     ( StorageChangeSet ) TransitionIdAddress | [H256] StorageEntry
 );
 
+table!(
+    /// Stores the current state of an [`Account`] indexed with `keccak256(Address)`.
+    /// It is used to calculate the state root.
+    ( HashedAccount ) H256 | Account
+);
+
+dupsort!(
+    /// Stores the current value of a storage key indexed with `keccak256(Address)` and
+    /// `keccak256(StorageKey)`. It is used to calculate the storage roots.
+    ( HashedStorage ) H256 | [H256] StorageEntry
+);
+
+table!(
+    /// Stores the branch nodes of the state trie that are needed to update the state root without
+    /// walking the whole hashed state, indexed with their path.
+    ( AccountsTrie ) StoredNibbles | BranchNodeCompact
+);
+
+table!(
+    /// Stores the branch nodes of the storage tries, indexed with `keccak256(Address)` and their
+    /// path.
+    ( StoragesTrie ) StorageTrieKey | BranchNodeCompact
+);
+
 table!(
     /// Stores the transaction sender for each transaction.
     /// It is needed to speed up execution stage and allows fetching signer without doing
@@ -271,6 +309,16 @@ table!(
     ( SyncStage ) StageId | BlockNumber
 );
 
+table!(
+    /// Stores the highest pruned block number of each prune segment.
+    ( PruneCheckpoints ) PruneSegmentId | BlockNumber
+);
+
+table!(
+    /// Stores the hashes of the latest safe and finalized blocks of the fork choice state.
+    ( ForkChoice ) ForkChoiceBlockId | BlockHash
+);
+
 ///
 /// Alias Types
 
@@ -278,6 +326,12 @@ table!(
 pub type TransitionList = IntegerList;
 /// Encoded stage id.
 pub type StageId = Vec<u8>;
+/// The path of a trie node, one nibble per byte.
+pub type StoredNibbles = Vec<u8>;
+/// Encoded prune segment id.
+pub type PruneSegmentId = Vec<u8>;
+/// Encoded id of a block of the fork choice state.
+pub type ForkChoiceBlockId = Vec<u8>;
 
 //
 // TODO: Temporary types, until they're properly defined alongside with the Encode and Decode Trait
@@ -290,6 +344,4 @@ pub type ConfigValue = Vec<u8>;
 /// Temporary placeholder type for DB.
 pub type BlockNumHashTxNumber = Vec<u8>;
 /// Temporary placeholder type for DB.
-pub type AddressStorageKey = Vec<u8>;
-/// Temporary placeholder type for DB.
 pub type Bytecode = Vec<u8>;
@@ -7,7 +7,7 @@ use crate::{
 };
 use bytes::Bytes;
 use reth_codecs::Compact;
-use reth_primitives::{Account, Address, TransitionId};
+use reth_primitives::{Account, Address, TransitionId, H256};
 use serde::{Deserialize, Serialize};
 
 /// Account as it is saved inside [`AccountChangeSet`]. [`Address`] is the subkey.
@@ -42,7 +42,7 @@ impl Compact for AccountBeforeTx {
 /// [`TxNumber`] concatenated with [`Address`]. Used as a key for [`StorageChangeSet`]
 ///
 /// Since it's used as a key, it isn't compressed when encoding it.
-#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TransitionIdAddress(pub (TransitionId, Address));
 
 impl TransitionIdAddress {
@@ -97,6 +97,56 @@ impl Decode for TransitionIdAddress {
 
 impl_fixed_arbitrary!(TransitionIdAddress, 28);
 
+/// [`Address`] concatenated with a storage key. Used as the key of the [`StorageHistory`] shards.
+///
+/// Since it's used as a key, it isn't compressed when encoding it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AddressStorageKey(pub (Address, H256));
+
+impl AddressStorageKey {
+    /// Return the address
+    pub fn address(&self) -> Address {
+        self.0 .0
+    }
+
+    /// Return the storage key
+    pub fn storage_key(&self) -> H256 {
+        self.0 .1
+    }
+}
+
+impl From<(Address, H256)> for AddressStorageKey {
+    fn from(tpl: (Address, H256)) -> Self {
+        AddressStorageKey(tpl)
+    }
+}
+
+impl Encode for AddressStorageKey {
+    type Encoded = [u8; 52];
+
+    fn encode(self) -> Self::Encoded {
+        let mut buf = [0u8; 52];
+
+        buf[..20].copy_from_slice(self.0 .0.as_bytes());
+        buf[20..].copy_from_slice(self.0 .1.as_bytes());
+        buf
+    }
+}
+
+impl Decode for AddressStorageKey {
+    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        let value: bytes::Bytes = value.into();
+        if value.len() != 52 {
+            return Err(Error::DecodeError)
+        }
+
+        let address = Address::from_slice(&value[..20]);
+        let storage_key = H256::from_slice(&value[20..]);
+
+        Ok(AddressStorageKey((address, storage_key)))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -127,4 +177,21 @@ mod test {
         let key = TransitionIdAddress::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
         assert_eq!(bytes, Encode::encode(key));
     }
+
+    #[test]
+    fn test_address_storage_key() {
+        let address = Address::from_str("ba5e000000000000000000000000000000000000").unwrap();
+        let storage_key = H256::from_low_u64_be(1);
+        let key = AddressStorageKey((address, storage_key));
+
+        let mut bytes = [0u8; 52];
+        bytes[..20].copy_from_slice(&address.0);
+        bytes[20..].copy_from_slice(&storage_key.0);
+
+        let encoded = Encode::encode(key.clone());
+        assert_eq!(encoded, bytes);
+
+        let decoded: AddressStorageKey = Decode::decode(encoded.to_vec()).unwrap();
+        assert_eq!(decoded, key);
+    }
 }
@@ -9,7 +9,7 @@ use crate::{
 };
 use bytes::Bytes;
 use reth_codecs::{main_codec, Compact};
-use reth_primitives::{BlockHash, BlockNumber, Header, TxNumber, H256};
+use reth_primitives::{BlockHash, BlockNumber, Header, TxNumber, Withdrawal, H256};
 use serde::{Deserialize, Serialize};
 
 /// Total chain number of transactions. Value for [`CumulativeTxCount`]. // TODO:
@@ -58,6 +58,26 @@ pub struct StoredBlockOmmers {
     pub ommers: Vec<Header>,
 }
 
+/// The storage representation of the withdrawals of a block.
+///
+/// It is only stored for blocks after Shanghai.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+#[main_codec]
+pub struct StoredBlockWithdrawals {
+    /// The withdrawals of the block.
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+/// The location of a canonical transaction. Value for [`TxHashNumber`].
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+#[main_codec]
+pub struct StoredTxLocation {
+    /// The id of the transaction.
+    pub tx_number: TxNumber,
+    /// The number of the block that includes the transaction.
+    pub block_number: BlockNumber,
+}
+
 /// Hash of the block header. Value for [`CanonicalHeaders`]
 pub type HeaderHash = H256;
 
@@ -6,6 +6,9 @@ use crate::{
 };
 use reth_primitives::TxNumber;
 
+/// Number of indices in one shard.
+pub const NUM_OF_INDICES_IN_SHARD: usize = 2_000;
+
 /// Sometimes data can be too big to be saved for a single key. This helps out by dividing the data
 /// into different shards. Example:
 ///
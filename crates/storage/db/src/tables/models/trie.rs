@@ -0,0 +1,85 @@
+//! Trie related models and types.
+
+use crate::{
+    table::{Decode, Encode},
+    Error,
+};
+use bytes::Bytes;
+use reth_primitives::H256;
+use serde::{Deserialize, Serialize};
+
+/// `keccak256(Address)` concatenated with the path of a node in the storage trie of the account,
+/// one nibble per byte. Used as a key for [`StoragesTrie`](crate::tables::StoragesTrie).
+///
+/// The nodes of a storage trie are adjacent and sorted by their paths, with the root node first.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StorageTrieKey(pub (H256, Vec<u8>));
+
+impl StorageTrieKey {
+    /// Return the hashed address
+    pub fn hashed_address(&self) -> H256 {
+        self.0 .0
+    }
+
+    /// Return the path of the node
+    pub fn nibbles(&self) -> &[u8] {
+        &self.0 .1
+    }
+}
+
+impl From<(H256, Vec<u8>)> for StorageTrieKey {
+    fn from(tpl: (H256, Vec<u8>)) -> Self {
+        StorageTrieKey(tpl)
+    }
+}
+
+impl Encode for StorageTrieKey {
+    type Encoded = Vec<u8>;
+
+    fn encode(self) -> Self::Encoded {
+        let (hashed_address, nibbles) = self.0;
+        let mut buf = Vec::with_capacity(32 + nibbles.len());
+        buf.extend_from_slice(hashed_address.as_bytes());
+        buf.extend_from_slice(&nibbles);
+        buf
+    }
+}
+
+impl Decode for StorageTrieKey {
+    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        let value: bytes::Bytes = value.into();
+        if value.len() < 32 {
+            return Err(Error::DecodeError)
+        }
+
+        let hashed_address = H256::from_slice(&value[..32]);
+        let nibbles = value[32..].to_vec();
+
+        Ok(StorageTrieKey((hashed_address, nibbles)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_storage_trie_key() {
+        let hashed_address = H256::random();
+        let key = StorageTrieKey((hashed_address, vec![0x1, 0xa]));
+
+        let mut bytes = hashed_address.as_bytes().to_vec();
+        bytes.extend_from_slice(&[0x1, 0xa]);
+
+        let encoded = Encode::encode(key.clone());
+        assert_eq!(encoded, bytes);
+
+        let decoded: StorageTrieKey = Decode::decode(encoded).unwrap();
+        assert_eq!(decoded, key);
+
+        // the root node of the trie is the first key of the account
+        let root: StorageTrieKey = Decode::decode(hashed_address.as_bytes().to_vec()).unwrap();
+        assert!(root.nibbles().is_empty());
+        assert!(Encode::encode(root) < bytes);
+    }
+}
@@ -0,0 +1,119 @@
+//! The version of the database format.
+//!
+//! The version is stored in a file next to the database. It is bumped whenever the encoding of a
+//! table changes, so a database of an older format is refused instead of being misread.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The current version of the database format.
+///
+/// Version 1 stores integers, hashes and bytes with the compact codec. Databases that were created
+/// before have no version file.
+pub const DB_VERSION: u64 = 1;
+
+/// The name of the file in the database folder that contains the version.
+pub const DB_VERSION_FILE_NAME: &str = "database.version";
+
+/// Errors of the version check of a database.
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseVersionError {
+    /// The database has no version file, it was created before the format was versioned.
+    #[error("database version file is missing, the database must be resynced")]
+    MissingFile,
+    /// The version file doesn't contain a number.
+    #[error("database version file is malformed")]
+    MalformedFile,
+    /// The database has a different version than the current one.
+    #[error("database version {version} is not {DB_VERSION}, the database must be resynced")]
+    VersionMismatch {
+        /// The version of the database.
+        version: u64,
+    },
+    /// The database folder or the version file couldn't be accessed.
+    #[error("failed to access database version file {}: {source}", .path.display())]
+    Io {
+        /// The path of the version file.
+        path: PathBuf,
+        /// The error of the file system.
+        source: io::Error,
+    },
+}
+
+/// Returns the path of the version file of the database in the given folder.
+pub fn db_version_file_path<P: AsRef<Path>>(db_path: P) -> PathBuf {
+    db_path.as_ref().join(DB_VERSION_FILE_NAME)
+}
+
+/// Checks that the database in the given folder has the current version.
+pub fn check_db_version_file<P: AsRef<Path>>(db_path: P) -> Result<(), DatabaseVersionError> {
+    let path = db_version_file_path(db_path);
+    let version = match std::fs::read_to_string(&path) {
+        Ok(version) => version,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(DatabaseVersionError::MissingFile)
+        }
+        Err(source) => return Err(DatabaseVersionError::Io { path, source }),
+    };
+    let version = version.trim().parse::<u64>().map_err(|_| DatabaseVersionError::MalformedFile)?;
+    if version != DB_VERSION {
+        return Err(DatabaseVersionError::VersionMismatch { version })
+    }
+    Ok(())
+}
+
+/// Writes the current version to the version file of the database in the given folder.
+///
+/// It must only be called for a new database.
+pub fn create_db_version_file<P: AsRef<Path>>(db_path: P) -> io::Result<()> {
+    std::fs::write(db_version_file_path(db_path), DB_VERSION.to_string())
+}
+
+/// Creates the version file if the given database folder is empty, i.e. the database is new, or
+/// checks the version of the existing database otherwise.
+pub fn init_db_version_file<P: AsRef<Path>>(db_path: P) -> Result<(), DatabaseVersionError> {
+    let db_path = db_path.as_ref();
+    let io_err = |source| DatabaseVersionError::Io { path: db_version_file_path(db_path), source };
+    if std::fs::read_dir(db_path).map_err(io_err)?.next().is_none() {
+        return create_db_version_file(db_path).map_err(io_err)
+    }
+    check_db_version_file(db_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_version() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(check_db_version_file(&dir), Err(DatabaseVersionError::MissingFile)));
+
+        create_db_version_file(&dir).unwrap();
+        assert!(check_db_version_file(&dir).is_ok());
+
+        std::fs::write(db_version_file_path(&dir), "0").unwrap();
+        assert!(matches!(
+            check_db_version_file(&dir),
+            Err(DatabaseVersionError::VersionMismatch { version: 0 })
+        ));
+
+        std::fs::write(db_version_file_path(&dir), "v1").unwrap();
+        assert!(matches!(check_db_version_file(&dir), Err(DatabaseVersionError::MalformedFile)));
+    }
+
+    #[test]
+    fn init_version() {
+        let dir = tempfile::tempdir().unwrap();
+        init_db_version_file(&dir).unwrap();
+        assert!(check_db_version_file(&dir).is_ok());
+        init_db_version_file(&dir).unwrap();
+
+        // a folder with a database of an older format has no version file
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("mdbx.dat"), b"").unwrap();
+        assert!(matches!(init_db_version_file(&dir), Err(DatabaseVersionError::MissingFile)));
+    }
+}
@@ -138,6 +138,30 @@ where
         mdbx_result(unsafe { ffi::mdbx_env_sync_ex(self.env(), force, false) })
     }
 
+    /// Copy the environment to a new data file at `path`, which must not exist yet.
+    ///
+    /// The copy is made within a read transaction, so it's consistent even while write
+    /// transactions are running. If `compact` is set, free pages are omitted and all pages are
+    /// renumbered sequentially.
+    pub fn copy(&self, path: &Path, compact: bool) -> Result<()> {
+        let path = match CString::new(path.as_os_str().as_bytes()) {
+            Ok(path) => path,
+            Err(..) => return Err(crate::Error::Invalid),
+        };
+        let flags = if compact { ffi::MDBX_CP_COMPACT } else { ffi::MDBX_CP_DEFAULTS };
+        mdbx_result(unsafe { ffi::mdbx_env_copy(self.env(), path.as_ptr(), flags) })?;
+        Ok(())
+    }
+
+    /// Clears the reader slots of processes that exited without ending their read transactions.
+    ///
+    /// Returns the number of cleared slots.
+    pub fn reader_check(&self) -> Result<usize> {
+        let mut dead = 0;
+        mdbx_result(unsafe { ffi::mdbx_reader_check(self.env(), &mut dead) })?;
+        Ok(dead as usize)
+    }
+
     /// Retrieves statistics about this environment.
     pub fn stat(&self) -> Result<Stat> {
         unsafe {
@@ -310,6 +334,12 @@ impl Info {
         self.0.mi_recent_txnid as usize
     }
 
+    /// ID of the oldest transaction that is still read by a reader
+    #[inline]
+    pub fn latter_reader_txnid(&self) -> usize {
+        self.0.mi_latter_reader_txnid as usize
+    }
+
     /// Max reader slots in the environment
     #[inline]
     pub fn max_readers(&self) -> usize {
@@ -83,6 +83,52 @@ fn test_sync() {
     }
 }
 
+#[test]
+fn test_copy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let tx = env.begin_rw_txn().unwrap();
+    tx.put(&tx.open_db(None).unwrap(), b"key", b"value", WriteFlags::default()).unwrap();
+    tx.commit().unwrap();
+
+    // a long-lived read transaction doesn't block the copy
+    let _reader = env.begin_ro_txn().unwrap();
+    let backup = tempdir().unwrap();
+    env.copy(&backup.path().join("mdbx.dat"), true).unwrap();
+    // the destination must not exist
+    assert!(env.copy(&backup.path().join("mdbx.dat"), true).is_err());
+
+    let copy = Environment::new().set_flags(Mode::ReadOnly.into()).open(backup.path()).unwrap();
+    let tx = copy.begin_ro_txn().unwrap();
+    let value: Option<Vec<u8>> = tx.get(&tx.open_db(None).unwrap(), b"key").unwrap();
+    assert_eq!(value.as_deref(), Some(&b"value"[..]));
+}
+
+#[test]
+fn test_reader_check() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    for _ in 0..2 {
+        let tx = env.begin_rw_txn().unwrap();
+        tx.put(&tx.open_db(None).unwrap(), b"key", b"value", WriteFlags::default()).unwrap();
+        tx.commit().unwrap();
+    }
+
+    let reader = env.begin_ro_txn().unwrap();
+    let tx = env.begin_rw_txn().unwrap();
+    tx.put(&tx.open_db(None).unwrap(), b"key", b"value", WriteFlags::default()).unwrap();
+    tx.commit().unwrap();
+
+    // the reader is alive, so it's not cleared and keeps its snapshot
+    assert_eq!(env.reader_check().unwrap(), 0);
+    let info = env.info().unwrap();
+    assert!(info.latter_reader_txnid() < info.last_txnid());
+
+    drop(reader);
+    let info = env.info().unwrap();
+    assert_eq!(info.latter_reader_txnid(), info.last_txnid());
+}
+
 #[test]
 fn test_stat() {
     let dir = tempdir().unwrap();
@@ -1,13 +1,13 @@
 use auto_impl::auto_impl;
 use reth_db::{
-    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers},
+    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals},
     tables,
     transaction::{DbTx, DbTxMut},
 };
 use reth_interfaces::{provider::Error as ProviderError, Result};
 use reth_primitives::{
     rpc::{BlockId, BlockNumber},
-    Block, BlockHash, BlockHashOrNumber, Header, SealedBlock, H256, U256,
+    Address, Block, BlockHash, BlockHashOrNumber, Header, SealedBlock, Withdrawal, H256, U256,
 };
 
 /// Client trait for fetching `Header` related data.
@@ -44,15 +44,23 @@ pub trait BlockProvider: Send + Sync {
     /// Returns the block. Returns `None` if block is not found.
     fn block(&self, id: BlockId) -> Result<Option<Block>>;
 
+    /// Returns the ommers of the block. Returns `None` if the body of the block is not found.
+    fn ommers(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Header>>>;
+
+    /// Returns the withdrawals of the block. Returns `None` if the block is not found or if it
+    /// has no withdrawals, i.e. it is before Shanghai.
+    fn withdrawals(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Withdrawal>>>;
+
     /// Converts the `BlockNumber` variants.
+    ///
+    /// There is no pending block, so `pending` resolves to the latest block.
     fn convert_block_number(
         &self,
         num: BlockNumber,
     ) -> Result<Option<reth_primitives::BlockNumber>> {
         let num = match num {
-            BlockNumber::Latest => self.chain_info()?.best_number,
+            BlockNumber::Latest | BlockNumber::Pending => self.chain_info()?.best_number,
             BlockNumber::Earliest => 0,
-            BlockNumber::Pending => return Ok(None),
             BlockNumber::Number(num) => num.as_u64(),
             BlockNumber::Finalized => return Ok(self.chain_info()?.last_finalized),
             BlockNumber::Safe => return Ok(self.chain_info()?.safe_finalized),
@@ -65,7 +73,7 @@ pub trait BlockProvider: Send + Sync {
         match block_id {
             BlockId::Hash(hash) => Ok(Some(hash)),
             BlockId::Number(num) => {
-                if matches!(num, BlockNumber::Latest) {
+                if matches!(num, BlockNumber::Latest | BlockNumber::Pending) {
                     return Ok(Some(self.chain_info()?.best_hash))
                 }
                 self.convert_block_number(num)?
@@ -112,13 +120,35 @@ pub struct ChainInfo {
 /// Check parent dependency in [tables::HeaderNumbers] and in [tables::CumulativeTxCount] tables.
 /// Inserts blocks data to [tables::CanonicalHeaders], [tables::Headers], [tables::HeaderNumbers],
 /// and transactions data to [tables::TxSenders], [tables::Transactions],
-/// [tables::CumulativeTxCount] and [tables::BlockBodies]
+/// [tables::CumulativeTxCount] and [tables::BlockBodies].
+/// The withdrawals of the block, if any, are inserted to [tables::BlockWithdrawals].
+///
+/// The block gets a transition after its transactions if it has a block reward or withdrawals.
+///
+/// The senders of the transactions are recovered if they are not provided, the block is rejected if
+/// they can't be recovered or if the number of provided senders doesn't match the transactions.
 pub fn insert_canonical_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
     tx: &TX,
     block: &SealedBlock,
+    senders: Option<Vec<Address>>,
     has_block_reward: bool,
 ) -> Result<()> {
     let block_num_hash = BlockNumHash((block.number, block.hash()));
+    let senders = match senders {
+        Some(senders) => senders,
+        None => block.senders().ok_or(ProviderError::BlockSenders {
+            block_number: block.number,
+            block_hash: block.hash(),
+        })?,
+    };
+    if senders.len() != block.body.len() {
+        return Err(ProviderError::BlockSendersMismatch {
+            block_number: block.number,
+            transactions: block.body.len(),
+            senders: senders.len(),
+        }
+        .into())
+    }
     tx.put::<tables::CanonicalHeaders>(block.number, block.hash())?;
     // Put header with canonical hashes.
     tx.put::<tables::Headers>(block_num_hash, block.header.as_ref().clone())?;
@@ -130,6 +160,13 @@ pub fn insert_canonical_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
         StoredBlockOmmers { ommers: block.ommers.iter().map(|h| h.as_ref().clone()).collect() },
     )?;
 
+    if let Some(withdrawals) = &block.withdrawals {
+        tx.put::<tables::BlockWithdrawals>(
+            block_num_hash,
+            StoredBlockWithdrawals { withdrawals: withdrawals.clone() },
+        )?;
+    }
+
     let (mut current_tx_id, mut transition_id) = {
         if block.number == 0 {
             (0, 0)
@@ -160,16 +197,15 @@ pub fn insert_canonical_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
         StoredBlockBody { start_tx_id: current_tx_id, tx_count: block.body.len() as u64 },
     )?;
 
-    for transaction in block.body.iter() {
-        let rec_tx = transaction.clone().into_ecrecovered().unwrap();
-        tx.put::<tables::TxSenders>(current_tx_id, rec_tx.signer())?;
-        tx.put::<tables::Transactions>(current_tx_id, rec_tx.into())?;
+    for (transaction, sender) in block.body.iter().zip(senders) {
+        tx.put::<tables::TxSenders>(current_tx_id, sender)?;
+        tx.put::<tables::Transactions>(current_tx_id, transaction.clone())?;
         tx.put::<tables::TxTransitionIndex>(current_tx_id, transition_id)?;
         current_tx_id += 1;
         transition_id += 1;
     }
 
-    if has_block_reward {
+    if has_block_reward || block.withdrawals_root.is_some() {
         transition_id += 1;
     }
     tx.put::<tables::BlockTransitionIndex>((block.number, block.hash()).into(), transition_id)?;
@@ -0,0 +1,341 @@
+use crate::{
+    BlockProvider, CanonStateNotification, CanonStateNotifications, ChainInfo, HeaderProvider,
+    LogWithMeta, ReceiptProvider, StageCheckpointProvider, StateProviderFactory, TransactionMeta,
+    TransactionsProvider,
+};
+use lru::LruCache;
+use parking_lot::Mutex;
+use reth_interfaces::Result;
+use reth_primitives::{
+    rpc::{BlockId, Filter},
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, Header, Receipt, TransactionSigned,
+    TransactionSignedEcRecovered, TxHash, TxNumber, Withdrawal, H256, U256,
+};
+use std::{hash::Hash, num::NonZeroUsize};
+use tokio::sync::broadcast::error::RecvError;
+
+/// The capacities of the caches of a [CachedProvider].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// The maximum number of cached headers, and of cached hashes of canonical blocks.
+    pub max_headers: usize,
+    /// The maximum number of cached blocks.
+    pub max_blocks: usize,
+    /// The maximum number of blocks whose receipts are cached.
+    pub max_receipts: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { max_headers: 1024, max_blocks: 128, max_receipts: 128 }
+    }
+}
+
+/// A provider that keeps the recently read headers, blocks and receipts in LRU caches, so the
+/// blocks at the tip of the chain that are queried over and over are only read from the database
+/// once.
+///
+/// The headers, blocks and receipts are cached by the hash of their block, and the hashes of the
+/// canonical blocks by their number. The entries of the blocks that are removed from the canonical
+/// chain are evicted on the [CanonStateNotification]s, which have to be passed to
+/// [CachedProvider::on_canon_state_notification], e.g. by spawning
+/// [CachedProvider::invalidate_on_notifications].
+///
+/// All other reads are passed through to the wrapped provider.
+#[derive(Debug)]
+pub struct CachedProvider<P> {
+    /// The wrapped provider.
+    inner: P,
+    /// Headers by the hash of their block.
+    headers: Mutex<LruCache<BlockHash, Header>>,
+    /// Hashes of the canonical blocks by their number.
+    canonical_hashes: Mutex<LruCache<BlockNumber, BlockHash>>,
+    /// Canonical blocks by their hash.
+    blocks: Mutex<LruCache<BlockHash, Block>>,
+    /// Receipts of the canonical blocks by the hash of their block.
+    receipts: Mutex<LruCache<BlockHash, Vec<Receipt>>>,
+}
+
+impl<P> CachedProvider<P> {
+    /// Wraps the provider with caches of the given capacities.
+    pub fn new(inner: P, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            headers: lru_cache(config.max_headers),
+            canonical_hashes: lru_cache(config.max_headers),
+            blocks: lru_cache(config.max_blocks),
+            receipts: lru_cache(config.max_receipts),
+        }
+    }
+
+    /// Returns the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Updates the caches with a change of the canonical chain.
+    ///
+    /// All entries of the reverted blocks are evicted. The hashes of the committed blocks replace
+    /// the hashes of the blocks with the same number, and their headers and receipts are cached
+    /// right away since they are likely to be queried next.
+    pub fn on_canon_state_notification(&self, notification: &CanonStateNotification) {
+        for reverted in notification.reverted() {
+            let hash = reverted.block.hash();
+            let number = reverted.block.number;
+            let mut canonical_hashes = self.canonical_hashes.lock();
+            if canonical_hashes.peek(&number) == Some(&hash) {
+                canonical_hashes.pop(&number);
+            }
+            drop(canonical_hashes);
+            self.headers.lock().pop(&hash);
+            self.blocks.lock().pop(&hash);
+            self.receipts.lock().pop(&hash);
+        }
+
+        for committed in notification.committed() {
+            let hash = committed.block.hash();
+            self.canonical_hashes.lock().put(committed.block.number, hash);
+            self.headers.lock().put(hash, committed.block.header.clone().unseal());
+            self.receipts.lock().put(hash, committed.receipts.clone());
+        }
+    }
+
+    /// Evicts all entries.
+    pub fn clear(&self) {
+        self.headers.lock().clear();
+        self.canonical_hashes.lock().clear();
+        self.blocks.lock().clear();
+        self.receipts.lock().clear();
+    }
+
+    /// Updates the caches with the changes of the canonical chain until the sender of the
+    /// notifications is dropped.
+    ///
+    /// If notifications were missed because the receiver lagged behind, all entries are evicted
+    /// since it is unknown which blocks were reverted.
+    pub async fn invalidate_on_notifications(&self, mut notifications: CanonStateNotifications) {
+        loop {
+            match notifications.recv().await {
+                Ok(notification) => self.on_canon_state_notification(&notification),
+                Err(RecvError::Lagged(_)) => self.clear(),
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Returns the hash of the block, resolving block numbers through the cache of canonical
+    /// hashes.
+    fn resolve_hash(&self, block: BlockHashOrNumber) -> Result<Option<BlockHash>>
+    where
+        P: BlockProvider,
+    {
+        match block {
+            BlockHashOrNumber::Hash(hash) => Ok(Some(hash)),
+            BlockHashOrNumber::Number(number) => self.block_hash(U256::from(number)),
+        }
+    }
+}
+
+/// Creates an LRU cache, holding at least one entry.
+fn lru_cache<K: Hash + Eq, V>(capacity: usize) -> Mutex<LruCache<K, V>> {
+    Mutex::new(LruCache::new(NonZeroUsize::new(capacity.max(1)).expect("capacity is not zero")))
+}
+
+/// Returns the cached value, or reads it with `f` and caches it if it exists.
+fn get_or_insert<K: Hash + Eq, V: Clone>(
+    cache: &Mutex<LruCache<K, V>>,
+    key: K,
+    f: impl FnOnce() -> Result<Option<V>>,
+) -> Result<Option<V>> {
+    if let Some(value) = cache.lock().get(&key) {
+        return Ok(Some(value.clone()))
+    }
+    // the lock is not held while reading, so concurrent reads of the same key may both miss
+    let value = f()?;
+    if let Some(value) = &value {
+        cache.lock().put(key, value.clone());
+    }
+    Ok(value)
+}
+
+impl<P: HeaderProvider + BlockProvider> HeaderProvider for CachedProvider<P> {
+    fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
+        get_or_insert(&self.headers, *block_hash, || self.inner.header(block_hash))
+    }
+
+    fn header_by_number(&self, num: u64) -> Result<Option<Header>> {
+        match self.block_hash(U256::from(num))? {
+            Some(hash) => self.header(&hash),
+            None => Ok(None),
+        }
+    }
+
+    fn header_td(&self, hash: &BlockHash) -> Result<Option<U256>> {
+        self.inner.header_td(hash)
+    }
+}
+
+impl<P: BlockProvider> BlockProvider for CachedProvider<P> {
+    fn chain_info(&self) -> Result<ChainInfo> {
+        self.inner.chain_info()
+    }
+
+    fn block(&self, id: BlockId) -> Result<Option<Block>> {
+        match self.block_hash_for_id(id)? {
+            Some(hash) => {
+                get_or_insert(&self.blocks, hash, || self.inner.block(BlockId::Hash(hash)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn ommers(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
+        self.inner.ommers(block)
+    }
+
+    fn withdrawals(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Withdrawal>>> {
+        self.inner.withdrawals(block)
+    }
+
+    fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
+        self.inner.block_number(hash)
+    }
+
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        match u64::try_from(number) {
+            Ok(number) => get_or_insert(&self.canonical_hashes, number, || {
+                self.inner.block_hash(U256::from(number))
+            }),
+            Err(_) => self.inner.block_hash(number),
+        }
+    }
+}
+
+impl<P: ReceiptProvider + BlockProvider> ReceiptProvider for CachedProvider<P> {
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
+        self.inner.receipt(id)
+    }
+
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
+        self.inner.receipt_by_hash(hash)
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        match self.resolve_hash(block)? {
+            Some(hash) => {
+                get_or_insert(&self.receipts, hash, || self.inner.receipts_by_block(hash.into()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn logs_by_block(
+        &self,
+        block: BlockHashOrNumber,
+        filter: &Filter,
+    ) -> Result<Option<Vec<LogWithMeta>>> {
+        self.inner.logs_by_block(block, filter)
+    }
+}
+
+impl<P: TransactionsProvider> TransactionsProvider for CachedProvider<P> {
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        self.inner.transaction_by_hash(hash)
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        self.inner.transaction_by_hash_with_meta(hash)
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        self.inner.transactions_by_block(block)
+    }
+
+    fn transactions_with_senders_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedEcRecovered>>> {
+        self.inner.transactions_with_senders_by_block(block)
+    }
+}
+
+impl<P: StateProviderFactory> StateProviderFactory for CachedProvider<P> {
+    type HistorySP<'a> = P::HistorySP<'a> where Self: 'a;
+    type LatestSP<'a> = P::LatestSP<'a> where Self: 'a;
+
+    fn latest(&self) -> Result<Self::LatestSP<'_>> {
+        self.inner.latest()
+    }
+
+    fn history_by_block_number(&self, block: BlockNumber) -> Result<Self::HistorySP<'_>> {
+        self.inner.history_by_block_number(block)
+    }
+
+    fn history_by_block_hash(&self, block: BlockHash) -> Result<Self::HistorySP<'_>> {
+        self.inner.history_by_block_hash(block)
+    }
+}
+
+impl<P: StageCheckpointProvider> StageCheckpointProvider for CachedProvider<P> {
+    fn stage_checkpoints(&self) -> Result<Vec<(String, BlockNumber)>> {
+        self.inner.stage_checkpoints()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{insert_canonical_block, CanonBlock, ProviderImpl};
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{rpc::BlockNumber, SealedBlock};
+    use std::sync::Arc;
+
+    #[test]
+    fn evict_reverted_blocks() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let block = SealedBlock::default();
+        db.update(|tx| insert_canonical_block(tx, &block, None, false)).unwrap().unwrap();
+
+        let provider = CachedProvider::new(ProviderImpl::new(db.clone()), CacheConfig::default());
+        let block_0 = || provider.block(BlockId::Number(BlockNumber::Number(0u64.into()))).unwrap();
+        let cached = block_0();
+        assert!(cached.is_some());
+
+        // the unwind removes the block from the database, but it stays cached until the revert
+        // is announced
+        db.update(|tx| tx.delete::<tables::CanonicalHeaders>(0, None)).unwrap().unwrap();
+        assert_eq!(block_0(), cached);
+
+        let old = vec![CanonBlock { block, senders: vec![], receipts: vec![] }];
+        let notification = CanonStateNotification::Revert { old: Arc::new(old) };
+        provider.on_canon_state_notification(&notification);
+        assert_eq!(block_0(), None);
+    }
+
+    #[test]
+    fn cache_committed_blocks() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let provider = CachedProvider::new(ProviderImpl::new(db), CacheConfig::default());
+
+        let block = SealedBlock::default();
+        let receipts = vec![Receipt { cumulative_gas_used: 1, ..Default::default() }];
+        let new = CanonBlock { block: block.clone(), senders: vec![], receipts: receipts.clone() };
+        let notification = CanonStateNotification::Commit { new: Arc::new(vec![new]) };
+        provider.on_canon_state_notification(&notification);
+
+        assert_eq!(provider.block_hash(U256::zero()).unwrap(), Some(block.hash()));
+        assert_eq!(provider.header_by_number(0).unwrap(), Some(block.header.unseal()));
+        assert_eq!(provider.receipts_by_block(0u64.into()).unwrap(), Some(receipts));
+    }
+}
@@ -0,0 +1,110 @@
+use reth_db::{models::BlockNumHash, tables, transaction::DbTx};
+use reth_interfaces::{provider::Error as ProviderError, Result};
+use reth_primitives::{Address, BlockNumber, Receipt, SealedBlock, SealedHeader};
+use std::{ops::RangeInclusive, sync::Arc};
+use tokio::sync::broadcast;
+
+/// Sender of [CanonStateNotification]s.
+pub type CanonStateNotificationSender = broadcast::Sender<CanonStateNotification>;
+
+/// Receiver of [CanonStateNotification]s.
+pub type CanonStateNotifications = broadcast::Receiver<CanonStateNotification>;
+
+/// A block of the canonical chain together with the outcome of its execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonBlock {
+    /// The block.
+    pub block: SealedBlock,
+    /// The senders of the transactions of the block.
+    pub senders: Vec<Address>,
+    /// The receipts of the transactions of the block.
+    pub receipts: Vec<Receipt>,
+}
+
+/// A change of the canonical chain.
+///
+/// A reorg is announced as a [CanonStateNotification::Revert] of the blocks of the old chain,
+/// followed by a [CanonStateNotification::Commit] of the blocks of the new chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonStateNotification {
+    /// The blocks were appended to the canonical chain, in ascending order.
+    Commit {
+        /// The new blocks.
+        new: Arc<Vec<CanonBlock>>,
+    },
+    /// The blocks were removed from the canonical chain, in ascending order.
+    Revert {
+        /// The removed blocks.
+        old: Arc<Vec<CanonBlock>>,
+    },
+}
+
+impl CanonStateNotification {
+    /// Returns the blocks that were appended to the canonical chain.
+    pub fn committed(&self) -> &[CanonBlock] {
+        match self {
+            CanonStateNotification::Commit { new } => new,
+            CanonStateNotification::Revert { .. } => &[],
+        }
+    }
+
+    /// Returns the blocks that were removed from the canonical chain.
+    pub fn reverted(&self) -> &[CanonBlock] {
+        match self {
+            CanonStateNotification::Commit { .. } => &[],
+            CanonStateNotification::Revert { old } => old,
+        }
+    }
+}
+
+/// Reads the canonical blocks in the range together with their senders and receipts.
+///
+/// The blocks are read from the database only, so they must not have been moved to static files.
+pub fn canon_blocks<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    range: RangeInclusive<BlockNumber>,
+) -> Result<Vec<CanonBlock>> {
+    let mut blocks = Vec::new();
+    for block_number in range {
+        let block_hash = tx
+            .get::<tables::CanonicalHeaders>(block_number)?
+            .ok_or(ProviderError::BlockNumber { block_number })?;
+        let key = BlockNumHash((block_number, block_hash));
+        let header =
+            tx.get::<tables::Headers>(key)?.ok_or(ProviderError::BlockHash { block_hash })?;
+        let body = tx
+            .get::<tables::BlockBodies>(key)?
+            .ok_or(ProviderError::BlockBody { block_number, block_hash })?;
+        let ommers = tx
+            .get::<tables::BlockOmmers>(key)?
+            .map(|stored| stored.ommers.into_iter().map(|ommer| ommer.seal()).collect())
+            .unwrap_or_default();
+        let withdrawals = tx.get::<tables::BlockWithdrawals>(key)?.map(|stored| stored.withdrawals);
+
+        let mut transactions = Vec::with_capacity(body.tx_count as usize);
+        let mut senders = Vec::with_capacity(body.tx_count as usize);
+        let mut receipts = Vec::with_capacity(body.tx_count as usize);
+        for id in body.tx_id_range() {
+            let transaction =
+                tx.get::<tables::Transactions>(id)?.ok_or(ProviderError::Transaction { id })?;
+            let sender = match tx.get::<tables::TxSenders>(id)? {
+                Some(sender) => sender,
+                None => {
+                    transaction.recover_signer().ok_or(ProviderError::TransactionSender { id })?
+                }
+            };
+            senders.push(sender);
+            transactions.push(transaction);
+            receipts.push(tx.get::<tables::Receipts>(id)?.ok_or(ProviderError::Receipt { id })?);
+        }
+
+        let block = SealedBlock {
+            header: SealedHeader::new(header, block_hash),
+            body: transactions,
+            ommers,
+            withdrawals,
+        };
+        blocks.push(CanonBlock { block, senders, receipts });
+    }
+    Ok(blocks)
+}
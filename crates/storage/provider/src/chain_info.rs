@@ -0,0 +1,179 @@
+use crate::ChainInfo;
+use reth_primitives::{SealedHeader, U256};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// The head of the canonical chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CanonicalHead {
+    /// The header of the head block.
+    pub header: SealedHeader,
+    /// The total difficulty of the head block.
+    pub total_difficulty: U256,
+}
+
+/// Tracks the head, safe and finalized blocks of the canonical chain.
+///
+/// The tracker is updated by the component that advances the canonical chain, e.g. the pipeline,
+/// and read by the components that need to know the tip of the chain, e.g. the RPC or the network,
+/// without querying the database. Clones share the same state, and each of the blocks can be
+/// watched for changes.
+#[derive(Debug, Clone, Default)]
+pub struct ChainInfoTracker {
+    inner: Arc<ChainInfoTrackerInner>,
+}
+
+#[derive(Debug)]
+struct ChainInfoTrackerInner {
+    /// The head of the canonical chain.
+    canonical_head: watch::Sender<CanonicalHead>,
+    /// The safe block of the fork choice state, if it is canonical.
+    safe_block: watch::Sender<Option<SealedHeader>>,
+    /// The finalized block of the fork choice state, if it is canonical.
+    finalized_block: watch::Sender<Option<SealedHeader>>,
+}
+
+impl Default for ChainInfoTrackerInner {
+    fn default() -> Self {
+        Self {
+            canonical_head: watch::channel(CanonicalHead::default()).0,
+            safe_block: watch::channel(None).0,
+            finalized_block: watch::channel(None).0,
+        }
+    }
+}
+
+impl ChainInfoTracker {
+    /// Create a new tracker with the head of the canonical chain.
+    pub fn new(head: CanonicalHead) -> Self {
+        let tracker = Self::default();
+        tracker.set_canonical_head(head);
+        tracker
+    }
+
+    /// Returns the head of the canonical chain.
+    pub fn canonical_head(&self) -> CanonicalHead {
+        self.inner.canonical_head.borrow().clone()
+    }
+
+    /// Returns the header of the safe block.
+    pub fn safe_block(&self) -> Option<SealedHeader> {
+        self.inner.safe_block.borrow().clone()
+    }
+
+    /// Returns the header of the finalized block.
+    pub fn finalized_block(&self) -> Option<SealedHeader> {
+        self.inner.finalized_block.borrow().clone()
+    }
+
+    /// Returns the current info for the chain.
+    ///
+    /// The safe and finalized blocks are omitted if they are after the head, e.g. while the head
+    /// is still syncing up to them.
+    pub fn chain_info(&self) -> ChainInfo {
+        let head = self.inner.canonical_head.borrow();
+        let best_number = head.header.number;
+        let number_up_to_head = |block: &watch::Sender<Option<SealedHeader>>| {
+            block.borrow().as_ref().map(|header| header.number).filter(|n| *n <= best_number)
+        };
+
+        ChainInfo {
+            best_hash: head.header.hash(),
+            best_number,
+            last_finalized: number_up_to_head(&self.inner.finalized_block),
+            safe_finalized: number_up_to_head(&self.inner.safe_block),
+        }
+    }
+
+    /// Set the head of the canonical chain.
+    pub fn set_canonical_head(&self, head: CanonicalHead) {
+        self.inner.canonical_head.send_if_modified(|current| replace_if_changed(current, head));
+    }
+
+    /// Set the header of the safe block.
+    pub fn set_safe_block(&self, header: Option<SealedHeader>) {
+        self.inner.safe_block.send_if_modified(|current| replace_if_changed(current, header));
+    }
+
+    /// Set the header of the finalized block.
+    pub fn set_finalized_block(&self, header: Option<SealedHeader>) {
+        self.inner.finalized_block.send_if_modified(|current| replace_if_changed(current, header));
+    }
+
+    /// Subscribe to the changes of the head of the canonical chain.
+    pub fn subscribe_canonical_head(&self) -> watch::Receiver<CanonicalHead> {
+        self.inner.canonical_head.subscribe()
+    }
+
+    /// Subscribe to the changes of the safe block.
+    pub fn subscribe_safe_block(&self) -> watch::Receiver<Option<SealedHeader>> {
+        self.inner.safe_block.subscribe()
+    }
+
+    /// Subscribe to the changes of the finalized block.
+    pub fn subscribe_finalized_block(&self) -> watch::Receiver<Option<SealedHeader>> {
+        self.inner.finalized_block.subscribe()
+    }
+}
+
+/// Replaces the value and returns `true` if it differs, so the watchers are only notified of
+/// actual changes.
+fn replace_if_changed<T: PartialEq>(current: &mut T, value: T) -> bool {
+    if *current == value {
+        return false
+    }
+    *current = value;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::Header;
+
+    fn header(number: u64) -> SealedHeader {
+        Header { number, ..Default::default() }.seal()
+    }
+
+    #[test]
+    fn chain_info_up_to_head() {
+        let tracker = ChainInfoTracker::new(CanonicalHead {
+            header: header(2),
+            total_difficulty: U256::from(2),
+        });
+        tracker.set_safe_block(Some(header(2)));
+        tracker.set_finalized_block(Some(header(1)));
+        assert_eq!(
+            tracker.chain_info(),
+            ChainInfo {
+                best_hash: header(2).hash(),
+                best_number: 2,
+                last_finalized: Some(1),
+                safe_finalized: Some(2),
+            }
+        );
+
+        // the head is unwound below the safe block
+        tracker.set_canonical_head(CanonicalHead { header: header(1), ..Default::default() });
+        let info = tracker.chain_info();
+        assert_eq!(
+            (info.best_number, info.safe_finalized, info.last_finalized),
+            (1, None, Some(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn notify_changed_head() {
+        let tracker = ChainInfoTracker::default();
+        let mut head = tracker.subscribe_canonical_head();
+
+        let new_head = CanonicalHead { header: header(1), total_difficulty: U256::from(1) };
+        tracker.clone().set_canonical_head(new_head.clone());
+        head.changed().await.unwrap();
+        assert_eq!(*head.borrow_and_update(), new_head);
+
+        // setting the same head again is not a change
+        tracker.set_canonical_head(new_head);
+        assert!(!head.has_changed().unwrap());
+    }
+}
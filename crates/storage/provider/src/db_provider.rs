@@ -2,7 +2,11 @@
 //! to provide higher level abstraction over database tables.
 
 mod block;
+mod receipts;
+mod stage;
 mod storage;
+mod transactions;
+use crate::{static_file::StaticFileProvider, ChainInfoTracker};
 use std::sync::Arc;
 
 pub use storage::{
@@ -11,27 +15,76 @@ pub use storage::{
 };
 
 use reth_db::database::Database;
+use reth_interfaces::consensus::ForkchoiceState;
+use tokio::sync::watch;
 
 /// A provider that fetches data from a database.
 // TODO: ProviderImpl is a bad name
 pub struct ProviderImpl<DB: Database> {
     /// Database
     db: Arc<DB>,
+    /// Watcher over the forkchoice state, used to resolve the `safe` and `finalized` blocks.
+    fork_choice_state: Option<watch::Receiver<ForkchoiceState>>,
+    /// The static files with the data of finalized blocks that was moved out of the database.
+    static_files: Option<Arc<StaticFileProvider>>,
+    /// Tracker of the head, safe and finalized blocks, used instead of the database if set.
+    chain_info: Option<ChainInfoTracker>,
 }
 
 impl<DB: Database> ProviderImpl<DB> {
     /// create new database provider
     pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+        Self { db, fork_choice_state: None, static_files: None, chain_info: None }
+    }
+
+    /// Resolves the `safe` and `finalized` blocks from the forkchoice state of the consensus
+    /// layer.
+    ///
+    /// Without a forkchoice state, there are no `safe` and `finalized` blocks.
+    pub fn with_fork_choice_state(
+        mut self,
+        fork_choice_state: watch::Receiver<ForkchoiceState>,
+    ) -> Self {
+        self.fork_choice_state = Some(fork_choice_state);
+        self
+    }
+
+    /// Reads the headers, transactions and receipts that are not in the database from the static
+    /// files.
+    pub fn with_static_files(mut self, static_files: Arc<StaticFileProvider>) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Reads the head, safe and finalized blocks from the tracker instead of the database and the
+    /// forkchoice state.
+    pub fn with_chain_info(mut self, chain_info: ChainInfoTracker) -> Self {
+        self.chain_info = Some(chain_info);
+        self
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::StateProviderFactory;
+    use crate::{
+        insert_canonical_block, write_fork_choice_state, AccountProvider, BlockProvider,
+        StateProvider, StateProviderFactory,
+    };
 
     use super::ProviderImpl;
-    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::{AccountBeforeTx, AddressStorageKey, ShardedKey},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_interfaces::consensus::ForkchoiceState;
+    use reth_primitives::{
+        rpc::{BlockId, BlockNumber},
+        Account, Address, Header, SealedBlock, StorageEntry, Withdrawal, H256, U256,
+    };
+    use tokio::sync::watch;
 
     #[test]
     fn common_history_provider() {
@@ -39,4 +92,142 @@ mod tests {
         let provider = ProviderImpl::new(db);
         let _ = provider.latest();
     }
+
+    #[test]
+    fn history_provider_reverts_later_changes() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let hashes = (0..3u64).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        let address = Address::from_low_u64_be(1);
+        let untouched = Address::from_low_u64_be(2);
+        let slot = H256::from_low_u64_be(3);
+        let old = Account { nonce: 1, ..Default::default() };
+        let new = Account { nonce: 2, ..Default::default() };
+
+        // The blocks end at the transitions 0, 2 and 4, the account and the slot are changed by
+        // the transition 3 of block 2
+        db.update(|tx| {
+            for (number, hash) in hashes.iter().enumerate() {
+                let number = number as u64;
+                tx.put::<tables::CanonicalHeaders>(number, *hash).unwrap();
+                tx.put::<tables::HeaderNumbers>(*hash, number).unwrap();
+                tx.put::<tables::BlockTransitionIndex>((number, *hash).into(), number * 2).unwrap();
+            }
+
+            tx.put::<tables::PlainAccountState>(address, new).unwrap();
+            tx.put::<tables::PlainAccountState>(untouched, old).unwrap();
+            tx.put::<tables::AccountChangeSet>(3, AccountBeforeTx { address, info: Some(old) })
+                .unwrap();
+            tx.put::<tables::AccountHistory>(ShardedKey::new(address, u64::MAX), vec![3u64].into())
+                .unwrap();
+
+            let new_value = StorageEntry { key: slot, value: U256::from(2) };
+            let old_value = StorageEntry { key: slot, value: U256::from(1) };
+            tx.put::<tables::PlainStorageState>(address, new_value).unwrap();
+            tx.put::<tables::StorageChangeSet>((3, address).into(), old_value).unwrap();
+            let key = ShardedKey::new(AddressStorageKey((address, slot)), u64::MAX);
+            tx.put::<tables::StorageHistory>(key, vec![3u64].into()).unwrap();
+        })
+        .unwrap();
+
+        let provider = ProviderImpl::new(db);
+
+        let state = provider.history_by_block_number(1).unwrap();
+        assert_eq!(state.basic_account(address), Ok(Some(old)));
+        assert_eq!(state.basic_account(untouched), Ok(Some(old)));
+        assert_eq!(state.storage(address, slot), Ok(Some(U256::from(1))));
+
+        let state = provider.history_by_block_hash(hashes[2]).unwrap();
+        assert_eq!(state.basic_account(address), Ok(Some(new)));
+        assert_eq!(state.storage(address, slot), Ok(Some(U256::from(2))));
+
+        let state = provider.latest().unwrap();
+        assert_eq!(state.basic_account(address), Ok(Some(new)));
+    }
+
+    #[test]
+    fn resolve_block_tags() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let hashes = (0..4u64).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        db.update(|tx| {
+            for (number, hash) in hashes.iter().enumerate() {
+                tx.put::<tables::CanonicalHeaders>(number as u64, *hash).unwrap();
+                tx.put::<tables::HeaderNumbers>(*hash, number as u64).unwrap();
+            }
+            tx.put::<tables::SyncStage>(b"Headers".to_vec(), 3).unwrap();
+            tx.put::<tables::SyncStage>(b"Execution".to_vec(), 2).unwrap();
+        })
+        .unwrap();
+
+        let (_tx, rx) = watch::channel(ForkchoiceState {
+            head_block_hash: hashes[3],
+            safe_block_hash: hashes[3],
+            finalized_block_hash: hashes[1],
+        });
+        let provider = ProviderImpl::new(db).with_fork_choice_state(rx);
+
+        let chain_info = provider.chain_info().unwrap();
+        assert_eq!(chain_info.best_number, 2);
+        assert_eq!(chain_info.best_hash, hashes[2]);
+        assert_eq!(chain_info.last_finalized, Some(1));
+        // the safe block is not executed yet
+        assert_eq!(chain_info.safe_finalized, None);
+
+        let hash_for = |num| provider.block_hash_for_id(BlockId::Number(num)).unwrap();
+        assert_eq!(hash_for(BlockNumber::Latest), Some(hashes[2]));
+        assert_eq!(hash_for(BlockNumber::Pending), Some(hashes[2]));
+        assert_eq!(hash_for(BlockNumber::Finalized), Some(hashes[1]));
+        assert_eq!(hash_for(BlockNumber::Safe), None);
+        assert_eq!(hash_for(BlockNumber::Earliest), Some(hashes[0]));
+    }
+
+    #[test]
+    fn resolve_persisted_block_tags() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let hashes = (0..3u64).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        db.update(|tx| {
+            for (number, hash) in hashes.iter().enumerate() {
+                tx.put::<tables::CanonicalHeaders>(number as u64, *hash).unwrap();
+                tx.put::<tables::HeaderNumbers>(*hash, number as u64).unwrap();
+            }
+            tx.put::<tables::SyncStage>(b"Headers".to_vec(), 2).unwrap();
+            let state = ForkchoiceState {
+                head_block_hash: hashes[2],
+                safe_block_hash: hashes[2],
+                finalized_block_hash: hashes[1],
+            };
+            write_fork_choice_state(tx, &state).unwrap();
+        })
+        .unwrap();
+
+        // the fork choice state of a previous run is known without the consensus
+        let chain_info = ProviderImpl::new(db).chain_info().unwrap();
+        assert_eq!(chain_info.last_finalized, Some(1));
+        assert_eq!(chain_info.safe_finalized, Some(2));
+    }
+
+    #[test]
+    fn block_ommers_and_withdrawals() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let ommer = Header { number: 7, ..Default::default() };
+        let withdrawal =
+            Withdrawal { index: 1, validator_index: 2, address: Address::random(), amount: 3 };
+        let pre_shanghai = SealedBlock { ommers: vec![ommer.clone().seal()], ..Default::default() };
+        let shanghai = SealedBlock {
+            header: Header { number: 1, ..Default::default() }.seal(),
+            withdrawals: Some(vec![withdrawal.clone()]),
+            ..Default::default()
+        };
+        db.update(|tx| {
+            insert_canonical_block(tx, &pre_shanghai, None, false).unwrap();
+            insert_canonical_block(tx, &shanghai, None, false).unwrap();
+        })
+        .unwrap();
+
+        let provider = ProviderImpl::new(db);
+        assert_eq!(provider.ommers(0u64.into()).unwrap(), Some(vec![ommer]));
+        assert_eq!(provider.withdrawals(0u64.into()).unwrap(), None);
+        assert_eq!(provider.ommers(shanghai.hash().into()).unwrap(), Some(vec![]));
+        assert_eq!(provider.withdrawals(shanghai.hash().into()).unwrap(), Some(vec![withdrawal]));
+        assert_eq!(provider.ommers(2u64.into()).unwrap(), None);
+    }
 }
@@ -1,11 +1,31 @@
-use crate::{BlockProvider, ChainInfo, HeaderProvider, ProviderImpl};
+use super::transactions::canonical_block_body;
+use crate::{
+    read_fork_choice_state, BlockProvider, ChainInfo, HeaderProvider, ProviderImpl,
+    StageCheckpointProvider, TransactionsProvider,
+};
 use reth_db::{database::Database, tables, transaction::DbTx};
 use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Block, BlockHash, BlockNumber, Header, H256, U256};
+use reth_primitives::{
+    rpc::BlockId, Block, BlockHash, BlockHashOrNumber, BlockNumber, Header, Withdrawal, H256, U256,
+};
 
 impl<DB: Database> HeaderProvider for ProviderImpl<DB> {
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
-        self.db.view(|tx| tx.get::<tables::Headers>((0, *block_hash).into()))?.map_err(Into::into)
+        let tx = self.db.tx()?;
+        let num = match tx.get::<tables::HeaderNumbers>(*block_hash)? {
+            Some(num) => num,
+            None => return Ok(None),
+        };
+
+        // only canonical headers are moved to static files
+        if let Some(static_files) = &self.static_files {
+            if tx.get::<tables::CanonicalHeaders>(num)? == Some(*block_hash) {
+                if let Some(header) = static_files.header(num)? {
+                    return Ok(Some(header))
+                }
+            }
+        }
+        Ok(tx.get::<tables::Headers>((num, *block_hash).into())?)
     }
 
     fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
@@ -28,17 +48,72 @@ impl<DB: Database> HeaderProvider for ProviderImpl<DB> {
 
 impl<DB: Database> BlockProvider for ProviderImpl<DB> {
     fn chain_info(&self) -> Result<ChainInfo> {
+        if let Some(chain_info) = &self.chain_info {
+            return Ok(chain_info.chain_info())
+        }
+
+        // the best block is the highest block that has been processed by all stages
+        let best_number = self
+            .stage_checkpoints()?
+            .into_iter()
+            .map(|(_, checkpoint)| checkpoint)
+            .min()
+            .unwrap_or_default();
+        let best_hash = self.block_hash(U256::from(best_number))?.unwrap_or_default();
+
+        // without the fork choice state of the consensus, the persisted one is the latest known
+        let state = match &self.fork_choice_state {
+            Some(state) => state.borrow().clone(),
+            None => read_fork_choice_state(&self.db.tx()?)?,
+        };
+        let (safe_hash, finalized_hash) = (state.safe_block_hash, state.finalized_block_hash);
+
         Ok(ChainInfo {
-            best_hash: Default::default(),
-            best_number: 0,
-            last_finalized: None,
-            safe_finalized: None,
+            best_hash,
+            best_number,
+            last_finalized: self.fork_choice_block_number(finalized_hash, best_number)?,
+            safe_finalized: self.fork_choice_block_number(safe_hash, best_number)?,
         })
     }
 
-    fn block(&self, _id: BlockId) -> Result<Option<Block>> {
-        // TODO
-        Ok(None)
+    fn block(&self, id: BlockId) -> Result<Option<Block>> {
+        let hash = match self.block_hash_for_id(id)? {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        let header = match self.header(&hash)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let body = match self.transactions_by_block(hash.into())? {
+            Some(body) => body,
+            None => return Ok(None),
+        };
+        let ommers = self.ommers(hash.into())?.unwrap_or_default();
+        let withdrawals = self.withdrawals(hash.into())?;
+
+        Ok(Some(Block { header, body, ommers, withdrawals }))
+    }
+
+    fn ommers(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
+        let tx = self.db.tx()?;
+        let (number, hash) = match canonical_block_body(&tx, block)? {
+            Some((number, hash, _)) => (number, hash),
+            None => return Ok(None),
+        };
+        // empty blocks have no ommers entry
+        let ommers = tx.get::<tables::BlockOmmers>((number, hash).into())?;
+        Ok(Some(ommers.map(|stored| stored.ommers).unwrap_or_default()))
+    }
+
+    fn withdrawals(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Withdrawal>>> {
+        let tx = self.db.tx()?;
+        let (number, hash) = match canonical_block_body(&tx, block)? {
+            Some((number, hash, _)) => (number, hash),
+            None => return Ok(None),
+        };
+        let withdrawals = tx.get::<tables::BlockWithdrawals>((number, hash).into())?;
+        Ok(withdrawals.map(|stored| stored.withdrawals))
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
@@ -52,3 +127,25 @@ impl<DB: Database> BlockProvider for ProviderImpl<DB> {
             .map_err(Into::into)
     }
 }
+
+impl<DB: Database> ProviderImpl<DB> {
+    /// Returns the number of a block of the forkchoice state.
+    ///
+    /// Returns `None` if the block is unknown, not canonical or not processed up to the best block
+    /// yet.
+    fn fork_choice_block_number(
+        &self,
+        hash: H256,
+        best_number: BlockNumber,
+    ) -> Result<Option<BlockNumber>> {
+        if hash.is_zero() {
+            return Ok(None)
+        }
+        let number = match self.block_number(hash)? {
+            Some(number) if number <= best_number => number,
+            _ => return Ok(None),
+        };
+        let canonical = self.block_hash(U256::from(number))? == Some(hash);
+        Ok(canonical.then_some(number))
+    }
+}
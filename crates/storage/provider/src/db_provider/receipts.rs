@@ -0,0 +1,106 @@
+use super::transactions::canonical_block_body;
+use crate::{
+    receipts::{filter_matches_bloom, filter_matches_log},
+    HeaderProvider, LogWithMeta, ProviderImpl, ReceiptProvider, TransactionMeta,
+};
+use reth_db::{database::Database, tables, transaction::DbTx};
+use reth_interfaces::{provider::Error as ProviderError, Result};
+use reth_primitives::{rpc::Filter, BlockHashOrNumber, Receipt, TxHash, TxNumber};
+
+impl<DB: Database> ReceiptProvider for ProviderImpl<DB> {
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
+        let tx = self.db.tx()?;
+        self.receipt_by_id(&tx, id)
+    }
+
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
+        let tx = self.db.tx()?;
+        match tx.get::<tables::TxHashNumber>(hash)? {
+            Some(location) => self.receipt_by_id(&tx, location.tx_number),
+            None => Ok(None),
+        }
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let tx = self.db.tx()?;
+        let body = match canonical_block_body(&tx, block)? {
+            Some((_, _, body)) => body,
+            None => return Ok(None),
+        };
+
+        let mut receipts = Vec::with_capacity(body.tx_count as usize);
+        for id in body.tx_id_range() {
+            receipts.push(self.receipt_by_id(&tx, id)?.ok_or(ProviderError::Receipt { id })?);
+        }
+        Ok(Some(receipts))
+    }
+
+    fn logs_by_block(
+        &self,
+        block: BlockHashOrNumber,
+        filter: &Filter,
+    ) -> Result<Option<Vec<LogWithMeta>>> {
+        let tx = self.db.tx()?;
+        let (block_number, block_hash, body) = match canonical_block_body(&tx, block)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        let header = self.header(&block_hash)?.ok_or(ProviderError::BlockHash { block_hash })?;
+        if !filter_matches_bloom(filter, &header.logs_bloom) {
+            return Ok(Some(Vec::new()))
+        }
+
+        let mut logs = Vec::new();
+        let mut log_index = 0u64;
+        for (index, id) in body.tx_id_range().enumerate() {
+            let receipt = self.receipt_by_id(&tx, id)?.ok_or(ProviderError::Receipt { id })?;
+            let first_log_index = log_index;
+            log_index += receipt.logs.len() as u64;
+            if !filter_matches_bloom(filter, &receipt.bloom) {
+                continue
+            }
+
+            let matching_logs = receipt
+                .logs
+                .into_iter()
+                .enumerate()
+                .filter(|(_, log)| filter_matches_log(filter, log))
+                .collect::<Vec<_>>();
+            if matching_logs.is_empty() {
+                continue
+            }
+
+            // the transaction is only read for the receipts with matching logs
+            let transaction =
+                self.transaction_by_id(&tx, id)?.ok_or(ProviderError::Transaction { id })?;
+            let meta = TransactionMeta {
+                tx_hash: transaction.hash(),
+                index: index as u64,
+                block_hash,
+                block_number,
+            };
+            logs.extend(matching_logs.into_iter().map(|(transaction_log_index, log)| {
+                LogWithMeta {
+                    log,
+                    meta,
+                    log_index: first_log_index + transaction_log_index as u64,
+                    transaction_log_index: transaction_log_index as u64,
+                }
+            }));
+        }
+        Ok(Some(logs))
+    }
+}
+
+impl<DB: Database> ProviderImpl<DB> {
+    /// Returns the receipt of the transaction from the static files or the database.
+    fn receipt_by_id<'a, TX: DbTx<'a>>(&self, tx: &TX, id: TxNumber) -> Result<Option<Receipt>> {
+        if let Some(static_files) = &self.static_files {
+            if let Some(receipt) = static_files.receipt(id)? {
+                return Ok(Some(receipt))
+            }
+        }
+        Ok(tx.get::<tables::Receipts>(id)?)
+    }
+}
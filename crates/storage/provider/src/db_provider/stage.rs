@@ -0,0 +1,18 @@
+use crate::{ProviderImpl, StageCheckpointProvider};
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
+use reth_interfaces::Result;
+use reth_primitives::BlockNumber;
+
+impl<DB: Database> StageCheckpointProvider for ProviderImpl<DB> {
+    fn stage_checkpoints(&self) -> Result<Vec<(String, BlockNumber)>> {
+        let tx = self.db.tx()?;
+        let mut cursor = tx.cursor::<tables::SyncStage>()?;
+
+        let mut checkpoints = Vec::new();
+        for entry in cursor.walk(Vec::new())? {
+            let (id, progress) = entry?;
+            checkpoints.push((String::from_utf8_lossy(&id).into_owned(), progress));
+        }
+        Ok(checkpoints)
+    }
+}
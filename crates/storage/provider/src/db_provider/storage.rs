@@ -3,7 +3,9 @@ use crate::{AccountProvider, Error, StateProvider, StateProviderFactory};
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
     database::{Database, DatabaseGAT},
-    tables,
+    models::{AddressStorageKey, ShardedKey},
+    table::Table,
+    tables::{self, TransitionList},
     transaction::DbTx,
 };
 use reth_interfaces::Result;
@@ -93,12 +95,18 @@ impl<'a, TX: DbTx<'a>> StateProvider for StateProviderImplHistory<'a, TX> {
 }
 /// State provider with given hash
 ///
+/// The state at the transition is the latest state with the changes after the transition
+/// reverted: the first changeset of an account or storage slot after the transition holds its
+/// value at the transition, and the history indices point to that changeset.
+///
 /// It will access:
 /// [tables::AccountHistory]
 /// [tables::Bytecodes]
 /// [tables::StorageHistory]
 /// [tables::AccountChangeSet]
 /// [tables::StorageChangeSet]
+/// [tables::PlainAccountState]
+/// [tables::PlainStorageState]
 pub struct StateProviderImplRefHistory<'a, 'b, TX: DbTx<'a>> {
     /// Transaction
     tx: &'b TX,
@@ -113,43 +121,58 @@ impl<'a, 'b, TX: DbTx<'a>> StateProviderImplRefHistory<'a, 'b, TX> {
     pub fn new(tx: &'b TX, transition: TransitionId) -> Self {
         Self { tx, transition, _phantom: PhantomData {} }
     }
+
+    /// Returns the first transition after the transition of the provider that changed the key,
+    /// according to the history index `T`.
+    fn next_change<T, K>(&self, key: K) -> Result<Option<TransitionId>>
+    where
+        T: Table<Key = ShardedKey<K>, Value = TransitionList>,
+        K: Clone + PartialEq,
+    {
+        let after = self.transition + 1;
+        let mut cursor = self.tx.cursor::<T>()?;
+        // The shards are sorted by their highest transition, so the first shard of the key whose
+        // highest transition is not before `after` contains the change.
+        let shard = cursor.walk(ShardedKey::new(key.clone(), after))?.next().transpose()?;
+        Ok(shard.and_then(|(shard_key, list)| {
+            if shard_key.key != key {
+                return None
+            }
+            list.iter(0).map(|id| id as TransitionId).find(|id| *id >= after)
+        }))
+    }
 }
 
 impl<'a, 'b, TX: DbTx<'a>> AccountProvider for StateProviderImplRefHistory<'a, 'b, TX> {
     /// Get basic account information.
-    fn basic_account(&self, _address: Address) -> Result<Option<Account>> {
-        // TODO add when AccountHistory is defined
-        Ok(None)
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        let Some(transition) = self.next_change::<tables::AccountHistory, _>(address)? else {
+            return StateProviderImplRefLatest::new(self.tx).basic_account(address)
+        };
+
+        let mut cursor = self.tx.cursor_dup::<tables::AccountChangeSet>()?;
+        let changeset = cursor
+            .seek_by_key_subkey(transition, address)?
+            .filter(|changeset| changeset.address == address)
+            .ok_or(Error::Changeset { transition })?;
+        Ok(changeset.info)
     }
 }
 
 impl<'a, 'b, TX: DbTx<'a>> StateProvider for StateProviderImplRefHistory<'a, 'b, TX> {
     /// Get storage.
     fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
-        // TODO when StorageHistory is defined
-        let transaction_number =
-            self.tx.get::<tables::StorageHistory>(Vec::new())?.map(|_integer_list|
-            // TODO select integer that is one less from transaction_number <- // TODO: (rkrasiuk) not sure this comment is still relevant
-            self.transition);
-
-        if transaction_number.is_none() {
-            return Ok(None)
-        }
-        let num = transaction_number.unwrap();
-        let mut cursor = self.tx.cursor_dup::<tables::StorageChangeSet>()?;
+        let slot = AddressStorageKey((account, storage_key));
+        let Some(transition) = self.next_change::<tables::StorageHistory, _>(slot)? else {
+            return StateProviderImplRefLatest::new(self.tx).storage(account, storage_key)
+        };
 
-        if let Some((_, entry)) = cursor.seek_exact((num, account).into())? {
-            if entry.key == storage_key {
-                return Ok(Some(entry.value))
-            }
-
-            if let Some((_, entry)) = cursor.seek(storage_key)? {
-                if entry.key == storage_key {
-                    return Ok(Some(entry.value))
-                }
-            }
-        }
-        Ok(None)
+        let mut cursor = self.tx.cursor_dup::<tables::StorageChangeSet>()?;
+        let entry = cursor
+            .seek_by_key_subkey((transition, account).into(), storage_key)?
+            .filter(|entry| entry.key == storage_key)
+            .ok_or(Error::Changeset { transition })?;
+        Ok(Some(entry.value))
     }
 
     /// Get account code by its hash
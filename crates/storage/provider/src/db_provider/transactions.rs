@@ -0,0 +1,131 @@
+use crate::{ProviderImpl, TransactionMeta, TransactionsProvider};
+use reth_db::{
+    database::Database,
+    models::{StoredBlockBody, StoredTxLocation},
+    tables,
+    transaction::DbTx,
+};
+use reth_interfaces::{provider::Error as ProviderError, Result};
+use reth_primitives::{
+    BlockHash, BlockHashOrNumber, BlockNumber, TransactionSigned, TransactionSignedEcRecovered,
+    TxHash, TxNumber,
+};
+
+impl<DB: Database> TransactionsProvider for ProviderImpl<DB> {
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        let tx = self.db.tx()?;
+        if let Some(location) = tx.get::<tables::TxHashNumber>(hash)? {
+            return self.transaction_by_id(&tx, location.tx_number)
+        }
+        Ok(None)
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        let tx = self.db.tx()?;
+        let StoredTxLocation { tx_number: id, block_number } =
+            match tx.get::<tables::TxHashNumber>(hash)? {
+                Some(location) => location,
+                None => return Ok(None),
+            };
+        let transaction = match self.transaction_by_id(&tx, id)? {
+            Some(transaction) => transaction,
+            None => return Ok(None),
+        };
+
+        let (_, block_hash, body) = match canonical_block_body(&tx, block_number.into())? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        let meta = TransactionMeta {
+            tx_hash: hash,
+            index: id - body.start_tx_id,
+            block_hash,
+            block_number,
+        };
+        Ok(Some((transaction, meta)))
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        let tx = self.db.tx()?;
+        let body = match canonical_block_body(&tx, block)? {
+            Some((_, _, body)) => body,
+            None => return Ok(None),
+        };
+
+        let mut transactions = Vec::with_capacity(body.tx_count as usize);
+        for id in body.tx_id_range() {
+            let transaction =
+                self.transaction_by_id(&tx, id)?.ok_or(ProviderError::Transaction { id })?;
+            transactions.push(transaction);
+        }
+        Ok(Some(transactions))
+    }
+
+    fn transactions_with_senders_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedEcRecovered>>> {
+        let tx = self.db.tx()?;
+        let body = match canonical_block_body(&tx, block)? {
+            Some((_, _, body)) => body,
+            None => return Ok(None),
+        };
+
+        let mut transactions = Vec::with_capacity(body.tx_count as usize);
+        for id in body.tx_id_range() {
+            let transaction =
+                self.transaction_by_id(&tx, id)?.ok_or(ProviderError::Transaction { id })?;
+            let sender = match tx.get::<tables::TxSenders>(id)? {
+                Some(sender) => sender,
+                // senders are not yet recovered for this block
+                None => {
+                    transaction.recover_signer().ok_or(ProviderError::TransactionSender { id })?
+                }
+            };
+            transactions
+                .push(TransactionSignedEcRecovered::from_signed_transaction(transaction, sender));
+        }
+        Ok(Some(transactions))
+    }
+}
+
+impl<DB: Database> ProviderImpl<DB> {
+    /// Returns the transaction from the static files or the database.
+    pub(super) fn transaction_by_id<'a, TX: DbTx<'a>>(
+        &self,
+        tx: &TX,
+        id: TxNumber,
+    ) -> Result<Option<TransactionSigned>> {
+        if let Some(static_files) = &self.static_files {
+            if let Some(transaction) = static_files.transaction(id)? {
+                return Ok(Some(transaction))
+            }
+        }
+        Ok(tx.get::<tables::Transactions>(id)?)
+    }
+}
+
+/// Returns the number, hash and stored body of the canonical block.
+pub(super) fn canonical_block_body<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    block: BlockHashOrNumber,
+) -> Result<Option<(BlockNumber, BlockHash, StoredBlockBody)>> {
+    let (number, hash) = match block {
+        BlockHashOrNumber::Hash(hash) => match tx.get::<tables::HeaderNumbers>(hash)? {
+            Some(number) => (number, hash),
+            None => return Ok(None),
+        },
+        BlockHashOrNumber::Number(number) => match tx.get::<tables::CanonicalHeaders>(number)? {
+            Some(hash) => (number, hash),
+            None => return Ok(None),
+        },
+    };
+    Ok(tx.get::<tables::BlockBodies>((number, hash).into())?.map(|body| (number, hash, body)))
+}
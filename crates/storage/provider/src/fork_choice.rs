@@ -0,0 +1,106 @@
+use reth_db::{
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_interfaces::consensus::ForkchoiceState;
+use reth_primitives::H256;
+
+/// A block of the fork choice state that is persisted, so it is known right after a restart and
+/// before the consensus layer announces it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkChoiceBlock {
+    /// The safe block.
+    Safe,
+    /// The finalized block.
+    Finalized,
+}
+
+impl ForkChoiceBlock {
+    /// The key of the block in the [tables::ForkChoice] table.
+    fn id(&self) -> &'static str {
+        match self {
+            ForkChoiceBlock::Safe => "Safe",
+            ForkChoiceBlock::Finalized => "Finalized",
+        }
+    }
+
+    /// Get the persisted hash of the block.
+    pub fn get<'db>(&self, tx: &impl DbTx<'db>) -> Result<Option<H256>, DbError> {
+        tx.get::<tables::ForkChoice>(self.id().as_bytes().to_vec())
+    }
+
+    /// Persist the hash of the block.
+    pub fn save<'db>(&self, tx: &impl DbTxMut<'db>, hash: H256) -> Result<(), DbError> {
+        tx.put::<tables::ForkChoice>(self.id().as_bytes().to_vec(), hash)
+    }
+}
+
+/// Read the persisted safe and finalized blocks.
+///
+/// The head of the returned state is always zero, since the head is not persisted.
+pub fn read_fork_choice_state<'db>(tx: &impl DbTx<'db>) -> Result<ForkchoiceState, DbError> {
+    Ok(ForkchoiceState {
+        head_block_hash: H256::zero(),
+        safe_block_hash: ForkChoiceBlock::Safe.get(tx)?.unwrap_or_default(),
+        finalized_block_hash: ForkChoiceBlock::Finalized.get(tx)?.unwrap_or_default(),
+    })
+}
+
+/// Persist the safe and finalized blocks of the fork choice state.
+///
+/// The blocks that are not set, i.e. zero, keep their persisted hash.
+pub fn write_fork_choice_state<'db>(
+    tx: &impl DbTxMut<'db>,
+    state: &ForkchoiceState,
+) -> Result<(), DbError> {
+    for (block, hash) in [
+        (ForkChoiceBlock::Safe, state.safe_block_hash),
+        (ForkChoiceBlock::Finalized, state.finalized_block_hash),
+    ] {
+        if !hash.is_zero() {
+            block.save(tx, hash)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+    };
+
+    #[test]
+    fn persist_fork_choice_state() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let empty = read_fork_choice_state(&db.tx().unwrap()).unwrap();
+        assert_eq!(empty, ForkchoiceState::default());
+
+        let state = ForkchoiceState {
+            head_block_hash: H256::from_low_u64_be(3),
+            safe_block_hash: H256::from_low_u64_be(2),
+            finalized_block_hash: H256::from_low_u64_be(1),
+        };
+        db.update(|tx| write_fork_choice_state(tx, &state)).unwrap().unwrap();
+
+        // an unset safe block keeps the persisted one
+        let update = ForkchoiceState {
+            finalized_block_hash: H256::from_low_u64_be(2),
+            ..Default::default()
+        };
+        db.update(|tx| write_fork_choice_state(tx, &update)).unwrap().unwrap();
+
+        let persisted = read_fork_choice_state(&db.tx().unwrap()).unwrap();
+        assert_eq!(
+            persisted,
+            ForkchoiceState {
+                head_block_hash: H256::zero(),
+                safe_block_hash: H256::from_low_u64_be(2),
+                finalized_block_hash: H256::from_low_u64_be(2),
+            }
+        );
+    }
+}
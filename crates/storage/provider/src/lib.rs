@@ -8,18 +8,40 @@
 //! <reth crate template>
 
 mod block;
+mod cache;
+mod chain;
+mod chain_info;
 
 pub mod db_provider;
+mod fork_choice;
+mod receipts;
+mod stage;
 mod state;
+mod state_cache;
+pub mod static_file;
+mod transactions;
 
 #[cfg(any(test, feature = "test-utils"))]
 /// Common test helpers for mocking the Provider.
 pub mod test_utils;
 
 pub use block::{insert_canonical_block, BlockProvider, ChainInfo, HeaderProvider};
+pub use cache::{CacheConfig, CachedProvider};
+pub use chain::{
+    canon_blocks, CanonBlock, CanonStateNotification, CanonStateNotificationSender,
+    CanonStateNotifications,
+};
+pub use chain_info::{CanonicalHead, ChainInfoTracker};
 pub use db_provider::{
     self as db, ProviderImpl, StateProviderImplHistory, StateProviderImplLatest,
     StateProviderImplRefHistory, StateProviderImplRefLatest,
 };
+pub use fork_choice::{read_fork_choice_state, write_fork_choice_state, ForkChoiceBlock};
+pub use receipts::{filter_matches_bloom, filter_matches_log, LogWithMeta, ReceiptProvider};
 pub use reth_interfaces::provider::Error;
+pub use stage::StageCheckpointProvider;
 pub use state::{AccountProvider, StateProvider, StateProviderFactory};
+pub use state_cache::{
+    CachedStateProvider, ChangedStateKeys, StateCache, StateCacheConfig, StateCacheMetrics,
+};
+pub use transactions::{TransactionMeta, TransactionsProvider};
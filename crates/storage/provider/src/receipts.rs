@@ -0,0 +1,154 @@
+use crate::TransactionMeta;
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{
+    bloom::bloom_contains,
+    rpc::{Filter, ValueOrArray},
+    BlockHashOrNumber, Bloom, Log, Receipt, TxHash, TxNumber, H256,
+};
+
+/// A log of a canonical transaction together with its location in the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogWithMeta {
+    /// The log.
+    pub log: Log,
+    /// The transaction that emitted the log.
+    pub meta: TransactionMeta,
+    /// Index of the log in the block.
+    pub log_index: u64,
+    /// Index of the log in the receipt of the transaction.
+    pub transaction_log_index: u64,
+}
+
+/// Client trait for fetching the [Receipt]s and logs of canonical transactions.
+#[auto_impl(&)]
+pub trait ReceiptProvider: Send + Sync {
+    /// Get the receipt of the transaction with the given id.
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>>;
+
+    /// Get the receipt of the transaction with the given hash. Returns `None` if the transaction is
+    /// not canonical.
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>>;
+
+    /// Get the receipts of all transactions of the block. Returns `None` if the block is not
+    /// canonical.
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>>;
+
+    /// Get the logs of the block that match the addresses and topics of the filter, the block range
+    /// of the filter is ignored. Returns `None` if the block is not canonical.
+    ///
+    /// The blooms of the block and of the receipts are checked first, so the receipts of a block
+    /// and the logs of a receipt are only read if they may contain a matching log.
+    fn logs_by_block(
+        &self,
+        block: BlockHashOrNumber,
+        filter: &Filter,
+    ) -> Result<Option<Vec<LogWithMeta>>>;
+}
+
+/// Returns true if the bloom may contain a log that matches the address and topics of the filter.
+pub fn filter_matches_bloom(filter: &Filter, bloom: &Bloom) -> bool {
+    let matches_address = match &filter.address {
+        Some(ValueOrArray::Value(address)) => bloom_contains(bloom, address.as_bytes()),
+        Some(ValueOrArray::Array(addresses)) => {
+            addresses.is_empty() ||
+                addresses.iter().any(|address| bloom_contains(bloom, address.as_bytes()))
+        }
+        None => true,
+    };
+
+    matches_address &&
+        filter.topics.iter().all(|topic| {
+            topic_matches(topic.as_ref(), |topic| bloom_contains(bloom, topic.as_bytes()))
+        })
+}
+
+/// Returns true if the log matches the address and topics of the filter.
+pub fn filter_matches_log(filter: &Filter, log: &Log) -> bool {
+    let matches_address = match &filter.address {
+        Some(ValueOrArray::Value(address)) => *address == log.address,
+        Some(ValueOrArray::Array(addresses)) => {
+            addresses.is_empty() || addresses.contains(&log.address)
+        }
+        None => true,
+    };
+
+    matches_address &&
+        filter.topics.iter().enumerate().all(|(idx, topic)| {
+            topic_matches(topic.as_ref(), |topic| log.topics.get(idx) == Some(topic))
+        })
+}
+
+/// Returns true if the topic of the filter is a wildcard, or if one of its values matches.
+fn topic_matches(
+    topic: Option<&ValueOrArray<Option<H256>>>,
+    matches: impl Fn(&H256) -> bool,
+) -> bool {
+    match topic {
+        None | Some(ValueOrArray::Value(None)) => true,
+        Some(ValueOrArray::Value(Some(topic))) => matches(topic),
+        Some(ValueOrArray::Array(topics)) => {
+            topics.is_empty() ||
+                topics.iter().any(|topic| match topic {
+                    None => true,
+                    Some(topic) => matches(topic),
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{bloom::logs_bloom, Address};
+
+    #[test]
+    fn match_log_filter() {
+        let address = Address::from_low_u64_be(1);
+        let topic = H256::from_low_u64_be(2);
+        let other_topic = H256::from_low_u64_be(3);
+        let log = Log { address, topics: vec![topic], data: Default::default() };
+
+        let mut filter = Filter::new();
+        assert!(filter_matches_log(&filter, &log));
+
+        filter.address = Some(ValueOrArray::Value(address));
+        assert!(filter_matches_log(&filter, &log));
+        filter.address = Some(ValueOrArray::Value(Address::from_low_u64_be(4)));
+        assert!(!filter_matches_log(&filter, &log));
+        filter.address = None;
+
+        filter.topics[0] = Some(ValueOrArray::Value(Some(topic)));
+        assert!(filter_matches_log(&filter, &log));
+        filter.topics[0] = Some(ValueOrArray::Value(Some(other_topic)));
+        assert!(!filter_matches_log(&filter, &log));
+        filter.topics[0] = Some(ValueOrArray::Array(vec![Some(other_topic), Some(topic)]));
+        assert!(filter_matches_log(&filter, &log));
+
+        // the log has no second topic
+        filter.topics[1] = Some(ValueOrArray::Value(Some(topic)));
+        assert!(!filter_matches_log(&filter, &log));
+    }
+
+    #[test]
+    fn match_bloom_filter() {
+        let address = Address::from_low_u64_be(1);
+        let topic = H256::from_low_u64_be(2);
+        let log = Log { address, topics: vec![topic], data: Default::default() };
+        let bloom = logs_bloom([&log]);
+
+        let mut filter = Filter::new();
+        assert!(filter_matches_bloom(&filter, &bloom));
+        assert!(filter_matches_bloom(&filter, &Bloom::zero()));
+
+        filter.address = Some(ValueOrArray::Array(vec![Address::from_low_u64_be(4), address]));
+        filter.topics[0] = Some(ValueOrArray::Value(Some(topic)));
+        assert!(filter_matches_bloom(&filter, &bloom));
+        assert!(!filter_matches_bloom(&filter, &Bloom::zero()));
+
+        filter.topics[1] = Some(ValueOrArray::Array(vec![None]));
+        assert!(filter_matches_bloom(&filter, &bloom));
+        filter.topics[1] = Some(ValueOrArray::Value(Some(H256::from_low_u64_be(3))));
+        assert!(!filter_matches_bloom(&filter, &bloom));
+    }
+}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::BlockNumber;
+
+/// Client trait for fetching the progress of the sync pipeline.
+#[auto_impl(&)]
+pub trait StageCheckpointProvider: Send + Sync {
+    /// Returns the last committed progress of all stages, keyed by the id of the stage.
+    fn stage_checkpoints(&self) -> Result<Vec<(String, BlockNumber)>>;
+}
@@ -0,0 +1,329 @@
+use crate::{AccountProvider, StateProvider};
+use lru::LruCache;
+use metrics::Counter;
+use parking_lot::Mutex;
+use reth_interfaces::Result;
+use reth_metrics_derive::Metrics;
+use reth_primitives::{Account, Address, Bytes, StorageKey, StorageValue, H256, U256};
+use std::{collections::BTreeSet, hash::Hash, num::NonZeroUsize};
+
+/// The capacities of the caches of a [StateCache].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCacheConfig {
+    /// The maximum number of cached accounts.
+    pub max_accounts: usize,
+    /// The maximum number of cached storage slots.
+    pub max_storage_slots: usize,
+    /// The maximum number of cached bytecodes.
+    pub max_bytecodes: usize,
+}
+
+impl Default for StateCacheConfig {
+    fn default() -> Self {
+        Self { max_accounts: 100_000, max_storage_slots: 1_000_000, max_bytecodes: 10_000 }
+    }
+}
+
+/// The keys of the state that are changed by a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedStateKeys {
+    /// The accounts whose info changed.
+    pub accounts: BTreeSet<Address>,
+    /// The changed storage slots.
+    pub storage: BTreeSet<(Address, StorageKey)>,
+    /// The destroyed accounts, whose whole storage is removed.
+    pub wiped_storage: BTreeSet<Address>,
+}
+
+/// LRU caches of the accounts, storage slots and bytecodes of the state at the tip of the chain,
+/// shared between consecutive block executions and the blocks built on top of the tip, so the
+/// state that is read by every block is only read from the database once.
+///
+/// The cached accounts and storage slots are the state after a single block, the tip of the
+/// cache. They are only read and written by the providers of the state after that block, see
+/// [StateCache::provider], so readers of other states neither see nor cache stale values. Once
+/// the next block is executed, [StateCache::advance] evicts the keys it changed and moves the
+/// tip to that block. Since the state after a block is determined by its hash, the cache stays
+/// valid even if the executed block is not committed to the database.
+///
+/// Bytecodes are cached by their hash, independent of the tip.
+#[derive(Debug)]
+pub struct StateCache {
+    inner: Mutex<StateCacheInner>,
+    metrics: StateCacheMetrics,
+}
+
+/// Metrics of the [StateCache].
+///
+/// The hit rate of a cache is the rate of its hits over the rate of all its reads.
+#[derive(Metrics)]
+#[metrics(scope = "state_cache")]
+pub struct StateCacheMetrics {
+    /// Number of account reads served by the cache
+    account_hits: Counter,
+    /// Number of account reads that missed the cache
+    account_misses: Counter,
+    /// Number of storage reads served by the cache
+    storage_hits: Counter,
+    /// Number of storage reads that missed the cache
+    storage_misses: Counter,
+    /// Number of bytecode reads served by the cache
+    bytecode_hits: Counter,
+    /// Number of bytecode reads that missed the cache
+    bytecode_misses: Counter,
+}
+
+#[derive(Debug)]
+struct StateCacheInner {
+    /// The hash of the block whose state is cached.
+    tip: Option<H256>,
+    /// Accounts by their address, `None` if the account doesn't exist.
+    accounts: LruCache<Address, Option<Account>>,
+    /// Storage values by the address of their account and their slot.
+    storage: LruCache<(Address, StorageKey), Option<StorageValue>>,
+    /// Bytecodes by their hash.
+    bytecodes: LruCache<H256, Bytes>,
+}
+
+impl StateCacheInner {
+    /// Evicts all accounts and storage slots and moves the tip to the given block.
+    fn reset(&mut self, tip: Option<H256>) {
+        self.tip = tip;
+        self.accounts.clear();
+        self.storage.clear();
+    }
+}
+
+impl StateCache {
+    /// Creates an empty cache with the given capacities.
+    pub fn new(config: StateCacheConfig) -> Self {
+        let inner = StateCacheInner {
+            tip: None,
+            accounts: lru_cache(config.max_accounts),
+            storage: lru_cache(config.max_storage_slots),
+            bytecodes: lru_cache(config.max_bytecodes),
+        };
+        Self { inner: Mutex::new(inner), metrics: StateCacheMetrics::default() }
+    }
+
+    /// Returns the hash of the block whose state is cached.
+    pub fn tip(&self) -> Option<H256> {
+        self.inner.lock().tip
+    }
+
+    /// Wraps the provider of the state after the block with the given hash, so its reads go
+    /// through the cache if that block is the tip of the cache.
+    pub fn provider<SP: StateProvider>(
+        &self,
+        inner: SP,
+        block_hash: H256,
+    ) -> CachedStateProvider<'_, SP> {
+        CachedStateProvider { inner, cache: self, block_hash }
+    }
+
+    /// Moves the tip of the cache from the parent to the executed block, evicting the keys
+    /// changed by the block.
+    ///
+    /// If the parent is not the tip, e.g. after an unwind, all accounts and storage slots are
+    /// evicted.
+    pub fn advance(&self, parent_hash: H256, block_hash: H256, changes: &ChangedStateKeys) {
+        let mut inner = self.inner.lock();
+        if inner.tip != Some(parent_hash) {
+            inner.reset(Some(block_hash));
+            return
+        }
+
+        inner.tip = Some(block_hash);
+        for address in &changes.accounts {
+            inner.accounts.pop(address);
+        }
+        for slot in &changes.storage {
+            inner.storage.pop(slot);
+        }
+        if !changes.wiped_storage.is_empty() {
+            let wiped = inner
+                .storage
+                .iter()
+                .filter(|((address, _), _)| changes.wiped_storage.contains(address))
+                .map(|(slot, _)| *slot)
+                .collect::<Vec<_>>();
+            for slot in wiped {
+                inner.storage.pop(&slot);
+            }
+        }
+    }
+
+    /// Evicts all accounts and storage slots.
+    pub fn clear(&self) {
+        self.inner.lock().reset(None);
+    }
+}
+
+/// Creates an LRU cache, holding at least one entry.
+fn lru_cache<K: Hash + Eq, V>(capacity: usize) -> LruCache<K, V> {
+    LruCache::new(NonZeroUsize::new(capacity.max(1)).expect("capacity is not zero"))
+}
+
+/// A [StateProvider] of the state after a block that reads through a [StateCache].
+#[derive(Debug)]
+pub struct CachedStateProvider<'a, SP> {
+    /// The wrapped provider.
+    inner: SP,
+    /// The shared cache.
+    cache: &'a StateCache,
+    /// The hash of the block whose state is provided.
+    block_hash: H256,
+}
+
+impl<'a, SP> CachedStateProvider<'a, SP> {
+    /// Returns the value cached in the selected cache if the provided state is the tip, or reads
+    /// it with `read` and caches it.
+    ///
+    /// The read is counted as a hit or a miss of the cache.
+    fn get_or_read<K: Hash + Eq, V: Clone>(
+        &self,
+        select: fn(&mut StateCacheInner) -> &mut LruCache<K, V>,
+        (hits, misses): (&Counter, &Counter),
+        key: K,
+        read: impl FnOnce() -> Result<V>,
+    ) -> Result<V> {
+        let mut inner = self.cache.inner.lock();
+        if inner.tip == Some(self.block_hash) {
+            if let Some(value) = select(&mut inner).get(&key) {
+                hits.increment(1);
+                return Ok(value.clone())
+            }
+        }
+        drop(inner);
+        misses.increment(1);
+
+        let value = read()?;
+        let mut inner = self.cache.inner.lock();
+        // the tip may have moved on while the lock was not held
+        if inner.tip == Some(self.block_hash) {
+            select(&mut inner).put(key, value.clone());
+        }
+        Ok(value)
+    }
+}
+
+impl<'a, SP: StateProvider> AccountProvider for CachedStateProvider<'a, SP> {
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        let metrics = &self.cache.metrics;
+        self.get_or_read(
+            |inner| &mut inner.accounts,
+            (&metrics.account_hits, &metrics.account_misses),
+            address,
+            || self.inner.basic_account(address),
+        )
+    }
+}
+
+impl<'a, SP: StateProvider> StateProvider for CachedStateProvider<'a, SP> {
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        let metrics = &self.cache.metrics;
+        self.get_or_read(
+            |inner| &mut inner.storage,
+            (&metrics.storage_hits, &metrics.storage_misses),
+            (account, storage_key),
+            || self.inner.storage(account, storage_key),
+        )
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        let metrics = &self.cache.metrics;
+        if let Some(bytecode) = self.cache.inner.lock().bytecodes.get(&code_hash) {
+            metrics.bytecode_hits.increment(1);
+            return Ok(Some(bytecode.clone()))
+        }
+        metrics.bytecode_misses.increment(1);
+        let bytecode = self.inner.bytecode_by_hash(code_hash)?;
+        if let Some(bytecode) = &bytecode {
+            self.cache.inner.lock().bytecodes.put(code_hash, bytecode.clone());
+        }
+        Ok(bytecode)
+    }
+
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        self.inner.block_hash(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// A state with a single account that counts the reads of the account.
+    #[derive(Debug)]
+    struct CountingState {
+        account: Account,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl AccountProvider for CountingState {
+        fn basic_account(&self, _: Address) -> Result<Option<Account>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            Ok(Some(self.account))
+        }
+    }
+
+    impl StateProvider for CountingState {
+        fn storage(&self, _: Address, _: StorageKey) -> Result<Option<StorageValue>> {
+            Ok(None)
+        }
+
+        fn bytecode_by_hash(&self, _: H256) -> Result<Option<Bytes>> {
+            Ok(None)
+        }
+
+        fn block_hash(&self, _: U256) -> Result<Option<H256>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn cache_state_of_tip() {
+        let cache = StateCache::new(StateCacheConfig::default());
+        let (block_1, block_2) = (H256::random(), H256::random());
+        let address = Address::random();
+        let reads = Arc::new(AtomicUsize::new(0));
+        let state = |nonce| CountingState {
+            account: Account { nonce, ..Default::default() },
+            reads: reads.clone(),
+        };
+        let nonce = |provider: &CachedStateProvider<'_, CountingState>| {
+            provider.basic_account(address).unwrap().unwrap().nonce
+        };
+
+        // nothing is cached before the cache has a tip
+        let provider = cache.provider(state(1), block_1);
+        nonce(&provider);
+        nonce(&provider);
+        assert_eq!(reads.load(Ordering::Relaxed), 2);
+
+        cache.advance(H256::random(), block_1, &ChangedStateKeys::default());
+        assert_eq!(cache.tip(), Some(block_1));
+        let provider = cache.provider(state(1), block_1);
+        assert_eq!(nonce(&provider), 1);
+        assert_eq!(nonce(&provider), 1);
+        assert_eq!(reads.load(Ordering::Relaxed), 3);
+
+        // the next block evicts the changed account
+        let changes =
+            ChangedStateKeys { accounts: BTreeSet::from([address]), ..Default::default() };
+        cache.advance(block_1, block_2, &changes);
+        let provider = cache.provider(state(2), block_2);
+        assert_eq!(nonce(&provider), 2);
+        assert_eq!(nonce(&provider), 2);
+        assert_eq!(reads.load(Ordering::Relaxed), 4);
+
+        // the state of other blocks is not cached
+        let provider = cache.provider(state(1), block_1);
+        assert_eq!(nonce(&provider), 1);
+        assert_eq!(reads.load(Ordering::Relaxed), 5);
+    }
+}
@@ -0,0 +1,290 @@
+use super::StaticFileSegment;
+use memmap2::Mmap;
+use reth_primitives::BlockNumber;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+};
+
+/// The size of the [StaticFileHeader] at the start of an index file.
+const HEADER_SIZE: u64 = 32;
+
+/// The size of a row offset in an index file.
+const OFFSET_SIZE: u64 = 8;
+
+/// The range of blocks and rows stored in a static file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StaticFileHeader {
+    /// The first block of the file.
+    pub first_block: BlockNumber,
+    /// The number of blocks in the file.
+    pub blocks: u64,
+    /// The number of the first row of the file.
+    pub first_row: u64,
+    /// The number of rows in the file.
+    pub rows: u64,
+}
+
+impl StaticFileHeader {
+    /// The blocks of the file, or `None` if the file has no blocks.
+    pub fn block_range(&self) -> Option<RangeInclusive<BlockNumber>> {
+        (self.blocks > 0).then(|| self.first_block..=self.first_block + self.blocks - 1)
+    }
+
+    /// The row numbers of the file.
+    pub fn row_range(&self) -> Range<u64> {
+        self.first_row..self.first_row + self.rows
+    }
+
+    fn encode(&self) -> [u8; HEADER_SIZE as usize] {
+        let mut buf = [0; HEADER_SIZE as usize];
+        for (chunk, value) in
+            buf.chunks_exact_mut(8).zip([self.first_block, self.blocks, self.first_row, self.rows])
+        {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        buf
+    }
+
+    fn decode(buf: &[u8; HEADER_SIZE as usize]) -> Self {
+        let value = |i: usize| u64::from_le_bytes(buf[i * 8..i * 8 + 8].try_into().unwrap());
+        Self { first_block: value(0), blocks: value(1), first_row: value(2), rows: value(3) }
+    }
+
+    fn read(index: &mut File) -> io::Result<Self> {
+        let mut buf = [0; HEADER_SIZE as usize];
+        index.seek(SeekFrom::Start(0))?;
+        index.read_exact(&mut buf)?;
+        Ok(Self::decode(&buf))
+    }
+}
+
+/// Returns the paths of the data and the index file of the segment file starting at
+/// `first_block`.
+pub(crate) fn file_paths(
+    dir: &Path,
+    segment: StaticFileSegment,
+    first_block: BlockNumber,
+) -> (PathBuf, PathBuf) {
+    let name = format!("{}_{first_block}", segment.name());
+    (dir.join(format!("{name}.dat")), dir.join(format!("{name}.idx")))
+}
+
+/// Parses the segment and the first block from the name of an index file.
+pub(crate) fn parse_index_file_name(name: &str) -> Option<(StaticFileSegment, BlockNumber)> {
+    let (segment, first_block) = name.strip_suffix(".idx")?.rsplit_once('_')?;
+    Some((StaticFileSegment::from_name(segment)?, first_block.parse().ok()?))
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// A memory mapped static file.
+///
+/// Only the rows that were committed when the file was opened are visible.
+#[derive(Debug)]
+pub(crate) struct StaticFile {
+    header: StaticFileHeader,
+    /// The maps of the index and the data file, if the file has any rows.
+    maps: Option<(Mmap, Mmap)>,
+}
+
+impl StaticFile {
+    /// Open the segment file starting at `first_block`.
+    pub(crate) fn open(
+        dir: &Path,
+        segment: StaticFileSegment,
+        first_block: BlockNumber,
+    ) -> io::Result<Self> {
+        let (data_path, index_path) = file_paths(dir, segment, first_block);
+        let mut index = File::open(index_path)?;
+        let header = StaticFileHeader::read(&mut index)?;
+        if header.rows == 0 {
+            return Ok(Self { header, maps: None })
+        }
+
+        // SAFETY: Static files are append-only, the committed rows are never modified.
+        let (index, data) = unsafe { (Mmap::map(&index)?, Mmap::map(&File::open(data_path)?)?) };
+        if (index.len() as u64) < HEADER_SIZE + header.rows * OFFSET_SIZE {
+            return Err(invalid_data("index file is shorter than its header"))
+        }
+        Ok(Self { header, maps: Some((index, data)) })
+    }
+
+    /// The range of blocks and rows of the file.
+    pub(crate) fn header(&self) -> &StaticFileHeader {
+        &self.header
+    }
+
+    /// Read the row, or `None` if it's not in this file or it's empty.
+    pub(crate) fn row(&self, number: u64) -> io::Result<Option<Vec<u8>>> {
+        let (index, data) = match &self.maps {
+            Some(maps) if self.header.row_range().contains(&number) => maps,
+            _ => return Ok(None),
+        };
+
+        let i = number - self.header.first_row;
+        let start = if i == 0 { 0 } else { read_offset(index, i - 1) };
+        let end = read_offset(index, i);
+        if start == end {
+            return Ok(None)
+        }
+        let row = data.get(start..end).ok_or_else(|| invalid_data("row out of bounds"))?;
+        snap::raw::Decoder::new().decompress_vec(row).map(Some).map_err(invalid_data)
+    }
+}
+
+/// Read the end offset of the i-th row of the file.
+fn read_offset(index: &[u8], i: u64) -> usize {
+    let pos = (HEADER_SIZE + i * OFFSET_SIZE) as usize;
+    u64::from_le_bytes(index[pos..pos + OFFSET_SIZE as usize].try_into().unwrap()) as usize
+}
+
+/// Appends blocks to a static file.
+///
+/// The appended blocks are only visible to new readers after [StaticFileWriter::commit].
+#[derive(Debug)]
+pub(crate) struct StaticFileWriter {
+    header: StaticFileHeader,
+    data: BufWriter<File>,
+    index: BufWriter<File>,
+    data_len: u64,
+}
+
+impl StaticFileWriter {
+    /// Open the segment file starting at `first_block` for appending.
+    ///
+    /// If the file doesn't exist, it's created with `first_row` as its first row. Rows that were
+    /// appended but not committed before are discarded.
+    pub(crate) fn open(
+        dir: &Path,
+        segment: StaticFileSegment,
+        first_block: BlockNumber,
+        first_row: u64,
+    ) -> io::Result<Self> {
+        let (data_path, index_path) = file_paths(dir, segment, first_block);
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true);
+        let mut index = options.open(index_path)?;
+        let mut data = options.open(data_path)?;
+
+        let header = if index.metadata()?.len() >= HEADER_SIZE {
+            StaticFileHeader::read(&mut index)?
+        } else {
+            StaticFileHeader { first_block, blocks: 0, first_row, rows: 0 }
+        };
+
+        let data_len = if header.rows == 0 {
+            0
+        } else {
+            let mut buf = [0; OFFSET_SIZE as usize];
+            index.seek(SeekFrom::Start(HEADER_SIZE + (header.rows - 1) * OFFSET_SIZE))?;
+            index.read_exact(&mut buf)?;
+            u64::from_le_bytes(buf)
+        };
+
+        // Discard everything after the last commit
+        index.set_len(HEADER_SIZE + header.rows * OFFSET_SIZE)?;
+        index.seek(SeekFrom::Start(0))?;
+        index.write_all(&header.encode())?;
+        index.seek(SeekFrom::End(0))?;
+        data.set_len(data_len)?;
+        data.seek(SeekFrom::End(0))?;
+
+        Ok(Self { header, data: BufWriter::new(data), index: BufWriter::new(index), data_len })
+    }
+
+    /// The range of blocks and rows of the file, including the uncommitted blocks.
+    pub(crate) fn header(&self) -> &StaticFileHeader {
+        &self.header
+    }
+
+    /// Append the rows of the next block.
+    ///
+    /// Empty rows take no space in the data file and are read as missing, so they keep the row
+    /// numbers of the following rows.
+    pub(crate) fn append_block(
+        &mut self,
+        rows: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> io::Result<()> {
+        let mut encoder = snap::raw::Encoder::new();
+        for row in rows {
+            if !row.as_ref().is_empty() {
+                let compressed = encoder.compress_vec(row.as_ref()).map_err(invalid_data)?;
+                self.data.write_all(&compressed)?;
+                self.data_len += compressed.len() as u64;
+            }
+            self.index.write_all(&self.data_len.to_le_bytes())?;
+            self.header.rows += 1;
+        }
+        self.header.blocks += 1;
+        Ok(())
+    }
+
+    /// Write the appended blocks to disk.
+    ///
+    /// The rows are synced before the header is updated, so a crash never exposes rows that
+    /// weren't fully written.
+    pub(crate) fn commit(&mut self) -> io::Result<()> {
+        self.data.flush()?;
+        self.data.get_ref().sync_all()?;
+        self.index.flush()?;
+        self.index.get_ref().sync_all()?;
+
+        let index = self.index.get_mut();
+        index.seek(SeekFrom::Start(0))?;
+        index.write_all(&self.header.encode())?;
+        index.sync_all()?;
+        index.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_read_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = StaticFileSegment::Transactions;
+
+        let mut writer = StaticFileWriter::open(dir.path(), segment, 0, 10).unwrap();
+        writer.append_block([b"a".to_vec(), b"bc".to_vec()]).unwrap();
+        writer.append_block(Vec::<Vec<u8>>::new()).unwrap();
+        writer.commit().unwrap();
+
+        // uncommitted blocks are discarded when reopening
+        writer.append_block([b"lost".to_vec()]).unwrap();
+        drop(writer);
+        let mut writer = StaticFileWriter::open(dir.path(), segment, 0, 10).unwrap();
+        writer.append_block([b"def".to_vec(), Vec::new()]).unwrap();
+        writer.commit().unwrap();
+
+        let file = StaticFile::open(dir.path(), segment, 0).unwrap();
+        assert_eq!(
+            file.header(),
+            &StaticFileHeader { first_block: 0, blocks: 3, first_row: 10, rows: 4 }
+        );
+        assert_eq!(file.header().block_range(), Some(0..=2));
+        assert_eq!(file.row(9).unwrap(), None);
+        assert_eq!(file.row(10).unwrap(), Some(b"a".to_vec()));
+        assert_eq!(file.row(11).unwrap(), Some(b"bc".to_vec()));
+        assert_eq!(file.row(12).unwrap(), Some(b"def".to_vec()));
+        // the empty row
+        assert_eq!(file.row(13).unwrap(), None);
+        assert_eq!(file.row(14).unwrap(), None);
+    }
+
+    #[test]
+    fn parse_file_names() {
+        let (_, index) = file_paths(Path::new("dir"), StaticFileSegment::Headers, 500_000);
+        let name = index.file_name().unwrap().to_str().unwrap();
+        assert_eq!(parse_index_file_name(name), Some((StaticFileSegment::Headers, 500_000)));
+        assert_eq!(parse_index_file_name("headers_0.dat"), None);
+        assert_eq!(parse_index_file_name("unknown_0.idx"), None);
+    }
+}
@@ -0,0 +1,61 @@
+//! Append-only storage of finalized chain data outside of the database.
+//!
+//! Finalized data never changes, so it doesn't need the transactional guarantees of the database.
+//! The [StaticFileProducer] moves the headers, transactions and receipts of finalized blocks out
+//! of the database into segment files, which are read through memory maps by the
+//! [StaticFileProvider].
+//!
+//! Each [StaticFileSegment] is split into files of [BLOCKS_PER_STATIC_FILE] blocks. A file consists
+//! of a data file with the snappy compressed rows and an index file with the range of blocks and
+//! rows of the file, followed by the end offset of each row in the data file. Rows that are not
+//! available, like the receipts of pruned blocks, are stored empty to keep the row numbers.
+
+mod file;
+mod producer;
+mod provider;
+
+pub use file::StaticFileHeader;
+pub use producer::StaticFileProducer;
+pub use provider::StaticFileProvider;
+
+use std::fmt::Display;
+
+/// The number of blocks stored in a single static file.
+pub const BLOCKS_PER_STATIC_FILE: u64 = 500_000;
+
+/// A segment of the chain data that is moved to static files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StaticFileSegment {
+    /// The headers of the blocks, one row per block.
+    Headers,
+    /// The transactions of the blocks, one row per transaction.
+    Transactions,
+    /// The receipts of the transactions, one row per transaction.
+    Receipts,
+}
+
+impl StaticFileSegment {
+    /// All segments.
+    pub const ALL: [StaticFileSegment; 3] =
+        [StaticFileSegment::Headers, StaticFileSegment::Transactions, StaticFileSegment::Receipts];
+
+    /// The name of the segment, used as prefix of its file names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StaticFileSegment::Headers => "headers",
+            StaticFileSegment::Transactions => "transactions",
+            StaticFileSegment::Receipts => "receipts",
+        }
+    }
+
+    /// Parse the segment from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|segment| segment.name() == name)
+    }
+}
+
+impl Display for StaticFileSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,274 @@
+use super::{StaticFileProvider, StaticFileSegment};
+use reth_db::{
+    cursor::DbCursorRO,
+    table::{Compress, Table},
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::{consensus::ForkchoiceState, provider::Error as ProviderError, Result};
+use reth_primitives::BlockNumber;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// The id of the receipts segment of the pruner in [tables::PruneCheckpoints].
+const RECEIPTS_PRUNE_SEGMENT: &[u8] = b"Receipts";
+
+/// Moves the headers, transactions and receipts of finalized blocks from the database to static
+/// files.
+///
+/// The rows are synced to the static files before they are deleted from the database, so they are
+/// never lost. Rows that were already moved but not deleted before a crash are deleted by the next
+/// run.
+///
+/// The receipts of the blocks up to the prune checkpoint of the receipts are stored as empty rows,
+/// so pruning doesn't stop the receipts from being moved.
+#[derive(Debug)]
+pub struct StaticFileProducer {
+    static_files: Arc<StaticFileProvider>,
+    /// Watcher over the forkchoice state, used to resolve the finalized block.
+    fork_choice_state: watch::Receiver<ForkchoiceState>,
+}
+
+impl StaticFileProducer {
+    /// Create a new producer that moves the finalized blocks of the forkchoice state.
+    pub fn new(
+        static_files: Arc<StaticFileProvider>,
+        fork_choice_state: watch::Receiver<ForkchoiceState>,
+    ) -> Self {
+        Self { static_files, fork_choice_state }
+    }
+
+    /// Move the data of the finalized blocks below the `tip` to the static files.
+    ///
+    /// The `tip` must have been reached by all stages. It's never moved, because the stages read
+    /// the header of their progress.
+    pub fn run<'a, TX>(&self, tx: &TX, tip: BlockNumber) -> Result<()>
+    where
+        TX: DbTx<'a> + DbTxMut<'a>,
+    {
+        let finalized_hash = self.fork_choice_state.borrow().finalized_block_hash;
+        if finalized_hash.is_zero() {
+            return Ok(())
+        }
+        let finalized = match tx.get::<tables::HeaderNumbers>(finalized_hash)? {
+            Some(number) if tx.get::<tables::CanonicalHeaders>(number)? == Some(finalized_hash) => {
+                number
+            }
+            _ => return Ok(()),
+        };
+        let target = match tip.checked_sub(1) {
+            Some(target) => target.min(finalized),
+            None => return Ok(()),
+        };
+
+        for segment in StaticFileSegment::ALL {
+            self.move_segment(tx, segment, target)?;
+        }
+        Ok(())
+    }
+
+    /// Move the blocks of the segment up to and including `target`.
+    fn move_segment<'a, TX>(
+        &self,
+        tx: &TX,
+        segment: StaticFileSegment,
+        target: BlockNumber,
+    ) -> Result<()>
+    where
+        TX: DbTx<'a> + DbTxMut<'a>,
+    {
+        let pruned = match segment {
+            StaticFileSegment::Receipts => {
+                tx.get::<tables::PruneCheckpoints>(RECEIPTS_PRUNE_SEGMENT.to_vec())?
+            }
+            _ => None,
+        };
+
+        let mut writer = self.static_files.writer(segment)?;
+        while writer.next_block() <= target {
+            let number = writer.next_block();
+            let is_pruned = pruned.map_or(false, |checkpoint| number <= checkpoint);
+            match block_rows(tx, segment, number, is_pruned)? {
+                Some(rows) => writer.append_block(rows)?,
+                None => break,
+            }
+        }
+        writer.commit()?;
+
+        let (next_block, next_row) = (writer.next_block(), writer.next_row());
+        match segment {
+            StaticFileSegment::Headers => {
+                delete_below::<tables::Headers, _>(tx, next_block, |key| key.number())
+            }
+            StaticFileSegment::Transactions => {
+                delete_below::<tables::Transactions, _>(tx, next_row, |id| *id)
+            }
+            StaticFileSegment::Receipts => {
+                delete_below::<tables::Receipts, _>(tx, next_row, |id| *id)
+            }
+        }
+    }
+}
+
+/// Read the rows of the segment of the canonical block.
+///
+/// Returns `None` if the rows are not available yet, which is the case for receipts that were not
+/// written. The missing receipts of a pruned block are returned as empty rows.
+fn block_rows<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    segment: StaticFileSegment,
+    number: BlockNumber,
+    is_pruned: bool,
+) -> Result<Option<Vec<Vec<u8>>>> {
+    let hash = tx
+        .get::<tables::CanonicalHeaders>(number)?
+        .ok_or(ProviderError::BlockNumber { block_number: number })?;
+
+    if segment == StaticFileSegment::Headers {
+        let header = tx
+            .get::<tables::Headers>((number, hash).into())?
+            .ok_or(ProviderError::BlockHash { block_hash: hash })?;
+        return Ok(Some(vec![header.compress()]))
+    }
+
+    let body = tx
+        .get::<tables::BlockBodies>((number, hash).into())?
+        .ok_or(ProviderError::BlockBody { block_number: number, block_hash: hash })?;
+    let mut rows = Vec::with_capacity(body.tx_count as usize);
+    for id in body.tx_id_range() {
+        let row = if segment == StaticFileSegment::Transactions {
+            tx.get::<tables::Transactions>(id)?.ok_or(ProviderError::Transaction { id })?.compress()
+        } else {
+            match tx.get::<tables::Receipts>(id)? {
+                Some(receipt) => receipt.compress(),
+                None if is_pruned => Vec::new(),
+                None => return Ok(None),
+            }
+        };
+        rows.push(row);
+    }
+    Ok(Some(rows))
+}
+
+/// Delete the entries of the table whose number, as selected by `number`, is below `end`.
+fn delete_below<'a, T, TX>(tx: &TX, end: u64, number: impl Fn(&T::Key) -> u64) -> Result<()>
+where
+    T: Table,
+    TX: DbTx<'a> + DbTxMut<'a>,
+{
+    let mut keys = Vec::new();
+    let mut cursor = tx.cursor::<T>()?;
+    let mut entry = cursor.first()?;
+    while let Some((key, _)) = entry {
+        if number(&key) >= end {
+            break
+        }
+        keys.push(key);
+        entry = cursor.next()?;
+    }
+
+    for key in keys {
+        tx.delete::<T>(key, None)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, Env, EnvKind, WriteMap},
+        models::StoredBlockBody,
+    };
+    use reth_primitives::{Header, Receipt, TransactionSigned, H256};
+
+    /// Insert five blocks with one transaction each, the hash of a block is its number plus one.
+    fn insert_blocks(db: &Env<WriteMap>) -> Vec<Header> {
+        let headers =
+            (0..5).map(|number| Header { number, ..Default::default() }).collect::<Vec<_>>();
+        db.update(|tx| {
+            for header in &headers {
+                let (number, hash) = (header.number, H256::from_low_u64_be(header.number + 1));
+                tx.put::<tables::CanonicalHeaders>(number, hash).unwrap();
+                tx.put::<tables::HeaderNumbers>(hash, number).unwrap();
+                tx.put::<tables::Headers>((number, hash).into(), header.clone()).unwrap();
+                tx.put::<tables::BlockBodies>(
+                    (number, hash).into(),
+                    StoredBlockBody { start_tx_id: number, tx_count: 1 },
+                )
+                .unwrap();
+                tx.put::<tables::Transactions>(number, TransactionSigned::default()).unwrap();
+            }
+        })
+        .unwrap();
+        headers
+    }
+
+    #[test]
+    fn move_finalized_blocks() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = Arc::new(StaticFileProvider::open(dir.path()).unwrap());
+        let headers = insert_blocks(&db);
+
+        let (fork_choice_tx, fork_choice_rx) = watch::channel(ForkchoiceState::default());
+        let producer = StaticFileProducer::new(static_files.clone(), fork_choice_rx);
+
+        // nothing is finalized yet
+        db.update(|tx| producer.run(tx, 4)).unwrap().unwrap();
+        assert_eq!(static_files.highest_block(StaticFileSegment::Headers), None);
+
+        // finalize block 3, the blocks below the tip are moved
+        let finalized = H256::from_low_u64_be(4);
+        fork_choice_tx
+            .send(ForkchoiceState { finalized_block_hash: finalized, ..Default::default() })
+            .unwrap();
+        db.update(|tx| producer.run(tx, 3)).unwrap().unwrap();
+
+        assert_eq!(static_files.highest_block(StaticFileSegment::Headers), Some(2));
+        assert_eq!(static_files.highest_block(StaticFileSegment::Transactions), Some(2));
+        // there are no receipts to move
+        assert_eq!(static_files.highest_block(StaticFileSegment::Receipts), None);
+        assert_eq!(static_files.header(2).unwrap(), Some(headers[2].clone()));
+        assert_eq!(static_files.transaction(2).unwrap(), Some(TransactionSigned::default()));
+
+        let tx = db.tx().unwrap();
+        let mut headers_cursor = tx.cursor::<tables::Headers>().unwrap();
+        assert_eq!(headers_cursor.first().unwrap().map(|(key, _)| key.number()), Some(3));
+        let mut transactions_cursor = tx.cursor::<tables::Transactions>().unwrap();
+        assert_eq!(transactions_cursor.first().unwrap().map(|(id, _)| id), Some(3));
+    }
+
+    #[test]
+    fn move_receipts_after_pruning() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = Arc::new(StaticFileProvider::open(dir.path()).unwrap());
+        insert_blocks(&db);
+
+        // the receipts of the first two blocks were pruned
+        db.update(|tx| {
+            tx.put::<tables::PruneCheckpoints>(RECEIPTS_PRUNE_SEGMENT.to_vec(), 1).unwrap();
+            for id in 2..5 {
+                tx.put::<tables::Receipts>(id, Receipt::default()).unwrap();
+            }
+        })
+        .unwrap();
+
+        let finalized = H256::from_low_u64_be(4);
+        let fork_choice = ForkchoiceState { finalized_block_hash: finalized, ..Default::default() };
+        let (_fork_choice_tx, fork_choice_rx) = watch::channel(fork_choice);
+        let producer = StaticFileProducer::new(static_files.clone(), fork_choice_rx);
+        db.update(|tx| producer.run(tx, 4)).unwrap().unwrap();
+
+        assert_eq!(static_files.highest_block(StaticFileSegment::Receipts), Some(3));
+        assert_eq!(static_files.receipt(1).unwrap(), None);
+        assert_eq!(static_files.receipt(2).unwrap(), Some(Receipt::default()));
+        assert_eq!(static_files.receipt(3).unwrap(), Some(Receipt::default()));
+
+        let tx = db.tx().unwrap();
+        let mut receipts_cursor = tx.cursor::<tables::Receipts>().unwrap();
+        assert_eq!(receipts_cursor.first().unwrap().map(|(id, _)| id), Some(4));
+    }
+}
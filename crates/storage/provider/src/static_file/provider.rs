@@ -0,0 +1,263 @@
+use super::{
+    file::{file_paths, parse_index_file_name, StaticFile, StaticFileWriter},
+    StaticFileHeader, StaticFileSegment, BLOCKS_PER_STATIC_FILE,
+};
+use reth_db::table::Decompress;
+use reth_interfaces::{provider::Error as ProviderError, Result};
+use reth_primitives::{BlockNumber, Header, Receipt, TransactionSigned, TxNumber};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+fn static_file_error(err: io::Error) -> ProviderError {
+    ProviderError::StaticFile(err.to_string())
+}
+
+/// Provides the data of the static files in a directory.
+#[derive(Debug)]
+pub struct StaticFileProvider {
+    dir: PathBuf,
+    /// The number of blocks of a new file.
+    blocks_per_file: u64,
+    /// The files of each segment, ordered by their first block.
+    files: RwLock<HashMap<StaticFileSegment, Vec<Arc<StaticFile>>>>,
+}
+
+impl StaticFileProvider {
+    /// Open the static files in the directory, creating the directory if it doesn't exist.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut files: HashMap<_, Vec<Arc<StaticFile>>> = HashMap::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let name = entry?.file_name();
+            if let Some((segment, first_block)) = name.to_str().and_then(parse_index_file_name) {
+                let file = StaticFile::open(&dir, segment, first_block)?;
+                // files without blocks were created by a writer that never committed
+                if file.header().blocks > 0 {
+                    files.entry(segment).or_default().push(Arc::new(file));
+                }
+            }
+        }
+        for segment_files in files.values_mut() {
+            segment_files.sort_by_key(|file| file.header().first_block);
+        }
+
+        Ok(Self { dir, blocks_per_file: BLOCKS_PER_STATIC_FILE, files: RwLock::new(files) })
+    }
+
+    /// Set the number of blocks of the files that are created from now on.
+    ///
+    /// Defaults to [BLOCKS_PER_STATIC_FILE].
+    pub fn with_blocks_per_file(mut self, blocks_per_file: u64) -> Self {
+        self.blocks_per_file = blocks_per_file;
+        self
+    }
+
+    /// The directory of the static files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Copy the static files to the directory, e.g. to back them up while the node is running.
+    ///
+    /// Only the files that were committed when they were opened are copied. The index file is
+    /// copied before the data file, so the copy of the data file contains at least the rows of the
+    /// copied index file, rows appended in between are truncated when the copy is written to.
+    pub fn copy_to(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let files = self.files.read().unwrap();
+        for (segment, segment_files) in files.iter() {
+            for file in segment_files {
+                let (data_path, index_path) =
+                    file_paths(&self.dir, *segment, file.header().first_block);
+                let (data_copy, index_copy) = file_paths(dir, *segment, file.header().first_block);
+                std::fs::copy(index_path, index_copy)?;
+                std::fs::copy(data_path, data_copy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The highest block of the segment that is stored in static files.
+    pub fn highest_block(&self, segment: StaticFileSegment) -> Option<BlockNumber> {
+        self.last_header(segment)?.block_range().map(|blocks| *blocks.end())
+    }
+
+    /// Get the header of the block from the static files.
+    pub fn header(&self, number: BlockNumber) -> Result<Option<Header>> {
+        self.get(StaticFileSegment::Headers, number)
+    }
+
+    /// Get the transaction from the static files.
+    pub fn transaction(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
+        self.get(StaticFileSegment::Transactions, id)
+    }
+
+    /// Get the receipt of the transaction from the static files.
+    pub fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
+        self.get(StaticFileSegment::Receipts, id)
+    }
+
+    /// Get the row of the segment, or `None` if it's not stored in static files.
+    fn get<T: Decompress>(&self, segment: StaticFileSegment, row: u64) -> Result<Option<T>> {
+        let file = self.files.read().unwrap().get(&segment).and_then(|files| {
+            files.iter().find(|file| file.header().row_range().contains(&row)).cloned()
+        });
+        match file {
+            Some(file) => match file.row(row).map_err(static_file_error)? {
+                Some(value) => Ok(Some(T::decompress(value)?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The header of the last file of the segment.
+    fn last_header(&self, segment: StaticFileSegment) -> Option<StaticFileHeader> {
+        let files = self.files.read().unwrap();
+        files.get(&segment)?.last().map(|file| *file.header())
+    }
+
+    /// Open a writer that appends blocks to the segment.
+    pub(crate) fn writer(&self, segment: StaticFileSegment) -> Result<StaticFileSegmentWriter<'_>> {
+        let (first_block, next_block, next_row) = match self.last_header(segment) {
+            Some(header) => {
+                let next_block = header.first_block + header.blocks;
+                // continue the last file unless it's full
+                let first_block = if header.blocks < self.blocks_per_file {
+                    header.first_block
+                } else {
+                    next_block
+                };
+                (first_block, next_block, header.row_range().end)
+            }
+            None => (0, 0, 0),
+        };
+        let writer = StaticFileWriter::open(&self.dir, segment, first_block, next_row)
+            .map_err(static_file_error)?;
+        Ok(StaticFileSegmentWriter { provider: self, segment, writer, next_block })
+    }
+
+    /// Reload the file of the segment starting at `first_block` after it was written.
+    fn reload(&self, segment: StaticFileSegment, first_block: BlockNumber) -> io::Result<()> {
+        let file = Arc::new(StaticFile::open(&self.dir, segment, first_block)?);
+        let mut files = self.files.write().unwrap();
+        let segment_files = files.entry(segment).or_default();
+        match segment_files.iter_mut().find(|f| f.header().first_block == first_block) {
+            Some(existing) => *existing = file,
+            None => segment_files.push(file),
+        }
+        Ok(())
+    }
+}
+
+/// Appends blocks to the static files of a segment, starting a new file whenever the current one
+/// is full.
+#[derive(Debug)]
+pub(crate) struct StaticFileSegmentWriter<'a> {
+    provider: &'a StaticFileProvider,
+    segment: StaticFileSegment,
+    writer: StaticFileWriter,
+    next_block: BlockNumber,
+}
+
+impl<'a> StaticFileSegmentWriter<'a> {
+    /// The next block that is appended.
+    pub(crate) fn next_block(&self) -> BlockNumber {
+        self.next_block
+    }
+
+    /// The number of the next row that is appended.
+    pub(crate) fn next_row(&self) -> u64 {
+        self.writer.header().row_range().end
+    }
+
+    /// Append the rows of the next block.
+    pub(crate) fn append_block(
+        &mut self,
+        rows: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> Result<()> {
+        if self.writer.header().blocks >= self.provider.blocks_per_file {
+            self.commit()?;
+            let (dir, next_row) = (&self.provider.dir, self.next_row());
+            self.writer = StaticFileWriter::open(dir, self.segment, self.next_block, next_row)
+                .map_err(static_file_error)?;
+        }
+        self.writer.append_block(rows).map_err(static_file_error)?;
+        self.next_block += 1;
+        Ok(())
+    }
+
+    /// Write the appended blocks to disk and make them visible to the provider.
+    pub(crate) fn commit(&mut self) -> Result<()> {
+        self.writer.commit().map_err(static_file_error)?;
+        self.provider
+            .reload(self.segment, self.writer.header().first_block)
+            .map_err(static_file_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::table::Compress;
+
+    #[test]
+    fn write_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StaticFileProvider::open(dir.path()).unwrap().with_blocks_per_file(2);
+        assert_eq!(provider.highest_block(StaticFileSegment::Headers), None);
+
+        let headers =
+            (0..3).map(|number| Header { number, ..Default::default() }).collect::<Vec<_>>();
+        let mut writer = provider.writer(StaticFileSegment::Headers).unwrap();
+        for header in &headers {
+            writer.append_block([header.clone().compress()]).unwrap();
+        }
+        // the full file is committed before the next one is started
+        assert_eq!(provider.highest_block(StaticFileSegment::Headers), Some(1));
+        writer.commit().unwrap();
+        assert_eq!(provider.highest_block(StaticFileSegment::Headers), Some(2));
+
+        // the last file is continued
+        let writer = provider.writer(StaticFileSegment::Headers).unwrap();
+        assert_eq!((writer.next_block(), writer.next_row()), (3, 3));
+        drop(writer);
+
+        // reopen from disk
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        for header in &headers {
+            assert_eq!(provider.header(header.number).unwrap(), Some(header.clone()));
+        }
+        assert_eq!(provider.header(3).unwrap(), None);
+        assert_eq!(provider.transaction(0).unwrap(), None);
+    }
+
+    #[test]
+    fn copy_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StaticFileProvider::open(dir.path()).unwrap().with_blocks_per_file(2);
+        let headers =
+            (0..3).map(|number| Header { number, ..Default::default() }).collect::<Vec<_>>();
+        let mut writer = provider.writer(StaticFileSegment::Headers).unwrap();
+        for header in &headers {
+            writer.append_block([header.clone().compress()]).unwrap();
+        }
+        writer.commit().unwrap();
+
+        let copy = tempfile::tempdir().unwrap();
+        provider.copy_to(copy.path()).unwrap();
+        let provider = StaticFileProvider::open(copy.path()).unwrap();
+        assert_eq!(provider.highest_block(StaticFileSegment::Headers), Some(2));
+        for header in &headers {
+            assert_eq!(provider.header(header.number).unwrap(), Some(header.clone()));
+        }
+    }
+}
@@ -1,6 +1,13 @@
-use crate::{BlockProvider, ChainInfo, HeaderProvider};
+use crate::{
+    BlockProvider, ChainInfo, HeaderProvider, LogWithMeta, ReceiptProvider,
+    StageCheckpointProvider, TransactionMeta, TransactionsProvider,
+};
 use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Block, BlockHash, BlockNumber, Header, H256, U256};
+use reth_primitives::{
+    rpc::{BlockId, Filter},
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, Header, Receipt, TransactionSigned,
+    TransactionSignedEcRecovered, TxHash, TxNumber, Withdrawal, H256, U256,
+};
 
 /// Supports various api interfaces for testing purposes.
 #[derive(Debug, Clone, Default)]
@@ -22,6 +29,14 @@ impl BlockProvider for TestApi {
         Ok(None)
     }
 
+    fn ommers(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
+        Ok(None)
+    }
+
+    fn withdrawals(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Withdrawal>>> {
+        Ok(None)
+    }
+
     fn block_number(&self, _hash: H256) -> Result<Option<BlockNumber>> {
         Ok(None)
     }
@@ -44,3 +59,58 @@ impl HeaderProvider for TestApi {
         Ok(None)
     }
 }
+
+impl TransactionsProvider for TestApi {
+    fn transaction_by_hash(&self, _hash: TxHash) -> Result<Option<TransactionSigned>> {
+        Ok(None)
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        _hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        Ok(None)
+    }
+
+    fn transactions_by_block(
+        &self,
+        _block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        Ok(None)
+    }
+
+    fn transactions_with_senders_by_block(
+        &self,
+        _block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedEcRecovered>>> {
+        Ok(None)
+    }
+}
+
+impl ReceiptProvider for TestApi {
+    fn receipt(&self, _id: TxNumber) -> Result<Option<Receipt>> {
+        Ok(None)
+    }
+
+    fn receipt_by_hash(&self, _hash: TxHash) -> Result<Option<Receipt>> {
+        Ok(None)
+    }
+
+    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        Ok(None)
+    }
+
+    fn logs_by_block(
+        &self,
+        _block: BlockHashOrNumber,
+        _filter: &Filter,
+    ) -> Result<Option<Vec<LogWithMeta>>> {
+        Ok(None)
+    }
+}
+
+impl StageCheckpointProvider for TestApi {
+    fn stage_checkpoints(&self) -> Result<Vec<(String, BlockNumber)>> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,45 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{
+    BlockHash, BlockHashOrNumber, BlockNumber, TransactionSigned, TransactionSignedEcRecovered,
+    TxHash,
+};
+
+/// Additional metadata of a canonical transaction.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct TransactionMeta {
+    /// Hash of the transaction.
+    pub tx_hash: TxHash,
+    /// Index of the transaction in the block.
+    pub index: u64,
+    /// Hash of the block that includes the transaction.
+    pub block_hash: BlockHash,
+    /// Number of the block that includes the transaction.
+    pub block_number: BlockNumber,
+}
+
+/// Client trait for fetching canonical [TransactionSigned] related data.
+#[auto_impl(&)]
+pub trait TransactionsProvider: Send + Sync {
+    /// Get transaction by hash. Returns `None` if the transaction is not canonical.
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>>;
+
+    /// Get transaction by hash together with the location of the transaction in the chain.
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>>;
+
+    /// Get all transactions of the block. Returns `None` if the block is not canonical.
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>>;
+
+    /// Get all transactions of the block with their senders. Returns `None` if the block is not
+    /// canonical.
+    fn transactions_with_senders_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedEcRecovered>>>;
+}
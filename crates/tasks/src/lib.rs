@@ -7,18 +7,46 @@
 
 //! reth task management
 
+use crate::shutdown::{signal, Shutdown, Signal};
 use futures_util::{Future, FutureExt, Stream};
 use std::{
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
     runtime::Handle,
     sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
 };
-use tracing::error;
+use tracing::{debug, error, warn};
 use tracing_futures::Instrument;
 
+pub mod shutdown;
+
+/// Spawns the future onto the runtime as a task with the given name.
+///
+/// The name identifies the task in [tokio-console](https://github.com/tokio-rs/console), which
+/// requires building with `RUSTFLAGS="--cfg tokio_unstable"`. Otherwise the name is ignored.
+pub fn spawn_named<F>(handle: &Handle, name: &str, fut: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(tokio_unstable)]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn_on(fut, handle)
+            .expect("spawning a task on a running runtime doesn't fail")
+    }
+    #[cfg(not(tokio_unstable))]
+    {
+        let _ = name;
+        handle.spawn(fut)
+    }
+}
+
 /// Many reth components require to spawn tasks for long-running jobs. For example `discovery`
 /// spawns tasks to handle egress and ingress of udp traffic or `network` that spawns session tasks
 /// that handle the traffic to and from a peer.
@@ -31,6 +59,9 @@ use tracing_futures::Instrument;
 /// diagnostic purposes, since tokio task essentially fail silently. Therefore, this type is a
 /// Stream that yields the name of panicked task, See [`TaskExecutor::spawn_critical`]. In order to
 /// execute Tasks use the [`TaskExecutor`] type [`TaskManager::executor`].
+///
+/// Tasks that depend on each other can be shut down in order, see
+/// [`TaskManager::graceful_shutdown`].
 pub struct TaskManager {
     /// Handle to the tokio runtime this task manager is associated with.
     ///
@@ -40,6 +71,20 @@ pub struct TaskManager {
     panicked_tasks_tx: UnboundedSender<String>,
     /// Listens for panicked tasks
     panicked_tasks_rx: UnboundedReceiver<String>,
+    /// Sender half for registering the tasks that are shut down gracefully
+    graceful_tasks_tx: UnboundedSender<GracefulTask>,
+    /// Receives the tasks that are shut down gracefully, in the order they were spawned
+    graceful_tasks_rx: UnboundedReceiver<GracefulTask>,
+}
+
+/// A task that is told to shut down by the [`TaskManager`].
+struct GracefulTask {
+    /// The name of the task.
+    name: &'static str,
+    /// Requests the shutdown of the task.
+    signal: Signal,
+    /// Resolves once the task finished.
+    handle: JoinHandle<()>,
 }
 
 // === impl TaskManager ===
@@ -48,7 +93,8 @@ impl TaskManager {
     /// Create a new instance connected to the given handle's tokio runtime.
     pub fn new(handle: Handle) -> Self {
         let (panicked_tasks_tx, panicked_tasks_rx) = unbounded_channel();
-        Self { handle, panicked_tasks_tx, panicked_tasks_rx }
+        let (graceful_tasks_tx, graceful_tasks_rx) = unbounded_channel();
+        Self { handle, panicked_tasks_tx, panicked_tasks_rx, graceful_tasks_tx, graceful_tasks_rx }
     }
 
     /// Returns a new [`TaskExecutor`] that can spawn new tasks onto the tokio runtime this type is
@@ -57,7 +103,34 @@ impl TaskManager {
         TaskExecutor {
             handle: self.handle.clone(),
             panicked_tasks_tx: self.panicked_tasks_tx.clone(),
+            graceful_tasks_tx: self.graceful_tasks_tx.clone(),
+        }
+    }
+
+    /// Shuts down the tasks spawned with [`TaskExecutor::spawn_critical_with_shutdown_signal`] in
+    /// the reverse order they were spawned, so a task is only told to shut down once all tasks that
+    /// were spawned after it, and may depend on it, finished.
+    ///
+    /// Returns `false` if the tasks didn't finish within the timeout, in which case the remaining
+    /// tasks are not waited for.
+    pub async fn graceful_shutdown(mut self, timeout: Duration) -> bool {
+        let mut tasks = Vec::new();
+        while let Ok(task) = self.graceful_tasks_rx.try_recv() {
+            tasks.push(task);
+        }
+
+        let shutdown = async move {
+            for GracefulTask { name, signal, handle } in tasks.into_iter().rev() {
+                debug!(target: "tasks", task = name, "Shutting down task");
+                signal.fire();
+                let _ = handle.await;
+            }
+        };
+        if tokio::time::timeout(timeout, shutdown).await.is_err() {
+            warn!(target: "tasks", ?timeout, "Tasks did not shut down in time");
+            return false
         }
+        true
     }
 }
 
@@ -81,6 +154,8 @@ pub struct TaskExecutor {
     handle: Handle,
     /// Sender half for sending panic signals to this type
     panicked_tasks_tx: UnboundedSender<String>,
+    /// Sender half for registering the tasks that are shut down gracefully
+    graceful_tasks_tx: UnboundedSender<GracefulTask>,
 }
 
 // === impl TaskExecutor ===
@@ -97,6 +172,14 @@ impl TaskExecutor {
         self.handle.spawn(task);
     }
 
+    /// Spawns the task onto the runtime with a name, see [`spawn_named`].
+    pub fn spawn_named<F>(&self, name: &str, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        spawn_named(&self.handle, name, fut.in_current_span());
+    }
+
     /// This spawns a critical task onto the runtime.
     ///
     /// If this task panics, the [`TaskManager`] is notified.
@@ -114,7 +197,36 @@ impl TaskExecutor {
                 let _ = panicked_tasks_tx.send(name.to_string());
             })
             .in_current_span();
-        self.handle.spawn(task);
+        spawn_named(&self.handle, name, task);
+    }
+
+    /// This spawns a critical task onto the runtime that shuts down once the given [`Shutdown`]
+    /// resolves, see [`TaskManager::graceful_shutdown`].
+    ///
+    /// If this task panics or stops before the shutdown was requested, the [`TaskManager`] is
+    /// notified.
+    pub fn spawn_critical_with_shutdown_signal<F>(
+        &self,
+        name: &'static str,
+        f: impl FnOnce(Shutdown) -> F,
+    ) where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (signal, shutdown) = signal();
+        let fut = f(shutdown.clone());
+        let panicked_tasks_tx = self.panicked_tasks_tx.clone();
+
+        let task = std::panic::AssertUnwindSafe(fut)
+            .catch_unwind()
+            .map(move |res| {
+                if res.is_err() || !shutdown.is_requested() {
+                    error!("Critical task `{name}` panicked: {res:?}");
+                    let _ = panicked_tasks_tx.send(name.to_string());
+                }
+            })
+            .in_current_span();
+        let handle = spawn_named(&self.handle, name, task);
+        let _ = self.graceful_tasks_tx.send(GracefulTask { name, signal, handle });
     }
 }
 
@@ -140,4 +252,41 @@ mod tests {
             assert_eq!(panicked_task, "this is a critical task");
         })
     }
+    #[test]
+    fn test_graceful_shutdown_in_reverse_order() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = TaskManager::new(runtime.handle().clone());
+        let executor = manager.executor();
+        let (order_tx, mut order_rx) = unbounded_channel();
+
+        for name in ["network", "engine", "rpc"] {
+            let order_tx = order_tx.clone();
+            executor.spawn_critical_with_shutdown_signal(name, |shutdown| async move {
+                shutdown.await;
+                let _ = order_tx.send(name);
+            });
+        }
+
+        runtime.block_on(async move {
+            assert!(manager.graceful_shutdown(Duration::from_secs(10)).await);
+            let mut order = Vec::new();
+            while let Ok(name) = order_rx.try_recv() {
+                order.push(name);
+            }
+            assert_eq!(order, vec!["rpc", "engine", "network"]);
+        })
+    }
+
+    #[test]
+    fn test_graceful_shutdown_timeout() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = TaskManager::new(runtime.handle().clone());
+        manager
+            .executor()
+            .spawn_critical_with_shutdown_signal("stuck", |_| futures_util::future::pending());
+
+        runtime.block_on(async move {
+            assert!(!manager.graceful_shutdown(Duration::from_millis(10)).await);
+        })
+    }
 }
@@ -0,0 +1,73 @@
+//! Helpers for shutting down tasks gracefully.
+
+use futures_util::{
+    future::{FusedFuture, Shared},
+    FutureExt,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::oneshot;
+
+/// A future that resolves once the task it is given to should shut down.
+///
+/// It also resolves if the [`Signal`] is dropped without being fired.
+#[derive(Debug, Clone)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Shutdown(Shared<oneshot::Receiver<()>>);
+
+impl Shutdown {
+    /// Returns `true` if the shutdown was requested.
+    pub fn is_requested(&self) -> bool {
+        self.0.is_terminated() || self.0.clone().now_or_never().is_some()
+    }
+}
+
+impl Future for Shutdown {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.0.is_terminated() {
+            return Poll::Ready(())
+        }
+        this.0.poll_unpin(cx).map(|_| ())
+    }
+}
+
+/// Requests the shutdown of the task that holds the [`Shutdown`].
+#[derive(Debug)]
+pub struct Signal(oneshot::Sender<()>);
+
+impl Signal {
+    /// Fires the signal, which resolves all [`Shutdown`] futures of this signal.
+    pub fn fire(self) {
+        let _ = self.0.send(());
+    }
+}
+
+/// Creates a new [`Signal`] and the [`Shutdown`] future it resolves.
+pub fn signal() -> (Signal, Shutdown) {
+    let (tx, rx) = oneshot::channel();
+    (Signal(tx), Shutdown(rx.shared()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fire_signal() {
+        let (signal, shutdown) = signal();
+        assert!(!shutdown.is_requested());
+
+        let task = tokio::spawn(shutdown.clone());
+        signal.fire();
+        task.await.unwrap();
+        assert!(shutdown.is_requested());
+        // the future can be polled again after it resolved
+        shutdown.clone().await;
+    }
+}
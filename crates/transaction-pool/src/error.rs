@@ -21,6 +21,18 @@ pub enum PoolError {
     /// respect the size limits of the pool.
     #[error("[{0:?}] Transaction discarded outright due to pool size constraints.")]
     DiscardedOnInsert(TxHash),
+    /// Thrown when a transaction is added to a pool that doesn't accept any transactions.
+    #[error("[{0:?}] Transaction pool does not accept transactions.")]
+    Disabled(TxHash),
+    /// Thrown if the transaction is signed for another chain.
+    #[error("[{0:?}] Transaction is signed for another chain.")]
+    InvalidChainId(TxHash),
+    /// Thrown if the nonce of the transaction is lower than the nonce of its sender.
+    #[error("[{0:?}] Transaction nonce is lower than the sender's nonce {1}.")]
+    NonceTooLow(TxHash, u64),
+    /// Thrown if the state the transaction is validated against can't be read.
+    #[error("[{0:?}] Failed to read the state to validate the transaction: {1}")]
+    StateUnavailable(TxHash, String),
 }
 
 // === impl PoolError ===
@@ -33,6 +45,10 @@ impl PoolError {
             PoolError::ProtocolFeeCapTooLow(hash, _) => hash,
             PoolError::SpammerExceededCapacity(_, hash) => hash,
             PoolError::DiscardedOnInsert(hash) => hash,
+            PoolError::Disabled(hash) => hash,
+            PoolError::InvalidChainId(hash) => hash,
+            PoolError::NonceTooLow(hash, _) => hash,
+            PoolError::StateUnavailable(hash, _) => hash,
         }
     }
 }
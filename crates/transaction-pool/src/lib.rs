@@ -81,18 +81,20 @@
 
 pub use crate::{
     config::PoolConfig,
-    ordering::TransactionOrdering,
+    ordering::{GasPriceOrdering, TransactionOrdering},
     traits::{
         BestTransactions, OnNewBlockEvent, PoolTransaction, PropagateKind, PropagatedTransactions,
-        TransactionOrigin, TransactionPool,
+        StateDiff, TransactionOrigin, TransactionPool,
+    },
+    validate::{
+        EthTransactionValidator, TransactionValidationOutcome, TransactionValidator,
+        ValidPoolTransaction,
     },
-    validate::{TransactionValidationOutcome, TransactionValidator},
 };
 use crate::{
     error::PoolResult,
     pool::PoolInner,
     traits::{NewTransactionEvent, PoolSize},
-    validate::ValidPoolTransaction,
 };
 use reth_primitives::{TxHash, U256};
 use std::{collections::HashMap, sync::Arc};
@@ -102,6 +104,7 @@ mod config;
 pub mod error;
 mod identifier;
 pub mod metrics;
+pub mod noop;
 mod ordering;
 pub mod pool;
 mod traits;
@@ -0,0 +1,98 @@
+//! A transaction pool implementation that does nothing.
+//!
+//! This is useful for wiring components that require a pool, e.g. block production, when the node
+//! doesn't accept any transactions.
+
+use crate::{
+    error::{PoolError, PoolResult},
+    traits::{NewTransactionEvent, PoolSize},
+    validate::ValidPoolTransaction,
+    BestTransactions, OnNewBlockEvent, PoolTransaction, PropagatedTransactions, TransactionOrigin,
+    TransactionPool,
+};
+use reth_primitives::{TransactionSignedEcRecovered, TxHash};
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, Receiver};
+
+/// A [`TransactionPool`] that is always empty and rejects all transactions.
+///
+/// The listeners it hands out are closed right away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopTransactionPool;
+
+#[async_trait::async_trait]
+impl TransactionPool for NoopTransactionPool {
+    type Transaction = TransactionSignedEcRecovered;
+
+    fn status(&self) -> PoolSize {
+        PoolSize {
+            pending: 0,
+            pending_size: 0,
+            basefee: 0,
+            basefee_size: 0,
+            queued: 0,
+            queued_size: 0,
+        }
+    }
+
+    fn on_new_block(&self, _event: OnNewBlockEvent) {}
+
+    async fn add_transaction(
+        &self,
+        _origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> PoolResult<TxHash> {
+        Err(PoolError::Disabled(*transaction.hash()))
+    }
+
+    async fn add_transactions(
+        &self,
+        _origin: TransactionOrigin,
+        transactions: Vec<Self::Transaction>,
+    ) -> PoolResult<Vec<PoolResult<TxHash>>> {
+        Ok(transactions
+            .iter()
+            .map(|transaction| Err(PoolError::Disabled(*transaction.hash())))
+            .collect())
+    }
+
+    fn pending_transactions_listener(&self) -> Receiver<TxHash> {
+        mpsc::channel(1).1
+    }
+
+    fn transactions_listener(&self) -> Receiver<NewTransactionEvent<Self::Transaction>> {
+        mpsc::channel(1).1
+    }
+
+    fn pooled_transactions(&self) -> Vec<TxHash> {
+        Vec::new()
+    }
+
+    fn best_transactions(
+        &self,
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>> {
+        Box::new(std::iter::empty())
+    }
+
+    fn remove_invalid(
+        &self,
+        _hashes: impl IntoIterator<Item = TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        Vec::new()
+    }
+
+    fn retain_unknown(&self, _hashes: &mut Vec<TxHash>) {}
+
+    fn get(&self, _tx_hash: &TxHash) -> Option<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        None
+    }
+
+    fn get_all(
+        &self,
+        _txs: impl IntoIterator<Item = TxHash>,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        Vec::new()
+    }
+
+    fn on_propagated(&self, _txs: PropagatedTransactions) {}
+}
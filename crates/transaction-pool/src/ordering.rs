@@ -1,5 +1,6 @@
 use crate::traits::PoolTransaction;
-use std::fmt;
+use reth_primitives::U256;
+use std::{fmt, marker::PhantomData};
 
 /// Transaction ordering trait to determine the order of transactions.
 ///
@@ -18,3 +19,24 @@ pub trait TransactionOrdering: Send + Sync + 'static {
     /// Returns the priority score for the given transaction.
     fn priority(&self, transaction: &Self::Transaction) -> Self::Priority;
 }
+
+/// Orders the transactions by the gas price they pay at most, see
+/// [PoolTransaction::effective_gas_price].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct GasPriceOrdering<T>(PhantomData<T>);
+
+impl<T> Default for GasPriceOrdering<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: PoolTransaction + 'static> TransactionOrdering for GasPriceOrdering<T> {
+    type Priority = U256;
+    type Transaction = T;
+
+    fn priority(&self, transaction: &Self::Transaction) -> Self::Priority {
+        transaction.effective_gas_price()
+    }
+}
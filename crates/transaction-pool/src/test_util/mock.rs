@@ -12,8 +12,8 @@ use rand::{
     prelude::Distribution,
 };
 use reth_primitives::{
-    Address, FromRecoveredTransaction, Transaction, TransactionSignedEcRecovered, TxEip1559,
-    TxHash, TxLegacy, H256, U256,
+    Address, FromRecoveredTransaction, IntoRecoveredTransaction, Signature, Transaction,
+    TransactionSigned, TransactionSignedEcRecovered, TxEip1559, TxHash, TxLegacy, H256, U256,
 };
 use std::{ops::Range, sync::Arc, time::Instant};
 
@@ -377,13 +377,48 @@ impl FromRecoveredTransaction for MockTransaction {
                 gas_limit,
                 value: value.into(),
             },
-            Transaction::Eip2930 { .. } => {
+            Transaction::Eip2930 { .. } | Transaction::Eip4844 { .. } => {
                 unimplemented!()
             }
         }
     }
 }
 
+impl IntoRecoveredTransaction for MockTransaction {
+    fn to_recovered_transaction(&self) -> TransactionSignedEcRecovered {
+        let transaction = match self {
+            MockTransaction::Legacy { nonce, gas_price, gas_limit, value, .. } => {
+                Transaction::Legacy(TxLegacy {
+                    nonce: *nonce,
+                    gas_price: gas_price.as_u128(),
+                    gas_limit: *gas_limit,
+                    value: value.as_u128(),
+                    ..Default::default()
+                })
+            }
+            MockTransaction::Eip1559 {
+                nonce,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                gas_limit,
+                value,
+                ..
+            } => Transaction::Eip1559(TxEip1559 {
+                nonce: *nonce,
+                max_fee_per_gas: max_fee_per_gas.as_u128(),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.as_u128(),
+                gas_limit: *gas_limit,
+                value: value.as_u128(),
+                ..Default::default()
+            }),
+        };
+        // the mock is not signed, so the hash and the sender are kept as they are
+        let signed =
+            TransactionSigned { hash: *self.hash(), signature: Signature::default(), transaction };
+        TransactionSignedEcRecovered::from_signed_transaction(signed, self.sender())
+    }
+}
+
 #[derive(Default)]
 pub struct MockTransactionFactory {
     pub(crate) ids: SenderIdentifiers,
@@ -1,5 +1,8 @@
 use crate::{error::PoolResult, pool::state::SubPool, validate::ValidPoolTransaction};
-use reth_primitives::{Address, FromRecoveredTransaction, PeerId, TxHash, H256, U256};
+use reth_primitives::{
+    Address, FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, Transaction,
+    TransactionSignedEcRecovered, TxHash, H256, U256,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc};
 use tokio::sync::mpsc::Receiver;
@@ -235,7 +238,9 @@ impl<T> BestTransactions for std::iter::Empty<T> {
 }
 
 /// Trait for transaction types used inside the pool
-pub trait PoolTransaction: fmt::Debug + Send + Sync + FromRecoveredTransaction {
+pub trait PoolTransaction:
+    fmt::Debug + Send + Sync + FromRecoveredTransaction + IntoRecoveredTransaction
+{
     /// Hash of the transaction.
     fn hash(&self) -> &TxHash;
 
@@ -272,6 +277,54 @@ pub trait PoolTransaction: fmt::Debug + Send + Sync + FromRecoveredTransaction {
     fn size(&self) -> usize;
 }
 
+impl PoolTransaction for TransactionSignedEcRecovered {
+    fn hash(&self) -> &TxHash {
+        &self.hash
+    }
+
+    fn sender(&self) -> Address {
+        self.signer()
+    }
+
+    fn nonce(&self) -> u64 {
+        self.transaction.nonce()
+    }
+
+    fn cost(&self) -> U256 {
+        U256::from(self.transaction.gas_limit()) * U256::from(self.transaction.max_fee_per_gas()) +
+            U256::from(*self.transaction.value())
+    }
+
+    fn effective_gas_price(&self) -> U256 {
+        // the base fee is not known here, so this is the upper bound for EIP-1559 transactions
+        U256::from(self.transaction.max_fee_per_gas())
+    }
+
+    fn gas_limit(&self) -> u64 {
+        self.transaction.gas_limit()
+    }
+
+    fn max_fee_per_gas(&self) -> Option<U256> {
+        match &self.transaction {
+            Transaction::Eip1559(tx) => Some(U256::from(tx.max_fee_per_gas)),
+            Transaction::Eip4844(tx) => Some(U256::from(tx.max_fee_per_gas)),
+            Transaction::Legacy(_) | Transaction::Eip2930(_) => None,
+        }
+    }
+
+    fn max_priority_fee_per_gas(&self) -> Option<U256> {
+        match &self.transaction {
+            Transaction::Eip1559(tx) => Some(U256::from(tx.max_priority_fee_per_gas)),
+            Transaction::Eip4844(tx) => Some(U256::from(tx.max_priority_fee_per_gas)),
+            Transaction::Legacy(_) | Transaction::Eip2930(_) => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.transaction.input().len()
+    }
+}
+
 /// Represents the current status of the pool.
 #[derive(Debug, Clone)]
 pub struct PoolSize {
@@ -5,8 +5,12 @@ use crate::{
     identifier::{SenderId, TransactionId},
     traits::{PoolTransaction, TransactionOrigin},
 };
-use reth_primitives::{rpc::Address, TxHash, U256};
-use std::{fmt, time::Instant};
+use reth_primitives::{
+    rpc::Address, ChainSpec, Transaction, TransactionSignedEcRecovered, TxEip1559, TxEip2930,
+    TxEip4844, TxHash, TxLegacy, U256,
+};
+use reth_provider::{AccountProvider, StateProviderFactory};
+use std::{fmt, sync::Arc, time::Instant};
 
 /// A Result type returned after checking a transaction's validity.
 #[derive(Debug)]
@@ -56,6 +60,81 @@ pub trait TransactionValidator: Send + Sync + 'static {
     ) -> TransactionValidationOutcome<Self::Transaction>;
 }
 
+/// Validates transactions against the latest state of the chain.
+///
+/// A transaction is rejected if it is signed for another chain or if its nonce was already used by
+/// the sender. The ordering of the nonces and the balance of the sender are checked by the pool.
+#[derive(Debug)]
+pub struct EthTransactionValidator<Client> {
+    /// Client to read the latest state.
+    client: Arc<Client>,
+    /// The chain the transactions have to be signed for.
+    chain_spec: ChainSpec,
+}
+
+impl<Client> EthTransactionValidator<Client> {
+    /// Creates a new validator of the transactions of the given chain.
+    pub fn new(client: Arc<Client>, chain_spec: ChainSpec) -> Self {
+        Self { client, chain_spec }
+    }
+}
+
+impl<Client: StateProviderFactory> EthTransactionValidator<Client> {
+    /// Returns the balance and the nonce of the sender in the latest state.
+    fn sender_state(&self, sender: Address) -> reth_interfaces::Result<(U256, u64)> {
+        let account = self.client.latest()?.basic_account(sender)?.unwrap_or_default();
+        Ok((account.balance, account.nonce))
+    }
+}
+
+#[async_trait::async_trait]
+impl<Client: StateProviderFactory + 'static> TransactionValidator
+    for EthTransactionValidator<Client>
+{
+    type Transaction = TransactionSignedEcRecovered;
+
+    async fn validate_transaction(
+        &self,
+        _origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let chain_id = match &transaction.transaction {
+            Transaction::Legacy(TxLegacy { chain_id, .. }) => *chain_id,
+            Transaction::Eip2930(TxEip2930 { chain_id, .. }) |
+            Transaction::Eip1559(TxEip1559 { chain_id, .. }) |
+            Transaction::Eip4844(TxEip4844 { chain_id, .. }) => Some(*chain_id),
+        };
+        // legacy transactions without a chain id are valid on every chain
+        if chain_id.map_or(false, |id| id != self.chain_spec.chain_id()) {
+            let hash = *transaction.hash();
+            return TransactionValidationOutcome::Invalid(
+                transaction,
+                PoolError::InvalidChainId(hash),
+            )
+        }
+
+        let (balance, state_nonce) = match self.sender_state(transaction.sender()) {
+            Ok(state) => state,
+            Err(err) => {
+                let hash = *transaction.hash();
+                return TransactionValidationOutcome::Invalid(
+                    transaction,
+                    PoolError::StateUnavailable(hash, err.to_string()),
+                )
+            }
+        };
+        if transaction.nonce() < state_nonce {
+            let hash = *transaction.hash();
+            return TransactionValidationOutcome::Invalid(
+                transaction,
+                PoolError::NonceTooLow(hash, state_nonce),
+            )
+        }
+
+        TransactionValidationOutcome::Valid { balance, state_nonce, transaction }
+    }
+}
+
 /// A valida transaction in the pool.
 pub struct ValidPoolTransaction<T: PoolTransaction> {
     /// The transaction
@@ -0,0 +1,59 @@
+use crate::Nibbles;
+use reth_db::{cursor::DbCursorRO, models::StorageTrieKey, tables, Error};
+use reth_primitives::{BranchNodeCompact, H256};
+
+/// A cursor over the stored branch nodes of a trie, sorted by their paths.
+pub(crate) trait TrieCursor {
+    /// Returns the node with the given path.
+    fn seek_exact(&mut self, path: Nibbles) -> Result<Option<(Nibbles, BranchNodeCompact)>, Error>;
+
+    /// Returns the first node with a path that is equal to or greater than the given path.
+    fn seek(&mut self, path: Nibbles) -> Result<Option<(Nibbles, BranchNodeCompact)>, Error>;
+}
+
+/// A cursor over the nodes of the state trie in the [AccountsTrie](tables::AccountsTrie) table.
+#[derive(Debug)]
+pub(crate) struct AccountTrieCursor<C>(pub(crate) C);
+
+impl<'tx, C: DbCursorRO<'tx, tables::AccountsTrie>> TrieCursor for AccountTrieCursor<C> {
+    fn seek_exact(&mut self, path: Nibbles) -> Result<Option<(Nibbles, BranchNodeCompact)>, Error> {
+        let entry = self.0.seek_exact(path.into_inner())?;
+        Ok(entry.map(|(path, node)| (Nibbles::from_hex(path), node)))
+    }
+
+    fn seek(&mut self, path: Nibbles) -> Result<Option<(Nibbles, BranchNodeCompact)>, Error> {
+        let entry = self.0.walk(path.into_inner())?.start.transpose()?;
+        Ok(entry.map(|(path, node)| (Nibbles::from_hex(path), node)))
+    }
+}
+
+/// A cursor over the nodes of the storage trie of a single account in the
+/// [StoragesTrie](tables::StoragesTrie) table.
+#[derive(Debug)]
+pub(crate) struct StorageTrieCursor<'a, C> {
+    cursor: &'a mut C,
+    hashed_address: H256,
+}
+
+impl<'a, C> StorageTrieCursor<'a, C> {
+    /// Creates a cursor over the storage trie of the account with the given hashed address.
+    pub(crate) fn new(cursor: &'a mut C, hashed_address: H256) -> Self {
+        Self { cursor, hashed_address }
+    }
+}
+
+impl<'a, 'tx, C: DbCursorRO<'tx, tables::StoragesTrie>> TrieCursor for StorageTrieCursor<'a, C> {
+    fn seek_exact(&mut self, path: Nibbles) -> Result<Option<(Nibbles, BranchNodeCompact)>, Error> {
+        let key = StorageTrieKey((self.hashed_address, path.into_inner()));
+        Ok(self.cursor.seek_exact(key)?.map(|(key, node)| (Nibbles::from_hex(key.0 .1), node)))
+    }
+
+    fn seek(&mut self, path: Nibbles) -> Result<Option<(Nibbles, BranchNodeCompact)>, Error> {
+        let key = StorageTrieKey((self.hashed_address, path.into_inner()));
+        let entry = self.cursor.walk(key)?.start.transpose()?;
+        // the nodes of the following account are not part of the trie
+        Ok(entry
+            .filter(|(key, _)| key.hashed_address() == self.hashed_address)
+            .map(|(key, node)| (Nibbles::from_hex(key.0 .1), node)))
+    }
+}
@@ -0,0 +1,438 @@
+use crate::{nibbles::encode_path, Nibbles, PrefixSet};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, BranchNodeCompact, H256};
+use reth_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use std::collections::BTreeMap;
+
+/// The value of the last added entry of the [HashBuilder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HashBuilderValue {
+    /// The RLP encoded value of a leaf.
+    Bytes(Vec<u8>),
+    /// The hash of a branch node whose subtree is not walked.
+    Hash(H256),
+}
+
+impl Default for HashBuilderValue {
+    fn default() -> Self {
+        Self::Bytes(Vec::new())
+    }
+}
+
+/// A streaming builder of the root hash of a Merkle Patricia Trie.
+///
+/// The leaves have to be added in the ascending order of their keys, and no key may be the prefix
+/// of another key, which holds for the fixed-length hashed keys of the state trie. Only the nodes
+/// along the path of the last added leaf are kept: whenever a new leaf is added, the nodes of the
+/// previous path that cannot change anymore are collapsed into their RLP encoding or hash.
+///
+/// Unchanged subtrees don't need to be walked, their hashes can be added as branches instead of
+/// their leaves. The builder can also collect the branch nodes that have to be stored, see
+/// [BranchNodeCompact], and the nodes on the paths to some keys, which prove their values.
+#[derive(Debug, Default)]
+pub struct HashBuilder {
+    /// The key of the last added leaf or branch.
+    key: Nibbles,
+    /// The value of the last added leaf or branch.
+    value: HashBuilderValue,
+    /// Whether the last added branch is stored in the database.
+    stored_in_database: bool,
+    /// The references of the collapsed nodes that are not part of a parent node yet.
+    stack: Vec<Vec<u8>>,
+    /// For each depth of the current path, the mask of the children of the branch node.
+    groups: Vec<u16>,
+    /// For each depth of the current path, the mask of the children that are stored branch nodes.
+    tree_masks: Vec<u16>,
+    /// For each depth of the current path, the mask of the children that are hashed branch nodes.
+    hash_masks: Vec<u16>,
+    /// The branch nodes that have to be stored, if they are collected.
+    updated_branch_nodes: Option<BTreeMap<Nibbles, BranchNodeCompact>>,
+    /// The keys whose proofs are collected.
+    proof_targets: Option<PrefixSet>,
+    /// The RLP encoded nodes on the paths to the proof targets, keyed by their paths.
+    proof_nodes: BTreeMap<Nibbles, Vec<u8>>,
+}
+
+impl HashBuilder {
+    /// Enables the collection of the branch nodes that have to be stored.
+    pub fn with_updates(mut self) -> Self {
+        self.updated_branch_nodes = Some(BTreeMap::new());
+        self
+    }
+
+    /// Returns the collected branch nodes, keyed by their paths.
+    pub fn take_updates(&mut self) -> BTreeMap<Nibbles, BranchNodeCompact> {
+        self.updated_branch_nodes.take().unwrap_or_default()
+    }
+
+    /// Enables the collection of the nodes on the paths to the given keys.
+    ///
+    /// The nodes on these paths must not be added as branches, their subtrees have to be walked.
+    pub fn with_proof_retainer(mut self, targets: PrefixSet) -> Self {
+        self.proof_targets = Some(targets);
+        self
+    }
+
+    /// Returns the collected proof nodes, keyed by their paths.
+    ///
+    /// The proof of a key consists of the nodes whose paths are prefixes of the key, ordered by
+    /// their paths from the root.
+    pub fn take_proofs(&mut self) -> BTreeMap<Nibbles, Vec<u8>> {
+        std::mem::take(&mut self.proof_nodes)
+    }
+
+    /// Adds a leaf to the trie.
+    ///
+    /// The key must be greater than the key of the previously added leaf or branch.
+    pub fn add_leaf(&mut self, key: Nibbles, value: &[u8]) {
+        debug_assert!(self.key < key, "leaves must be added in ascending order");
+        if !self.key.is_empty() {
+            self.update(&key);
+        }
+        self.key = key;
+        self.value = HashBuilderValue::Bytes(value.to_vec());
+    }
+
+    /// Adds the hash of a branch node whose subtree is unchanged, instead of the leaves of the
+    /// subtree.
+    ///
+    /// The key is the path of the branch node, and an empty key adds the root of the trie.
+    /// `stored_in_database` tells whether the branch node itself is stored in the database.
+    pub fn add_branch(&mut self, key: Nibbles, hash: H256, stored_in_database: bool) {
+        debug_assert!(
+            self.key < key || (self.key.is_empty() && key.is_empty()),
+            "branches must be added in ascending order"
+        );
+        if !self.key.is_empty() {
+            self.update(&key);
+        } else if key.is_empty() {
+            self.stack.push(rlp_hash(hash));
+        }
+        self.key = key;
+        self.value = HashBuilderValue::Hash(hash);
+        self.stored_in_database = stored_in_database;
+    }
+
+    /// Returns the root hash of the trie, or the [EMPTY_ROOT] if no leaves were added.
+    ///
+    /// This collapses the remaining nodes, so no more leaves can be added afterwards.
+    pub fn root(&mut self) -> H256 {
+        if !self.key.is_empty() {
+            self.update(&Nibbles::default());
+            self.key = Nibbles::default();
+            self.value = HashBuilderValue::default();
+        }
+        self.current_root()
+    }
+
+    /// Returns the hash of the node on top of the stack.
+    fn current_root(&self) -> H256 {
+        match self.stack.last() {
+            // a hash reference, `0xa0 || hash`
+            Some(node) if node.len() == 33 => H256::from_slice(&node[1..]),
+            // the root is always hashed, even if its encoding is shorter than a hash
+            Some(node) => keccak256(node),
+            None => EMPTY_ROOT,
+        }
+    }
+
+    /// Collapses the nodes of the path of the last added leaf that are not shared with the
+    /// succeeding key.
+    ///
+    /// An empty succeeding key collapses the whole path.
+    fn update(&mut self, succeeding: &[u8]) {
+        let mut build_extensions = false;
+        let mut current = self.key.clone();
+
+        loop {
+            let preceding_exists = !self.groups.is_empty();
+            let preceding_len = self.groups.len().saturating_sub(1);
+
+            let common_prefix_len = current.common_prefix_length(succeeding);
+            let len = preceding_len.max(common_prefix_len);
+            assert!(len < current.len(), "a key must not be the prefix of another key");
+
+            // the current node is a child of the branch node at `len`
+            let extra_digit = current[len];
+            if self.groups.len() <= len {
+                self.groups.resize(len + 1, 0);
+            }
+            self.groups[len] |= 1 << extra_digit;
+            if self.tree_masks.len() < current.len() {
+                self.resize_masks(current.len());
+            }
+
+            let mut len_from = len;
+            if !succeeding.is_empty() || preceding_exists {
+                len_from += 1;
+            }
+
+            // the path of the node without the nibbles of its parent branch node
+            let short_node_key = &current[len_from..];
+
+            if !build_extensions {
+                match &self.value {
+                    HashBuilderValue::Bytes(value) => {
+                        let leaf = leaf_node_rlp(short_node_key, value);
+                        self.retain_proof(&current[..len_from], &leaf);
+                        self.stack.push(rlp_node(leaf));
+                    }
+                    HashBuilderValue::Hash(hash) => {
+                        self.stack.push(rlp_hash(*hash));
+
+                        // the added branch is a hashed child of the branch node above it
+                        let depth = current.len() - 1;
+                        let flag = 1u16 << current[depth];
+                        if self.stored_in_database {
+                            self.tree_masks[depth] |= flag;
+                        }
+                        self.hash_masks[depth] |= flag;
+
+                        build_extensions = true;
+                    }
+                }
+            }
+
+            if build_extensions && !short_node_key.is_empty() {
+                self.update_masks(&current, len_from);
+                let child = self.stack.pop().expect("extension node has a child");
+                let extension = extension_node_rlp(short_node_key, &child);
+                self.retain_proof(&current[..len_from], &extension);
+                self.stack.push(rlp_node(extension));
+                self.resize_masks(len_from);
+            }
+
+            // the branch node is shared with the succeeding key
+            if preceding_len <= common_prefix_len && !succeeding.is_empty() {
+                return
+            }
+
+            if !succeeding.is_empty() || preceding_exists {
+                self.push_branch_node(&current, len);
+            }
+
+            self.groups.truncate(len);
+            self.resize_masks(len);
+
+            if preceding_len == 0 {
+                return
+            }
+
+            current.truncate(preceding_len);
+            while self.groups.last() == Some(&0) {
+                self.groups.pop();
+            }
+
+            build_extensions = true;
+        }
+    }
+
+    /// Moves the masks of a branch node that becomes the child of an extension node to the
+    /// parent of the extension node. The extension node itself is not hashed as a branch node.
+    fn update_masks(&mut self, current: &[u8], len_from: usize) {
+        if len_from > 0 {
+            let flag = 1u16 << current[len_from - 1];
+            self.hash_masks[len_from - 1] &= !flag;
+            if self.tree_masks[current.len() - 1] != 0 {
+                self.tree_masks[len_from - 1] |= flag;
+            }
+        }
+    }
+
+    /// Replaces the children of the branch node at the given depth on the stack with the branch
+    /// node, and collects the branch node if it has to be stored.
+    ///
+    /// A branch node is stored if any of its children is a stored or a hashed branch node.
+    fn push_branch_node(&mut self, current: &[u8], len: usize) {
+        let state_mask = self.groups[len];
+        let hash_mask = self.hash_masks[len];
+        let first_child = self.stack.len() - state_mask.count_ones() as usize;
+        let children = &self.stack[first_child..];
+        let hashes = (0..16u8)
+            .filter(|nibble| state_mask & (1 << nibble) != 0)
+            .zip(children)
+            .filter(|(nibble, _)| hash_mask & (1 << nibble) != 0)
+            .map(|(_, child)| H256::from_slice(&child[1..]))
+            .collect();
+        let branch = branch_node_rlp(children, state_mask);
+        self.retain_proof(&current[..len], &branch);
+        self.stack.truncate(first_child);
+        self.stack.push(rlp_node(branch));
+
+        if len > 0 && self.stack.last().map_or(false, |node| node.len() == 33) {
+            self.hash_masks[len - 1] |= 1 << current[len - 1];
+        }
+
+        if self.tree_masks[len] != 0 || self.hash_masks[len] != 0 {
+            if len > 0 {
+                self.tree_masks[len - 1] |= 1 << current[len - 1];
+            }
+
+            // only the root node keeps its own hash
+            let root_hash = (len == 0).then(|| self.current_root());
+            if let Some(nodes) = &mut self.updated_branch_nodes {
+                let node = BranchNodeCompact::new(
+                    state_mask,
+                    self.tree_masks[len],
+                    hash_mask,
+                    hashes,
+                    root_hash,
+                );
+                nodes.insert(Nibbles::from_hex(current[..len].to_vec()), node);
+            }
+        }
+    }
+
+    /// Collects the encoded node at the given path if it is on the path to a proof target.
+    fn retain_proof(&mut self, path: &[u8], rlp: &[u8]) {
+        if self.proof_targets.as_ref().map_or(false, |targets| targets.contains(path)) {
+            self.proof_nodes.insert(Nibbles::from_hex(path.to_vec()), rlp.to_vec());
+        }
+    }
+
+    /// Resizes the tree and hash masks to the given depth.
+    fn resize_masks(&mut self, len: usize) {
+        self.tree_masks.resize(len, 0);
+        self.hash_masks.resize(len, 0);
+    }
+}
+
+/// Returns the reference to a hashed node, the encoding of the hash.
+fn rlp_hash(hash: H256) -> Vec<u8> {
+    let mut out = Vec::with_capacity(33);
+    hash.as_bytes().encode(&mut out);
+    out
+}
+
+/// Returns the reference to a node: its encoding if it is shorter than 32 bytes, the encoding of
+/// its hash otherwise.
+fn rlp_node(rlp: Vec<u8>) -> Vec<u8> {
+    if rlp.len() < 32 {
+        rlp
+    } else {
+        rlp_hash(keccak256(&rlp))
+    }
+}
+
+/// Encodes a leaf node, `[encoded path, value]`.
+fn leaf_node_rlp(path: &[u8], value: &[u8]) -> Vec<u8> {
+    let path = encode_path(path, true);
+    let mut out = Vec::new();
+    Header { list: true, payload_length: path.as_slice().length() + value.length() }
+        .encode(&mut out);
+    path.as_slice().encode(&mut out);
+    value.encode(&mut out);
+    out
+}
+
+/// Encodes an extension node, `[encoded path, child reference]`.
+fn extension_node_rlp(path: &[u8], child: &[u8]) -> Vec<u8> {
+    let path = encode_path(path, false);
+    let mut out = Vec::new();
+    Header { list: true, payload_length: path.as_slice().length() + child.len() }.encode(&mut out);
+    path.as_slice().encode(&mut out);
+    out.extend_from_slice(child);
+    out
+}
+
+/// Encodes a branch node without a value, `[child reference or empty string; 16, empty string]`.
+///
+/// The references of the children are given in the order of the set bits of the state mask.
+fn branch_node_rlp(children: &[Vec<u8>], state_mask: u16) -> Vec<u8> {
+    let children_len: usize = children.iter().map(Vec::len).sum();
+    let empty_children = 16 - children.len();
+    let mut out = Vec::new();
+    Header { list: true, payload_length: children_len + empty_children + 1 }.encode(&mut out);
+    let mut children = children.iter();
+    for nibble in 0..16 {
+        if state_mask & (1 << nibble) != 0 {
+            out.extend_from_slice(children.next().expect("child for every set bit"));
+        } else {
+            out.push(EMPTY_STRING_CODE);
+        }
+    }
+    out.push(EMPTY_STRING_CODE);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use reth_primitives::{proofs::calculate_storage_root, U256};
+
+    fn storage_root_with_builder(storage: &BTreeMap<H256, U256>) -> H256 {
+        add_storage(&mut HashBuilder::default(), storage)
+    }
+
+    fn add_storage(hb: &mut HashBuilder, storage: &BTreeMap<H256, U256>) -> H256 {
+        for (key, value) in storage {
+            let mut value_rlp = Vec::new();
+            value.encode(&mut value_rlp);
+            hb.add_leaf(Nibbles::unpack(key), &value_rlp);
+        }
+        hb.root()
+    }
+
+    #[test]
+    fn empty_root() {
+        assert_eq!(HashBuilder::default().root(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn matches_in_memory_trie() {
+        let mut rng = rand::thread_rng();
+        for size in [1, 2, 3, 16, 17, 100, 1000] {
+            let storage: BTreeMap<H256, U256> = (0..size)
+                .map(|_| (H256::random(), U256::from(rng.gen_range(1..u64::MAX))))
+                .collect();
+            assert_eq!(
+                storage_root_with_builder(&storage),
+                calculate_storage_root(storage.clone()),
+                "root of {size} leaves"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_in_memory_trie_with_shared_prefixes() {
+        // keys that share long prefixes produce extension nodes and nested branches
+        let storage: BTreeMap<H256, U256> = [
+            H256::from_low_u64_be(0x1),
+            H256::from_low_u64_be(0x2),
+            H256::from_low_u64_be(0x1234),
+            H256::from_low_u64_be(0x1235),
+            H256::repeat_byte(0xaa),
+            H256::repeat_byte(0xab),
+        ]
+        .into_iter()
+        .zip(1u64..)
+        .map(|(key, value)| (key, U256::from(value)))
+        .collect();
+        assert_eq!(storage_root_with_builder(&storage), calculate_storage_root(storage.clone()));
+    }
+
+    #[test]
+    fn stored_branch_nodes() {
+        let mut rng = rand::thread_rng();
+        let storage: BTreeMap<H256, U256> =
+            (0..1000).map(|_| (H256::random(), U256::from(rng.gen_range(1..u64::MAX)))).collect();
+
+        let mut hb = HashBuilder::default().with_updates();
+        let root = add_storage(&mut hb, &storage);
+        let updates = hb.take_updates();
+
+        // the children of the root are branch nodes, so the root keeps its hash
+        let root_node = &updates[&Nibbles::default()];
+        assert_eq!(root_node.state_mask, 0xffff);
+        assert_eq!(root_node.root_hash, Some(root));
+        for (path, node) in &updates {
+            assert_eq!(node.hashes.len(), node.hash_mask.count_ones() as usize);
+            assert_eq!(node.root_hash.is_some(), path.is_empty());
+        }
+
+        // an unchanged trie is added by the hash of its root alone
+        let mut hb = HashBuilder::default();
+        hb.add_branch(Nibbles::default(), root, true);
+        assert_eq!(hb.root(), root);
+    }
+}
@@ -0,0 +1,35 @@
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Merkle Patricia Trie implementation.
+//!
+//! The state root is computed over the hashed state, i.e. the
+//! [HashedAccount](reth_db::tables::HashedAccount) and
+//! [HashedStorage](reth_db::tables::HashedStorage) tables. Both tables are sorted by their hashed
+//! keys, so the trie can be built with a [HashBuilder] that consumes the leaves in order and only
+//! keeps the nodes of the current path in memory.
+//!
+//! The branch nodes of the tries are stored in the [AccountsTrie](reth_db::tables::AccountsTrie)
+//! and [StoragesTrie](reth_db::tables::StoragesTrie) tables. Given the changed keys, the state
+//! root is updated incrementally: unchanged subtrees are skipped and their stored hashes are used.
+//! The same walk along the paths to some keys generates their Merkle [Proof]s.
+
+mod cursor;
+mod hash_builder;
+mod nibbles;
+mod prefix_set;
+mod proof;
+mod state_root;
+mod updates;
+mod walker;
+
+pub use hash_builder::HashBuilder;
+pub use nibbles::Nibbles;
+pub use prefix_set::PrefixSet;
+pub use proof::{AccountProof, Proof, StorageProof};
+pub use state_root::{storage_root, StateRoot};
+pub use updates::TrieUpdates;
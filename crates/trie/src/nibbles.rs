@@ -0,0 +1,133 @@
+use std::{borrow::Borrow, ops::Deref};
+
+/// The path of a node in the trie, stored as one nibble per byte.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nibbles(Vec<u8>);
+
+impl Nibbles {
+    /// Unpacks the bytes of a key into its nibbles, the high nibble of each byte first.
+    pub fn unpack(bytes: impl AsRef<[u8]>) -> Self {
+        Self(bytes.as_ref().iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect())
+    }
+
+    /// Creates the path from nibbles that are already unpacked.
+    ///
+    /// Every element must be smaller than 16.
+    pub fn from_hex(hex: Vec<u8>) -> Self {
+        debug_assert!(hex.iter().all(|nibble| *nibble < 16));
+        Self(hex)
+    }
+
+    /// Returns the length of the common prefix of the path and the given nibbles.
+    pub fn common_prefix_length(&self, other: &[u8]) -> usize {
+        self.0.iter().zip(other).take_while(|(a, b)| a == b).count()
+    }
+
+    /// Shortens the path to the first `len` nibbles.
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len)
+    }
+
+    /// Appends a nibble to the path.
+    pub fn push(&mut self, nibble: u8) {
+        debug_assert!(nibble < 16);
+        self.0.push(nibble)
+    }
+
+    /// Returns the smallest path that is greater than the path and all the paths it prefixes, or
+    /// `None` if the path consists of `0xf` nibbles only.
+    pub fn increment(&self) -> Option<Self> {
+        let mut incremented = self.0.clone();
+        while let Some(nibble) = incremented.pop() {
+            if nibble < 0xf {
+                incremented.push(nibble + 1);
+                return Some(Self(incremented))
+            }
+        }
+        None
+    }
+
+    /// Packs the nibbles into bytes, the last byte is padded with a zero nibble if the path has
+    /// an odd length.
+    pub fn pack(&self) -> Vec<u8> {
+        self.0
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or_default())
+            .collect()
+    }
+
+    /// Returns the nibbles, one per byte.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Nibbles {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<[u8]> for Nibbles {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Encodes the path of a leaf or extension node with the hex-prefix encoding.
+///
+/// The first nibble is a flag for the node type and the parity of the path. An odd path is packed
+/// with its first nibble into the first byte, an even path is preceded by a zero nibble.
+pub(crate) fn encode_path(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | path[0]);
+        &path[1..]
+    } else {
+        encoded.push(flag);
+        path
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_and_prefix() {
+        let nibbles = Nibbles::unpack([0x12, 0xab]);
+        assert_eq!(&nibbles[..], &[0x1, 0x2, 0xa, 0xb]);
+        assert_eq!(nibbles.common_prefix_length(&[0x1, 0x2, 0xb]), 2);
+        assert_eq!(nibbles.common_prefix_length(&[]), 0);
+    }
+
+    #[test]
+    fn increment_and_pack() {
+        let nibbles = Nibbles::from_hex(vec![0x1, 0xf, 0xf]);
+        assert_eq!(nibbles.increment(), Some(Nibbles::from_hex(vec![0x2])));
+        assert_eq!(nibbles.pack(), vec![0x1f, 0xf0]);
+        assert_eq!(Nibbles::from_hex(vec![0xf, 0xf]).increment(), None);
+        assert_eq!(Nibbles::unpack([0x12, 0xab]).pack(), vec![0x12, 0xab]);
+    }
+
+    #[test]
+    fn hex_prefix_encoding() {
+        // examples from the appendix of the yellow paper
+        assert_eq!(encode_path(&[0x1, 0x2, 0x3, 0x4, 0x5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(
+            encode_path(&[0x0, 0x1, 0x2, 0x3, 0x4, 0x5], false),
+            vec![0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            encode_path(&[0x0, 0xf, 0x1, 0xc, 0xb, 0x8], true),
+            vec![0x20, 0x0f, 0x1c, 0xb8]
+        );
+        assert_eq!(encode_path(&[0xf, 0x1, 0xc, 0xb, 0x8], true), vec![0x3f, 0x1c, 0xb8]);
+        assert_eq!(encode_path(&[], true), vec![0x20]);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::Nibbles;
+use std::{collections::BTreeSet, ops::Bound};
+
+/// The set of the changed keys of a trie.
+///
+/// A stored node of the trie has to be recomputed if any changed key starts with its path,
+/// otherwise its stored hash is still valid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixSet {
+    keys: BTreeSet<Nibbles>,
+    /// Whether every key is changed, i.e. the trie is computed from scratch.
+    all: bool,
+}
+
+impl PrefixSet {
+    /// Returns the set that contains every prefix, so no stored node is used.
+    pub fn all() -> Self {
+        Self { keys: BTreeSet::new(), all: true }
+    }
+
+    /// Adds a changed key to the set.
+    pub fn insert(&mut self, key: Nibbles) {
+        self.keys.insert(key);
+    }
+
+    /// Returns `true` if any of the keys starts with the given prefix.
+    pub fn contains(&self, prefix: &[u8]) -> bool {
+        self.all ||
+            self.keys
+                .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+                .next()
+                .map_or(false, |key| key.starts_with(prefix))
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        !self.all && self.keys.is_empty()
+    }
+}
+
+impl FromIterator<Nibbles> for PrefixSet {
+    fn from_iter<I: IntoIterator<Item = Nibbles>>(iter: I) -> Self {
+        Self { keys: iter.into_iter().collect(), all: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_prefix() {
+        let set: PrefixSet = [vec![0x1, 0x2, 0x3], vec![0x1, 0x2, 0x4], vec![0xa]]
+            .into_iter()
+            .map(Nibbles::from_hex)
+            .collect();
+
+        assert!(set.contains(&[]));
+        assert!(set.contains(&[0x1, 0x2]));
+        assert!(set.contains(&[0x1, 0x2, 0x4]));
+        assert!(set.contains(&[0xa]));
+        assert!(!set.contains(&[0x1, 0x3]));
+        assert!(!set.contains(&[0x1, 0x2, 0x3, 0x0]));
+        assert!(!set.contains(&[0xb]));
+        assert!(!PrefixSet::default().contains(&[]));
+        assert!(PrefixSet::all().contains(&[0xb]));
+    }
+}
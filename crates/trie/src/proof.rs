@@ -0,0 +1,185 @@
+use crate::{state_root::walk_storage, HashBuilder, Nibbles, PrefixSet, StateRoot};
+use reth_db::{cursor::DbDupCursorRO, tables, transaction::DbTx, Error};
+use reth_primitives::{keccak256, Account, Address, Bytes, H256, U256};
+use std::collections::BTreeMap;
+
+/// The Merkle proof of an account and some of its storage slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountProof {
+    /// The address of the account.
+    pub address: Address,
+    /// The account, `None` if it doesn't exist.
+    pub info: Option<Account>,
+    /// The storage root of the account.
+    pub storage_root: H256,
+    /// The RLP encoded nodes of the state trie on the path to the account, starting with the root.
+    pub proof: Vec<Bytes>,
+    /// The proofs of the requested storage slots.
+    pub storage_proofs: Vec<StorageProof>,
+}
+
+/// The Merkle proof of a storage slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    /// The storage slot.
+    pub key: H256,
+    /// The value of the slot, zero if it is not set.
+    pub value: U256,
+    /// The RLP encoded nodes of the storage trie on the path to the slot, starting with the root.
+    pub proof: Vec<Bytes>,
+}
+
+/// Generates Merkle proofs from the hashed state and the stored trie nodes.
+///
+/// Only the paths to the proven keys are walked, the hashes of all other subtrees are taken from
+/// the [AccountsTrie](tables::AccountsTrie) and [StoragesTrie](tables::StoragesTrie) tables, so
+/// the stored nodes have to be in sync with the hashed state.
+///
+/// A proof of a missing key proves its absence: it ends with the node where the path to the key
+/// diverges from the trie.
+#[derive(Debug)]
+pub struct Proof<'a, TX> {
+    tx: &'a TX,
+}
+
+impl<'a, 'tx, TX: DbTx<'tx>> Proof<'a, TX> {
+    /// Create a new proof generator on top of the given transaction.
+    pub fn new(tx: &'a TX) -> Self {
+        Self { tx }
+    }
+
+    /// Generates the proof of the account with the given address and of the given storage slots
+    /// of the account.
+    pub fn account_proof(&self, address: Address, slots: &[H256]) -> Result<AccountProof, Error> {
+        let hashed_address = keccak256(address);
+        let target = Nibbles::unpack(hashed_address);
+        let targets: PrefixSet = std::iter::once(target.clone()).collect();
+
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(targets.clone());
+        StateRoot::with_walked_accounts(self.tx, targets).walk(&mut hash_builder, None)?;
+        hash_builder.root();
+        let proof = proof_of(&hash_builder.take_proofs(), &target);
+
+        let info = self.tx.get::<tables::HashedAccount>(hashed_address)?;
+        let (storage_root, storage_proofs) = self.storage_proofs(hashed_address, slots)?;
+        Ok(AccountProof { address, info, storage_root, proof, storage_proofs })
+    }
+
+    /// Generates the proofs of the given storage slots, and returns them with the storage root.
+    fn storage_proofs(
+        &self,
+        hashed_address: H256,
+        slots: &[H256],
+    ) -> Result<(H256, Vec<StorageProof>), Error> {
+        let hashed_slots: Vec<_> = slots.iter().map(keccak256).collect();
+        let targets: PrefixSet = hashed_slots.iter().map(Nibbles::unpack).collect();
+
+        let mut storage = self.tx.cursor_dup::<tables::HashedStorage>()?;
+        let mut storage_trie = self.tx.cursor::<tables::StoragesTrie>()?;
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(targets.clone());
+        walk_storage(&mut storage, &mut storage_trie, hashed_address, &targets, &mut hash_builder)?;
+        let storage_root = hash_builder.root();
+        let proofs = hash_builder.take_proofs();
+
+        let mut storage_proofs = Vec::with_capacity(slots.len());
+        for (slot, hashed_slot) in slots.iter().zip(hashed_slots) {
+            let value = storage
+                .seek_by_key_subkey(hashed_address, hashed_slot)?
+                .filter(|entry| entry.key == hashed_slot)
+                .map(|entry| entry.value)
+                .unwrap_or_default();
+            let proof = proof_of(&proofs, &Nibbles::unpack(hashed_slot));
+            storage_proofs.push(StorageProof { key: *slot, value, proof });
+        }
+        Ok((storage_root, storage_proofs))
+    }
+}
+
+/// Returns the collected nodes on the path to the key, starting with the root.
+fn proof_of(nodes: &BTreeMap<Nibbles, Vec<u8>>, key: &Nibbles) -> Vec<Bytes> {
+    nodes
+        .iter()
+        .filter(|(path, _)| key.starts_with(path))
+        .map(|(_, node)| Bytes::from(node.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{proofs::TrieAccount, StorageEntry};
+    use reth_rlp::Encodable;
+
+    /// Checks that the proof links the root to a node that contains the value.
+    fn assert_proof(root: H256, proof: &[Bytes], value: &[u8]) {
+        assert_eq!(keccak256(&proof[0]), root);
+        for nodes in proof.windows(2) {
+            let child = if nodes[1].len() < 32 {
+                nodes[1].to_vec()
+            } else {
+                let mut reference = Vec::new();
+                keccak256(&nodes[1]).as_bytes().encode(&mut reference);
+                reference
+            };
+            assert!(nodes[0].windows(child.len()).any(|window| window == child));
+        }
+        let last = proof.last().unwrap();
+        assert!(last.windows(value.len()).any(|window| window == value));
+    }
+
+    #[test]
+    fn account_and_storage_proofs() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut rng = rand::thread_rng();
+        let tx = db.tx_mut().unwrap();
+
+        let mut addresses = Vec::new();
+        for _ in 0..300 {
+            let address = Address::random();
+            let account = Account {
+                nonce: rng.gen_range(0..10),
+                balance: U256::from(rng.gen::<u64>()),
+                bytecode_hash: None,
+            };
+            tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+            addresses.push(address);
+        }
+        let slots: Vec<_> = (0..100).map(|_| H256::random()).collect();
+        let address = addresses[0];
+        for (index, slot) in slots.iter().enumerate() {
+            let entry = StorageEntry { key: keccak256(slot), value: U256::from(index + 1) };
+            tx.put::<tables::HashedStorage>(keccak256(address), entry).unwrap();
+        }
+
+        // the proofs walk the whole tries if there are no stored nodes
+        let proof = Proof::new(&tx).account_proof(address, &slots[..2]).unwrap();
+        let (root, updates) = StateRoot::new(&tx).root_with_updates().unwrap();
+        updates.flush(&tx).unwrap();
+        assert_eq!(Proof::new(&tx).account_proof(address, &slots[..2]).unwrap(), proof);
+
+        let info = proof.info.unwrap();
+        let mut account_rlp = Vec::new();
+        TrieAccount::new(info, proof.storage_root).encode(&mut account_rlp);
+        assert_proof(root, &proof.proof, &account_rlp);
+        for (index, storage_proof) in proof.storage_proofs.iter().enumerate() {
+            assert_eq!(storage_proof.key, slots[index]);
+            assert_eq!(storage_proof.value, U256::from(index + 1));
+            let mut value_rlp = Vec::new();
+            storage_proof.value.encode(&mut value_rlp);
+            assert_proof(proof.storage_root, &storage_proof.proof, &value_rlp);
+        }
+
+        // a missing account and slot are proven by the path to where they would be
+        let proof = Proof::new(&tx).account_proof(Address::random(), &[H256::random()]).unwrap();
+        assert_eq!(proof.info, None);
+        assert_eq!(keccak256(&proof.proof[0]), root);
+        assert_eq!(proof.storage_proofs[0].value, U256::zero());
+        assert!(proof.storage_proofs[0].proof.is_empty());
+    }
+}
@@ -0,0 +1,490 @@
+use crate::{
+    cursor::{AccountTrieCursor, StorageTrieCursor},
+    walker::TrieWalker,
+    HashBuilder, Nibbles, PrefixSet, TrieUpdates,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    models::{AccountBeforeTx, TransitionIdAddress},
+    tables,
+    transaction::DbTx,
+    Error,
+};
+use reth_primitives::{keccak256, proofs::TrieAccount, Address, StorageEntry, TransitionId, H256};
+use reth_rlp::Encodable;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+};
+
+/// The changes of the hashed state since the stored trie nodes were computed.
+#[derive(Debug, Default)]
+struct StateChanges {
+    /// The hashed addresses of the changed accounts, including the accounts with changed storage.
+    accounts: PrefixSet,
+    /// The hashed slots of the changed storage, by hashed address.
+    storage: BTreeMap<H256, PrefixSet>,
+    /// The hashed addresses of the changed accounts that don't exist anymore.
+    destroyed_accounts: BTreeSet<H256>,
+}
+
+/// Computes the state root from the hashed state.
+///
+/// The accounts of the [HashedAccount](tables::HashedAccount) table and the storage slots of
+/// each account in the [HashedStorage](tables::HashedStorage) table are read in order and fed
+/// into [HashBuilder]s, so the trie is never materialized in memory.
+///
+/// The branch nodes of the tries are stored in the [AccountsTrie](tables::AccountsTrie) and
+/// [StoragesTrie](tables::StoragesTrie) tables, see [StateRoot::root_with_updates]. With the
+/// changes since they were stored, an [incremental](StateRoot::incremental) computation only
+/// walks the changed parts of the tries and takes the hashes of the unchanged subtrees from the
+/// stored nodes.
+#[derive(Debug)]
+pub struct StateRoot<'a, TX> {
+    tx: &'a TX,
+    /// The changes since the stored nodes were computed, `None` if the stored nodes are not used.
+    changes: Option<StateChanges>,
+}
+
+impl<'a, 'tx, TX: DbTx<'tx>> StateRoot<'a, TX> {
+    /// Create a new state root calculator on top of the given transaction, that computes the
+    /// state root from the whole hashed state.
+    pub fn new(tx: &'a TX) -> Self {
+        Self { tx, changes: None }
+    }
+
+    /// Create a state root calculator that updates the stored trie nodes with the changes of the
+    /// given transitions.
+    ///
+    /// The changed accounts and storage slots are read from the [AccountChangeSet] and
+    /// [StorageChangeSet] tables. The hashed state has to be updated with the changes already,
+    /// while the stored trie nodes have to reflect the state before them.
+    ///
+    /// [AccountChangeSet]: tables::AccountChangeSet
+    /// [StorageChangeSet]: tables::StorageChangeSet
+    pub fn incremental(
+        tx: &'a TX,
+        transitions: RangeInclusive<TransitionId>,
+    ) -> Result<Self, Error> {
+        let mut changed_accounts = BTreeSet::new();
+        let mut account_changesets = tx.cursor::<tables::AccountChangeSet>()?;
+        for entry in account_changesets.walk_range(transitions.clone())? {
+            let (_, AccountBeforeTx { address, .. }) = entry?;
+            changed_accounts.insert(keccak256(address));
+        }
+
+        let mut changes = StateChanges::default();
+        let mut storage_changesets = tx.cursor::<tables::StorageChangeSet>()?;
+        let start = TransitionIdAddress::from((*transitions.start(), Address::zero()));
+        let end = TransitionIdAddress::from((*transitions.end() + 1, Address::zero()));
+        for entry in storage_changesets.walk_range(start..end)? {
+            let (key, StorageEntry { key: slot, .. }) = entry?;
+            let hashed_address = keccak256(key.address());
+            changed_accounts.insert(hashed_address);
+            changes
+                .storage
+                .entry(hashed_address)
+                .or_default()
+                .insert(Nibbles::unpack(keccak256(slot)));
+        }
+
+        for hashed_address in changed_accounts {
+            changes.accounts.insert(Nibbles::unpack(hashed_address));
+            if tx.get::<tables::HashedAccount>(hashed_address)?.is_none() {
+                changes.destroyed_accounts.insert(hashed_address);
+            }
+        }
+
+        Ok(Self { tx, changes: Some(changes) })
+    }
+
+    /// Create a state root calculator that walks the paths to the given hashed addresses and
+    /// takes the hashes of all other subtrees from the stored nodes.
+    pub(crate) fn with_walked_accounts(tx: &'a TX, accounts: PrefixSet) -> Self {
+        Self { tx, changes: Some(StateChanges { accounts, ..Default::default() }) }
+    }
+
+    /// Walks the hashed state and returns the state root.
+    pub fn root(&self) -> Result<H256, Error> {
+        Ok(self.calculate(false)?.0)
+    }
+
+    /// Walks the hashed state and returns the state root, together with the changes of the stored
+    /// trie nodes that have to be [flushed](TrieUpdates::flush) to keep them in sync with the
+    /// hashed state.
+    pub fn root_with_updates(&self) -> Result<(H256, TrieUpdates), Error> {
+        self.calculate(true)
+    }
+
+    fn calculate(&self, retain_updates: bool) -> Result<(H256, TrieUpdates), Error> {
+        let mut hash_builder = HashBuilder::default();
+        if retain_updates {
+            hash_builder = hash_builder.with_updates();
+        }
+
+        let mut trie_updates = TrieUpdates::default();
+        let removed_nodes =
+            self.walk(&mut hash_builder, retain_updates.then_some(&mut trie_updates))?;
+        let root = hash_builder.root();
+
+        if retain_updates {
+            trie_updates.extend_account_nodes(removed_nodes, hash_builder.take_updates());
+            for hashed_address in
+                self.changes.iter().flat_map(|changes| &changes.destroyed_accounts)
+            {
+                trie_updates.wipe_storage_trie(*hashed_address);
+            }
+        }
+
+        Ok((root, trie_updates))
+    }
+
+    /// Adds the accounts and the unchanged subtrees of the state trie to the hash builder, and
+    /// returns the paths of the walked stored nodes.
+    ///
+    /// The changes of the stored nodes of the storage tries are collected if `trie_updates` are
+    /// given.
+    pub(crate) fn walk(
+        &self,
+        hash_builder: &mut HashBuilder,
+        mut trie_updates: Option<&mut TrieUpdates>,
+    ) -> Result<Vec<Nibbles>, Error> {
+        let all = PrefixSet::all();
+        let unchanged = PrefixSet::default();
+        let changed_accounts = self.changes.as_ref().map_or(&all, |changes| &changes.accounts);
+
+        let mut accounts = self.tx.cursor::<tables::HashedAccount>()?;
+        let mut storage = self.tx.cursor_dup::<tables::HashedStorage>()?;
+        let mut storage_trie = self.tx.cursor::<tables::StoragesTrie>()?;
+
+        let mut account_trie = AccountTrieCursor(self.tx.cursor::<tables::AccountsTrie>()?);
+        let mut walker = TrieWalker::new(&mut account_trie, changed_accounts)?;
+
+        let mut account_rlp = Vec::new();
+        while let Some(key) = walker.key() {
+            if walker.can_skip_current_node() {
+                let hash = walker.hash().expect("skipped node has a hash");
+                hash_builder.add_branch(key, hash, walker.children_are_in_trie());
+            }
+
+            let Some(seek_key) = walker.next_unprocessed_key() else { break };
+            let next_key = walker.advance()?;
+
+            // the accounts up to the next node are not covered by a stored hash
+            for entry in accounts.walk(seek_key)? {
+                let (hashed_address, account) = entry?;
+                let account_nibbles = Nibbles::unpack(hashed_address);
+                if next_key.as_ref().map_or(false, |next_key| *next_key < account_nibbles) {
+                    break
+                }
+
+                let changed_storage = match &self.changes {
+                    Some(changes) => changes.storage.get(&hashed_address).unwrap_or(&unchanged),
+                    None => &all,
+                };
+                let storage_root = storage_root_with_cursors(
+                    &mut storage,
+                    &mut storage_trie,
+                    hashed_address,
+                    changed_storage,
+                    trie_updates.as_deref_mut(),
+                )?;
+
+                account_rlp.clear();
+                TrieAccount::new(account, storage_root).encode(&mut account_rlp);
+                hash_builder.add_leaf(account_nibbles, &account_rlp);
+            }
+        }
+
+        Ok(walker.take_removed_nodes())
+    }
+}
+
+/// Walks the hashed storage of the account with the given hashed address and returns its storage
+/// root.
+pub fn storage_root<'tx, TX: DbTx<'tx>>(tx: &TX, hashed_address: H256) -> Result<H256, Error> {
+    storage_root_with_cursors(
+        &mut tx.cursor_dup::<tables::HashedStorage>()?,
+        &mut tx.cursor::<tables::StoragesTrie>()?,
+        hashed_address,
+        &PrefixSet::all(),
+        None,
+    )
+}
+
+/// Computes the storage root with existing cursors over the hashed storage and the stored storage
+/// trie nodes, and collects the changes of the stored nodes if `updates` are given.
+fn storage_root_with_cursors<'cursor, S, T>(
+    storage: &mut S,
+    storage_trie: &mut T,
+    hashed_address: H256,
+    changes: &PrefixSet,
+    updates: Option<&mut TrieUpdates>,
+) -> Result<H256, Error>
+where
+    S: DbDupCursorRO<'cursor, tables::HashedStorage>,
+    T: DbCursorRO<'cursor, tables::StoragesTrie>,
+{
+    let mut hash_builder = HashBuilder::default();
+    if updates.is_some() {
+        hash_builder = hash_builder.with_updates();
+    }
+
+    let removed_nodes =
+        walk_storage(storage, storage_trie, hashed_address, changes, &mut hash_builder)?;
+    let root = hash_builder.root();
+
+    if let Some(updates) = updates {
+        updates.extend_storage_nodes(hashed_address, removed_nodes, hash_builder.take_updates());
+    }
+    Ok(root)
+}
+
+/// Adds the storage slots and the unchanged subtrees of the storage trie of an account to the hash
+/// builder, and returns the paths of the walked stored nodes.
+pub(crate) fn walk_storage<'cursor, S, T>(
+    storage: &mut S,
+    storage_trie: &mut T,
+    hashed_address: H256,
+    changes: &PrefixSet,
+    hash_builder: &mut HashBuilder,
+) -> Result<Vec<Nibbles>, Error>
+where
+    S: DbDupCursorRO<'cursor, tables::HashedStorage>,
+    T: DbCursorRO<'cursor, tables::StoragesTrie>,
+{
+    let mut trie_cursor = StorageTrieCursor::new(storage_trie, hashed_address);
+    let mut walker = TrieWalker::new(&mut trie_cursor, changes)?;
+
+    let mut value_rlp = Vec::new();
+    while let Some(key) = walker.key() {
+        if walker.can_skip_current_node() {
+            let hash = walker.hash().expect("skipped node has a hash");
+            hash_builder.add_branch(key, hash, walker.children_are_in_trie());
+        }
+
+        let Some(seek_key) = walker.next_unprocessed_key() else { break };
+        let next_key = walker.advance()?;
+
+        // the slots up to the next node are not covered by a stored hash
+        let mut entry = storage.seek_by_key_subkey(hashed_address, seek_key)?;
+        while let Some(StorageEntry { key, value }) = entry {
+            let slot_nibbles = Nibbles::unpack(key);
+            if next_key.as_ref().map_or(false, |next_key| *next_key < slot_nibbles) {
+                break
+            }
+
+            // zero values are not part of the trie
+            if !value.is_zero() {
+                value_rlp.clear();
+                value.encode(&mut value_rlp);
+                hash_builder.add_leaf(slot_nibbles, &value_rlp);
+            }
+            entry = storage.next_dup()?.map(|(_, entry)| entry);
+        }
+    }
+
+    Ok(walker.take_removed_nodes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::StorageTrieKey,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{
+        proofs::{calculate_state_root, calculate_storage_root, EMPTY_ROOT},
+        Account, BranchNodeCompact, U256,
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn empty_state_root() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let tx = db.tx().unwrap();
+        assert_eq!(StateRoot::new(&tx).root().unwrap(), EMPTY_ROOT);
+        assert_eq!(storage_root(&tx, H256::random()).unwrap(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn state_root_matches_in_memory_trie() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut rng = rand::thread_rng();
+
+        let mut state = BTreeMap::new();
+        for index in 0..100u64 {
+            let account = Account {
+                nonce: rng.gen_range(0..10),
+                balance: U256::from(rng.gen::<u64>()),
+                bytecode_hash: (index % 3 == 0).then(H256::random),
+            };
+            // some accounts without storage
+            let storage: BTreeMap<H256, U256> = (0..index % 5 * 10)
+                .map(|_| (H256::random(), U256::from(rng.gen_range(1..u64::MAX))))
+                .collect();
+            state.insert(H256::random(), (account, storage));
+        }
+
+        let tx = db.tx_mut().unwrap();
+        for (hashed_address, (account, storage)) in &state {
+            tx.put::<tables::HashedAccount>(*hashed_address, *account).unwrap();
+            for (key, value) in storage {
+                tx.put::<tables::HashedStorage>(
+                    *hashed_address,
+                    StorageEntry { key: *key, value: *value },
+                )
+                .unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        let expected =
+            calculate_state_root(state.iter().map(|(hashed_address, (account, storage))| {
+                (*hashed_address, *account, calculate_storage_root(storage.clone()))
+            }));
+
+        let tx = db.tx().unwrap();
+        assert_eq!(StateRoot::new(&tx).root().unwrap(), expected);
+        let (hashed_address, (_, storage)) = state.iter().nth(1).unwrap();
+        assert_eq!(
+            storage_root(&tx, *hashed_address).unwrap(),
+            calculate_storage_root(storage.clone())
+        );
+    }
+
+    type PlainState = BTreeMap<Address, (Account, BTreeMap<H256, U256>)>;
+
+    fn random_account<R: Rng>(rng: &mut R) -> Account {
+        Account {
+            nonce: rng.gen_range(0..10),
+            balance: U256::from(rng.gen::<u64>()),
+            bytecode_hash: rng.gen_bool(0.3).then(H256::random),
+        }
+    }
+
+    fn expected_root(state: &PlainState) -> H256 {
+        calculate_state_root(state.iter().map(|(address, (account, storage))| {
+            let storage = storage.iter().map(|(slot, value)| (keccak256(slot), *value));
+            (keccak256(address), *account, calculate_storage_root(storage))
+        }))
+    }
+
+    /// Replaces the hashed account and its hashed storage.
+    fn write_hashed_account<'tx, TX: DbTxMut<'tx>>(
+        tx: &TX,
+        address: Address,
+        account: Option<&(Account, BTreeMap<H256, U256>)>,
+    ) {
+        let hashed_address = keccak256(address);
+        tx.delete::<tables::HashedAccount>(hashed_address, None).unwrap();
+        tx.delete::<tables::HashedStorage>(hashed_address, None).unwrap();
+        if let Some((account, storage)) = account {
+            tx.put::<tables::HashedAccount>(hashed_address, *account).unwrap();
+            for (slot, value) in storage {
+                let entry = StorageEntry { key: keccak256(slot), value: *value };
+                tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+            }
+        }
+    }
+
+    fn stored_nodes<'tx, TX: DbTx<'tx>>(
+        tx: &TX,
+    ) -> (Vec<(Vec<u8>, BranchNodeCompact)>, Vec<(StorageTrieKey, BranchNodeCompact)>) {
+        let mut accounts_trie = tx.cursor::<tables::AccountsTrie>().unwrap();
+        let mut storages_trie = tx.cursor::<tables::StoragesTrie>().unwrap();
+        (
+            accounts_trie.walk(Vec::new()).unwrap().collect::<Result<_, _>>().unwrap(),
+            storages_trie
+                .walk(StorageTrieKey::default())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn incremental_root_matches_full_root() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut rng = rand::thread_rng();
+
+        let mut state = PlainState::new();
+        for index in 0..500u64 {
+            let storage = (0..index % 4 * 20)
+                .map(|_| (H256::random(), U256::from(rng.gen_range(1..u64::MAX))))
+                .collect();
+            state.insert(Address::random(), (random_account(&mut rng), storage));
+        }
+
+        let tx = db.tx_mut().unwrap();
+        for (address, account) in &state {
+            write_hashed_account(&tx, *address, Some(account));
+        }
+        let (root, updates) = StateRoot::new(&tx).root_with_updates().unwrap();
+        assert_eq!(root, expected_root(&state));
+        assert!(!updates.is_empty());
+        updates.flush(&tx).unwrap();
+
+        // change some accounts and storage slots, destroy some accounts and create a new one
+        let addresses = state.keys().copied().collect::<Vec<_>>();
+        for (index, address) in addresses.into_iter().enumerate().filter(|(i, _)| i % 7 == 0) {
+            let (account, storage) = state.get_mut(&address).unwrap();
+            let info = Some(*account);
+            tx.put::<tables::AccountChangeSet>(1, AccountBeforeTx { address, info }).unwrap();
+
+            if index % 28 == 0 {
+                for (slot, value) in std::mem::take(storage) {
+                    let key = TransitionIdAddress((1, address));
+                    tx.put::<tables::StorageChangeSet>(key, StorageEntry { key: slot, value })
+                        .unwrap();
+                }
+                state.remove(&address);
+                write_hashed_account(&tx, address, None);
+                continue
+            }
+
+            account.nonce += 1;
+            let changed_slots = storage
+                .iter_mut()
+                .take(2)
+                .map(|(slot, value)| {
+                    let entry = StorageEntry { key: *slot, value: *value };
+                    *value += U256::from(1);
+                    entry
+                })
+                .collect::<Vec<_>>();
+            for entry in changed_slots {
+                tx.put::<tables::StorageChangeSet>(TransitionIdAddress((1, address)), entry)
+                    .unwrap();
+            }
+            write_hashed_account(&tx, address, state.get(&address));
+        }
+
+        let address = Address::random();
+        let storage = BTreeMap::from([(H256::random(), U256::from(1))]);
+        state.insert(address, (random_account(&mut rng), storage));
+        tx.put::<tables::AccountChangeSet>(1, AccountBeforeTx { address, info: None }).unwrap();
+        for slot in state[&address].1.keys() {
+            let entry = StorageEntry { key: *slot, value: U256::zero() };
+            tx.put::<tables::StorageChangeSet>(TransitionIdAddress((1, address)), entry).unwrap();
+        }
+        write_hashed_account(&tx, address, state.get(&address));
+
+        let (root, updates) =
+            StateRoot::incremental(&tx, 1..=1).unwrap().root_with_updates().unwrap();
+        assert_eq!(root, expected_root(&state));
+        updates.flush(&tx).unwrap();
+        let nodes = stored_nodes(&tx);
+
+        // the updated nodes are the nodes of the new state
+        tx.clear::<tables::AccountsTrie>().unwrap();
+        tx.clear::<tables::StoragesTrie>().unwrap();
+        let (_, updates) = StateRoot::new(&tx).root_with_updates().unwrap();
+        updates.flush(&tx).unwrap();
+        assert_eq!(stored_nodes(&tx), nodes);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::Nibbles;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    models::StorageTrieKey,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error,
+};
+use reth_primitives::{BranchNodeCompact, H256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The changes of the stored branch nodes of the tries, collected while the state root is
+/// computed. `None` removes the node from the database.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TrieUpdates {
+    /// The changed nodes of the state trie.
+    account_nodes: BTreeMap<Nibbles, Option<BranchNodeCompact>>,
+    /// The changed nodes of the storage tries, by hashed address.
+    storage_nodes: BTreeMap<H256, BTreeMap<Nibbles, Option<BranchNodeCompact>>>,
+    /// The hashed addresses of the removed accounts, whose storage tries are removed entirely.
+    wiped_storage_tries: BTreeSet<H256>,
+}
+
+impl TrieUpdates {
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.account_nodes.is_empty() &&
+            self.storage_nodes.is_empty() &&
+            self.wiped_storage_tries.is_empty()
+    }
+
+    /// Adds the nodes of a walk of the state trie.
+    pub(crate) fn extend_account_nodes(
+        &mut self,
+        removed: Vec<Nibbles>,
+        updated: BTreeMap<Nibbles, BranchNodeCompact>,
+    ) {
+        extend_nodes(&mut self.account_nodes, removed, updated);
+    }
+
+    /// Adds the nodes of a walk of the storage trie of an account.
+    pub(crate) fn extend_storage_nodes(
+        &mut self,
+        hashed_address: H256,
+        removed: Vec<Nibbles>,
+        updated: BTreeMap<Nibbles, BranchNodeCompact>,
+    ) {
+        if !removed.is_empty() || !updated.is_empty() {
+            let nodes = self.storage_nodes.entry(hashed_address).or_default();
+            extend_nodes(nodes, removed, updated);
+        }
+    }
+
+    /// Removes the whole storage trie of an account.
+    pub(crate) fn wipe_storage_trie(&mut self, hashed_address: H256) {
+        self.wiped_storage_tries.insert(hashed_address);
+    }
+
+    /// Writes the changes to the [AccountsTrie](tables::AccountsTrie) and
+    /// [StoragesTrie](tables::StoragesTrie) tables.
+    pub fn flush<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(self, tx: &TX) -> Result<(), Error> {
+        for (path, node) in self.account_nodes {
+            match node {
+                Some(node) => tx.put::<tables::AccountsTrie>(path.into_inner(), node)?,
+                None => {
+                    tx.delete::<tables::AccountsTrie>(path.into_inner(), None)?;
+                }
+            }
+        }
+
+        let mut storage_trie = tx.cursor_mut::<tables::StoragesTrie>()?;
+        for hashed_address in self.wiped_storage_tries {
+            // the nodes of the trie are adjacent, starting with the root node
+            let start = StorageTrieKey((hashed_address, Vec::new()));
+            loop {
+                let entry = storage_trie.walk(start.clone())?.start.transpose()?;
+                match entry {
+                    Some((key, _)) if key.hashed_address() == hashed_address => {
+                        storage_trie.delete_current()?
+                    }
+                    _ => break,
+                }
+            }
+        }
+
+        for (hashed_address, nodes) in self.storage_nodes {
+            for (path, node) in nodes {
+                let key = StorageTrieKey((hashed_address, path.into_inner()));
+                match node {
+                    Some(node) => tx.put::<tables::StoragesTrie>(key, node)?,
+                    None => {
+                        tx.delete::<tables::StoragesTrie>(key, None)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes the walked nodes, unless they are recomputed.
+fn extend_nodes(
+    nodes: &mut BTreeMap<Nibbles, Option<BranchNodeCompact>>,
+    removed: Vec<Nibbles>,
+    updated: BTreeMap<Nibbles, BranchNodeCompact>,
+) {
+    nodes.extend(removed.into_iter().map(|path| (path, None)));
+    nodes.extend(updated.into_iter().map(|(path, node)| (path, Some(node))));
+}
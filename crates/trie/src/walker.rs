@@ -0,0 +1,217 @@
+use crate::{cursor::TrieCursor, Nibbles, PrefixSet};
+use reth_db::Error;
+use reth_primitives::{BranchNodeCompact, H256};
+
+/// A position in the stored trie: a branch node and one of its children.
+#[derive(Debug)]
+struct CursorSubNode {
+    /// The path of the node.
+    key: Nibbles,
+    /// The stored node, `None` for the root of a trie without stored nodes.
+    node: Option<BranchNodeCompact>,
+    /// The current child of the node, `-1` for the node itself.
+    nibble: i8,
+}
+
+impl CursorSubNode {
+    fn new(key: Nibbles, node: Option<BranchNodeCompact>) -> Self {
+        // only the root node has its own hash, the other nodes start at their first child
+        let nibble = match &node {
+            Some(node) if node.root_hash.is_none() => node.state_mask.trailing_zeros() as i8,
+            _ => -1,
+        };
+        Self { key, node, nibble }
+    }
+
+    /// Returns the path of the current child.
+    fn full_key(&self) -> Nibbles {
+        let mut key = self.key.clone();
+        if self.nibble >= 0 {
+            key.push(self.nibble as u8);
+        }
+        key
+    }
+
+    /// Returns `true` if the current child exists.
+    fn state_flag(&self) -> bool {
+        match &self.node {
+            Some(node) if self.nibble >= 0 => node.state_mask & (1 << self.nibble) != 0,
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if the current child is a stored branch node.
+    fn tree_flag(&self) -> bool {
+        match &self.node {
+            Some(node) if self.nibble >= 0 => node.tree_mask & (1 << self.nibble) != 0,
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if the hash of the current child is stored.
+    fn hash_flag(&self) -> bool {
+        match &self.node {
+            Some(node) if self.nibble >= 0 => node.hash_mask & (1 << self.nibble) != 0,
+            Some(node) => node.root_hash.is_some(),
+            None => false,
+        }
+    }
+
+    /// Returns the stored hash of the current child.
+    fn hash(&self) -> Option<H256> {
+        let node = self.node.as_ref().filter(|_| self.hash_flag())?;
+        if self.nibble >= 0 {
+            Some(node.hash_for_nibble(self.nibble as u8))
+        } else {
+            node.root_hash
+        }
+    }
+}
+
+/// Walks the stored branch nodes of a trie in the order of their paths.
+///
+/// The subtrees that don't contain any changed key are skipped, so their stored hashes can be used
+/// instead of their leaves. The keys between the nodes of the walk are not covered by any stored
+/// hash and have to be read from the hashed state.
+#[derive(Debug)]
+pub(crate) struct TrieWalker<'a, C> {
+    cursor: &'a mut C,
+    changes: &'a PrefixSet,
+    stack: Vec<CursorSubNode>,
+    can_skip_current_node: bool,
+    /// The stored nodes that have been walked into, they are replaced by the recomputed nodes.
+    removed_nodes: Vec<Nibbles>,
+}
+
+impl<'a, C: TrieCursor> TrieWalker<'a, C> {
+    /// Creates a walker that starts at the root node of the trie.
+    pub(crate) fn new(cursor: &'a mut C, changes: &'a PrefixSet) -> Result<Self, Error> {
+        let mut walker = Self {
+            cursor,
+            changes,
+            stack: vec![CursorSubNode::new(Nibbles::default(), None)],
+            can_skip_current_node: false,
+            removed_nodes: Vec::new(),
+        };
+
+        if let Some((key, node)) = walker.cursor.seek_exact(Nibbles::default())? {
+            walker.stack[0] = CursorSubNode::new(key, Some(node));
+            walker.update_skip_node();
+            if !walker.can_skip_current_node {
+                walker.removed_nodes.push(Nibbles::default());
+            }
+        }
+        Ok(walker)
+    }
+
+    /// Returns the path of the current node, or `None` if the walk is finished.
+    pub(crate) fn key(&self) -> Option<Nibbles> {
+        self.stack.last().map(CursorSubNode::full_key)
+    }
+
+    /// Returns the stored hash of the current node.
+    pub(crate) fn hash(&self) -> Option<H256> {
+        self.stack.last().and_then(CursorSubNode::hash)
+    }
+
+    /// Returns `true` if the current node is unchanged and has a stored hash.
+    pub(crate) fn can_skip_current_node(&self) -> bool {
+        self.can_skip_current_node
+    }
+
+    /// Returns `true` if the current node is a stored branch node.
+    pub(crate) fn children_are_in_trie(&self) -> bool {
+        self.stack.last().map_or(false, CursorSubNode::tree_flag)
+    }
+
+    /// Returns the first hashed key that is not covered by the walk so far.
+    pub(crate) fn next_unprocessed_key(&self) -> Option<H256> {
+        let key = self.key()?;
+        let key = if self.can_skip_current_node { key.increment()? } else { key };
+        let mut packed = key.pack();
+        packed.resize(32, 0);
+        Some(H256::from_slice(&packed))
+    }
+
+    /// Moves to the next node of the walk and returns its path.
+    pub(crate) fn advance(&mut self) -> Result<Option<Nibbles>, Error> {
+        if let Some(last) = self.stack.last() {
+            let nibble = last.nibble;
+            if !self.can_skip_current_node && last.tree_flag() {
+                if nibble < 0 {
+                    self.move_to_next_sibling(true)?;
+                } else {
+                    self.consume_node()?;
+                }
+            } else {
+                self.move_to_next_sibling(false)?;
+            }
+            self.update_skip_node();
+        }
+        Ok(self.key())
+    }
+
+    /// Returns the paths of the stored nodes that have to be removed or replaced.
+    pub(crate) fn take_removed_nodes(&mut self) -> Vec<Nibbles> {
+        std::mem::take(&mut self.removed_nodes)
+    }
+
+    /// Descends to the next stored node at or after the current path.
+    fn consume_node(&mut self) -> Result<(), Error> {
+        let key = self.key().expect("walk is not finished");
+        let Some((key, node)) = self.cursor.seek(key)? else {
+            self.stack.clear();
+            return Ok(())
+        };
+
+        // the found node may be below the current child of the root
+        if !key.is_empty() && !self.stack.is_empty() {
+            self.stack[0].nibble = key[0] as i8;
+        }
+
+        let subnode = CursorSubNode::new(key.clone(), Some(node));
+        let nibble = subnode.nibble;
+        self.stack.push(subnode);
+        self.update_skip_node();
+
+        if !self.can_skip_current_node || nibble != -1 {
+            self.removed_nodes.push(key);
+        }
+        Ok(())
+    }
+
+    /// Moves to the next existing child, going up the stack when a node has no more children.
+    fn move_to_next_sibling(&mut self, allow_root_to_child_nibble: bool) -> Result<(), Error> {
+        let mut allow_child_nibble = allow_root_to_child_nibble;
+        while let Some(subnode) = self.stack.last_mut() {
+            if subnode.nibble >= 15 || (subnode.nibble < 0 && !allow_child_nibble) {
+                self.stack.pop();
+                allow_child_nibble = false;
+                continue
+            }
+
+            subnode.nibble += 1;
+            if subnode.node.is_none() {
+                return self.consume_node()
+            }
+
+            while subnode.nibble < 16 {
+                if subnode.state_flag() {
+                    return Ok(())
+                }
+                subnode.nibble += 1;
+            }
+
+            self.stack.pop();
+            allow_child_nibble = false;
+        }
+        Ok(())
+    }
+
+    fn update_skip_node(&mut self) {
+        self.can_skip_current_node = self
+            .stack
+            .last()
+            .map_or(false, |node| node.hash_flag() && !self.changes.contains(&node.full_key()));
+    }
+}